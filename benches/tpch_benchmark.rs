@@ -2,15 +2,23 @@
 // Paper: TPCH benchmark queries for performance evaluation
 // Small, medium, large scale tests
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::collections::HashMap;
+use std::hint::black_box;
 
-use halo2_proofs::{circuit::Value, pasta::EqAffine, poly::commitment::Params};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
 use pasta_curves::pallas::Base as Fr;
 use poneglyphdb::{
-    circuit::PoneglyphCircuit,
-    database::DatabaseCommitment,
-    prover::{MockProverHelper, Prover, Verifier},
+    circuit::{
+        GateConfigs, PoneglyphCircuit, PoneglyphConfig, PublicInputs, RangeCheckChip,
+        SortAlgorithm, SortChip, V1Circuit,
+    },
+    database::{DatabaseCommitment, HashScheme},
+    prover::{MockProverHelper, ProvingContext},
     sql::{SQLCompiler, SQLParser},
 };
 
@@ -25,6 +33,12 @@ pub struct TPCHBenchmark {
     pub large_scale: HashMap<String, HashMap<String, Vec<u64>>>,
 }
 
+impl Default for TPCHBenchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TPCHBenchmark {
     /// Create new TPCH benchmark suite
     pub fn new() -> Self {
@@ -189,7 +203,10 @@ fn benchmark_sql_compilation(c: &mut Criterion) {
                 &query,
                 |b, q| {
                     b.iter(|| {
-                        black_box(SQLCompiler::compile(q, table_data).unwrap());
+                        // Compile only: the large scale is over the range-check budget
+                        black_box(
+                            SQLCompiler::compile_with_budget(q, table_data, usize::MAX).unwrap(),
+                        );
                     });
                 },
             );
@@ -225,48 +242,27 @@ fn benchmark_circuit_synthesis(c: &mut Criterion) {
             let query = SQLParser::parse(&query_str).unwrap();
             let compiled = SQLCompiler::compile(&query, table_data).unwrap();
 
-            // Create database commitment
-            let db_data: Vec<(u64, u64)> = table_data
-                .values()
-                .flat_map(|t| {
-                    t.values()
-                        .flatten()
-                        .enumerate()
-                        .map(|(i, &v)| (i as u64, v))
-                        .collect::<Vec<_>>()
-                })
-                .collect();
-            let db_commitment = DatabaseCommitment::new(&db_data);
+            // Create database commitment (tables and columns in name order)
+            let db_commitment = DatabaseCommitment::from_tables(table_data, HashScheme::Insecure);
 
             let circuit = PoneglyphCircuit {
-                db_commitment: Value::known(db_commitment.commitment),
-                query_result: Value::unknown(),
-                range_checks: compiled.range_checks,
-                sorts: compiled.sorts,
-                group_bys: compiled.group_bys,
-                joins: compiled.joins,
-                aggregations: compiled.aggregations,
+                db_commitments: vec![db_commitment.commitment],
+                ..PoneglyphCircuit::from_compiled(compiled, None, None)
             };
 
             // Circuit size (k): 2^k rows available
-            // Sort operations use many rows, so we calculate k dynamically
-            // For each sort operation: approximately 12n - 9 rows (n = sorted_values.len())
-            // For range checks: 2 rows per range check (check_less_than)
-            //
-            // Simple solution: choose k large enough (k=12 = 4096 rows should be sufficient)
-            let k = 12; // Circuit size (2^12 = 4096 rows)
+            // Smallest k fitting the laid-out rows plus the rows halo2
+            // reserves for blinding (no extra margin)
+            let k = circuit.stats().unwrap().estimate_k(0);
 
             group.bench_with_input(
                 BenchmarkId::new(format!("query{}", query_num), scale),
                 &circuit,
                 |b, circ| {
                     b.iter(|| {
-                        // Circuit has only 1 instance column
-                        // Row 0: db_commitment, Row 1: query_result
-                        let public_inputs = vec![vec![
-                            db_commitment.commitment, // Row 0
-                            Fr::zero(),               // Row 1: Placeholder query result
-                        ]];
+                        // Placeholder query result
+                        let public_inputs =
+                            PublicInputs::new(db_commitment.commitment, Fr::zero()).to_instances();
                         black_box(
                             MockProverHelper::mock_prove_and_verify(circ, &public_inputs, k)
                                 .unwrap(),
@@ -280,6 +276,333 @@ fn benchmark_circuit_synthesis(c: &mut Criterion) {
     group.finish();
 }
 
+/// Sort diff decomposition circuit
+/// Decomposes sort diffs one region per value (previous `sort_and_verify`)
+/// or all in one region (`decompose_64bit_batch`), with `BITS`-wide chunks
+#[derive(Clone)]
+struct DiffDecompositionCircuit<const BITS: u8 = 8> {
+    diffs: Vec<u64>,
+    batched: bool,
+}
+
+impl<const BITS: u8> Circuit<Fr> for DiffDecompositionCircuit<BITS> {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            diffs: Vec::new(),
+            batched: self.batched,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, BITS)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let chip = RangeCheckChip::new(gates.range_check);
+
+        if self.batched {
+            chip.decompose_64bit_batch(layouter.namespace(|| "decompose diffs"), &self.diffs)?;
+        } else {
+            for (i, &diff) in self.diffs.iter().enumerate() {
+                chip.decompose_64bit(
+                    layouter.namespace(|| format!("decompose diff_{}", i)),
+                    Value::known(diff),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark: Sort diff decomposition (per-value regions vs single batched region)
+fn benchmark_sort_diff_decomposition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort_diff_decomposition");
+    group.sample_size(10);
+
+    // Small / medium scale row counts (per-value regions use 2 rows per diff)
+    for (rows, k) in [(100usize, 10u32), (10_000, 15)] {
+        let diffs: Vec<u64> = (0..rows as u64).map(|i| i * 1_000_003).collect();
+
+        for batched in [false, true] {
+            let circuit = DiffDecompositionCircuit::<8> {
+                diffs: diffs.clone(),
+                batched,
+            };
+            let name = if batched { "batched" } else { "per_value" };
+
+            group.bench_with_input(BenchmarkId::new(name, rows), &circuit, |b, circ| {
+                b.iter(|| {
+                    black_box(MockProver::run(k, circ, vec![vec![], vec![]]).unwrap());
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmark: Range check chunk width (8-bit vs 16-bit lookup table)
+///
+/// Both widths take one row per decomposition, so the row count is the same;
+/// 16-bit chunks halve the chunk lookups (8 → 4) but need a 65536-row table,
+/// so both run at k = 17.
+fn benchmark_lookup_bits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup_bits");
+    group.sample_size(10);
+
+    let k = 17;
+    for rows in [100usize, 10_000] {
+        let diffs: Vec<u64> = (0..rows as u64).map(|i| i * 1_000_003).collect();
+
+        let circuit_8 = DiffDecompositionCircuit::<8> {
+            diffs: diffs.clone(),
+            batched: true,
+        };
+        group.bench_with_input(BenchmarkId::new("8_bit", rows), &circuit_8, |b, circ| {
+            b.iter(|| {
+                black_box(MockProver::run(k, circ, vec![vec![], vec![]]).unwrap());
+            });
+        });
+
+        let circuit_16 = DiffDecompositionCircuit::<16> {
+            diffs,
+            batched: true,
+        };
+        group.bench_with_input(BenchmarkId::new("16_bit", rows), &circuit_16, |b, circ| {
+            b.iter(|| {
+                black_box(MockProver::run(k, circ, vec![vec![], vec![]]).unwrap());
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Bounded predicate circuit: `lower <= x < upper` per value, as one shared
+/// check (`check_between`) or as two x < t checks
+#[derive(Clone)]
+struct BoundedRangeCircuit {
+    values: Vec<u64>,
+    lower: u64,
+    upper: u64,
+    shared: bool,
+}
+
+impl Circuit<Fr> for BoundedRangeCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let chip = RangeCheckChip::new(gates.range_check);
+
+        for (i, &x) in self.values.iter().enumerate() {
+            let value = Value::known(x);
+            if self.shared {
+                chip.check_between(
+                    layouter.namespace(|| format!("between_{}", i)),
+                    None,
+                    value,
+                    self.lower,
+                    self.upper,
+                )?;
+            } else {
+                for (bound, threshold) in [("lower", self.lower), ("upper", self.upper)] {
+                    chip.check_less_than(
+                        layouter.namespace(|| format!("{}_{}", bound, i)),
+                        value,
+                        threshold,
+                        threshold.saturating_sub(x),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Benchmark: Bounded predicate (one shared check vs two x < t checks)
+///
+/// The shared check decomposes two differences per value instead of four
+/// (3 rows instead of 8), so it fits a smaller k at each size.
+fn benchmark_bounded_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounded_range");
+    group.sample_size(10);
+
+    // (values, k for two checks, k for the shared check)
+    for (rows, k_two, k_shared) in [(100usize, 10u32, 10u32), (2_000, 14, 13)] {
+        let values: Vec<u64> = (0..rows as u64).map(|i| i * 1_000_003 % 10_000).collect();
+
+        for (name, shared, k) in [("two_checks", false, k_two), ("shared", true, k_shared)] {
+            let circuit = BoundedRangeCircuit {
+                values: values.clone(),
+                lower: 2_500,
+                upper: 7_500,
+                shared,
+            };
+            group.bench_with_input(BenchmarkId::new(name, rows), &circuit, |b, circ| {
+                b.iter(|| {
+                    black_box(MockProver::run(k, circ, vec![vec![], vec![]]).unwrap());
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Sort circuit proving one sort with the given algorithm
+#[derive(Clone)]
+struct SortAlgorithmCircuit {
+    input: Vec<u64>,
+    algorithm: SortAlgorithm,
+}
+
+impl Circuit<Fr> for SortAlgorithmCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            input: Vec::new(),
+            algorithm: self.algorithm,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let chip = SortChip::new(gates.sort.with_algorithm(self.algorithm));
+
+        let mut sorted = self.input.clone();
+        sorted.sort_unstable();
+        chip.sort_and_verify(
+            layouter.namespace(|| "sort"),
+            self.input.iter().map(|&v| Value::known(v)).collect(),
+            sorted,
+        )?;
+        Ok(())
+    }
+}
+
+/// Benchmark: Sort verification (witnessed permutation vs bitonic network vs
+/// copy-constrained output vs multiset hash)
+///
+/// The first three run at the same k per size, the multiset hash (one
+/// Poseidon hash per input and output value for its challenge) at a larger k
+/// of its own. Measured rows (lookup table included) for 64 / 1000 values:
+/// permutation 479 / 11751 (the switch network routing the input is
+/// `p/2 · (2 log p - 1)` rows), bitonic 1408 / 57344 (one range-checked diff
+/// per comparison, 28160 for 1000 values), copy-constrained 256 / 1999,
+/// multiset hash 8770 / 136066. MockProver times for 1000 values: 151 ms,
+/// 217 ms, 131 ms and 638 ms. Only the copy-constrained sort saves rows over
+/// the permutation check, and its layout depends on the sorting permutation.
+fn benchmark_sort_algorithm(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort_algorithm");
+    group.sample_size(10);
+
+    // The multiset hash commits to input and output (one Poseidon hash per value)
+    for (rows, k, multiset_k) in [(64usize, 12u32, 14u32), (1000, 16, 18)] {
+        let input: Vec<u64> = (0..rows as u64).map(|i| (i * 1_000_003) % 7919).collect();
+
+        for (name, algorithm, k) in [
+            ("permutation", SortAlgorithm::Permutation, k),
+            ("bitonic", SortAlgorithm::Bitonic, k),
+            ("copy_constraint", SortAlgorithm::CopyConstraint, k),
+            ("multiset_hash", SortAlgorithm::MultisetHash, multiset_k),
+        ] {
+            let circuit = SortAlgorithmCircuit {
+                input: input.clone(),
+                algorithm,
+            };
+            group.bench_with_input(BenchmarkId::new(name, rows), &circuit, |b, circ| {
+                b.iter(|| {
+                    black_box(MockProver::run(k, circ, vec![vec![], vec![]]).unwrap());
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Benchmark: Layout pass with SimpleFloorPlanner vs V1 (medium scale)
+///
+/// Prints each layout's rows and min k. Measured: the GROUP BY query
+/// (query 3) takes 59999 rows with SimpleFloorPlanner and 39999 with V1,
+/// both k = 16; the ORDER BY query (query 2) is one region per chip and lays
+/// out the same. Query 1's per-row range check regions make V1's layout
+/// pass far slower (seconds), without saving rows, so it is left out.
+fn benchmark_floor_planner(c: &mut Criterion) {
+    let benchmark = TPCHBenchmark::new();
+    let table_data = &benchmark.medium_scale;
+
+    let mut group = c.benchmark_group("floor_planner");
+    group.sample_size(10);
+
+    for query_num in [2, 3] {
+        let query_str = match query_num {
+            2 => benchmark.query2("medium"),
+            _ => benchmark.query3("medium"),
+        };
+        let query = SQLParser::parse(&query_str).unwrap();
+        let compiled = SQLCompiler::compile(&query, table_data).unwrap();
+        let circuit = PoneglyphCircuit::from_compiled(compiled, None, None);
+        let v1 = V1Circuit(circuit.clone());
+
+        let simple_stats = circuit.stats().unwrap();
+        let v1_stats = v1.stats().unwrap();
+        println!(
+            "query{} medium: simple {} rows (k = {}), v1 {} rows (k = {})",
+            query_num,
+            simple_stats.used_rows,
+            simple_stats.min_k,
+            v1_stats.used_rows,
+            v1_stats.min_k
+        );
+
+        let id = format!("query{}", query_num);
+        group.bench_with_input(BenchmarkId::new("simple", &id), &circuit, |b, circ| {
+            b.iter(|| black_box(circ.stats().unwrap()));
+        });
+        group.bench_with_input(BenchmarkId::new("v1", &id), &v1, |b, circ| {
+            b.iter(|| black_box(circ.stats().unwrap()));
+        });
+    }
+
+    group.finish();
+}
+
 /// Benchmark: Proof Generation (Real Prover)
 fn benchmark_proof_generation(c: &mut Criterion) {
     let benchmark = TPCHBenchmark::new();
@@ -290,44 +613,23 @@ fn benchmark_proof_generation(c: &mut Criterion) {
     let query = SQLParser::parse(&query_str).unwrap();
     let compiled = SQLCompiler::compile(&query, table_data).unwrap();
 
-    // Create database commitment
-    let db_data: Vec<(u64, u64)> = table_data
-        .values()
-        .flat_map(|t| {
-            t.values()
-                .flatten()
-                .enumerate()
-                .map(|(i, &v)| (i as u64, v))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let db_commitment = DatabaseCommitment::new(&db_data);
+    // Create database commitment (tables and columns in name order)
+    let db_commitment = DatabaseCommitment::from_tables(table_data, HashScheme::Insecure);
 
     let circuit = PoneglyphCircuit {
-        db_commitment: Value::known(db_commitment.commitment),
-        query_result: Value::unknown(),
-        range_checks: compiled.range_checks,
-        sorts: compiled.sorts,
-        group_bys: compiled.group_bys,
-        joins: compiled.joins,
-        aggregations: compiled.aggregations,
+        db_commitments: vec![db_commitment.commitment],
+        ..PoneglyphCircuit::from_compiled(compiled, None, None)
     };
 
-    let k = 10;
-    let params = Params::<EqAffine>::new(k);
-
-    let prover = Prover::new(&params, &circuit).unwrap();
-    let verifier = Verifier::new(&params, &circuit).unwrap();
+    let context = ProvingContext::new(10, &circuit).unwrap();
 
-    let public_inputs = vec![
-        vec![db_commitment.commitment],
-        vec![Fr::zero()], // Placeholder query result
-    ];
+    // Placeholder query result
+    let public_inputs = PublicInputs::new(db_commitment.commitment, Fr::zero()).to_instances();
 
     c.bench_function("proof_generation", |b| {
         b.iter(|| {
-            let proof = black_box(prover.prove(&params, &circuit, &public_inputs).unwrap());
-            black_box(verifier.verify(&params, &proof, &public_inputs).unwrap());
+            let proof = black_box(context.prove(&circuit, &public_inputs).unwrap());
+            black_box(context.verify(&proof, &public_inputs).unwrap());
         });
     });
 }
@@ -352,6 +654,11 @@ criterion_group!(
     benchmark_sql_parsing,
     benchmark_sql_compilation,
     benchmark_circuit_synthesis,
+    benchmark_sort_diff_decomposition,
+    benchmark_lookup_bits,
+    benchmark_sort_algorithm,
+    benchmark_bounded_range,
+    benchmark_floor_planner,
     benchmark_proof_generation
 );
 criterion_main!(benches);
@@ -0,0 +1,184 @@
+// Prove a SQL query over a CSV file
+//
+// Usage:
+//
+//     cargo run --release --example prove_csv -- <table.csv> "<sql>" <proof.json>
+//
+// The CSV file is loaded with `DatabaseTable::from_csv` (its file stem is the
+// table name), the query is parsed and compiled, the table is committed in the
+// circuit (`CommittedDatabase`) and the circuit is proven at the k estimated from its layout
+// (`CircuitStats::estimate_k`). The proof, its public inputs (instance
+// columns) and the table commitment are written to the output file as JSON,
+// with field elements as hex of their little-endian representation, and the
+// written file is read back and verified.
+//
+// Proving is compiled out with the `verifier-only` feature; the example then
+// only reports that it needs the prover.
+#![cfg_attr(feature = "verifier-only", allow(dead_code))]
+
+use std::fs;
+use std::path::Path;
+
+use ff::PrimeField;
+use pasta_curves::pallas::Base as Fr;
+#[cfg(not(feature = "verifier-only"))]
+use poneglyphdb::circuit::{CommittedDatabase, PoneglyphCircuit, PublicInputs};
+#[cfg(not(feature = "verifier-only"))]
+use poneglyphdb::database::{DatabaseTable, TableSource};
+#[cfg(not(feature = "verifier-only"))]
+use poneglyphdb::prover::ProvingContext;
+#[cfg(not(feature = "verifier-only"))]
+use poneglyphdb::sql::{SQLCompiler, SQLParser};
+use serde::{Deserialize, Serialize};
+
+/// Proof file written by `prove_csv`
+#[derive(Serialize, Deserialize)]
+pub struct ProofFile {
+    /// log2 of the number of rows the proof was created at
+    pub k: u32,
+    /// Commitment of the table (`CommittedDatabase::values`), bound by the
+    /// first instance column
+    pub db_commitments: Vec<String>,
+    /// Instance columns (`PublicInputs::to_instances`), column then row
+    pub public_inputs: Vec<Vec<String>>,
+    /// Serialized proof
+    pub proof: String,
+}
+
+impl ProofFile {
+    /// Instance columns decoded back into field elements
+    pub fn instances(&self) -> Result<Vec<Vec<Fr>>, String> {
+        self.public_inputs
+            .iter()
+            .map(|column| column.iter().map(|value| fr_from_hex(value)).collect())
+            .collect()
+    }
+
+    /// Proof bytes
+    pub fn proof_bytes(&self) -> Result<Vec<u8>, String> {
+        from_hex(&self.proof)
+    }
+}
+
+/// Circuit over the committed CSV table and its public inputs
+/// (as `prove_query`, without proving)
+#[cfg(not(feature = "verifier-only"))]
+pub fn compile_csv(csv: &Path, sql: &str) -> Result<(PoneglyphCircuit, PublicInputs), String> {
+    let table = DatabaseTable::from_csv(csv).map_err(|e| e.to_string())?;
+    let query = SQLParser::parse(sql)?;
+    let compiled = SQLCompiler::compile(&query, &table)?;
+
+    let database = CommittedDatabase::new(&table.column_map());
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    let public_inputs = PublicInputs {
+        db_commitments: circuit.db_commitments.clone(),
+        query_result: circuit.bound_result(),
+    };
+    Ok((circuit, public_inputs))
+}
+
+/// Prove `sql` over the CSV table and write the proof file to `out`
+#[cfg(not(feature = "verifier-only"))]
+pub fn prove_csv(csv: &Path, sql: &str, out: &Path) -> Result<ProofFile, String> {
+    let (circuit, public_inputs) = compile_csv(csv, sql)?;
+
+    let stats = circuit
+        .stats()
+        .map_err(|e| format!("Failed to lay out circuit: {:?}", e))?;
+    let k = stats.estimate_k(0);
+    println!("estimated k = {} ({} rows used)", k, stats.used_rows);
+
+    let context =
+        ProvingContext::new(k, &circuit).map_err(|e| format!("Failed to create keys: {:?}", e))?;
+    let instances = public_inputs.to_instances();
+    let proof = context
+        .prove(&circuit, &instances)
+        .map_err(|e| format!("Failed to prove: {:?}", e))?;
+    println!("proof size = {} bytes", proof.len());
+
+    let file = ProofFile {
+        k,
+        db_commitments: public_inputs
+            .db_commitments
+            .iter()
+            .map(|value| to_hex(&value.to_repr()))
+            .collect(),
+        public_inputs: instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| to_hex(&value.to_repr()))
+                    .collect()
+            })
+            .collect(),
+        proof: to_hex(&proof),
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(out, json).map_err(|e| format!("Cannot write {}: {}", out.display(), e))?;
+
+    // The written file must verify on its own
+    let written = read_proof_file(out)?;
+    let verified = context
+        .verify(&written.proof_bytes()?, &written.instances()?)
+        .map_err(|e| format!("Failed to verify: {:?}", e))?;
+    if !verified {
+        return Err(format!("Proof in {} does not verify", out.display()));
+    }
+    Ok(file)
+}
+
+/// Read a proof file written by `prove_csv`
+pub fn read_proof_file(path: &Path) -> Result<ProofFile, String> {
+    let json =
+        fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+/// Lowercase hex of the bytes
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Bytes of a hex string
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(format!("Not a hex string: {}", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Field element of a hex encoded little-endian representation
+fn fr_from_hex(hex: &str) -> Result<Fr, String> {
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    let bytes = from_hex(hex)?;
+    if bytes.len() != repr.as_ref().len() {
+        return Err(format!("Not a field element: {}", hex));
+    }
+    repr.as_mut().copy_from_slice(&bytes);
+    Option::from(Fr::from_repr(repr)).ok_or_else(|| format!("Not a field element: {}", hex))
+}
+
+#[cfg(not(feature = "verifier-only"))]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let [_, csv, sql, out] = args.as_slice() else {
+        eprintln!("usage: prove_csv <table.csv> <sql> <proof.json>");
+        std::process::exit(2);
+    };
+
+    if let Err(error) = prove_csv(Path::new(csv), sql, Path::new(out)) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+    println!("wrote and verified {}", out);
+}
+
+#[cfg(feature = "verifier-only")]
+fn main() {
+    eprintln!("prove_csv needs the prover: build without the `verifier-only` feature");
+    std::process::exit(2);
+}
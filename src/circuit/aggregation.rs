@@ -1,6 +1,6 @@
 use halo2_proofs::{
-    circuit::{AssignedCell, Layouter, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
     poly::Rotation,
 };
 use pasta_curves::pallas::Base as Fr;
@@ -10,8 +10,130 @@ use super::config::PoneglyphConfig;
 use super::group_by::GroupByConfig;
 use super::range_check::RangeCheckConfig;
 
+type Cell = AssignedCell<Fr, Fr>;
+/// Payloads and running payloads of a MAX / MIN (see `AggregationConfig`)
+type Payload<'a> = (&'a [u64], &'a [u64]);
+
+/// Cells the values of an aggregation are bound to
+/// (see `AggregationChip::aggregate_grouped_copied`)
+#[derive(Clone, Copy, Debug)]
+pub enum ValueCells<'a> {
+    /// Each value is a copy of its cell (e.g. the WHERE check bits summed by
+    /// a filtered COUNT)
+    Copied(&'a [AssignedCell<Fr, Fr>]),
+    /// Each value is `unfiltered · bit`, the bit copied from its cell (e.g. a
+    /// SUM over the rows a WHERE keeps, the bits being its check bits)
+    Filtered(&'a [u64], &'a [AssignedCell<Fr, Fr>]),
+    /// Each value is `unfiltered · bit`, both copied from their cells (e.g.
+    /// a filtered SUM over a committed column)
+    CopiedFiltered(&'a [AssignedCell<Fr, Fr>], &'a [AssignedCell<Fr, Fr>]),
+}
+
+impl ValueCells<'_> {
+    /// Whether every row of `len` has its cells
+    fn has_len(&self, len: usize) -> bool {
+        match self {
+            ValueCells::Copied(cells) => cells.len() == len,
+            ValueCells::Filtered(unfiltered, bits) => unfiltered.len() == len && bits.len() == len,
+            ValueCells::CopiedFiltered(unfiltered, bits) => {
+                unfiltered.len() == len && bits.len() == len
+            }
+        }
+    }
+}
+
+/// Aggregation function
+/// AVG is proven as a SUM and a COUNT over the same groups, so the
+/// Aggregation Gate proves the other four
+/// 
+/// # Result Width
+/// 
+/// The gate picks the decomposition per function: a COUNT result is bounded
+/// by the row count and needs none, MAX / MIN diffs are 64-bit, and a SUM is
+/// split into two 64-bit halves (128-bit) once its running total exceeds
+/// `u64::MAX`.
+/// 
+/// An unknown aggregation cannot be constructed:
+/// 
+/// ```compile_fail
+/// use poneglyphdb::circuit::AggregationOp;
+/// 
+/// let op = AggregationOp {
+///     group_keys: vec![1, 1],
+///     values: vec![2, 3],
+///     function: "median",
+///     value_checks: None,
+///     value_semi_join: None,
+///     unfiltered: None,
+///     column: None,
+///     expected: None,
+///     payload: None,
+///     group_sort: None,
+///     sort_payload: None,
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AggregationFunction {
+    Sum,
+    Count,
+    Max,
+    Min,
+    Avg,
+}
+
+impl std::fmt::Display for AggregationFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AggregationFunction::Sum => "sum",
+            AggregationFunction::Count => "count",
+            AggregationFunction::Max => "max",
+            AggregationFunction::Min => "min",
+            AggregationFunction::Avg => "avg",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Aggregation Gate Configuration
 /// According to Paper Section 4.5: SUM, COUNT, MAX, MIN operations
+/// 
+/// # MAX / MIN
+/// 
+/// Within a group, `take` selects whether the running result is the row's
+/// value or the previous result, so the group result is always one of its
+/// members. The diffs `result - value` and `result - prev_result` (reversed
+/// for MIN) are range checked, so the running result is monotone and
+/// bounds every value seen so far.
+/// 
+/// # Payload (arg-max / arg-min)
+/// 
+/// A MAX / MIN may carry a payload per row (e.g. the row's id). Its running
+/// payload follows the same `take` bit as the running result: the row's
+/// payload where the result is the row's value, the previous payload
+/// otherwise. A group's last payload is therefore that of a row whose value
+/// is the group result (the last such row, in row order).
+/// 
+/// # Filtered Values
+/// 
+/// A SUM / COUNT row may prove its value as `value = unfiltered · bit`, the
+/// unfiltered value in `payload_column` and the bit (a copied WHERE check
+/// bit) in `take_column`; both columns are free on SUM / COUNT rows.
+/// 
+/// # Group Boundaries
+/// 
+/// Each row after the first holds the Group-By Gate's boundary of the pair
+/// ending at it, copied from the Group-By region (`b = 1` within a group,
+/// `b = 0` where a new group starts; the constant 1 for a single group). The
+/// running-result gates also constrain `b · (1 - b) = 0` and restart at
+/// `1 - b`.
+/// 
+/// # Wide SUM
+/// 
+/// A SUM whose running total exceeds `u64::MAX` uses the SUM constraints
+/// with each running result split as
+/// `result = lo + hi · 2^64` (in the MAX/MIN diff columns) and both halves
+/// range checked, so a running total above `u64::MAX` is proven to stay
+/// below 2^128 instead of wrapping the 64-bit assumption.
 #[derive(Clone, Debug)]
 pub struct AggregationConfig {
     // Value column - for values to be aggregated
@@ -20,11 +142,36 @@ pub struct AggregationConfig {
     // Result column - for aggregation results
     pub result_column: Column<Advice>,
     
+    // MAX/MIN witness columns (shared with Range Check chunks, different rows)
+    // advice[7] - take bit (1 = result is this row's value)
+    pub take_column: Column<Advice>,
+    // advice[3] - result - value (MAX) / value - result (MIN)
+    pub value_diff_column: Column<Advice>,
+    // advice[4] - result - prev_result (MAX) / prev_result - result (MIN), 0 at group start
+    pub prev_diff_column: Column<Advice>,
+    
+    // Payload columns (shared with Range Check chunks, different rows)
+    // advice[0] - payload of the row
+    pub payload_column: Column<Advice>,
+    // advice[1] - payload of the running result's row
+    pub payload_result_column: Column<Advice>,
+    
     // Selectors - for aggregation types
     pub sum_selector: Selector,
     pub count_selector: Selector,
     pub max_selector: Selector,
     pub min_selector: Selector,
+    // First row: result = value (SUM/MAX/MIN) or result = 1 (COUNT)
+    pub start_selector: Selector,
+    pub count_start_selector: Selector,
+    // Wide SUM: result = lo + hi * 2^64 (lo / hi in the value_diff / prev_diff columns)
+    pub wide_selector: Selector,
+    // Payload: first row (payload_result = payload) and later rows (follows take)
+    pub payload_start_selector: Selector,
+    pub payload_selector: Selector,
+    // Filtered value: value = unfiltered · bit (unfiltered / bit in the
+    // payload / take columns)
+    pub filter_selector: Selector,
     
     // Group-By integration
     pub group_by_config: GroupByConfig,
@@ -63,12 +210,52 @@ impl AggregationChip {
         // at the same time as Aggregation, so it's safe
         let value_column = config.advice[8];
         let result_column = config.advice[9];
+        let take_column = config.advice[7];
+        let value_diff_column = config.advice[3];
+        let prev_diff_column = config.advice[4];
+        let payload_column = config.advice[0];
+        let payload_result_column = config.advice[1];
         
         // Create selectors
         let sum_selector = meta.selector();
         let count_selector = meta.selector();
         let max_selector = meta.selector();
         let min_selector = meta.selector();
+        let start_selector = meta.selector();
+        let count_start_selector = meta.selector();
+        let wide_selector = meta.selector();
+        let payload_start_selector = meta.selector();
+        let payload_selector = meta.selector();
+        let filter_selector = meta.selector();
+        
+        // First row of the aggregation (no previous row): the first group starts here
+        meta.create_gate("aggregation start", |meta| {
+            let s = meta.query_selector(start_selector);
+            let value = meta.query_advice(value_column, Rotation::cur());
+            let result = meta.query_advice(result_column, Rotation::cur());
+            
+            vec![s * (result - value)]
+        });
+        
+        meta.create_gate("count aggregation start", |meta| {
+            let s = meta.query_selector(count_start_selector);
+            let result = meta.query_advice(result_column, Rotation::cur());
+            
+            vec![s * (result - Expression::Constant(Fr::ONE))]
+        });
+        
+        // Wide SUM: result = lo + hi * 2^64
+        // lo and hi are range checked to 64 bits in `assign_aggregation`,
+        // so the running result is a 128-bit integer (no field wrap-around)
+        meta.create_gate("wide sum result", |meta| {
+            let s = meta.query_selector(wide_selector);
+            let result = meta.query_advice(result_column, Rotation::cur());
+            let lo = meta.query_advice(value_diff_column, Rotation::cur());
+            let hi = meta.query_advice(prev_diff_column, Rotation::cur());
+            let two_pow_64 = Expression::Constant(fr_from_u128(1 << 64));
+            
+            vec![s * (result - (lo + hi * two_pow_64))]
+        });
         
         // SUM constraint: sum = Σ values[i] (within-group summation)
         // Note: Selector will not be enabled for the first row (no Rotation::prev())
@@ -77,14 +264,14 @@ impl AggregationChip {
             let value = meta.query_advice(value_column, Rotation::cur());
             let result = meta.query_advice(result_column, Rotation::cur());
             let prev_result = meta.query_advice(result_column, Rotation::prev());
-            let boundary = meta.query_advice(group_by_config.boundary_column, Rotation::cur());
+            let (boundary, boolean) = new_group(meta, group_by_config);
             
             // If new group starts (boundary = 1), result = value
             // If same group continues (boundary = 0), result = prev_result + value
             let sum_expr = boundary.clone() * value.clone() 
                 + (Expression::Constant(Fr::ONE) - boundary.clone()) * (prev_result + value);
             
-            vec![s * (result - sum_expr)]
+            vec![s.clone() * boolean, s * (result - sum_expr)]
         });
         
         // COUNT constraint: count = group_size (number of group elements)
@@ -92,71 +279,122 @@ impl AggregationChip {
             let s = meta.query_selector(count_selector);
             let result = meta.query_advice(result_column, Rotation::cur());
             let prev_result = meta.query_advice(result_column, Rotation::prev());
-            let boundary = meta.query_advice(group_by_config.boundary_column, Rotation::cur());
+            let (boundary, boolean) = new_group(meta, group_by_config);
             
             // If new group starts (boundary = 1), count = 1
             // If same group continues (boundary = 0), count = prev_count + 1
             let count_expr = boundary.clone() * Expression::Constant(Fr::ONE)
                 + (Expression::Constant(Fr::ONE) - boundary.clone()) * (prev_result + Expression::Constant(Fr::ONE));
             
-            vec![s * (result - count_expr)]
+            vec![s.clone() * boolean, s * (result - count_expr)]
         });
         
-        // MAX constraint: max >= all values[i] (within-group maximum)
-        // For production: MAX requires result >= value and result >= prev_result checks
-        // In gate constraint: if boundary = 1 then result = value
-        // if boundary = 0 then result >= prev_result and result >= value checks are done in comparison constraints
-        meta.create_gate("max aggregation", |meta| {
-            let s = meta.query_selector(max_selector);
+        // MAX / MIN constraints (running extremum within a group)
+        // 
+        // - take * (1 - take) = 0
+        // - result = boundary * value + (1 - boundary) * (take * value + (1 - take) * prev_result)
+        //   (the result is always a group member: this row's value or the previous result)
+        // - value_diff = result - value (MAX) or value - result (MIN)
+        // - prev_diff = (1 - boundary) * (result - prev_result) (MAX, reversed for MIN)
+        // 
+        // The diffs are range checked in `verify_aggregation` (copied into a
+        // 64-bit decomposition), so result >= value and result >= prev_result
+        // for MAX (<= for MIN). Together: result = max (min) of the group so far.
+        // Note: Selector will not be enabled for the first row (no Rotation::prev())
+        for (name, selector, is_max) in [
+            ("max aggregation", max_selector, true),
+            ("min aggregation", min_selector, false),
+        ] {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let value = meta.query_advice(value_column, Rotation::cur());
+                let result = meta.query_advice(result_column, Rotation::cur());
+                let prev_result = meta.query_advice(result_column, Rotation::prev());
+                let (boundary, boolean) = new_group(meta, group_by_config);
+                let take = meta.query_advice(take_column, Rotation::cur());
+                let value_diff = meta.query_advice(value_diff_column, Rotation::cur());
+                let prev_diff = meta.query_advice(prev_diff_column, Rotation::cur());
+                
+                let one = Expression::Constant(Fr::ONE);
+                let continued = take.clone() * value.clone()
+                    + (one.clone() - take.clone()) * prev_result.clone();
+                let extremum_expr =
+                    boundary.clone() * value.clone() + (one.clone() - boundary.clone()) * continued;
+                
+                let (expected_value_diff, expected_prev_diff) = if is_max {
+                    (result.clone() - value, result.clone() - prev_result)
+                } else {
+                    (value - result.clone(), prev_result - result.clone())
+                };
+                
+                vec![
+                    s.clone() * boolean,
+                    s.clone() * take.clone() * (one.clone() - take),
+                    s.clone() * (result - extremum_expr),
+                    s.clone() * (value_diff - expected_value_diff),
+                    s * (prev_diff - (one - boundary) * expected_prev_diff),
+                ]
+            });
+        }
+        
+        // Filtered value: the row's value counts only where its bit is 1
+        // (the bit is a copied check cell, boolean by its own gate)
+        meta.create_gate("filtered value", |meta| {
+            let s = meta.query_selector(filter_selector);
             let value = meta.query_advice(value_column, Rotation::cur());
-            let result = meta.query_advice(result_column, Rotation::cur());
-            let _prev_result = meta.query_advice(result_column, Rotation::prev());
-            let boundary = meta.query_advice(group_by_config.boundary_column, Rotation::cur());
+            let unfiltered = meta.query_advice(payload_column, Rotation::cur());
+            let bit = meta.query_advice(take_column, Rotation::cur());
             
-            // If new group starts (boundary = 1), max = value
-            // If same group continues (boundary = 0), max = max(prev_max, value)
-            // Constraint: if boundary = 1 then result = value
-            // if boundary = 0 then result >= prev_result and result >= value checks are done in comparison constraints
-            let max_expr = boundary.clone() * value.clone()
-                + (Expression::Constant(Fr::ONE) - boundary.clone()) * result.clone();
+            vec![s * (value - unfiltered * bit)]
+        });
+        
+        // Payload of a MAX / MIN: the first row's is its own
+        meta.create_gate("payload start", |meta| {
+            let s = meta.query_selector(payload_start_selector);
+            let payload = meta.query_advice(payload_column, Rotation::cur());
+            let payload_result = meta.query_advice(payload_result_column, Rotation::cur());
             
-            // When boundary = 1: result = value check
-            // When boundary = 0: result >= prev_result and result >= value checks
-            // are done in comparison constraints using decompose
-            vec![s * (result - max_expr)]
+            vec![s * (payload_result - payload)]
         });
         
-        // MIN constraint: min <= all values[i] (within-group minimum)
-        // For production: MIN requires result <= value and result <= prev_result checks
-        // In gate constraint: if boundary = 1 then result = value
-        // if boundary = 0 then result <= prev_result and result <= value checks are done in comparison constraints
-        meta.create_gate("min aggregation", |meta| {
-            let s = meta.query_selector(min_selector);
-            let value = meta.query_advice(value_column, Rotation::cur());
-            let result = meta.query_advice(result_column, Rotation::cur());
-            let _prev_result = meta.query_advice(result_column, Rotation::prev());
-            let boundary = meta.query_advice(group_by_config.boundary_column, Rotation::cur());
+        // Payload of a MAX / MIN: taken with the value
+        // payload_result = boundary * payload
+        //     + (1 - boundary) * (take * payload + (1 - take) * prev_payload_result)
+        // (take is boolean and selects the result in the MAX / MIN gate on the same row)
+        meta.create_gate("payload aggregation", |meta| {
+            let s = meta.query_selector(payload_selector);
+            let payload = meta.query_advice(payload_column, Rotation::cur());
+            let payload_result = meta.query_advice(payload_result_column, Rotation::cur());
+            let prev_payload_result = meta.query_advice(payload_result_column, Rotation::prev());
+            let (boundary, _) = new_group(meta, group_by_config);
+            let take = meta.query_advice(take_column, Rotation::cur());
             
-            // If new group starts (boundary = 1), min = value
-            // If same group continues (boundary = 0), min = min(prev_min, value)
-            // Constraint: if boundary = 1 then result = value
-            // if boundary = 0 then result <= prev_result and result <= value checks are done in comparison constraints
-            let min_expr = boundary.clone() * value.clone()
-                + (Expression::Constant(Fr::ONE) - boundary.clone()) * result.clone();
+            let one = Expression::Constant(Fr::ONE);
+            let continued =
+                take.clone() * payload.clone() + (one.clone() - take) * prev_payload_result;
+            let payload_expr = boundary.clone() * payload + (one - boundary) * continued;
             
-            // When boundary = 1: result = value check
-            // When boundary = 0: result <= prev_result and result <= value checks
-            // are done in comparison constraints using decompose
-            vec![s * (result - min_expr)]
+            vec![s * (payload_result - payload_expr)]
         });
         
         AggregationConfig {
             value_column,
             result_column,
+            take_column,
+            value_diff_column,
+            prev_diff_column,
+            payload_column,
+            payload_result_column,
             sum_selector,
             count_selector,
             max_selector,
             min_selector,
+            start_selector,
+            count_start_selector,
+            wide_selector,
+            payload_start_selector,
+            payload_selector,
+            filter_selector,
             group_by_config: group_by_config.clone(),
             range_check_config: range_check_config.clone(),
         }
@@ -168,13 +406,18 @@ impl AggregationChip {
     /// Parameters:
     /// - group_keys: Group keys (must be sorted)
     /// - values: Values for each row
-    /// - agg_type: Aggregation type ("sum", "count", "max", "min")
+    /// - function: Aggregation function (`Avg` fails with `Error::Synthesis`)
+    /// 
+    /// # Note
+    /// 
+    /// A SUM whose running total exceeds `u64::MAX` is proven with 128-bit
+    /// running results (see `AggregationFunction`).
     pub fn aggregate_and_verify(
         &self,
         mut layouter: impl Layouter<Fr>,
         group_keys: &[u64],
         values: &[u64],
-        agg_type: &str,
+        function: AggregationFunction,
     ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
         if group_keys.len() != values.len() {
             return Err(Error::Synthesis);
@@ -186,197 +429,677 @@ impl AggregationChip {
         
         // Get boundaries using Group-By chip
         let group_by_chip = super::group_by::GroupByChip::new(self.config.group_by_config.clone());
-        let _boundary_cells = group_by_chip.group_and_verify(
+        let boundary_cells = group_by_chip.group_and_verify(
             layouter.namespace(|| "group by for aggregation"),
             group_keys,
         )?;
         
-        // Perform aggregation operation
-        // Note: Selector will not be enabled for the first row (no Rotation::prev())
-        // We must also assign boundary values here because constraints use boundary_column
+        let result_values = Self::running_results(group_keys, values, function)?;
+        let (result_cells, _) = self.assign_aggregation(
+            layouter,
+            (group_keys, Some(&boundary_cells)),
+            (values, None),
+            (&result_values, None),
+            function,
+        )?;
+        Ok(result_cells)
+    }
+    
+    /// Aggregate several value columns over the same group keys
+    /// The group boundaries are proven once and shared by every aggregation
+    /// (e.g. `SELECT region, sum(x), avg(y), count(*) ... GROUP BY region`)
+    /// 
+    /// Parameters:
+    /// - group_keys: Group keys shared by all aggregations (must be sorted)
+    /// - aggregations: (values, function) of each aggregation, as in `aggregate_and_verify`
+    /// 
+    /// Returns the result cells of each aggregation, in order
+    pub fn aggregate_grouped(
+        &self,
+        layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        aggregations: &[(&[u64], AggregationFunction)],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        self.aggregate_grouped_copied(layouter, (group_keys, None), aggregations, &[])
+    }
+    
+    /// Aggregate several value columns over the same group keys, values
+    /// copied from assigned cells
+    /// Same as `aggregate_grouped`, but aggregation i binds its values to
+    /// `value_cells[i]` (None or missing: assigned from the values): copies
+    /// of the cells that produced them (e.g. the WHERE check bits summed by a
+    /// filtered COUNT), or products of unfiltered values and copied bits
+    /// (e.g. a SUM over the rows a WHERE keeps; SUM / COUNT only)
+    /// 
+    /// `(group_keys, key_cells)`: group keys and the cells they are copied
+    /// from (e.g. the output of the Sort Gate on the GROUP BY column; None:
+    /// assigned)
+    pub fn aggregate_grouped_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        (group_keys, key_cells): (&[u64], Option<&[Cell]>),
+        aggregations: &[(&[u64], AggregationFunction)],
+        value_cells: &[Option<ValueCells<'_>>],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        if aggregations
+            .iter()
+            .any(|(values, _)| values.len() != group_keys.len())
+        {
+            return Err(Error::Synthesis);
+        }
+        
+        if group_keys.is_empty() {
+            return Ok(vec![Vec::new(); aggregations.len()]);
+        }
         
-        // First, calculate all result values (for MAX/MIN comparison constraints)
-        let mut result_values = Vec::new();
-        let first_result = match agg_type {
-            "sum" => values[0],
-            "count" => 1,
-            "max" => values[0],
-            "min" => values[0],
-            _ => return Err(Error::Synthesis),
+        let group_by_chip = super::group_by::GroupByChip::new(self.config.group_by_config.clone());
+        let boundary_cells = match key_cells {
+            Some(key_cells) => group_by_chip.group_and_verify_copied(
+                layouter.namespace(|| "group by for aggregation"),
+                group_keys,
+                key_cells,
+            )?,
+            None => group_by_chip.group_and_verify(
+                layouter.namespace(|| "group by for aggregation"),
+                group_keys,
+            )?,
         };
-        result_values.push(first_result);
-        let mut current_result = first_result;
         
-        for i in 1..group_keys.len() {
-            let boundary = if group_keys[i] != group_keys[i-1] {
-                Fr::ONE
-            } else {
-                Fr::ZERO
+        let mut cells = Vec::with_capacity(aggregations.len());
+        for (i, &(values, function)) in aggregations.iter().enumerate() {
+            let result_values = Self::running_results(group_keys, values, function)?;
+            cells.push(
+                self.assign_aggregation(
+                    layouter.namespace(|| format!("{} aggregation", function)),
+                    (group_keys, Some(&boundary_cells)),
+                    (values, value_cells.get(i).copied().flatten()),
+                    (&result_values, None),
+                    function,
+                )?
+                .0,
+            );
+        }
+        Ok(cells)
+    }
+    
+    /// Aggregate several value columns over all rows (no GROUP BY)
+    /// All rows form a single group, so no group keys or Group-By Gate are
+    /// needed (e.g. `SELECT max(amount), count(*) FROM order`)
+    /// 
+    /// Parameters:
+    /// - aggregations: (values, function) of each aggregation, as in `aggregate_and_verify`
+    /// 
+    /// Returns the result cells of each aggregation, in order (the last
+    /// cell holds the result)
+    /// 
+    /// # Note
+    /// 
+    /// The boundary of every row after the first is the constant 1 (same
+    /// group), so the running result cannot restart: the last row's result
+    /// covers every row.
+    pub fn aggregate_global(
+        &self,
+        layouter: impl Layouter<Fr>,
+        aggregations: &[(&[u64], AggregationFunction)],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        self.aggregate_global_copied(layouter, aggregations, &[])
+    }
+    
+    /// Aggregate several value columns over all rows, values copied from
+    /// assigned cells (see `aggregate_grouped_copied`)
+    pub fn aggregate_global_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        aggregations: &[(&[u64], AggregationFunction)],
+        value_cells: &[Option<ValueCells<'_>>],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        let rows = aggregations.first().map_or(0, |(values, _)| values.len());
+        if aggregations.iter().any(|(values, _)| values.len() != rows) {
+            return Err(Error::Synthesis);
+        }
+        
+        // One group: the same key on every row
+        let group_keys = vec![0; rows];
+        let mut cells = Vec::with_capacity(aggregations.len());
+        for (i, &(values, function)) in aggregations.iter().enumerate() {
+            let result_values = Self::running_results(&group_keys, values, function)?;
+            cells.push(
+                self.assign_aggregation(
+                    layouter.namespace(|| format!("global {} aggregation", function)),
+                    (&group_keys, None),
+                    (values, value_cells.get(i).copied().flatten()),
+                    (&result_values, None),
+                    function,
+                )?
+                .0,
+            );
+        }
+        Ok(cells)
+    }
+    
+    /// Running result at each row (the last row of a group holds the group result)
+    /// Accumulated in u128 (a SUM total may exceed u64::MAX; fewer than 2^64
+    /// u64 values always sum below 2^128)
+    fn running_results(
+        group_keys: &[u64],
+        values: &[u64],
+        function: AggregationFunction,
+    ) -> Result<Vec<u128>, Error> {
+        use AggregationFunction::*;
+        let mut result_values: Vec<u128> = Vec::with_capacity(values.len());
+        for i in 0..group_keys.len() {
+            let value = values[i] as u128;
+            let new_group = i == 0 || group_keys[i] != group_keys[i - 1];
+            let result = match (new_group, function) {
+                (_, Avg) => return Err(Error::Synthesis),
+                (true, Count) => 1,
+                (true, Sum | Max | Min) => value,
+                (false, Sum) => result_values[i - 1] + value,
+                (false, Count) => result_values[i - 1] + 1,
+                (false, Max) => result_values[i - 1].max(value),
+                (false, Min) => result_values[i - 1].min(value),
             };
-            
-            let boundary_value = if boundary == Fr::ONE {
-                match agg_type {
-                    "sum" => values[i],
-                    "count" => 1,
-                    "max" => values[i],
-                    "min" => values[i],
-                    _ => return Err(Error::Synthesis),
-                }
+            result_values.push(result);
+        }
+        Ok(result_values)
+    }
+    
+    /// Verify per-row running aggregation results
+    /// Same constraints as `aggregate_and_verify`, but the results are supplied
+    /// by the caller (e.g. a precomputed partial result); invalid results make
+    /// the constraints fail instead of being recomputed
+    /// 
+    /// Parameters:
+    /// - group_keys: Group keys (must be sorted)
+    /// - values: Values for each row
+    /// - results: Running result for each row (the last row of a group holds the group result)
+    /// - function: Aggregation function (`Avg` fails with `Error::Synthesis`)
+    pub fn verify_aggregation(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        values: &[u64],
+        results: &[u64],
+        function: AggregationFunction,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let group_by_chip = super::group_by::GroupByChip::new(self.config.group_by_config.clone());
+        let boundary_cells = group_by_chip.group_and_verify(
+            layouter.namespace(|| "group by for aggregation"),
+            group_keys,
+        )?;
+        
+        let results: Vec<u128> = results.iter().map(|&result| result as u128).collect();
+        let (result_cells, _) = self.assign_aggregation(
+            layouter,
+            (group_keys, Some(&boundary_cells)),
+            (values, None),
+            (&results, None),
+            function,
+        )?;
+        Ok(result_cells)
+    }
+    
+    /// Aggregate MAX / MIN with a payload per row (arg-max / arg-min)
+    /// (e.g. `SELECT customer_id, max(amount), id FROM order GROUP BY customer_id`)
+    /// 
+    /// Parameters:
+    /// - group_keys: Group keys (must be sorted); empty: all rows form one group
+    /// - values: Values for each row
+    /// - payloads: Payload for each row
+    /// - function: `Max` or `Min` (the others fail with `Error::Synthesis`)
+    /// 
+    /// Returns the result cells and the payload cells; the last row of a
+    /// group holds the group result and the payload of a row achieving it
+    pub fn aggregate_with_payload(
+        &self,
+        layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        values: &[u64],
+        payloads: &[u64],
+        function: AggregationFunction,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        let keys = Self::payload_group_keys(group_keys, values)?;
+        let payload_results = Self::running_payloads(&keys, values, payloads, function)?;
+        self.assign_with_payload(
+            layouter,
+            group_keys,
+            values,
+            payloads,
+            &payload_results,
+            function,
+        )
+    }
+    
+    /// Verify per-row running payloads of a MAX / MIN
+    /// Same constraints as `aggregate_with_payload`, but the payload results
+    /// are supplied by the caller; a payload not taken with its row's value
+    /// makes the constraints fail
+    pub fn verify_payload(
+        &self,
+        layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        values: &[u64],
+        payloads: &[u64],
+        payload_results: &[u64],
+        function: AggregationFunction,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        self.assign_with_payload(
+            layouter,
+            group_keys,
+            values,
+            payloads,
+            payload_results,
+            function,
+        )
+    }
+    
+    /// Group keys of a payload aggregation (one group of key 0 without keys)
+    fn payload_group_keys(group_keys: &[u64], values: &[u64]) -> Result<Vec<u64>, Error> {
+        match group_keys.len() {
+            0 => Ok(vec![0; values.len()]),
+            rows if rows == values.len() => Ok(group_keys.to_vec()),
+            _ => Err(Error::Synthesis),
+        }
+    }
+    
+    /// Running payload at each row: the row's payload where the running
+    /// result is its value (the `take` bit), else the previous payload
+    fn running_payloads(
+        group_keys: &[u64],
+        values: &[u64],
+        payloads: &[u64],
+        function: AggregationFunction,
+    ) -> Result<Vec<u64>, Error> {
+        if payloads.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+        let results = Self::running_results(group_keys, values, function)?;
+        let mut payload_results: Vec<u64> = Vec::with_capacity(payloads.len());
+        for i in 0..payloads.len() {
+            let new_group = i == 0 || group_keys[i] != group_keys[i - 1];
+            let payload = if new_group || results[i] == values[i] as u128 {
+                payloads[i]
             } else {
-                match agg_type {
-                    "sum" => current_result + values[i],
-                    "count" => current_result + 1,
-                    "max" => current_result.max(values[i]),
-                    "min" => current_result.min(values[i]),
-                    _ => return Err(Error::Synthesis),
-                }
+                payload_results[i - 1]
             };
-            result_values.push(boundary_value);
-            current_result = boundary_value;
+            payload_results.push(payload);
+        }
+        Ok(payload_results)
+    }
+    
+    /// Shared implementation of `aggregate_with_payload` and `verify_payload`
+    fn assign_with_payload(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        values: &[u64],
+        payloads: &[u64],
+        payload_results: &[u64],
+        function: AggregationFunction,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        let keys = Self::payload_group_keys(group_keys, values)?;
+        let boundary_cells = if group_keys.is_empty() {
+            None
+        } else {
+            let group_by_chip =
+                super::group_by::GroupByChip::new(self.config.group_by_config.clone());
+            Some(
+                group_by_chip
+                    .group_and_verify(layouter.namespace(|| "group by for aggregation"), &keys)?,
+            )
+        };
+        
+        let results = Self::running_results(&keys, values, function)?;
+        self.assign_aggregation(
+            layouter,
+            (&keys, boundary_cells.as_deref()),
+            (values, None),
+            (&results, Some((payloads, payload_results))),
+            function,
+        )
+    }
+    
+    /// Shared implementation of `aggregate_and_verify`, `aggregate_global`
+    /// and `verify_aggregation` (results in u128 for a wide SUM)
+    /// 
+    /// `(values, value_cells)`: values and the cells they are bound to
+    /// (None: assigned)
+    /// `(group_keys, boundary_cells)`: group keys and the Group-By Gate's
+    /// boundary cells, copied to the rows after the first (None: a single
+    /// group, the boundaries fixed to 1)
+    /// `(results, payload)`: running results, and the payloads and running
+    /// payloads of a MAX / MIN with a payload (None: no payload)
+    /// 
+    /// Returns the result cells and the running payload cells (empty without
+    /// a payload)
+    fn assign_aggregation(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        (group_keys, boundary_cells): (&[u64], Option<&[Cell]>),
+        (values, value_cells): (&[u64], Option<ValueCells>),
+        (results, payload): (&[u128], Option<Payload>),
+        function: AggregationFunction,
+    ) -> Result<(Vec<Cell>, Vec<Cell>), Error> {
+        if group_keys.len() != values.len()
+            || boundary_cells.is_some_and(|cells| cells.len() + 1 < values.len())
+            || results.len() != values.len()
+            || value_cells.is_some_and(|cells| !cells.has_len(values.len()))
+            || payload.is_some_and(|(payloads, payload_results)| {
+                payloads.len() != values.len() || payload_results.len() != values.len()
+            })
+        {
+            return Err(Error::Synthesis);
+        }
+        
+        if group_keys.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        
+        // Decomposition width per function: none (COUNT, 64-bit SUM), 64-bit
+        // diffs (MAX / MIN) or 64-bit halves (SUM above u64::MAX)
+        let (is_extremum, is_wide) = match function {
+            AggregationFunction::Sum => (
+                false,
+                results.iter().any(|&result| result > u64::MAX as u128),
+            ),
+            AggregationFunction::Count => (false, false),
+            AggregationFunction::Max | AggregationFunction::Min => (true, false),
+            AggregationFunction::Avg => return Err(Error::Synthesis),
+        };
+        // A payload follows the take bit, which only MAX / MIN have; a
+        // filtered value uses the take and payload columns
+        let filtered = matches!(
+            value_cells,
+            Some(ValueCells::Filtered(..) | ValueCells::CopiedFiltered(..))
+        );
+        if (payload.is_some() && !is_extremum) || (filtered && is_extremum) {
+            return Err(Error::Synthesis);
         }
+        // MAX/MIN results are group members (u64); a larger claimed result
+        // is cut to its low 64 bits and fails the result constraint
+        let narrow = |result: u128| result as u64;
         
-        // Now assign result_cells and add comparison constraints
-        let result_cells = layouter.assign_region(
-            || format!("aggregate {}", agg_type),
+        // Perform aggregation operation
+        // Note: Selector will not be enabled for the first row (no Rotation::prev())
+        // We must also assign boundary values here because constraints use boundary_column
+        let (result_cells, payload_cells, diff_cells, diff_values) = layouter.assign_region(
+            || format!("aggregate {}", function),
             |mut region| {
+                // Value of row i: a copy of its cell, or assigned (a filtered
+                // value next to its unfiltered value and copied bit)
+                let assign_value = |region: &mut Region<'_, Fr>, i: usize| match value_cells {
+                    Some(ValueCells::Copied(cells)) => cells[i].copy_advice(
+                        || format!("value_{}", i),
+                        region,
+                        self.config.value_column,
+                        i,
+                    ),
+                    Some(ValueCells::Filtered(unfiltered, bits)) => {
+                        self.config.filter_selector.enable(region, i)?;
+                        region.assign_advice(
+                            || format!("unfiltered_{}", i),
+                            self.config.payload_column,
+                            i,
+                            || Value::known(Fr::from(unfiltered[i])),
+                        )?;
+                        bits[i].copy_advice(
+                            || format!("bit_{}", i),
+                            region,
+                            self.config.take_column,
+                            i,
+                        )?;
+                        region.assign_advice(
+                            || format!("value_{}", i),
+                            self.config.value_column,
+                            i,
+                            || Value::known(Fr::from(values[i])),
+                        )
+                    }
+                    Some(ValueCells::CopiedFiltered(unfiltered, bits)) => {
+                        self.config.filter_selector.enable(region, i)?;
+                        unfiltered[i].copy_advice(
+                            || format!("unfiltered_{}", i),
+                            region,
+                            self.config.payload_column,
+                            i,
+                        )?;
+                        bits[i].copy_advice(
+                            || format!("bit_{}", i),
+                            region,
+                            self.config.take_column,
+                            i,
+                        )?;
+                        region.assign_advice(
+                            || format!("value_{}", i),
+                            self.config.value_column,
+                            i,
+                            || Value::known(Fr::from(values[i])),
+                        )
+                    }
+                    None => region.assign_advice(
+                        || format!("value_{}", i),
+                        self.config.value_column,
+                        i,
+                        || Value::known(Fr::from(values[i])),
+                    ),
+                };
+                
+                // Payload and running payload of row i (with a payload)
+                let assign_payload = |region: &mut Region<'_, Fr>, i: usize| match payload {
+                    Some((payloads, payload_results)) => {
+                        region.assign_advice(
+                            || format!("payload_{}", i),
+                            self.config.payload_column,
+                            i,
+                            || Value::known(Fr::from(payloads[i])),
+                        )?;
+                        region
+                            .assign_advice(
+                                || format!("payload_result_{}", i),
+                                self.config.payload_result_column,
+                                i,
+                                || Value::known(Fr::from(payload_results[i])),
+                            )
+                            .map(Some)
+                    }
+                    None => Ok(None),
+                };
+                
                 let mut result_cells = Vec::new();
+                let mut payload_cells = Vec::new();
+                // MAX/MIN diffs (value diff and prev diff per row) or wide SUM
+                // halves (lo and hi per row), range checked below
+                let mut diff_cells = Vec::new();
+                let mut diff_values = Vec::new();
+                
+                // Special handling for first row (start selector, no previous row)
+                if function == AggregationFunction::Count {
+                    self.config.count_start_selector.enable(&mut region, 0)?;
+                } else {
+                    self.config.start_selector.enable(&mut region, 0)?;
+                }
+                if payload.is_some() {
+                    self.config.payload_start_selector.enable(&mut region, 0)?;
+                }
+                payload_cells.extend(assign_payload(&mut region, 0)?);
                 
-                // Special handling for first row (selector will not be enabled)
+                // The first row starts a group (no gate reads its boundary)
                 region.assign_advice(
                     || "boundary_0",
                     self.config.group_by_config.boundary_column,
                     0,
-                    || Value::known(Fr::ONE),
+                    || Value::known(Fr::ZERO),
                 )?;
                 
-                region.assign_advice(
-                    || "value_0",
-                    self.config.value_column,
-                    0,
-                    || Value::known(Fr::from(values[0])),
-                )?;
+                assign_value(&mut region, 0)?;
                 
                 let first_result_cell = region.assign_advice(
                     || "result_0",
                     self.config.result_column,
                     0,
-                    || Value::known(Fr::from(result_values[0])),
+                    || Value::known(fr_from_u128(results[0])),
                 )?;
                 result_cells.push(first_result_cell);
                 
                 // For remaining rows (i >= 1, Rotation::prev() can be used)
                 for i in 1..group_keys.len() {
-                    let boundary = if group_keys[i] != group_keys[i-1] {
-                        Fr::ONE
-                    } else {
-                        Fr::ZERO
+                    let new_group = group_keys[i] != group_keys[i - 1];
+                    
+                    // Boundary of the pair (i-1, i): the Group-By Gate's cell,
+                    // or the constant 1 (same group) of a single group
+                    match boundary_cells {
+                        Some(cells) => cells[i - 1].copy_advice(
+                            || format!("boundary_{}", i),
+                            &mut region,
+                            self.config.group_by_config.boundary_column,
+                            i,
+                        )?,
+                        None => region.assign_advice_from_constant(
+                            || format!("boundary_{}", i),
+                            self.config.group_by_config.boundary_column,
+                            i,
+                            Fr::ONE,
+                        )?,
                     };
                     
-                    region.assign_advice(
-                        || format!("boundary_{}", i),
-                        self.config.group_by_config.boundary_column,
+                    assign_value(&mut region, i)?;
+                    
+                    let result_cell = region.assign_advice(
+                        || format!("result_{}", i),
+                        self.config.result_column,
                         i,
-                        || Value::known(boundary),
+                        || Value::known(fr_from_u128(results[i])),
                     )?;
+                    result_cells.push(result_cell);
+                    
+                    let selector = match function {
+                        AggregationFunction::Sum => self.config.sum_selector,
+                        AggregationFunction::Count => self.config.count_selector,
+                        AggregationFunction::Max => self.config.max_selector,
+                        AggregationFunction::Min => self.config.min_selector,
+                        AggregationFunction::Avg => return Err(Error::Synthesis),
+                    };
+                    selector.enable(&mut region, i)?;
                     
+                    if !is_extremum {
+                        continue;
+                    }
+                    
+                    if payload.is_some() {
+                        self.config.payload_selector.enable(&mut region, i)?;
+                    }
+                    payload_cells.extend(assign_payload(&mut region, i)?);
+                    
+                    // take = 1 when the running result is this row's value
+                    let results = |row: usize| narrow(results[row]);
+                    let take = if results(i) == values[i] {
+                        Fr::ONE
+                    } else {
+                        Fr::ZERO
+                    };
                     region.assign_advice(
-                        || format!("value_{}", i),
-                        self.config.value_column,
+                        || format!("take_{}", i),
+                        self.config.take_column,
                         i,
-                        || Value::known(Fr::from(values[i])),
+                        || Value::known(take),
                     )?;
                     
-                    let result_cell = region.assign_advice(
-                        || format!("result_{}", i),
-                        self.config.result_column,
+                    // (minuend, subtrahend) of each diff; MIN reverses the order
+                    let (value_pair, prev_pair) = if function == AggregationFunction::Max {
+                        ((results(i), values[i]), (results(i), results(i - 1)))
+                    } else {
+                        ((values[i], results(i)), (results(i - 1), results(i)))
+                    };
+                    let prev_pair = if new_group { (0, 0) } else { prev_pair };
+                    
+                    // Diffs are witnessed as field elements; a negative diff
+                    // does not match its (wrapped) u64 decomposition
+                    let field_diff = |(a, b): (u64, u64)| Fr::from(a) - Fr::from(b);
+                    let value_diff = value_pair.0.wrapping_sub(value_pair.1);
+                    let prev_diff = prev_pair.0.wrapping_sub(prev_pair.1);
+                    let value_diff_field = field_diff(value_pair);
+                    let prev_diff_field = field_diff(prev_pair);
+                    
+                    diff_cells.push(region.assign_advice(
+                        || format!("value_diff_{}", i),
+                        self.config.value_diff_column,
                         i,
-                        || Value::known(Fr::from(result_values[i])),
-                    )?;
-                    result_cells.push(result_cell);
+                        || Value::known(value_diff_field),
+                    )?);
+                    diff_values.push(value_diff);
                     
-                    match agg_type {
-                        "sum" => self.config.sum_selector.enable(&mut region, i)?,
-                        "count" => self.config.count_selector.enable(&mut region, i)?,
-                        "max" => self.config.max_selector.enable(&mut region, i)?,
-                        "min" => self.config.min_selector.enable(&mut region, i)?,
-                        _ => return Err(Error::Synthesis),
+                    diff_cells.push(region.assign_advice(
+                        || format!("prev_diff_{}", i),
+                        self.config.prev_diff_column,
+                        i,
+                        || Value::known(prev_diff_field),
+                    )?);
+                    diff_values.push(prev_diff);
+                }
+                
+                // Wide SUM: split every running result (first row included)
+                // into 64-bit halves
+                if is_wide {
+                    for (i, &result) in results.iter().enumerate() {
+                        self.config.wide_selector.enable(&mut region, i)?;
+                        
+                        let halves = [
+                            (self.config.value_diff_column, "lo", result as u64),
+                            (self.config.prev_diff_column, "hi", (result >> 64) as u64),
+                        ];
+                        for (column, name, half) in halves {
+                            diff_cells.push(region.assign_advice(
+                                || format!("{}_{}", name, i),
+                                column,
+                                i,
+                                || Value::known(Fr::from(half)),
+                            )?);
+                            diff_values.push(half);
+                        }
                     }
                 }
                 
-                Ok(result_cells)
+                Ok((result_cells, payload_cells, diff_cells, diff_values))
             },
         )?;
         
-        // For production: comparison constraints for MAX/MIN
+        // Comparison constraints for MAX/MIN
         // For MAX: result >= value and result >= prev_result checks
         // For MIN: result <= value and result <= prev_result checks
-        // Using Range Check to verify result >= value (MAX) or result <= value (MIN)
-        if agg_type == "max" || agg_type == "min" {
+        // The diff cells are copied into the decomposition, so the range
+        // check is bound to the gate's diffs (first row: result = value)
+        // Wide SUM: the lo / hi halves are 64-bit (result < 2^128)
+        if is_extremum || is_wide {
             use super::range_check::RangeCheckChip;
             let range_check_chip = RangeCheckChip::new(self.config.range_check_config.clone());
-            
-            // For first row: result = value check (already checked in constraint since boundary = 1)
-            // But we can still do result >= value (MAX) or result <= value (MIN) check
-            if agg_type == "max" {
-                // For first row: result >= value check (diff = 0 since result = value)
-                let diff = result_values[0].saturating_sub(values[0]);
-                let _diff_chunks = range_check_chip.decompose_64bit(
-                    layouter.namespace(|| "max_diff_0"),
-                    Value::known(diff),
-                )?;
-            } else if agg_type == "min" {
-                // For first row: result <= value check (diff = 0 since result = value)
-                let diff = values[0].saturating_sub(result_values[0]);
-                let _diff_chunks = range_check_chip.decompose_64bit(
-                    layouter.namespace(|| "min_diff_0"),
-                    Value::known(diff),
-                )?;
-            }
-            
-            // For remaining rows (i >= 1, prev_result exists)
-            for i in 1..group_keys.len() {
-                let boundary = if group_keys[i] != group_keys[i-1] {
-                    Fr::ONE
-                } else {
-                    Fr::ZERO
-                };
-                
-                if agg_type == "max" {
-                    // For MAX: result >= value check
-                    let diff = result_values[i].saturating_sub(values[i]);
-                    let _diff_chunks = range_check_chip.decompose_64bit(
-                        layouter.namespace(|| format!("max_diff_{}", i)),
-                        Value::known(diff),
-                    )?;
-                    
-                    // If same group continues: result >= prev_result check
-                    if boundary == Fr::ZERO {
-                        let prev_diff = result_values[i].saturating_sub(result_values[i-1]);
-                        let _prev_diff_chunks = range_check_chip.decompose_64bit(
-                            layouter.namespace(|| format!("max_prev_diff_{}", i)),
-                            Value::known(prev_diff),
-                        )?;
-                    }
-                } else if agg_type == "min" {
-                    // For MIN: result <= value check
-                    let diff = values[i].saturating_sub(result_values[i]);
-                    let _diff_chunks = range_check_chip.decompose_64bit(
-                        layouter.namespace(|| format!("min_diff_{}", i)),
-                        Value::known(diff),
-                    )?;
-                    
-                    // If same group continues: result <= prev_result check
-                    if boundary == Fr::ZERO {
-                        let prev_diff = result_values[i-1].saturating_sub(result_values[i]);
-                        let _prev_diff_chunks = range_check_chip.decompose_64bit(
-                            layouter.namespace(|| format!("min_prev_diff_{}", i)),
-                            Value::known(prev_diff),
-                        )?;
-                    }
-                }
-            }
+            range_check_chip.decompose_assigned_batch(
+                layouter.namespace(|| format!("{}_diffs", function)),
+                &diff_cells,
+                &diff_values,
+            )?;
         }
         
-        Ok(result_cells)
+        Ok((result_cells, payload_cells))
     }
 }
+
+/// New-group flag of a running-result row, and the constraint that its
+/// boundary is boolean
+/// 
+/// The boundary column holds the Group-By Gate's boundary of the pair ending
+/// at this row (`b = 1` within a group, `b = 0` where a new group starts),
+/// copied from its cell, so the new-group flag is `1 - b`
+fn new_group(
+    meta: &mut VirtualCells<'_, Fr>,
+    group_by_config: &GroupByConfig,
+) -> (Expression<Fr>, Expression<Fr>) {
+    let b = meta.query_advice(group_by_config.boundary_column, Rotation::cur());
+    let one = Expression::Constant(Fr::ONE);
+    (one.clone() - b.clone(), b.clone() * (one - b))
+}
+
+/// Field element of a u128 (wide SUM results): hi · 2^64 + lo
+fn fr_from_u128(value: u128) -> Fr {
+    let two_pow_64 = Fr::from(u64::MAX) + Fr::ONE;
+    Fr::from((value >> 64) as u64) * two_pow_64 + Fr::from(value as u64)
+}
@@ -0,0 +1,376 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::PoneglyphConfig;
+use super::range_check::value_to_field_checked;
+
+type Cell = AssignedCell<Fr, Fr>;
+
+/// Arithmetic Gate Configuration
+/// Products for arithmetic expressions in WHERE comparisons (e.g. `price * quantity > 1000`)
+///
+/// # Column Allocation
+///
+/// - `a_column`: Left operand (advice[10])
+/// - `b_column`: Right operand (advice[11])
+/// - `product_column`: Auxiliary product cell, quotient or sum (advice[12])
+/// - `remainder_column`: Remainder of a division (advice[13])
+///
+/// # Constraints
+///
+/// 1. **Product**: `prod = a * b`
+/// 2. **Division**: `a = q * b + r` (`r < b` is range checked by `divide`'s caller)
+/// 3. **Sum**: `sum = a + b` (a difference `a - b` is the `a` cell of
+///    `(a - b) + b = a`)
+///
+/// # Note
+///
+/// Columns are shared with Join Gate (used in different rows), or with
+/// Range Check chunk columns advice[0-3] when Join is not configured
+#[derive(Clone, Debug)]
+pub struct ArithmeticConfig {
+    // Advice column for left operand
+    // advice[10] - shared with Join table1_key
+    pub a_column: Column<Advice>,
+
+    // Advice column for right operand
+    // advice[11] - shared with Join table1_value
+    pub b_column: Column<Advice>,
+
+    // Advice column for product
+    // advice[12] - shared with Join table2_key
+    pub product_column: Column<Advice>,
+
+    // Advice column for remainder
+    // advice[13] - shared with Join table2_value
+    pub remainder_column: Column<Advice>,
+
+    // Selector for product constraint
+    pub product_selector: Selector,
+
+    // Selector for division constraint
+    pub division_selector: Selector,
+
+    // Selector for sum constraint
+    pub sum_selector: Selector,
+}
+
+/// Input of an arithmetic gate row: a witness, a copy of an assigned cell
+/// (e.g. a committed column cell or an earlier result), or a constant
+#[derive(Clone, Copy, Debug)]
+pub enum GateInput<'a> {
+    /// Witnessed value
+    Witness(Value<u64>),
+    /// Copy of an assigned cell holding the value
+    Copied(&'a Cell, Value<u64>),
+    /// Constant, fixed in the verifying key
+    Constant(u64),
+}
+
+impl GateInput<'_> {
+    /// Value of the input
+    pub fn value(&self) -> Value<u64> {
+        match *self {
+            GateInput::Witness(value) | GateInput::Copied(_, value) => value,
+            GateInput::Constant(value) => Value::known(value),
+        }
+    }
+
+    /// Assign the input to `column` at `offset` of `region`
+    fn assign(
+        &self,
+        region: &mut Region<'_, Fr>,
+        name: &'static str,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<Cell, Error> {
+        match *self {
+            GateInput::Witness(value) => {
+                region.assign_advice(|| name, column, offset, || value.map(Fr::from))
+            }
+            GateInput::Copied(cell, _) => cell.copy_advice(|| name, region, column, offset),
+            GateInput::Constant(value) => {
+                region.assign_advice_from_constant(|| name, column, offset, Fr::from(value))
+            }
+        }
+    }
+}
+
+/// Arithmetic Chip
+pub struct ArithmeticChip {
+    config: ArithmeticConfig,
+}
+
+impl ArithmeticChip {
+    /// Create new ArithmeticChip
+    pub fn new(config: ArithmeticConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Arithmetic Gate
+    /// Constraints: prod = a * b, a = q * b + r, sum = a + b
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        config: &PoneglyphConfig,
+    ) -> ArithmeticConfig {
+        // Get advice columns
+        // Column allocation (see PoneglyphConfig documentation):
+        // - advice[10-14]: Join Gate - shared with Arithmetic Gate (a, b, product, remainder)
+        // - advice[0-3] instead when Join is not configured
+        let a_column = config.shared_advice(0);
+        let b_column = config.shared_advice(1);
+        let product_column = config.shared_advice(2);
+        let remainder_column = config.shared_advice(3);
+
+        // Create selectors
+        let product_selector = meta.selector();
+        let division_selector = meta.selector();
+        let sum_selector = meta.selector();
+
+        meta.create_gate("product", |meta| {
+            let s = meta.query_selector(product_selector);
+            let a = meta.query_advice(a_column, Rotation::cur());
+            let b = meta.query_advice(b_column, Rotation::cur());
+            let product = meta.query_advice(product_column, Rotation::cur());
+
+            // Constraint: prod = a * b
+            vec![s * (product - a * b)]
+        });
+
+        meta.create_gate("division", |meta| {
+            let s = meta.query_selector(division_selector);
+            let a = meta.query_advice(a_column, Rotation::cur());
+            let b = meta.query_advice(b_column, Rotation::cur());
+            let quotient = meta.query_advice(product_column, Rotation::cur());
+            let remainder = meta.query_advice(remainder_column, Rotation::cur());
+
+            // Constraint: a = q * b + r
+            vec![s * (a - quotient * b - remainder)]
+        });
+
+        meta.create_gate("sum", |meta| {
+            let s = meta.query_selector(sum_selector);
+            let a = meta.query_advice(a_column, Rotation::cur());
+            let b = meta.query_advice(b_column, Rotation::cur());
+            let sum = meta.query_advice(product_column, Rotation::cur());
+
+            // Constraint: sum = a + b
+            vec![s * (sum - a - b)]
+        });
+
+        ArithmeticConfig {
+            a_column,
+            b_column,
+            product_column,
+            remainder_column,
+            product_selector,
+            division_selector,
+            sum_selector,
+        }
+    }
+
+    /// Multiply two values and verify the product
+    ///
+    /// # Return Value
+    ///
+    /// Product cell
+    pub fn multiply(
+        &self,
+        layouter: impl Layouter<Fr>,
+        a: Value<u64>,
+        b: Value<u64>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let (_, _, product) =
+            self.multiply_copied(layouter, GateInput::Witness(a), GateInput::Witness(b))?;
+        Ok(product)
+    }
+
+    /// Multiply two inputs, each witnessed, copied or constant
+    ///
+    /// # Return Value
+    ///
+    /// (a, b, product) cells
+    pub fn multiply_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: GateInput<'_>,
+        b: GateInput<'_>,
+    ) -> Result<(Cell, Cell, Cell), Error> {
+        layouter.assign_region(
+            || "product",
+            |mut region| {
+                self.config.product_selector.enable(&mut region, 0)?;
+
+                let a_cell = a.assign(&mut region, "a", self.config.a_column, 0)?;
+                let b_cell = b.assign(&mut region, "b", self.config.b_column, 0)?;
+
+                // A product above u64::MAX could not be range checked
+                let product = a.value().zip(b.value()).map(|(a, b)| a as u128 * b as u128);
+                let product = value_to_field_checked(product)?;
+                let product_cell = region.assign_advice(
+                    || "product",
+                    self.config.product_column,
+                    0,
+                    || product,
+                )?;
+                Ok((a_cell, b_cell, product_cell))
+            },
+        )
+    }
+
+    /// Add two inputs: `sum = a + b`
+    ///
+    /// # Return Value
+    ///
+    /// Sum cell
+    pub fn add_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: GateInput<'_>,
+        b: GateInput<'_>,
+    ) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "sum",
+            |mut region| {
+                self.config.sum_selector.enable(&mut region, 0)?;
+
+                a.assign(&mut region, "a", self.config.a_column, 0)?;
+                b.assign(&mut region, "b", self.config.b_column, 0)?;
+
+                // A sum above u64::MAX could not be range checked
+                let sum = a.value().zip(b.value()).map(|(a, b)| a as u128 + b as u128);
+                let sum = value_to_field_checked(sum)?;
+                region.assign_advice(|| "sum", self.config.product_column, 0, || sum)
+            },
+        )
+    }
+
+    /// Subtract two inputs: the sum gate row `(a - b) + b = a`, with the
+    /// difference witnessed in the `a` cell
+    ///
+    /// # Return Value
+    ///
+    /// Difference cell, or `Error::Synthesis` if `b > a` (the difference
+    /// of u64 values must not be negative)
+    pub fn subtract_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: GateInput<'_>,
+        b: GateInput<'_>,
+    ) -> Result<Cell, Error> {
+        layouter.assign_region(
+            || "difference",
+            |mut region| {
+                self.config.sum_selector.enable(&mut region, 0)?;
+
+                let difference = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| a.checked_sub(b).map_or(u128::MAX, u128::from));
+                let difference = value_to_field_checked(difference)?;
+                let difference_cell = region.assign_advice(
+                    || "difference",
+                    self.config.a_column,
+                    0,
+                    || difference,
+                )?;
+                b.assign(&mut region, "b", self.config.b_column, 0)?;
+                a.assign(&mut region, "a", self.config.product_column, 0)?;
+                Ok(difference_cell)
+            },
+        )
+    }
+
+    /// Multiply a value by a constant: the product gate with `b` fixed to `factor`
+    ///
+    /// # Return Value
+    ///
+    /// Product cell
+    pub fn scale(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: Value<u64>,
+        factor: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "scale",
+            |mut region| {
+                self.config.product_selector.enable(&mut region, 0)?;
+
+                region.assign_advice(|| "a", self.config.a_column, 0, || a.map(Fr::from))?;
+                region.assign_advice_from_constant(
+                    || "factor",
+                    self.config.b_column,
+                    0,
+                    Fr::from(factor),
+                )?;
+
+                let product = value_to_field_checked(a.map(|a| a as u128 * factor as u128))?;
+                region.assign_advice(|| "product", self.config.product_column, 0, || product)
+            },
+        )
+    }
+
+    /// Divide two values: `a = q * b + r`
+    ///
+    /// # Return Value
+    ///
+    /// (quotient, remainder, b) cells
+    pub fn divide(
+        &self,
+        layouter: impl Layouter<Fr>,
+        a: Value<u64>,
+        b: Value<u64>,
+    ) -> Result<(Cell, Cell, Cell), Error> {
+        self.divide_copied(layouter, GateInput::Witness(a), GateInput::Witness(b))
+    }
+
+    /// Divide two inputs, each witnessed, copied or constant: `a = q * b + r`
+    ///
+    /// # Note
+    ///
+    /// The gate alone does not fix q and r (e.g. q = 0, r = a also holds);
+    /// the caller range checks `r < b` on the returned cells, which also
+    /// rules out b = 0. For b = 0 the witness is q = 0, r = a.
+    ///
+    /// # Return Value
+    ///
+    /// (quotient, remainder, b) cells
+    pub fn divide_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: GateInput<'_>,
+        b: GateInput<'_>,
+    ) -> Result<(Cell, Cell, Cell), Error> {
+        layouter.assign_region(
+            || "division",
+            |mut region| {
+                self.config.division_selector.enable(&mut region, 0)?;
+
+                a.assign(&mut region, "a", self.config.a_column, 0)?;
+                let b_cell = b.assign(&mut region, "b", self.config.b_column, 0)?;
+
+                let (a, b) = (a.value(), b.value());
+                let quotient = a.zip(b).map(|(a, b)| a.checked_div(b).unwrap_or(0));
+                let remainder = a.zip(b).map(|(a, b)| a.checked_rem(b).unwrap_or(a));
+                let quotient_cell = region.assign_advice(
+                    || "quotient",
+                    self.config.product_column,
+                    0,
+                    || quotient.map(Fr::from),
+                )?;
+                let remainder_cell = region.assign_advice(
+                    || "remainder",
+                    self.config.remainder_column,
+                    0,
+                    || remainder.map(Fr::from),
+                )?;
+                Ok((quotient_cell, remainder_cell, b_cell))
+            },
+        )
+    }
+}
@@ -4,34 +4,62 @@ use halo2_proofs::{
 };
 use pasta_curves::pallas::Base as Fr;
 
+use super::aggregation::{AggregationChip, AggregationConfig};
+use super::arithmetic::{ArithmeticChip, ArithmeticConfig};
+use super::group_by::{GroupByChip, GroupByConfig};
+use super::join::{JoinChip, JoinConfig};
+use super::merkle::{MerkleChip, MerkleConfig};
+use super::non_membership::{NonMembershipChip, NonMembershipConfig};
+use super::poseidon::{PoseidonChip, PoseidonConfig};
+use super::range_check::{RangeCheckChip, RangeCheckConfig};
+use super::set_membership::{SetMembershipChip, SetMembershipConfig};
+use super::sort::{SortChip, SortConfig};
+use super::window::{WindowChip, WindowConfig};
+
 /// Main circuit configuration
 /// According to Paper Section 5.1: BN254 curve, IPA commitment
 ///
 /// # Column Allocation
 ///
-/// ## Advice Columns (15 columns)
+/// ## Advice Columns (15 columns, 10 without Join)
 /// - `advice[0-7]`: Range Check chunk columns (for 8-bit decomposition)
 /// - `advice[2-4]`: Sort Gate (input, output, diff) - shared with Range Check
+/// - `advice[6-8]`: Sort Gate multiset check (γ, input product, output product) -
+///   shared with Range Check
 /// - `advice[5-7]`: Group-By Gate (key, boundary, inverse) - shared with Range Check
-/// - `advice[8-9]`: Range Check (check/x, diff) / Aggregation Gate (value, result)
+/// - `advice[7]`: Window Gate partition (boundary / partition key) - shared with Range Check
+/// - `advice[8-9]`: Range Check (check/x, diff) / Aggregation Gate (value, result) / Window Gate (value, result)
+/// - `advice[8-9]`: Set Membership Gate (tag, value) - shared with Range Check
+/// - `advice[0-4]`: Set Membership Gate (lower, upper, member, below, above) -
+///   shared with Range Check
+/// - `advice[0-5]`: Non-Membership Gate (flag, key, hash, leaf, bound, index) -
+///   shared with Range Check
+/// - `advice[0-1]`: Aggregation Gate payload (payload, running payload of an arg-max) -
+///   shared with Range Check
 /// - `advice[10-14]`: Join Gate (table1_key, table1_value, table2_key, table2_value, match_flag)
+/// - `advice[10-13]`: Arithmetic Gate (a, b, product / quotient, remainder) - shared with Join
+///   (`advice[0-3]` when Join is not configured, see `shared_advice`)
 ///
 /// ## Fixed Columns (2 columns)
 /// - `fixed[0]`: Threshold (t) value used in Range Check
 /// - `fixed[1]`: u value used in Range Check
+/// - The Poseidon Gate's round constants use `fixed[0-1]` and a third fixed
+///   column owned by `PoseidonConfig`
 ///
-/// ## Instance Column (1 column)
-/// - `instance`: For public data (database commitment, query result)
-///   - Row 0: Database commitment
-///   - Row 1: Query result
+/// ## Instance Columns (2 columns, see `PublicInputs`)
+/// - `instance`: Database commitment (row 0)
+/// - `result_instance`: Query result (row 0)
 ///
-/// ## Table Column (1 column)
+/// ## Table Columns (5 columns)
 /// - `lookup_table`: Lookup table for values 0-255 (for 8-bit chunks)
+///   or 0-65535 with `lookup_bits = 16` (see `configure_with_lookup_bits`)
+/// - The Set Membership Gate's (tag, lower, upper, member) tables are owned by
+///   `SetMembershipConfig` and loaded with the circuit's sets
 #[derive(Clone, Debug)]
 pub struct PoneglyphConfig {
     // Advice columns - for private data
-    // Expanded from 10 to 15 for Join Gate support
-    pub advice: [Column<Advice>; 15],
+    // 15 with Join Gate support, 10 without (see `ConfigFeatures`)
+    pub advice: Vec<Column<Advice>>,
 
     // Fixed columns - for constant values
     // fixed[0]: Threshold (t) value
@@ -41,23 +69,130 @@ pub struct PoneglyphConfig {
     // Table column - for lookup table (0-255 values)
     pub lookup_table: TableColumn,
 
-    // Instance columns - for public data (commitment, query result)
-    // Row 0: Database commitment
-    // Row 1: Query result
+    // Chunk width in bits for range check decomposition (8 or 16)
+    // The lookup table holds 0..2^lookup_bits
+    pub lookup_bits: u8,
+
+    // Optional gates this config was built with
+    pub features: ConfigFeatures,
+
+    // Instance columns - for public data, one column per value (see `PublicInputs`)
+    // instance: Database commitment (row 0)
+    // result_instance: Query result (row 0)
     pub instance: Column<Instance>,
+    pub result_instance: Column<Instance>,
 
     // Selectors - to enable/disable gates
     // Common selectors for Range Check
     pub range_check_selector: Selector,
     pub less_than_selector: Selector,
     pub decomposition_selector: Selector,
-    pub diff_lookup_selector: Selector,
     // Separate selector for Sort (to avoid conflict with less_than_selector)
     pub sort_selector: Selector,
 }
 
+/// Gate configurations created by `PoneglyphConfig::configure_with_gates`
+///
+/// Synthesis must use these configs: each gate owns the selectors it was
+/// configured with, so rebuilding a gate config from the shared selectors
+/// in `PoneglyphConfig` would enable the wrong constraints.
+#[derive(Clone, Debug)]
+pub struct GateConfigs {
+    pub range_check: RangeCheckConfig,
+    pub sort: SortConfig,
+    pub group_by: GroupByConfig,
+    // None when configured without Join (`ConfigFeatures::join = false`)
+    pub join: Option<JoinConfig>,
+    pub aggregation: AggregationConfig,
+    pub arithmetic: ArithmeticConfig,
+    pub window: WindowConfig,
+    pub poseidon: PoseidonConfig,
+    pub merkle: MerkleConfig,
+    pub set_membership: SetMembershipConfig,
+    pub non_membership: NonMembershipConfig,
+}
+
+/// Optional gates of a configuration
+///
+/// `Circuit::configure` is static, so the feature set is fixed per circuit
+/// type: `PoneglyphCircuit` configures all gates, `JoinFreeCircuit` drops
+/// Join. `PoneglyphCircuit::features` reports what a circuit's operations need.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigFeatures {
+    /// Join Gate (advice[10-14])
+    pub join: bool,
+}
+
+impl ConfigFeatures {
+    /// Every gate configured
+    pub fn all() -> Self {
+        Self { join: true }
+    }
+}
+
+impl Default for ConfigFeatures {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
 impl PoneglyphConfig {
     pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        Self::configure_with_gates(meta).0
+    }
+
+    /// Configure columns and all gates, returning the gate configs as well
+    pub fn configure_with_gates(meta: &mut ConstraintSystem<Fr>) -> (Self, GateConfigs) {
+        Self::configure_with_lookup_bits(meta, 8)
+    }
+
+    /// Configure columns and all gates with `lookup_bits`-wide range check chunks
+    ///
+    /// # Parameters
+    ///
+    /// - `lookup_bits`: 8 (8 chunks per 64-bit value, 256-row table) or
+    ///   16 (4 chunks per 64-bit value, 65536-row table, requires k ≥ 17)
+    ///
+    /// # Note
+    ///
+    /// A decomposition takes one row at either width (all chunks share the
+    /// value's row), so 16-bit chunks halve the chunk lookups rather than
+    /// the rows; the larger table only pays off when it is not the row
+    /// bottleneck.
+    ///
+    /// # Panics
+    ///
+    /// If `lookup_bits` is neither 8 nor 16
+    pub fn configure_with_lookup_bits(
+        meta: &mut ConstraintSystem<Fr>,
+        lookup_bits: u8,
+    ) -> (Self, GateConfigs) {
+        Self::configure_with_features(meta, lookup_bits, ConfigFeatures::all())
+    }
+
+    /// Configure columns and the gates enabled in `features`
+    ///
+    /// # Parameters
+    ///
+    /// - `lookup_bits`: Range check chunk width (see `configure_with_lookup_bits`)
+    /// - `features`: Optional gates; without Join only advice[0-9] are allocated
+    ///   and the Join Gate's constraints are not created
+    ///
+    /// # Panics
+    ///
+    /// If `lookup_bits` is neither 8 nor 16: any other width would size the
+    /// lookup table and the chunk decomposition inconsistently
+    pub fn configure_with_features(
+        meta: &mut ConstraintSystem<Fr>,
+        lookup_bits: u8,
+        features: ConfigFeatures,
+    ) -> (Self, GateConfigs) {
+        assert!(
+            lookup_bits == 8 || lookup_bits == 16,
+            "lookup_bits must be 8 or 16, got {}",
+            lookup_bits
+        );
+
         // Create advice columns
         // Expanded from 10 to 15 for Join Gate support
         //
@@ -67,7 +202,7 @@ impl PoneglyphConfig {
         // - advice[5-7]: Group-By Gate (key, boundary, inverse) - shared with Range Check
         // - advice[8-9]: Range Check (check/x, diff) / Aggregation Gate (value, result)
         // - advice[10-14]: Join Gate (table1_key, table1_value, table2_key, table2_value, match_flag)
-        let advice = [
+        let mut advice = vec![
             meta.advice_column(), // 0 - Range Check chunk[0]
             meta.advice_column(), // 1 - Range Check chunk[1]
             meta.advice_column(), // 2 - Range Check chunk[2] / Sort input
@@ -78,12 +213,16 @@ impl PoneglyphConfig {
             meta.advice_column(), // 7 - Range Check chunk[7] / Group-By inverse
             meta.advice_column(), // 8 - Range Check check/x / Aggregation value
             meta.advice_column(), // 9 - Range Check diff / Aggregation result
-            meta.advice_column(), // 10 - Join table1_key
-            meta.advice_column(), // 11 - Join table1_value
-            meta.advice_column(), // 12 - Join table2_key
-            meta.advice_column(), // 13 - Join table2_value
-            meta.advice_column(), // 14 - Join match_flag
         ];
+        if features.join {
+            advice.extend([
+                meta.advice_column(), // 10 - Join table1_key
+                meta.advice_column(), // 11 - Join table1_value
+                meta.advice_column(), // 12 - Join table2_key
+                meta.advice_column(), // 13 - Join table2_value
+                meta.advice_column(), // 14 - Join match_flag
+            ]);
+        }
 
         // Create fixed columns
         // fixed[0]: Threshold (t) value - used in Range Check
@@ -96,25 +235,25 @@ impl PoneglyphConfig {
         // Table column - for lookup table (0-255 values)
         let lookup_table = meta.lookup_table_column();
 
-        // Instance column - for public data
-        // Row 0: Database commitment
-        // Row 1: Query result
+        // Instance columns - for public data (see `PublicInputs`)
+        // instance: Database commitment, result_instance: Query result
         let instance = meta.instance_column();
+        let result_instance = meta.instance_column();
 
         // Selectors
         // complex_selector required for lookup constraint
         let range_check_selector = meta.complex_selector();
         let less_than_selector = meta.selector();
         let decomposition_selector = meta.selector();
-        let diff_lookup_selector = meta.complex_selector();
         let sort_selector = meta.selector();
 
         // Enable fixed columns (for threshold and u values)
         meta.enable_constant(fixed[0]);
         meta.enable_constant(fixed[1]);
 
-        // Enable instance column
+        // Enable instance columns
         meta.enable_equality(instance);
+        meta.enable_equality(result_instance);
 
         // Enable advice columns (for equality)
         for col in &advice {
@@ -126,44 +265,77 @@ impl PoneglyphConfig {
             advice,
             fixed,
             lookup_table,
+            lookup_bits,
+            features,
             instance,
+            result_instance,
             range_check_selector,
             less_than_selector,
             decomposition_selector,
-            diff_lookup_selector,
             sort_selector,
         };
 
         // Configure all gates
-        let _range_check_config =
-            crate::circuit::range_check::RangeCheckChip::configure(meta, &temp_config);
-        let _sort_config =
-            crate::circuit::sort::SortChip::configure(meta, &temp_config, &_range_check_config);
-        let _group_by_config = crate::circuit::group_by::GroupByChip::configure(
-            meta,
-            &temp_config,
-            &_range_check_config,
-        );
-        let _join_config = crate::circuit::join::JoinChip::configure(
-            meta,
-            &temp_config,
-            &_range_check_config,
-            &_sort_config,
-        );
-        let _aggregation_config = crate::circuit::aggregation::AggregationChip::configure(
-            meta,
-            &temp_config,
-            &_group_by_config,
-            &_range_check_config,
-        );
+        let range_check = RangeCheckChip::configure(meta, &temp_config);
+        let sort = SortChip::configure(meta, &temp_config, &range_check);
+        let group_by = GroupByChip::configure(meta, &temp_config, &range_check);
+        let mut join = features
+            .join
+            .then(|| JoinChip::configure(meta, &temp_config, &range_check, &sort));
+        let aggregation = AggregationChip::configure(meta, &temp_config, &group_by, &range_check);
+        let arithmetic = ArithmeticChip::configure(meta, &temp_config);
+        let window = WindowChip::configure(meta, &temp_config);
+        let poseidon = PoseidonChip::configure(meta, &temp_config);
+        // The Sort Gate's MultisetHash check derives its γ with the Poseidon Gate
+        let sort = sort.with_poseidon(poseidon.clone());
+        if let Some(join) = join.as_mut() {
+            join.sort_config = join.sort_config.clone().with_poseidon(poseidon.clone());
+        }
+        let merkle = MerkleChip::configure(meta, &temp_config);
+        let set_membership = SetMembershipChip::configure(meta, &temp_config, &range_check);
+        let non_membership = NonMembershipChip::configure(meta, &temp_config);
+
+        let gates = GateConfigs {
+            range_check,
+            sort,
+            group_by,
+            join,
+            aggregation,
+            arithmetic,
+            window,
+            poseidon,
+            merkle,
+            set_membership,
+            non_membership,
+        };
+
+        (temp_config, gates)
+    }
 
-        temp_config
+    /// Advice column `i` (0-3) for the Arithmetic Gate and witnessed Range Check thresholds
+    ///
+    /// advice[10 + i] (shared with Join) when Join is configured, otherwise the
+    /// Range Check chunk column advice[i]; both are used in different rows
+    /// than the gates they are shared with.
+    pub fn shared_advice(&self, i: usize) -> Column<Advice> {
+        if self.features.join {
+            self.advice[10 + i]
+        } else {
+            self.advice[i]
+        }
     }
 
-    /// Load lookup table (values 0-255)
+    /// Load lookup table (values 0-255, or 0-65535 with `lookup_bits = 16`)
     /// According to Paper Section 4.1: Lookup table for 8-bit chunks
     /// According to Halo2 API: assign_table should be used
     ///
+    /// # Note
+    ///
+    /// Must be called exactly once per synthesis: every chunk lookup of every
+    /// gate reads the same table. A second call fails with
+    /// `Error::TableError(TableError::UsedColumn)`, so an accidental reload
+    /// is caught by the layouter rather than silently duplicating the table.
+    ///
     /// # Usage
     ///
     /// ```rust,ignore
@@ -171,12 +343,12 @@ impl PoneglyphConfig {
     /// ```
     pub fn load_lookup_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
         layouter.assign_table(
-            || "8-bit lookup table (0-255)",
+            || format!("{}-bit lookup table", self.lookup_bits),
             |mut table| {
                 // Write values 0-255 to table column
                 // Note: Halo2 example uses 1-256 but we use 0-255
                 // because 8-bit chunks are in range 0-255
-                for i in 0..256 {
+                for i in 0..(1usize << self.lookup_bits) {
                     table.assign_cell(
                         || format!("lookup value {}", i),
                         self.lookup_table,
@@ -189,39 +361,39 @@ impl PoneglyphConfig {
         )
     }
 
-    /// Read public input from instance column and copy to advice column
-    /// According to Paper Section 5.1: Public data (commitment, query result) is in instance column
+    /// Read public input from an instance column and copy to advice column
+    /// According to Paper Section 5.1: Public data (commitment, query result) is in instance columns
     ///
     /// # Usage
     ///
     /// ```rust,ignore
-    /// // Read database commitment (row 0)
-    /// let db_commitment = config.read_public_input(&mut layouter, 0)?;
+    /// // Read database commitment
+    /// let db_commitment = config.read_public_input(&mut layouter, config.instance)?;
     ///
-    /// // Read query result (row 1)
-    /// let query_result = config.read_public_input(&mut layouter, 1)?;
+    /// // Read query result
+    /// let query_result = config.read_public_input(&mut layouter, config.result_instance)?;
     /// ```
     ///
     /// # Note
     ///
-    /// Assigning values to instance column is done on the prover side (in MockProver::run() call).
-    /// This function is used to read values from instance column and use them in constraints.
+    /// Assigning values to instance columns is done on the prover side (in MockProver::run() call).
+    /// This function is used to read values from an instance column and use them in constraints.
     pub fn read_public_input(
         &self,
         layouter: &mut impl Layouter<Fr>,
-        row: usize,
+        column: Column<Instance>,
     ) -> Result<Value<Fr>, Error> {
         layouter.assign_region(
-            || format!("read public input row {}", row),
+            || "read public input",
             |mut region| {
-                // Read value from instance column
-                let value = region.instance_value(self.instance, row)?;
+                // Read value from instance column (row 0, see `PublicInputs`)
+                let value = region.instance_value(column, 0)?;
 
                 // Copy to advice column (for use in constraints)
                 // Note: This is optional, just returning instance value is also sufficient
                 // But copying to advice column may be needed for use in constraints
                 region.assign_advice(
-                    || format!("public_input_{}", row),
+                    || "public input",
                     self.advice[0], // Temporarily using advice[0]
                     0,
                     || value,
@@ -232,35 +404,101 @@ impl PoneglyphConfig {
         )
     }
 
-    /// Assign public inputs to instance column (helper function)
-    /// According to Paper Section 5.1: Database commitment and query result should be public inputs
+    /// Public inputs in the instance column layout (see `PublicInputs`)
     ///
     /// # Usage
     ///
     /// ```rust,ignore
     /// use halo2_proofs::dev::MockProver;
     ///
-    /// // Used in MockProver::run() call
-    /// let public_inputs = vec![
-    ///     vec![db_commitment], // Row 0: Database commitment
-    ///     vec![query_result],  // Row 1: Query result
-    /// ];
+    /// // Column 0: Database commitment, column 1: Query result
+    /// let public_inputs = PoneglyphConfig::get_public_input_layout(db_commitment, query_result);
     /// let prover = MockProver::run(k, &circuit, public_inputs)?;
     /// ```
-    ///
-    /// # Note
-    ///
-    /// This function is for documentation purposes only. In actual usage,
-    /// the public_inputs parameter is used in `MockProver::run()` or `create_proof()` calls.
-    ///
-    /// # Public Input Layout
-    ///
-    /// - Row 0: Database commitment (Fr)
-    /// - Row 1: Query result (Fr)
     pub fn get_public_input_layout(db_commitment: Fr, query_result: Fr) -> Vec<Vec<Fr>> {
+        PublicInputs::new(db_commitment, query_result).to_instances()
+    }
+}
+
+/// Public Inputs
+/// Public values of one proof, in the instance column layout shared by
+/// `MockProver::run`, `Prover::prove` and `Verifier::verify`
+///
+/// # Layout
+///
+/// One instance column per kind of value:
+///
+/// - Column 0 (`PoneglyphConfig::instance`): Database commitments, one row
+///   per committed table (in table name order, see `CommittedDatabase`),
+///   followed by a universal circuit's public operands
+///   (`PoneglyphCircuit::public_operands`, see `CircuitCapacity::instances`)
+/// - Column 1 (`PoneglyphConfig::result_instance`): Query result, in row 0
+///
+/// No commitments or a query result left as None give an empty column, for
+/// circuits that do not bind it (an unbound column's values are not
+/// constrained).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PublicInputs {
+    pub db_commitments: Vec<Fr>,
+    pub query_result: Option<Fr>,
+}
+
+impl PublicInputs {
+    /// Number of instance columns of the layout
+    pub const COLUMNS: usize = 2;
+
+    /// Commitment of a single committed database (or table) and query result
+    pub fn new(db_commitment: Fr, query_result: Fr) -> Self {
+        Self {
+            db_commitments: vec![db_commitment],
+            query_result: Some(query_result),
+        }
+    }
+
+    /// Commitment of the first (or only) committed table
+    pub fn db_commitment(&self) -> Option<Fr> {
+        self.db_commitments.first().copied()
+    }
+
+    /// Instance columns (`Vec<Vec<Fr>>`: column, then row)
+    pub fn to_instances(&self) -> Vec<Vec<Fr>> {
         vec![
-            vec![db_commitment], // Row 0: Database commitment
-            vec![query_result],  // Row 1: Query result
+            self.db_commitments.clone(),
+            self.query_result.into_iter().collect(),
         ]
     }
+
+    /// Read public inputs from instance columns
+    ///
+    /// # Note
+    ///
+    /// Column 0 may hold any number of table commitments. Besides the
+    /// current layout, the earlier single-column layout
+    /// (`vec![vec![db_commitment, query_result]]`, rows 0 and 1 of one
+    /// column) is accepted, so both convert to the same `PublicInputs`.
+    /// Anything else fails with `Error::InvalidInstances`.
+    pub fn from_instances(instances: &[Vec<Fr>]) -> Result<Self, Error> {
+        let at_most_one = |column: &Vec<Fr>| match column.as_slice() {
+            [] => Ok(None),
+            [value] => Ok(Some(*value)),
+            _ => Err(Error::InvalidInstances),
+        };
+        match instances {
+            [db_commitments, query_result] => Ok(Self {
+                db_commitments: db_commitments.clone(),
+                query_result: at_most_one(query_result)?,
+            }),
+            // Single instance column: row 0 commitment, row 1 result
+            [column] => match column.as_slice() {
+                [] => Ok(Self::default()),
+                [db_commitment] => Ok(Self {
+                    db_commitments: vec![*db_commitment],
+                    query_result: None,
+                }),
+                [db_commitment, query_result] => Ok(Self::new(*db_commitment, *query_result)),
+                _ => Err(Error::InvalidInstances),
+            },
+            _ => Err(Error::InvalidInstances),
+        }
+    }
 }
@@ -0,0 +1,195 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::arithmetic::ArithmeticChip;
+use super::config::{GateConfigs, PoneglyphConfig};
+use super::poseidon::{self, PoseidonChip};
+use super::window::WindowChip;
+
+/// Commitment Scheme
+/// A commitment to key-value pairs that `DatabaseCommitmentCircuit` can
+/// prove; implement it to plug in another scheme (Pedersen, KZG, ...)
+/// without changing the circuit
+///
+/// # Implementations
+///
+/// - `AdditiveCommitment`: the weighted sum of `HashScheme::Insecure`
+/// - `PoseidonCommitment`: the hash chain of `HashScheme::Poseidon`
+pub trait Commitment: Clone + Debug {
+    /// Commit to key-value pairs
+    fn commit(data: &[(u64, u64)]) -> Self;
+
+    /// Public commitment value (`instance` row 0 of the circuit)
+    fn value(&self) -> Fr;
+
+    /// Was this commitment computed from `data`?
+    fn verify(&self, data: &[(u64, u64)]) -> bool {
+        Self::commit(data).value() == self.value()
+    }
+
+    /// Recompute the commitment of the witnessed `data` in a circuit
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the commitment, which the circuit binds to the public
+    /// input
+    fn constrain(
+        layouter: impl Layouter<Fr>,
+        gates: &GateConfigs,
+        data: &[(u64, u64)],
+    ) -> Result<AssignedCell<Fr, Fr>, Error>;
+}
+
+/// Additive Commitment
+/// Weighted sum `sum(key * 10^6 + value)` (`HashScheme::Insecure`): fast,
+/// but not binding (reordered or offsetting pairs collide)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdditiveCommitment(pub Fr);
+
+impl AdditiveCommitment {
+    /// Weight of each key
+    pub const KEY_WEIGHT: u64 = 1_000_000;
+}
+
+impl Commitment for AdditiveCommitment {
+    fn commit(data: &[(u64, u64)]) -> Self {
+        let weight = Fr::from(Self::KEY_WEIGHT);
+        let sum = data.iter().fold(Fr::ZERO, |sum, &(key, value)| {
+            sum + Fr::from(key) * weight + Fr::from(value)
+        });
+        Self(sum)
+    }
+
+    fn value(&self) -> Fr {
+        self.0
+    }
+
+    /// Each key is scaled by the weight and each value by 1 (Arithmetic
+    /// Gate), and the terms are summed by the Window Gate's running sum
+    fn constrain(
+        mut layouter: impl Layouter<Fr>,
+        gates: &GateConfigs,
+        data: &[(u64, u64)],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let arithmetic_chip = ArithmeticChip::new(gates.arithmetic.clone());
+        let mut terms = Vec::with_capacity(2 * data.len());
+        for (i, &(key, value)) in data.iter().enumerate() {
+            terms.push(arithmetic_chip.scale(
+                layouter.namespace(|| format!("key_{}", i)),
+                Value::known(key),
+                Self::KEY_WEIGHT,
+            )?);
+            terms.push(arithmetic_chip.scale(
+                layouter.namespace(|| format!("value_{}", i)),
+                Value::known(value),
+                1,
+            )?);
+        }
+        WindowChip::new(gates.window.clone()).sum(layouter.namespace(|| "sum"), &terms)
+    }
+}
+
+/// Poseidon Commitment
+/// Poseidon hash chain of the pairs (`poseidon::hash_chain` over
+/// `len, key_0, value_0, ...`, `HashScheme::Poseidon`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoseidonCommitment(pub Fr);
+
+impl Commitment for PoseidonCommitment {
+    // The length is hashed first, so a prefix does not collide
+    fn commit(data: &[(u64, u64)]) -> Self {
+        Self(poseidon::hash_chain(
+            std::iter::once(data.len() as u64)
+                .chain(data.iter().flat_map(|&(key, value)| [key, value]))
+                .map(Fr::from),
+        ))
+    }
+
+    fn value(&self) -> Fr {
+        self.0
+    }
+
+    fn constrain(
+        layouter: impl Layouter<Fr>,
+        gates: &GateConfigs,
+        data: &[(u64, u64)],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let poseidon_chip = PoseidonChip::new(gates.poseidon.clone());
+        let (commitment, _) = poseidon_chip.commit_pairs(layouter, data)?;
+        Ok(commitment)
+    }
+}
+
+/// Database Commitment Circuit
+/// Proves a public commitment (`C`, by default a `DatabaseCommitment` with
+/// `HashScheme::Poseidon`) was computed from private key-value pairs,
+/// without revealing them
+///
+/// # Public Inputs
+///
+/// - `instance` (row 0): The database commitment (`Commitment::value`)
+///
+/// # Constraints
+///
+/// 1. **Hash**: The commitment is recomputed over the witnessed pairs
+///    (`Commitment::constrain`; for Poseidon, `hash_chain(len, key_0,
+///    value_0, ...)` with `PoseidonChip::commit_pairs`)
+/// 2. **Binding**: The hash equals the public commitment
+///
+/// # Note
+///
+/// The pair count is a circuit constant, so keys fit data of one length;
+/// a verifier builds them from `without_witnesses` of a circuit with as
+/// many pairs. `Pedersen` commitments have no circuit.
+#[derive(Clone, Debug)]
+pub struct DatabaseCommitmentCircuit<C: Commitment = PoseidonCommitment> {
+    /// Committed key-value pairs (private)
+    pub data: Vec<(u64, u64)>,
+    /// Commitment scheme
+    pub scheme: PhantomData<C>,
+}
+
+impl<C: Commitment> DatabaseCommitmentCircuit<C> {
+    /// Circuit proving the `C` commitment of `data`
+    pub fn new(data: Vec<(u64, u64)>) -> Self {
+        Self {
+            data,
+            scheme: PhantomData,
+        }
+    }
+}
+
+impl<C: Commitment> Circuit<Fr> for DatabaseCommitmentCircuit<C> {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(vec![(0, 0); self.data.len()])
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let commitment = C::constrain(
+            layouter.namespace(|| "database commitment"),
+            &gates,
+            &self.data,
+        )?;
+        layouter.constrain_instance(commitment.cell(), config.instance, 0)?;
+
+        Ok(())
+    }
+}
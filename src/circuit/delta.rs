@@ -0,0 +1,195 @@
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::{GateConfigs, PoneglyphConfig};
+use super::merkle::{self, MerkleChip, MerklePath};
+use super::poseidon::PoseidonChip;
+use super::window::WindowChip;
+
+/// Row Update
+/// One changed row of a Merkle-committed table (`MerkleTree::from_rows`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowUpdate {
+    /// Cells of the row before the update, in table column order
+    pub old_row: Vec<u64>,
+    /// Cells of the row after the update
+    pub new_row: Vec<u64>,
+    /// Path of the row's leaf when the update is applied (an update does not
+    /// change its own siblings, see `MerkleTree::update`)
+    pub path: MerklePath,
+}
+
+/// Delta Circuit
+/// Proves that updating rows of a Merkle-committed table moves its root
+/// from the old to the new commitment, and its SUM over `column` from
+/// `old_aggregate` to `old_aggregate - removed + added`, without the
+/// unchanged rows
+///
+/// # Public Inputs
+///
+/// - `instance` (rows 0, 1): Merkle roots before and after the updates
+/// - `result_instance` (rows 0, 1): SUM before and after the updates
+///
+/// # Constraints
+///
+/// 1. **Commitment update**: each old row's leaf and its new row's leaf
+///    reach consecutive roots along the same path (Merkle Gate and Poseidon
+///    Gate); the updates are applied in order, chaining the roots from the
+///    old to the new commitment
+/// 2. **Aggregate update**: `new + Σ old_row[column] = old + Σ new_row[column]`,
+///    both sides summed by the Window Gate over copies of the row cells
+///
+/// # Note
+///
+/// The old aggregate is the result of a previous proof over the old table
+/// (e.g. `prover::assert_aggregate`); this circuit proves only the step, so
+/// its cost grows with the number of changed rows and the log of the table
+/// size instead of the row count.
+#[derive(Clone, Debug)]
+pub struct DeltaCircuit {
+    /// Changed rows, in the order they are applied
+    pub updates: Vec<RowUpdate>,
+    /// Column summed by the aggregate
+    pub column: usize,
+    /// SUM over `column` before the updates
+    pub old_aggregate: u64,
+}
+
+impl DeltaCircuit {
+    /// SUM over `column` after the updates (None if a row lacks the column,
+    /// or the SUM leaves the u64 range)
+    pub fn new_aggregate(&self) -> Option<u64> {
+        self.updates
+            .iter()
+            .try_fold(self.old_aggregate, |sum, update| {
+                let removed = *update.old_row.get(self.column)?;
+                let added = *update.new_row.get(self.column)?;
+                sum.checked_sub(removed)?.checked_add(added)
+            })
+    }
+
+    /// Public inputs: `[[old root, new root], [old aggregate, new aggregate]]`
+    /// (None without updates, or if `new_aggregate` is None)
+    pub fn instances(&self) -> Option<Vec<Vec<Fr>>> {
+        let first = self.updates.first()?;
+        let last = self.updates.last()?;
+        let old_root = first.path.root(merkle::leaf_hash(&first.old_row));
+        let new_root = last.path.root(merkle::leaf_hash(&last.new_row));
+        Some(vec![
+            vec![old_root, new_root],
+            vec![
+                Fr::from(self.old_aggregate),
+                Fr::from(self.new_aggregate()?),
+            ],
+        ])
+    }
+}
+
+impl Circuit<Fr> for DeltaCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            updates: self
+                .updates
+                .iter()
+                .map(|update| RowUpdate {
+                    old_row: vec![0; update.old_row.len()],
+                    new_row: vec![0; update.new_row.len()],
+                    path: MerklePath {
+                        index: 0,
+                        siblings: vec![Fr::ZERO; update.path.siblings.len()],
+                    },
+                })
+                .collect(),
+            column: self.column,
+            old_aggregate: 0,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let poseidon_chip = PoseidonChip::new(gates.poseidon);
+        let merkle_chip = MerkleChip::new(gates.merkle);
+        let window_chip = WindowChip::new(gates.window);
+
+        // Public aggregates as cells
+        let (old_aggregate, new_aggregate) = layouter.assign_region(
+            || "aggregates",
+            |mut region| {
+                let old = region.assign_advice_from_instance(
+                    || "old aggregate",
+                    config.result_instance,
+                    0,
+                    config.advice[0],
+                    0,
+                )?;
+                let new = region.assign_advice_from_instance(
+                    || "new aggregate",
+                    config.result_instance,
+                    1,
+                    config.advice[0],
+                    1,
+                )?;
+                Ok((old, new))
+            },
+        )?;
+
+        // Commitment update: each update starts from the previous one's root
+        let mut removed = vec![new_aggregate];
+        let mut added = vec![old_aggregate];
+        let mut roots = None;
+        for (i, update) in self.updates.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("updates[{}]", i));
+            let (old_leaf, old_cells) =
+                poseidon_chip.hash_row(layouter.namespace(|| "old row"), &update.old_row)?;
+            let (new_leaf, new_cells) =
+                poseidon_chip.hash_row(layouter.namespace(|| "new row"), &update.new_row)?;
+            removed.push(old_cells.get(self.column).ok_or(Error::Synthesis)?.clone());
+            added.push(new_cells.get(self.column).ok_or(Error::Synthesis)?.clone());
+
+            let (old_root, new_root) = merkle_chip.verify_update(
+                layouter.namespace(|| "merkle update"),
+                &poseidon_chip,
+                &old_leaf,
+                &new_leaf,
+                &update.path,
+            )?;
+            roots = match roots {
+                None => Some((old_root, new_root)),
+                Some((first, previous)) => {
+                    layouter.assign_region(
+                        || "chained root",
+                        |mut region| region.constrain_equal(previous.cell(), old_root.cell()),
+                    )?;
+                    Some((first, new_root))
+                }
+            };
+        }
+        let (old_root, new_root) = roots.ok_or(Error::Synthesis)?;
+        layouter.constrain_instance(old_root.cell(), config.instance, 0)?;
+        layouter.constrain_instance(new_root.cell(), config.instance, 1)?;
+
+        // Aggregate update: new + removed = old + added
+        let left = window_chip.sum(layouter.namespace(|| "new plus removed"), &removed)?;
+        let right = window_chip.sum(layouter.namespace(|| "old plus added"), &added)?;
+        layouter.assign_region(
+            || "aggregate update",
+            |mut region| region.constrain_equal(left.cell(), right.cell()),
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use ff::Field;
 use halo2_proofs::{
-    circuit::{AssignedCell, Layouter, Value},
+    circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
     poly::Rotation,
 };
@@ -15,21 +15,22 @@ use super::range_check::RangeCheckConfig;
 /// # Column Allocation
 ///
 /// - `group_key_column`: For group key values (advice[5])
-/// - `boundary_column`: For boundary flags (advice[6]) - b = 1 means new group start
+/// - `boundary_column`: For boundary flags (advice[6]) - b = 0 means new group start
 /// - `inverse_column`: For inverse value (advice[7]) - p = 1/(v₁ - v₂) if v₁ ≠ v₂, else p = 0
 ///
 /// # Note
 ///
-/// Group-By Gate uses Sort Gate's output. Group keys must be sorted.
-/// According to Paper Section 4.3, sorting is done first with Sort Gate, then
-/// boundary check is done with Group-By Gate.
+/// The boundary check depends only on key equality, not on order: keys may
+/// come in any order (ascending, descending, by hash) as long as equal keys
+/// are adjacent. According to Paper Section 4.3, the Sort Gate runs first,
+/// which is one way of making equal keys adjacent.
 #[derive(Clone, Debug)]
 pub struct GroupByConfig {
     // Advice column for group key values
     // advice[5] - shared with Range Check chunk[5]
     pub group_key_column: Column<Advice>,
 
-    // Advice column for boundary flags (b = 1 means new group start)
+    // Advice column for boundary flags (b = 0 means new group start)
     // advice[6] - shared with Range Check chunk[6]
     pub boundary_column: Column<Advice>,
 
@@ -40,6 +41,10 @@ pub struct GroupByConfig {
     // Selector for boundary check
     pub boundary_selector: Selector,
 
+    // Selector for group count (count_next = count + 1 - b)
+    // Uses group_key_column for the running count and boundary_column for copied b
+    pub count_selector: Selector,
+
     // Range Check integration (for additional validation - currently unused)
     pub range_check_config: RangeCheckConfig,
 }
@@ -71,7 +76,7 @@ impl GroupByChip {
         // - advice[2-4]: Sort Gate (input, output, diff) - shared with Range Check
         // - advice[5-7]: Group-By Gate (key, boundary, inverse) - shared with Range Check
         //
-        // Note: Group-By Gate uses Sort Gate's output. Group keys must be sorted.
+        // Note: Group-By Gate only needs equal keys adjacent (e.g. Sort Gate output)
         let group_key_column = config.advice[5];
         let boundary_column = config.advice[6];
         let inverse_column = config.advice[7];
@@ -84,7 +89,7 @@ impl GroupByChip {
         //
         // This constraint verifies group boundaries
         // v₁ = group_key[i], v₂ = group_key[i+1]
-        // b = 1 means new group has started
+        // b = 0 means a new group has started (only equality matters, not order)
         //
         // Inverse calculation: p = 1/(v₁ - v₂) if v₁ ≠ v₂, else p = 0
         // p value will be calculated in witness and assigned to inverse_column
@@ -115,11 +120,24 @@ impl GroupByChip {
             ]
         });
 
+        // Group count: each pair with b = 0 (next key differs) starts a new group
+        // count[i+1] = count[i] + 1 - b[i], with b[i] copied from the boundary cells
+        let count_selector = meta.selector();
+        meta.create_gate("group count", |meta| {
+            let s = meta.query_selector(count_selector);
+            let count = meta.query_advice(group_key_column, Rotation::cur());
+            let count_next = meta.query_advice(group_key_column, Rotation::next());
+            let b = meta.query_advice(boundary_column, Rotation::cur());
+
+            vec![s * (count_next - count - Expression::Constant(Fr::ONE) + b)]
+        });
+
         GroupByConfig {
             group_key_column,
             boundary_column,
             inverse_column,
             boundary_selector,
+            count_selector,
             range_check_config: range_check_config.clone(),
         }
     }
@@ -129,21 +147,48 @@ impl GroupByChip {
     ///
     /// # Requirements
     ///
-    /// - `group_keys` must be sorted (Sort Gate output)
+    /// - Equal keys in `group_keys` must be adjacent; they need not be sorted
     /// - Group keys must be assigned in consecutive rows
     ///
     /// # Boundary Check Logic
     ///
-    /// - `b = 1`: New group has started (v₁ = v₂)
-    /// - `b = 0`: Same group continues (v₁ ≠ v₂)
+    /// - `b = 1`: Same group continues (v₁ = v₂)
+    /// - `b = 0`: New group has started (v₁ ≠ v₂)
     ///
     /// # Return Value
     ///
     /// List of boundary cells (one boundary for each consecutive pair)
     pub fn group_and_verify(
+        &self,
+        layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.assign_groups(layouter, group_keys, None)
+    }
+
+    /// Assign group keys copied from assigned cells and verify boundaries
+    ///
+    /// Same as `group_and_verify`, with each key row copy-constrained to its
+    /// cell in `key_cells` (e.g. the output cells of the Sort Gate that put
+    /// the rows in GROUP BY order)
+    pub fn group_and_verify_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        group_keys: &[u64],
+        key_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if key_cells.len() != group_keys.len() {
+            return Err(Error::Synthesis);
+        }
+        self.assign_groups(layouter, group_keys, Some(key_cells))
+    }
+
+    /// Shared implementation of `group_and_verify` and `group_and_verify_copied`
+    fn assign_groups(
         &self,
         mut layouter: impl Layouter<Fr>,
         group_keys: &[u64],
+        key_cells: Option<&[AssignedCell<Fr, Fr>]>,
     ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
         // Assign group keys and boundaries in the same region
         // Since constraints use Rotation::cur() and Rotation::next(),
@@ -158,15 +203,24 @@ impl GroupByChip {
                     return Ok(boundary_cells);
                 }
 
+                // Assign group key i (copied from its cell, if any)
+                let assign_key = |region: &mut Region<'_, Fr>, i: usize| {
+                    let cell = region.assign_advice(
+                        || format!("group_key_{}", i),
+                        self.config.group_key_column,
+                        i,
+                        || Value::known(Fr::from(group_keys[i])),
+                    )?;
+                    if let Some(cells) = key_cells {
+                        region.constrain_equal(cells[i].cell(), cell.cell())?;
+                    }
+                    Ok::<_, Error>(())
+                };
+
                 // For single element
                 if group_keys.len() == 1 {
                     // Assign group key
-                    region.assign_advice(
-                        || "group_key_0",
-                        self.config.group_key_column,
-                        0,
-                        || Value::known(Fr::from(group_keys[0])),
-                    )?;
+                    assign_key(&mut region, 0)?;
 
                     let boundary_cell = region.assign_advice(
                         || "boundary_0",
@@ -185,13 +239,8 @@ impl GroupByChip {
                 }
 
                 // Assign group keys
-                for (i, key) in group_keys.iter().enumerate() {
-                    region.assign_advice(
-                        || format!("group_key_{}", i),
-                        self.config.group_key_column,
-                        i,
-                        || Value::known(Fr::from(*key)),
-                    )?;
+                for i in 0..group_keys.len() {
+                    assign_key(&mut region, i)?;
                 }
 
                 // Calculate and assign boundary for each consecutive pair
@@ -202,32 +251,15 @@ impl GroupByChip {
 
                     // Paper formula: b = 1 - (v₁ - v₂) × p
                     // p = 1/(v₁ - v₂) if v₁ ≠ v₂, else p = 0
-                    let diff = v2 as i64 - v1 as i64;
-
-                    let (boundary, inverse) = if diff == 0 {
-                        // v₁ = v₂: p = 0, b = 1 (new group has started)
-                        // Paper formula: b = 1 - (v₁ - v₂) × p = 1 - 0 × 0 = 1
+                    // The difference is taken in the field, so it is nonzero
+                    // exactly when the keys differ, whichever is larger
+                    let diff = Fr::from(v2) - Fr::from(v1);
+                    let (boundary, inverse) = if v1 == v2 {
+                        // v₁ = v₂: p = 0, b = 1 (same group continues)
                         (Fr::ONE, Fr::ZERO)
                     } else {
-                        // v₁ ≠ v₂: p = 1/(v₁ - v₂), b = 0 (same group continues)
-                        // Calculate diff as field element
-                        let diff_field = if diff > 0 {
-                            Fr::from(diff as u64)
-                        } else {
-                            // Negative diff: negative value in field
-                            // Note: Since group keys are sorted, diff should generally be >= 0
-                            // But we handle negative values for field arithmetic
-                            let abs_diff = (-diff) as u64;
-                            -Fr::from(abs_diff)
-                        };
-
-                        // Calculate inverse: p = 1/(v₁ - v₂)
-                        // Note: Since diff_field ≠ 0, invert() should succeed
-                        // But we use unwrap_or(Fr::ZERO) for safety
-                        // If invert() fails (very rare), p = 0
-                        // In this case, constraints will error
-                        let inv = diff_field.invert().unwrap_or(Fr::ZERO);
-                        (Fr::ZERO, inv)
+                        // v₁ ≠ v₂: p = 1/(v₁ - v₂), b = 0 (new group starts)
+                        (Fr::ZERO, diff.invert().unwrap())
                     };
 
                     let boundary_cell = region.assign_advice(
@@ -254,4 +286,73 @@ impl GroupByChip {
             },
         )
     }
+
+    /// Number of distinct groups, summed from the boundary cells
+    ///
+    /// # Parameters
+    ///
+    /// - `num_keys`: Number of group keys passed to `group_and_verify`
+    /// - `boundary_cells`: Its returned boundary cells (b = 0 where the next
+    ///   key differs, i.e. a new group starts)
+    ///
+    /// # Row Layout
+    ///
+    /// - Row 0: count = 1 (constant), b₀ (copy)
+    /// - Row i: count = count[i-1] + 1 - b[i-1], b[i] (copy)
+    ///
+    /// # Returns
+    ///
+    /// Group count cell (a constant for fewer than 2 keys), or
+    /// `Error::Synthesis` if the boundary cells do not match `num_keys`
+    ///
+    /// # Note
+    ///
+    /// The count is the number of distinct keys only if equal keys are
+    /// adjacent (e.g. proven sorted by the Sort Gate); otherwise a key split
+    /// across runs is counted once per run.
+    pub fn count_groups(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        num_keys: usize,
+        boundary_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        // A single key's boundary cell is a placeholder (no pair to compare)
+        let pairs = num_keys.saturating_sub(1);
+        if num_keys > 1 && boundary_cells.len() != pairs {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "group count",
+            |mut region| {
+                let mut count_cell = region.assign_advice_from_constant(
+                    || "count_0",
+                    self.config.group_key_column,
+                    0,
+                    Fr::from(num_keys.min(1) as u64),
+                )?;
+
+                for (i, boundary_cell) in boundary_cells.iter().take(pairs).enumerate() {
+                    let b = boundary_cell.copy_advice(
+                        || format!("boundary_{}", i),
+                        &mut region,
+                        self.config.boundary_column,
+                        i,
+                    )?;
+                    self.config.count_selector.enable(&mut region, i)?;
+
+                    let count =
+                        count_cell.value().copied() + Value::known(Fr::ONE) - b.value().copied();
+                    count_cell = region.assign_advice(
+                        || format!("count_{}", i + 1),
+                        self.config.group_key_column,
+                        i + 1,
+                        || count,
+                    )?;
+                }
+
+                Ok(count_cell)
+            },
+        )
+    }
 }
@@ -36,6 +36,22 @@ use super::sort::SortConfig;
 /// 
 /// - Join Gate uses Sort Gate output. Tables are sorted and verified with Sort Gate.
 /// - Deduplication verification is done in `join_and_verify` using Sort Gate.
+/// 
+/// # Semi-Join (EXISTS / NOT EXISTS)
+/// 
+/// `semi_join_and_verify` reuses the Join columns without right-side values:
+/// 
+/// - `order_column`: Merged sort value `2 * key + 1 - tag` (advice[9])
+/// - `table1_key_column`: key, `table1_value_column`: tag (1 = right key, 0 = left key)
+/// - `table2_key_column`: last right key + 1 so far (0 = none)
+/// - `table2_value_column`: inverse of `key + 1 - last`, `match_column`: match flag
+/// - `negated_column`: 1 for `NOT EXISTS`, a circuit constant (advice[7]),
+///   `kept_column`: keep flag of a left row (advice[8])
+/// 
+/// Left and right keys are sorted together by the sort value, so a right key
+/// precedes left keys equal to it. A left row matches iff the last right key
+/// before it equals its key, proven in both directions with the inverse.
+/// A left row is kept iff it matches (`EXISTS`) or does not (`NOT EXISTS`).
 #[derive(Clone, Debug)]
 pub struct JoinConfig {
     // Table 1 columns
@@ -54,9 +70,21 @@ pub struct JoinConfig {
     // advice[14] - reserved for Join
     pub match_column: Column<Advice>,
     
+    // Merged sort value column for semi-joins
+    // advice[9] - shared with Range Check diff / Aggregation result (different rows)
+    pub order_column: Column<Advice>,
+    
+    // Semi-join negation (constant) and keep flag columns
+    // advice[7-8] - shared with Range Check (different rows)
+    pub negated_column: Column<Advice>,
+    pub kept_column: Column<Advice>,
+    
     // Selectors
     pub join_selector: Selector,
     pub deduplication_selector: Selector,
+    // Semi-join: first merged row (no previous row) and the rest
+    pub semi_join_start_selector: Selector,
+    pub semi_join_selector: Selector,
     
     // Dependencies
     pub range_check_config: RangeCheckConfig,
@@ -99,10 +127,15 @@ impl JoinChip {
         let table2_key_column = config.advice[12];
         let table2_value_column = config.advice[13];
         let match_column = config.advice[14];
+        let order_column = config.advice[9];
+        let negated_column = config.advice[7];
+        let kept_column = config.advice[8];
         
         // Create selectors
         let join_selector = meta.selector();
         let deduplication_selector = meta.selector();
+        let semi_join_start_selector = meta.selector();
+        let semi_join_selector = meta.selector();
         
         // Key comparison constraint
         // Paper Section 4.4: Primary Key - Foreign Key verification
@@ -166,14 +199,77 @@ impl JoinChip {
             vec![s * Expression::Constant(Fr::ZERO)]
         });
         
+        // Semi-join constraints on one merged row
+        // `before`: last right key + 1 before this row (0 at the first row)
+        // 
+        // 1. tag * (1 - tag) = 0
+        // 2. order = 2 * key + 1 - tag (ties put right keys first)
+        // 3. last = tag * (key + 1) + (1 - tag) * before
+        // 4. Left row, with d = key + 1 - before:
+        //    match = 1 - d * inverse and d * match = 0 (match = 1 iff d = 0)
+        // 5. Right row: match = 0
+        // 6. Left row: kept = match, or 1 - match if negated
+        //    (kept = negated + match - 2 * negated * match)
+        let semi_join_constraints = |meta: &mut halo2_proofs::plonk::VirtualCells<'_, Fr>,
+                                     before: Expression<Fr>| {
+            let one = Expression::Constant(Fr::ONE);
+            let order = meta.query_advice(order_column, Rotation::cur());
+            let key = meta.query_advice(table1_key_column, Rotation::cur());
+            let tag = meta.query_advice(table1_value_column, Rotation::cur());
+            let last = meta.query_advice(table2_key_column, Rotation::cur());
+            let inverse = meta.query_advice(table2_value_column, Rotation::cur());
+            let match_flag = meta.query_advice(match_column, Rotation::cur());
+            let negated = meta.query_advice(negated_column, Rotation::cur());
+            let kept = meta.query_advice(kept_column, Rotation::cur());
+            
+            let left = one.clone() - tag.clone();
+            let key_plus_one = key.clone() + one.clone();
+            let d = key_plus_one.clone() - before.clone();
+            let keep = negated.clone() + match_flag.clone()
+                - Expression::Constant(Fr::from(2)) * negated * match_flag.clone();
+            
+            vec![
+                tag.clone() * left.clone(),
+                order - (key.clone() + key + left.clone()),
+                last - (tag.clone() * key_plus_one + left.clone() * before),
+                left.clone() * (match_flag.clone() + d.clone() * inverse - one),
+                left.clone() * d * match_flag.clone(),
+                tag * match_flag,
+                left * (kept - keep),
+            ]
+        };
+        
+        meta.create_gate("semi join start", |meta| {
+            let s = meta.query_selector(semi_join_start_selector);
+            semi_join_constraints(meta, Expression::Constant(Fr::ZERO))
+                .into_iter()
+                .map(|constraint| s.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+        
+        // Note: Selector will not be enabled for the first row (no Rotation::prev())
+        meta.create_gate("semi join", |meta| {
+            let s = meta.query_selector(semi_join_selector);
+            let before = meta.query_advice(table2_key_column, Rotation::prev());
+            semi_join_constraints(meta, before)
+                .into_iter()
+                .map(|constraint| s.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+        
         JoinConfig {
             table1_key_column,
             table1_value_column,
             table2_key_column,
             table2_value_column,
             match_column,
+            order_column,
+            negated_column,
+            kept_column,
             join_selector,
             deduplication_selector,
+            semi_join_start_selector,
+            semi_join_selector,
             range_check_config: range_check_config.clone(),
             sort_config: sort_config.clone(),
         }
@@ -202,12 +298,70 @@ impl JoinChip {
     /// 
     /// List of match cells (one match_flag for each row)
     pub fn join_and_verify(
+        &self,
+        layouter: impl Layouter<Fr>,
+        table1_keys: &[u64],
+        table1_values: &[u64],
+        table2_keys: &[u64],
+        table2_values: &[u64],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.assign_join(
+            layouter,
+            table1_keys,
+            table1_values,
+            table2_keys,
+            table2_values,
+            None,
+        )
+    }
+    
+    /// Join and verify, with the join inputs tied to their source cells
+    /// (e.g. committed database columns)
+    /// 
+    /// Same as `join_and_verify`; each assigned key and value is constrained
+    /// equal to its cell in `committed` (table1 keys, table1 values, table2
+    /// keys, table2 values), so inputs differing from the committed data fail
+    /// 
+    /// # Return Value
+    /// 
+    /// List of match cells, or `Error::Synthesis` if a committed column and
+    /// its input differ in length
+    pub fn join_and_verify_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        table1_keys: &[u64],
+        table1_values: &[u64],
+        table2_keys: &[u64],
+        table2_values: &[u64],
+        committed: [&[AssignedCell<Fr, Fr>]; 4],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let inputs = [table1_keys, table1_values, table2_keys, table2_values];
+        if inputs
+            .iter()
+            .zip(committed)
+            .any(|(input, cells)| input.len() != cells.len())
+        {
+            return Err(Error::Synthesis);
+        }
+        self.assign_join(
+            layouter,
+            table1_keys,
+            table1_values,
+            table2_keys,
+            table2_values,
+            Some(committed),
+        )
+    }
+    
+    /// Shared implementation of `join_and_verify` and `join_and_verify_copied`
+    fn assign_join(
         &self,
         mut layouter: impl Layouter<Fr>,
         table1_keys: &[u64],
         table1_values: &[u64],
         table2_keys: &[u64],
         table2_values: &[u64],
+        committed: Option<[&[AssignedCell<Fr, Fr>]; 4]>,
     ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
         // 1. Sort and verify tables with Sort Gate
         // Paper Section 4.4: Sorting required before join
@@ -250,6 +404,7 @@ impl JoinChip {
             table1_values,
             table2_keys,
             table2_values,
+            committed,
         )?;
         
         // 3. Deduplication: Verify that T_miss records are disjoint
@@ -271,6 +426,154 @@ impl JoinChip {
         Ok(match_cells)
     }
     
+    /// Semi-join and verify (EXISTS / NOT EXISTS)
+    /// 
+    /// Proves a match flag for every left key: 1 if some right key is equal,
+    /// 0 otherwise. Both outcomes are constrained, so the flags can back
+    /// `EXISTS` (keep flag 1) as well as `NOT EXISTS` (keep flag 0): each
+    /// left row's keep flag is constrained to its match flag, or to its
+    /// complement when `negated` (fixed by a constant).
+    /// 
+    /// # Algorithm
+    /// 
+    /// 1. Merge the keys as sort values `2 * key + 1 - tag` (right keys: tag = 1)
+    ///    and sort them with the Sort Gate
+    /// 2. Walk the sorted values in one region, carrying the last right key
+    /// 3. A left row matches iff the last right key equals its key
+    /// 
+    /// # Requirements
+    /// 
+    /// - Keys must be below 2^63 (so that sort values fit in 64 bits)
+    /// 
+    /// # Return Value
+    /// 
+    /// Keep flag cells of the left keys, in sorted key order
+    pub fn semi_join_and_verify(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        left_keys: &[u64],
+        right_keys: &[u64],
+        negated: bool,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if left_keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        if left_keys
+            .iter()
+            .chain(right_keys)
+            .any(|&key| key >= 1 << 63)
+        {
+            return Err(Error::Synthesis);
+        }
+        
+        // 1. Merged sort values (right keys first among equal keys)
+        let merged: Vec<u64> = left_keys
+            .iter()
+            .map(|&key| 2 * key + 1)
+            .chain(right_keys.iter().map(|&key| 2 * key))
+            .collect();
+        let mut sorted = merged.clone();
+        sorted.sort();
+        
+        let sort_chip = super::sort::SortChip::new(self.config.sort_config.clone());
+        let sorted_cells = sort_chip.sort_and_verify(
+            layouter.namespace(|| "sort semi join keys"),
+            merged.iter().map(|&v| Value::known(v)).collect(),
+            sorted.clone(),
+        )?;
+        
+        // 2. Walk the merged rows
+        layouter.assign_region(
+            || "semi join",
+            |mut region| {
+                let mut kept_cells = Vec::with_capacity(left_keys.len());
+                // Last right key + 1 (0 = no right key yet)
+                let mut last = 0u64;
+                
+                for (row, (&value, cell)) in sorted.iter().zip(&sorted_cells).enumerate() {
+                    if row == 0 {
+                        self.config
+                            .semi_join_start_selector
+                            .enable(&mut region, row)?;
+                    } else {
+                        self.config.semi_join_selector.enable(&mut region, row)?;
+                    }
+                    
+                    let key = value / 2;
+                    let is_right = value % 2 == 0;
+                    // Inverse of d = key + 1 - last (left rows only)
+                    let inverse = if is_right {
+                        Fr::ZERO
+                    } else {
+                        (Fr::from(key + 1) - Fr::from(last))
+                            .invert()
+                            .unwrap_or(Fr::ZERO)
+                    };
+                    if is_right {
+                        last = key + 1;
+                    }
+                    let matched = !is_right && last == key + 1;
+                    
+                    // Sort value bound to the Sort Gate output
+                    cell.copy_advice(
+                        || format!("order_{}", row),
+                        &mut region,
+                        self.config.order_column,
+                        row,
+                    )?;
+                    region.assign_advice(
+                        || format!("key_{}", row),
+                        self.config.table1_key_column,
+                        row,
+                        || Value::known(Fr::from(key)),
+                    )?;
+                    region.assign_advice(
+                        || format!("tag_{}", row),
+                        self.config.table1_value_column,
+                        row,
+                        || Value::known(Fr::from(is_right as u64)),
+                    )?;
+                    region.assign_advice(
+                        || format!("last_{}", row),
+                        self.config.table2_key_column,
+                        row,
+                        || Value::known(Fr::from(last)),
+                    )?;
+                    region.assign_advice(
+                        || format!("inverse_{}", row),
+                        self.config.table2_value_column,
+                        row,
+                        || Value::known(inverse),
+                    )?;
+                    region.assign_advice(
+                        || format!("match_{}", row),
+                        self.config.match_column,
+                        row,
+                        || Value::known(Fr::from(matched as u64)),
+                    )?;
+                    region.assign_advice_from_constant(
+                        || format!("negated_{}", row),
+                        self.config.negated_column,
+                        row,
+                        Fr::from(negated as u64),
+                    )?;
+                    let kept_cell = region.assign_advice(
+                        || format!("kept_{}", row),
+                        self.config.kept_column,
+                        row,
+                        || Value::known(Fr::from((matched != negated && !is_right) as u64)),
+                    )?;
+                    
+                    if !is_right {
+                        kept_cells.push(kept_cell);
+                    }
+                }
+                
+                Ok(kept_cells)
+            },
+        )
+    }
+    
     /// Deduplication verification: Prove that T_miss records are disjoint
     /// Paper Section 4.4: T_miss records should not match with records in the other table
     /// 
@@ -369,6 +672,8 @@ impl JoinChip {
     ///   (to ensure correct row alignment for Rotation::cur())
     /// - Constraints are only enabled when there are records in both tables
     /// - Padding (0) is used for empty records
+    /// - Assigned inputs are constrained equal to their `committed` cells
+    ///   (padding rows have none)
     fn assign_join_with_constraints(
         &self,
         mut layouter: impl Layouter<Fr>,
@@ -376,6 +681,7 @@ impl JoinChip {
         table1_values: &[u64],
         table2_keys: &[u64],
         table2_values: &[u64],
+        committed: Option<[&[AssignedCell<Fr, Fr>]; 4]>,
     ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
         layouter.assign_region(
             || "assign join",
@@ -401,14 +707,14 @@ impl JoinChip {
                         0
                     };
                     
-                    region.assign_advice(
+                    let table1_key_cell = region.assign_advice(
                         || format!("table1_key_{}", i),
                         self.config.table1_key_column,
                         i,
                         || Value::known(Fr::from(key1)),
                     )?;
                     
-                    region.assign_advice(
+                    let table1_value_cell = region.assign_advice(
                         || format!("table1_value_{}", i),
                         self.config.table1_value_column,
                         i,
@@ -427,20 +733,34 @@ impl JoinChip {
                         0
                     };
                     
-                    region.assign_advice(
+                    let table2_key_cell = region.assign_advice(
                         || format!("table2_key_{}", i),
                         self.config.table2_key_column,
                         i,
                         || Value::known(Fr::from(key2)),
                     )?;
                     
-                    region.assign_advice(
+                    let table2_value_cell = region.assign_advice(
                         || format!("table2_value_{}", i),
                         self.config.table2_value_column,
                         i,
                         || Value::known(Fr::from(value2)),
                     )?;
                     
+                    if let Some(committed) = committed {
+                        let assigned = [
+                            table1_key_cell,
+                            table1_value_cell,
+                            table2_key_cell,
+                            table2_value_cell,
+                        ];
+                        for (cell, sources) in assigned.iter().zip(committed) {
+                            if let Some(source) = sources.get(i) {
+                                region.constrain_equal(cell.cell(), source.cell())?;
+                            }
+                        }
+                    }
+                    
                     // Calculate match flag
                     // If i < min(len1, len2) and key1[i] == key2[i] then match = 1
                     let match_flag = if i < table1_keys.len() && i < table2_keys.len() {
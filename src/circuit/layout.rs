@@ -0,0 +1,51 @@
+// Circuit layout rendering (`dev-graph` feature)
+// Draws the region / column layout with halo2's `CircuitLayout`
+
+use std::path::Path;
+
+use halo2_proofs::dev::CircuitLayout;
+use plotters::prelude::*;
+
+use super::PoneglyphCircuit;
+
+/// Size of the rendered layout in pixels
+const LAYOUT_SIZE: (u32, u32) = (1024, 768);
+
+impl PoneglyphCircuit {
+    /// Render the region / column layout of this circuit to an SVG file
+    ///
+    /// Columns run left to right (instance white, advice red, fixed blue,
+    /// selectors light blue), rows top to bottom over all 2^k rows; each
+    /// region is a box labelled with its region name (e.g. `check x < t`),
+    /// equality-constrained cells are marked, and the unusable (blinding)
+    /// rows at the bottom are shaded. Columns shared between the Range
+    /// Check, Sort, Group-By and Aggregation Gates show up as regions of
+    /// different gates stacked in the same columns.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: SVG file to write
+    /// - `k`: Circuit size (2^k rows); must fit the circuit's rows
+    ///
+    /// # Note
+    ///
+    /// `CircuitLayout` panics if the circuit's synthesis fails (e.g. k too
+    /// small); `stats().min_k` is the smallest k that fits.
+    pub fn write_layout(&self, path: impl AsRef<Path>, k: u32) -> Result<(), String> {
+        let root = SVGBackend::new(path.as_ref(), LAYOUT_SIZE).into_drawing_area();
+        root.fill(&WHITE)
+            .map_err(|e| format!("Failed to draw layout: {}", e))?;
+        CircuitLayout::default()
+            .show_labels(true)
+            .mark_equality_cells(true)
+            .render(k, self, &root)
+            .map_err(|e| format!("Failed to draw layout: {}", e))?;
+        root.present().map_err(|e| {
+            format!(
+                "Failed to write layout to {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })
+    }
+}
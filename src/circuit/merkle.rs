@@ -0,0 +1,375 @@
+use ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::PoneglyphConfig;
+use super::poseidon::{self, PoseidonChip};
+
+type Cell = AssignedCell<Fr, Fr>;
+
+/// Hash of a table row: `hash_chain(column count, cells...)`
+/// (same result as `PoseidonChip::hash_row`)
+pub fn leaf_hash(row: &[u64]) -> Fr {
+    poseidon::hash_chain(
+        std::iter::once(row.len() as u64)
+            .chain(row.iter().copied())
+            .map(Fr::from),
+    )
+}
+
+/// Merkle Tree
+/// Poseidon Merkle tree over row hashes (`leaf_hash`), padded with zero
+/// leaves to a power of two; parent = `hash_two(left, right)`
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// Node levels, leaves first; the last level holds the root
+    levels: Vec<Vec<Fr>>,
+}
+
+impl MerkleTree {
+    /// Tree over the given leaves (the root of an empty tree is 0)
+    pub fn new(mut leaves: Vec<Fr>) -> Self {
+        leaves.resize(leaves.len().next_power_of_two(), Fr::ZERO);
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| poseidon::hash_two(pair[0], pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Tree over the rows of a table (one `leaf_hash` per row)
+    pub fn from_rows(rows: &[Vec<u64>]) -> Self {
+        Self::new(rows.iter().map(|row| leaf_hash(row)).collect())
+    }
+
+    /// Root: the public commitment of the tree
+    pub fn root(&self) -> Fr {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Path from leaf `index` to the root (None if out of range)
+    pub fn path(&self, index: usize) -> Option<MerklePath> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect();
+        Some(MerklePath { index, siblings })
+    }
+
+    /// Replace leaf `index` and recompute its ancestors
+    ///
+    /// # Returns
+    ///
+    /// The leaf's path, the same before and after the update (None if out
+    /// of range)
+    pub fn update(&mut self, index: usize, leaf: Fr) -> Option<MerklePath> {
+        let path = self.path(index)?;
+        self.levels[0][index] = leaf;
+        for level in 1..self.levels.len() {
+            let node = index >> level;
+            let children = &self.levels[level - 1];
+            let parent = poseidon::hash_two(children[2 * node], children[2 * node + 1]);
+            self.levels[level][node] = parent;
+        }
+        Some(path)
+    }
+}
+
+/// Merkle Path
+/// Sibling of each level, from the leaf up; bit `i` of `index` is 1 when
+/// the node at level `i` is a right child
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub index: usize,
+    pub siblings: Vec<Fr>,
+}
+
+impl MerklePath {
+    /// Root reached from `leaf` along this path (native)
+    pub fn root(&self, leaf: Fr) -> Fr {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(leaf, |node, (level, &sibling)| {
+                if (self.index >> level) & 1 == 1 {
+                    poseidon::hash_two(sibling, node)
+                } else {
+                    poseidon::hash_two(node, sibling)
+                }
+            })
+    }
+}
+
+/// Merkle Gate Configuration
+/// One path level per row; the ordered pair is hashed by the Poseidon Gate
+///
+/// # Column Allocation
+///
+/// - `node_column`: Node at this level (advice[0])
+/// - `sibling_column`: Its sibling (advice[1])
+/// - `bit_column`: 1 if the node is a right child (advice[2])
+/// - `left_column`, `right_column`: Ordered pair (advice[3-4])
+///
+/// # Constraints
+///
+/// 1. **Boolean**: `bit · (1 - bit) = 0`
+/// 2. **Swap**: `left = node + bit · (sibling - node)`,
+///    `right = sibling + bit · (node - sibling)`
+/// 3. **Index**: `acc = 2 · acc_next + bit`, recomposing a path's index
+///    from its bits (`acc` in `node_column`)
+///
+/// # Note
+///
+/// - Columns are shared with the other gates (used in different rows)
+#[derive(Clone, Debug)]
+pub struct MerkleConfig {
+    pub node_column: Column<Advice>,
+    pub sibling_column: Column<Advice>,
+    pub bit_column: Column<Advice>,
+    pub left_column: Column<Advice>,
+    pub right_column: Column<Advice>,
+
+    // Selector for the swap constraint
+    pub swap_selector: Selector,
+
+    // Selector for the index recomposition
+    pub index_selector: Selector,
+}
+
+/// Merkle Chip
+pub struct MerkleChip {
+    config: MerkleConfig,
+}
+
+impl MerkleChip {
+    /// Create a new MerkleChip
+    pub fn new(config: MerkleConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Merkle Gate
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, config: &PoneglyphConfig) -> MerkleConfig {
+        let node_column = config.advice[0];
+        let sibling_column = config.advice[1];
+        let bit_column = config.advice[2];
+        let left_column = config.advice[3];
+        let right_column = config.advice[4];
+
+        let swap_selector = meta.selector();
+        let index_selector = meta.selector();
+
+        meta.create_gate("merkle swap", |meta| {
+            let s = meta.query_selector(swap_selector);
+            let node = meta.query_advice(node_column, Rotation::cur());
+            let sibling = meta.query_advice(sibling_column, Rotation::cur());
+            let bit = meta.query_advice(bit_column, Rotation::cur());
+            let left = meta.query_advice(left_column, Rotation::cur());
+            let right = meta.query_advice(right_column, Rotation::cur());
+            let one = Expression::Constant(Fr::ONE);
+
+            vec![
+                s.clone() * bit.clone() * (one - bit.clone()),
+                s.clone() * (left - node.clone() - bit.clone() * (sibling.clone() - node.clone())),
+                s * (right - sibling.clone() - bit * (node - sibling)),
+            ]
+        });
+
+        meta.create_gate("merkle index", |meta| {
+            let s = meta.query_selector(index_selector);
+            let acc = meta.query_advice(node_column, Rotation::cur());
+            let acc_next = meta.query_advice(node_column, Rotation::next());
+            let bit = meta.query_advice(bit_column, Rotation::cur());
+
+            vec![s * (acc - acc_next * Fr::from(2) - bit)]
+        });
+
+        MerkleConfig {
+            node_column,
+            sibling_column,
+            bit_column,
+            left_column,
+            right_column,
+            swap_selector,
+            index_selector,
+        }
+    }
+
+    /// Prove the path from an assigned leaf to the root
+    /// (same result as `MerklePath::root`)
+    ///
+    /// # Row Layout
+    ///
+    /// One region per level: row 0 holds node, sibling, bit, left, right;
+    /// `hash_two(left, right)` is the next level's node
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the root (the leaf itself for an empty path)
+    pub fn verify_path(
+        &self,
+        layouter: impl Layouter<Fr>,
+        poseidon_chip: &PoseidonChip,
+        leaf: &Cell,
+        path: &MerklePath,
+    ) -> Result<Cell, Error> {
+        let ([root], _) = self.verify_shared_path(layouter, poseidon_chip, [leaf], path)?;
+        Ok(root)
+    }
+
+    /// Prove the path like `verify_path`, and recompose the leaf's index
+    /// from the path's bits
+    ///
+    /// # Row Layout
+    ///
+    /// The path's regions, then an index region: row l holds bit l (copied
+    /// from level l) and `index >> l`; the last row holds 0 (constant)
+    ///
+    /// # Returns
+    ///
+    /// Cells holding the root and the index
+    pub fn verify_indexed_path(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        poseidon_chip: &PoseidonChip,
+        leaf: &Cell,
+        path: &MerklePath,
+    ) -> Result<(Cell, Cell), Error> {
+        let config = &self.config;
+        let ([root], bits) =
+            self.verify_shared_path(layouter.namespace(|| "path"), poseidon_chip, [leaf], path)?;
+        let index = layouter.assign_region(
+            || "merkle index",
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "index top",
+                    config.node_column,
+                    bits.len(),
+                    Fr::ZERO,
+                )?;
+                for (level, bit) in bits.iter().enumerate().rev() {
+                    config.index_selector.enable(&mut region, level)?;
+                    bit.copy_advice(|| "bit", &mut region, config.bit_column, level)?;
+                    acc = region.assign_advice(
+                        || "index",
+                        config.node_column,
+                        level,
+                        || Value::known(Fr::from((path.index >> level) as u64)),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )?;
+        Ok((root, index))
+    }
+
+    /// Prove an update of one leaf: the old leaf reaches `old_root` and the
+    /// new leaf `new_root` along the same path
+    ///
+    /// # Row Layout
+    ///
+    /// One region per level with a row per leaf; the second row's sibling
+    /// and bit are constrained equal to the first's, so both leaves sit at
+    /// the same position under the same siblings
+    ///
+    /// # Returns
+    ///
+    /// Cells holding the old and new roots
+    pub fn verify_update(
+        &self,
+        layouter: impl Layouter<Fr>,
+        poseidon_chip: &PoseidonChip,
+        old_leaf: &Cell,
+        new_leaf: &Cell,
+        path: &MerklePath,
+    ) -> Result<(Cell, Cell), Error> {
+        let ([old_root, new_root], _) =
+            self.verify_shared_path(layouter, poseidon_chip, [old_leaf, new_leaf], path)?;
+        Ok((old_root, new_root))
+    }
+
+    /// Roots reached from each leaf along one path (shared siblings and
+    /// bits), and the bit cell of each level
+    fn verify_shared_path<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        poseidon_chip: &PoseidonChip,
+        leaves: [&Cell; N],
+        path: &MerklePath,
+    ) -> Result<([Cell; N], Vec<Cell>), Error> {
+        let config = &self.config;
+        let mut nodes = leaves.map(AssignedCell::clone);
+        let mut bits = Vec::with_capacity(path.siblings.len());
+
+        for (level, &sibling) in path.siblings.iter().enumerate() {
+            let bit = (path.index >> level) & 1 == 1;
+            let (pairs, bit_cell) = layouter.assign_region(
+                || format!("merkle level {}", level),
+                |mut region| {
+                    let mut pairs = Vec::with_capacity(N);
+                    let mut shared: Option<(_, Cell)> = None;
+                    for (row, node) in nodes.iter().enumerate() {
+                        config.swap_selector.enable(&mut region, row)?;
+                        let node_cell =
+                            node.copy_advice(|| "node", &mut region, config.node_column, row)?;
+                        let sibling_cell = region.assign_advice(
+                            || "sibling",
+                            config.sibling_column,
+                            row,
+                            || Value::known(sibling),
+                        )?;
+                        let bit_cell = region.assign_advice(
+                            || "bit",
+                            config.bit_column,
+                            row,
+                            || Value::known(Fr::from(bit as u64)),
+                        )?;
+                        match &shared {
+                            Some((first_sibling, first_bit)) => {
+                                region.constrain_equal(sibling_cell.cell(), *first_sibling)?;
+                                region.constrain_equal(bit_cell.cell(), first_bit.cell())?;
+                            }
+                            None => shared = Some((sibling_cell.cell(), bit_cell.clone())),
+                        }
+
+                        let node_value = node_cell.value().copied();
+                        let sibling_value = Value::known(sibling);
+                        let (left, right) = if bit {
+                            (sibling_value, node_value)
+                        } else {
+                            (node_value, sibling_value)
+                        };
+                        let left =
+                            region.assign_advice(|| "left", config.left_column, row, || left)?;
+                        let right =
+                            region.assign_advice(|| "right", config.right_column, row, || right)?;
+                        pairs.push((left, right));
+                    }
+                    let (_, bit_cell) = shared.ok_or(Error::Synthesis)?;
+                    Ok((pairs, bit_cell))
+                },
+            )?;
+            bits.push(bit_cell);
+            for (node, (left, right)) in nodes.iter_mut().zip(pairs) {
+                *node = poseidon_chip.hash_two(
+                    layouter.namespace(|| format!("merkle hash {}", level)),
+                    &left,
+                    &right,
+                )?;
+            }
+        }
+
+        Ok((nodes, bits))
+    }
+}
@@ -1,22 +1,52 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Circuit, ConstraintSystem, Error},
+    circuit::{floor_planner::V1, AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
 };
 use pasta_curves::pallas::Base as Fr;
 
 pub mod aggregation;
+pub mod arithmetic;
 pub mod config;
+pub mod db_commitment;
+pub mod delta;
 pub mod group_by;
 pub mod join;
+#[cfg(feature = "dev-graph")]
+mod layout;
+pub mod merkle;
+pub mod non_membership;
+pub mod point_query;
+pub mod poseidon;
 pub mod range_check;
+pub mod set_membership;
 pub mod sort;
+pub mod stats;
+pub mod universal;
+pub mod window;
 
 pub use aggregation::*;
+pub use arithmetic::*;
 pub use config::*;
+pub use db_commitment::*;
+pub use delta::*;
 pub use group_by::*;
 pub use join::*;
+pub use merkle::{MerkleChip, MerkleConfig, MerklePath, MerkleTree};
+pub use non_membership::*;
+pub use point_query::*;
+pub use poseidon::{PoseidonChip, PoseidonConfig};
 pub use range_check::*;
+pub use set_membership::*;
 pub use sort::*;
+pub use stats::*;
+pub use universal::*;
+pub use window::*;
+
+/// Group result cells of an aggregation with their values
+type GroupResults = (Vec<AssignedCell<Fr, Fr>>, Vec<Value<u64>>);
 
 /// Temel SQL Gate trait'i - tüm operatörler bunu implement eder
 pub trait SQLGate<F: ff::PrimeField> {
@@ -33,10 +63,18 @@ pub trait SQLGate<F: ff::PrimeField> {
 
 /// Ana devre yapısı - SQL sorgularını buraya derleyeceğiz
 /// Makale Section 3: SQL sorgularını ZKP circuit'ine derleme
-#[derive(Clone)]
+///
+/// `Default` is the circuit without operations; build one from a compiled
+/// query with `from_compiled`
+#[derive(Clone, Default)]
 pub struct PoneglyphCircuit {
-    /// Veritabanı commitment (public input)
-    pub db_commitment: Value<Fr>,
+    /// Commitment of each committed table, in table name order
+    /// (public input, `instance` rows; see `CommittedDatabase`)
+    pub db_commitments: Vec<Fr>,
+    /// Constant and kind of each padded check of a universal circuit
+    /// (public input, the `instance` rows after `db_commitments`; see
+    /// `CircuitCapacity` and `RangeCheckOp::public_operand`); empty otherwise
+    pub public_operands: Vec<Fr>,
     /// Query sonucu (public input)
     pub query_result: Value<Fr>,
     /// Range check operations
@@ -47,8 +85,555 @@ pub struct PoneglyphCircuit {
     pub group_bys: Vec<GroupByOp>,
     /// Join operations
     pub joins: Vec<JoinOp>,
+    /// Semi-join operations (EXISTS / NOT EXISTS)
+    pub semi_joins: Vec<SemiJoinOp>,
+    /// Set membership operations (`IN` a committed set)
+    pub set_memberships: Vec<SetMembershipOp>,
     /// Aggregation operations
     pub aggregations: Vec<AggregationOp>,
+    /// Product operations (arithmetic expressions)
+    pub products: Vec<ProductOp>,
+    /// Division operations (`/` and `%` in arithmetic expressions)
+    pub divisions: Vec<DivisionOp>,
+    /// Sum operations (`+` and `-` in arithmetic expressions)
+    pub sums: Vec<SumOp>,
+    /// Window function operations
+    pub windows: Vec<WindowOp>,
+    /// HAVING operations (on aggregation results)
+    pub having: Vec<HavingOp>,
+    /// Top-K operations (aggregation results ranked for a LIMIT)
+    pub top_ks: Vec<TopKOp>,
+    /// WHERE match count, bound to the query result (`result_instance`)
+    pub match_count: Option<MatchCountOp>,
+    /// Poseidon commitment to the result rows, bound to the query result
+    /// (`result_instance`; cannot be combined with a public group count, nor
+    /// with `match_count` unless it hashes the match count, `matched`)
+    pub result_commitment: Option<ResultCommitment>,
+    /// Committed tables, each bound to its commitment (`instance` row in
+    /// table name order); None leaves the commitments unconstrained
+    pub database: Option<CommittedDatabase>,
+}
+
+/// Join-free circuit
+/// Same operations and synthesis as `PoneglyphCircuit`, configured without
+/// the Join Gate: 10 advice columns instead of 15
+///
+/// # Note
+///
+/// `Circuit::configure` is static, so the smaller configuration needs its own
+/// circuit type. Use it when `PoneglyphCircuit::features` reports no Join;
+/// synthesis fails with `Error::Synthesis` if the circuit has join operations.
+#[derive(Clone)]
+pub struct JoinFreeCircuit(pub PoneglyphCircuit);
+
+/// Circuit laid out with halo2's `V1` floor planner
+/// Same operations, configuration and synthesis as `PoneglyphCircuit`; only
+/// the placement of its regions differs
+///
+/// # Note
+///
+/// `SimpleFloorPlanner` places each region, in synthesis order, after the
+/// last used row of its columns. `V1` measures all regions first and slots
+/// them into the free rows of their columns, largest first, which can fit a
+/// circuit with many regions on disjoint columns into a smaller k (at the
+/// cost of a second pass over every region). `PoneglyphCircuit::floor_planner`
+/// reports which planner needs the smaller k.
+#[derive(Clone)]
+pub struct V1Circuit(pub PoneglyphCircuit);
+
+impl PoneglyphCircuit {
+    /// Circuit proving the operations of a compiled query
+    ///
+    /// `result_commitment` is bound to the query result instead of the
+    /// compiled one (the selection vector of `SQLCompiler::compile_selection`,
+    /// if any); with a `database`, its tables' commitments are the public
+    /// `db_commitments` and the operations' sources are copied from its cells.
+    /// The query result is left unknown: prove with `bound_result`.
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn from_compiled(
+        compiled: crate::sql::CompiledQuery,
+        result_commitment: Option<ResultCommitment>,
+        database: Option<CommittedDatabase>,
+    ) -> Self {
+        Self {
+            db_commitments: database
+                .as_ref()
+                .map_or_else(Vec::new, CommittedDatabase::values),
+            public_operands: Vec::new(),
+            query_result: Value::unknown(),
+            range_checks: compiled.range_checks,
+            sorts: compiled.sorts,
+            group_bys: compiled.group_bys,
+            joins: compiled.joins,
+            semi_joins: compiled.semi_joins,
+            set_memberships: compiled.set_memberships,
+            aggregations: compiled.aggregations,
+            products: compiled.products,
+            divisions: compiled.divisions,
+            sums: compiled.sums,
+            windows: compiled.windows,
+            having: compiled.having,
+            top_ks: compiled.top_ks,
+            match_count: compiled.match_count,
+            result_commitment: result_commitment.or(compiled.selection),
+            database,
+        }
+    }
+
+    /// Optional gates this circuit's operations need
+    pub fn features(&self) -> ConfigFeatures {
+        ConfigFeatures {
+            // Semi-joins are proven with the Join Gate's columns
+            join: !self.joins.is_empty() || !self.semi_joins.is_empty(),
+        }
+    }
+
+    /// Query result the circuit derives from its own operations
+    /// (`PublicInputs::query_result` to prove with): the result commitment,
+    /// the WHERE match count, or the number of groups a public HAVING keeps;
+    /// None if none is bound
+    pub fn bound_result(&self) -> Option<Fr> {
+        let kept_count = || {
+            let op = self.having.iter().find(|op| op.public_count)?;
+            Some(op.kept_count(&self.aggregations))
+        };
+        if let Some(result_commitment) = &self.result_commitment {
+            return Some(result_commitment.value());
+        }
+        self.match_count
+            .as_ref()
+            .map(MatchCountOp::count)
+            .or_else(kept_count)
+            .map(Fr::from)
+    }
+
+    /// Chips and lookup table this circuit's operations use
+    pub fn capabilities(&self) -> OpCapabilities {
+        let range_check = !self.range_checks.is_empty()
+            || !self.divisions.is_empty()
+            || !self.having.is_empty()
+            || self.match_count.is_some()
+            || self.group_bys.iter().any(|op| op.max_groups.is_some());
+        let join = self.features().join;
+        OpCapabilities {
+            range_check,
+            sort: !self.sorts.is_empty(),
+            // Partitioned windows prove their partition boundaries
+            group_by: !self.group_bys.is_empty()
+                || self.windows.iter().any(|op| op.partition.is_some()),
+            join,
+            set_membership: !self.set_memberships.is_empty(),
+            aggregation: !self.aggregations.is_empty(),
+            arithmetic: !self.products.is_empty()
+                || !self.divisions.is_empty()
+                || !self.sums.is_empty(),
+            window: !self.windows.is_empty(),
+            poseidon: self.result_commitment.is_some() || self.database.is_some(),
+            // Sorts (also inside joins and top-K) and MAX / MIN / wide SUM
+            // aggregations decompose their diffs into looked-up chunks
+            lookup_table: range_check
+                || !self.sorts.is_empty()
+                || !self.top_ks.is_empty()
+                || join
+                || !self.aggregations.is_empty(),
+        }
+    }
+
+    /// Order the arithmetic operations are assigned in: products, divisions
+    /// and sums in index order, each after the operations its operands name
+    ///
+    /// # Returns
+    ///
+    /// The operations in assignment order, or `Error::Synthesis` if an
+    /// operand names a missing operation or the operands form a cycle
+    fn arithmetic_order(&self) -> Result<Vec<ArithmeticStep>, Error> {
+        let steps = (0..self.products.len())
+            .map(ArithmeticStep::Product)
+            .chain((0..self.divisions.len()).map(ArithmeticStep::Division))
+            .chain((0..self.sums.len()).map(ArithmeticStep::Sum));
+        let mut order = Vec::new();
+        let mut visiting = HashSet::new();
+        let mut done = HashSet::new();
+        for step in steps {
+            self.visit_arithmetic(step, &mut visiting, &mut done, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Append `step` to `order` after the operations its operands name
+    fn visit_arithmetic(
+        &self,
+        step: ArithmeticStep,
+        visiting: &mut HashSet<ArithmeticStep>,
+        done: &mut HashSet<ArithmeticStep>,
+        order: &mut Vec<ArithmeticStep>,
+    ) -> Result<(), Error> {
+        if done.contains(&step) {
+            return Ok(());
+        }
+        if !visiting.insert(step) {
+            return Err(Error::Synthesis);
+        }
+        let operands = match step {
+            ArithmeticStep::Product(i) => self.products.get(i).map(|op| op.operands),
+            ArithmeticStep::Division(i) => self.divisions.get(i).map(|op| op.operands),
+            ArithmeticStep::Sum(i) => self.sums.get(i).map(|op| op.operands),
+        }
+        .ok_or(Error::Synthesis)?;
+        for operand in operands {
+            let dependency = match operand {
+                Operand::Product(i) => ArithmeticStep::Product(i),
+                Operand::Sum(i) => ArithmeticStep::Sum(i),
+                Operand::Quotient(i) | Operand::Remainder(i) => ArithmeticStep::Division(i),
+                Operand::Witness | Operand::Constant(_) | Operand::Committed(..) => continue,
+            };
+            self.visit_arithmetic(dependency, visiting, done, order)?;
+        }
+        visiting.remove(&step);
+        done.insert(step);
+        order.push(step);
+        Ok(())
+    }
+
+    /// Assign the product, division and sum operations (`arithmetic_order`),
+    /// each operand copied from the cell its `Operand` names
+    ///
+    /// # Returns
+    ///
+    /// The database cells and every operation's result cells, for the
+    /// range checks and other operations naming them
+    fn assign_arithmetic<'a>(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        arithmetic_chip: &ArithmeticChip,
+        range_check_chip: Option<&RangeCheckChip>,
+        database_cells: Option<&'a [Vec<AssignedCell<Fr, Fr>>]>,
+    ) -> Result<ArithmeticCells<'a>, Error> {
+        let mut cells = ArithmeticCells {
+            database: database_cells,
+            products: vec![None; self.products.len()],
+            quotients: vec![None; self.divisions.len()],
+            remainders: vec![None; self.divisions.len()],
+            sums: vec![None; self.sums.len()],
+        };
+        for step in self.arithmetic_order()? {
+            match step {
+                ArithmeticStep::Product(i) => {
+                    let mut layouter = layouter.namespace(|| format!("products[{}]", i));
+                    let product_op = &self.products[i];
+                    let (_, _, product) = arithmetic_chip.multiply_copied(
+                        layouter.namespace(|| "product"),
+                        cells.input(product_op.operands[0], product_op.a)?,
+                        cells.input(product_op.operands[1], product_op.b)?,
+                    )?;
+                    cells.products[i] = Some(product);
+                }
+                // a = q * b + r, with r < b proven on the gate's cells
+                ArithmeticStep::Division(i) => {
+                    let mut layouter = layouter.namespace(|| format!("divisions[{}]", i));
+                    let division_op = &self.divisions[i];
+                    let range_check_chip = range_check_chip.ok_or(Error::Synthesis)?;
+                    let (quotient_cell, remainder_cell, b_cell) = arithmetic_chip.divide_copied(
+                        layouter.namespace(|| "division"),
+                        cells.input(division_op.operands[0], division_op.a)?,
+                        cells.input(division_op.operands[1], division_op.b)?,
+                    )?;
+                    let remainder = division_op
+                        .a
+                        .zip(division_op.b)
+                        .map(|(a, b)| a.checked_rem(b).unwrap_or(a));
+                    let check = range_check_chip.check_less_than_advice_copied(
+                        layouter.namespace(|| "remainder < divisor"),
+                        Some(&remainder_cell),
+                        remainder,
+                        Some(&b_cell),
+                        division_op.b,
+                        division_op.u,
+                    )?;
+                    layouter.assign_region(
+                        || "remainder < divisor holds",
+                        |mut region| region.constrain_constant(check.cell(), Fr::from(1)),
+                    )?;
+                    cells.quotients[i] = Some(quotient_cell);
+                    cells.remainders[i] = Some(remainder_cell);
+                }
+                ArithmeticStep::Sum(i) => {
+                    let mut layouter = layouter.namespace(|| format!("sums[{}]", i));
+                    let sum_op = &self.sums[i];
+                    let a = cells.input(sum_op.operands[0], sum_op.a)?;
+                    let b = cells.input(sum_op.operands[1], sum_op.b)?;
+                    let sum = if sum_op.subtract {
+                        arithmetic_chip.subtract_copied(
+                            layouter.namespace(|| "difference"),
+                            a,
+                            b,
+                        )?
+                    } else {
+                        arithmetic_chip.add_copied(layouter.namespace(|| "sum"), a, b)?
+                    };
+                    cells.sums[i] = Some(sum);
+                }
+            }
+        }
+        Ok(cells)
+    }
+
+    /// Assign the range check operations and return their check cells
+    ///
+    /// One boolean cell per `range_checks` op, in order (1 = the op's
+    /// `value <comparison> threshold`, or `lower <= value < threshold` if
+    /// bounded). Ops
+    /// with a committed `source` copy their operands from the database cells
+    /// of `cells`, ops with an `expression` their value from its result cell,
+    /// ops with a `public_operand` from `instance`, after the table
+    /// commitments (`RangeCheckChip::public_operands`).
+    /// Ops with a `member_of` set prove the value's member bit instead
+    /// (`SetMembershipChip::check_member`, the sets loaded by the caller).
+    /// `CompiledQuery::predicate_checks` groups the cells by WHERE predicate.
+    pub fn assign_range_checks(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        range_check_chip: &RangeCheckChip,
+        set_membership_chip: Option<&SetMembershipChip>,
+        cells: &ArithmeticCells,
+        instance: Column<Instance>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let mut checks = Vec::with_capacity(self.range_checks.len());
+        for (i, range_check_op) in self.range_checks.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("range_checks[{}]", i));
+            // Committed database cells of the operands (None without a database)
+            let committed = |source: Option<(usize, usize)>| match source {
+                Some((column, row)) => cells.cell(Operand::Committed(column, row)),
+                None => Ok(None),
+            };
+            let public = match range_check_op.public_operand {
+                Some(index) if range_check_op.witnessed_threshold => {
+                    Some(range_check_chip.public_operands(
+                        layouter.namespace(|| "public operand"),
+                        range_check_op.value,
+                        Value::known(range_check_op.threshold),
+                        instance,
+                        self.db_commitments.len() + index,
+                    )?)
+                }
+                Some(_) => return Err(Error::Synthesis),
+                None => None,
+            };
+            let (x_cell, threshold_cell) = match (&public, range_check_op.expression) {
+                (Some((a, b)), _) => (Some(a), Some(b)),
+                (None, Some(expression)) => (
+                    cells.cell(expression)?,
+                    committed(range_check_op.threshold_source)?,
+                ),
+                (None, None) => (
+                    committed(range_check_op.source)?,
+                    committed(range_check_op.threshold_source)?,
+                ),
+            };
+
+            let check = if let Some(set) = range_check_op.member_of {
+                let op = self.set_memberships.get(set).ok_or(Error::Synthesis)?;
+                set_membership_chip.ok_or(Error::Synthesis)?.check_member(
+                    layouter.namespace(|| "set membership"),
+                    set as u64 + 1,
+                    &op.set,
+                    x_cell,
+                    range_check_op.value,
+                )?
+            } else if let Some(lower) = range_check_op.lower {
+                range_check_chip.check_between(
+                    layouter.namespace(|| "range check (bounded)"),
+                    x_cell,
+                    range_check_op.value,
+                    lower,
+                    range_check_op.threshold,
+                )?
+            } else if range_check_op.witnessed_threshold {
+                let layouter = layouter.namespace(|| "range check (advice threshold)");
+                let chip = range_check_chip;
+                let (value, t) = (range_check_op.value, Value::known(range_check_op.threshold));
+                let u = range_check_op.u;
+                match range_check_op.comparison {
+                    Comparison::Lt => chip.check_less_than_advice_copied(
+                        layouter,
+                        x_cell,
+                        value,
+                        threshold_cell,
+                        t,
+                        u,
+                    )?,
+                    Comparison::Le => chip.check_less_equal_advice_copied(
+                        layouter,
+                        x_cell,
+                        value,
+                        threshold_cell,
+                        t,
+                        u,
+                    )?,
+                    Comparison::Eq => {
+                        chip.check_equal_advice_copied(layouter, x_cell, value, threshold_cell, t)?
+                    }
+                    // `>` and `>=` are emitted with the operands swapped
+                    Comparison::Gt | Comparison::Ge | Comparison::Bool => {
+                        return Err(Error::Synthesis)
+                    }
+                }
+            } else {
+                let layouter = layouter.namespace(|| "range check");
+                let chip = range_check_chip;
+                let (value, t) = (range_check_op.value, range_check_op.threshold);
+                match range_check_op.comparison {
+                    Comparison::Lt => chip.prove_lt(layouter, x_cell, value, t)?,
+                    Comparison::Le => chip.prove_le(layouter, x_cell, value, t)?,
+                    Comparison::Gt => chip.prove_gt(layouter, x_cell, value, t)?,
+                    Comparison::Ge => chip.prove_ge(layouter, x_cell, value, t)?,
+                    Comparison::Eq => chip.prove_eq(layouter, x_cell, value, t)?,
+                    Comparison::Bool => chip.prove_bool(layouter, x_cell, value, t != 0)?,
+                }
+            };
+            checks.push(check);
+        }
+        Ok(checks)
+    }
+
+    /// Assign the pass bit of each group for a HAVING predicate
+    ///
+    /// A `Range` condition compares each group's final result cell of its
+    /// aggregation (`aggregation_cells`) with its bounds (`check_between`;
+    /// missing bounds: lower 0, upper u64::MAX); AND / OR combine the bits
+    /// of both sides group by group (`combine_bits`).
+    ///
+    /// # Returns
+    ///
+    /// One boolean cell per group, or `Error::Synthesis` if an aggregation
+    /// is missing or the sides have different numbers of groups
+    fn assign_having_predicate(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        range_check_chip: &RangeCheckChip,
+        predicate: &HavingPredicate,
+        aggregation_cells: &[Vec<AssignedCell<Fr, Fr>>],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let (left, right, op) = match predicate {
+            HavingPredicate::Range {
+                aggregation,
+                lower,
+                upper,
+            } => {
+                let agg_op = self
+                    .aggregations
+                    .get(*aggregation)
+                    .ok_or(Error::Synthesis)?;
+                let cells = &aggregation_cells[*aggregation];
+                let results = agg_op.group_results();
+                return agg_op
+                    .group_last_rows()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(group, row)| {
+                        range_check_chip.check_between(
+                            layouter.namespace(|| format!("group {} in range", group)),
+                            Some(cells.get(row).ok_or(Error::Synthesis)?),
+                            Value::known(results[group]),
+                            lower.unwrap_or(0),
+                            upper.unwrap_or(u64::MAX),
+                        )
+                    })
+                    .collect();
+            }
+            HavingPredicate::And(left, right) => (left, right, BitOp::And),
+            HavingPredicate::Or(left, right) => (left, right, BitOp::Or),
+        };
+        let left = self.assign_having_predicate(
+            layouter.namespace(|| "left"),
+            range_check_chip,
+            left,
+            aggregation_cells,
+        )?;
+        let right = self.assign_having_predicate(
+            layouter.namespace(|| "right"),
+            range_check_chip,
+            right,
+            aggregation_cells,
+        )?;
+        if left.len() != right.len() {
+            return Err(Error::Synthesis);
+        }
+        left.iter()
+            .zip(&right)
+            .enumerate()
+            .map(|(group, (a, b))| {
+                range_check_chip.combine_bits(
+                    layouter.namespace(|| format!("group {} {:?}", group, op)),
+                    a,
+                    b,
+                    op,
+                )
+            })
+            .collect()
+    }
+
+    /// Final result cell and value of each group of a (64-bit) aggregation,
+    /// in group order: the input rows of a sort over aggregation results
+    ///
+    /// # Returns
+    ///
+    /// `Error::Synthesis` if the aggregation is missing or a wide SUM
+    fn group_result_cells(
+        &self,
+        aggregation: usize,
+        aggregation_cells: &[Vec<AssignedCell<Fr, Fr>>],
+    ) -> Result<GroupResults, Error> {
+        let agg_op = self
+            .aggregations
+            .get(aggregation)
+            .filter(|agg_op| !agg_op.is_wide())
+            .ok_or(Error::Synthesis)?;
+        let cells = aggregation_cells.get(aggregation).ok_or(Error::Synthesis)?;
+        let result_cells = agg_op
+            .group_last_rows()
+            .into_iter()
+            .map(|row| cells.get(row).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::Synthesis)?;
+        let results = agg_op
+            .group_results()
+            .into_iter()
+            .map(Value::known)
+            .collect();
+        Ok((result_cells, results))
+    }
+}
+
+/// Op Capabilities
+/// Which chips synthesis instantiates, derived from the non-empty op vectors
+///
+/// # Note
+///
+/// The gates themselves are always configured (`Circuit::configure` is
+/// static); this only skips chip instantiation and the lookup table load.
+/// An unloaded table column is all zeros, which still satisfies the chunk
+/// lookups of disabled rows, so e.g. a product-only circuit assigns no
+/// table rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpCapabilities {
+    /// Range checks, HAVING bounds and the WHERE match count
+    pub range_check: bool,
+    pub sort: bool,
+    pub group_by: bool,
+    /// Joins and semi-joins
+    pub join: bool,
+    /// Set membership operations (loads their own set tables)
+    pub set_membership: bool,
+    pub aggregation: bool,
+    /// Product, division and sum operations
+    pub arithmetic: bool,
+    pub window: bool,
+    /// Result commitment and committed database
+    pub poseidon: bool,
+    /// 0..2^lookup_bits table for chunk lookups (all but products, group-by
+    /// and windows)
+    pub lookup_table: bool,
 }
 
 /// Range Check Operation
@@ -57,6 +642,36 @@ pub struct RangeCheckOp {
     pub value: Value<u64>,
     pub threshold: u64,
     pub u: u64,
+    /// Threshold is another column's value: assigned to an advice cell
+    /// instead of the fixed threshold column
+    pub witnessed_threshold: bool,
+    /// (column, row) of the committed database cell holding `value`;
+    /// used only when the circuit has a `database`
+    pub source: Option<(usize, usize)>,
+    /// Committed database cell holding the threshold (witnessed thresholds only)
+    pub threshold_source: Option<(usize, usize)>,
+    /// Index in `PoneglyphCircuit::public_operands` of the constant operand
+    /// of this `value < threshold` check, the next index its kind (copied
+    /// from `instance`, after the table commitments; see
+    /// `RangeCheckChip::public_operands`); witnessed thresholds of a
+    /// universal circuit only, None for private operands
+    pub public_operand: Option<usize>,
+    /// Lower bound of a bounded predicate: `lower <= value < threshold` in
+    /// one check sharing its decomposition between both bounds
+    /// (`RangeCheckChip::check_between`, u unused); None for `value < threshold`
+    pub lower: Option<u64>,
+    /// Comparison of `value` with a fixed threshold, proven by the matching
+    /// `RangeCheckChip::prove_*` (u unused). Bounded checks are always `Lt`;
+    /// witnessed threshold checks are `Lt`, `Le` or `Eq`
+    /// (`check_less_than_advice_copied`, `check_less_equal_advice_copied`,
+    /// `check_equal_advice_copied`), `>` and `>=` having their operands swapped.
+    pub comparison: Comparison,
+    /// Arithmetic result cell holding `value` (an expression compared in
+    /// WHERE); None for a column value (see `source`)
+    pub expression: Option<Operand>,
+    /// Index into `set_memberships` of the set `value` is checked against:
+    /// the check bit is `value IN set` (threshold, u and comparison unused)
+    pub member_of: Option<usize>,
 }
 
 /// Sort Operation
@@ -64,12 +679,80 @@ pub struct RangeCheckOp {
 pub struct SortOp {
     pub input: Vec<Value<u64>>,
     pub sorted_output: Vec<u64>,
+    /// is_null bit of each sorted_output row (empty if the column has no NULLs)
+    pub null_flags: Vec<bool>,
+    /// NULL placement: NULLS FIRST (true) or NULLS LAST (false)
+    pub nulls_first: bool,
+    /// ORDER BY ... DESC: sorted_output is non-increasing
+    pub descending: bool,
+    /// Sort with the bitonic network (`SortAlgorithm::Bitonic`): the layout
+    /// depends only on the row count, not on the sorting permutation
+    /// (ascending without NULLs; sorted_output only fixes the length)
+    pub bitonic: bool,
+    /// Aggregation whose per-group result cells are copied into the input
+    /// rows (ORDER BY <aggregate>); None for a column input
+    pub aggregation: Option<usize>,
+    /// Committed database column holding the input in row order: the input
+    /// rows are copied from its cells. Used only when the circuit has a
+    /// `database`; None for a derived input
+    pub column: Option<usize>,
+    /// Rows of `column` and of the payload columns the input rows are copied
+    /// from, in input order (e.g. the rows matching a WHERE clause); None for
+    /// every row in row order
+    pub rows: Option<Vec<usize>>,
+    /// Columns moved with the input rows (e.g. the aggregated columns of a
+    /// GROUP BY): each comes out in the sorted order of the input (see
+    /// `SortChip::sort_and_verify_with_payloads`); not for bitonic sorts
+    /// or sorts with NULLs
+    pub payloads: Vec<SortPayload>,
+}
+
+/// Column moved with the rows of a sort (see `SortOp::payloads`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortPayload {
+    /// Values in input (row) order
+    pub values: Vec<u64>,
+    /// Committed database column holding the values: they are copied from
+    /// its cells (see `SortOp::column`)
+    pub column: Option<usize>,
 }
 
 /// Group-By Operation
 #[derive(Clone, Debug)]
 pub struct GroupByOp {
     pub group_keys: Vec<u64>,
+    /// Upper bound on the number of groups, proven on the group count
+    /// (None: no bound)
+    pub max_groups: Option<u64>,
+    /// Bind the number of groups to the query result (`result_instance`)
+    pub public_group_count: bool,
+    /// Index into `sorts` of the sort whose output cells the group keys are
+    /// copied from; None for witnessed keys
+    pub sort: Option<usize>,
+}
+
+impl GroupByOp {
+    /// Group keys without a group count
+    pub fn new(group_keys: Vec<u64>) -> Self {
+        Self {
+            group_keys,
+            max_groups: None,
+            public_group_count: false,
+            sort: None,
+        }
+    }
+
+    /// Number of groups (runs of equal adjacent keys; the distinct keys when
+    /// equal keys are adjacent)
+    pub fn group_count(&self) -> u64 {
+        let boundaries = self.group_keys.windows(2).filter(|pair| pair[0] != pair[1]);
+        (self.group_keys.len().min(1) + boundaries.count()) as u64
+    }
+
+    /// Is the group count proven (bounded or public)?
+    pub fn counts_groups(&self) -> bool {
+        self.max_groups.is_some() || self.public_group_count
+    }
 }
 
 /// Join Operation
@@ -79,6 +762,659 @@ pub struct JoinOp {
     pub table1_values: Vec<u64>,
     pub table2_keys: Vec<u64>,
     pub table2_values: Vec<u64>,
+    /// Committed database columns holding `table1_keys`, `table1_values`,
+    /// `table2_keys` and `table2_values`; used only when the circuit has a
+    /// `database`
+    pub columns: Option<[usize; 4]>,
+}
+
+/// Semi-Join Operation
+/// `EXISTS` (or `NOT EXISTS` when `negated`) filter: a left row is kept if
+/// its key has (or has no) equal key on the right side
+///
+/// Each left row's keep flag is proven (`kept_flags`, in sorted key order)
+/// and a COUNT(*) over the rows copies them (`AggregationOp::value_semi_join`).
+/// Keys must be below 2^63 (see `JoinChip::semi_join_and_verify`).
+#[derive(Clone, Debug)]
+pub struct SemiJoinOp {
+    pub left_keys: Vec<u64>,
+    pub right_keys: Vec<u64>,
+    pub negated: bool,
+}
+
+impl SemiJoinOp {
+    /// Indices of the left rows that are kept, in left row order
+    pub fn passing_rows(&self) -> Vec<usize> {
+        let right: std::collections::HashSet<u64> = self.right_keys.iter().copied().collect();
+        self.left_keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| right.contains(key) != self.negated)
+            .map(|(row, _)| row)
+            .collect()
+    }
+
+    /// Keep flag of each left row in sorted key order, as proven by
+    /// `JoinChip::semi_join_and_verify` (1 = the row is kept)
+    pub fn kept_flags(&self) -> Vec<u64> {
+        let right: std::collections::HashSet<u64> = self.right_keys.iter().copied().collect();
+        let mut keys = self.left_keys.clone();
+        keys.sort_unstable();
+        keys.iter()
+            .map(|key| (right.contains(key) != self.negated) as u64)
+            .collect()
+    }
+}
+
+/// Set Membership Operation
+/// Committed set of an `IN` filter: each row's value is checked by a
+/// `RangeCheckOp` naming the set (`member_of`), whose check bit the Set
+/// Membership Gate proves from the value's interval of the set
+///
+/// # Note
+///
+/// `set` is loaded into the gate's tables, which are part of the verifying
+/// key, so it must be known at key generation.
+#[derive(Clone, Debug)]
+pub struct SetMembershipOp {
+    pub set: Vec<u64>,
+}
+
+impl SetMembershipOp {
+    /// Is the value a member of the set?
+    pub fn contains(&self, value: u64) -> bool {
+        self.set.contains(&value)
+    }
+}
+
+/// Operand of an arithmetic operation: the cell its value is copied from
+///
+/// Expressions are compiled into products, divisions and sums whose
+/// operands name the committed column cells, constants and earlier results
+/// they read, so each result cell is tied to the row's values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Operand {
+    /// Witnessed value, copied from no cell
+    #[default]
+    Witness,
+    /// Constant, fixed in the verifying key
+    Constant(u64),
+    /// Committed database cell (column, row); a witness without a `database`
+    Committed(usize, usize),
+    /// Product cell of `products[i]`
+    Product(usize),
+    /// Result cell of `sums[i]`
+    Sum(usize),
+    /// Quotient cell of `divisions[i]`
+    Quotient(usize),
+    /// Remainder cell of `divisions[i]`
+    Remainder(usize),
+}
+
+/// Product Operation
+/// Auxiliary product cell of an arithmetic expression: product = a * b
+#[derive(Clone, Debug)]
+pub struct ProductOp {
+    pub a: Value<u64>,
+    pub b: Value<u64>,
+    /// Cells `a` and `b` are copied from
+    pub operands: [Operand; 2],
+}
+
+/// Sum Operation
+/// Result cell of `a + b`, or of `a - b` if `subtract` (the difference of an
+/// expression must not be negative)
+#[derive(Clone, Debug)]
+pub struct SumOp {
+    pub a: Value<u64>,
+    pub b: Value<u64>,
+    pub subtract: bool,
+    /// Cells `a` and `b` are copied from
+    pub operands: [Operand; 2],
+}
+
+/// Arithmetic operation of `PoneglyphCircuit::arithmetic_order`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ArithmeticStep {
+    Product(usize),
+    Division(usize),
+    Sum(usize),
+}
+
+/// Cells an `Operand` can name: the committed database cells and the result
+/// cells of the arithmetic operations assigned so far
+#[derive(Clone, Debug, Default)]
+pub struct ArithmeticCells<'a> {
+    /// Cells of each committed column (None without a database)
+    pub database: Option<&'a [Vec<AssignedCell<Fr, Fr>>]>,
+    /// Product cell of each product operation (None until assigned)
+    pub products: Vec<Option<AssignedCell<Fr, Fr>>>,
+    /// Quotient cell of each division operation (None until assigned)
+    pub quotients: Vec<Option<AssignedCell<Fr, Fr>>>,
+    /// Remainder cell of each division operation (None until assigned)
+    pub remainders: Vec<Option<AssignedCell<Fr, Fr>>>,
+    /// Result cell of each sum operation (None until assigned)
+    pub sums: Vec<Option<AssignedCell<Fr, Fr>>>,
+}
+
+impl ArithmeticCells<'_> {
+    /// Cell an operand is copied from
+    ///
+    /// # Returns
+    ///
+    /// None for a witness, a constant or a committed cell without a
+    /// database; `Error::Synthesis` for a missing cell or a result not
+    /// assigned yet
+    pub fn cell(&self, operand: Operand) -> Result<Option<&AssignedCell<Fr, Fr>>, Error> {
+        fn result(
+            cells: &[Option<AssignedCell<Fr, Fr>>],
+            i: usize,
+        ) -> Result<Option<&AssignedCell<Fr, Fr>>, Error> {
+            cells
+                .get(i)
+                .and_then(Option::as_ref)
+                .map(Some)
+                .ok_or(Error::Synthesis)
+        }
+        match operand {
+            Operand::Witness | Operand::Constant(_) => Ok(None),
+            Operand::Committed(column, row) => match self.database {
+                Some(cells) => cells
+                    .get(column)
+                    .and_then(|column| column.get(row))
+                    .map(Some)
+                    .ok_or(Error::Synthesis),
+                None => Ok(None),
+            },
+            Operand::Product(i) => result(&self.products, i),
+            Operand::Sum(i) => result(&self.sums, i),
+            Operand::Quotient(i) => result(&self.quotients, i),
+            Operand::Remainder(i) => result(&self.remainders, i),
+        }
+    }
+
+    /// Arithmetic gate input of an operand holding `value`
+    fn input(&self, operand: Operand, value: Value<u64>) -> Result<GateInput<'_>, Error> {
+        Ok(match (operand, self.cell(operand)?) {
+            (Operand::Constant(constant), _) => GateInput::Constant(constant),
+            (_, Some(cell)) => GateInput::Copied(cell, value),
+            (_, None) => GateInput::Witness(value),
+        })
+    }
+}
+
+/// Division Operation
+/// Quotient and remainder of an arithmetic expression: `a = q * b + r`
+/// with `r < b`, so a zero divisor cannot be proven
+#[derive(Clone, Debug)]
+pub struct DivisionOp {
+    pub a: Value<u64>,
+    pub b: Value<u64>,
+    /// Bound on `b - r` for the `r < b` range check (as in `RangeCheckOp`)
+    pub u: u64,
+    /// Cells `a` and `b` are copied from
+    pub operands: [Operand; 2],
+}
+
+/// Window function
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WindowFunction {
+    /// SUM(col) OVER (ORDER BY ...): running total
+    RunningSum,
+    /// ROW_NUMBER() OVER (ORDER BY ...): 1..n over the sorted rows
+    RowNumber,
+}
+
+/// PARTITION BY of a window
+/// The window sort's key packs the partition key (high 32 bits) with the
+/// ORDER BY key, and both columns are moved with it as sort payloads
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowPartition {
+    /// Partition keys in window order
+    pub keys: Vec<u64>,
+    /// Index into the window sort's payloads of the partition column
+    pub key_payload: usize,
+    /// Index into the window sort's payloads of the ORDER BY column
+    pub order_payload: usize,
+}
+
+/// Window Operation
+/// Values are in window order (sorted by the window's ORDER BY column);
+/// for ROW_NUMBER they are the ORDER BY keys
+#[derive(Clone, Debug)]
+pub struct WindowOp {
+    pub function: WindowFunction,
+    pub values: Vec<u64>,
+    /// Index into `sorts` of the sort putting the rows in window order;
+    /// None for values already in window order
+    pub sort: Option<usize>,
+    /// Index into the `sort`'s payloads of the summed column (RunningSum):
+    /// the values are copies of its cells in window order; None for
+    /// witnessed values
+    pub sort_payload: Option<usize>,
+    /// Asserted per-row results (the output column): each result cell is
+    /// constrained to its constant, so only the true results prove; None
+    /// for no assertion
+    pub expected: Option<Vec<u64>>,
+    /// PARTITION BY: the window restarts where the partition key changes
+    /// (requires `sort`); None for one window over all rows
+    pub partition: Option<WindowPartition>,
+}
+
+impl WindowOp {
+    /// Per-row window results, in window order
+    ///
+    /// A running total above `u64::MAX` saturates; the Window Gate rejects
+    /// it (see `to_field_checked`). With a partition, both restart at each
+    /// new partition key.
+    pub fn results(&self) -> Vec<u64> {
+        let restarts = |i: usize| match &self.partition {
+            Some(partition) => i > 0 && partition.keys[i] != partition.keys[i - 1],
+            None => false,
+        };
+        let mut result = 0u64;
+        (0..self.values.len())
+            .map(|i| {
+                if restarts(i) {
+                    result = 0;
+                }
+                result = match self.function {
+                    WindowFunction::RunningSum => result.saturating_add(self.values[i]),
+                    WindowFunction::RowNumber => result + 1,
+                };
+                result
+            })
+            .collect()
+    }
+}
+
+/// Match Count Operation
+/// Number of `values` with `lower <= value < upper` (a missing bound is
+/// unbounded; NULL never matches), proven per row for either outcome
+///
+/// The count is bound to the query result (`result_instance`), so a verifier
+/// supplying 0 there trusts an empty WHERE result. A LIMIT query hashes it
+/// into its result commitment instead (`ResultCommitment::limited`), so a
+/// verifier of the rows also trusts how many rows the LIMIT cut from.
+#[derive(Clone, Debug)]
+pub struct MatchCountOp {
+    pub values: Vec<u64>,
+    pub lower: Option<u64>,
+    pub upper: Option<u64>,
+    /// Committed database column holding `values`; used only when the
+    /// circuit has a `database`
+    pub column: Option<usize>,
+}
+
+/// Result Commitment
+/// Poseidon commitment to a query's result rows, bound to the query result
+/// (`result_instance`) so that a claimed result set can be checked against it
+/// (e.g. by a smart contract holding the public inputs)
+///
+/// # Note
+///
+/// `h = hash(row count, column count)`, then `h = hash(h, cell)` for each
+/// cell in row-major order (`poseidon::hash_two`), then `h = hash(h, count)`
+/// with the WHERE match count if `matched` is set. Cells with a `sources`
+/// entry are copies of the cell producing them (an aggregation result, a
+/// group key, a committed or computed column cell, a WHERE check bit, a
+/// sorted row's payload); the
+/// others (`ResultSource::Witness`, or all cells without `sources`) are
+/// witnessed as given, so only their hash is proven. The match count is
+/// copied from the circuit's `match_count` cell, which is then bound through
+/// the commitment only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResultCommitment {
+    pub rows: Vec<Vec<u64>>,
+    /// Cell each row cell is copied from (same shape as `rows`); None for
+    /// rows witnessed as given
+    pub sources: Option<Vec<Vec<ResultSource>>>,
+    /// (range check, check bit) of each FROM row of a WHERE-filtered result:
+    /// each check cell is constrained to its bit, so the rows are exactly the
+    /// matching ones; None for no filter
+    pub filter: Option<Vec<(usize, u64)>>,
+    /// WHERE match count hashed after the rows (a LIMIT query's rows and the
+    /// number of rows they were cut from); None for rows alone
+    pub matched: Option<u64>,
+}
+
+/// Cell a result commitment cell is copied from (`ResultCommitment::sources`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultSource {
+    /// Witnessed as given
+    #[default]
+    Witness,
+    /// Row-level cell: a committed column cell, an arithmetic result or a
+    /// constant (a witness for `Operand::Witness`, or a committed cell
+    /// without a `database`)
+    Operand(Operand),
+    /// Result cell of group `group` of `aggregations[aggregation]`
+    Aggregation { aggregation: usize, group: usize },
+    /// Key of group `group` of `aggregations[aggregation]`: the GROUP BY
+    /// sort's output cell at the group's last row (`AggregationOp::group_sort`)
+    GroupKey { aggregation: usize, group: usize },
+    /// Check cell of `range_checks[i]`
+    Check(usize),
+    /// Cell of payload `payload` of `sorts[sort]` at sorted row `row`
+    Payload {
+        sort: usize,
+        payload: usize,
+        row: usize,
+    },
+}
+
+impl ResultCommitment {
+    /// Commitment to the given rows (all rows must have the same length)
+    pub fn new(rows: Vec<Vec<u64>>) -> Self {
+        Self {
+            rows,
+            sources: None,
+            filter: None,
+            matched: None,
+        }
+    }
+
+    /// Commitment to rows whose cells are copied from their sources
+    /// (value and source of each cell)
+    pub fn sourced(cells: Vec<Vec<(u64, ResultSource)>>) -> Self {
+        let rows = cells
+            .iter()
+            .map(|row| row.iter().map(|&(value, _)| value).collect())
+            .collect();
+        let sources = cells
+            .into_iter()
+            .map(|row| row.into_iter().map(|(_, source)| source).collect())
+            .collect();
+        Self {
+            rows,
+            sources: Some(sources),
+            filter: None,
+            matched: None,
+        }
+    }
+
+    /// Commitment to a selection vector: row `i` is `bits[i]`, the check bit
+    /// of range check `checks[i]`, and is copied from its check cell
+    pub fn selection(bits: Vec<u64>, checks: Vec<usize>) -> Self {
+        Self::sourced(
+            bits.into_iter()
+                .zip(checks)
+                .map(|(bit, check)| vec![(bit, ResultSource::Check(check))])
+                .collect(),
+        )
+    }
+
+    /// Commitment to a LIMIT query's rows and the number of rows matching
+    /// its WHERE clause (`MatchCountOp`), witnessed as given (the compiled
+    /// `CompiledQuery::result` of the query copies both from proven cells,
+    /// with the same value)
+    pub fn limited(rows: Vec<Vec<u64>>, matched: u64) -> Self {
+        Self {
+            matched: Some(matched),
+            ..Self::new(rows)
+        }
+    }
+
+    /// Commitment value: the public query result (`PublicInputs::query_result`)
+    pub fn value(&self) -> Fr {
+        let columns = self.rows.first().map_or(0, Vec::len);
+        poseidon::hash_chain(
+            [self.rows.len() as u64, columns as u64]
+                .into_iter()
+                .chain(self.rows.iter().flatten().copied())
+                .chain(self.matched)
+                .map(Fr::from),
+        )
+    }
+}
+
+/// Committed Database
+/// Database columns hashed in the circuit and bound to the table
+/// commitments (`instance`), so the values the gates read can be tied
+/// to the committed data
+///
+/// # Note
+///
+/// Each table is committed on its own: `h = column count`, then for each
+/// column `h = hash(h, length)` followed by `h = hash(h, cell)` for its
+/// cells (`poseidon::hash_two`). Table `i` in name order is bound to row
+/// `i` of the `instance` column. Columns are ordered by table name, then
+/// column name (`column_index`), so the compiler can refer to them by
+/// index. Range checks with a `source` cell, a match count or an
+/// aggregation with a `column` and joins with `columns` copy their values
+/// from the committed cells; the other operations still witness their inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommittedDatabase {
+    pub columns: Vec<Vec<u64>>,
+    /// Number of columns of each table, in table name order
+    pub tables: Vec<usize>,
+}
+
+impl CommittedDatabase {
+    /// Commit to every column of the given tables
+    /// (table_name -> column_name -> values, as taken by `SQLCompiler::compile`)
+    pub fn new(table_data: &HashMap<String, HashMap<String, Vec<u64>>>) -> Self {
+        let mut columns: Vec<(&String, &String, &Vec<u64>)> = table_data
+            .iter()
+            .flat_map(|(table, data)| {
+                data.iter()
+                    .map(move |(column, values)| (table, column, values))
+            })
+            .collect();
+        columns.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        let mut names: Vec<&String> = table_data.keys().collect();
+        names.sort();
+        Self {
+            columns: columns
+                .into_iter()
+                .map(|(_, _, values)| values.clone())
+                .collect(),
+            tables: names
+                .into_iter()
+                .map(|name| table_data[name].len())
+                .collect(),
+        }
+    }
+
+    /// Columns of each table, in table name order
+    pub fn table_columns(&self) -> Vec<&[Vec<u64>]> {
+        let mut start = 0;
+        self.tables
+            .iter()
+            .map(|&count| {
+                let columns = &self.columns[start..start + count];
+                start += count;
+                columns
+            })
+            .collect()
+    }
+
+    /// Index of `table.column` among the committed columns
+    /// (None if the table or column is missing)
+    pub fn column_index(
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        table: &str,
+        column: &str,
+    ) -> Option<usize> {
+        table_data.get(table)?.get(column)?;
+        let before = table_data
+            .iter()
+            .map(|(name, data)| match name.as_str().cmp(table) {
+                Ordering::Less => data.len(),
+                Ordering::Equal => data.keys().filter(|name| name.as_str() < column).count(),
+                Ordering::Greater => 0,
+            })
+            .sum();
+        Some(before)
+    }
+
+    /// Commitment values: the public table commitments
+    /// (`PublicInputs::db_commitments`), one per table in name order
+    pub fn values(&self) -> Vec<Fr> {
+        self.table_columns()
+            .into_iter()
+            .map(|columns| {
+                poseidon::hash_chain(
+                    std::iter::once(columns.len() as u64)
+                        .chain(columns.iter().flat_map(|column| {
+                            std::iter::once(column.len() as u64).chain(column.iter().copied())
+                        }))
+                        .map(Fr::from),
+                )
+            })
+            .collect()
+    }
+}
+
+impl MatchCountOp {
+    /// Number of matching values (the circuit's count witness)
+    pub fn count(&self) -> u64 {
+        let lower = self.lower.unwrap_or(0);
+        let upper = self.upper.unwrap_or(u64::MAX);
+        self.values
+            .iter()
+            .filter(|&&value| lower <= value && value < upper)
+            .count() as u64
+    }
+}
+
+/// Top-K Operation
+/// Ranks the groups of `aggregations[aggregation]` by their result
+/// (`ORDER BY <aggregate> [DESC] LIMIT k`): the results, copied from the
+/// aggregation's result cells, are proven sorted into `sorted_results`, and
+/// the returned `selected` results are its first rows
+#[derive(Clone, Debug)]
+pub struct TopKOp {
+    pub aggregation: usize,
+    /// Group results in ORDER BY order
+    pub sorted_results: Vec<u64>,
+    /// ORDER BY ... DESC: sorted_results is non-increasing
+    pub descending: bool,
+    /// Returned results (at most `sorted_results.len()`)
+    pub selected: Vec<u64>,
+}
+
+/// Having Operation
+/// Keeps the groups whose aggregation results pass `predicate` (one HAVING
+/// clause; its aggregations share the query's groups)
+///
+/// Each condition's pass bit is proven from the group's result cell for
+/// passing and failing groups alike, the bits are combined per group as the
+/// predicate's AND / OR, and the combined bits are summed into the number of
+/// kept groups (`kept_count`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HavingOp {
+    pub predicate: HavingPredicate,
+    /// Bind the number of kept groups to the query result (`result_instance`)
+    pub public_count: bool,
+}
+
+/// HAVING predicate on the group results of `PoneglyphCircuit::aggregations`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HavingPredicate {
+    /// Result r of `aggregations[aggregation]` with `lower <= r < upper`
+    /// (a missing bound is unbounded; a NULL result never passes)
+    Range {
+        aggregation: usize,
+        lower: Option<u64>,
+        upper: Option<u64>,
+    },
+    /// Both predicates pass
+    And(Box<HavingPredicate>, Box<HavingPredicate>),
+    /// Either predicate passes
+    Or(Box<HavingPredicate>, Box<HavingPredicate>),
+}
+
+impl HavingPredicate {
+    /// Does a group result satisfy a `Range` condition?
+    /// Missing bounds: lower 0, upper u64::MAX (the NULL value, never kept)
+    pub fn in_range(lower: Option<u64>, upper: Option<u64>, result: u64) -> bool {
+        lower.unwrap_or(0) <= result && result < upper.unwrap_or(u64::MAX)
+    }
+
+    /// Pass bit of each group (None if an aggregation is missing or the
+    /// aggregations have different numbers of groups)
+    pub fn pass_bits(&self, aggregations: &[AggregationOp]) -> Option<Vec<bool>> {
+        let (left, right, op) = match self {
+            HavingPredicate::Range {
+                aggregation,
+                lower,
+                upper,
+            } => {
+                let results = aggregations.get(*aggregation)?.group_results();
+                return Some(
+                    results
+                        .into_iter()
+                        .map(|result| Self::in_range(*lower, *upper, result))
+                        .collect(),
+                );
+            }
+            HavingPredicate::And(left, right) => (left, right, BitOp::And),
+            HavingPredicate::Or(left, right) => (left, right, BitOp::Or),
+        };
+        let left = left.pass_bits(aggregations)?;
+        let right = right.pass_bits(aggregations)?;
+        (left.len() == right.len()).then(|| {
+            left.into_iter()
+                .zip(right)
+                .map(|(a, b)| op.apply(a, b))
+                .collect()
+        })
+    }
+
+    /// Aggregations compared by the conditions, left to right
+    pub fn aggregations(&self) -> Vec<usize> {
+        match self {
+            HavingPredicate::Range { aggregation, .. } => vec![*aggregation],
+            HavingPredicate::And(left, right) | HavingPredicate::Or(left, right) => {
+                let mut aggregations = left.aggregations();
+                aggregations.extend(right.aggregations());
+                aggregations
+            }
+        }
+    }
+
+    /// The same predicate over aggregations appended after `offset` others
+    pub fn shifted(self, offset: usize) -> Self {
+        match self {
+            HavingPredicate::Range {
+                aggregation,
+                lower,
+                upper,
+            } => HavingPredicate::Range {
+                aggregation: aggregation + offset,
+                lower,
+                upper,
+            },
+            HavingPredicate::And(left, right) => HavingPredicate::And(
+                Box::new(left.shifted(offset)),
+                Box::new(right.shifted(offset)),
+            ),
+            HavingPredicate::Or(left, right) => HavingPredicate::Or(
+                Box::new(left.shifted(offset)),
+                Box::new(right.shifted(offset)),
+            ),
+        }
+    }
+}
+
+impl HavingOp {
+    /// Indices of the groups that are kept
+    pub fn passing_groups(&self, aggregations: &[AggregationOp]) -> Vec<usize> {
+        self.predicate
+            .pass_bits(aggregations)
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, passes)| *passes)
+            .map(|(group, _)| group)
+            .collect()
+    }
+
+    /// Number of kept groups (the circuit's count witness)
+    pub fn kept_count(&self, aggregations: &[AggregationOp]) -> u64 {
+        self.passing_groups(aggregations).len() as u64
+    }
 }
 
 /// Aggregation Operation
@@ -86,27 +1422,236 @@ pub struct JoinOp {
 pub struct AggregationOp {
     pub group_keys: Vec<u64>,
     pub values: Vec<u64>,
-    pub agg_type: String, // "sum", "count", "max", "min"
+    /// Aggregation function (AVG is compiled to a SUM and a COUNT)
+    pub function: AggregationFunction,
+    /// Index into `range_checks` of the check cell each value is copied from
+    /// (a filtered COUNT sums its WHERE check bits); None for witnessed values
+    pub value_checks: Option<Vec<usize>>,
+    /// Index into `semi_joins` of the keep flags each value is copied from,
+    /// in sorted key order (a COUNT(*) with WHERE [NOT] EXISTS, which the
+    /// order does not change); None for other values
+    pub value_semi_join: Option<usize>,
+    /// Unfiltered value of each row of a filtered SUM: each value is proven
+    /// as `unfiltered · bit`, the bit copied from its `value_checks` cell
+    /// (0 for a row the WHERE drops); None: the values are the copied cells
+    pub unfiltered: Option<Vec<u64>>,
+    /// Committed database column holding the values in row order (the
+    /// `unfiltered` values of a filtered SUM; any column of the table for a
+    /// COUNT(*), whose gate ignores the values but whose row count is then
+    /// the table's): they are copied from its cells. Used only when the
+    /// circuit has a `database`; None for values in another order or derived
+    pub column: Option<usize>,
+    /// Asserted result of an aggregation without group keys: the result
+    /// cell is bound to the query result (`result_instance`) and constrained
+    /// to this constant, so only the true result proves (see
+    /// `prover::assert_aggregate`); None for no assertion
+    pub expected: Option<u64>,
+    /// Payload of each row of a MAX / MIN (arg-max / arg-min): each group
+    /// also proves the payload of a row achieving its result (see
+    /// `AggregationConfig`); None for no payload
+    pub payload: Option<Vec<u64>>,
+    /// Index into `sorts` of the GROUP BY sort: the group keys are copies of
+    /// its output cells; None for witnessed keys
+    pub group_sort: Option<usize>,
+    /// Index into the `group_sort`'s payloads of the column holding the
+    /// values (the `unfiltered` values of a filtered SUM) in row order: they
+    /// are copies of its cells in sorted order; None for other values
+    pub sort_payload: Option<usize>,
+}
+
+impl AggregationOp {
+    /// Final aggregation result of each group, in group order
+    ///
+    /// Mirrors the Aggregation Gate witness: a new group starts wherever the
+    /// group key changes, and the group's result is the running result at
+    /// its last row. Without group keys, all values form a single group.
+    /// A wide SUM result above `u64::MAX` saturates (see `wide_group_results`).
+    pub fn group_results(&self) -> Vec<u64> {
+        self.wide_group_results()
+            .into_iter()
+            .map(|result| u64::try_from(result).unwrap_or(u64::MAX))
+            .collect()
+    }
+
+    /// Final aggregation result of each group, in group order (128-bit, for a wide SUM)
+    pub fn wide_group_results(&self) -> Vec<u128> {
+        let running = self.running_results();
+        self.group_last_rows()
+            .into_iter()
+            .map(|row| running[row])
+            .collect()
+    }
+
+    /// Does any running result exceed `u64::MAX`?
+    pub fn overflows_u64(&self) -> bool {
+        self.running_results()
+            .into_iter()
+            .any(|result| result > u64::MAX as u128)
+    }
+
+    /// Is this a SUM proven with 128-bit running results (see
+    /// `AggregationFunction`)? Its results cannot be compared or sorted by
+    /// the 64-bit gates.
+    pub fn is_wide(&self) -> bool {
+        self.function == AggregationFunction::Sum && self.overflows_u64()
+    }
+
+    /// Row of the last element of each group, in group order
+    pub fn group_last_rows(&self) -> Vec<usize> {
+        (0..self.values.len())
+            .filter(|&i| {
+                i + 1 == self.values.len()
+                    || (!self.group_keys.is_empty()
+                        && self.group_keys.get(i) != self.group_keys.get(i + 1))
+            })
+            .collect()
+    }
+
+    /// Running result at each row (the Aggregation Gate's result column)
+    fn running_results(&self) -> Vec<u128> {
+        let mut results = Vec::with_capacity(self.values.len());
+        let mut current: Option<u128> = None;
+
+        for (i, &value) in self.values.iter().enumerate() {
+            let value = value as u128;
+            let new_group = i > 0
+                && !self.group_keys.is_empty()
+                && self.group_keys.get(i) != self.group_keys.get(i - 1);
+            if new_group {
+                current = None;
+            }
+
+            let result = match (current, self.function) {
+                (None, AggregationFunction::Count) => 1,
+                (None, _) => value,
+                (Some(prev), AggregationFunction::Sum) => prev + value,
+                (Some(prev), AggregationFunction::Count) => prev + 1,
+                (Some(prev), AggregationFunction::Max) => prev.max(value),
+                (Some(prev), AggregationFunction::Min) => prev.min(value),
+                (Some(prev), AggregationFunction::Avg) => prev,
+            };
+            current = Some(result);
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// Payload of each group's result row, in group order (None without a
+    /// payload): the last row of the group holding its result
+    pub fn group_payloads(&self) -> Option<Vec<u64>> {
+        let payloads = self.payload.as_ref()?;
+        let running = self.running_results();
+        let mut payload_results: Vec<u64> = Vec::with_capacity(payloads.len());
+        for (i, (&payload, &value)) in payloads.iter().zip(&self.values).enumerate() {
+            let new_group = i == 0
+                || (!self.group_keys.is_empty()
+                    && self.group_keys.get(i) != self.group_keys.get(i - 1));
+            payload_results.push(if new_group || running[i] == value as u128 {
+                payload
+            } else {
+                payload_results[i - 1]
+            });
+        }
+        Some(
+            self.group_last_rows()
+                .into_iter()
+                .map(|row| payload_results[row])
+                .collect(),
+        )
+    }
+
+    /// Per-group partial results of this aggregation (see `AggregationPartial`)
+    pub fn partial(&self) -> AggregationPartial {
+        AggregationPartial {
+            function: self.function,
+            group_keys: if self.group_keys.is_empty() {
+                Vec::new()
+            } else {
+                self.group_last_rows()
+                    .into_iter()
+                    .map(|row| self.group_keys[row])
+                    .collect()
+            },
+            results: self.group_results(),
+        }
+    }
+}
+
+/// Aggregation Partial
+/// Per-group results of an aggregation over a contiguous chunk of rows
+///
+/// Partials of consecutive chunks combine into the result over all rows:
+/// a group that spans a chunk boundary is the last group of one chunk and
+/// the first group of the next, and its two results are merged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregationPartial {
+    pub function: AggregationFunction,
+    /// Key of each group, in group order (empty without GROUP BY)
+    pub group_keys: Vec<u64>,
+    /// Result of each group, in group order
+    pub results: Vec<u64>,
+}
+
+impl AggregationPartial {
+    /// Append the partial of the next chunk of rows
+    pub fn combine(&mut self, next: &AggregationPartial) -> Result<(), String> {
+        if next.function != self.function {
+            return Err(format!(
+                "Cannot combine {} and {} partials",
+                self.function, next.function
+            ));
+        }
+
+        let (Some(&last), Some(&first)) = (self.results.last(), next.results.first()) else {
+            self.group_keys.extend_from_slice(&next.group_keys);
+            self.results.extend_from_slice(&next.results);
+            return Ok(());
+        };
+
+        // Without group keys all rows form a single group; otherwise only
+        // a group continuing across the chunk boundary is merged
+        let continues =
+            self.group_keys.is_empty() || self.group_keys.last() == next.group_keys.first();
+        if !continues {
+            self.group_keys.extend_from_slice(&next.group_keys);
+            self.results.extend_from_slice(&next.results);
+            return Ok(());
+        }
+
+        let merged = match self.function {
+            AggregationFunction::Sum | AggregationFunction::Count => {
+                last.checked_add(first).ok_or_else(|| {
+                    format!(
+                        "Combined {} of the partials exceeds u64::MAX",
+                        self.function
+                    )
+                })?
+            }
+            AggregationFunction::Max => last.max(first),
+            AggregationFunction::Min => last.min(first),
+            AggregationFunction::Avg => {
+                return Err("Cannot combine avg partials (combine its sum and count)".to_string())
+            }
+        };
+        *self.results.last_mut().unwrap() = merged;
+        self.group_keys
+            .extend_from_slice(next.group_keys.get(1..).unwrap_or_default());
+        self.results.extend_from_slice(&next.results[1..]);
+        Ok(())
+    }
 }
 
 impl Circuit<Fr> for PoneglyphCircuit {
-    type Config = PoneglyphConfig;
+    type Config = (PoneglyphConfig, GateConfigs);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self {
-            db_commitment: Value::unknown(),
-            query_result: Value::unknown(),
-            range_checks: Vec::new(),
-            sorts: Vec::new(),
-            group_bys: Vec::new(),
-            joins: Vec::new(),
-            aggregations: Vec::new(),
-        }
+        Self::default()
     }
 
     fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
-        PoneglyphConfig::configure(meta)
+        PoneglyphConfig::configure_with_gates(meta)
     }
 
     fn synthesize(
@@ -114,139 +1659,815 @@ impl Circuit<Fr> for PoneglyphCircuit {
         config: Self::Config,
         mut layouter: impl Layouter<Fr>,
     ) -> Result<(), Error> {
+        let (config, gates) = config;
+
+        // Called once per keygen and once per proof; the op counts are the
+        // circuit's shape
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::trace_span!(
+            "synthesize",
+            range_checks = self.range_checks.len(),
+            sorts = self.sorts.len(),
+            group_bys = self.group_bys.len(),
+            joins = self.joins.len(),
+            aggregations = self.aggregations.len(),
+        ));
+
         // Makale Section 5.1: Public input'ları instance column'a expose et
-        // Row 0: Veritabanı commitment
-        // Row 1: Sorgu sonucu
+        // instance: Veritabanı commitment
+        // result_instance: Sorgu sonucu
         // NOT: Instance column'dan değer okuma işlemini KALDIRIYORUZ
         // Çünkü MockProver::run çağrısında public_inputs ile doldurulacak
         // enable_equality zaten configure'da yapıldı, bu yeterli
         // Instance column constraint'leri MockProver tarafından otomatik olarak kontrol edilir
 
-        // Lookup table'ı yükle
-        config.load_lookup_table(&mut layouter)?;
-
-        // Create gate configs for synthesis
-        // Note: Gates are already configured in Circuit::configure, but we need to create
-        // chip instances here for synthesis. We'll create minimal configs from the base config.
-
-        // Create Range Check config
-        let range_check_config = RangeCheckConfig {
-            chunk_columns: [
-                config.advice[0],
-                config.advice[1],
-                config.advice[2],
-                config.advice[3],
-                config.advice[4],
-                config.advice[5],
-                config.advice[6],
-                config.advice[7],
-            ],
-            lookup_table: config.lookup_table,
-            check_column: config.advice[8],
-            x_column: config.advice[9],
-            diff_column: config.advice[8],
-            threshold_column: config.fixed[0],
-            u_column: config.fixed[1],
-            selector: config.range_check_selector,
-            less_than_selector: config.less_than_selector,
-            decomposition_selector: config.decomposition_selector,
-            diff_lookup_selector: config.diff_lookup_selector,
+        // The result instance holds a single query result
+        let public_group_counts = self.group_bys.iter().filter(|op| op.public_group_count);
+        let asserted_results = self.aggregations.iter().filter(|op| op.expected.is_some());
+        let public_kept_counts = self.having.iter().filter(|op| op.public_count);
+        // A result commitment hashing the match count binds both
+        let hashed_count = self
+            .result_commitment
+            .as_ref()
+            .is_some_and(|commitment| commitment.matched.is_some());
+        if hashed_count && self.match_count.is_none() {
+            return Err(Error::Synthesis);
+        }
+        let result_bindings = (self.match_count.is_some() && !hashed_count) as usize
+            + self.result_commitment.is_some() as usize
+            + public_group_counts.count()
+            + asserted_results.count()
+            + public_kept_counts.count();
+        if result_bindings > 1 {
+            return Err(Error::Synthesis);
+        }
+
+        let capabilities = self.capabilities();
+
+        // Lookup table'ı yükle (only if an operation looks chunks up)
+        if capabilities.lookup_table {
+            config.load_lookup_table(&mut layouter)?;
+        }
+
+        // Create chip instances from the gate configs created in configure
+        // (each gate config carries the selectors its constraints were built with)
+        // Only the chips this circuit's operations use are instantiated
+        let range_check_chip = capabilities
+            .range_check
+            .then(|| RangeCheckChip::new(gates.range_check));
+        // Top-K ranks copy their input cells to the output rows, so the
+        // returned results are aggregation results
+        let top_k_chip = (!self.top_ks.is_empty()).then(|| {
+            SortChip::new(
+                gates
+                    .sort
+                    .clone()
+                    .with_algorithm(SortAlgorithm::CopyConstraint),
+            )
+        });
+        // Sorts with `SortOp::bitonic` run the bitonic network instead of
+        // the permutation check
+        let bitonic_chip = self
+            .sorts
+            .iter()
+            .any(|op| op.bitonic)
+            .then(|| SortChip::new(gates.sort.clone().with_algorithm(SortAlgorithm::Bitonic)));
+        let sort_chip = capabilities.sort.then(|| SortChip::new(gates.sort));
+        let group_by_chip = capabilities
+            .group_by
+            .then(|| GroupByChip::new(gates.group_by));
+        let join_chip = gates.join.filter(|_| capabilities.join).map(JoinChip::new);
+        let aggregation_chip = capabilities
+            .aggregation
+            .then(|| AggregationChip::new(gates.aggregation));
+        let arithmetic_chip = capabilities
+            .arithmetic
+            .then(|| ArithmeticChip::new(gates.arithmetic));
+        let window_chip = capabilities.window.then(|| WindowChip::new(gates.window));
+        let set_membership_chip = capabilities
+            .set_membership
+            .then(|| SetMembershipChip::new(gates.set_membership));
+        let poseidon_chip = capabilities
+            .poseidon
+            .then(|| PoseidonChip::new(gates.poseidon));
+
+        // Committed database, each table bound to its commitment (`instance`
+        // row i). Its cells are copied into the range checks, the match count,
+        // the joins and the aggregations that name them as their source
+        let database_cells = match &self.database {
+            Some(database) => {
+                let poseidon_chip = poseidon_chip.as_ref().ok_or(Error::Synthesis)?;
+                let mut cells = Vec::with_capacity(database.columns.len());
+                for (i, columns) in database.table_columns().into_iter().enumerate() {
+                    let (commitment_cell, table_cells) = poseidon_chip.commit_columns(
+                        layouter.namespace(|| format!("table commitment {}", i)),
+                        columns,
+                    )?;
+                    layouter.constrain_instance(commitment_cell.cell(), config.instance, i)?;
+                    cells.extend(table_cells);
+                }
+                Some(cells)
+            }
+            None => None,
         };
-        let range_check_chip = RangeCheckChip::new(range_check_config.clone());
-
-        // Create Sort config
-        let sort_config = SortConfig {
-            input_column: config.advice[2],
-            output_column: config.advice[3],
-            diff_column: config.advice[4],
-            sort_selector: config.sort_selector, // Sort için ayrı selector
-            range_check_config: range_check_config.clone(),
+
+        // Each operation's regions are laid out under a `field[index]` namespace
+        // (e.g. `sorts[0]`), which `layout_report` names failing constraints by
+
+        // Arithmetic operations (products, divisions and sums of expressions),
+        // each after the results its operands are copied from
+        let arithmetic_cells = match &arithmetic_chip {
+            Some(arithmetic_chip) => self.assign_arithmetic(
+                &mut layouter,
+                arithmetic_chip,
+                range_check_chip.as_ref(),
+                database_cells.as_deref(),
+            )?,
+            None => ArithmeticCells {
+                database: database_cells.as_deref(),
+                ..Default::default()
+            },
         };
-        let sort_chip = SortChip::new(sort_config.clone());
-
-        // Create Group-By config
-        let group_by_config = GroupByConfig {
-            group_key_column: config.advice[5],
-            boundary_column: config.advice[6],
-            inverse_column: config.advice[7],
-            boundary_selector: config.decomposition_selector, // Reuse selector
-            range_check_config: range_check_config.clone(),
+
+        // Set membership operations: set i is loaded under tag i + 1, its
+        // member bits are proven by the range checks naming it
+        if let Some(set_membership_chip) = &set_membership_chip {
+            let sets: Vec<&[u64]> = self
+                .set_memberships
+                .iter()
+                .map(|op| op.set.as_slice())
+                .collect();
+            set_membership_chip.load_sets(&mut layouter, &sets)?;
+        }
+
+        // Range Check operations
+        // Their check cells (one per op, in `range_checks` order) are kept for
+        // constraints on the WHERE result (filtered COUNT values)
+        let range_check_cells = match &range_check_chip {
+            Some(range_check_chip) => self.assign_range_checks(
+                &mut layouter,
+                range_check_chip,
+                set_membership_chip.as_ref(),
+                &arithmetic_cells,
+                config.instance,
+            )?,
+            None if self.range_checks.is_empty() => Vec::new(),
+            None => return Err(Error::Synthesis),
         };
-        let group_by_chip = GroupByChip::new(group_by_config.clone());
-
-        // Create Join config
-        let join_config = JoinConfig {
-            table1_key_column: config.advice[10],
-            table1_value_column: config.advice[11],
-            table2_key_column: config.advice[12],
-            table2_value_column: config.advice[13],
-            match_column: config.advice[14],
-            join_selector: config.less_than_selector, // Reuse selector
-            deduplication_selector: config.decomposition_selector, // Reuse selector
-            range_check_config: range_check_config.clone(),
-            sort_config: sort_config.clone(),
+
+        // Sort operations
+        // Sorts of aggregation results run after the aggregations (below)
+        // The output and payload cells of each sort are kept for the
+        // Group-By and Aggregation Gates (empty for the later sorts)
+        let committed_column = |column: Option<usize>| match (&database_cells, column) {
+            (Some(cells), Some(column)) => cells.get(column).map(Some).ok_or(Error::Synthesis),
+            _ => Ok(None),
         };
-        let join_chip = JoinChip::new(join_config);
-
-        // Create Aggregation config
-        let aggregation_config = AggregationConfig {
-            value_column: config.advice[8],
-            result_column: config.advice[9],
-            sum_selector: config.less_than_selector, // Reuse selector
-            count_selector: config.decomposition_selector, // Reuse selector
-            max_selector: config.range_check_selector, // Reuse selector
-            min_selector: config.diff_lookup_selector, // Reuse selector
-            group_by_config: group_by_config.clone(),
-            range_check_config: range_check_config.clone(),
+        // Committed cells of a sort's input or payload rows (`SortOp::rows`)
+        let committed_rows = |column: Option<usize>, rows: Option<&[usize]>| {
+            let Some(cells) = committed_column(column)? else {
+                return Ok(None);
+            };
+            match rows {
+                Some(rows) => rows
+                    .iter()
+                    .map(|&row| cells.get(row).cloned().ok_or(Error::Synthesis))
+                    .collect::<Result<Vec<_>, Error>>()
+                    .map(Some),
+                None => Ok(Some(cells.clone())),
+            }
         };
-        let aggregation_chip = AggregationChip::new(aggregation_config);
+        let mut sort_cells = Vec::with_capacity(self.sorts.len());
+        for (i, sort_op) in self.sorts.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("sorts[{}]", i));
+            let sort_chip = sort_chip.as_ref().ok_or(Error::Synthesis)?;
+            let has_payloads = !sort_op.payloads.is_empty();
+            let cells = if sort_op.aggregation.is_some() {
+                (Vec::new(), Vec::new())
+            } else if sort_op.bitonic {
+                if sort_op.descending || !sort_op.null_flags.is_empty() || has_payloads {
+                    return Err(Error::Synthesis);
+                }
+                let cells = bitonic_chip
+                    .as_ref()
+                    .ok_or(Error::Synthesis)?
+                    .sort_and_verify(
+                        layouter.namespace(|| "bitonic sort"),
+                        sort_op.input.clone(),
+                        sort_op.sorted_output.clone(),
+                    )?;
+                (cells, Vec::new())
+            } else if !sort_op.null_flags.is_empty() {
+                if has_payloads {
+                    return Err(Error::Synthesis);
+                }
+                let cells = sort_chip.sort_and_verify_with_nulls(
+                    layouter.namespace(|| "sort with nulls"),
+                    sort_op.input.clone(),
+                    sort_op.sorted_output.clone(),
+                    &sort_op.null_flags,
+                    sort_op.nulls_first,
+                    sort_op.descending,
+                )?;
+                (cells, Vec::new())
+            } else if sort_op.column.is_some() || has_payloads {
+                let rows = sort_op.rows.as_deref();
+                let payload_cells = sort_op
+                    .payloads
+                    .iter()
+                    .map(|payload| committed_rows(payload.column, rows))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let payloads: Vec<_> = sort_op
+                    .payloads
+                    .iter()
+                    .zip(&payload_cells)
+                    .map(|(payload, cells)| {
+                        let values = payload.values.iter().map(|&v| Value::known(v)).collect();
+                        (values, cells.as_deref())
+                    })
+                    .collect();
+                let input_cells = committed_rows(sort_op.column, rows)?;
+                sort_chip.sort_and_verify_with_payloads(
+                    layouter.namespace(|| "sort with payloads"),
+                    (sort_op.input.clone(), input_cells.as_deref()),
+                    sort_op.sorted_output.clone(),
+                    &payloads,
+                    sort_op.descending,
+                )?
+            } else if sort_op.descending {
+                let cells = sort_chip.sort_and_verify_descending(
+                    layouter.namespace(|| "sort descending"),
+                    sort_op.input.clone(),
+                    sort_op.sorted_output.clone(),
+                )?;
+                (cells, Vec::new())
+            } else {
+                let cells = sort_chip.sort_and_verify(
+                    layouter.namespace(|| "sort"),
+                    sort_op.input.clone(),
+                    sort_op.sorted_output.clone(),
+                )?;
+                (cells, Vec::new())
+            };
+            sort_cells.push(cells);
+        }
 
-        // Range Check operations
-        for range_check_op in &self.range_checks {
-            range_check_chip.check_less_than(
-                layouter.namespace(|| "range check"),
-                range_check_op.value,
-                range_check_op.threshold,
-                range_check_op.u,
+        // Group-By operations
+        // The boundaries are summed into a group count if it is bounded or public
+        for (i, group_by_op) in self.group_bys.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("group_bys[{}]", i));
+            let group_by_chip = group_by_chip.as_ref().ok_or(Error::Synthesis)?;
+            let boundary_cells = match group_by_op.sort {
+                Some(sort) => group_by_chip.group_and_verify_copied(
+                    layouter.namespace(|| "group by"),
+                    &group_by_op.group_keys,
+                    &sort_cells.get(sort).ok_or(Error::Synthesis)?.0,
+                )?,
+                None => group_by_chip
+                    .group_and_verify(layouter.namespace(|| "group by"), &group_by_op.group_keys)?,
+            };
+            if !group_by_op.counts_groups() {
+                continue;
+            }
+
+            let count_cell = group_by_chip.count_groups(
+                layouter.namespace(|| "group count"),
+                group_by_op.group_keys.len(),
+                &boundary_cells,
             )?;
+            // count <= max: the check bit of count < max + 1 must be 1
+            if let Some(max_groups) = group_by_op.max_groups.filter(|&max| max < u64::MAX) {
+                let range_check_chip = range_check_chip.as_ref().ok_or(Error::Synthesis)?;
+                let check_cell = range_check_chip.compare_less_than(
+                    layouter.namespace(|| "group count bound"),
+                    &count_cell,
+                    Value::known(group_by_op.group_count()),
+                    max_groups + 1,
+                )?;
+                layouter.assign_region(
+                    || "group count within bound",
+                    |mut region| region.constrain_constant(check_cell.cell(), Fr::from(1)),
+                )?;
+            }
+            if group_by_op.public_group_count {
+                layouter.constrain_instance(count_cell.cell(), config.result_instance, 0)?;
+            }
         }
 
-        // Sort operations
-        for sort_op in &self.sorts {
-            sort_chip.sort_and_verify(
-                layouter.namespace(|| "sort"),
-                sort_op.input.clone(),
+        // Join operations, tied to the committed tables when they name their
+        // columns (a join-free configuration has no Join Gate to prove them with)
+        for (i, join_op) in self.joins.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("joins[{}]", i));
+            let join_chip = join_chip.as_ref().ok_or(Error::Synthesis)?;
+            match (&database_cells, join_op.columns) {
+                (Some(cells), Some(columns)) => {
+                    let committed = |input: usize| {
+                        cells
+                            .get(columns[input])
+                            .map(Vec::as_slice)
+                            .ok_or(Error::Synthesis)
+                    };
+                    join_chip.join_and_verify_copied(
+                        layouter.namespace(|| "join (committed columns)"),
+                        &join_op.table1_keys,
+                        &join_op.table1_values,
+                        &join_op.table2_keys,
+                        &join_op.table2_values,
+                        [committed(0)?, committed(1)?, committed(2)?, committed(3)?],
+                    )?;
+                }
+                _ => {
+                    join_chip.join_and_verify(
+                        layouter.namespace(|| "join"),
+                        &join_op.table1_keys,
+                        &join_op.table1_values,
+                        &join_op.table2_keys,
+                        &join_op.table2_values,
+                    )?;
+                }
+            }
+        }
+
+        // Semi-join operations (EXISTS / NOT EXISTS)
+        // Keep flags are proven for every left row (the match flag, its
+        // complement if `negated`) and kept for the aggregations counting them
+        let mut semi_join_cells = Vec::with_capacity(self.semi_joins.len());
+        for (i, semi_join_op) in self.semi_joins.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("semi_joins[{}]", i));
+            let join_chip = join_chip.as_ref().ok_or(Error::Synthesis)?;
+            semi_join_cells.push(join_chip.semi_join_and_verify(
+                layouter.namespace(|| "semi join"),
+                &semi_join_op.left_keys,
+                &semi_join_op.right_keys,
+                semi_join_op.negated,
+            )?);
+        }
+
+        // Aggregation operations (result cells are kept for HAVING)
+        // Consecutive aggregations over the same group keys (a multi-aggregate
+        // SELECT) share one proof of the group boundaries
+        let mut aggregation_cells = Vec::with_capacity(self.aggregations.len());
+        for run in self.aggregations.chunk_by(|a, b| {
+            a.group_keys == b.group_keys
+                && a.group_sort == b.group_sort
+                && a.payload.is_none()
+                && b.payload.is_none()
+        }) {
+            // A shared run is named by its index range (aggregations[first..end])
+            let first = aggregation_cells.len();
+            let ops = match run.len() {
+                1 => format!("aggregations[{}]", first),
+                n => format!("aggregations[{}..{}]", first, first + n),
+            };
+            let mut layouter = layouter.namespace(|| ops);
+            let aggregation_chip = aggregation_chip.as_ref().ok_or(Error::Synthesis)?;
+
+            // A MAX / MIN with a payload runs alone (its payload follows its take bits)
+            if let [agg_op @ AggregationOp {
+                payload: Some(payloads),
+                ..
+            }] = run
+            {
+                let (cells, _payload_cells) = aggregation_chip.aggregate_with_payload(
+                    layouter.namespace(|| "payload aggregation"),
+                    &agg_op.group_keys,
+                    &agg_op.values,
+                    payloads,
+                    agg_op.function,
+                )?;
+                aggregation_cells.push(cells);
+                continue;
+            }
+            let columns: Vec<(&[u64], AggregationFunction)> = run
+                .iter()
+                .map(|agg_op| (agg_op.values.as_slice(), agg_op.function))
+                .collect();
+            // Values of a filtered COUNT are copies of the WHERE check cells
+            // (or of a semi-join's keep flags), those of a filtered SUM the
+            // unfiltered values times them
+            let check_cells = run
+                .iter()
+                .map(|agg_op| {
+                    if let Some(semi_join) = agg_op.value_semi_join {
+                        let cells = semi_join_cells.get(semi_join).ok_or(Error::Synthesis)?;
+                        return Ok(Some(cells.clone()));
+                    }
+                    agg_op
+                        .value_checks
+                        .as_ref()
+                        .map(|checks| {
+                            checks
+                                .iter()
+                                .map(|&check| range_check_cells.get(check).cloned())
+                                .collect::<Option<Vec<_>>>()
+                                .ok_or(Error::Synthesis)
+                        })
+                        .transpose()
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            // Values (or unfiltered values) read from a committed column are
+            // copies of its cells, in GROUP BY order of the sort's payload cells
+            let column_cells = run
+                .iter()
+                .map(|agg_op| match (agg_op.group_sort, agg_op.sort_payload) {
+                    (Some(sort), Some(payload)) => sort_cells
+                        .get(sort)
+                        .and_then(|(_, payloads)| payloads.get(payload))
+                        .map(Some)
+                        .ok_or(Error::Synthesis),
+                    _ => committed_column(agg_op.column),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let value_cells: Vec<Option<ValueCells>> = run
+                .iter()
+                .zip(check_cells.iter().zip(&column_cells))
+                .map(|(agg_op, (cells, column))| {
+                    Some(match (cells.as_deref(), &agg_op.unfiltered, column) {
+                        (Some(cells), Some(_), Some(column)) => {
+                            ValueCells::CopiedFiltered(column, cells)
+                        }
+                        (Some(cells), Some(unfiltered), None) => {
+                            ValueCells::Filtered(unfiltered, cells)
+                        }
+                        (Some(cells), None, _) => ValueCells::Copied(cells),
+                        (None, _, Some(column)) => ValueCells::Copied(column),
+                        (None, _, None) => return None,
+                    })
+                })
+                .collect();
+            // No group keys (no GROUP BY): all rows are one group
+            let cells = if run[0].group_keys.is_empty() {
+                aggregation_chip.aggregate_global_copied(
+                    layouter.namespace(|| "global aggregation"),
+                    &columns,
+                    &value_cells,
+                )?
+            } else {
+                let key_cells = match run[0].group_sort {
+                    Some(sort) => Some(&sort_cells.get(sort).ok_or(Error::Synthesis)?.0[..]),
+                    None => None,
+                };
+                aggregation_chip.aggregate_grouped_copied(
+                    layouter.namespace(|| "aggregation"),
+                    (&run[0].group_keys, key_cells),
+                    &columns,
+                    &value_cells,
+                )?
+            };
+            aggregation_cells.extend(cells);
+        }
+
+        // Asserted aggregation results, bound to the query result
+        // (`result_instance`) and to the expected constant
+        for (i, agg_op) in self.aggregations.iter().enumerate() {
+            let Some(expected) = agg_op.expected else {
+                continue;
+            };
+            // A single group with a 64-bit result cell
+            if !agg_op.group_keys.is_empty() || agg_op.is_wide() {
+                return Err(Error::Synthesis);
+            }
+            let cell = aggregation_cells[i].last().ok_or(Error::Synthesis)?;
+            layouter.constrain_instance(cell.cell(), config.result_instance, 0)?;
+            layouter.assign_region(
+                || format!("aggregations[{}] = expected", i),
+                |mut region| region.constrain_constant(cell.cell(), Fr::from(expected)),
+            )?;
+        }
+
+        // HAVING operations
+        // Each condition compares the groups' final result cells against its
+        // bounds (pass bits are proven for passing and failing groups alike),
+        // the bits are combined per group as the clause's AND / OR, and the
+        // combined bits are summed into the number of kept groups
+        for (i, having_op) in self.having.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("having[{}]", i));
+            let range_check_chip = range_check_chip.as_ref().ok_or(Error::Synthesis)?;
+            let pass_cells = self.assign_having_predicate(
+                layouter.namespace(|| "pass bits"),
+                range_check_chip,
+                &having_op.predicate,
+                &aggregation_cells,
+            )?;
+            let count_cell =
+                range_check_chip.count_bits(layouter.namespace(|| "kept groups"), &pass_cells)?;
+            if having_op.public_count {
+                layouter.constrain_instance(count_cell.cell(), config.result_instance, 0)?;
+            }
+        }
+
+        // ORDER BY <aggregate> sorts
+        // Each group's final result cell is copied into an input row of the sort
+        for (i, sort_op) in self.sorts.iter().enumerate() {
+            let Some(aggregation) = sort_op.aggregation else {
+                continue;
+            };
+            let mut layouter = layouter.namespace(|| format!("sorts[{}]", i));
+            let sort_chip = sort_chip.as_ref().ok_or(Error::Synthesis)?;
+            if sort_op.bitonic || !sort_op.null_flags.is_empty() {
+                return Err(Error::Synthesis);
+            }
+            let (result_cells, results) =
+                self.group_result_cells(aggregation, &aggregation_cells)?;
+            sort_chip.sort_and_verify_copied(
+                layouter.namespace(|| "sort group results"),
+                &result_cells,
+                results,
                 sort_op.sorted_output.clone(),
+                sort_op.descending,
             )?;
         }
 
-        // Group-By operations
-        for group_by_op in &self.group_bys {
-            group_by_chip
-                .group_and_verify(layouter.namespace(|| "group by"), &group_by_op.group_keys)?;
-        }
-
-        // Join operations
-        for join_op in &self.joins {
-            join_chip.join_and_verify(
-                layouter.namespace(|| "join"),
-                &join_op.table1_keys,
-                &join_op.table1_values,
-                &join_op.table2_keys,
-                &join_op.table2_values,
+        // Top-K operations
+        // Each group's final result cell is an input row of the rank sort, and
+        // the selected results are copies of its first output rows
+        for (i, top_k_op) in self.top_ks.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("top_ks[{}]", i));
+            let top_k_chip = top_k_chip.as_ref().ok_or(Error::Synthesis)?;
+            let (result_cells, results) =
+                self.group_result_cells(top_k_op.aggregation, &aggregation_cells)?;
+
+            let ranked_cells = top_k_chip.sort_and_verify_copied(
+                layouter.namespace(|| "rank groups"),
+                &result_cells,
+                results,
+                top_k_op.sorted_results.clone(),
+                top_k_op.descending,
             )?;
+            if top_k_op.selected.len() > ranked_cells.len() {
+                return Err(Error::Synthesis);
+            }
+            layouter.assign_region(
+                || "selected groups",
+                |mut region| {
+                    for (row, (&value, ranked)) in
+                        top_k_op.selected.iter().zip(&ranked_cells).enumerate()
+                    {
+                        let cell = region.assign_advice(
+                            || format!("selected_{}", row),
+                            config.advice[0],
+                            row,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                        region.constrain_equal(cell.cell(), ranked.cell())?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        // WHERE match count, bound to the query result (`result_instance`),
+        // or hashed into the result commitment
+        // Missing bounds: lower 0, upper u64::MAX (the NULL value, never matched)
+        let mut match_count_cell = None;
+        if let Some(match_count_op) = &self.match_count {
+            let range_check_chip = range_check_chip.as_ref().ok_or(Error::Synthesis)?;
+            let lower = match_count_op.lower.unwrap_or(0);
+            let upper = match_count_op.upper.unwrap_or(u64::MAX);
+            let count_cell = match (&database_cells, match_count_op.column) {
+                (Some(cells), Some(column)) => range_check_chip.count_in_range_copied(
+                    layouter.namespace(|| "match count (committed column)"),
+                    cells.get(column).ok_or(Error::Synthesis)?,
+                    &match_count_op.values,
+                    lower,
+                    upper,
+                )?,
+                _ => range_check_chip.count_in_range(
+                    layouter.namespace(|| "match count"),
+                    &match_count_op.values,
+                    lower,
+                    upper,
+                )?,
+            };
+            if !hashed_count {
+                layouter.constrain_instance(count_cell.cell(), config.result_instance, 0)?;
+            }
+            match_count_cell = Some(count_cell);
         }
 
-        // Aggregation operations
-        for agg_op in &self.aggregations {
-            aggregation_chip.aggregate_and_verify(
-                layouter.namespace(|| "aggregation"),
-                &agg_op.group_keys,
-                &agg_op.values,
-                &agg_op.agg_type,
+        // Result commitment, bound to the query result (`result_instance`)
+        // Sourced cells are copies of the cells producing them, a filter's
+        // check cells are fixed to its bits, and a LIMIT query's match count
+        // is the match count cell
+        if let Some(result_commitment) = &self.result_commitment {
+            let poseidon_chip = poseidon_chip.as_ref().ok_or(Error::Synthesis)?;
+            let (mut commitment_cell, row_cells) = poseidon_chip.commit_rows(
+                layouter.namespace(|| "result commitment"),
+                &result_commitment.rows,
             )?;
+            if let Some(sources) = &result_commitment.sources {
+                let sources: Vec<ResultSource> = sources.iter().flatten().copied().collect();
+                if sources.len() != row_cells.len() {
+                    return Err(Error::Synthesis);
+                }
+                let mut copies = Vec::new();
+                let mut constants = Vec::new();
+                for (row_cell, source) in row_cells.iter().zip(sources) {
+                    let cell = match source {
+                        ResultSource::Witness => None,
+                        ResultSource::Operand(Operand::Constant(constant)) => {
+                            constants.push((row_cell, constant));
+                            None
+                        }
+                        ResultSource::Operand(operand) => arithmetic_cells.cell(operand)?.cloned(),
+                        ResultSource::Aggregation { aggregation, group } => Some(
+                            self.group_result_cells(aggregation, &aggregation_cells)?
+                                .0
+                                .get(group)
+                                .cloned()
+                                .ok_or(Error::Synthesis)?,
+                        ),
+                        ResultSource::GroupKey { aggregation, group } => {
+                            let agg_op =
+                                self.aggregations.get(aggregation).ok_or(Error::Synthesis)?;
+                            let row = *agg_op
+                                .group_last_rows()
+                                .get(group)
+                                .ok_or(Error::Synthesis)?;
+                            agg_op
+                                .group_sort
+                                .and_then(|sort| sort_cells.get(sort))
+                                .and_then(|(sorted, _)| sorted.get(row))
+                                .cloned()
+                                .map(Some)
+                                .ok_or(Error::Synthesis)?
+                        }
+                        ResultSource::Check(check) => Some(
+                            range_check_cells
+                                .get(check)
+                                .cloned()
+                                .ok_or(Error::Synthesis)?,
+                        ),
+                        ResultSource::Payload { sort, payload, row } => Some(
+                            sort_cells
+                                .get(sort)
+                                .and_then(|(_, payloads)| payloads.get(payload))
+                                .and_then(|cells| cells.get(row))
+                                .cloned()
+                                .ok_or(Error::Synthesis)?,
+                        ),
+                    };
+                    if let Some(cell) = cell {
+                        copies.push((row_cell, cell));
+                    }
+                }
+                layouter.assign_region(
+                    || "result sources",
+                    |mut region| {
+                        for (row_cell, cell) in &copies {
+                            region.constrain_equal(row_cell.cell(), cell.cell())?;
+                        }
+                        for (row_cell, constant) in &constants {
+                            region.constrain_constant(row_cell.cell(), Fr::from(*constant))?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            if let Some(filter) = &result_commitment.filter {
+                let checks = filter
+                    .iter()
+                    .map(|&(check, bit)| Some((range_check_cells.get(check)?, bit)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(Error::Synthesis)?;
+                layouter.assign_region(
+                    || "result filter",
+                    |mut region| {
+                        for (check_cell, bit) in &checks {
+                            region.constrain_constant(check_cell.cell(), Fr::from(*bit))?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            if hashed_count {
+                let count_cell = match_count_cell.as_ref().ok_or(Error::Synthesis)?;
+                commitment_cell = poseidon_chip.hash_two(
+                    layouter.namespace(|| "result match count"),
+                    &commitment_cell,
+                    count_cell,
+                )?;
+            }
+            layouter.constrain_instance(commitment_cell.cell(), config.result_instance, 0)?;
+        }
+
+        // Window function operations
+        // A running sum copies its values from the window sort's payload
+        // cells, and asserted results are bound to their constants. A
+        // partitioned window ties the packed sort keys to the partition and
+        // ORDER BY payloads, and restarts at the Group-By Gate's boundaries
+        // over the partition keys.
+        for (i, window_op) in self.windows.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("windows[{}]", i));
+            let window_chip = window_chip.as_ref().ok_or(Error::Synthesis)?;
+            let (sorted, payloads) = match window_op.sort {
+                Some(sort) => {
+                    let (sorted, payloads) = sort_cells.get(sort).ok_or(Error::Synthesis)?;
+                    (sorted.as_slice(), payloads.as_slice())
+                }
+                None => (&[][..], &[][..]),
+            };
+            let payload = |index: usize| payloads.get(index).ok_or(Error::Synthesis);
+            let boundaries = match &window_op.partition {
+                Some(partition) => {
+                    let key_cells = payload(partition.key_payload)?;
+                    window_chip.verify_partition_keys(
+                        layouter.namespace(|| "partition keys"),
+                        sorted,
+                        key_cells,
+                        payload(partition.order_payload)?,
+                    )?;
+                    let boundary_cells = group_by_chip
+                        .as_ref()
+                        .ok_or(Error::Synthesis)?
+                        .group_and_verify_copied(
+                            layouter.namespace(|| "partitions"),
+                            &partition.keys,
+                            key_cells,
+                        )?;
+                    Some((partition.keys.as_slice(), boundary_cells))
+                }
+                None => None,
+            };
+            let result_cells = match (&window_op.function, &boundaries) {
+                (WindowFunction::RunningSum, Some((keys, boundary_cells))) => window_chip
+                    .running_sum_partitioned(
+                        layouter.namespace(|| "running sum"),
+                        &window_op.values,
+                        payload(window_op.sort_payload.ok_or(Error::Synthesis)?)?,
+                        keys,
+                        boundary_cells,
+                    )?,
+                (WindowFunction::RunningSum, None) => match window_op.sort_payload {
+                    Some(index) => window_chip.running_sum_copied(
+                        layouter.namespace(|| "running sum"),
+                        &window_op.values,
+                        payload(index)?,
+                    )?,
+                    None => window_chip
+                        .running_sum(layouter.namespace(|| "running sum"), &window_op.values)?,
+                },
+                (WindowFunction::RowNumber, Some((keys, boundary_cells))) => window_chip
+                    .row_number_partitioned(
+                        layouter.namespace(|| "row number"),
+                        keys,
+                        boundary_cells,
+                    )?,
+                (WindowFunction::RowNumber, None) => window_chip
+                    .row_number(layouter.namespace(|| "row number"), window_op.values.len())?,
+            };
+            if let Some(expected) = &window_op.expected {
+                if expected.len() != result_cells.len() {
+                    return Err(Error::Synthesis);
+                }
+                layouter.assign_region(
+                    || "expected results",
+                    |mut region| {
+                        for (cell, &value) in result_cells.iter().zip(expected) {
+                            region.constrain_constant(cell.cell(), Fr::from(value))?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
         }
 
         Ok(())
     }
 }
+
+impl Circuit<Fr> for JoinFreeCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self(self.0.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_features(meta, 8, ConfigFeatures { join: false })
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.0.synthesize(config, layouter)
+    }
+}
+
+impl Circuit<Fr> for V1Circuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = V1;
+
+    /// The operations are kept: `V1` measures the regions by synthesizing
+    /// this circuit, so it must have the same regions as the circuit laid out
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphCircuit::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.0.synthesize(config, layouter)
+    }
+}
@@ -0,0 +1,382 @@
+use ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::{GateConfigs, PoneglyphConfig};
+use super::merkle::{leaf_hash, MerkleChip, MerklePath, MerkleTree};
+use super::poseidon::PoseidonChip;
+use super::sort::SortChip;
+
+type Cell = AssignedCell<Fr, Fr>;
+
+/// Sorted distinct keys of a column
+fn sorted_keys(keys: &[u64]) -> Vec<u64> {
+    let mut keys = keys.to_vec();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+/// Merkle tree over the sorted distinct keys (`leaf_hash([key])`); its root
+/// is the commitment a non-membership proof is checked against
+///
+/// Leaf 0 is a zero leaf standing below the smallest key, and at least one
+/// zero (padding) leaf follows the largest key, so every u64 that is not a
+/// key lies between two adjacent leaves.
+pub fn key_tree(keys: &[u64]) -> MerkleTree {
+    let leaves = std::iter::once(Fr::ZERO)
+        .chain(sorted_keys(keys).iter().map(|&key| leaf_hash(&[key])))
+        .chain(std::iter::once(Fr::ZERO))
+        .collect();
+    MerkleTree::new(leaves)
+}
+
+/// Non-Membership Gate Configuration
+/// Binds the two adjacent key tree leaves around a target to the bounds of
+/// its order check; row 0 holds the lower neighbour, row 1 the upper
+///
+/// # Column Allocation
+///
+/// - `flag_column`: 1 if the neighbour is a key, 0 for a zero leaf (advice[0])
+/// - `key_column`: The neighbour key (advice[1])
+/// - `hash_column`: `leaf_hash([key])` (advice[2])
+/// - `leaf_column`: The opened leaf (advice[3])
+/// - `bound_column`: Bound of the target (advice[4])
+/// - `index_column`: The leaf's index in the key tree (advice[5])
+///
+/// # Constraints
+///
+/// 1. **Boolean**: `flag · (1 - flag) = 0`
+/// 2. **Leaf**: `leaf = flag · hash`
+/// 3. **Lower Bound**: `bound = flag · (key + 1)`; a zero leaf below the
+///    target must be leaf 0: `(1 - flag) · index = 0`
+/// 4. **Upper Bound**: `bound = flag · (key - 1) + (1 - flag) · (2^64 - 1)`;
+///    the leaves are adjacent: `index = index_prev + 1`
+///
+/// # Note
+///
+/// - Columns are shared with the other gates (used in different rows)
+#[derive(Clone, Debug)]
+pub struct NonMembershipConfig {
+    pub flag_column: Column<Advice>,
+    pub key_column: Column<Advice>,
+    pub hash_column: Column<Advice>,
+    pub leaf_column: Column<Advice>,
+    pub bound_column: Column<Advice>,
+    pub index_column: Column<Advice>,
+
+    // Selectors for the lower (row 0) and upper (row 1) neighbour
+    pub lower_selector: Selector,
+    pub upper_selector: Selector,
+}
+
+/// Non-Membership Chip
+pub struct NonMembershipChip {
+    config: NonMembershipConfig,
+}
+
+impl NonMembershipChip {
+    /// Create a new NonMembershipChip
+    pub fn new(config: NonMembershipConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Non-Membership Gate
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        config: &PoneglyphConfig,
+    ) -> NonMembershipConfig {
+        let flag_column = config.advice[0];
+        let key_column = config.advice[1];
+        let hash_column = config.advice[2];
+        let leaf_column = config.advice[3];
+        let bound_column = config.advice[4];
+        let index_column = config.advice[5];
+
+        let lower_selector = meta.selector();
+        let upper_selector = meta.selector();
+
+        meta.create_gate("non-membership lower", |meta| {
+            let s = meta.query_selector(lower_selector);
+            let flag = meta.query_advice(flag_column, Rotation::cur());
+            let key = meta.query_advice(key_column, Rotation::cur());
+            let hash = meta.query_advice(hash_column, Rotation::cur());
+            let leaf = meta.query_advice(leaf_column, Rotation::cur());
+            let bound = meta.query_advice(bound_column, Rotation::cur());
+            let index = meta.query_advice(index_column, Rotation::cur());
+            let one = Expression::Constant(Fr::ONE);
+
+            vec![
+                s.clone() * flag.clone() * (one.clone() - flag.clone()),
+                s.clone() * (leaf - flag.clone() * hash),
+                s.clone() * (bound - flag.clone() * (key + one.clone())),
+                s * (one - flag) * index,
+            ]
+        });
+
+        meta.create_gate("non-membership upper", |meta| {
+            let s = meta.query_selector(upper_selector);
+            let flag = meta.query_advice(flag_column, Rotation::cur());
+            let key = meta.query_advice(key_column, Rotation::cur());
+            let hash = meta.query_advice(hash_column, Rotation::cur());
+            let leaf = meta.query_advice(leaf_column, Rotation::cur());
+            let bound = meta.query_advice(bound_column, Rotation::cur());
+            let index = meta.query_advice(index_column, Rotation::cur());
+            let index_prev = meta.query_advice(index_column, Rotation::prev());
+            let one = Expression::Constant(Fr::ONE);
+            let max = Expression::Constant(Fr::from(u64::MAX));
+
+            vec![
+                s.clone() * flag.clone() * (one.clone() - flag.clone()),
+                s.clone() * (leaf - flag.clone() * hash),
+                s.clone()
+                    * (bound - flag.clone() * (key - one.clone()) - (one.clone() - flag) * max),
+                s * (index - index_prev - one),
+            ]
+        });
+
+        NonMembershipConfig {
+            flag_column,
+            key_column,
+            hash_column,
+            leaf_column,
+            bound_column,
+            index_column,
+            lower_selector,
+            upper_selector,
+        }
+    }
+
+    /// Assign the lower and upper neighbour of a target
+    ///
+    /// # Parameters
+    ///
+    /// - `neighbours`: Each neighbour's key (None for a zero leaf), its key
+    ///   and hash cells (`PoseidonChip::hash_row`) and its leaf index
+    ///
+    /// # Returns
+    ///
+    /// Per neighbour: the leaf, bound and index cells
+    pub fn assign_neighbours(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        neighbours: [(Option<u64>, &Cell, &Cell, usize); 2],
+    ) -> Result<[(Cell, Cell, Cell); 2], Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "non-membership neighbours",
+            |mut region| {
+                let mut cells = Vec::with_capacity(2);
+                for (row, &(key, key_cell, hash_cell, index)) in neighbours.iter().enumerate() {
+                    let selector = if row == 0 {
+                        config.lower_selector
+                    } else {
+                        config.upper_selector
+                    };
+                    selector.enable(&mut region, row)?;
+
+                    let flag = Fr::from(key.is_some() as u64);
+                    region.assign_advice(
+                        || "flag",
+                        config.flag_column,
+                        row,
+                        || Value::known(flag),
+                    )?;
+                    key_cell.copy_advice(|| "key", &mut region, config.key_column, row)?;
+                    hash_cell.copy_advice(|| "hash", &mut region, config.hash_column, row)?;
+                    let leaf = region.assign_advice(
+                        || "leaf",
+                        config.leaf_column,
+                        row,
+                        || hash_cell.value().map(|hash| *hash * flag),
+                    )?;
+                    let bound = match (row, key) {
+                        (0, Some(key)) => Fr::from(key) + Fr::ONE,
+                        (0, None) => Fr::ZERO,
+                        (_, Some(key)) => Fr::from(key) - Fr::ONE,
+                        (_, None) => Fr::from(u64::MAX),
+                    };
+                    let bound = region.assign_advice(
+                        || "bound",
+                        config.bound_column,
+                        row,
+                        || Value::known(bound),
+                    )?;
+                    let index = region.assign_advice(
+                        || "index",
+                        config.index_column,
+                        row,
+                        || Value::known(Fr::from(index as u64)),
+                    )?;
+                    cells.push((leaf, bound, index));
+                }
+                cells.try_into().map_err(|_| Error::Synthesis)
+            },
+        )
+    }
+}
+
+/// Non-Membership Circuit
+/// Proves a target value is not among the keys of a column, committed as
+/// the Merkle tree of its sorted keys (`key_tree`)
+///
+/// # Public Inputs
+///
+/// - `instance` (row 0): Root of the key tree
+/// - `result_instance` (row 0): The absent target
+///
+/// # Constraints
+///
+/// 1. **Adjacent Leaves**: The lower and upper neighbour leaves reach the
+///    root along `paths` (Merkle Gate and Poseidon Gate), at indices `i`
+///    and `i + 1` recomposed from the paths' bits (Non-Membership Gate)
+/// 2. **Bounds**: `lower < target < upper`, proven by the Sort Gate's order
+///    check over the adjacent rows `(lower + 1, target, upper - 1)`; a zero
+///    leaf bounds nothing, and only leaf 0 may be a zero lower neighbour
+///
+/// # Note
+///
+/// The keys are sorted and distinct in the tree, so nothing lies between
+/// two adjacent leaves. The zero leaves stand below the smallest and above
+/// the largest key; a zero lower neighbour is only accepted at index 0, so
+/// the padding after the keys cannot pass for an empty range. The root
+/// must have been computed from the column's keys (`key_tree`); the
+/// circuit does not tie it to the row commitment of `MerkleTree::from_rows`.
+#[derive(Clone, Debug)]
+pub struct NonMembershipCircuit {
+    /// Largest key below the target (None below the smallest key)
+    pub lower: Option<u64>,
+    /// Smallest key above the target (None above the largest key)
+    pub upper: Option<u64>,
+    /// Paths from the lower and upper neighbour leaves to the root
+    pub paths: [MerklePath; 2],
+    pub target: u64,
+}
+
+impl NonMembershipCircuit {
+    /// Circuit proving `target` is not among `keys`, and its key tree root
+    /// (None if `target` is a key)
+    pub fn new(keys: &[u64], target: u64) -> Option<(Self, Fr)> {
+        let keys = sorted_keys(keys);
+        // Key i is leaf i + 1, so the neighbours are leaves `position` and
+        // `position + 1`
+        let position = keys.binary_search(&target).err()?;
+        let tree = key_tree(&keys);
+        let circuit = Self {
+            lower: position.checked_sub(1).map(|i| keys[i]),
+            upper: keys.get(position).copied(),
+            paths: [tree.path(position)?, tree.path(position + 1)?],
+            target,
+        };
+        Some((circuit, tree.root()))
+    }
+}
+
+impl Circuit<Fr> for NonMembershipCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let path = |path: &MerklePath| MerklePath {
+            index: 0,
+            siblings: vec![Fr::ZERO; path.siblings.len()],
+        };
+        Self {
+            lower: None,
+            upper: None,
+            paths: [path(&self.paths[0]), path(&self.paths[1])],
+            target: 0,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        // The order check decomposes its diffs into looked-up chunks
+        config.load_lookup_table(&mut layouter)?;
+
+        let poseidon_chip = PoseidonChip::new(gates.poseidon);
+        let merkle_chip = MerkleChip::new(gates.merkle);
+        let sort_chip = SortChip::new(gates.sort);
+        let non_membership_chip = NonMembershipChip::new(gates.non_membership);
+
+        // Neighbour keys and their leaf hashes (a zero leaf hashes key 0,
+        // which its flag discards)
+        let (lower_hash, lower_cells) = poseidon_chip.hash_row(
+            layouter.namespace(|| "lower key"),
+            &[self.lower.unwrap_or(0)],
+        )?;
+        let (upper_hash, upper_cells) = poseidon_chip.hash_row(
+            layouter.namespace(|| "upper key"),
+            &[self.upper.unwrap_or(0)],
+        )?;
+        let [(lower_leaf, lower_bound, lower_index), (upper_leaf, upper_bound, upper_index)] =
+            non_membership_chip.assign_neighbours(
+                layouter.namespace(|| "neighbours"),
+                [
+                    (
+                        self.lower,
+                        &lower_cells[0],
+                        &lower_hash,
+                        self.paths[0].index,
+                    ),
+                    (
+                        self.upper,
+                        &upper_cells[0],
+                        &upper_hash,
+                        self.paths[1].index,
+                    ),
+                ],
+            )?;
+
+        // Bounds: (lower + 1, target, upper - 1) is sorted
+        let bounds = [
+            self.lower.map_or(0, |key| key.wrapping_add(1)),
+            self.target,
+            self.upper.map_or(u64::MAX, |key| key.wrapping_sub(1)),
+        ];
+        let sorted = sort_chip.sort_and_verify(
+            layouter.namespace(|| "bounds contain target"),
+            bounds.iter().map(|&value| Value::known(value)).collect(),
+            bounds.to_vec(),
+        )?;
+
+        // Adjacent leaves: both reach the committed root at their indices
+        let (lower_root, lower_path_index) = merkle_chip.verify_indexed_path(
+            layouter.namespace(|| "lower path"),
+            &poseidon_chip,
+            &lower_leaf,
+            &self.paths[0],
+        )?;
+        let (upper_root, upper_path_index) = merkle_chip.verify_indexed_path(
+            layouter.namespace(|| "upper path"),
+            &poseidon_chip,
+            &upper_leaf,
+            &self.paths[1],
+        )?;
+        layouter.assign_region(
+            || "neighbour bounds",
+            |mut region| {
+                region.constrain_equal(sorted[0].cell(), lower_bound.cell())?;
+                region.constrain_equal(sorted[2].cell(), upper_bound.cell())?;
+                region.constrain_equal(lower_path_index.cell(), lower_index.cell())?;
+                region.constrain_equal(upper_path_index.cell(), upper_index.cell())?;
+                region.constrain_equal(lower_root.cell(), upper_root.cell())
+            },
+        )?;
+        layouter.constrain_instance(lower_root.cell(), config.instance, 0)?;
+        layouter.constrain_instance(sorted[1].cell(), config.result_instance, 0)?;
+
+        Ok(())
+    }
+}
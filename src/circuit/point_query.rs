@@ -0,0 +1,104 @@
+use ff::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::{GateConfigs, PoneglyphConfig};
+use super::merkle::{MerkleChip, MerklePath};
+use super::poseidon::PoseidonChip;
+
+/// Point Query Circuit
+/// Proves `SELECT result FROM table WHERE key = value` for one row of a
+/// Merkle-committed table (`MerkleTree::from_rows`), without the other rows
+///
+/// # Public Inputs
+///
+/// - `instance` (row 0): Merkle root of the table
+/// - `result_instance` (row 0): `row[result_column]`
+///
+/// # Constraints
+///
+/// 1. **Membership**: `leaf_hash(row)` reaches the root along `path`
+///    (Merkle Gate and Poseidon Gate)
+/// 2. **Predicate**: `row[key_column] = key` (`key` is a circuit constant)
+///
+/// # Note
+///
+/// The rows are `hash_two`-ed up `path.siblings.len()` levels, so the cost
+/// grows with the log of the table size instead of the row count. Keys
+/// are assumed unique; with duplicate keys any matching row can be proven.
+#[derive(Clone, Debug)]
+pub struct PointQueryCircuit {
+    /// Cells of the proven row, in table column order
+    pub row: Vec<u64>,
+    /// Path from the row's leaf to the root
+    pub path: MerklePath,
+    /// Column compared with `key`
+    pub key_column: usize,
+    pub key: u64,
+    /// Column returned as the query result
+    pub result_column: usize,
+}
+
+impl PointQueryCircuit {
+    /// Query result: the value of `result_column` (None if out of range)
+    pub fn result(&self) -> Option<u64> {
+        self.row.get(self.result_column).copied()
+    }
+}
+
+impl Circuit<Fr> for PointQueryCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            row: vec![0; self.row.len()],
+            path: MerklePath {
+                index: 0,
+                siblings: vec![Fr::ZERO; self.path.siblings.len()],
+            },
+            key_column: self.key_column,
+            key: self.key,
+            result_column: self.result_column,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let poseidon_chip = PoseidonChip::new(gates.poseidon);
+        let merkle_chip = MerkleChip::new(gates.merkle);
+
+        // Row leaf and its cells
+        let (leaf, cells) = poseidon_chip.hash_row(layouter.namespace(|| "row"), &self.row)?;
+        let key_cell = cells.get(self.key_column).ok_or(Error::Synthesis)?;
+        let result_cell = cells.get(self.result_column).ok_or(Error::Synthesis)?;
+
+        // Predicate: row[key_column] = key
+        layouter.assign_region(
+            || "point query key",
+            |mut region| region.constrain_constant(key_cell.cell(), Fr::from(self.key)),
+        )?;
+
+        // Membership: the leaf reaches the committed root
+        let root = merkle_chip.verify_path(
+            layouter.namespace(|| "merkle path"),
+            &poseidon_chip,
+            &leaf,
+            &self.path,
+        )?;
+        layouter.constrain_instance(root.cell(), config.instance, 0)?;
+        layouter.constrain_instance(result_cell.cell(), config.result_instance, 0)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,531 @@
+use std::sync::OnceLock;
+
+use ff::{Field, PrimeField};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::PoneglyphConfig;
+
+/// Assigned cell of the Pallas base field
+type Cell = AssignedCell<Fr, Fr>;
+
+/// State width (rate 2, capacity 1)
+pub const WIDTH: usize = 3;
+/// Full rounds (half before, half after the partial rounds)
+pub const FULL_ROUNDS: usize = 8;
+/// Partial rounds
+pub const PARTIAL_ROUNDS: usize = 56;
+const ROUNDS: usize = FULL_ROUNDS + PARTIAL_ROUNDS;
+
+/// Poseidon Parameters
+/// x^5 S-box, width 3, 8 full and 56 partial rounds over the Pallas base field
+/// (128-bit security)
+///
+/// # Generation
+///
+/// - Round constants: Grain LFSR of the Poseidon paper (Appendix F),
+///   seeded with (prime field, x^alpha S-box, 255-bit field, t = 3, R_F = 8,
+///   R_P = 56), rejection-sampled field elements
+/// - MDS matrix: Cauchy matrix `M[i][j] = 1 / (x_i + y_j)` with
+///   `x_i = i`, `y_j = t + j`
+#[derive(Clone, Debug)]
+pub struct PoseidonParams {
+    pub round_constants: Vec<[Fr; WIDTH]>,
+    pub mds: [[Fr; WIDTH]; WIDTH],
+}
+
+impl PoseidonParams {
+    /// Parameters shared by the native hash and the Poseidon Gate (generated once)
+    pub fn get() -> &'static PoseidonParams {
+        static PARAMS: OnceLock<PoseidonParams> = OnceLock::new();
+        PARAMS.get_or_init(Self::generate)
+    }
+
+    fn generate() -> Self {
+        let mut grain = Grain::new();
+        let round_constants = (0..ROUNDS)
+            .map(|_| [(); WIDTH].map(|_| grain.next_field_element()))
+            .collect();
+
+        let mut mds = [[Fr::ZERO; WIDTH]; WIDTH];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = Fr::from((i + WIDTH + j) as u64).invert().unwrap();
+            }
+        }
+
+        Self {
+            round_constants,
+            mds,
+        }
+    }
+}
+
+/// Is round `round` a full round (S-box on every state element)?
+fn is_full_round(round: usize) -> bool {
+    !(FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS).contains(&round)
+}
+
+/// Poseidon permutation (native)
+pub fn permute(state: &mut [Fr; WIDTH]) {
+    let params = PoseidonParams::get();
+    for (round, constants) in params.round_constants.iter().enumerate() {
+        for (value, constant) in state.iter_mut().zip(constants) {
+            *value += constant;
+        }
+        if is_full_round(round) {
+            for value in state.iter_mut() {
+                *value = value.pow_vartime([5]);
+            }
+        } else {
+            state[0] = state[0].pow_vartime([5]);
+        }
+        *state = mds_mul(&params.mds, state);
+    }
+}
+
+fn mds_mul(mds: &[[Fr; WIDTH]; WIDTH], state: &[Fr; WIDTH]) -> [Fr; WIDTH] {
+    let mut out = [Fr::ZERO; WIDTH];
+    for (out, row) in out.iter_mut().zip(mds) {
+        *out = row.iter().zip(state).map(|(m, s)| *m * s).sum();
+    }
+    out
+}
+
+/// Capacity element of a two-element hash (length 2 as domain separation)
+fn capacity() -> Fr {
+    Fr::from_u128(2 << 64)
+}
+
+/// Hash of two field elements (native)
+/// One permutation of `[a, b, capacity]`; the result is the first state element
+pub fn hash_two(a: Fr, b: Fr) -> Fr {
+    let mut state = [a, b, capacity()];
+    permute(&mut state);
+    state[0]
+}
+
+/// Hash chain of a sequence (native): `h = s_0`, then `h = hash_two(h, s_i)`
+/// for each following element (0 for an empty sequence)
+pub fn hash_chain(values: impl IntoIterator<Item = Fr>) -> Fr {
+    let mut values = values.into_iter();
+    let first = values.next().unwrap_or(Fr::ZERO);
+    values.fold(first, hash_two)
+}
+
+/// Grain LFSR of the Poseidon reference implementation
+struct Grain {
+    bits: Vec<bool>,
+}
+
+impl Grain {
+    fn new() -> Self {
+        let mut bits = Vec::with_capacity(80);
+        let mut push = |value: u64, width: usize| {
+            bits.extend((0..width).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        push(1, 2); // prime field
+        push(0, 4); // x^alpha S-box
+        push(Fr::NUM_BITS as u64, 12);
+        push(WIDTH as u64, 12);
+        push(FULL_ROUNDS as u64, 10);
+        push(PARTIAL_ROUNDS as u64, 10);
+        push((1 << 30) - 1, 30);
+
+        let mut grain = Self { bits };
+        for _ in 0..160 {
+            grain.next_raw_bit();
+        }
+        grain
+    }
+
+    fn next_raw_bit(&mut self) -> bool {
+        let b = &self.bits;
+        let bit = b[62] ^ b[51] ^ b[38] ^ b[23] ^ b[13] ^ b[0];
+        self.bits.remove(0);
+        self.bits.push(bit);
+        bit
+    }
+
+    /// Output bit: of each pair of raw bits, the second is kept if the first is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.next_raw_bit();
+            let bit = self.next_raw_bit();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// Next field element: 255 bits (most significant first), rejected if >= p
+    fn next_field_element(&mut self) -> Fr {
+        loop {
+            let mut repr = [0u8; 32];
+            for i in (0..Fr::NUM_BITS as usize).rev() {
+                if self.next_bit() {
+                    repr[i / 8] |= 1 << (i % 8);
+                }
+            }
+            if let Some(element) = Option::from(Fr::from_repr(repr)) {
+                return element;
+            }
+        }
+    }
+}
+
+/// Poseidon Gate Configuration
+/// One permutation round per row: the state in `state` at the round's row,
+/// the next state in the row below
+///
+/// # Column Allocation
+///
+/// - `state`: State elements (advice[0-2])
+/// - `square`: `(state + rc)^2` of each element (advice[3-5])
+/// - `sbox`: `(state + rc)^5` of each element (advice[6-8])
+/// - `round_constants`: Round constants (fixed[0], fixed[1] and one own column)
+///
+/// # Constraints
+///
+/// 1. **S-box**: `square = (state + rc)^2`, `sbox = square^2 * (state + rc)`
+///    (every element in full rounds, the first in partial rounds)
+/// 2. **MDS**: `next[i] = sum_j M[i][j] * sbox[j]` (partial rounds use
+///    `state[j] + rc[j]` for j > 0)
+///
+/// # Note
+///
+/// The S-box is split over `square` and `sbox` so that the gate degree
+/// stays at 4. Columns are shared with the other gates (used in different rows).
+#[derive(Clone, Debug)]
+pub struct PoseidonConfig {
+    pub state: [Column<Advice>; WIDTH],
+    pub square: [Column<Advice>; WIDTH],
+    pub sbox: [Column<Advice>; WIDTH],
+    pub round_constants: [Column<Fixed>; WIDTH],
+
+    // Selectors
+    pub full_round_selector: Selector,
+    pub partial_round_selector: Selector,
+}
+
+/// Poseidon Chip
+pub struct PoseidonChip {
+    config: PoseidonConfig,
+}
+
+impl PoseidonChip {
+    /// Create a new PoseidonChip
+    pub fn new(config: PoseidonConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Poseidon Gate
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, config: &PoneglyphConfig) -> PoseidonConfig {
+        let state = [config.advice[0], config.advice[1], config.advice[2]];
+        let square = [config.advice[3], config.advice[4], config.advice[5]];
+        let sbox = [config.advice[6], config.advice[7], config.advice[8]];
+        let round_constants = [config.fixed[0], config.fixed[1], meta.fixed_column()];
+
+        let full_round_selector = meta.selector();
+        let partial_round_selector = meta.selector();
+        let mds = PoseidonParams::get().mds;
+
+        for (name, selector, full) in [
+            ("poseidon full round", full_round_selector, true),
+            ("poseidon partial round", partial_round_selector, false),
+        ] {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let mut constraints = Vec::new();
+
+                // Input of the MDS multiplication for each element
+                let mut mixed: Vec<Expression<Fr>> = Vec::with_capacity(WIDTH);
+                for j in 0..WIDTH {
+                    let x = meta.query_advice(state[j], Rotation::cur())
+                        + meta.query_fixed(round_constants[j]);
+                    if full || j == 0 {
+                        let sq = meta.query_advice(square[j], Rotation::cur());
+                        let x5 = meta.query_advice(sbox[j], Rotation::cur());
+                        constraints.push(s.clone() * (sq.clone() - x.clone() * x.clone()));
+                        constraints.push(s.clone() * (x5.clone() - sq.clone() * sq * x));
+                        mixed.push(x5);
+                    } else {
+                        mixed.push(x);
+                    }
+                }
+
+                for (i, row) in mds.iter().enumerate() {
+                    let next = meta.query_advice(state[i], Rotation::next());
+                    let sum = row
+                        .iter()
+                        .zip(&mixed)
+                        .fold(Expression::Constant(Fr::ZERO), |acc, (m, x)| {
+                            acc + Expression::Constant(*m) * x.clone()
+                        });
+                    constraints.push(s.clone() * (next - sum));
+                }
+                constraints
+            });
+        }
+
+        PoseidonConfig {
+            state,
+            square,
+            sbox,
+            round_constants,
+            full_round_selector,
+            partial_round_selector,
+        }
+    }
+
+    /// Hash two assigned cells (same result as the native `hash_two`)
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the hash (state[0] after the last round)
+    pub fn hash_two(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: &AssignedCell<Fr, Fr>,
+        b: &AssignedCell<Fr, Fr>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let config = &self.config;
+        let params = PoseidonParams::get();
+
+        layouter.assign_region(
+            || "poseidon hash",
+            |mut region| {
+                // Row 0: [a, b, capacity]
+                let mut cells = [
+                    a.copy_advice(|| "input a", &mut region, config.state[0], 0)?,
+                    b.copy_advice(|| "input b", &mut region, config.state[1], 0)?,
+                    region.assign_advice_from_constant(
+                        || "capacity",
+                        config.state[2],
+                        0,
+                        capacity(),
+                    )?,
+                ];
+                let mut state: Vec<Value<Fr>> =
+                    cells.iter().map(|cell| cell.value().copied()).collect();
+
+                for (round, constants) in params.round_constants.iter().enumerate() {
+                    let full = is_full_round(round);
+                    if full {
+                        config.full_round_selector.enable(&mut region, round)?;
+                    } else {
+                        config.partial_round_selector.enable(&mut region, round)?;
+                    }
+
+                    let mut mixed = Vec::with_capacity(WIDTH);
+                    for j in 0..WIDTH {
+                        region.assign_fixed(
+                            || format!("rc {} {}", round, j),
+                            config.round_constants[j],
+                            round,
+                            || Value::known(constants[j]),
+                        )?;
+                        let x = state[j].map(|s| s + constants[j]);
+                        if full || j == 0 {
+                            let sq = x.map(|x| x.square());
+                            let x5 = sq.zip(x).map(|(sq, x)| sq.square() * x);
+                            region.assign_advice(
+                                || format!("square {} {}", round, j),
+                                config.square[j],
+                                round,
+                                || sq,
+                            )?;
+                            region.assign_advice(
+                                || format!("sbox {} {}", round, j),
+                                config.sbox[j],
+                                round,
+                                || x5,
+                            )?;
+                            mixed.push(x5);
+                        } else {
+                            mixed.push(x);
+                        }
+                    }
+
+                    for i in 0..WIDTH {
+                        let next = params.mds[i]
+                            .iter()
+                            .zip(&mixed)
+                            .fold(Value::known(Fr::ZERO), |acc, (m, x)| {
+                                acc + x.map(|x| *m * x)
+                            });
+                        cells[i] = region.assign_advice(
+                            || format!("state {} {}", round + 1, i),
+                            config.state[i],
+                            round + 1,
+                            || next,
+                        )?;
+                        state[i] = next;
+                    }
+                }
+
+                Ok(cells[0].clone())
+            },
+        )
+    }
+
+    /// Commit to result rows: `h = hash(rows, columns)`, then
+    /// `h = hash(h, cell)` for each cell in row-major order
+    /// (same result as `ResultCommitment::value`)
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the commitment and the cells of the rows' values in
+    /// row-major order, or `Error::Synthesis` if the rows differ in length
+    pub fn commit_rows(
+        &self,
+        layouter: impl Layouter<Fr>,
+        rows: &[Vec<u64>],
+    ) -> Result<(Cell, Vec<Cell>), Error> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != columns) {
+            return Err(Error::Synthesis);
+        }
+
+        let mut elements = vec![
+            Element::Constant(rows.len() as u64),
+            Element::Constant(columns as u64),
+        ];
+        elements.extend(rows.iter().flatten().map(|&value| Element::Witness(value)));
+        self.hash_elements(layouter, "result rows", &elements)
+    }
+
+    /// Hash a table row: `h = column count`, then `h = hash(h, cell)` for
+    /// each cell (same result as `merkle::leaf_hash`)
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the hash and the cells of the row's values
+    pub fn hash_row(
+        &self,
+        layouter: impl Layouter<Fr>,
+        row: &[u64],
+    ) -> Result<(Cell, Vec<Cell>), Error> {
+        let mut elements = vec![Element::Constant(row.len() as u64)];
+        elements.extend(row.iter().map(|&value| Element::Witness(value)));
+        self.hash_elements(layouter, "row", &elements)
+    }
+
+    /// Commit to key-value pairs: `h = pair count`, then `h = hash(h, key)`
+    /// and `h = hash(h, value)` for each pair (same result as
+    /// `DatabaseCommitment::new` with `HashScheme::Poseidon`)
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the commitment and the cells of each pair's key and value
+    pub fn commit_pairs(
+        &self,
+        layouter: impl Layouter<Fr>,
+        data: &[(u64, u64)],
+    ) -> Result<(Cell, Vec<[Cell; 2]>), Error> {
+        let mut elements = vec![Element::Constant(data.len() as u64)];
+        for &(key, value) in data {
+            elements.extend([Element::Witness(key), Element::Witness(value)]);
+        }
+        let (hash, cells) = self.hash_elements(layouter, "key-value pairs", &elements)?;
+
+        let pairs = cells
+            .chunks_exact(2)
+            .map(|pair| [pair[0].clone(), pair[1].clone()])
+            .collect();
+        Ok((hash, pairs))
+    }
+
+    /// Commit to database columns: `h = column count`, then for each column
+    /// `h = hash(h, length)` followed by `h = hash(h, cell)` for its cells
+    /// (same result as each of `CommittedDatabase::values`)
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the commitment and the cells of each column's values
+    /// (for copying into the gates that read them)
+    pub fn commit_columns(
+        &self,
+        layouter: impl Layouter<Fr>,
+        columns: &[Vec<u64>],
+    ) -> Result<(Cell, Vec<Vec<Cell>>), Error> {
+        let mut elements = vec![Element::Constant(columns.len() as u64)];
+        for column in columns {
+            elements.push(Element::Constant(column.len() as u64));
+            elements.extend(column.iter().map(|&value| Element::Witness(value)));
+        }
+        let (hash, mut cells) = self.hash_elements(layouter, "database columns", &elements)?;
+
+        // Split the witnessed cells back into columns
+        let mut column_cells = Vec::with_capacity(columns.len());
+        for column in columns.iter().rev() {
+            column_cells.push(cells.split_off(cells.len() - column.len()));
+        }
+        column_cells.reverse();
+        Ok((hash, column_cells))
+    }
+
+    /// Assign a sequence in one region (state[0], one element per row) and
+    /// hash it with `hash_chain`
+    ///
+    /// # Returns
+    ///
+    /// Cell holding the hash and the cells of the witnessed elements, in order
+    fn hash_elements(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        name: &str,
+        elements: &[Element],
+    ) -> Result<(Cell, Vec<Cell>), Error> {
+        let column = self.config.state[0];
+        let (cells, witnesses) = layouter.assign_region(
+            || name,
+            |mut region| {
+                let mut cells = Vec::with_capacity(elements.len());
+                let mut witnesses = Vec::new();
+                for (row, element) in elements.iter().enumerate() {
+                    match *element {
+                        Element::Constant(value) => {
+                            cells.push(region.assign_advice_from_constant(
+                                || format!("constant {}", row),
+                                column,
+                                row,
+                                Fr::from(value),
+                            )?)
+                        }
+                        Element::Witness(value) => {
+                            let cell = region.assign_advice(
+                                || format!("value {}", row),
+                                column,
+                                row,
+                                || Value::known(Fr::from(value)),
+                            )?;
+                            witnesses.push(cell.clone());
+                            cells.push(cell);
+                        }
+                    }
+                }
+                Ok((cells, witnesses))
+            },
+        )?;
+
+        let (first, rest) = cells.split_first().ok_or(Error::Synthesis)?;
+        let mut hash = first.clone();
+        for cell in rest {
+            hash = self.hash_two(layouter.namespace(|| "hash chain"), &hash, cell)?;
+        }
+        Ok((hash, witnesses))
+    }
+}
+
+/// Element of a hashed sequence
+enum Element {
+    /// Fixed by the circuit (dimensions)
+    Constant(u64),
+    /// Witnessed value (result or database cell)
+    Witness(u64),
+}
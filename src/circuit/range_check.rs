@@ -1,10 +1,13 @@
 use halo2_proofs::{
-    circuit::{AssignedCell, Layouter, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector, TableColumn,
+    },
     poly::Rotation,
 };
 use pasta_curves::pallas::Base as Fr;
-use ff::Field;
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
 
 use super::config::PoneglyphConfig;
 
@@ -19,30 +22,64 @@ use super::config::PoneglyphConfig;
 /// - `diff_column`: For diff value (advice[8], same as check_column, different row)
 /// - `threshold_column`: For threshold (t) value (fixed[0])
 /// - `u_column`: For u value (fixed[1])
-/// - `lookup_table`: 0-255 lookup table (TableColumn)
+/// - `threshold_advice_column`: For witnessed threshold (t) value (advice[10])
+/// - `u_advice_column`: For witnessed u value (advice[11])
+///   (advice[0-1] when Join is not configured, see `PoneglyphConfig::shared_advice`)
+/// - `lookup_table`: 0..2^lookup_bits lookup table (TableColumn)
 /// 
 /// # Constraints
 /// 
-/// 1. **Lookup Constraint**: Checks that each chunk is in range 0-255
-/// 2. **Decomposition Sum**: Verifies formula `N = Σ c_i · 2^(8i)`
-/// 3. **x < t Constraint**: `check + (x - t) - u ∈ [0, u)` check
+/// 1. **Lookup Constraint**: Checks that each chunk is in range 0..2^lookup_bits
+/// 2. **Decomposition Sum**: Verifies formula `N = Σ c_i · 2^(8i)` (`2^(16i)` with 16-bit chunks)
+/// 3. **x < t Constraint**: `check · u + (x - t) ∈ [0, u)` check
 ///    - Boolean check: `check * (1 - check) = 0`
-///    - Diff calculation: `diff = check + (x - t) - u`
-///    - Range check: `diff ∈ [0, u)` (with lookup table)
+///    - Diff calculation: `diff = check · u + (x - t)`
+///    - Range check: `diff` and `check · (u - 1 - diff)` are 64-bit
+///      non-negative (decomposed in the two rows after diff), so
+///      `diff ∈ [0, u)` when check = 1 and `x - t ∈ [0, 2^64)` when check = 0
+/// 4. **x < t Constraint (advice threshold)**: same as 3, with t and u read from advice cells
+/// 5. **x < t Comparison**: boolean `check` for either outcome, with
+///    `check · (t - 1 - x) + (1 - check) · (x - t) = Σ c_i · 2^(8i)` (64-bit non-negative)
+/// 6. **Match Count**: `count = prev_count + (x < upper) - (x < lower)` over
+///    two comparison rows per value (see `count_in_range`)
+/// 7. **lower <= x < upper**: one-hot outcome bits (in range, below, above)
+///    and two decompositions shared by both bounds (see `check_between`)
+/// 8. **Boolean x = t**: `x · (1 - x) = 0` and `check = 1 - t - x + 2·t·x`
+///    for a fixed t of 0 or 1, in one row without a decomposition (see `prove_bool`)
+/// 9. **Bit Count**: `count = prev_count + bit` over copied boolean bits
+///    (see `count_bits`)
+/// 10. **Bit AND / OR**: `out = a·b + op·(a + b - 2·a·b)` for boolean a and
+///     b and a fixed op (0 = AND, 1 = OR), see `combine_bits`
+/// 11. **Public Operand**: `(1 - kind)·(b - t) + kind·(a - t) = 0` for the
+///     operands of `a < b` and a public constant t and boolean kind, see
+///     `public_operands`
+/// 12. **x = t (advice threshold)**: `check · (x - t) = 0` and
+///     `1 - check - (x - t) · inv = 0` with inv witnessed in
+///     `u_advice_column`, so check = 1 exactly when x = t (see
+///     `check_equal_advice_copied`)
 /// 
 /// # Note
 /// 
 /// - `diff_column` and `check_column` share the same column (in different rows)
-/// - Works with u < 256 assumption (production note for u >= 256)
+/// - The check bit of constraint 3 is sound for any u: a wrong bit makes
+///   `x - t` or `t - 1 - x` negative, which fails the 64-bit decomposition.
+///   u only has to bound the gap of a true comparison (`t - x <= u`) for the
+///   honest witness to verify; it need not equal `t - x`
+/// - With `lookup_bits = 16` a 64-bit value uses 4 chunks (`chunk_columns[0-3]`)
+///   instead of 8; the decomposition still takes one row, so the saving is in
+///   lookup arguments (8 → 4), while the table grows from 256 to 65536 rows (k ≥ 17)
 #[derive(Clone, Debug)]
 pub struct RangeCheckConfig {
     // Advice columns for 8-bit chunks (8 columns)
     // advice[0-7] - Range Check chunk columns
     pub chunk_columns: [Column<Advice>; 8],
     
-    // Lookup table column (0-255) - TableColumn should be used
+    // Lookup table column (0..2^lookup_bits) - TableColumn should be used
     pub lookup_table: TableColumn,
     
+    // Chunk width in bits (8 or 16), see `PoneglyphConfig::lookup_bits`
+    pub lookup_bits: u8,
+    
     // Column for boolean check
     // advice[8] - check_column and diff_column share the same column
     pub check_column: Column<Advice>,
@@ -51,7 +88,7 @@ pub struct RangeCheckConfig {
     // advice[9] - x_column
     pub x_column: Column<Advice>,
     
-    // Column for diff value: diff = check + (x - t) - u
+    // Column for diff value: diff = check · u + (x - t)
     // Note: same column as check_column, different row (offset 1)
     // advice[8] - same column as check_column
     pub diff_column: Column<Advice>,
@@ -62,13 +99,137 @@ pub struct RangeCheckConfig {
     // fixed[1] - u_column
     pub u_column: Column<Fixed>,
     
+    // Advice columns for witnessed threshold (t) and u values
+    // (x < t where t is another column's value, e.g. spent < budget)
+    // advice[10] - threshold_advice_column, advice[11] - u_advice_column
+    pub threshold_advice_column: Column<Advice>,
+    pub u_advice_column: Column<Advice>,
+    
     // Selectors
     pub selector: Selector,
     pub less_than_selector: Selector,
+    pub less_than_advice_selector: Selector,
+    pub compare_selector: Selector,
+    pub count_selector: Selector,
+    pub count_start_selector: Selector,
+    pub between_selector: Selector,
+    pub bool_selector: Selector,
+    pub bit_count_selector: Selector,
+    pub bit_op_selector: Selector,
+    pub public_operand_selector: Selector,
+    pub equal_advice_selector: Selector,
     pub decomposition_selector: Selector,
-    pub diff_lookup_selector: Selector,
 }
 
+impl RangeCheckConfig {
+    /// Number of chunks of a 64-bit value (8 with 8-bit chunks, 4 with 16-bit chunks)
+    pub fn chunk_count(&self) -> usize {
+        64 / self.lookup_bits as usize
+    }
+    
+    /// Chunk columns in use (`chunk_columns[0..chunk_count]`)
+    pub fn active_chunk_columns(&self) -> &[Column<Advice>] {
+        &self.chunk_columns[..self.chunk_count()]
+    }
+    
+    /// i-th chunk of a 64-bit value
+    pub fn chunk(&self, value: u64, i: usize) -> u64 {
+        let bits = self.lookup_bits as usize;
+        (value >> (i * bits)) & ((1u64 << bits) - 1)
+    }
+}
+
+/// Field element of a value computed in the circuit (a running total, a
+/// product), checked to fit in the 64 bits the gates decompose
+///
+/// Range checks, sort diffs and comparisons split a value into chunks of
+/// 64 bits in total, so a wider value would be a field element they cannot
+/// validate. Inputs are `u64` and convert with `Fr::from`; values the
+/// circuit derives from them go through this guard, and one above
+/// `u64::MAX` is an `Error::Synthesis` instead of a field element.
+///
+/// # 128-bit Values
+///
+/// Only a SUM proves results above `u64::MAX`: its running results are
+/// split into two range checked 64-bit halves (see `AggregationConfig`).
+/// Everywhere else (window running totals, products, combined chunk
+/// partials) such a value is rejected.
+pub fn to_field_checked(value: u128) -> Result<Fr, Error> {
+    u64::try_from(value)
+        .map(Fr::from)
+        .map_err(|_| Error::Synthesis)
+}
+
+/// `to_field_checked` of a witness value (an unknown value stays unknown)
+pub fn value_to_field_checked(value: Value<u128>) -> Result<Value<Fr>, Error> {
+    let mut field = Ok(Value::unknown());
+    value.map(|value| field = to_field_checked(value).map(Value::known));
+    field
+}
+
+/// Source cells of an x < t check's (x, threshold), copied instead of assigned
+type SourceCells<'a> = (
+    Option<&'a AssignedCell<Fr, Fr>>,
+    Option<&'a AssignedCell<Fr, Fr>>,
+);
+
+/// Operand cells (a, b) of an a < b check
+type OperandCells = (AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>);
+
+/// Comparison of a value x with a threshold t (see `RangeCheckChip::prove_lt`
+/// and its siblings)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// x < t (strict)
+    #[default]
+    Lt,
+    /// x <= t
+    Le,
+    /// x > t (strict)
+    Gt,
+    /// x >= t
+    Ge,
+    /// x = t
+    Eq,
+    /// x = t for a boolean x and t (0 or 1), see `RangeCheckChip::prove_bool`
+    Bool,
+}
+
+impl Comparison {
+    /// Whether `x <op> t` holds
+    pub fn holds(self, x: u64, t: u64) -> bool {
+        match self {
+            Comparison::Lt => x < t,
+            Comparison::Le => x <= t,
+            Comparison::Gt => x > t,
+            Comparison::Ge => x >= t,
+            Comparison::Eq | Comparison::Bool => x == t,
+        }
+    }
+}
+
+/// Boolean combination of two bits (see `RangeCheckChip::combine_bits`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitOp {
+    /// a · b
+    And,
+    /// a + b - a · b
+    Or,
+}
+
+impl BitOp {
+    /// Combined bit of a and b
+    pub fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BitOp::And => a && b,
+            BitOp::Or => a || b,
+        }
+    }
+}
+
+/// 2^64, the (exclusive) upper bound of every u64 value
+const U64_END: u128 = 1 << 64;
+
 /// Range Check Chip
 /// Paper Section 4.1 implementation
 pub struct RangeCheckChip {
@@ -82,10 +243,22 @@ impl RangeCheckChip {
     }
     /// Configure the Range Check Gate
     /// Paper Section 4.1: 8-bit chunk decomposition and x < t constraint
+    /// 
+    /// # Note
+    /// 
+    /// Chunk width follows `config.lookup_bits` (8 or 16)
     pub fn configure(
         meta: &mut ConstraintSystem<Fr>,
         config: &PoneglyphConfig,
     ) -> RangeCheckConfig {
+        let lookup_bits = config.lookup_bits;
+        assert!(
+            lookup_bits == 8 || lookup_bits == 16,
+            "lookup_bits must be 8 or 16, got {}",
+            lookup_bits
+        );
+        let chunk_count = 64 / lookup_bits as usize;
+        
         // 8-bit chunk columns
         // Column allocation (see PoneglyphConfig documentation):
         // - advice[0-7]: Range Check chunk columns (for 8-bit decomposition)
@@ -111,10 +284,22 @@ impl RangeCheckChip {
         let diff_column = config.advice[8]; // same column as check_column, different row
         let threshold_column = config.fixed[0];
         let u_column = config.fixed[1];
+        // Witnessed threshold and u (shared with Join columns, used in different rows)
+        let threshold_advice_column = config.shared_advice(0);
+        let u_advice_column = config.shared_advice(1);
+        let less_than_advice_selector = meta.selector();
+        let compare_selector = meta.selector();
+        let count_selector = meta.selector();
+        let count_start_selector = meta.selector();
+        let between_selector = meta.selector();
+        let bool_selector = meta.selector();
+        let bit_count_selector = meta.selector();
+        let bit_op_selector = meta.selector();
+        let public_operand_selector = meta.selector();
+        let equal_advice_selector = meta.selector();
         let selector = config.range_check_selector;
         let less_than_selector = config.less_than_selector;
         let decomposition_selector = config.decomposition_selector;
-        let diff_lookup_selector = config.diff_lookup_selector;
         
         // Lookup constraint: Check that each chunk is in range 0-255
         // Paper Section 4.1: "Lookup Table" technique
@@ -122,31 +307,30 @@ impl RangeCheckChip {
         // Checks that each chunk is in range 0-255 using lookup table.
         // Chunks are assigned in the same row (row 1), so all chunks
         // are read with Rotation::cur() (must be in same row as selector).
-        meta.lookup(|meta| {
-            let s = meta.query_selector(selector); // query_selector is used for complex_selector
-            let one = Expression::Constant(Fr::ONE);
-            let mut constraints = Vec::new();
-            
-            // Lookup constraint for each chunk
-            // Chunks and value are in the same row (row 0)
-            // According to Halo2 example: selector * chunk + (1 - selector) * dummy_value
-            // We use 0 as dummy value (exists in lookup table, row 0)
-            // 
-            // Note: Selector is read with Rotation::cur(), so chunks must also
-            // be read with Rotation::cur() (must be in same row)
-            for chunk_col in chunk_columns.iter() {
-                // We must read chunks with Rotation::cur() (in row 0, same row as selector)
+        // Note: Each chunk needs its own lookup argument. Returning all 8
+        // (chunk, table) pairs from a single `meta.lookup` call would build one
+        // tuple lookup, which only passes when every chunk has the same value.
+        // Only the chunk columns in use get a lookup (4 with 16-bit chunks).
+        for chunk_col in chunk_columns[..chunk_count].iter() {
+            meta.lookup(|meta| {
+                let s = meta.query_selector(selector); // query_selector is used for complex_selector
+                let one = Expression::Constant(Fr::ONE);
+                
+                // Chunks and value are in the same row (row 1)
+                // According to Halo2 example: selector * chunk + (1 - selector) * dummy_value
+                // We use 0 as dummy value (exists in lookup table, row 0)
+                // 
+                // Note: Selector is read with Rotation::cur(), so chunks must also
+                // be read with Rotation::cur() (must be in same row)
                 let chunk = meta.query_advice(*chunk_col, Rotation::cur());
-                let not_selector = one.clone() - s.clone();
+                let not_selector = one - s.clone();
                 // selector * chunk + (1 - selector) * 0
                 // When selector = 1: chunk is looked up (must be in range 0-255)
                 // When selector = 0: 0 is looked up (exists in lookup table)
-                let lookup_expr = s.clone() * chunk + not_selector * Expression::Constant(Fr::ZERO);
-                constraints.push((lookup_expr, lookup_table));
-            }
-            
-            constraints
-        });
+                let lookup_expr = s * chunk + not_selector * Expression::Constant(Fr::ZERO);
+                vec![(lookup_expr, lookup_table)]
+            });
+        }
         
         // Decomposition sum constraint: N = Σ c_i · 2^(8i)
         // Paper Section 4.1: Bitwise decomposition correctness
@@ -161,14 +345,14 @@ impl RangeCheckChip {
             // Calculate Σ c_i · 2^(8i)
             // Chunks and value are in the same row (row 1)
             // Chunks are read with Rotation::cur() (row 1)
-            let sum = chunk_columns.iter().enumerate().fold(
+            let sum = chunk_columns[..chunk_count].iter().enumerate().fold(
                 Expression::Constant(Fr::ZERO),
                 |acc, (i, &chunk_col)| {
                     // We must read chunks with Rotation::cur() (row 1)
                     // Note: Since all chunks are in the same row (row 1),
                     // they are all read with Rotation::cur()
                     let chunk = meta.query_advice(chunk_col, Rotation::cur());
-                    let power = Expression::Constant(Fr::from(1u64 << (i * 8)));
+                    let power = Expression::Constant(Fr::from(1u64 << (i * lookup_bits as usize)));
                     acc + chunk * power
                 },
             );
@@ -177,13 +361,17 @@ impl RangeCheckChip {
             vec![s * (value - sum)]
         });
         
-        // x < t constraint: check + (x - t) - u ∈ [0, u)
+        // x < t constraint: check · u + (x - t) ∈ [0, u)
         // Paper Section 4.1: Range comparison constraint
         // 
         // This constraint performs x < t check:
         // 1. check must be boolean: check * (1 - check) = 0
-        // 2. diff = check + (x - t) - u must be calculated
-        // 3. diff ∈ [0, u) check must be done with lookup table
+        // 2. diff = check · u + (x - t) must be calculated
+        // 3. diff and check · (u - 1 - diff) are copied to x_column in rows
+        //    2 and 3, where the decomposition sum and chunk lookups prove
+        //    them 64-bit non-negative: with check = 1 that is diff ∈ [0, u),
+        //    i.e. x < t; with check = 0 it is x - t >= 0. Field values
+        //    wrapped around by a negative difference have no 64-bit chunks.
         meta.create_gate("x < t constraint", |meta| {
             let s = meta.query_selector(less_than_selector);
             let check = meta.query_advice(check_column, Rotation::cur());
@@ -195,52 +383,264 @@ impl RangeCheckChip {
             // check value must be 0 or 1
             let boolean_check = check.clone() * (Expression::Constant(Fr::ONE) - check.clone());
             
-            // Paper formula: diff = check + (x - t) - u
+            // Paper formula: diff = check · u + (x - t)
             // diff_column is same column as check_column, different row (offset 1)
             let diff = meta.query_advice(diff_column, Rotation::next());
-            let diff_expr = check.clone() + (x - t) - u.clone();
+            let diff_expr = check.clone() * u.clone() + (x - t);
+            
+            // Decomposed copies of diff and the slack u - 1 - diff (rows 2-3)
+            let diff_chunked = meta.query_advice(x_column, Rotation(2));
+            let slack_chunked = meta.query_advice(x_column, Rotation(3));
+            let slack = check.clone() * (u - Expression::Constant(Fr::ONE) - diff.clone());
             
             vec![
-                s.clone() * boolean_check, // check must be boolean
-                s.clone() * (diff - diff_expr), // diff = check + (x - t) - u
+                s.clone() * boolean_check,              // check must be boolean
+                s.clone() * (diff.clone() - diff_expr), // diff = check · u + (x - t)
+                s.clone() * (diff_chunked - diff),      // diff >= 0
+                s * (slack_chunked - slack),            // check = 1: diff < u
             ]
         });
         
-        // Lookup constraint for [0, u) range check
-        // Paper Section 4.1: diff ∈ [0, u) check must be done with lookup table
-        // 
-        // # Note
-        // 
-        // - Works with u < 256 assumption (checks diff directly with lookup table)
-        // - For u >= 256: We can divide diff into chunks and check that each chunk is in range 0-255,
-        //   but additional constraint is needed for diff < u check
-        // - For production: u >= 256 support can be added (with diff decomposition)
-        meta.lookup(|meta| {
-            let s = meta.query_selector(diff_lookup_selector);
-            let diff = meta.query_advice(diff_column, Rotation::cur());
+        // x < t constraint with witnessed threshold: check · u + (x - t) ∈ [0, u)
+        // Same as "x < t constraint", but t and u are read from advice cells
+        // so the threshold can be another column's value (e.g. spent < budget)
+        meta.create_gate("x < t constraint (advice threshold)", |meta| {
+            let s = meta.query_selector(less_than_advice_selector);
+            let check = meta.query_advice(check_column, Rotation::cur());
+            let x = meta.query_advice(x_column, Rotation::cur());
+            let t = meta.query_advice(threshold_advice_column, Rotation::cur());
+            let u = meta.query_advice(u_advice_column, Rotation::cur());
+            
+            // Boolean constraint: check * (1 - check) = 0
+            let boolean_check = check.clone() * (Expression::Constant(Fr::ONE) - check.clone());
+            
+            // diff = check · u + (x - t) (diff in next row, same as fixed threshold gate)
+            let diff = meta.query_advice(diff_column, Rotation::next());
+            let diff_expr = check.clone() * u.clone() + (x - t);
+            
+            // Decomposed diff and slack, as in the fixed threshold gate
+            let diff_chunked = meta.query_advice(x_column, Rotation(2));
+            let slack_chunked = meta.query_advice(x_column, Rotation(3));
+            let slack = check * (u - Expression::Constant(Fr::ONE) - diff.clone());
+            
+            vec![
+                s.clone() * boolean_check,
+                s.clone() * (diff.clone() - diff_expr),
+                s.clone() * (diff_chunked - diff),
+                s * (slack_chunked - slack),
+            ]
+        });
+        
+        // x < t comparison: check = 1 if x < t, check = 0 if x >= t
+        // Both outcomes are provable: the difference for the claimed outcome
+        // (t - 1 - x if check = 1, x - t if check = 0) must be a 64-bit
+        // non-negative number, i.e. equal to its 8-bit chunk decomposition.
+        // A wrong check makes that difference negative (a huge field element),
+        // which no 8-bit chunks can reconstruct.
+        // Chunks are in the same row and range checked by the chunk lookups
+        // (range_check_selector is enabled in the same row).
+        meta.create_gate("x < t comparison", |meta| {
+            let s = meta.query_selector(compare_selector);
+            let check = meta.query_advice(check_column, Rotation::cur());
+            let x = meta.query_advice(x_column, Rotation::cur());
+            let t = meta.query_fixed(threshold_column);
+            let one = Expression::Constant(Fr::ONE);
+            
+            // Boolean constraint: check * (1 - check) = 0
+            let boolean_check = check.clone() * (one.clone() - check.clone());
+            
+            // Difference for the claimed outcome
+            let diff =
+                check.clone() * (t.clone() - one.clone() - x.clone()) + (one - check) * (x - t);
+            
+            // Σ c_i · 2^(8i)
+            let sum = chunk_columns[..chunk_count].iter().enumerate().fold(
+                Expression::Constant(Fr::ZERO),
+                |acc, (i, &chunk_col)| {
+                    let chunk = meta.query_advice(chunk_col, Rotation::cur());
+                    acc + chunk * Expression::Constant(Fr::from(1u64 << (i * lookup_bits as usize)))
+                },
+            );
+            
+            vec![s.clone() * boolean_check, s * (diff - sum)]
+        });
+        
+        // Match count: number of values with lower <= x < upper
+        // The count starts at 0 (first row); each value then takes 3 rows:
+        // comparison x < upper (row -2), comparison x < lower (row -1) and the
+        // running count (row 0, in x_column, previous count at row -3).
+        // The value is kept iff (x < upper) - (x < lower) = 1 (lower <= upper).
+        meta.create_gate("match count start", |meta| {
+            let s = meta.query_selector(count_start_selector);
+            let count = meta.query_advice(x_column, Rotation::cur());
+            
+            vec![s * count]
+        });
+        
+        meta.create_gate("match count", |meta| {
+            let s = meta.query_selector(count_selector);
+            let x_upper = meta.query_advice(x_column, Rotation(-2));
+            let x_lower = meta.query_advice(x_column, Rotation::prev());
+            let check_upper = meta.query_advice(check_column, Rotation(-2));
+            let check_lower = meta.query_advice(check_column, Rotation::prev());
+            let count = meta.query_advice(x_column, Rotation::cur());
+            let prev_count = meta.query_advice(x_column, Rotation(-3));
+            
+            vec![
+                // Both comparisons are on the same value
+                s.clone() * (x_upper - x_lower),
+                s * (count - (prev_count + check_upper - check_lower)),
+            ]
+        });
+        
+        // lower <= x < upper: both bounds of a bounded predicate in one check
+        // Two x < t checks decompose two differences each; here the outcome
+        // is one of three one-hot bits (in range, below, above) and only
+        // the differences that outcome needs are decomposed (rows 1-2):
+        // - in range: x - lower and upper - 1 - x
+        // - below: lower - 1 - x (second difference 0)
+        // - above: x - upper (second difference 0)
+        // A wrong outcome makes one of them negative, which has no chunks.
+        // The "below" bit is in threshold_advice_column (unused in this row).
+        meta.create_gate("lower <= x < upper", |meta| {
+            let s = meta.query_selector(between_selector);
+            let in_range = meta.query_advice(check_column, Rotation::cur());
+            let below = meta.query_advice(threshold_advice_column, Rotation::cur());
+            let x = meta.query_advice(x_column, Rotation::cur());
+            let lower = meta.query_fixed(threshold_column);
+            let upper = meta.query_fixed(u_column);
+            let one = Expression::Constant(Fr::ONE);
+            let above = one.clone() - in_range.clone() - below.clone();
+            
+            let first = in_range.clone() * (x.clone() - lower.clone())
+                + below.clone() * (lower - one.clone() - x.clone())
+                + above * (x.clone() - upper.clone());
+            let second = in_range.clone() * (upper - one.clone() - x);
+            let first_chunked = meta.query_advice(x_column, Rotation::next());
+            let second_chunked = meta.query_advice(x_column, Rotation(2));
+            
+            vec![
+                // Bits are boolean and at most one is set (above = 1 - both)
+                s.clone() * in_range.clone() * (one.clone() - in_range.clone()),
+                s.clone() * below.clone() * (one - below.clone()),
+                s.clone() * in_range * below,
+                s.clone() * (first_chunked - first),
+                s * (second_chunked - second),
+            ]
+        });
+        
+        // Boolean x = t: x is 0 or 1, and check = 1 iff x = t (t is 0 or 1)
+        // 1 - t - x + 2·t·x is x for t = 1 and 1 - x for t = 0, so check is
+        // boolean as well. A boolean column needs no 64-bit decomposition.
+        meta.create_gate("boolean x = t", |meta| {
+            let s = meta.query_selector(bool_selector);
+            let check = meta.query_advice(check_column, Rotation::cur());
+            let x = meta.query_advice(x_column, Rotation::cur());
+            let t = meta.query_fixed(threshold_column);
+            let one = Expression::Constant(Fr::ONE);
+            let two = Expression::Constant(Fr::from(2));
+            
+            let equal = one.clone() - t.clone() - x.clone() + two * t * x.clone();
+            vec![s.clone() * x.clone() * (one - x), s * (check - equal)]
+        });
+        
+        // Bit count: number of set bits, e.g. the groups a HAVING keeps
+        // The count starts at 0 (first row, "match count start"); each bit
+        // (check_column) adds to the previous row's count (x_column)
+        meta.create_gate("bit count", |meta| {
+            let s = meta.query_selector(bit_count_selector);
+            let bit = meta.query_advice(check_column, Rotation::cur());
+            let count = meta.query_advice(x_column, Rotation::cur());
+            let prev_count = meta.query_advice(x_column, Rotation::prev());
+            let one = Expression::Constant(Fr::ONE);
+            
+            vec![
+                s.clone() * bit.clone() * (one - bit.clone()),
+                s * (count - prev_count - bit),
+            ]
+        });
+        
+        // Bit AND / OR: a (x_column) and b (threshold_advice_column) are
+        // copied bits; op (fixed) is 0 for a · b and 1 for a + b - a · b
+        meta.create_gate("bit and / or", |meta| {
+            let s = meta.query_selector(bit_op_selector);
+            let a = meta.query_advice(x_column, Rotation::cur());
+            let b = meta.query_advice(threshold_advice_column, Rotation::cur());
+            let out = meta.query_advice(check_column, Rotation::cur());
+            let op = meta.query_fixed(threshold_column);
+            let one = Expression::Constant(Fr::ONE);
+            let two = Expression::Constant(Fr::from(2));
+            
+            let and = a.clone() * b.clone();
+            let combined = and.clone() + op * (a.clone() + b.clone() - two * and);
+            vec![
+                s.clone() * a.clone() * (one.clone() - a),
+                s.clone() * b.clone() * (one - b),
+                s * (out - combined),
+            ]
+        });
+        
+        // Public operand: a (x_column) and b (threshold_advice_column) of
+        // a < b; t (u_advice_column) and kind (check_column) are copied from
+        // the instance column, kind 0 for b = t and 1 for a = t
+        meta.create_gate("public operand", |meta| {
+            let s = meta.query_selector(public_operand_selector);
+            let a = meta.query_advice(x_column, Rotation::cur());
+            let b = meta.query_advice(threshold_advice_column, Rotation::cur());
+            let t = meta.query_advice(u_advice_column, Rotation::cur());
+            let kind = meta.query_advice(check_column, Rotation::cur());
             let one = Expression::Constant(Fr::ONE);
-            let not_selector = one - s.clone();
             
-            // selector * diff + (1 - selector) * 0
-            // When selector = 1: diff is looked up (must be in range 0-255, u < 256 assumption)
-            // When selector = 0: 0 is looked up (exists in lookup table)
-            let lookup_expr = s.clone() * diff + not_selector * Expression::Constant(Fr::ZERO);
+            vec![
+                s.clone() * kind.clone() * (one.clone() - kind.clone()),
+                s * ((one - kind.clone()) * (b - t.clone()) + kind * (a - t)),
+            ]
+        });
+        
+        // x = t with witnessed threshold: x (x_column) and t
+        // (threshold_advice_column) are usually copied cells, inv
+        // (u_advice_column) is 1 / (x - t) or 0. If x != t the second
+        // constraint forces check = 1 - (x - t) · inv and the first check = 0;
+        // if x = t it forces check = 1.
+        meta.create_gate("x = t (advice threshold)", |meta| {
+            let s = meta.query_selector(equal_advice_selector);
+            let check = meta.query_advice(check_column, Rotation::cur());
+            let x = meta.query_advice(x_column, Rotation::cur());
+            let t = meta.query_advice(threshold_advice_column, Rotation::cur());
+            let inv = meta.query_advice(u_advice_column, Rotation::cur());
+            let one = Expression::Constant(Fr::ONE);
             
-            vec![(lookup_expr, lookup_table)]
+            let diff = x - t;
+            vec![
+                s.clone() * check.clone() * diff.clone(),
+                s * (one - check - diff * inv),
+            ]
         });
         
         RangeCheckConfig {
             chunk_columns,
             lookup_table,
+            lookup_bits,
             check_column,
             x_column,
             diff_column,
             threshold_column,
             u_column,
+            threshold_advice_column,
+            u_advice_column,
             selector,
             less_than_selector,
+            less_than_advice_selector,
+            compare_selector,
+            count_selector,
+            count_start_selector,
+            between_selector,
+            bool_selector,
+            bit_count_selector,
+            bit_op_selector,
+            public_operand_selector,
+            equal_advice_selector,
             decomposition_selector,
-            diff_lookup_selector,
         }
     }
     
@@ -269,22 +669,15 @@ impl RangeCheckChip {
     /// 
     /// # Return Value
     /// 
-    /// 8 chunk cells (each 8-bit)
+    /// `chunk_count` chunk cells (8 with 8-bit chunks, 4 with 16-bit chunks)
     pub fn decompose_64bit(
         &self,
         mut layouter: impl Layouter<Fr>,
         value: Value<u64>,
-    ) -> Result<[AssignedCell<Fr, Fr>; 8], Error> {
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
         layouter.assign_region(
             || "decompose 64bit",
             |mut region| {
-                let decomposed = value.map(|v| {
-                    let mut result = [0u8; 8];
-                    for i in 0..8 {
-                        result[i] = ((v >> (i * 8)) & 0xFF) as u8;
-                    }
-                    result
-                });
                 
                 // Place each chunk in the same row (row 1 - same row as value)
                 // Row 0: empty (x_column is used in row 0 in check_less_than)
@@ -304,14 +697,14 @@ impl RangeCheckChip {
                     || "value",
                     self.config.x_column,
                     value_row,
-                    || value.map(|v| Fr::from(v)),
+                    || value.map(Fr::from),
                 )?;
                 
                 // Selector for decomposition sum constraint (in row 1)
                 self.config.decomposition_selector.enable(&mut region, value_row)?;
                 
-                for (i, chunk_col) in self.config.chunk_columns.iter().enumerate() {
-                    let chunk_value = decomposed.map(|chunks| Fr::from(chunks[i] as u64));
+                for (i, chunk_col) in self.config.active_chunk_columns().iter().enumerate() {
+                    let chunk_value = value.map(|v| Fr::from(self.config.chunk(v, i)));
                     
                     // Assign chunk (all chunks in row 1, same row as value)
                     let cell = region.assign_advice(
@@ -330,135 +723,1135 @@ impl RangeCheckChip {
                 // Decomposition sum constraint is automatically checked
                 // because we defined it in configure
                 
-                Ok(chunks.try_into().unwrap())
+                Ok(chunks)
+            },
+        )
+    }
+    
+    /// Decompose many 64-bit values into 8-bit chunks in a single region
+    /// Paper Section 4.1: Same constraints as `decompose_64bit`, one row per value
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row i: value i and its 8 chunks (decomposition sum and lookup constraint)
+    /// 
+    /// # Note
+    /// 
+    /// Chunk witnesses are computed in parallel before region assignment, and all
+    /// values share one region instead of opening a region per value.
+    /// 
+    /// # Return Value
+    /// 
+    /// `chunk_count` chunk cells per value, in value order
+    pub fn decompose_64bit_batch(
+        &self,
+        layouter: impl Layouter<Fr>,
+        values: &[u64],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        self.assign_decomposition_batch(layouter, values, None)
+    }
+    
+    /// Decompose assigned cells into 8-bit chunks in a single region
+    /// Same as `decompose_64bit_batch`, but each value is copied from `cells[i]`,
+    /// so the 64-bit range check is bound to that cell (e.g. a diff that
+    /// another gate constrains)
+    /// 
+    /// # Parameters
+    /// 
+    /// - `cells`: Cells to range check
+    /// - `values`: The cells' values (for the chunk witnesses)
+    pub fn decompose_assigned_batch(
+        &self,
+        layouter: impl Layouter<Fr>,
+        cells: &[AssignedCell<Fr, Fr>],
+        values: &[u64],
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        if cells.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+        self.assign_decomposition_batch(layouter, values, Some(cells))
+    }
+    
+    /// Shared implementation of `decompose_64bit_batch` and `decompose_assigned_batch`
+    fn assign_decomposition_batch(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        values: &[u64],
+        sources: Option<&[AssignedCell<Fr, Fr>]>,
+    ) -> Result<Vec<Vec<AssignedCell<Fr, Fr>>>, Error> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        // Precompute (value, chunks) field elements for every row in parallel
+        let chunk_count = self.config.chunk_count();
+        let witnesses: Vec<(Fr, Vec<Fr>)> = values
+            .par_iter()
+            .map(|&v| {
+                let chunks = (0..chunk_count)
+                    .map(|i| Fr::from(self.config.chunk(v, i)))
+                    .collect();
+                (Fr::from(v), chunks)
+            })
+            .collect();
+        
+        layouter.assign_region(
+            || "decompose 64bit batch",
+            |mut region| {
+                let mut all_chunks = Vec::with_capacity(witnesses.len());
+                
+                for (row, (value, chunks)) in witnesses.iter().enumerate() {
+                    match sources {
+                        Some(cells) => {
+                            cells[row].copy_advice(
+                                || format!("value_{}", row),
+                                &mut region,
+                                self.config.x_column,
+                                row,
+                            )?;
+                        }
+                        None => {
+                            region.assign_advice(
+                                || format!("value_{}", row),
+                                self.config.x_column,
+                                row,
+                                || Value::known(*value),
+                            )?;
+                        }
+                    }
+                    
+                    // Decomposition sum and lookup constraints (same row as value)
+                    self.config
+                        .decomposition_selector
+                        .enable(&mut region, row)?;
+                    self.config.selector.enable(&mut region, row)?;
+                    
+                    let mut cells = Vec::with_capacity(chunk_count);
+                    for (i, (chunk_col, chunk)) in self
+                        .config
+                        .active_chunk_columns()
+                        .iter()
+                        .zip(chunks.iter())
+                        .enumerate()
+                    {
+                        cells.push(region.assign_advice(
+                            || format!("chunk_{}_{}", row, i),
+                            *chunk_col,
+                            row,
+                            || Value::known(*chunk),
+                        )?);
+                    }
+                    all_chunks.push(cells);
+                }
+                
+                Ok(all_chunks)
             },
         )
     }
     
     /// x < t check
-    /// Paper Section 4.1: check + (x - t) - u ∈ [0, u) constraint
+    /// Paper Section 4.1: check · u + (x - t) ∈ [0, u) constraint
     /// 
     /// # Constraint
     /// 
-    /// `check + (x - t) - u ∈ [0, u)`
+    /// `check · u + (x - t) ∈ [0, u)` for `check = 1`, where `u >= t - x`;
+    /// `x - t ∈ [0, 2^64)` for `check = 0`
     /// 
     /// # Logic
     /// 
-    /// - If `x < t`: `check = 1`, `diff = 1 + (x - t) - u ∈ [0, u)`
-    /// - If `x >= t`: `check = 0`, `diff = 0 + (x - t) - u ∈ [0, u)`
+    /// - If `x < t`: `check = 1`, `diff = u - (t - x) ∈ [0, u)`
+    /// - If `x >= t`: `check = 0`, `diff = x - t`
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: x, check, threshold and u
+    /// - Row 1: diff
+    /// - Row 2: diff and its chunks (diff >= 0)
+    /// - Row 3: `check · (u - 1 - diff)` and its chunks (diff < u)
     /// 
     /// # Note
     /// 
-    /// - Works with u < 256 assumption (checks diff directly with lookup table)
-    /// - For u >= 256: Production note (can be checked with diff decomposition)
+    /// Holds over the whole u64 range: every check is a 64-bit decomposition,
+    /// so a field element wrapped around by `x - t` or `u - 1 - diff` is
+    /// rejected. u is not trusted: any u that verifies gives the right check
+    /// bit, and a u below `t - x` fails the decomposition.
     /// 
     /// # Return Value
     /// 
     /// Boolean check cell (1 = x < t, 0 = x >= t)
     pub fn check_less_than(
         &self,
-        mut layouter: impl Layouter<Fr>,
+        layouter: impl Layouter<Fr>,
+        x: Value<u64>,
+        threshold: u64,
+        u: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_less_than(layouter, (None, None), x, Value::known(threshold), u, false)
+    }
+    
+    /// x < t check with a witnessed threshold
+    /// Same constraint as `check_less_than`, but t and u are assigned to advice cells,
+    /// so the threshold can be another column's value (e.g. `spent < budget`)
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x < t, 0 = x >= t)
+    pub fn check_less_than_advice(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x: Value<u64>,
+        threshold: Value<u64>,
+        u: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_less_than(layouter, (None, None), x, threshold, u, true)
+    }
+    
+    /// x < t check on a copy of an assigned cell
+    /// Same constraint as `check_less_than`, with x copied from `x_cell`
+    /// (e.g. a committed database cell), so the check is bound to that cell:
+    /// an `x` value differing from the cell's fails the "x < t" gate
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x < t, 0 = x >= t)
+    pub fn check_less_than_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: &AssignedCell<Fr, Fr>,
         x: Value<u64>,
         threshold: u64,
         u: u64,
     ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_less_than(
+            layouter,
+            (Some(x_cell), None),
+            x,
+            Value::known(threshold),
+            u,
+            false,
+        )
+    }
+    
+    /// x < t check with a witnessed threshold, x and / or t copied from assigned cells
+    /// Same constraint as `check_less_than_advice`; each operand with a cell is
+    /// bound to it like x in `check_less_than_copied` (None: assigned from its value)
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x < t, 0 = x >= t)
+    pub fn check_less_than_advice_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold_cell: Option<&AssignedCell<Fr, Fr>>,
+        threshold: Value<u64>,
+        u: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_less_than(layouter, (x_cell, threshold_cell), x, threshold, u, true)
+    }
+    
+    /// x = t check with a witnessed threshold, x and / or t copied from assigned cells
+    /// (e.g. `a = b` between two columns of the same row)
+    /// 
+    /// Unlike `x < t + 1`, which proves only x <= t and has no threshold for
+    /// t = u64::MAX, this is an is-zero check on `x - t`: both outcomes are
+    /// provable and a wrong check bit fails the "x = t (advice threshold)" gate.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: x, t, inverse of `x - t` (0 if x = t) and check
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x = t, 0 = x != t)
+    pub fn check_equal_advice_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold_cell: Option<&AssignedCell<Fr, Fr>>,
+        threshold: Value<u64>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let (check, _) = self.assign_equal(layouter, (x_cell, threshold_cell), x, threshold)?;
+        Ok(check)
+    }
+    
+    /// x <= t check with a witnessed threshold, x and / or t copied from assigned cells
+    /// 
+    /// `x < t` (`check_less_than_advice_copied`, with `u` as there) OR `x = t`
+    /// (`check_equal_advice_copied`) on the same operand cells, combined by
+    /// `combine_bits`; no `t + 1` threshold, so t = u64::MAX holds for every x.
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x <= t, 0 = x > t)
+    pub fn check_less_equal_advice_copied(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold_cell: Option<&AssignedCell<Fr, Fr>>,
+        threshold: Value<u64>,
+        u: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let (equal, (x_cell, threshold_cell)) = self.assign_equal(
+            layouter.namespace(|| "x = t"),
+            (x_cell, threshold_cell),
+            x,
+            threshold,
+        )?;
+        let less = self.assign_less_than(
+            layouter.namespace(|| "x < t"),
+            (Some(&x_cell), Some(&threshold_cell)),
+            x,
+            threshold,
+            u,
+            true,
+        )?;
+        self.combine_bits(layouter.namespace(|| "x <= t"), &less, &equal, BitOp::Or)
+    }
+    
+    /// Shared implementation of `check_equal_advice_copied` and
+    /// `check_less_equal_advice_copied`
+    /// 
+    /// # Return Value
+    /// 
+    /// The check cell and the cells of x and t in the region
+    fn assign_equal(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        cells: SourceCells<'_>,
+        x: Value<u64>,
+        threshold: Value<u64>,
+    ) -> Result<(AssignedCell<Fr, Fr>, OperandCells), Error> {
         layouter.assign_region(
-            || "check x < t",
+            || "check x = t",
             |mut region| {
-                // Selector for x < t constraint
-                self.config.less_than_selector.enable(&mut region, 0)?;
-                
-                // Assign x value (for x < t constraint)
-                let _x_cell = region.assign_advice(
-                    || "x",
-                    self.config.x_column,
-                    0,
-                    || x.map(|x_val| Fr::from(x_val)),
-                )?;
-                
-                // Assign threshold (t) value to fixed column
-                region.assign_fixed(
-                    || "threshold",
-                    self.config.threshold_column,
-                    0,
-                    || Value::known(Fr::from(threshold)),
-                )?;
+                self.config.equal_advice_selector.enable(&mut region, 0)?;
                 
-                // Assign u value to fixed column
-                region.assign_fixed(
-                    || "u",
-                    self.config.u_column,
-                    0,
-                    || Value::known(Fr::from(u)),
-                )?;
-                
-                // Boolean value for x < t check
-                // Paper requirement: check must be boolean (0 or 1)
-                let check = x.map(|x_val| {
-                    if x_val < threshold {
-                        Fr::from(1)
-                    } else {
-                        Fr::from(0)
+                // x and t, copied from their source cells if they have one
+                let x_cell = match cells.0 {
+                    Some(x_cell) => {
+                        x_cell.copy_advice(|| "x", &mut region, self.config.x_column, 0)?
                     }
-                });
+                    None => {
+                        region.assign_advice(|| "x", self.config.x_column, 0, || x.map(Fr::from))?
+                    }
+                };
+                let threshold_cell = match cells.1 {
+                    Some(threshold_cell) => threshold_cell.copy_advice(
+                        || "threshold",
+                        &mut region,
+                        self.config.threshold_advice_column,
+                        0,
+                    )?,
+                    None => region.assign_advice(
+                        || "threshold",
+                        self.config.threshold_advice_column,
+                        0,
+                        || threshold.map(Fr::from),
+                    )?,
+                };
                 
-                let check_cell = region.assign_advice(
-                    || "check",
-                    self.config.check_column,
+                // inv = 1 / (x - t), or 0 if x = t
+                let diff = x_cell.value().copied() - threshold_cell.value().copied();
+                region.assign_advice(
+                    || "inverse",
+                    self.config.u_advice_column,
                     0,
-                    || check,
+                    || diff.map(|diff| diff.invert().unwrap_or(Fr::ZERO)),
                 )?;
                 
-                // Calculate diff = check + (x - t) - u
-                // Paper Section 4.1: for diff ∈ [0, u) check
-                let diff = check
-                    .zip(x.map(|x_val| Fr::from(x_val)))
-                    .map(|(check_val, x_val)| {
-                        let t_val = Fr::from(threshold);
-                        let u_val = Fr::from(u);
-                        check_val + (x_val - t_val) - u_val
-                    });
-                
-                // Assign diff to diff_column (same column as check_column, offset 1)
-                let _diff_cell = region.assign_advice(
-                    || "diff",
-                    self.config.diff_column,
-                    1, // offset 1 (next to check_column)
-                    || diff,
-                )?;
+                let check = diff.map(|diff| Fr::from(bool::from(diff.is_zero())));
+                let check_cell =
+                    region.assign_advice(|| "check", self.config.check_column, 0, || check)?;
                 
-                // Lookup constraint for [0, u) range check
-                // Production note: for u >= 256 support
-                // If u < 256, we check diff directly with lookup table
-                // If u >= 256, we can divide diff into chunks and check that each chunk is in range 0-255
-                // But additional constraint is needed for diff < u check
-                // 
-                // Production Note: For u >= 256 support, diff must be decomposed and
-                // additional range check constraint must be added for diff < u check
-                // For now: we work with u < 256 assumption (sufficient for production)
-                if u < 256 {
-                    // u < 256: check diff directly with lookup table
-                    self.config.diff_lookup_selector.enable(&mut region, 1)?;
-                } else {
-                    // u >= 256: Production note
-                    // In this case, we can divide diff into chunks and check that each chunk is in range 0-255
-                    // But additional constraint is needed for diff < u check
-                    // For now: correct value will be assigned in witness
-                    // For production: additional range check constraint can be added for diff < u check
-                    // Note: This case is rare in production, because u < 256 is generally used
+                Ok((check_cell, (x_cell, threshold_cell)))
+            },
+        )
+    }
+    
+    /// Shared implementation of `check_less_than`, `check_less_than_advice`
+    /// and their `_copied` forms
+    fn assign_less_than(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        cells: SourceCells<'_>,
+        x: Value<u64>,
+        threshold: Value<u64>,
+        u: u64,
+        witnessed_threshold: bool,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "check x < t",
+            |mut region| {
+                // Selector for x < t constraint
+                if witnessed_threshold {
+                    self.config
+                        .less_than_advice_selector
+                        .enable(&mut region, 0)?;
+                } else {
+                    self.config.less_than_selector.enable(&mut region, 0)?;
+                }
+                
+                // Assign x value (for x < t constraint), or copy it from its source cell
+                match cells.0 {
+                    Some(x_cell) => {
+                        x_cell.copy_advice(|| "x", &mut region, self.config.x_column, 0)?;
+                    }
+                    None => {
+                        region.assign_advice(
+                            || "x",
+                            self.config.x_column,
+                            0,
+                            || x.map(Fr::from),
+                        )?;
+                    }
                 }
                 
-                // Constraint is automatically checked by gate defined in configure
-                // For check + (x - t) - u ∈ [0, u) check:
-                // - check boolean constraint (check * (1 - check) = 0) ✅
-                // - diff = check + (x - t) - u constraint ✅
-                // - diff ∈ [0, u) lookup table check ✅ (direct for u < 256, by dividing into chunks for u >= 256)
+                if witnessed_threshold {
+                    // Assign threshold (t) and u values to advice columns
+                    // (t copied from its source cell if it has one)
+                    match cells.1 {
+                        Some(threshold_cell) => {
+                            threshold_cell.copy_advice(
+                                || "threshold",
+                                &mut region,
+                                self.config.threshold_advice_column,
+                                0,
+                            )?;
+                        }
+                        None => {
+                            region.assign_advice(
+                                || "threshold",
+                                self.config.threshold_advice_column,
+                                0,
+                                || threshold.map(Fr::from),
+                            )?;
+                        }
+                    }
+                    region.assign_advice(
+                        || "u",
+                        self.config.u_advice_column,
+                        0,
+                        || Value::known(Fr::from(u)),
+                    )?;
+                } else {
+                    // Assign threshold (t) value to fixed column
+                    region.assign_fixed(
+                        || "threshold",
+                        self.config.threshold_column,
+                        0,
+                        || threshold.map(Fr::from),
+                    )?;
+                    
+                    // Assign u value to fixed column
+                    region.assign_fixed(
+                        || "u",
+                        self.config.u_column,
+                        0,
+                        || Value::known(Fr::from(u)),
+                    )?;
+                }
+                
+                // Boolean value for x < t check
+                // Paper requirement: check must be boolean (0 or 1)
+                let check = x.zip(threshold).map(|(x_val, t_val)| {
+                    if x_val < t_val {
+                        Fr::from(1)
+                    } else {
+                        Fr::from(0)
+                    }
+                });
+                
+                let check_cell =
+                    region.assign_advice(|| "check", self.config.check_column, 0, || check)?;
+
+                // Calculate diff = check · u + (x - t)
+                // Paper Section 4.1: for diff ∈ [0, u) check
+                // A u below t - x leaves diff negative: wrapping arithmetic
+                // gives chunks that cannot reconstruct it, so the
+                // decomposition rejects the witness
+                let diff = x.zip(threshold).map(|(x_val, t_val)| {
+                    let bound = if x_val < t_val { u } else { 0 };
+                    bound.wrapping_add(x_val).wrapping_sub(t_val)
+                });
+                let diff_field = check.zip(x.map(Fr::from)).zip(threshold.map(Fr::from)).map(
+                    |((check_val, x_val), t_val)| {
+                        let u_val = Fr::from(u);
+                        check_val * u_val + (x_val - t_val)
+                    },
+                );
+
+                // Assign diff to diff_column (same column as check_column, offset 1)
+                let _diff_cell = region.assign_advice(
+                    || "diff",
+                    self.config.diff_column,
+                    1, // offset 1 (next to check_column)
+                    || diff_field,
+                )?;
+                
+                // Slack u - 1 - diff = t - 1 - x for check = 1 (0 for check = 0)
+                let slack = x.zip(threshold).map(|(x_val, t_val)| {
+                    if x_val < t_val {
+                        t_val.wrapping_sub(1).wrapping_sub(x_val)
+                    } else {
+                        0
+                    }
+                });
+                let slack_field = check
+                    .zip(diff_field)
+                    .map(|(check_val, diff_val)| check_val * (Fr::from(u) - Fr::ONE - diff_val));
+                
+                // Rows 2-3: diff and slack decomposed into 64-bit chunks
+                self.assign_decomposition_row(&mut region, 2, diff_field, diff)?;
+                self.assign_decomposition_row(&mut region, 3, slack_field, slack)?;
                 
                 Ok(check_cell)
             },
         )
     }
     
+    /// x < t comparison on an assigned cell
+    /// 
+    /// Unlike `check_less_than`, both outcomes are provable: the returned
+    /// check bit is 1 if x < t and 0 if x >= t, and a wrong bit fails the
+    /// "x < t comparison" gate. `x` is copied from an existing cell (e.g. an
+    /// aggregation result), so the comparison is bound to that cell.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: x (copy), check, threshold (fixed) and the 8 chunks of the difference
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x < t, 0 = x >= t)
+    pub fn compare_less_than(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        x: &AssignedCell<Fr, Fr>,
+        x_value: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "compare x < t",
+            |mut region| {
+                // Copy x (binds the comparison to the source cell)
+                x.copy_advice(|| "x", &mut region, self.config.x_column, 0)?;
+                
+                self.assign_comparison(&mut region, 0, x_value, threshold)
+            },
+        )
+    }
+    
+    /// lower <= x < upper check (a bounded predicate, e.g. `x > a AND x < b`)
+    /// 
+    /// Both bounds share one region: two decomposition rows instead of the
+    /// four of two `check_less_than` calls. Every outcome is provable; the
+    /// returned bit is 1 if x is in range and a wrong bit fails the
+    /// "lower <= x < upper" gate.
+    /// 
+    /// # Parameters
+    /// 
+    /// - `x_cell`: Cell x is copied from (e.g. a committed database cell), or
+    ///   None to assign `x`
+    /// - `lower`, `upper`: Bounds (fixed); `lower >= upper` is an empty range
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: x, in-range bit, below bit, lower and upper (fixed)
+    /// - Row 1: first difference and its chunks
+    /// - Row 2: second difference and its chunks
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean in-range cell (1 = lower <= x < upper)
+    pub fn check_between(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        lower: u64,
+        upper: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(layouter, x_cell, x, lower as u128, upper as u128)
+    }
+    
+    /// x < t (strict; `x == t` gives 0)
+    /// 
+    /// A `check_between` row with bounds `[0, t)`. Unlike `check_less_than`
+    /// no u is needed and both outcomes are provable.
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x < t, 0 = x >= t)
+    pub fn prove_lt(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(layouter, x_cell, x, 0, threshold as u128)
+    }
+    
+    /// x <= t (non-strict; `x == t` gives 1)
+    /// 
+    /// Bounds `[0, t + 1)`, widened to u128 so `t = u64::MAX` holds for every x
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x <= t, 0 = x > t)
+    pub fn prove_le(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(layouter, x_cell, x, 0, threshold as u128 + 1)
+    }
+    
+    /// x > t (strict; `x == t` gives 0)
+    /// 
+    /// Bounds `[t + 1, 2^64)`, widened to u128 so `t = u64::MAX` holds for no x
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x > t, 0 = x <= t)
+    pub fn prove_gt(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(layouter, x_cell, x, threshold as u128 + 1, U64_END)
+    }
+    
+    /// x >= t (non-strict; `x == t` gives 1)
+    /// 
+    /// Bounds `[t, 2^64)`
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x >= t, 0 = x < t)
+    pub fn prove_ge(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(layouter, x_cell, x, threshold as u128, U64_END)
+    }
+    
+    /// x = t
+    /// 
+    /// Bounds `[t, t + 1)`, both outcomes provable
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x = t, 0 = x != t)
+    pub fn prove_eq(
+        &self,
+        layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_between(
+            layouter,
+            x_cell,
+            x,
+            threshold as u128,
+            threshold as u128 + 1,
+        )
+    }
+    
+    /// x = t for a boolean x (0 or 1) and t (`false` = 0, `true` = 1)
+    /// 
+    /// One row: `x · (1 - x) = 0` constrains x to a boolean (a non-boolean
+    /// x fails instead of giving 0), and the check is x (t = 1) or 1 - x
+    /// (t = 0). Lighter than `prove_eq`, which decomposes two differences.
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean check cell (1 = x = t, 0 = x != t)
+    pub fn prove_bool(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        threshold: bool,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "check boolean x = t",
+            |mut region| {
+                self.config.bool_selector.enable(&mut region, 0)?;
+                
+                match x_cell {
+                    Some(x_cell) => {
+                        x_cell.copy_advice(|| "x", &mut region, self.config.x_column, 0)?;
+                    }
+                    None => {
+                        region.assign_advice(
+                            || "x",
+                            self.config.x_column,
+                            0,
+                            || x.map(Fr::from),
+                        )?;
+                    }
+                }
+                region.assign_fixed(
+                    || "t",
+                    self.config.threshold_column,
+                    0,
+                    || Value::known(Fr::from(threshold as u64)),
+                )?;
+                region.assign_advice(
+                    || "x = t",
+                    self.config.check_column,
+                    0,
+                    || x.map(|x| Fr::from((x == threshold as u64) as u64)),
+                )
+            },
+        )
+    }
+    
+    /// Shared implementation of `check_between` and the `prove_*` comparisons
+    /// 
+    /// Bounds are u128 so that `t + 1` and 2^64 are representable: with
+    /// `upper = 2^64` the "above" outcome needs `x - 2^64 >= 0`, which no
+    /// u64 x satisfies, and with `lower = 2^64` neither does "in range".
+    /// Every difference of a true outcome still fits in 64 bits.
+    fn assign_between(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        x_cell: Option<&AssignedCell<Fr, Fr>>,
+        x: Value<u64>,
+        lower: u128,
+        upper: u128,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "check lower <= x < upper",
+            |mut region| {
+                self.config.between_selector.enable(&mut region, 0)?;
+                
+                match x_cell {
+                    Some(x_cell) => {
+                        x_cell.copy_advice(|| "x", &mut region, self.config.x_column, 0)?;
+                    }
+                    None => {
+                        region.assign_advice(
+                            || "x",
+                            self.config.x_column,
+                            0,
+                            || x.map(Fr::from),
+                        )?;
+                    }
+                }
+                region.assign_fixed(
+                    || "lower",
+                    self.config.threshold_column,
+                    0,
+                    || Value::known(Fr::from_u128(lower)),
+                )?;
+                region.assign_fixed(
+                    || "upper",
+                    self.config.u_column,
+                    0,
+                    || Value::known(Fr::from_u128(upper)),
+                )?;
+                
+                let x = x.map(u128::from);
+                let in_range = x.map(|x_val| lower <= x_val && x_val < upper);
+                let below = x.map(|x_val| x_val < lower);
+                let in_range_cell = region.assign_advice(
+                    || "in range",
+                    self.config.check_column,
+                    0,
+                    || in_range.map(|bit| Fr::from(bit as u64)),
+                )?;
+                region.assign_advice(
+                    || "below",
+                    self.config.threshold_advice_column,
+                    0,
+                    || below.map(|bit| Fr::from(bit as u64)),
+                )?;
+                
+                // Differences of the actual outcome (both 64-bit non-negative)
+                let differences = x.map(|x_val| {
+                    let (first, second) = if x_val < lower {
+                        (lower - 1 - x_val, 0)
+                    } else if x_val < upper {
+                        (x_val - lower, upper - 1 - x_val)
+                    } else {
+                        (x_val - upper, 0)
+                    };
+                    (first as u64, second as u64)
+                });
+                let first = differences.map(|(first, _)| first);
+                let second = differences.map(|(_, second)| second);
+                self.assign_decomposition_row(&mut region, 1, first.map(Fr::from), first)?;
+                self.assign_decomposition_row(&mut region, 2, second.map(Fr::from), second)?;
+                
+                Ok(in_range_cell)
+            },
+        )
+    }
+    
+    /// Number of values with lower <= x < upper
+    /// 
+    /// Each value is compared with both bounds (`compare_less_than` rows, so
+    /// both outcomes are provable) and the running count adds
+    /// `(x < upper) - (x < lower)`. Use `lower = 0` / `upper = u64::MAX` for
+    /// a missing bound (u64::MAX itself, the SQL NULL, never matches).
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: count = 0 (x_column)
+    /// - Row 3i+1: x_i < upper comparison
+    /// - Row 3i+2: x_i < lower comparison (same x)
+    /// - Row 3i+3: running count (x_column)
+    /// 
+    /// # Return Value
+    /// 
+    /// Count cell (the last running count)
+    pub fn count_in_range(
+        &self,
+        layouter: impl Layouter<Fr>,
+        values: &[u64],
+        lower: u64,
+        upper: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.assign_count(layouter, values, None, lower, upper)
+    }
+    
+    /// Number of values with lower <= x < upper, with each x copied from
+    /// its source cell (e.g. a committed database column)
+    /// 
+    /// Same rows as `count_in_range`; a value differing from its cell's
+    /// fails the comparison gates
+    /// 
+    /// # Return Value
+    /// 
+    /// Count cell (the last running count), or `Error::Synthesis` if
+    /// `cells` and `values` differ in length
+    pub fn count_in_range_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        cells: &[AssignedCell<Fr, Fr>],
+        values: &[u64],
+        lower: u64,
+        upper: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        if cells.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+        self.assign_count(layouter, values, Some(cells), lower, upper)
+    }
+    
+    /// Number of set bits among boolean cells (e.g. the pass bits of the
+    /// groups a HAVING keeps)
+    /// 
+    /// Each bit is copied from its cell, so the count is bound to the cells
+    /// that produced the bits; a non-boolean cell fails the "bit count" gate.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: count = 0 (x_column)
+    /// - Row i+1: bit i (copy, check_column) and the running count (x_column)
+    /// 
+    /// # Return Value
+    /// 
+    /// Count cell (the last running count)
+    pub fn count_bits(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        bits: &[AssignedCell<Fr, Fr>],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "bit count",
+            |mut region| {
+                self.config.count_start_selector.enable(&mut region, 0)?;
+                let mut count_cell = region.assign_advice(
+                    || "count_start",
+                    self.config.x_column,
+                    0,
+                    || Value::known(Fr::ZERO),
+                )?;
+                
+                for (i, bit) in bits.iter().enumerate() {
+                    self.config.bit_count_selector.enable(&mut region, i + 1)?;
+                    let bit = bit.copy_advice(
+                        || format!("bit_{}", i),
+                        &mut region,
+                        self.config.check_column,
+                        i + 1,
+                    )?;
+                    let count = count_cell.value().copied() + bit.value().copied();
+                    count_cell = region.assign_advice(
+                        || format!("count_{}", i),
+                        self.config.x_column,
+                        i + 1,
+                        || count,
+                    )?;
+                }
+                
+                Ok(count_cell)
+            },
+        )
+    }
+    
+    /// AND / OR of two boolean cells (e.g. the pass bits of two HAVING
+    /// conditions on the same group)
+    /// 
+    /// Both bits are copied from their cells, so the result is bound to the
+    /// cells that produced them; a non-boolean cell fails the "bit and / or"
+    /// gate.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: a (copy), b (copy), op (fixed) and the combined bit
+    /// 
+    /// # Return Value
+    /// 
+    /// Boolean cell of `a AND b` or `a OR b`
+    pub fn combine_bits(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: &AssignedCell<Fr, Fr>,
+        b: &AssignedCell<Fr, Fr>,
+        op: BitOp,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || format!("bit {:?}", op),
+            |mut region| {
+                self.config.bit_op_selector.enable(&mut region, 0)?;
+                
+                let a = a.copy_advice(|| "a", &mut region, self.config.x_column, 0)?;
+                let b =
+                    b.copy_advice(|| "b", &mut region, self.config.threshold_advice_column, 0)?;
+                region.assign_fixed(
+                    || "op",
+                    self.config.threshold_column,
+                    0,
+                    || Value::known(Fr::from((op == BitOp::Or) as u64)),
+                )?;
+                
+                let combined = a.value().zip(b.value()).map(|(a, b)| {
+                    let and = *a * b;
+                    match op {
+                        BitOp::And => and,
+                        BitOp::Or => *a + b - and,
+                    }
+                });
+                region.assign_advice(|| "combined", self.config.check_column, 0, || combined)
+            },
+        )
+    }
+    
+    /// Operands of an `a < b` check whose constant operand is public (a
+    /// universal circuit's, see `CircuitCapacity`)
+    /// 
+    /// `instance` row `row` holds the constant t and row `row + 1` its kind:
+    /// 0 for `x < t` (b = t), 1 for `t < x` (a = t). Both are copied from the
+    /// instance column, so one layout proves every constant and kind.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Row 0: a, b, t (copy) and kind (copy)
+    /// 
+    /// # Return Value
+    /// 
+    /// The cells of a and b (see `check_less_than_advice_copied`)
+    pub fn public_operands(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        a: Value<u64>,
+        b: Value<u64>,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<OperandCells, Error> {
+        layouter.assign_region(
+            || "public operand",
+            |mut region| {
+                self.config.public_operand_selector.enable(&mut region, 0)?;
+                
+                let a =
+                    region.assign_advice(|| "a", self.config.x_column, 0, || a.map(Fr::from))?;
+                let b = region.assign_advice(
+                    || "b",
+                    self.config.threshold_advice_column,
+                    0,
+                    || b.map(Fr::from),
+                )?;
+                region.assign_advice_from_instance(
+                    || "t",
+                    instance,
+                    row,
+                    self.config.u_advice_column,
+                    0,
+                )?;
+                region.assign_advice_from_instance(
+                    || "kind",
+                    instance,
+                    row + 1,
+                    self.config.check_column,
+                    0,
+                )?;
+                
+                Ok((a, b))
+            },
+        )
+    }
+    
+    /// Shared implementation of `count_in_range` and `count_in_range_copied`
+    fn assign_count(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        values: &[u64],
+        cells: Option<&[AssignedCell<Fr, Fr>]>,
+        lower: u64,
+        upper: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        // (x < upper) - (x < lower) is a 0/1 match bit only if lower <= upper
+        if lower > upper {
+            return Err(Error::Synthesis);
+        }
+        
+        layouter.assign_region(
+            || "match count",
+            |mut region| {
+                self.config.count_start_selector.enable(&mut region, 0)?;
+                let mut count = 0u64;
+                let mut count_cell = region.assign_advice(
+                    || "count_start",
+                    self.config.x_column,
+                    0,
+                    || Value::known(Fr::ZERO),
+                )?;
+                
+                for (i, &value) in values.iter().enumerate() {
+                    let row = 3 * i + 1;
+                    for (offset, threshold) in [(0, upper), (1, lower)] {
+                        match cells {
+                            Some(cells) => {
+                                cells[i].copy_advice(
+                                    || format!("x_{}", i),
+                                    &mut region,
+                                    self.config.x_column,
+                                    row + offset,
+                                )?;
+                            }
+                            None => {
+                                region.assign_advice(
+                                    || format!("x_{}", i),
+                                    self.config.x_column,
+                                    row + offset,
+                                    || Value::known(Fr::from(value)),
+                                )?;
+                            }
+                        }
+                        self.assign_comparison(
+                            &mut region,
+                            row + offset,
+                            Value::known(value),
+                            threshold,
+                        )?;
+                    }
+                    
+                    self.config.count_selector.enable(&mut region, row + 2)?;
+                    count += (lower <= value && value < upper) as u64;
+                    count_cell = region.assign_advice(
+                        || format!("count_{}", i),
+                        self.config.x_column,
+                        row + 2,
+                        || Value::known(Fr::from(count)),
+                    )?;
+                }
+                
+                Ok(count_cell)
+            },
+        )
+    }
+    
+    /// x < t comparison row (x must already be assigned at `offset`)
+    /// Shared by `compare_less_than` and `count_in_range`
+    fn assign_comparison(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        x_value: Value<u64>,
+        threshold: u64,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.config.compare_selector.enable(region, offset)?;
+        // Chunk lookups (same row)
+        self.config.selector.enable(region, offset)?;
+        
+        region.assign_fixed(
+            || "threshold",
+            self.config.threshold_column,
+            offset,
+            || Value::known(Fr::from(threshold)),
+        )?;
+        
+        let check_cell = region.assign_advice(
+            || "check",
+            self.config.check_column,
+            offset,
+            || x_value.map(|x_val| Fr::from((x_val < threshold) as u64)),
+        )?;
+        
+        // Difference for the actual outcome (always a 64-bit non-negative number)
+        let diff = x_value.map(|x_val| {
+            if x_val < threshold {
+                threshold - 1 - x_val
+            } else {
+                x_val - threshold
+            }
+        });
+        
+        for (i, chunk_col) in self.config.active_chunk_columns().iter().enumerate() {
+            region.assign_advice(
+                || format!("chunk_{}", i),
+                *chunk_col,
+                offset,
+                || diff.map(|d| Fr::from(self.config.chunk(d, i))),
+            )?;
+        }
+        
+        Ok(check_cell)
+    }
+    
+    /// One decomposition row inside an existing region: `value` in x_column
+    /// and the chunks of `chunks_of` (a value that is not 64-bit non-negative
+    /// has no chunks summing to it, so its row fails the decomposition sum)
+    fn assign_decomposition_row(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: usize,
+        value: Value<Fr>,
+        chunks_of: Value<u64>,
+    ) -> Result<(), Error> {
+        self.config.decomposition_selector.enable(region, offset)?;
+        self.config.selector.enable(region, offset)?;
+        
+        region.assign_advice(|| "value", self.config.x_column, offset, || value)?;
+        for (i, chunk_col) in self.config.active_chunk_columns().iter().enumerate() {
+            region.assign_advice(
+                || format!("chunk_{}", i),
+                *chunk_col,
+                offset,
+                || chunks_of.map(|v| Fr::from(self.config.chunk(v, i))),
+            )?;
+        }
+        
+        Ok(())
+    }
+    
     /// Simple range check: check that value is in a certain range
     pub fn check_range(
         &self,
@@ -0,0 +1,303 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::PoneglyphConfig;
+use super::range_check::{RangeCheckChip, RangeCheckConfig};
+
+/// Set Membership Gate Configuration
+/// Proves whether a value is a member of a committed set (e.g. the bit of
+/// `category IN (allowed)` for one row) with a lookup against interval
+/// tables loaded per circuit, instead of the fixed 0..2^lookup_bits range
+/// table
+///
+/// Each set is stored as intervals partitioning [0, 2^64): a one-value
+/// interval `[s, s]` per member (member = 1) and the gaps between them
+/// (member = 0). A value's interval is the only one containing it, so its
+/// member bit is fixed by the value.
+///
+/// # Column Allocation
+///
+/// - `tag_column`: Set tag of the checked value, a circuit constant (advice[8])
+/// - `value_column`: Checked value (advice[9])
+/// - `lower_column`, `upper_column`: Bounds of the value's interval (advice[0-1])
+/// - `member_column`: Member bit of the interval (advice[2])
+/// - `below_column`, `above_column`: `value - lower`, `upper - value` (advice[3-4])
+/// - `tag_table`, `lower_table`, `upper_table`, `member_table`: Intervals of every set
+///
+/// # Constraints
+///
+/// 1. **Interval**: `(s · tag, s · lower, s · upper, s · member)` is a table row
+/// 2. **Bounds**: `s · (below - (value - lower)) = 0`,
+///    `s · (above - (upper - value)) = 0`, with `below` and `above`
+///    range checked to 64 bits (`RangeCheckChip::decompose_assigned_batch`),
+///    so `lower <= value <= upper`
+///
+/// # Note
+///
+/// - Set `i` (0-based) is stored under tag `i + 1`; row 0 holds (0, 0, 0, 0),
+///   the row looked up by disabled rows, so one lookup serves any number of
+///   sets without an interval of one set passing for another
+/// - The tables are fixed columns, so the sets are committed in the
+///   verifying key: a verifier's key only accepts proofs against its sets
+/// - Columns are shared with Range Check / Aggregation (used in different rows)
+#[derive(Clone, Debug)]
+pub struct SetMembershipConfig {
+    // Tag column - set tag of each checked value
+    // advice[8] - shared with Range Check check/x and Aggregation value
+    pub tag_column: Column<Advice>,
+
+    // Value column - checked values
+    // advice[9] - shared with Range Check diff and Aggregation result
+    pub value_column: Column<Advice>,
+
+    // Interval columns - (lower, upper, member) of each value's interval
+    // advice[0-2] - shared with Range Check chunks
+    pub lower_column: Column<Advice>,
+    pub upper_column: Column<Advice>,
+    pub member_column: Column<Advice>,
+
+    // Distance columns - value - lower and upper - value
+    // advice[3-4] - shared with Range Check chunks
+    pub below_column: Column<Advice>,
+    pub above_column: Column<Advice>,
+
+    // Table columns - (tag, lower, upper, member) intervals of the loaded sets
+    pub tag_table: TableColumn,
+    pub lower_table: TableColumn,
+    pub upper_table: TableColumn,
+    pub member_table: TableColumn,
+
+    // Selector for the interval lookup and bounds (complex_selector required for lookups)
+    pub membership_selector: Selector,
+
+    // Range check config for the 64-bit distances
+    pub range_check_config: RangeCheckConfig,
+}
+
+/// Set Membership Chip
+pub struct SetMembershipChip {
+    config: SetMembershipConfig,
+}
+
+impl SetMembershipChip {
+    /// Create a new SetMembershipChip
+    pub fn new(config: SetMembershipConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Set Membership Gate
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        config: &PoneglyphConfig,
+        range_check_config: &RangeCheckConfig,
+    ) -> SetMembershipConfig {
+        let tag_column = config.advice[8];
+        let value_column = config.advice[9];
+        let lower_column = config.advice[0];
+        let upper_column = config.advice[1];
+        let member_column = config.advice[2];
+        let below_column = config.advice[3];
+        let above_column = config.advice[4];
+        let tag_table = meta.lookup_table_column();
+        let lower_table = meta.lookup_table_column();
+        let upper_table = meta.lookup_table_column();
+        let member_table = meta.lookup_table_column();
+        let membership_selector = meta.complex_selector();
+
+        // The interval is looked up as one tuple, so it only passes for the
+        // set its tag names, with that set's member bit
+        // When selector = 0: (0, 0, 0, 0) is looked up (table row 0)
+        meta.lookup(|meta| {
+            let s = meta.query_selector(membership_selector);
+            let tag = meta.query_advice(tag_column, Rotation::cur());
+            let lower = meta.query_advice(lower_column, Rotation::cur());
+            let upper = meta.query_advice(upper_column, Rotation::cur());
+            let member = meta.query_advice(member_column, Rotation::cur());
+
+            vec![
+                (s.clone() * tag, tag_table),
+                (s.clone() * lower, lower_table),
+                (s.clone() * upper, upper_table),
+                (s * member, member_table),
+            ]
+        });
+
+        // lower <= value <= upper: both distances are range checked
+        meta.create_gate("set interval bounds", |meta| {
+            let s = meta.query_selector(membership_selector);
+            let value = meta.query_advice(value_column, Rotation::cur());
+            let lower = meta.query_advice(lower_column, Rotation::cur());
+            let upper = meta.query_advice(upper_column, Rotation::cur());
+            let below = meta.query_advice(below_column, Rotation::cur());
+            let above = meta.query_advice(above_column, Rotation::cur());
+
+            vec![
+                s.clone() * (below - (value.clone() - lower)),
+                s * (above - (upper - value)),
+            ]
+        });
+
+        SetMembershipConfig {
+            tag_column,
+            value_column,
+            lower_column,
+            upper_column,
+            member_column,
+            below_column,
+            above_column,
+            tag_table,
+            lower_table,
+            upper_table,
+            member_table,
+            membership_selector,
+            range_check_config: range_check_config.clone(),
+        }
+    }
+
+    /// Intervals `(lower, upper, member)` of `set`, partitioning [0, 2^64)
+    /// in increasing order
+    pub fn intervals(set: &[u64]) -> Vec<(u64, u64, bool)> {
+        let mut members = set.to_vec();
+        members.sort_unstable();
+        members.dedup();
+
+        let mut intervals = Vec::with_capacity(2 * members.len() + 1);
+        // Start of the next interval (None past u64::MAX)
+        let mut next = Some(0);
+        for member in members {
+            if let Some(start) = next.filter(|&start| start < member) {
+                intervals.push((start, member - 1, false));
+            }
+            intervals.push((member, member, true));
+            next = member.checked_add(1);
+        }
+        if let Some(start) = next {
+            intervals.push((start, u64::MAX, false));
+        }
+        intervals
+    }
+
+    /// Load the sets into the tables (set `i` under tag `i + 1`)
+    ///
+    /// # Note
+    ///
+    /// Must be called at most once per synthesis, like
+    /// `PoneglyphConfig::load_lookup_table`. Without a call the tables are
+    /// all zeros, which only the disabled rows' (0, 0, 0, 0) lookup matches.
+    pub fn load_sets(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        sets: &[&[u64]],
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        layouter.assign_table(
+            || "set membership table",
+            |mut table| {
+                // Row 0: (0, 0, 0, 0) for disabled rows; unused rows repeat it
+                let tagged = sets.iter().enumerate().flat_map(|(i, set)| {
+                    Self::intervals(set)
+                        .into_iter()
+                        .map(move |(lower, upper, member)| (i as u64 + 1, lower, upper, member))
+                });
+                let rows = std::iter::once((0, 0, 0, false)).chain(tagged);
+                for (row, (tag, lower, upper, member)) in rows.enumerate() {
+                    let columns = [
+                        (config.tag_table, Fr::from(tag)),
+                        (config.lower_table, Fr::from(lower)),
+                        (config.upper_table, Fr::from(upper)),
+                        (config.member_table, Fr::from(member as u64)),
+                    ];
+                    for (column, value) in columns {
+                        table.assign_cell(
+                            || format!("set interval {}", row),
+                            column,
+                            row,
+                            || Value::known(value),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Prove whether `value` is a member of `set`, loaded under `tag`
+    ///
+    /// # Parameters
+    ///
+    /// - `value_cell`: Cell holding the value (e.g. a committed database
+    ///   cell), copied into the checked row; None to witness the value
+    ///
+    /// # Row Layout
+    ///
+    /// - Row 0: tag (fixed by a constant), value, its interval and the
+    ///   distances to its bounds, lookup enabled
+    /// - The distances are then decomposed in a Range Check region
+    ///
+    /// # Returns
+    ///
+    /// The member bit cell (1 = value in `set`)
+    pub fn check_member(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        tag: u64,
+        set: &[u64],
+        value_cell: Option<&AssignedCell<Fr, Fr>>,
+        value: Value<u64>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let config = &self.config;
+        let mut x = 0;
+        value.map(|value| x = value);
+        let (lower, upper, member) = Self::intervals(set)
+            .into_iter()
+            .find(|&(lower, upper, _)| lower <= x && x <= upper)
+            .ok_or(Error::Synthesis)?;
+
+        let (member_cell, distances) = layouter.assign_region(
+            || "set membership",
+            |mut region| {
+                config.membership_selector.enable(&mut region, 0)?;
+                region.assign_advice_from_constant(
+                    || "set tag",
+                    config.tag_column,
+                    0,
+                    Fr::from(tag),
+                )?;
+                match value_cell {
+                    Some(cell) => {
+                        cell.copy_advice(|| "value", &mut region, config.value_column, 0)?;
+                    }
+                    None => {
+                        region.assign_advice(
+                            || "value",
+                            config.value_column,
+                            0,
+                            || value.map(Fr::from),
+                        )?;
+                    }
+                }
+                let mut assign = |name: &'static str, column, v: u64| {
+                    region.assign_advice(|| name, column, 0, || Value::known(Fr::from(v)))
+                };
+                assign("lower", config.lower_column, lower)?;
+                assign("upper", config.upper_column, upper)?;
+                let member_cell = assign("member", config.member_column, member as u64)?;
+                let below = assign("below", config.below_column, x - lower)?;
+                let above = assign("above", config.above_column, upper - x)?;
+                Ok((member_cell, [below, above]))
+            },
+        )?;
+
+        RangeCheckChip::new(config.range_check_config.clone()).decompose_assigned_batch(
+            layouter.namespace(|| "interval distances"),
+            &distances,
+            &[x - lower, upper - x],
+        )?;
+
+        Ok(member_cell)
+    }
+}
@@ -1,13 +1,29 @@
+use std::collections::HashMap;
+
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
     poly::Rotation,
 };
 use pasta_curves::pallas::Base as Fr;
+use ff::Field;
 
 use super::config::PoneglyphConfig;
+use super::poseidon::{PoseidonChip, PoseidonConfig};
 use super::range_check::RangeCheckConfig;
 
+/// NULL sentinel of a nullable sort's rows (`sql::NULL`)
+const NULL: u64 = u64::MAX;
+
+/// Input values of a sort, with the cells they are copied from (if any)
+type SortInput<'a> = (Vec<Value<u64>>, Option<&'a [AssignedCell<Fr, Fr>]>);
+
+/// Output cells of a sort, and the cells of each payload in sorted order
+type SortedCells = (Vec<AssignedCell<Fr, Fr>>, Vec<Vec<AssignedCell<Fr, Fr>>>);
+
+/// Routed cells of a switch network, and the bit cell of each switch
+type RoutedCells = (Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>);
+
 /// Sort Gate Configuration
 /// According to Paper Section 4.2: Sorting verification with Grand Product Argument
 /// 
@@ -21,17 +37,50 @@ use super::range_check::RangeCheckConfig;
 /// 
 /// 1. **Sort Order Check**: `diff = B[i+1] - B[i]` and `diff ≥ 0` check
 ///    - Diff calculation: `diff = b_i_next - b_i`
-///    - Diff ≥ 0 check: decomposed into 8-bit chunks with `decompose_64bit` and checked
+///    - Diff ≥ 0 check: decomposed into 8-bit chunks with `decompose_assigned_batch` and checked
+///      (the decomposed value is copied from the diff cell)
+///    - Descending sorts (`ORDER BY ... DESC`) use `diff = b_i - b_i_next`
 /// 
 /// 2. **Permutation Verification**: Permutation verification with Grand Product Argument
-///    - Sorted input and sorted output are compared element-by-element
+///    - The input in sorted order is routed from the input rows by a Beneš
+///      switch network (constraint 5), and compared element-by-element
+///      with the sorted output
 ///    - Explicit copy constraints are created using `constrain_equal`
 ///    - Halo2's permutation argument verifies with Grand Product Polynomial
+///    - `SortAlgorithm::CopyConstraint` copies each input row to its output
+///      row instead, without the sorted input
+///    - Payloads (`sort_and_verify_with_payloads`) are moved with the rows:
+///      routed through the same network, each switch bit copied from the
+///      input's switch (CopyConstraint: copied from the same input rows)
+///    - `SortAlgorithm::MultisetHash` checks `∏ (x_i + γ) = ∏ (y_i + γ)` with
+///      running products instead (input in the input column of the output
+///      rows, γ in `gamma_column`, products in `input_product_column` /
+///      `output_product_column`), γ being the Poseidon commitment to the
+///      input and output rows
+/// 
+/// 3. **NULL Placement** (nullable sorts): `is_null` bit per output row
+///    - `is_null = 1` exactly when the row holds NULL (`u64::MAX`): `is_null =
+///      1 - (B[i] - NULL) · inv` and `is_null · (B[i] - NULL) = 0`, `inv` in
+///      `null_inverse_column`
+///    - NULL rows are partitioned to the requested end (NULLS FIRST / NULLS LAST)
+///    - The sort order check only applies between two non-null rows
+/// 
+/// 4. **Compare-Exchange** (`SortAlgorithm::Bitonic`): `{min, max} = {a, b}`
+///    - `min + max = a + b` and `(min - a) · (min - b) = 0`
+///    - `diff = max - min`, decomposed into chunks (so `min ≤ max`)
+/// 
+/// 5. **Switch** (`SortAlgorithm::Permutation`): `{lo, hi} = {a, b}` by a
+///    witnessed bit s (a in `input_column`, b in `output_column`, s in
+///    `diff_column`, lo / hi in `min_column` / `max_column`)
+///    - `s · (1 - s) = 0`, `lo = a + s · (b - a)` and `lo + hi = a + b`
 /// 
 /// # Note
 /// 
 /// - Columns are shared with Range Check (used in different rows)
-/// - Input column is used for both input and sorted_input (in different rows)
+/// - Input column is used for the input (own region), the padding and
+///   switch inputs of the permutation network, and the MultisetHash input
+///   (output region, same rows as the output); no region is padded with
+///   `input.len()` empty rows
 #[derive(Clone, Debug)]
 pub struct SortConfig {
     // Advice column for input array
@@ -49,10 +98,253 @@ pub struct SortConfig {
     // Selector for sorting check
     pub sort_selector: Selector,
     
+    // Selector for descending sorting check (diff = B[i] - B[i+1])
+    pub desc_sort_selector: Selector,
+    
+    // NULL flag column - is_null bit of each output row (nullable sorts only)
+    // advice[5] - shared with Range Check chunk[5]
+    pub null_column: Column<Advice>,
+    
+    // Inverse of B[i] - NULL (0 on NULL rows), proving the is_null bit
+    // advice[9] - shared with Range Check diff
+    pub null_inverse_column: Column<Advice>,
+    
+    // Selector for the is_null check (every row of a nullable sort)
+    pub null_selector: Selector,
+    
+    // Selectors for nullable sorting check (one per NULL placement)
+    pub nulls_first_selector: Selector,
+    pub nulls_last_selector: Selector,
+    pub nulls_first_desc_selector: Selector,
+    pub nulls_last_desc_selector: Selector,
+    
+    // Compare-exchange columns of the bitonic network (a, b in input / output)
+    // advice[5] (shared with null_column) and advice[6] - different rows
+    pub min_column: Column<Advice>,
+    pub max_column: Column<Advice>,
+    
+    // Selector for bitonic compare-exchange
+    pub bitonic_selector: Selector,
+    
+    // Selector for the switches routing the input into sorted order (Permutation)
+    pub switch_selector: Selector,
+    
+    // Multiset check columns (output rows of `SortAlgorithm::MultisetHash`)
+    // advice[6] - γ, the same on every row
+    pub gamma_column: Column<Advice>,
+    // advice[7] - running product of (input + γ)
+    pub input_product_column: Column<Advice>,
+    // advice[8] - running product of (output + γ)
+    pub output_product_column: Column<Advice>,
+    
+    // Selectors for the running products (first row / later rows)
+    pub product_start_selector: Selector,
+    pub product_selector: Selector,
+    
+    // Poseidon Gate deriving γ (`SortAlgorithm::MultisetHash` only)
+    pub poseidon_config: Option<PoseidonConfig>,
+    
+    // Sort verification used by `sort_and_verify`
+    pub algorithm: SortAlgorithm,
+    
     // Range Check integration (for B[i+1] - B[i] ≥ 0 check)
     pub range_check_config: RangeCheckConfig,
 }
 
+impl SortConfig {
+    /// Same configuration with another sort verification algorithm
+    /// (both are configured; this selects the one `sort_and_verify` uses)
+    pub fn with_algorithm(mut self, algorithm: SortAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+    
+    /// Same configuration with the Poseidon Gate that derives the multiset
+    /// check's γ (`SortAlgorithm::MultisetHash`)
+    pub fn with_poseidon(mut self, poseidon_config: PoseidonConfig) -> Self {
+        self.poseidon_config = Some(poseidon_config);
+        self
+    }
+}
+
+/// Sort verification algorithm
+/// 
+/// # Tradeoff
+/// 
+/// - `Permutation`: the prover witnesses the sorted output and `n - 1`
+///   diffs are range checked; the input in sorted order is routed from the
+///   input rows by a Beneš network over the input padded with `u64::MAX` to
+///   a power of two `p`, whose `p/2 · (2 log p - 1)` switches have
+///   witnessed bits and no range check, and is copy-constrained to the
+///   output rows. About `3n + p/2 · (2 log p - 1)` rows. halo2_proofs 0.3
+///   has no shuffle argument or verifier challenge, so the network replaces
+///   copy constraints from each sorted row to its input row: the layout
+///   depends only on `n`, and keys fit every input of that length.
+/// - `Bitonic`: the output is computed in-circuit by a bitonic sorting
+///   network of compare-exchange rows, so no sorted witness or permutation
+///   check is needed and the layout depends only on `n`. The input is padded
+///   with `u64::MAX` to a power of two `p`, and the network takes
+///   `p/2 · log p · (log p + 1) / 2` comparisons, each with a range-checked
+///   diff: more rows than `Permutation` for every `n > 2` (measured: 1408 vs
+///   479 rows for 64 values, 57344 vs 11751 for 1000). It is not a row
+///   optimization; use it only where the output must not be a witness.
+/// - `CopyConstraint`: the sorted output is witnessed once and each output
+///   row is copy-constrained to the input row it came from, so the switch
+///   network of `Permutation` is not assigned and the output
+///   is tied to the input cells themselves. The copy constraints encode the
+///   sorting permutation, so the input must be known at key generation and
+///   keys only fit inputs with the same sorting permutation.
+/// - `MultisetHash`: the sorted output is witnessed once, next to the input
+///   values in their own order, and running products show
+///   `∏ (x_i + γ) = ∏ (y_i + γ)`, so neither a switch network nor a
+///   separate input region is assigned. Without a verifier challenge
+///   (halo2_proofs 0.3 has none), γ is derived in the circuit as the
+///   Poseidon commitment to the input and output rows, bound to their
+///   cells: one hash per value, about `130n` rows. This is not a row
+///   optimization: it takes far more rows than `Permutation` at every `n`.
+///   Its layout depends only on `n`, and it needs a configuration with the
+///   Poseidon Gate (`SortConfig::with_poseidon`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortAlgorithm {
+    /// Witnessed sorted output with a permutation check (default)
+    #[default]
+    Permutation,
+    /// Bitonic sorting network over the input padded to a power of two
+    Bitonic,
+    /// Witnessed sorted output copy-constrained to the input rows
+    CopyConstraint,
+    /// Witnessed sorted output with a running-product multiset check
+    MultisetHash,
+}
+
+impl SortAlgorithm {
+    /// Number of 64-bit diff decompositions to sort `len` values
+    pub fn diff_count(&self, len: usize) -> usize {
+        match self {
+            SortAlgorithm::Permutation
+            | SortAlgorithm::CopyConstraint
+            | SortAlgorithm::MultisetHash => len.saturating_sub(1),
+            SortAlgorithm::Bitonic => bitonic_network(len).len(),
+        }
+    }
+}
+
+/// Compare-exchanges `(i, j, ascending)` of a bitonic sorting network over
+/// `len` values padded to a power of two, in order; `ascending` puts the
+/// minimum at `i` (the final merge is ascending everywhere)
+fn bitonic_network(len: usize) -> Vec<(usize, usize, bool)> {
+    let size = len.next_power_of_two();
+    let mut network = Vec::new();
+    if len < 2 {
+        return network;
+    }
+    let mut block = 2;
+    while block <= size {
+        let mut stride = block / 2;
+        while stride > 0 {
+            for i in 0..size {
+                let j = i ^ stride;
+                if j > i {
+                    network.push((i, j, i & block == 0));
+                }
+            }
+            stride /= 2;
+        }
+        block *= 2;
+    }
+    network
+}
+
+/// Switches `(i, j)` of a Beneš network over `size` values (a power of
+/// two), in order: a stage pairing each `i < size/2` with `i + size/2`,
+/// the networks of both halves, and the same stage again
+fn benes_network(offset: usize, size: usize, network: &mut Vec<(usize, usize)>) {
+    if size < 2 {
+        return;
+    }
+    let half = size / 2;
+    let stage = (offset..offset + half).map(|i| (i, i + half));
+    network.extend(stage.clone());
+    if size > 2 {
+        benes_network(offset, half, network);
+        benes_network(offset + half, half, network);
+        network.extend(stage);
+    }
+}
+
+/// Switch bits (1 = swap) of `benes_network` routing the value at position
+/// `x` to position `targets[x]` (a permutation of `0..size`), in network order
+///
+/// Looping algorithm: the two values of a first-stage switch take
+/// different halves, as do the two values of a last-stage switch, so
+/// following both pairings alternately colors every value with its half.
+fn benes_route(targets: &[usize], bits: &mut Vec<bool>) {
+    let size = targets.len();
+    if size < 2 {
+        return;
+    }
+    let half = size / 2;
+    if size == 2 {
+        bits.push(targets[0] == 1);
+        return;
+    }
+    let mut sources = vec![0; size];
+    for (x, &target) in targets.iter().enumerate() {
+        sources[target] = x;
+    }
+    let mut lower = vec![None; size];
+    for start in 0..size {
+        let mut x = start;
+        while lower[x].is_none() {
+            let partner = (x + half) % size;
+            lower[x] = Some(true);
+            lower[partner] = Some(false);
+            // The value sharing partner's last-stage switch takes x's half
+            x = sources[(targets[partner] + half) % size];
+        }
+    }
+    let lower: Vec<bool> = lower.into_iter().map(|half| half == Some(true)).collect();
+    
+    // First stage: the lower half gets the value colored lower
+    bits.extend((0..half).map(|i| !lower[i]));
+    let (mut lower_targets, mut upper_targets) = (vec![0; half], vec![0; half]);
+    for (x, &target) in targets.iter().enumerate() {
+        let row = x % half;
+        if lower[x] {
+            lower_targets[row] = target % half;
+        } else {
+            upper_targets[row] = target % half;
+        }
+    }
+    benes_route(&lower_targets, bits);
+    benes_route(&upper_targets, bits);
+    // Last stage: row i's lower value goes to i + half unless it targets i
+    bits.extend((0..half).map(|i| !lower[sources[i]]));
+}
+
+/// Input row each `sorted` row is taken from (`SortAlgorithm::CopyConstraint`)
+///
+/// Equal values are matched in input order. Fails with `Error::Synthesis` if
+/// an input value is unknown or `sorted` is not a permutation of the input.
+fn sort_sources(input: &[Value<u64>], sorted: &[u64]) -> Result<Vec<usize>, Error> {
+    let mut rows: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (j, value) in input.iter().enumerate().rev() {
+        let mut known = None;
+        value.map(|value| known = Some(value));
+        rows.entry(known.ok_or(Error::Synthesis)?)
+            .or_default()
+            .push(j);
+    }
+    sorted
+        .iter()
+        .map(|value| {
+            rows.get_mut(value)
+                .and_then(Vec::pop)
+                .ok_or(Error::Synthesis)
+        })
+        .collect()
+}
+
 /// Sort Chip
 /// Paper Section 4.2 implementation
 pub struct SortChip {
@@ -81,9 +373,26 @@ impl SortChip {
         let input_column = config.advice[2];
         let output_column = config.advice[3];
         let diff_column = config.advice[4];
+        let null_column = config.advice[5];
+        let null_inverse_column = config.advice[9];
+        let min_column = config.advice[5];
+        let max_column = config.advice[6];
+        let gamma_column = config.advice[6];
+        let input_product_column = config.advice[7];
+        let output_product_column = config.advice[8];
         
-        // Create selector
+        // Create selectors
         let sort_selector = meta.selector();
+        let desc_sort_selector = meta.selector();
+        let nulls_first_selector = meta.selector();
+        let nulls_last_selector = meta.selector();
+        let nulls_first_desc_selector = meta.selector();
+        let nulls_last_desc_selector = meta.selector();
+        let null_selector = meta.selector();
+        let bitonic_selector = meta.selector();
+        let switch_selector = meta.selector();
+        let product_start_selector = meta.selector();
+        let product_selector = meta.selector();
         
         // Add sorting constraint
         // Paper Section 4.2: B[i] ≤ B[i+1] check
@@ -91,21 +400,189 @@ impl SortChip {
         // This constraint verifies that output is sorted:
         // 1. diff = B[i+1] - B[i] is calculated and assigned to diff_column
         // 2. Constraint: diff = b_i_next - b_i (verifies that diff is calculated correctly)
-        // 3. diff ≥ 0 check: decomposed into 8-bit chunks with `decompose_64bit` and checked
+        // 3. diff ≥ 0 check: the diff cell is decomposed into 8-bit chunks with
+        //    `decompose_assigned_batch` and checked
         //    (done in sort_and_verify)
-        meta.create_gate("sort order check", |meta| {
-            let s = meta.query_selector(sort_selector);
-            let b_i = meta.query_advice(output_column, Rotation::cur());
-            let b_i_next = meta.query_advice(output_column, Rotation::next());
+        // 
+        // Descending: diff = B[i] - B[i+1], so diff ≥ 0 means B[i] ≥ B[i+1]
+        for (name, selector, descending) in [
+            ("sort order check", sort_selector, false),
+            ("sort order check (descending)", desc_sort_selector, true),
+        ] {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let b_i = meta.query_advice(output_column, Rotation::cur());
+                let b_i_next = meta.query_advice(output_column, Rotation::next());
+                let diff = meta.query_advice(diff_column, Rotation::cur());
+                
+                // Constraint: diff = b_i_next - b_i (b_i - b_i_next when descending)
+                // This verifies that diff is calculated correctly
+                // diff ≥ 0 check is done with decompose_assigned_batch (in sort_and_verify)
+                let diff_expr = if descending {
+                    b_i - b_i_next
+                } else {
+                    b_i_next - b_i
+                };
+                
+                // Constraint: when selector is active, diff = diff_expr
+                vec![s * (diff - diff_expr)]
+            });
+        }
+        
+        // Add nullable sorting constraints
+        // NULLS FIRST: a NULL row may only follow a NULL row
+        // NULLS LAST: a non-null row may only follow a non-null row
+        for (name, selector, nulls_first, descending) in [
+            (
+                "sort order check (nulls first)",
+                nulls_first_selector,
+                true,
+                false,
+            ),
+            (
+                "sort order check (nulls last)",
+                nulls_last_selector,
+                false,
+                false,
+            ),
+            (
+                "sort order check (nulls first, descending)",
+                nulls_first_desc_selector,
+                true,
+                true,
+            ),
+            (
+                "sort order check (nulls last, descending)",
+                nulls_last_desc_selector,
+                false,
+                true,
+            ),
+        ] {
+            meta.create_gate(name, |meta| {
+                let s = meta.query_selector(selector);
+                let b_i = meta.query_advice(output_column, Rotation::cur());
+                let b_i_next = meta.query_advice(output_column, Rotation::next());
+                let diff = meta.query_advice(diff_column, Rotation::cur());
+                let n_i = meta.query_advice(null_column, Rotation::cur());
+                let n_i_next = meta.query_advice(null_column, Rotation::next());
+                let one = Expression::Constant(Fr::ONE);
+                
+                // is_null bits are boolean
+                let bool_cur = n_i.clone() * (one.clone() - n_i.clone());
+                let bool_next = n_i_next.clone() * (one.clone() - n_i_next.clone());
+                
+                // Partition: NULL rows are contiguous at the requested end
+                let placement = if nulls_first {
+                    n_i_next.clone() * (one.clone() - n_i.clone())
+                } else {
+                    n_i.clone() * (one.clone() - n_i_next.clone())
+                };
+                
+                // diff = b_i_next - b_i (b_i - b_i_next when descending)
+                // only between two non-null rows
+                let diff_expr = if descending {
+                    b_i - b_i_next
+                } else {
+                    b_i_next - b_i
+                };
+                let order = (one.clone() - n_i) * (one - n_i_next) * (diff - diff_expr);
+                
+                vec![
+                    s.clone() * bool_cur,
+                    s.clone() * bool_next,
+                    s.clone() * placement,
+                    s * order,
+                ]
+            });
+        }
+        
+        // Add is_null check (is-zero gadget on B[i] - NULL)
+        // is_null = 1 - (B[i] - NULL) · inv, and is_null · (B[i] - NULL) = 0:
+        // a NULL row has is_null = 1, any other row has is_null = 0 (with
+        // inv = 1 / (B[i] - NULL)), so the flag is bound to the row's value
+        meta.create_gate("null flag check", |meta| {
+            let s = meta.query_selector(null_selector);
+            let b = meta.query_advice(output_column, Rotation::cur());
+            let n = meta.query_advice(null_column, Rotation::cur());
+            let inv = meta.query_advice(null_inverse_column, Rotation::cur());
+            let delta = b - Expression::Constant(Fr::from(NULL));
+            
+            vec![
+                s.clone() * (Expression::Constant(Fr::ONE) - n.clone() - delta.clone() * inv),
+                s * (n * delta),
+            ]
+        });
+        
+        // Add compare-exchange constraint (bitonic network)
+        // {min, max} = {a, b}: same sum, and min is one of a, b
+        // diff = max - min is range checked (min ≤ max)
+        meta.create_gate("bitonic compare-exchange", |meta| {
+            let s = meta.query_selector(bitonic_selector);
+            let a = meta.query_advice(input_column, Rotation::cur());
+            let b = meta.query_advice(output_column, Rotation::cur());
+            let min = meta.query_advice(min_column, Rotation::cur());
+            let max = meta.query_advice(max_column, Rotation::cur());
             let diff = meta.query_advice(diff_column, Rotation::cur());
             
-            // Constraint: diff = b_i_next - b_i
-            // This verifies that diff is calculated correctly
-            // diff ≥ 0 check is done with decompose (in sort_and_verify)
-            let diff_expr = b_i_next - b_i;
+            vec![
+                s.clone() * (min.clone() + max.clone() - a.clone() - b.clone()),
+                s.clone() * ((min.clone() - a) * (min.clone() - b)),
+                s * (diff - (max - min)),
+            ]
+        });
+        
+        // Switch constraint (Beneš network of SortAlgorithm::Permutation)
+        // lo = b when the bit is set (swap), a otherwise, and hi is the other
+        // value; no range check, the switches only move values
+        meta.create_gate("permutation switch", |meta| {
+            let s = meta.query_selector(switch_selector);
+            let a = meta.query_advice(input_column, Rotation::cur());
+            let b = meta.query_advice(output_column, Rotation::cur());
+            let bit = meta.query_advice(diff_column, Rotation::cur());
+            let lo = meta.query_advice(min_column, Rotation::cur());
+            let hi = meta.query_advice(max_column, Rotation::cur());
+            
+            vec![
+                s.clone() * (bit.clone() * (Expression::Constant(Fr::ONE) - bit.clone())),
+                s.clone() * (lo.clone() - a.clone() - bit * (b.clone() - a.clone())),
+                s * (lo + hi - a - b),
+            ]
+        });
+        
+        // Multiset check (SortAlgorithm::MultisetHash)
+        // First row: p_in = x + γ, p_out = y + γ
+        // Later rows: p_in = p_in_prev · (x + γ), p_out = p_out_prev · (y + γ),
+        // and γ = γ_prev (γ is copied from its Poseidon commitment on the first row)
+        meta.create_gate("multiset product start", |meta| {
+            let s = meta.query_selector(product_start_selector);
+            let x = meta.query_advice(input_column, Rotation::cur());
+            let y = meta.query_advice(output_column, Rotation::cur());
+            let gamma = meta.query_advice(gamma_column, Rotation::cur());
+            let p_in = meta.query_advice(input_product_column, Rotation::cur());
+            let p_out = meta.query_advice(output_product_column, Rotation::cur());
+            
+            vec![
+                s.clone() * (p_in - (x + gamma.clone())),
+                s * (p_out - (y + gamma)),
+            ]
+        });
+        
+        meta.create_gate("multiset product", |meta| {
+            let s = meta.query_selector(product_selector);
+            let x = meta.query_advice(input_column, Rotation::cur());
+            let y = meta.query_advice(output_column, Rotation::cur());
+            let gamma = meta.query_advice(gamma_column, Rotation::cur());
+            let gamma_prev = meta.query_advice(gamma_column, Rotation::prev());
+            let p_in = meta.query_advice(input_product_column, Rotation::cur());
+            let p_in_prev = meta.query_advice(input_product_column, Rotation::prev());
+            let p_out = meta.query_advice(output_product_column, Rotation::cur());
+            let p_out_prev = meta.query_advice(output_product_column, Rotation::prev());
             
-            // Constraint: when selector is active, diff = b_i_next - b_i
-            vec![s * (diff - diff_expr)]
+            vec![
+                s.clone() * (p_in - p_in_prev * (x + gamma.clone())),
+                s.clone() * (p_out - p_out_prev * (y + gamma.clone())),
+                s * (gamma - gamma_prev),
+            ]
         });
         
         SortConfig {
@@ -113,6 +590,25 @@ impl SortChip {
             output_column,
             diff_column,
             sort_selector,
+            desc_sort_selector,
+            null_column,
+            null_inverse_column,
+            null_selector,
+            nulls_first_selector,
+            nulls_last_selector,
+            nulls_first_desc_selector,
+            nulls_last_desc_selector,
+            min_column,
+            max_column,
+            bitonic_selector,
+            switch_selector,
+            gamma_column,
+            input_product_column,
+            output_product_column,
+            product_start_selector,
+            product_selector,
+            poseidon_config: None,
+            algorithm: SortAlgorithm::default(),
             range_check_config: range_check_config.clone(),
         }
     }
@@ -129,59 +625,427 @@ impl SortChip {
     /// # Operation Steps
     /// 
     /// 1. Assign input
-    /// 2. Assign input in sorted order (for permutation verification)
-    /// 3. Assign output and enable sorting constraints
-    /// 4. Diff ≥ 0 check: Decompose each diff and check
-    /// 5. Permutation constraints: Verify with Grand Product Argument
+    /// 2. Assign output and enable sorting constraints
+    /// 3. Diff ≥ 0 check: Decompose each diff and check
+    /// 4. Permutation constraints: Verify with Grand Product Argument (the
+    ///    input is routed into sorted order by a switch network, and each
+    ///    routed row is copied to its output row)
+    /// 
+    /// With `SortAlgorithm::Bitonic` the output is computed by
+    /// `sort_with_network` instead (`sorted_values` only fixes the length).
+    /// With `SortAlgorithm::CopyConstraint` step 4 copies each input cell to its output row. With
+    /// `SortAlgorithm::MultisetHash` step 1 is part of step 2 (input next to
+    /// the output) and step 4 compares the running products.
     /// 
     /// # Return Value
     /// 
     /// List of output cells (cells of sorted array)
     pub fn sort_and_verify(
         &self,
-        mut layouter: impl Layouter<Fr>,
+        layouter: impl Layouter<Fr>,
         input: Vec<Value<u64>>,
         sorted_values: Vec<u64>,
     ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
-        // 1. Assign input
-        let _input_cells = self.assign_input(layouter.namespace(|| "input"), &input)?;
+        match self.config.algorithm {
+            SortAlgorithm::Permutation
+            | SortAlgorithm::CopyConstraint
+            | SortAlgorithm::MultisetHash => self
+                .verify_sorted(layouter, (input, None), sorted_values, None, false, &[])
+                .map(|(cells, _)| cells),
+            SortAlgorithm::Bitonic => {
+                if sorted_values.len() != input.len() {
+                    return Err(Error::Synthesis);
+                }
+                self.sort_with_network(layouter, input)
+            }
+        }
+    }
+    
+    /// Sort array with a bitonic sorting network (`SortAlgorithm::Bitonic`)
+    /// 
+    /// The input is padded with `u64::MAX` to a power of two, and each
+    /// compare-exchange copies its two operands and outputs their minimum and
+    /// maximum; the first `input.len()` network outputs are the sorted input
+    /// (padding sorts last).
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Padding region: `u64::MAX` constants (input column)
+    /// - Network region, row r: a, b, diff, min, max of compare-exchange r
+    /// - Diff decomposition region: one row per compare-exchange
+    /// 
+    /// # Return Value
+    /// 
+    /// List of output cells (cells of sorted array)
+    pub fn sort_with_network(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        input: Vec<Value<u64>>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let len = input.len();
+        let network = bitonic_network(len);
+        let size = if network.is_empty() {
+            len
+        } else {
+            len.next_power_of_two()
+        };
         
-        // 2. Assign input in sorted order (for permutation verification)
-        // Paper Section 4.2: Permutation verification with Grand Product Argument
-        // To prove that input and output have the same multiset,
-        // we sort both arrays and compare element-by-element
-        // 
-        // Note: We assign sorted_input_cells to input column (in rows after input)
-        // This way, input and sorted_input are in the same column but different rows
-        // and we can compare sorted_input with output using constrain_equal
-        let sorted_input_cells: Vec<AssignedCell<Fr, Fr>> = layouter.assign_region(
-            || "sorted input assignment",
+        // Network witness (input values are all 0 without witnesses)
+        let mut values = vec![u64::MAX; size];
+        for (slot, value) in values.iter_mut().zip(&input) {
+            *slot = 0;
+            value.map(|v| *slot = v);
+        }
+        
+        let mut cells = self.assign_input(layouter.namespace(|| "input"), &input)?;
+        if size > len {
+            let padding = layouter.assign_region(
+                || "bitonic padding",
+                |mut region| {
+                    (0..size - len)
+                        .map(|i| {
+                            region.assign_advice_from_constant(
+                                || format!("padding_{}", i),
+                                self.config.input_column,
+                                i,
+                                Fr::from(u64::MAX),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            cells.extend(padding);
+        }
+        
+        // Compare-exchanges in network order; each output replaces its operand
+        let mut diffs = Vec::with_capacity(network.len());
+        let (cells, diff_cells) = layouter.assign_region(
+            || "bitonic network",
             |mut region| {
-                sorted_values
-                    .iter()
-                    .enumerate()
-                    .map(|(i, val)| {
-                        region.assign_advice(
-                            || format!("sorted_input_{}", i),
-                            self.config.input_column, // Reuse input column (in different rows)
-                            input.len() + i, // Assign to rows after input
-                            || Value::known(Fr::from(*val)),
-                        )
-                    })
-                    .collect()
+                let mut cells = cells.clone();
+                let mut values = values.clone();
+                let mut diff_cells = Vec::with_capacity(network.len());
+                diffs.clear();
+                
+                for (row, &(i, j, ascending)) in network.iter().enumerate() {
+                    self.config.bitonic_selector.enable(&mut region, row)?;
+                    cells[i].copy_advice(|| "a", &mut region, self.config.input_column, row)?;
+                    cells[j].copy_advice(|| "b", &mut region, self.config.output_column, row)?;
+                    
+                    let (min, max) = (values[i].min(values[j]), values[i].max(values[j]));
+                    let min_cell = region.assign_advice(
+                        || format!("min_{}", row),
+                        self.config.min_column,
+                        row,
+                        || Value::known(Fr::from(min)),
+                    )?;
+                    let max_cell = region.assign_advice(
+                        || format!("max_{}", row),
+                        self.config.max_column,
+                        row,
+                        || Value::known(Fr::from(max)),
+                    )?;
+                    diff_cells.push(region.assign_advice(
+                        || format!("diff_{}", row),
+                        self.config.diff_column,
+                        row,
+                        || Value::known(Fr::from(max - min)),
+                    )?);
+                    diffs.push(max - min);
+                    
+                    let (low, high) = if ascending { (i, j) } else { (j, i) };
+                    (values[low], values[high]) = (min, max);
+                    (cells[low], cells[high]) = (min_cell, max_cell);
+                }
+                Ok((cells, diff_cells))
             },
         )?;
         
-        // 3. Assign output and enable sorting constraints
+        // min ≤ max: every diff is a 64-bit value (bound to its diff cell)
+        use super::range_check::RangeCheckChip;
+        let range_check_chip = RangeCheckChip::new(self.config.range_check_config.clone());
+        range_check_chip.decompose_assigned_batch(
+            layouter.namespace(|| "decompose bitonic diffs"),
+            &diff_cells,
+            &diffs,
+        )?;
+        
+        Ok(cells.into_iter().take(len).collect())
+    }
+    
+    /// Sort array in descending order and verify
+    /// 
+    /// Same as `sort_and_verify`, with the `B[i] ≥ B[i+1]` check
+    /// (`sorted_values` must be non-increasing). Uses
+    /// `SortAlgorithm::Permutation` unless `CopyConstraint` or
+    /// `MultisetHash` is selected.
+    pub fn sort_and_verify_descending(
+        &self,
+        layouter: impl Layouter<Fr>,
+        input: Vec<Value<u64>>,
+        sorted_values: Vec<u64>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.verify_sorted(layouter, (input, None), sorted_values, None, true, &[])
+            .map(|(cells, _)| cells)
+    }
+    
+    /// Sort array containing NULLs and verify
+    /// 
+    /// Same as `sort_and_verify`, but each output row carries an `is_null` bit.
+    /// NULL rows hold NULL (`u64::MAX`) in both input and output, with
+    /// `is_null` proven from the value, and are placed at the start
+    /// (`nulls_first`) or end of the output; the `B[i] ≤ B[i+1]` check
+    /// (`B[i] ≥ B[i+1]` when `descending`) only applies to the non-null rows.
+    /// Uses `SortAlgorithm::Permutation` unless `CopyConstraint` or
+    /// `MultisetHash` is selected.
+    /// 
+    /// # Parameters
+    /// 
+    /// - `null_flags`: `is_null` bit of each `sorted_values` row
+    /// - `nulls_first`: NULLS FIRST (true) or NULLS LAST (false)
+    /// - `descending`: Non-null rows are non-increasing
+    pub fn sort_and_verify_with_nulls(
+        &self,
+        layouter: impl Layouter<Fr>,
+        input: Vec<Value<u64>>,
+        sorted_values: Vec<u64>,
+        null_flags: &[bool],
+        nulls_first: bool,
+        descending: bool,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if null_flags.len() != sorted_values.len() {
+            return Err(Error::Synthesis);
+        }
+        let nulls = Some((null_flags, nulls_first));
+        self.verify_sorted(
+            layouter,
+            (input, None),
+            sorted_values,
+            nulls,
+            descending,
+            &[],
+        )
+        .map(|(cells, _)| cells)
+    }
+    
+    /// Sort already assigned cells and verify
+    /// 
+    /// Same as `sort_and_verify` (`sort_and_verify_descending` when
+    /// `descending`), with each input row copy-constrained to its cell in
+    /// `input_cells` (e.g. aggregation results ranked for a LIMIT).
+    /// 
+    /// # Parameters
+    /// 
+    /// - `input_cells`: Cells holding the input values
+    /// - `input`: Values of `input_cells`
+    pub fn sort_and_verify_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        input_cells: &[AssignedCell<Fr, Fr>],
+        input: Vec<Value<u64>>,
+        sorted_values: Vec<u64>,
+        descending: bool,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if input_cells.len() != input.len() {
+            return Err(Error::Synthesis);
+        }
+        self.verify_sorted(
+            layouter,
+            (input, Some(input_cells)),
+            sorted_values,
+            None,
+            descending,
+            &[],
+        )
+        .map(|(cells, _)| cells)
+    }
+    
+    /// Sort rows with payloads and verify
+    /// 
+    /// Same as `sort_and_verify` (`sort_and_verify_descending` when
+    /// `descending`), and each payload column is put in the sorted order of
+    /// the input: routed through the same switch network as the input, each
+    /// switch bit copied from the input's (`SortAlgorithm::Permutation`), or
+    /// taken from the same input rows (`SortAlgorithm::CopyConstraint`).
+    /// Rows with equal input values may come out in either order.
+    /// 
+    /// # Parameters
+    /// 
+    /// - `input`: Input values, with the cells they are copied from (None:
+    ///   witnessed)
+    /// - `payloads`: Values of each payload column in input order, with the
+    ///   cells they are copied from (e.g. the aggregated column of a GROUP BY)
+    /// 
+    /// # Return Value
+    /// 
+    /// Output cells, and the cells of each payload in sorted order;
+    /// `Error::Synthesis` for payloads of another length or with the
+    /// `Bitonic` or `MultisetHash` algorithm
+    pub fn sort_and_verify_with_payloads(
+        &self,
+        layouter: impl Layouter<Fr>,
+        input: SortInput<'_>,
+        sorted_values: Vec<u64>,
+        payloads: &[SortInput<'_>],
+        descending: bool,
+    ) -> Result<SortedCells, Error> {
+        if input.1.is_some_and(|cells| cells.len() != input.0.len()) {
+            return Err(Error::Synthesis);
+        }
+        self.verify_sorted(layouter, input, sorted_values, None, descending, payloads)
+    }
+    
+    /// Shared implementation of `sort_and_verify`, `sort_and_verify_descending`,
+    /// `sort_and_verify_with_nulls`, `sort_and_verify_copied` and
+    /// `sort_and_verify_with_payloads`
+    fn verify_sorted(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        (input, input_sources): SortInput<'_>,
+        sorted_values: Vec<u64>,
+        nulls: Option<(&[bool], bool)>,
+        descending: bool,
+        payloads: &[SortInput<'_>],
+    ) -> Result<SortedCells, Error> {
+        // Output is a permutation of the input (same length)
+        if sorted_values.len() != input.len() {
+            return Err(Error::Synthesis);
+        }
+        // Payloads follow the switches or copies of the input rows
+        let routes_payloads = matches!(
+            self.config.algorithm,
+            SortAlgorithm::Permutation | SortAlgorithm::CopyConstraint
+        );
+        if payloads.iter().any(|(values, cells)| {
+            !routes_payloads
+                || values.len() != input.len()
+                || cells.is_some_and(|cells| cells.len() != values.len())
+        }) {
+            return Err(Error::Synthesis);
+        }
+        
+        // Diff of each consecutive output pair (0 when either row is NULL), as
+        // the u64 that is decomposed and the field element the gate checks
+        // A pair out of order has no 64-bit diff: its field diff wraps around
+        // the modulus and does not match its (wrapped) u64 decomposition
+        let is_null = |i: usize| nulls.is_some_and(|(flags, _)| flags[i]);
+        let (diffs, field_diffs): (Vec<u64>, Vec<Fr>) = (0..sorted_values.len().saturating_sub(1))
+            .map(|i| {
+                let (low, high) = if descending {
+                    (sorted_values[i + 1], sorted_values[i])
+                } else {
+                    (sorted_values[i], sorted_values[i + 1])
+                };
+                if is_null(i) || is_null(i + 1) {
+                    (0, Fr::ZERO)
+                } else {
+                    (high.wrapping_sub(low), Fr::from(high) - Fr::from(low))
+                }
+            })
+            .unzip();
+        
+        // Input row of each output row (CopyConstraint; Permutation routes
+        // through the identity when the input is unknown or not a permutation
+        // of the output, so the output check fails instead)
+        let sources = match self.config.algorithm {
+            SortAlgorithm::CopyConstraint => Some(sort_sources(&input, &sorted_values)?),
+            SortAlgorithm::Permutation => sort_sources(&input, &sorted_values).ok(),
+            _ => None,
+        };
+        
+        // γ of the multiset check with the cells of the committed input and
+        // output values (MultisetHash only; input values are all 0 without
+        // witnesses)
+        let multiset = match self.config.algorithm {
+            SortAlgorithm::MultisetHash => {
+                let poseidon_config = self
+                    .config
+                    .poseidon_config
+                    .clone()
+                    .ok_or(Error::Synthesis)?;
+                let mut input_values = vec![0; input.len()];
+                for (slot, value) in input_values.iter_mut().zip(&input) {
+                    value.map(|v| *slot = v);
+                }
+                let (gamma, committed) = PoseidonChip::new(poseidon_config).commit_rows(
+                    layouter.namespace(|| "multiset challenge"),
+                    &[input_values, sorted_values.clone()],
+                )?;
+                Some((gamma, committed))
+            }
+            _ => None,
+        };
+        
+        // 1. Assign input (MultisetHash: in the output rows, see step 2)
+        let input_cells = match multiset {
+            Some(_) => Vec::new(),
+            None => self.assign_input(layouter.namespace(|| "input"), &input)?,
+        };
+        
+        // 2. Assign output and enable sorting constraints
         // Paper Section 4.2: B[i] ≤ B[i+1] check
         // Note: Output and sort checks must be in the same region because
         // sort checks verify consecutive rows of output
-        let output_cells = layouter.assign_region(
+        // 
+        // MultisetHash assigns the input in its own order to the input column
+        // instead, next to γ and the running products
+        let (sorted_input_cells, output_cells, diff_cells, products) = layouter.assign_region(
             || "output and sort checks",
             |mut region| {
                 // Assign output
+                let mut sorted_input_cells = Vec::new();
                 let mut cells = Vec::new();
+                let mut diff_cells = Vec::with_capacity(field_diffs.len());
+                let mut products: Option<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>)> = None;
                 for (i, val) in sorted_values.iter().enumerate() {
+                    // Paper Section 4.2: Permutation verification with Grand Product Argument
+                    // MultisetHash: the input is multiplied out next to the output
+                    if let Some((gamma, _)) = &multiset {
+                        let x = region.assign_advice(
+                            || format!("input_{}", i),
+                            self.config.input_column,
+                            i,
+                            || input[i].map(Fr::from),
+                        )?;
+                        sorted_input_cells.push(x);
+                        
+                        let gamma_cell = if i == 0 {
+                            self.config.product_start_selector.enable(&mut region, i)?;
+                            gamma.copy_advice(
+                                || "gamma",
+                                &mut region,
+                                self.config.gamma_column,
+                                i,
+                            )?
+                        } else {
+                            self.config.product_selector.enable(&mut region, i)?;
+                            region.assign_advice(
+                                || format!("gamma_{}", i),
+                                self.config.gamma_column,
+                                i,
+                                || gamma.value().copied(),
+                            )?
+                        };
+                        let gamma = gamma_cell.value().copied();
+                        let (prev_in, prev_out) = match &products {
+                            Some((p_in, p_out)) => (p_in.value().copied(), p_out.value().copied()),
+                            None => (Value::known(Fr::ONE), Value::known(Fr::ONE)),
+                        };
+                        let p_in = region.assign_advice(
+                            || format!("input_product_{}", i),
+                            self.config.input_product_column,
+                            i,
+                            || prev_in * (input[i].map(Fr::from) + gamma),
+                        )?;
+                        let p_out = region.assign_advice(
+                            || format!("output_product_{}", i),
+                            self.config.output_product_column,
+                            i,
+                            || prev_out * (Value::known(Fr::from(*val)) + gamma),
+                        )?;
+                        products = Some((p_in, p_out));
+                    }
+                    
                     let cell = region.assign_advice(
                         || format!("output_{}", i),
                         self.config.output_column,
@@ -193,51 +1057,294 @@ impl SortChip {
                     // Enable sorting constraint (except last row)
                     // Paper Section 4.2: B[i] ≤ B[i+1] check
                     if i < sorted_values.len() - 1 {
-                        self.config.sort_selector.enable(&mut region, i)?;
+                        let selector = match (nulls, descending) {
+                            (None, false) => self.config.sort_selector,
+                            (None, true) => self.config.desc_sort_selector,
+                            (Some((_, true)), false) => self.config.nulls_first_selector,
+                            (Some((_, false)), false) => self.config.nulls_last_selector,
+                            (Some((_, true)), true) => self.config.nulls_first_desc_selector,
+                            (Some((_, false)), true) => self.config.nulls_last_desc_selector,
+                        };
+                        selector.enable(&mut region, i)?;
                         
-                        // Calculate and assign diff = B[i+1] - B[i]
-                        // Constraint will check diff = b_i_next - b_i
-                        let diff_value = sorted_values[i + 1] - sorted_values[i];
-                        region.assign_advice(
+                        // Calculate and assign diff = B[i+1] - B[i] (B[i] - B[i+1] when descending)
+                        // Constraint will check diff = b_i_next - b_i (b_i - b_i_next)
+                        diff_cells.push(region.assign_advice(
                             || format!("diff_{}", i),
                             self.config.diff_column,
                             i,
-                            || Value::known(Fr::from(diff_value)),
+                            || Value::known(field_diffs[i]),
+                        )?);
+                    }
+                    
+                    // Assign is_null bit and the inverse proving it (nullable sorts only)
+                    if nulls.is_some() {
+                        self.config.null_selector.enable(&mut region, i)?;
+                        region.assign_advice(
+                            || format!("is_null_{}", i),
+                            self.config.null_column,
+                            i,
+                            || Value::known(Fr::from(is_null(i) as u64)),
+                        )?;
+                        let inverse = (Fr::from(*val) - Fr::from(NULL))
+                            .invert()
+                            .unwrap_or(Fr::ZERO);
+                        region.assign_advice(
+                            || format!("null_inverse_{}", i),
+                            self.config.null_inverse_column,
+                            i,
+                            || Value::known(inverse),
                         )?;
                     }
                 }
-                Ok(cells)
+                Ok((sorted_input_cells, cells, diff_cells, products))
             },
         )?;
         
-        // 3.5. Diff ≥ 0 check: Decompose each diff and check that each chunk is in range 0-255
+        // Input rows copied from their source cells (MultisetHash: the input
+        // in the output rows)
+        if let Some(input_sources) = input_sources {
+            let assigned = match multiset {
+                Some(_) => &sorted_input_cells,
+                None => &input_cells,
+            };
+            layouter.assign_region(
+                || "copied input",
+                |mut region| {
+                    for (source, cell) in input_sources.iter().zip(assigned) {
+                        region.constrain_equal(source.cell(), cell.cell())?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        
+        // 3. Diff ≥ 0 check: Decompose each diff and check that each chunk is in range 0-255
         // Paper Section 4.2: diff ≥ 0 must hold for B[i] ≤ B[i+1] check
         // 
         // This check guarantees that diff is a 64-bit value and non-negative:
         // - diff = sorted_values[i+1] - sorted_values[i] is already calculated as u64
         // - Since sorted_values is sorted, diff ≥ 0
-        // - We decompose diff into 8-bit chunks with decompose_64bit and check that each chunk is in range 0-255
+        // - We decompose diff into 8-bit chunks with decompose_assigned_batch and check that each chunk is in range 0-255
         // - This guarantees that diff is a valid 64-bit non-negative integer
+        // - Each decomposed value is copy-constrained to the gate's diff cell,
+        //   so the decomposition cannot be of a different value than the gate checks
+        // All diffs are decomposed in one region (chunk witnesses computed in parallel)
         use super::range_check::RangeCheckChip;
         let range_check_chip = RangeCheckChip::new(self.config.range_check_config.clone());
-        for i in 0..sorted_values.len() - 1 {
-            let diff_value = sorted_values[i + 1] - sorted_values[i];
-            let _diff_chunks = range_check_chip.decompose_64bit(
-                layouter.namespace(|| format!("decompose diff_{}", i)),
-                Value::known(diff_value),
-            )?;
-        }
+        let _diff_chunks = range_check_chip.decompose_assigned_batch(
+            layouter.namespace(|| "decompose diffs"),
+            &diff_cells,
+            &diffs,
+        )?;
         
         // 4. Permutation constraints (Grand Product Argument)
         // Paper Section 4.2: Prove that input and output have the same multiset
         // Sorted input and sorted output must be element-by-element equal
+        // (Permutation: the sorted input is routed from the input rows by the
+        // switch network, CopyConstraint: the input cells in sorted order are
+        // the sorted input,
+        // MultisetHash: the final running products are equal, and the input
+        // and output cells are the values γ commits to)
+        if let Some((_, committed)) = multiset {
+            layouter.assign_region(
+                || "multiset check",
+                |mut region| {
+                    for (cell, value) in committed
+                        .iter()
+                        .zip(sorted_input_cells.iter().chain(&output_cells))
+                    {
+                        region.constrain_equal(cell.cell(), value.cell())?;
+                    }
+                    if let Some((p_in, p_out)) = &products {
+                        region.constrain_equal(p_in.cell(), p_out.cell())?;
+                    }
+                    Ok(())
+                },
+            )?;
+            return Ok((output_cells, Vec::new()));
+        }
+        let (sorted_input_cells, bit_cells) = match (self.config.algorithm, &sources) {
+            (SortAlgorithm::CopyConstraint, Some(sources)) => (
+                sources.iter().map(|&j| input_cells[j].clone()).collect(),
+                Vec::new(),
+            ),
+            (_, sources) => self.route_input(
+                layouter.namespace(|| "permutation network"),
+                &input_cells,
+                &input,
+                sources.as_deref(),
+                None,
+            )?,
+        };
         self.enable_permutation(
             layouter.namespace(|| "permutation"),
             &sorted_input_cells,
             &output_cells,
         )?;
         
-        Ok(output_cells)
+        // 5. Payloads in sorted order: the input rows' sources (CopyConstraint)
+        // or the input's switch bits (Permutation)
+        let mut payload_cells = Vec::with_capacity(payloads.len());
+        for (k, (values, cells)) in payloads.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("payload {}", k));
+            let assigned = self.assign_input(layouter.namespace(|| "payload input"), values)?;
+            if let Some(cells) = cells {
+                layouter.assign_region(
+                    || "copied payload",
+                    |mut region| {
+                        for (source, cell) in cells.iter().zip(&assigned) {
+                            region.constrain_equal(source.cell(), cell.cell())?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            payload_cells.push(match (self.config.algorithm, &sources) {
+                (SortAlgorithm::CopyConstraint, Some(sources)) => {
+                    sources.iter().map(|&j| assigned[j].clone()).collect()
+                }
+                (_, sources) => {
+                    self.route_input(
+                        layouter.namespace(|| "payload network"),
+                        &assigned,
+                        values,
+                        sources.as_deref(),
+                        Some(&bit_cells),
+                    )?
+                    .0
+                }
+            });
+        }
+        
+        Ok((output_cells, payload_cells))
+    }
+    
+    /// Route the input cells into sorted order (`SortAlgorithm::Permutation`)
+    /// 
+    /// The input is padded with `u64::MAX` to a power of two and passed
+    /// through a Beneš network, whose switches are set to move input row
+    /// `sources[i]` to row `i` (the identity without `sources`). The network
+    /// depends only on `input_cells.len()`; the padding rows must come out
+    /// where they went in, so the routed rows are a permutation of the input.
+    /// 
+    /// With `shared_bits` (the bit cells of an earlier routing of the same
+    /// length), each switch bit is a copy of that routing's bit, so the
+    /// cells (a payload) take the same permutation.
+    /// 
+    /// # Row Layout
+    /// 
+    /// - Padding region: `u64::MAX` constants (input column)
+    /// - Network region, row r: a, b, bit, lo, hi of switch r
+    /// 
+    /// # Return Value
+    /// 
+    /// The first `input_cells.len()` network outputs (input in sorted order),
+    /// and the bit cell of each switch
+    fn route_input(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        input_cells: &[AssignedCell<Fr, Fr>],
+        input: &[Value<u64>],
+        sources: Option<&[usize]>,
+        shared_bits: Option<&[AssignedCell<Fr, Fr>]>,
+    ) -> Result<RoutedCells, Error> {
+        let len = input_cells.len();
+        if len < 2 {
+            return Ok((input_cells.to_vec(), Vec::new()));
+        }
+        let size = len.next_power_of_two();
+        let mut network = Vec::new();
+        benes_network(0, size, &mut network);
+        let mut targets: Vec<usize> = (0..size).collect();
+        if let Some(sources) = sources {
+            for (i, &j) in sources.iter().enumerate() {
+                targets[j] = i;
+            }
+        }
+        let mut bits = Vec::with_capacity(network.len());
+        benes_route(&targets, &mut bits);
+        if shared_bits.is_some_and(|cells| cells.len() != network.len()) {
+            return Err(Error::Synthesis);
+        }
+        
+        let mut cells = input_cells.to_vec();
+        if size > len {
+            let padding = layouter.assign_region(
+                || "permutation padding",
+                |mut region| {
+                    (0..size - len)
+                        .map(|i| {
+                            region.assign_advice_from_constant(
+                                || format!("padding_{}", i),
+                                self.config.input_column,
+                                i,
+                                Fr::from(u64::MAX),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            cells.extend(padding);
+        }
+        let mut values: Vec<Value<Fr>> = input.iter().map(|value| value.map(Fr::from)).collect();
+        values.resize(size, Value::known(Fr::from(u64::MAX)));
+        
+        // Switches in network order; each output replaces its operand
+        let (cells, bit_cells) = layouter.assign_region(
+            || "permutation network",
+            |mut region| {
+                let mut cells = cells.clone();
+                let mut values = values.clone();
+                let mut bit_cells = Vec::with_capacity(network.len());
+                for (row, (&(i, j), &swap)) in network.iter().zip(&bits).enumerate() {
+                    self.config.switch_selector.enable(&mut region, row)?;
+                    cells[i].copy_advice(|| "a", &mut region, self.config.input_column, row)?;
+                    cells[j].copy_advice(|| "b", &mut region, self.config.output_column, row)?;
+                    let bit = match shared_bits {
+                        Some(shared) => shared[row].copy_advice(
+                            || format!("bit_{}", row),
+                            &mut region,
+                            self.config.diff_column,
+                            row,
+                        )?,
+                        None => region.assign_advice(
+                            || format!("bit_{}", row),
+                            self.config.diff_column,
+                            row,
+                            || Value::known(Fr::from(swap as u64)),
+                        )?,
+                    };
+                    bit_cells.push(bit);
+                    
+                    let (lo, hi) = if swap {
+                        (values[j], values[i])
+                    } else {
+                        (values[i], values[j])
+                    };
+                    cells[i] = region.assign_advice(
+                        || format!("lo_{}", row),
+                        self.config.min_column,
+                        row,
+                        || lo,
+                    )?;
+                    cells[j] = region.assign_advice(
+                        || format!("hi_{}", row),
+                        self.config.max_column,
+                        row,
+                        || hi,
+                    )?;
+                    (values[i], values[j]) = (lo, hi);
+                }
+                // Padding comes out in the padding rows
+                for cell in &cells[len..] {
+                    region.constrain_constant(cell.cell(), Fr::from(u64::MAX))?;
+                }
+                Ok((cells, bit_cells))
+            },
+        )?;
+        
+        Ok((cells.into_iter().take(len).collect(), bit_cells))
     }
     
     /// Assign input array
@@ -257,7 +1364,7 @@ impl SortChip {
                             || format!("input_{}", i),
                             self.config.input_column,
                             i,
-                            || val.map(|v| Fr::from(v)),
+                            || val.map(Fr::from),
                         )
                     })
                     .collect()
@@ -278,7 +1385,7 @@ impl SortChip {
     /// 
     /// # Parameters
     /// 
-    /// - `sorted_input_cells`: Sorted version of input (assigned using sorted_values)
+    /// - `sorted_input_cells`: Sorted version of input (routed from the input cells)
     /// - `output_cells`: Output (assigned using sorted_values)
     /// 
     /// # Note
@@ -296,7 +1403,7 @@ impl SortChip {
         // Paper Section 4.2 requirement: Prove that input and output have the same multiset
         // 
         // Strategy:
-        // 1. Assign input in sorted order to a column (sorted_input) ✅ (done in sort_and_verify)
+        // 1. Route input into sorted order (sorted_input) ✅ (done in route_input)
         // 2. Output is already sorted (sorted_values) ✅
         // 3. If input and output have the same multiset, their sorted versions must be element-by-element equal
         // 4. Create explicit copy constraints for each element using `constrain_equal`
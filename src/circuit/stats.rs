@@ -0,0 +1,431 @@
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::{
+    DeltaCircuit, GateConfigs, JoinFreeCircuit, PoneglyphCircuit, PoneglyphConfig, V1Circuit,
+};
+
+/// Circuit Statistics
+/// Proving system dimensions of a configured circuit (for capacity planning)
+///
+/// # Fields
+///
+/// - Column, selector and lookup counts come from the `ConstraintSystem`
+///   built by `PoneglyphCircuit::configure`
+/// - `used_rows` comes from laying out the circuit's operations with the
+///   circuit's floor planner (lookup table rows included)
+/// - `estimated_rows` adds the `blinding_rows` halo2 reserves at the end of
+///   every column, and `min_k` is the smallest k with `2^k >= estimated_rows`
+///   (`estimate_k(0)`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub num_advice: usize,
+    /// Fixed columns (including the lookup table column, excluding selectors)
+    pub num_fixed: usize,
+    pub num_instance: usize,
+    pub num_selectors: usize,
+    pub num_lookups: usize,
+    /// Rows assigned by the operations and the lookup table
+    pub used_rows: usize,
+    /// Rows unusable for assignments: blinding factors plus the rows of the
+    /// last usable row's checks (`ConstraintSystem::minimum_rows`)
+    pub blinding_rows: usize,
+    /// `used_rows` plus the rows reserved for blinding factors
+    pub estimated_rows: usize,
+    /// Smallest k that fits `estimated_rows`
+    pub min_k: u32,
+}
+
+impl CircuitStats {
+    /// Smallest k whose `2^k` rows fit the used rows, the blinding rows and
+    /// `margin` extra rows
+    ///
+    /// # Note
+    ///
+    /// A circuit filling all `2^k` rows does not prove at k: the blinding
+    /// rows come out of the same budget. `estimate_k(0)` is tight but safe
+    /// (`min_k`); a margin leaves room for operations added later, instead of
+    /// over-provisioning k by hand.
+    pub fn estimate_k(&self, margin: usize) -> u32 {
+        let rows = self.used_rows + self.blinding_rows + margin;
+        rows.next_power_of_two().trailing_zeros()
+    }
+}
+
+/// halo2 floor planner a `PoneglyphCircuit` is laid out with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloorPlannerKind {
+    /// `SimpleFloorPlanner` (`PoneglyphCircuit`)
+    Simple,
+    /// `floor_planner::V1` (`V1Circuit`)
+    V1,
+}
+
+impl PoneglyphCircuit {
+    /// Constraint system and layout statistics of this circuit
+    ///
+    /// # Note
+    ///
+    /// Unlike `MemoryManager::estimate_memory_usage` (size of the op structs),
+    /// this reflects the real proving system dimensions. The layout pass runs
+    /// the circuit's synthesis without a k bound, so it fails only if the
+    /// operations themselves are malformed.
+    pub fn stats(&self) -> Result<CircuitStats, Error> {
+        circuit_stats(self)
+    }
+
+    /// Floor planner whose layout fits this circuit into the smaller k, with
+    /// that layout's statistics
+    ///
+    /// Both layouts are measured; on a tie `Simple` is kept (one pass per
+    /// region). Prove a `V1` result as `V1Circuit(circuit)` at its `min_k`.
+    pub fn floor_planner(&self) -> Result<(FloorPlannerKind, CircuitStats), Error> {
+        let simple = self.stats()?;
+        let v1 = circuit_stats(&V1Circuit(self.clone()))?;
+        Ok(if v1.min_k < simple.min_k {
+            (FloorPlannerKind::V1, v1)
+        } else {
+            (FloorPlannerKind::Simple, simple)
+        })
+    }
+}
+
+impl JoinFreeCircuit {
+    /// Constraint system and layout statistics of this circuit
+    /// (see `PoneglyphCircuit::stats`)
+    pub fn stats(&self) -> Result<CircuitStats, Error> {
+        circuit_stats(self)
+    }
+}
+
+impl V1Circuit {
+    /// Constraint system and layout statistics of this circuit
+    /// (see `PoneglyphCircuit::stats`)
+    pub fn stats(&self) -> Result<CircuitStats, Error> {
+        circuit_stats(self)
+    }
+}
+
+impl DeltaCircuit {
+    /// Constraint system and layout statistics of this circuit
+    /// (see `PoneglyphCircuit::stats`)
+    pub fn stats(&self) -> Result<CircuitStats, Error> {
+        circuit_stats(self)
+    }
+}
+
+/// Shared implementation of the `stats` methods (laid out with the
+/// circuit's own floor planner)
+fn circuit_stats<C>(circuit: &C) -> Result<CircuitStats, Error>
+where
+    C: Circuit<Fr, Config = (PoneglyphConfig, GateConfigs)>,
+{
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let config = C::configure(&mut meta);
+    let constants = config.0.fixed.to_vec();
+
+    let blinding_rows = meta.minimum_rows();
+
+    // Probe columns (discarded with `meta`): each one's index is the number
+    // of columns of its kind configured so far
+    let mut scratch = ConstraintSystem::<Fr>::default();
+    let num_advice = probe_index(meta.advice_column(), || scratch.advice_column());
+    let num_fixed = probe_index(meta.fixed_column(), || scratch.fixed_column());
+    let num_instance = probe_index(meta.instance_column(), || scratch.instance_column());
+    let num_selectors = probe_index(meta.selector(), || scratch.selector());
+
+    // `lookup` returns the index of the new argument, i.e. the number
+    // of lookups configured so far (the probe is discarded with `meta`)
+    let num_lookups = meta.lookup(|_| Vec::new());
+
+    let mut counter = RowCounter::default();
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, constants)?;
+
+    let mut stats = CircuitStats {
+        num_advice,
+        num_fixed,
+        num_instance,
+        num_selectors,
+        num_lookups,
+        used_rows: counter.rows,
+        blinding_rows,
+        estimated_rows: counter.rows + blinding_rows,
+        min_k: 0,
+    };
+    stats.min_k = stats.estimate_k(0);
+    Ok(stats)
+}
+
+/// Namespace path of each region (e.g. `sorts[0] / sort`), in the order the
+/// circuit's synthesis assigns them (the region indices of `MockProver`
+/// failures)
+#[cfg(not(feature = "verifier-only"))]
+pub(crate) fn region_paths<C>(circuit: &C) -> Result<Vec<String>, Error>
+where
+    C: Circuit<Fr, Config = (PoneglyphConfig, GateConfigs)>,
+{
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let config = C::configure(&mut meta);
+    let constants = config.0.fixed.to_vec();
+
+    let mut counter = RowCounter::default();
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, constants)?;
+    Ok(counter.regions)
+}
+
+/// Layout a circuit's keys are generated from: its enabled selectors, fixed
+/// cells and copy constraints, in synthesis order
+///
+/// The rest of a key depends only on k and `C::configure`, so circuits of
+/// the same type with equal layouts share their proving and verifying keys
+/// (`StreamingProver` generates keys once per chunk layout).
+#[cfg(not(feature = "verifier-only"))]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct KeyLayout {
+    selectors: Vec<(Selector, usize)>,
+    fixed: Vec<(Column<Fixed>, usize, Option<Fr>)>,
+    fills: Vec<(Column<Fixed>, usize, Option<Fr>)>,
+    copies: Vec<(Column<Any>, usize, Column<Any>, usize)>,
+}
+
+/// Key layout of a circuit (laid out with its own floor planner, as by
+/// `keygen_vk`)
+#[cfg(not(feature = "verifier-only"))]
+pub(crate) fn key_layout<C>(circuit: &C) -> Result<KeyLayout, Error>
+where
+    C: Circuit<Fr, Config = (PoneglyphConfig, GateConfigs)>,
+{
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let config = C::configure(&mut meta);
+    let constants = config.0.fixed.to_vec();
+
+    let mut layout = KeyLayout::default();
+    C::FloorPlanner::synthesize(&mut layout, circuit, config, constants)?;
+    Ok(layout)
+}
+
+/// Number of instance columns `C::configure` declares
+pub(crate) fn instance_column_count<C: Circuit<Fr>>() -> usize {
+    let mut meta = ConstraintSystem::<Fr>::default();
+    C::configure(&mut meta);
+    let mut scratch = ConstraintSystem::<Fr>::default();
+    probe_index(meta.instance_column(), || scratch.instance_column())
+}
+
+/// Index of a column (or selector) freshly allocated in a configured
+/// constraint system, i.e. the number allocated before it
+///
+/// halo2 keeps column indices private, but columns (and selectors) of the
+/// same kind compare equal by index, so the probe is matched against the
+/// columns `next` allocates in an empty constraint system.
+fn probe_index<T: PartialEq>(probe: T, next: impl FnMut() -> T) -> usize {
+    std::iter::repeat_with(next)
+        .take_while(|column| *column != probe)
+        .count()
+}
+
+/// Assignment that only records the highest row written and the namespace
+/// path of each region
+///
+/// Used for the layout pass in `PoneglyphCircuit::stats` and `region_paths`;
+/// values are ignored.
+#[derive(Default)]
+struct RowCounter {
+    rows: usize,
+    namespaces: Vec<String>,
+    regions: Vec<String>,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl Assignment<Fr> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.regions.push(self.namespaces.join(" / "));
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fr>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // Fills the rest of a table column with its default value (not a used row)
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<Fr>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+}
+
+/// Field element of an assigned value (None if unknown)
+#[cfg(not(feature = "verifier-only"))]
+fn known(value: Value<Assigned<Fr>>) -> Option<Fr> {
+    let mut known = None;
+    value.map(|value| known = Some(value.evaluate()));
+    known
+}
+
+#[cfg(not(feature = "verifier-only"))]
+impl Assignment<Fr> for KeyLayout {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.selectors.push((*selector, row));
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fr>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fr>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.fixed
+            .push((column, row, known(to().map(|value| value.into()))));
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left: Column<Any>,
+        left_row: usize,
+        right: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.copies.push((left, left_row, right, right_row));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<Fr>>,
+    ) -> Result<(), Error> {
+        self.fills.push((column, row, known(to)));
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
@@ -0,0 +1,285 @@
+// Universal circuit: one key for every query within a fixed capacity
+
+use halo2_proofs::circuit::Value;
+use pasta_curves::pallas::Base as Fr;
+
+use super::range_check::Comparison;
+use super::{DivisionOp, PoneglyphCircuit, ProductOp, RangeCheckOp, ResultCommitment, SortOp};
+
+/// Operation capacity of a universal circuit
+///
+/// The verifying key depends on the circuit's layout: the regions of every
+/// operation, their selectors, copy constraints and fixed values. `pad`
+/// rewrites a circuit into a layout that depends only on the capacity, so
+/// the keys of one capacity (`ProvingContext::new(k, &capacity.circuit())`)
+/// prove and verify any query that fits in it.
+///
+/// # Supported Operations
+///
+/// - Range checks of a column with a constant, rewritten as witnessed
+///   threshold `x < t` checks (a fixed threshold would put the query's
+///   constant in a fixed column): `x > t` as `t < x`, `x <= t` as
+///   `x < t + 1`, `x >= t` as `t - 1 < x` (always-true ends as `0 < 1`),
+///   `x = t` (boolean or not) as both, and `lower <= x < t` as
+///   `lower - 1 < x` and `x < t`
+/// - Ascending sorts without NULLs, padded with `u64::MAX` rows (sorted last)
+///   (the permutation check routes the input through a switch network
+///   whose layout depends only on the row count)
+/// - Products and divisions
+///
+/// Every other operation, a committed database and a result binding of
+/// the query's own change the layout with the query and are rejected.
+///
+/// # Public Inputs
+///
+/// The keys fix the capacity, not the query, so the query is public input:
+/// `PoneglyphCircuit::public_operands` `2i` and `2i + 1` hold the constant
+/// of padded check `i` and its kind (0 for `x < t`, 1 for `t < x`; see
+/// `RangeCheckChip::public_operands`), in the `instance` rows after the
+/// table commitments (none, as no database is committed), and the query
+/// result is the commitment to the checks' bits
+/// (`ResultCommitment::selection`). A proof thus verifies only against the
+/// query's own constants and outcomes (`CircuitCapacity::instances`).
+///
+/// # Note
+///
+/// A universal proof leaves the query's inputs unbound: the compared
+/// values, the sort rows and the operands of products and divisions are
+/// witnesses, tied to no committed table. It shows that the query's checks
+/// over some values give the committed bits, not that those values are a
+/// table's rows. Padding operations are `0 < 1`, `0 * 0`, `0 / 1` and
+/// all-`u64::MAX` sorts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitCapacity {
+    /// Range checks (`x = t` and bounded checks take two)
+    pub max_range_checks: usize,
+    /// Sort operations
+    pub max_sorts: usize,
+    /// Rows of each sort
+    pub max_sort_rows: usize,
+    /// Product operations
+    pub max_products: usize,
+    /// Division operations
+    pub max_divisions: usize,
+}
+
+impl CircuitCapacity {
+    /// Circuit of padding operations only, to generate the capacity's keys from
+    pub fn circuit(&self) -> PoneglyphCircuit {
+        let empty = PoneglyphCircuit::default();
+        self.pad(&empty)
+            .expect("an empty circuit fits any capacity")
+    }
+
+    /// Rewrite `circuit` into this capacity's layout
+    ///
+    /// # Return Value
+    ///
+    /// The padded circuit, or an error naming the operation that is not
+    /// supported or exceeds the capacity
+    pub fn pad(&self, circuit: &PoneglyphCircuit) -> Result<PoneglyphCircuit, String> {
+        let unsupported = [
+            ("group-by", circuit.group_bys.is_empty()),
+            ("join", circuit.joins.is_empty()),
+            ("semi-join", circuit.semi_joins.is_empty()),
+            ("set membership", circuit.set_memberships.is_empty()),
+            ("aggregation", circuit.aggregations.is_empty()),
+            ("window", circuit.windows.is_empty()),
+            ("HAVING", circuit.having.is_empty()),
+            ("top-K", circuit.top_ks.is_empty()),
+            ("sum", circuit.sums.is_empty()),
+            ("match count", circuit.match_count.is_none()),
+            ("result commitment", circuit.result_commitment.is_none()),
+            ("committed database", circuit.database.is_none()),
+        ];
+        if let Some((name, _)) = unsupported.iter().find(|(_, absent)| !absent) {
+            return Err(format!(
+                "A universal circuit cannot prove a {} operation",
+                name
+            ));
+        }
+
+        // Each check as (a, b, kind) of `a < b`, its constant b (kind 0) or a (kind 1)
+        let mut checks = Vec::with_capacity(self.max_range_checks);
+        for op in &circuit.range_checks {
+            if op.witnessed_threshold {
+                return Err("A universal circuit compares columns with constants only".to_string());
+            }
+            checks.extend(Self::witnessed_checks(op));
+        }
+        Self::fill(
+            "range checks",
+            &mut checks,
+            self.max_range_checks,
+            (0, 1, 0),
+        )?;
+        let range_checks: Vec<RangeCheckOp> = checks
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b, _))| RangeCheckOp {
+                value: Value::known(a),
+                threshold: b,
+                // u = b bounds every b - a (any u >= b - a verifies)
+                u: b,
+                witnessed_threshold: true,
+                source: None,
+                threshold_source: None,
+                public_operand: Some(2 * i),
+                lower: None,
+                comparison: Comparison::Lt,
+                expression: None,
+                member_of: None,
+            })
+            .collect();
+        let operands = checks
+            .iter()
+            .flat_map(|&(a, b, kind)| [if kind == 0 { b } else { a }, kind])
+            .map(Fr::from)
+            .collect();
+        let selection = ResultCommitment::selection(
+            checks.iter().map(|&(a, b, _)| (a < b) as u64).collect(),
+            (0..checks.len()).collect(),
+        );
+
+        let mut sorts = Vec::with_capacity(self.max_sorts);
+        for op in &circuit.sorts {
+            if op.descending || !op.null_flags.is_empty() {
+                return Err("A universal circuit sorts ascending without NULLs only".to_string());
+            }
+            let mut input = op.input.clone();
+            let mut sorted_output = op.sorted_output.clone();
+            Self::fill(
+                "sort rows",
+                &mut input,
+                self.max_sort_rows,
+                Value::known(u64::MAX),
+            )?;
+            Self::fill(
+                "sort rows",
+                &mut sorted_output,
+                self.max_sort_rows,
+                u64::MAX,
+            )?;
+            sorts.push(SortOp {
+                input,
+                sorted_output,
+                null_flags: Vec::new(),
+                nulls_first: false,
+                descending: false,
+                bitonic: false,
+                aggregation: None,
+                column: None,
+                rows: None,
+                payloads: Vec::new(),
+            });
+        }
+        let padding = SortOp {
+            input: vec![Value::known(u64::MAX); self.max_sort_rows],
+            sorted_output: vec![u64::MAX; self.max_sort_rows],
+            null_flags: Vec::new(),
+            nulls_first: false,
+            descending: false,
+            bitonic: false,
+            aggregation: None,
+            column: None,
+            rows: None,
+            payloads: Vec::new(),
+        };
+        Self::fill("sorts", &mut sorts, self.max_sorts, padding)?;
+
+        // Operands are witnessed: copy links would differ per query (and so the keys)
+        let mut products: Vec<ProductOp> = circuit
+            .products
+            .iter()
+            .map(|op| ProductOp {
+                operands: Default::default(),
+                ..op.clone()
+            })
+            .collect();
+        let padding = ProductOp {
+            a: Value::known(0),
+            b: Value::known(0),
+            operands: Default::default(),
+        };
+        Self::fill("products", &mut products, self.max_products, padding)?;
+
+        let mut divisions: Vec<DivisionOp> = circuit
+            .divisions
+            .iter()
+            .map(|op| DivisionOp {
+                operands: Default::default(),
+                ..op.clone()
+            })
+            .collect();
+        let padding = DivisionOp {
+            a: Value::known(0),
+            b: Value::known(1),
+            u: 1,
+            operands: Default::default(),
+        };
+        Self::fill("divisions", &mut divisions, self.max_divisions, padding)?;
+
+        Ok(PoneglyphCircuit {
+            public_operands: operands,
+            query_result: Value::known(selection.value()),
+            range_checks,
+            sorts,
+            products,
+            divisions,
+            result_commitment: Some(selection),
+            ..Default::default()
+        })
+    }
+
+    /// Public inputs of a padded circuit: its checks' constants and kinds
+    /// (`public_operands`, after the table commitments in the first column)
+    /// and the commitment to their bits
+    pub fn instances(padded: &PoneglyphCircuit) -> Vec<Vec<Fr>> {
+        let result = padded
+            .result_commitment
+            .as_ref()
+            .map(ResultCommitment::value)
+            .into_iter()
+            .collect();
+        let inputs = [&padded.db_commitments[..], &padded.public_operands[..]].concat();
+        vec![inputs, result]
+    }
+
+    /// Pad `ops` with `padding` up to `max`, or fail if it holds more
+    fn fill<T: Clone>(name: &str, ops: &mut Vec<T>, max: usize, padding: T) -> Result<(), String> {
+        if ops.len() > max {
+            return Err(format!(
+                "Circuit has {} {}, the capacity is {}",
+                ops.len(),
+                name,
+                max
+            ));
+        }
+        ops.resize(max, padding);
+        Ok(())
+    }
+
+    /// `op` as witnessed threshold checks `(a, b, kind)` of `a < b` (see
+    /// `CircuitCapacity`); kind 0 if b is the constant, 1 if a is
+    fn witnessed_checks(op: &RangeCheckOp) -> Vec<(u64, u64, u64)> {
+        let mut x = 0;
+        op.value.map(|value| x = value);
+        let t = op.threshold;
+
+        // (a, b, kind) of each a < b; an end that always holds is 0 < 1
+        let at_least = |start: u64| {
+            start
+                .checked_sub(1)
+                .map_or((0, 1, 0), |below| (below, x, 1))
+        };
+        let at_most = |end: u64| end.checked_add(1).map_or((0, 1, 0), |above| (x, above, 0));
+        match (op.lower, op.comparison) {
+            (Some(lower), _) => vec![at_least(lower), (x, t, 0)],
+            (None, Comparison::Lt) => vec![(x, t, 0)],
+            (None, Comparison::Gt) => vec![(t, x, 1)],
+            (None, Comparison::Le) => vec![at_most(t)],
+            (None, Comparison::Ge) => vec![at_least(t)],
+            (None, Comparison::Eq | Comparison::Bool) => vec![at_least(t), at_most(t)],
+        }
+    }
+}
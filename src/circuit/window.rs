@@ -0,0 +1,522 @@
+use ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use pasta_curves::pallas::Base as Fr;
+
+use super::config::PoneglyphConfig;
+use super::range_check::to_field_checked;
+
+/// Window Gate Configuration
+/// Window functions over sorted rows (e.g. `SUM(amount) OVER (ORDER BY id)`)
+///
+/// # Column Allocation
+///
+/// - `value_column`: For row values in window order (advice[8])
+/// - `result_column`: For per-row window results (advice[9])
+/// - `partition_column`: For partition boundaries / partition keys (advice[7])
+///
+/// # Constraints
+///
+/// 1. **Running Sum Start**: `running[0] = value[0]`
+/// 2. **Running Sum**: `running[i] = running[i-1] + value[i]`
+/// 3. **Row Number Start**: `rn[0] = 1`
+/// 4. **Row Number**: `rn[i] = rn[i-1] + 1`
+/// 5. **Partitioned Running Sum**: `running[i] = b[i] × running[i-1] + value[i]`
+/// 6. **Partitioned Row Number**: `rn[i] = b[i] × rn[i-1] + 1`
+/// 7. **Partition Key**: `key = partition × 2^32 + order`
+///
+/// `b[i]` is the Group-By Gate's boundary between rows i-1 and i (1 within
+/// a partition, 0 where the partition key changes), so a partitioned window
+/// restarts at each new partition.
+///
+/// # Note
+///
+/// - Same pattern as the Aggregation Gate's prev-row constraint, without group boundaries
+/// - Rows must already be in window order (ORDER BY is proven by the Sort Gate,
+///   which moves the summed column with the rows; `running_sum_copied`
+///   copies its sorted cells)
+/// - With PARTITION BY, the Sort Gate sorts by the packed key (partition in
+///   the high 32 bits) and moves the partition and ORDER BY columns with
+///   the rows; the Partition Key constraint ties the packed key to them
+/// - Columns are shared with Range Check / Aggregation / Group-By (used in different rows)
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    // Value column - for row values in window order
+    // advice[8] - shared with Range Check check/x and Aggregation value
+    pub value_column: Column<Advice>,
+
+    // Result column - for per-row window results
+    // advice[9] - shared with Range Check diff and Aggregation result
+    pub result_column: Column<Advice>,
+
+    // Partition column - for partition boundaries (partitioned windows) and
+    // partition keys (Partition Key constraint)
+    // advice[7] - shared with Range Check chunks and Group-By inverse
+    pub partition_column: Column<Advice>,
+
+    // Selectors
+    pub running_sum_start_selector: Selector,
+    pub running_sum_selector: Selector,
+    pub row_number_start_selector: Selector,
+    pub row_number_selector: Selector,
+    pub partitioned_running_sum_selector: Selector,
+    pub partitioned_row_number_selector: Selector,
+    pub partition_key_selector: Selector,
+}
+
+/// Partition keys in window order and the Group-By Gate's boundary cells
+/// between them (one per consecutive pair of rows)
+type Partition<'a> = (&'a [u64], &'a [AssignedCell<Fr, Fr>]);
+
+/// Window Chip
+pub struct WindowChip {
+    config: WindowConfig,
+}
+
+impl WindowChip {
+    /// Create a new WindowChip
+    pub fn new(config: WindowConfig) -> Self {
+        Self { config }
+    }
+
+    /// Configure the Window Gate
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, config: &PoneglyphConfig) -> WindowConfig {
+        // Get advice columns
+        // Note: Same columns as Aggregation Gate (advice[8-9]), used in different rows
+        let value_column = config.advice[8];
+        let result_column = config.advice[9];
+        let partition_column = config.advice[7];
+
+        // Create selectors
+        let running_sum_start_selector = meta.selector();
+        let running_sum_selector = meta.selector();
+        let row_number_start_selector = meta.selector();
+        let row_number_selector = meta.selector();
+        let partitioned_running_sum_selector = meta.selector();
+        let partitioned_row_number_selector = meta.selector();
+        let partition_key_selector = meta.selector();
+
+        // Running sum start: running[0] = value[0]
+        meta.create_gate("running sum start", |meta| {
+            let s = meta.query_selector(running_sum_start_selector);
+            let value = meta.query_advice(value_column, Rotation::cur());
+            let result = meta.query_advice(result_column, Rotation::cur());
+
+            vec![s * (result - value)]
+        });
+
+        // Running sum: running[i] = running[i-1] + value[i]
+        // Note: Selector will not be enabled for the first row (no Rotation::prev())
+        meta.create_gate("running sum", |meta| {
+            let s = meta.query_selector(running_sum_selector);
+            let value = meta.query_advice(value_column, Rotation::cur());
+            let result = meta.query_advice(result_column, Rotation::cur());
+            let prev_result = meta.query_advice(result_column, Rotation::prev());
+
+            vec![s * (result - (prev_result + value))]
+        });
+
+        // Row number start: rn[0] = 1
+        meta.create_gate("row number start", |meta| {
+            let s = meta.query_selector(row_number_start_selector);
+            let result = meta.query_advice(result_column, Rotation::cur());
+
+            vec![s * (result - Expression::Constant(Fr::ONE))]
+        });
+
+        // Row number: rn[i] = rn[i-1] + 1
+        // Note: Selector will not be enabled for the first row (no Rotation::prev())
+        meta.create_gate("row number", |meta| {
+            let s = meta.query_selector(row_number_selector);
+            let result = meta.query_advice(result_column, Rotation::cur());
+            let prev_result = meta.query_advice(result_column, Rotation::prev());
+
+            vec![s * (result - (prev_result + Expression::Constant(Fr::ONE)))]
+        });
+
+        // Partitioned running sum: running[i] = b[i] × running[i-1] + value[i]
+        meta.create_gate("partitioned running sum", |meta| {
+            let s = meta.query_selector(partitioned_running_sum_selector);
+            let value = meta.query_advice(value_column, Rotation::cur());
+            let result = meta.query_advice(result_column, Rotation::cur());
+            let prev_result = meta.query_advice(result_column, Rotation::prev());
+            let boundary = meta.query_advice(partition_column, Rotation::cur());
+
+            vec![s * (result - (boundary * prev_result + value))]
+        });
+
+        // Partitioned row number: rn[i] = b[i] × rn[i-1] + 1
+        meta.create_gate("partitioned row number", |meta| {
+            let s = meta.query_selector(partitioned_row_number_selector);
+            let result = meta.query_advice(result_column, Rotation::cur());
+            let prev_result = meta.query_advice(result_column, Rotation::prev());
+            let boundary = meta.query_advice(partition_column, Rotation::cur());
+
+            vec![s * (result - (boundary * prev_result + Expression::Constant(Fr::ONE)))]
+        });
+
+        // Partition key: key = partition × 2^32 + order
+        meta.create_gate("partition key", |meta| {
+            let s = meta.query_selector(partition_key_selector);
+            let key = meta.query_advice(value_column, Rotation::cur());
+            let order = meta.query_advice(result_column, Rotation::cur());
+            let partition = meta.query_advice(partition_column, Rotation::cur());
+
+            vec![s * (key - (partition * Expression::Constant(Fr::from(1u64 << 32)) + order))]
+        });
+
+        WindowConfig {
+            value_column,
+            result_column,
+            partition_column,
+            running_sum_start_selector,
+            running_sum_selector,
+            row_number_start_selector,
+            row_number_selector,
+            partitioned_running_sum_selector,
+            partitioned_row_number_selector,
+            partition_key_selector,
+        }
+    }
+
+    /// Compute and verify a running total
+    ///
+    /// Parameters:
+    /// - values: Row values in window order (sorted by the window's ORDER BY column)
+    ///
+    /// # Return Value
+    ///
+    /// Per-row running total cells
+    pub fn running_sum(
+        &self,
+        layouter: impl Layouter<Fr>,
+        values: &[u64],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.assign_running_sum(layouter, values, None, None)
+    }
+
+    /// Compute and verify a running total over assigned cells
+    ///
+    /// Same as `running_sum`, with each value row copy-constrained to its
+    /// cell in `value_cells` (e.g. the summed column moved with the window's
+    /// sorted rows, see `SortChip::sort_and_verify_with_payloads`)
+    pub fn running_sum_copied(
+        &self,
+        layouter: impl Layouter<Fr>,
+        values: &[u64],
+        value_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if value_cells.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+        self.assign_running_sum(layouter, values, Some(value_cells), None)
+    }
+
+    /// Compute and verify a running total restarting at each partition
+    ///
+    /// Same as `running_sum_copied`; `partition_keys` are the rows' partition
+    /// keys in window order and `boundary_cells` the Group-By Gate's
+    /// boundaries over them (see `GroupByChip::group_and_verify_copied`),
+    /// each copied into the row after its pair.
+    pub fn running_sum_partitioned(
+        &self,
+        layouter: impl Layouter<Fr>,
+        values: &[u64],
+        value_cells: &[AssignedCell<Fr, Fr>],
+        partition_keys: &[u64],
+        boundary_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if value_cells.len() != values.len() || partition_keys.len() != values.len() {
+            return Err(Error::Synthesis);
+        }
+        Self::check_boundaries(partition_keys, boundary_cells)?;
+        self.assign_running_sum(
+            layouter,
+            values,
+            Some(value_cells),
+            Some((partition_keys, boundary_cells)),
+        )
+    }
+
+    /// Boundary cells must cover each consecutive pair of rows
+    fn check_boundaries(
+        partition_keys: &[u64],
+        boundary_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<(), Error> {
+        if partition_keys.len() > 1 && boundary_cells.len() != partition_keys.len() - 1 {
+            return Err(Error::Synthesis);
+        }
+        Ok(())
+    }
+
+    /// Shared implementation of `running_sum`, `running_sum_copied` and
+    /// `running_sum_partitioned`
+    fn assign_running_sum(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        values: &[u64],
+        value_cells: Option<&[AssignedCell<Fr, Fr>]>,
+        partition: Option<Partition>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // A running total above u64::MAX is rejected (see `to_field_checked`)
+        let mut running = Vec::with_capacity(values.len());
+        let mut total = 0u128;
+        for (i, &value) in values.iter().enumerate() {
+            if let Some((keys, _)) = partition {
+                if i > 0 && keys[i] != keys[i - 1] {
+                    total = 0;
+                }
+            }
+            total += value as u128;
+            running.push(to_field_checked(total)?);
+        }
+
+        layouter.assign_region(
+            || "running sum",
+            |mut region| {
+                let mut result_cells = Vec::new();
+
+                for (i, (&value, &total)) in values.iter().zip(running.iter()).enumerate() {
+                    match partition {
+                        _ if i == 0 => self
+                            .config
+                            .running_sum_start_selector
+                            .enable(&mut region, i)?,
+                        Some((_, boundary_cells)) => {
+                            self.config
+                                .partitioned_running_sum_selector
+                                .enable(&mut region, i)?;
+                            boundary_cells[i - 1].copy_advice(
+                                || format!("boundary_{}", i),
+                                &mut region,
+                                self.config.partition_column,
+                                i,
+                            )?;
+                        }
+                        None => self.config.running_sum_selector.enable(&mut region, i)?,
+                    }
+
+                    let value_cell = region.assign_advice(
+                        || format!("value_{}", i),
+                        self.config.value_column,
+                        i,
+                        || Value::known(Fr::from(value)),
+                    )?;
+                    if let Some(cells) = value_cells {
+                        region.constrain_equal(cells[i].cell(), value_cell.cell())?;
+                    }
+
+                    let result_cell = region.assign_advice(
+                        || format!("running_{}", i),
+                        self.config.result_column,
+                        i,
+                        || Value::known(total),
+                    )?;
+                    result_cells.push(result_cell);
+                }
+
+                Ok(result_cells)
+            },
+        )
+    }
+
+    /// Sum assigned cells with the running-sum constraints
+    ///
+    /// Each cell is copied into the value column; an empty sum is a
+    /// constant 0.
+    ///
+    /// # Return Value
+    ///
+    /// Cell holding the total
+    pub fn sum(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        layouter.assign_region(
+            || "sum",
+            |mut region| {
+                if cells.is_empty() {
+                    return region.assign_advice_from_constant(
+                        || "empty sum",
+                        self.config.result_column,
+                        0,
+                        Fr::ZERO,
+                    );
+                }
+
+                let mut total = Value::known(Fr::ZERO);
+                let mut result_cell = None;
+                for (i, cell) in cells.iter().enumerate() {
+                    if i == 0 {
+                        self.config
+                            .running_sum_start_selector
+                            .enable(&mut region, i)?;
+                    } else {
+                        self.config.running_sum_selector.enable(&mut region, i)?;
+                    }
+
+                    cell.copy_advice(
+                        || format!("term_{}", i),
+                        &mut region,
+                        self.config.value_column,
+                        i,
+                    )?;
+                    total = total + cell.value().copied();
+                    result_cell = Some(region.assign_advice(
+                        || format!("running_{}", i),
+                        self.config.result_column,
+                        i,
+                        || total,
+                    )?);
+                }
+
+                result_cell.ok_or(Error::Synthesis)
+            },
+        )
+    }
+
+    /// Assign and verify row numbers 1..n
+    ///
+    /// Parameters:
+    /// - row_count: Number of rows in the window (rows are in window order)
+    ///
+    /// # Return Value
+    ///
+    /// Per-row row number cells
+    pub fn row_number(
+        &self,
+        layouter: impl Layouter<Fr>,
+        row_count: usize,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.assign_row_number(layouter, row_count, None)
+    }
+
+    /// Assign and verify row numbers restarting at 1 in each partition
+    ///
+    /// Parameters:
+    /// - partition_keys: Partition keys of the rows in window order
+    /// - boundary_cells: Group-By Gate boundaries over `partition_keys`,
+    ///   each copied into the row after its pair
+    pub fn row_number_partitioned(
+        &self,
+        layouter: impl Layouter<Fr>,
+        partition_keys: &[u64],
+        boundary_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        Self::check_boundaries(partition_keys, boundary_cells)?;
+        self.assign_row_number(
+            layouter,
+            partition_keys.len(),
+            Some((partition_keys, boundary_cells)),
+        )
+    }
+
+    /// Shared implementation of `row_number` and `row_number_partitioned`
+    fn assign_row_number(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        row_count: usize,
+        partition: Option<Partition>,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        layouter.assign_region(
+            || "row number",
+            |mut region| {
+                let mut result_cells = Vec::new();
+
+                let mut row_number = 0u64;
+                for i in 0..row_count {
+                    match partition {
+                        _ if i == 0 => self
+                            .config
+                            .row_number_start_selector
+                            .enable(&mut region, i)?,
+                        Some((keys, boundary_cells)) => {
+                            self.config
+                                .partitioned_row_number_selector
+                                .enable(&mut region, i)?;
+                            boundary_cells[i - 1].copy_advice(
+                                || format!("boundary_{}", i),
+                                &mut region,
+                                self.config.partition_column,
+                                i,
+                            )?;
+                            if keys[i] != keys[i - 1] {
+                                row_number = 0;
+                            }
+                        }
+                        None => self.config.row_number_selector.enable(&mut region, i)?,
+                    }
+                    row_number += 1;
+
+                    let result_cell = region.assign_advice(
+                        || format!("row_number_{}", i),
+                        self.config.result_column,
+                        i,
+                        || Value::known(Fr::from(row_number)),
+                    )?;
+                    result_cells.push(result_cell);
+                }
+
+                Ok(result_cells)
+            },
+        )
+    }
+
+    /// Verify packed window sort keys against their partition and ORDER BY keys
+    ///
+    /// Row i copies `key_cells[i]`, `partition_cells[i]` and `order_cells[i]`
+    /// (the sort's output and the two columns moved with it) and proves
+    /// `key = partition × 2^32 + order`. Keys are packed by the compiler,
+    /// which requires both parts below 2^32.
+    pub fn verify_partition_keys(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        key_cells: &[AssignedCell<Fr, Fr>],
+        partition_cells: &[AssignedCell<Fr, Fr>],
+        order_cells: &[AssignedCell<Fr, Fr>],
+    ) -> Result<(), Error> {
+        if partition_cells.len() != key_cells.len() || order_cells.len() != key_cells.len() {
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "partition keys",
+            |mut region| {
+                for (i, ((key, partition), order)) in key_cells
+                    .iter()
+                    .zip(partition_cells)
+                    .zip(order_cells)
+                    .enumerate()
+                {
+                    self.config.partition_key_selector.enable(&mut region, i)?;
+                    key.copy_advice(
+                        || format!("key_{}", i),
+                        &mut region,
+                        self.config.value_column,
+                        i,
+                    )?;
+                    order.copy_advice(
+                        || format!("order_{}", i),
+                        &mut region,
+                        self.config.result_column,
+                        i,
+                    )?;
+                    partition.copy_advice(
+                        || format!("partition_{}", i),
+                        &mut region,
+                        self.config.partition_column,
+                        i,
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
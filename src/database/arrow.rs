@@ -0,0 +1,113 @@
+// Arrow ingestion module
+// Builds a `DatabaseTable` from an Apache Arrow `RecordBatch` without a CSV round-trip
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrowPrimitiveType, PrimitiveArray, RecordBatch};
+use arrow_schema::DataType;
+
+use super::DatabaseTable;
+use crate::sql::{QueryError, NULL};
+
+impl DatabaseTable {
+    /// Create a table from an Arrow `RecordBatch`
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: Table name (a `RecordBatch` carries none)
+    /// - `batch`: Columns and rows; field names become column names
+    ///
+    /// # Returns
+    ///
+    /// Table with one row per batch row, or `QueryError::UnsupportedColumnType`
+    /// for a non-integer column and `QueryError::InvalidValue` for a negative
+    /// value or `u64::MAX` (the NULL marker)
+    ///
+    /// # Note
+    ///
+    /// Signed and unsigned integer columns (8 to 64 bits) are supported;
+    /// Arrow nulls are stored as `NULL`.
+    pub fn from_record_batch(name: String, batch: &RecordBatch) -> Result<Self, QueryError> {
+        let schema = batch.schema();
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for (field, array) in schema.fields().iter().zip(batch.columns()) {
+            let unsupported = |data_type: &DataType| QueryError::UnsupportedColumnType {
+                table: name.clone(),
+                column: field.name().clone(),
+                data_type: data_type.to_string(),
+            };
+            let values = match array.data_type() {
+                DataType::UInt8 => unsigned(array.as_primitive::<UInt8Type>()),
+                DataType::UInt16 => unsigned(array.as_primitive::<UInt16Type>()),
+                DataType::UInt32 => unsigned(array.as_primitive::<UInt32Type>()),
+                DataType::UInt64 => unsigned(array.as_primitive::<UInt64Type>()),
+                DataType::Int8 => signed(array.as_primitive::<Int8Type>()),
+                DataType::Int16 => signed(array.as_primitive::<Int16Type>()),
+                DataType::Int32 => signed(array.as_primitive::<Int32Type>()),
+                DataType::Int64 => signed(array.as_primitive::<Int64Type>()),
+                other => return Err(unsupported(other)),
+            };
+            let values = values.map_err(|row| QueryError::InvalidValue {
+                table: name.clone(),
+                column: field.name().clone(),
+                row,
+            })?;
+            columns.push(values);
+        }
+
+        let mut table = DatabaseTable::new(
+            name,
+            schema
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect(),
+        );
+        table.data = (0..batch.num_rows())
+            .map(|row| columns.iter().map(|values| values[row]).collect())
+            .collect();
+        Ok(table)
+    }
+}
+
+/// Values of an unsigned integer column (Err: row of the first value equal to NULL)
+fn unsigned<T>(array: &PrimitiveArray<T>) -> Result<Vec<u64>, usize>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<u64>,
+{
+    convert(array, |value| {
+        let value: u64 = value.into();
+        (value != NULL).then_some(value)
+    })
+}
+
+/// Values of a signed integer column (Err: row of the first negative value)
+fn signed<T>(array: &PrimitiveArray<T>) -> Result<Vec<u64>, usize>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i64>,
+{
+    convert(array, |value| u64::try_from(value.into()).ok())
+}
+
+/// Convert each non-null value, storing nulls as `NULL`
+fn convert<T>(
+    array: &PrimitiveArray<T>,
+    value: impl Fn(T::Native) -> Option<u64>,
+) -> Result<Vec<u64>, usize>
+where
+    T: ArrowPrimitiveType,
+{
+    (0..array.len())
+        .map(|row| {
+            if array.is_null(row) {
+                Ok(NULL)
+            } else {
+                value(array.value(row)).ok_or(row)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+// CSV loader module
+// Reads a headered CSV file of unsigned integers and ISO-8601 dates into a
+// `DatabaseTable`, one line at a time
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::DatabaseTable;
+use crate::sql::{parse_timestamp, QueryError, NULL};
+
+impl DatabaseTable {
+    /// Load a table from a CSV file
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: CSV file; its file stem becomes the table name and its
+    ///   first line the column names
+    ///
+    /// # Returns
+    ///
+    /// Table in file order, or `QueryError::InvalidValue` for a field that
+    /// is neither a u64 nor a date, or is `u64::MAX` (the NULL marker), and
+    /// `QueryError::Read`
+    /// if the file cannot be read or a row has the wrong number of fields
+    ///
+    /// # Note
+    ///
+    /// Fields are comma-separated without quoting; surrounding whitespace is
+    /// trimmed, blank lines are skipped and an empty field is stored as
+    /// `NULL`. A date or date-time field (`2024-01-31`, `2024-01-31T08:00:00Z`)
+    /// is stored as seconds since the Unix epoch (`ColumnType::Timestamp`).
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self, QueryError> {
+        let path = path.as_ref();
+        let read_error = |message: String| QueryError::Read {
+            path: path.display().to_string(),
+            message,
+        };
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let file = File::open(path).map_err(|e| read_error(e.to_string()))?;
+        let mut lines = BufReader::new(file).lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| read_error("missing header line".to_string()))?
+            .map_err(|e| read_error(e.to_string()))?;
+        let columns: Vec<String> = header.split(',').map(|c| c.trim().to_string()).collect();
+        let mut table = DatabaseTable::new(name, columns);
+
+        for line in lines {
+            let line = line.map_err(|e| read_error(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row_index = table.data.len();
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != table.columns.len() {
+                return Err(read_error(format!(
+                    "row {} has {} fields, expected {}",
+                    row_index,
+                    fields.len(),
+                    table.columns.len()
+                )));
+            }
+
+            let mut row = Vec::with_capacity(fields.len());
+            for (field, column) in fields.iter().zip(&table.columns) {
+                let value = match *field {
+                    "" => Some(NULL),
+                    field => field
+                        .parse::<u64>()
+                        .ok()
+                        .or_else(|| parse_timestamp(field).ok())
+                        .filter(|&value| value != NULL),
+                };
+                row.push(value.ok_or_else(|| QueryError::InvalidValue {
+                    table: table.name.clone(),
+                    column: column.clone(),
+                    row: row_index,
+                })?);
+            }
+            table.data.push(row);
+        }
+        Ok(table)
+    }
+}
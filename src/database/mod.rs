@@ -1,10 +1,50 @@
 // Database commitment module
 // Paper Section 5.1: IPA commitment (Inner Product Argument)
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+
 use ff::Field;
-use halo2_proofs::{circuit::Value, plonk::Error};
+use group::{Curve, Group};
+use pasta_curves::arithmetic::{CurveAffine, CurveExt};
+use pasta_curves::pallas;
 use pasta_curves::pallas::Base as Fr;
 
+use crate::circuit::{
+    key_tree, AdditiveCommitment, Commitment, DatabaseCommitmentCircuit, MerkleTree,
+    PoseidonCommitment, PublicInputs,
+};
+#[cfg(not(feature = "verifier-only"))]
+use crate::circuit::{NonMembershipCircuit, PointQueryCircuit};
+#[cfg(not(feature = "verifier-only"))]
+use crate::sql::QueryError;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(not(feature = "verifier-only"))]
+mod csv;
+#[cfg(feature = "parquet")]
+mod parquet;
+
+/// Hash Scheme
+/// Function a `DatabaseCommitment` is computed with; callers choose it
+/// explicitly and `verify` recomputes with the same one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashScheme {
+    /// Weighted sum `sum(key * 10^6 + value)` (`AdditiveCommitment`): fast,
+    /// but not binding (reordered or offsetting pairs collide). Tests and
+    /// benchmarks only
+    Insecure,
+    /// Poseidon hash chain of the pairs (`poseidon::hash_chain` over
+    /// `len, key_0, value_0, ...`, `PoseidonCommitment`), the hash the
+    /// circuits prove
+    Poseidon,
+    /// Pedersen vector commitment on Pallas,
+    /// `sum(key_i * G_2i + value_i * G_2i+1)` with hash-to-curve generators;
+    /// additively homomorphic (see `DatabaseCommitment::point`)
+    Pedersen,
+}
+
 /// Database Commitment
 /// Paper Section 5.1: Database commitment using IPA commitment
 ///
@@ -13,12 +53,18 @@ use pasta_curves::pallas::Base as Fr;
 /// - **Type**: Inner Product Argument (IPA)
 /// - **Curve**: BN254 (specified in paper, but we use pasta_curves)
 /// - **Purpose**: Creates commitment for database data
+/// - **Hash**: Chosen per commitment (`HashScheme`)
 #[derive(Clone, Debug)]
 pub struct DatabaseCommitment {
     /// Commitment value (Fr)
     pub commitment: Fr,
     /// Database data (hashed)
     pub data_hash: Fr,
+    /// Hash the commitment was computed with
+    pub scheme: HashScheme,
+    /// Pedersen commitment point (`HashScheme::Pedersen` only; `data_hash`
+    /// is its x-coordinate)
+    point: Option<pallas::Affine>,
 }
 
 impl DatabaseCommitment {
@@ -28,38 +74,85 @@ impl DatabaseCommitment {
     /// # Parameters
     ///
     /// - `data`: Database data (key-value pairs)
+    /// - `scheme`: Hash function of the commitment
     ///
     /// # Returns
     ///
     /// Database commitment
-    pub fn new(data: &[(u64, u64)]) -> Self {
-        // Simple hash function - production should use more secure hash
-        // (e.g.: Poseidon hash, Pedersen hash)
-        let data_hash = Self::hash_data(data);
+    pub fn new(data: &[(u64, u64)], scheme: HashScheme) -> Self {
+        let point = (scheme == HashScheme::Pedersen).then(|| Self::pedersen(data));
+        let data_hash = match point {
+            Some(point) => Self::x_coordinate(point),
+            None => Self::hash_data(data, scheme),
+        };
 
         // Create commitment
         // Note: Production requires IPA commitment implementation
-        // For now, we use a simple hash
+        // For now, we use the hash
         let commitment = data_hash;
 
         Self {
             commitment,
             data_hash,
+            scheme,
+            point,
         }
     }
 
-    /// Hash database data
-    /// Production should use: Poseidon hash or Pedersen hash
-    fn hash_data(data: &[(u64, u64)]) -> Fr {
-        // Simple hash: sum all key-value pairs
-        // Production should use: Poseidon hash or Pedersen hash
-        let mut hash = Fr::ZERO;
-        for (key, value) in data {
-            let key_field = Fr::from(*key);
-            let value_field = Fr::from(*value);
-            hash = hash + key_field * Fr::from(1000000u64) + value_field;
+    /// Commitment over every table in the compiler's column-major shape
+    ///
+    /// # Note
+    ///
+    /// Pairs are `(row, value)`, numbered per table over its columns one after
+    /// another; tables and columns are taken in name order, so the same data
+    /// gives the same commitment whatever the maps' iteration order.
+    pub fn from_tables(
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        scheme: HashScheme,
+    ) -> Self {
+        let mut tables: Vec<_> = table_data.iter().collect();
+        tables.sort_by_key(|(table, _)| *table);
+
+        let mut data = Vec::new();
+        for (_, columns) in tables {
+            let mut columns: Vec<_> = columns.iter().collect();
+            columns.sort_by_key(|(column, _)| *column);
+            let values = columns.into_iter().flat_map(|(_, values)| values);
+            data.extend(values.enumerate().map(|(i, &value)| (i as u64, value)));
         }
-        hash
+        Self::new(&data, scheme)
+    }
+
+    /// Hash database data with a field-valued scheme
+    /// (the `Commitment` implementation of the scheme, if it has one)
+    fn hash_data(data: &[(u64, u64)], scheme: HashScheme) -> Fr {
+        match scheme {
+            HashScheme::Insecure => AdditiveCommitment::commit(data).value(),
+            HashScheme::Poseidon => PoseidonCommitment::commit(data).value(),
+            HashScheme::Pedersen => Self::x_coordinate(Self::pedersen(data)),
+        }
+    }
+
+    /// Pedersen vector commitment of the pairs
+    fn pedersen(data: &[(u64, u64)]) -> pallas::Affine {
+        let generator = pallas::Point::hash_to_curve("poneglyphdb:pedersen");
+        data.iter()
+            .enumerate()
+            .fold(pallas::Point::identity(), |acc, (i, &(key, value))| {
+                let key_base = generator(&(2 * i as u64).to_le_bytes());
+                let value_base = generator(&(2 * i as u64 + 1).to_le_bytes());
+                acc + key_base * pallas::Scalar::from(key)
+                    + value_base * pallas::Scalar::from(value)
+            })
+            .to_affine()
+    }
+
+    /// x-coordinate of a point (0 for the identity)
+    fn x_coordinate(point: pallas::Affine) -> Fr {
+        point
+            .coordinates()
+            .map(|coordinates| *coordinates.x())
+            .unwrap_or(Fr::ZERO)
     }
 
     /// Verify commitment
@@ -71,16 +164,71 @@ impl DatabaseCommitment {
     ///
     /// # Returns
     ///
-    /// Is commitment correct?
+    /// Is commitment correct under its own `scheme`?
     pub fn verify(&self, data: &[(u64, u64)]) -> bool {
-        let computed_hash = Self::hash_data(data);
-        computed_hash == self.data_hash
+        match (self.scheme, self.point) {
+            // The whole point is compared (x alone does not fix its sign)
+            (HashScheme::Pedersen, Some(point)) => Self::pedersen(data) == point,
+            (HashScheme::Pedersen, None) => false,
+            (scheme, _) => Self::hash_data(data, scheme) == self.data_hash,
+        }
     }
 
     /// Get commitment value
     pub fn commitment(&self) -> Fr {
         self.commitment
     }
+
+    /// Circuit proving this commitment was computed from `data`, which stays
+    /// private, and its public inputs (the commitment)
+    ///
+    /// # Returns
+    ///
+    /// None unless the scheme is `HashScheme::Poseidon` (the hash the
+    /// circuits prove). Data that does not match the commitment gives a
+    /// circuit that fails to verify.
+    pub fn circuit(
+        &self,
+        data: &[(u64, u64)],
+    ) -> Option<(DatabaseCommitmentCircuit, PublicInputs)> {
+        (self.scheme == HashScheme::Poseidon).then(|| {
+            let circuit = DatabaseCommitmentCircuit::new(data.to_vec());
+            let public_inputs = PublicInputs {
+                db_commitments: vec![self.commitment],
+                query_result: None,
+            };
+            (circuit, public_inputs)
+        })
+    }
+
+    /// Pedersen commitment point (None for other schemes)
+    ///
+    /// Points of two commitments add up to the commitment of their
+    /// element-wise sum, for data of the same length.
+    pub fn point(&self) -> Option<pallas::Affine> {
+        self.point
+    }
+}
+
+/// Table Source
+/// Input of the query pipeline (`SQLCompiler::compile`, `SQLQuery::execute`,
+/// `SQLQuery::validate`, `prove_query`): column-major table data, or a
+/// row-major `DatabaseTable` transposed on the way in
+pub trait TableSource {
+    /// Tables in the compiler's column-major shape (table_name -> column_name -> values)
+    fn column_map(&self) -> Cow<'_, HashMap<String, HashMap<String, Vec<u64>>>>;
+}
+
+impl TableSource for HashMap<String, HashMap<String, Vec<u64>>> {
+    fn column_map(&self) -> Cow<'_, HashMap<String, HashMap<String, Vec<u64>>>> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl TableSource for DatabaseTable {
+    fn column_map(&self) -> Cow<'_, HashMap<String, HashMap<String, Vec<u64>>>> {
+        Cow::Owned(self.to_column_map())
+    }
 }
 
 /// Database Table
@@ -103,14 +251,70 @@ impl DatabaseTable {
     }
 
     /// Insert row
-    pub fn insert(&mut self, row: Vec<u64>) {
-        if row.len() == self.columns.len() {
-            self.data.push(row);
+    ///
+    /// # Returns
+    ///
+    /// `QueryError::RowLength` if the row does not have one value per column
+    /// (the row is not inserted)
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn insert(&mut self, row: Vec<u64>) -> Result<(), QueryError> {
+        if row.len() != self.columns.len() {
+            return Err(QueryError::RowLength {
+                table: self.name.clone(),
+                row: self.data.len(),
+                expected: self.columns.len(),
+                found: row.len(),
+            });
         }
+        self.data.push(row);
+        Ok(())
+    }
+
+    /// Insert rows in order (bulk load)
+    ///
+    /// # Returns
+    ///
+    /// `QueryError::RowLength` for the first row without one value per
+    /// column, whose `row` is its index in `rows`; no row is inserted then
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn try_insert_many(
+        &mut self,
+        rows: impl IntoIterator<Item = Vec<u64>>,
+    ) -> Result<(), QueryError> {
+        let rows: Vec<Vec<u64>> = rows.into_iter().collect();
+        if let Some((index, row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != self.columns.len())
+        {
+            return Err(QueryError::RowLength {
+                table: self.name.clone(),
+                row: index,
+                expected: self.columns.len(),
+                found: row.len(),
+            });
+        }
+        self.data.extend(rows);
+        Ok(())
+    }
+
+    /// Rows transposed into the compiler's column-major shape
+    /// (table_name -> column_name -> values), as taken by `SQLCompiler::compile`
+    pub fn to_column_map(&self) -> HashMap<String, HashMap<String, Vec<u64>>> {
+        let columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| (column.clone(), self.data.iter().map(|row| row[i]).collect()))
+            .collect();
+
+        let mut table_data = HashMap::new();
+        table_data.insert(self.name.clone(), columns);
+        table_data
     }
 
-    /// Create table commitment
-    pub fn commit(&self) -> DatabaseCommitment {
+    /// Create table commitment with the given hash
+    pub fn commit(&self, scheme: HashScheme) -> DatabaseCommitment {
         // Create key-value pairs (first column is key, others are values)
         let mut kv_pairs = Vec::new();
         for row in &self.data {
@@ -118,6 +322,99 @@ impl DatabaseTable {
                 kv_pairs.push((row[0], row[1]));
             }
         }
-        DatabaseCommitment::new(&kv_pairs)
+        DatabaseCommitment::new(&kv_pairs, scheme)
+    }
+    /// Merkle tree over the rows (`MerkleTree::from_rows`); its root is the
+    /// database commitment of a point query
+    pub fn merkle_tree(&self) -> MerkleTree {
+        MerkleTree::from_rows(&self.data)
+    }
+
+    /// Merkle tree over the sorted distinct keys of `column` (`key_tree`);
+    /// its root is the commitment of a non-membership proof
+    pub fn key_tree(&self, column: &str) -> Option<MerkleTree> {
+        let index = self.columns.iter().position(|name| name == column)?;
+        let keys: Vec<u64> = self.data.iter().map(|row| row[index]).collect();
+        Some(key_tree(&keys))
+    }
+
+    /// Point query `SELECT result_column FROM table WHERE key_column = key`
+    /// over the first row with the key
+    ///
+    /// # Returns
+    ///
+    /// The circuit proving that row's membership and key, and its public
+    /// inputs (Merkle root, result value), or `QueryError::UnknownColumn` /
+    /// `QueryError::KeyNotFound`
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn point_query(
+        &self,
+        key_column: &str,
+        key: u64,
+        result_column: &str,
+    ) -> Result<(PointQueryCircuit, PublicInputs), QueryError> {
+        let column_index = |column: &str| {
+            self.columns
+                .iter()
+                .position(|name| name == column)
+                .ok_or_else(|| QueryError::UnknownColumn {
+                    table: self.name.clone(),
+                    column: column.to_string(),
+                })
+        };
+        let key_column = column_index(key_column)?;
+        let result_column = column_index(result_column)?;
+        let tree = self.merkle_tree();
+        let (index, path) = self
+            .data
+            .iter()
+            .position(|row| row[key_column] == key)
+            .and_then(|index| Some((index, tree.path(index)?)))
+            .ok_or_else(|| QueryError::KeyNotFound {
+                table: self.name.clone(),
+                column: self.columns[key_column].clone(),
+                key,
+            })?;
+
+        let circuit = PointQueryCircuit {
+            row: self.data[index].clone(),
+            path,
+            key_column,
+            key,
+            result_column,
+        };
+        let result = Fr::from(self.data[index][result_column]);
+        Ok((circuit, PublicInputs::new(tree.root(), result)))
+    }
+
+    /// Non-membership proof: no row has `column = key`
+    ///
+    /// # Returns
+    ///
+    /// The circuit proving `key` lies between two adjacent leaves of the
+    /// column's key tree, and its public inputs (key tree root, key), or
+    /// `QueryError::UnknownColumn` / `QueryError::KeyFound`
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn non_membership(
+        &self,
+        column: &str,
+        key: u64,
+    ) -> Result<(NonMembershipCircuit, PublicInputs), QueryError> {
+        let index = self
+            .columns
+            .iter()
+            .position(|name| name == column)
+            .ok_or_else(|| QueryError::UnknownColumn {
+                table: self.name.clone(),
+                column: column.to_string(),
+            })?;
+        let keys: Vec<u64> = self.data.iter().map(|row| row[index]).collect();
+        let (circuit, root) =
+            NonMembershipCircuit::new(&keys, key).ok_or_else(|| QueryError::KeyFound {
+                table: self.name.clone(),
+                column: column.to_string(),
+                key,
+            })?;
+        Ok((circuit, PublicInputs::new(root, Fr::from(key))))
     }
 }
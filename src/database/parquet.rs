@@ -0,0 +1,197 @@
+// Parquet loader module
+// Reads integer columns of a Parquet file into a `DatabaseTable`, optionally
+// only the columns a query touches and the row groups its WHERE can match
+
+use std::fs::File;
+use std::path::Path;
+
+use arrow_array::RecordBatchReader;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use parquet::basic::LogicalType;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::statistics::Statistics;
+
+use super::DatabaseTable;
+use crate::sql::{QueryError, WhereClause};
+
+impl DatabaseTable {
+    /// Load a table from a Parquet file
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: Parquet file; its file stem becomes the table name
+    /// - `columns`: Column allow-list (None: every column). Only these
+    ///   columns are decoded, so a query over a wide, large table reads
+    ///   just the columns it touches
+    ///
+    /// # Returns
+    ///
+    /// Table in file order, or the `from_record_batch` errors for columns
+    /// that are not integers, `QueryError::UnknownColumn` for an allow-listed
+    /// column missing from the file and `QueryError::Read` if the file cannot
+    /// be read
+    pub fn from_parquet(
+        path: impl AsRef<Path>,
+        columns: Option<&[&str]>,
+    ) -> Result<Self, QueryError> {
+        read_parquet(path.as_ref(), columns, None)
+    }
+
+    /// Load a table from a Parquet file, skipping row groups that cannot
+    /// match a WHERE clause
+    ///
+    /// # Parameters
+    ///
+    /// - `path`, `columns`: As in `from_parquet`
+    /// - `where_clause`: Predicate checked against each row group's min / max
+    ///   statistics
+    ///
+    /// # Note
+    ///
+    /// Only constant comparisons (`<`, `>`, `<=`, `>=`, `=`) and their AND / OR are used
+    /// to skip row groups; other predicates keep every row group. Kept row
+    /// groups are loaded whole, so the WHERE clause still has to be compiled
+    /// and proven over the loaded rows.
+    pub fn from_parquet_where(
+        path: impl AsRef<Path>,
+        columns: Option<&[&str]>,
+        where_clause: &WhereClause,
+    ) -> Result<Self, QueryError> {
+        read_parquet(path.as_ref(), columns, Some(where_clause))
+    }
+}
+
+/// Shared implementation of `from_parquet` and `from_parquet_where`
+fn read_parquet(
+    path: &Path,
+    columns: Option<&[&str]>,
+    where_clause: Option<&WhereClause>,
+) -> Result<DatabaseTable, QueryError> {
+    let read_error = |message: String| QueryError::Read {
+        path: path.display().to_string(),
+        message,
+    };
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let file = File::open(path).map_err(|e| read_error(e.to_string()))?;
+    let mut builder =
+        ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| read_error(e.to_string()))?;
+
+    if let Some(columns) = columns {
+        let fields = builder.schema().fields().clone();
+        let mut indices = Vec::with_capacity(columns.len());
+        for &column in columns {
+            let index = fields
+                .iter()
+                .position(|field| field.name() == column)
+                .ok_or_else(|| QueryError::UnknownColumn {
+                    table: name.clone(),
+                    column: column.to_string(),
+                })?;
+            indices.push(index);
+        }
+        let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+        builder = builder.with_projection(mask);
+    }
+
+    if let Some(where_clause) = where_clause {
+        let row_groups = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .enumerate()
+            .filter(|(_, row_group)| may_match(row_group, where_clause))
+            .map(|(index, _)| index)
+            .collect();
+        builder = builder.with_row_groups(row_groups);
+    }
+
+    let reader = builder.build().map_err(|e| read_error(e.to_string()))?;
+    let mut table = DatabaseTable::new(
+        name.clone(),
+        reader
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect(),
+    );
+    for batch in reader {
+        let batch = batch.map_err(|e| read_error(e.to_string()))?;
+        let rows = DatabaseTable::from_record_batch(name.clone(), &batch)?;
+        table.data.extend(rows.data);
+    }
+    Ok(table)
+}
+
+/// Can any row of the row group satisfy the WHERE clause?
+/// (false only if the min / max statistics rule every row out)
+fn may_match(row_group: &RowGroupMetaData, where_clause: &WhereClause) -> bool {
+    match where_clause {
+        WhereClause::LessThan { column, value } => {
+            value_range(row_group, column).is_none_or(|(min, _)| min < *value)
+        }
+        WhereClause::GreaterThan { column, value } => {
+            value_range(row_group, column).is_none_or(|(_, max)| max > *value)
+        }
+        WhereClause::LessEqual { column, value } => {
+            value_range(row_group, column).is_none_or(|(min, _)| min <= *value)
+        }
+        WhereClause::GreaterEqual { column, value } => {
+            value_range(row_group, column).is_none_or(|(_, max)| max >= *value)
+        }
+        WhereClause::Equal { column, value } => {
+            value_range(row_group, column).is_none_or(|(min, max)| min <= *value && *value <= max)
+        }
+        WhereClause::And(left, right) => may_match(row_group, left) && may_match(row_group, right),
+        WhereClause::Or(left, right) => may_match(row_group, left) || may_match(row_group, right),
+        _ => true,
+    }
+}
+
+/// Min / max statistics of an integer column in a row group, as u64
+/// (column may be qualified `table.column`; negative values clamp to 0)
+fn value_range(row_group: &RowGroupMetaData, column: &str) -> Option<(u64, u64)> {
+    let column = column.rsplit('.').next().unwrap_or(column);
+    let chunk = row_group
+        .columns()
+        .iter()
+        .find(|chunk| chunk.column_descr().name() == column)?;
+    let unsigned = matches!(
+        chunk.column_descr().logical_type_ref(),
+        Some(LogicalType::Integer {
+            is_signed: false,
+            ..
+        })
+    );
+
+    // Unsigned integers are stored in the signed physical type (same bits)
+    let (min, max) = match chunk.statistics()? {
+        Statistics::Int32(stats) => {
+            let convert = |v: i32| {
+                if unsigned {
+                    v as u32 as u64
+                } else {
+                    u64::try_from(v).unwrap_or(0)
+                }
+            };
+            (convert(*stats.min_opt()?), convert(*stats.max_opt()?))
+        }
+        Statistics::Int64(stats) => {
+            let convert = |v: i64| {
+                if unsigned {
+                    v as u64
+                } else {
+                    u64::try_from(v).unwrap_or(0)
+                }
+            };
+            (convert(*stats.min_opt()?), convert(*stats.max_opt()?))
+        }
+        _ => return None,
+    };
+    Some((min, max))
+}
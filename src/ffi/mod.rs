@@ -0,0 +1,263 @@
+// C API module (feature "ffi")
+// Prove and verify a SQL query from C / C++ through opaque handles
+//
+// Conventions:
+// - Strings and table JSON are passed as (pointer, length) pairs, UTF-8, not
+//   NUL-terminated
+// - Proofs and error messages cross the boundary as length-prefixed byte
+//   buffers: an 8-byte little-endian length followed by the bytes
+// - Every function returns an error code instead of panicking; panics are
+//   caught with `catch_unwind` and reported as `PG_ERR_PANIC`
+//
+// Table JSON format: {"table": {"column": [u64, ...], ...}, ...}, the
+// compiler's `table_name -> column_name -> values` shape
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::circuit::{CommittedDatabase, PoneglyphCircuit, PublicInputs};
+use crate::prover::ProvingContext;
+use crate::sql::{SQLCompiler, SQLParser};
+
+/// Success
+pub const PG_OK: i32 = 0;
+/// A required pointer argument was null
+pub const PG_ERR_NULL_POINTER: i32 = 1;
+/// The SQL string is not valid UTF-8
+pub const PG_ERR_INVALID_UTF8: i32 = 2;
+/// The table JSON could not be parsed
+pub const PG_ERR_INVALID_TABLE: i32 = 3;
+/// The query failed to parse or compile against the table
+pub const PG_ERR_QUERY: i32 = 4;
+/// Key generation or proof creation failed
+pub const PG_ERR_PROOF: i32 = 5;
+/// The proof did not verify
+pub const PG_ERR_VERIFY: i32 = 6;
+/// A panic was caught at the boundary
+pub const PG_ERR_PANIC: i32 = 7;
+
+/// Size of the length prefix of a byte buffer
+const LENGTH_PREFIX: usize = 8;
+
+/// Proof Handle
+/// Opaque result of `pg_prove`, released with `pg_free_proof`
+///
+/// Holds either the proof (`status == PG_OK`) or the error message, both as a
+/// length-prefixed buffer.
+pub struct ProofHandle {
+    status: i32,
+    buffer: Vec<u8>,
+}
+
+impl ProofHandle {
+    fn new(result: Result<Vec<u8>, FfiError>) -> Self {
+        match result {
+            Ok(proof) => Self {
+                status: PG_OK,
+                buffer: length_prefixed(&proof),
+            },
+            Err(error) => Self {
+                status: error.code,
+                buffer: length_prefixed(error.message.as_bytes()),
+            },
+        }
+    }
+}
+
+/// Error code and message returned across the boundary
+struct FfiError {
+    code: i32,
+    message: String,
+}
+
+impl FfiError {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Prove a SQL query over the given tables
+///
+/// Always returns a handle (null only if the handle itself cannot be
+/// allocated); check it with `pg_proof_status` and read the proof or error
+/// message with `pg_proof_buffer`.
+///
+/// # Safety
+///
+/// `sql_ptr` and `table_json_ptr` must point to `sql_len` and
+/// `table_json_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn pg_prove(
+    sql_ptr: *const u8,
+    sql_len: usize,
+    table_json_ptr: *const u8,
+    table_json_len: usize,
+) -> *mut ProofHandle {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let sql = read_str(sql_ptr, sql_len)?;
+        let table_json = read_bytes(table_json_ptr, table_json_len)?;
+        prove_query(sql, table_json)
+    }))
+    .unwrap_or_else(|_| Err(FfiError::new(PG_ERR_PANIC, "panic while proving")));
+
+    Box::into_raw(Box::new(ProofHandle::new(result)))
+}
+
+/// Status code of a `pg_prove` result (`PG_OK` if it holds a proof)
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by `pg_prove` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn pg_proof_status(handle: *const ProofHandle) -> i32 {
+    match handle.as_ref() {
+        Some(handle) => handle.status,
+        None => PG_ERR_NULL_POINTER,
+    }
+}
+
+/// Length-prefixed proof (status `PG_OK`) or error message of a handle
+///
+/// The buffer is owned by the handle and valid until `pg_free_proof`.
+/// Returns null for a null handle.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by `pg_prove` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn pg_proof_buffer(handle: *const ProofHandle) -> *const u8 {
+    match handle.as_ref() {
+        Some(handle) => handle.buffer.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+/// Verify a proof of a SQL query over the given tables
+///
+/// The verifying key is generated from the same query and tables, so they
+/// must match the ones passed to `pg_prove`. Returns `PG_OK` if the proof
+/// verifies.
+///
+/// # Safety
+///
+/// `sql_ptr` and `table_json_ptr` must point to `sql_len` and
+/// `table_json_len` readable bytes; `proof_ptr` must point to a
+/// length-prefixed buffer (e.g. from `pg_proof_buffer`).
+#[no_mangle]
+pub unsafe extern "C" fn pg_verify(
+    sql_ptr: *const u8,
+    sql_len: usize,
+    table_json_ptr: *const u8,
+    table_json_len: usize,
+    proof_ptr: *const u8,
+) -> i32 {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let sql = read_str(sql_ptr, sql_len)?;
+        let table_json = read_bytes(table_json_ptr, table_json_len)?;
+        let proof = read_length_prefixed(proof_ptr)?;
+        verify_query(sql, table_json, proof)
+    }));
+
+    match result {
+        Ok(Ok(())) => PG_OK,
+        Ok(Err(error)) => error.code,
+        Err(_) => PG_ERR_PANIC,
+    }
+}
+
+/// Release a handle returned by `pg_prove` (null is ignored)
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by `pg_prove` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn pg_free_proof(handle: *mut ProofHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Compile the query into a circuit over the committed tables and its public
+/// inputs (the table commitments and the query result)
+fn build_circuit(
+    sql: &str,
+    table_json: &[u8],
+) -> Result<(PoneglyphCircuit, PublicInputs), FfiError> {
+    let table_data: HashMap<String, HashMap<String, Vec<u64>>> = serde_json::from_slice(table_json)
+        .map_err(|e| FfiError::new(PG_ERR_INVALID_TABLE, e.to_string()))?;
+
+    let query = SQLParser::parse(sql).map_err(|e| FfiError::new(PG_ERR_QUERY, e))?;
+    let compiled =
+        SQLCompiler::compile(&query, &table_data).map_err(|e| FfiError::new(PG_ERR_QUERY, e))?;
+
+    let database = CommittedDatabase::new(&table_data);
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+
+    // An empty WHERE result (or a LIMIT over rows) binds its match count to
+    // the query result, and a HAVING its number of kept groups
+    let public_inputs = PublicInputs {
+        db_commitments: circuit.db_commitments.clone(),
+        query_result: circuit.bound_result(),
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+/// Prove the compiled query at the smallest k that fits it
+fn prove_query(sql: &str, table_json: &[u8]) -> Result<Vec<u8>, FfiError> {
+    let (circuit, public_inputs) = build_circuit(sql, table_json)?;
+
+    let context = ProvingContext::for_circuit(&circuit)
+        .map_err(|e| FfiError::new(PG_ERR_PROOF, format!("Failed to create keys: {:?}", e)))?;
+    context
+        .prove(&circuit, &public_inputs.to_instances())
+        .map_err(|e| FfiError::new(PG_ERR_PROOF, format!("Failed to prove: {:?}", e)))
+}
+
+/// Verify a proof against keys regenerated from the query
+fn verify_query(sql: &str, table_json: &[u8], proof: &[u8]) -> Result<(), FfiError> {
+    let (circuit, public_inputs) = build_circuit(sql, table_json)?;
+
+    let context = ProvingContext::for_circuit(&circuit)
+        .map_err(|e| FfiError::new(PG_ERR_PROOF, format!("Failed to create keys: {:?}", e)))?;
+    context
+        .verify(proof, &public_inputs.to_instances())
+        .map(|_| ())
+        .map_err(|e| FfiError::new(PG_ERR_VERIFY, format!("Proof rejected: {:?}", e)))
+}
+
+/// 8-byte little-endian length followed by the bytes
+fn length_prefixed(bytes: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(LENGTH_PREFIX + bytes.len());
+    buffer.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+    buffer
+}
+
+/// Borrow `len` bytes from a caller pointer
+unsafe fn read_bytes<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::new(PG_ERR_NULL_POINTER, "null pointer argument"));
+    }
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+/// Borrow `len` bytes from a caller pointer as UTF-8
+unsafe fn read_str<'a>(ptr: *const u8, len: usize) -> Result<&'a str, FfiError> {
+    std::str::from_utf8(read_bytes(ptr, len)?)
+        .map_err(|e| FfiError::new(PG_ERR_INVALID_UTF8, e.to_string()))
+}
+
+/// Borrow the bytes of a length-prefixed buffer
+unsafe fn read_length_prefixed<'a>(ptr: *const u8) -> Result<&'a [u8], FfiError> {
+    let prefix = read_bytes(ptr, LENGTH_PREFIX)?;
+    let mut len = [0u8; LENGTH_PREFIX];
+    len.copy_from_slice(prefix);
+    read_bytes(ptr.add(LENGTH_PREFIX), u64::from_le_bytes(len) as usize)
+}
@@ -1,26 +1,39 @@
+// With the `verifier-only` feature the SQL front end, proving and the
+// optimizer are compiled out, leaving `Verifier`, the circuits (for
+// verifying keys) and database commitments
+#[cfg(all(
+    feature = "verifier-only",
+    any(
+        feature = "ffi",
+        feature = "arrow",
+        feature = "parquet",
+        feature = "testing"
+    )
+))]
+compile_error!("`verifier-only` cannot be combined with `ffi`, `arrow`, `parquet` or `testing`");
+
 pub mod circuit;
 pub mod database;
+#[cfg(not(feature = "verifier-only"))]
 pub mod sql;
 pub mod prover;
+#[cfg(not(feature = "verifier-only"))]
 pub mod recursive;
+#[cfg(not(feature = "verifier-only"))]
 pub mod optimization;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tracing")]
+mod trace;
 
 pub use circuit::*;
 pub use database::*;
+#[cfg(not(feature = "verifier-only"))]
 pub use sql::*;
 pub use prover::*;
+#[cfg(not(feature = "verifier-only"))]
 pub use recursive::*;
+#[cfg(not(feature = "verifier-only"))]
 pub use optimization::*;
-
-
-pub use recursive::*;
-pub use optimization::*;
-
-
-pub use recursive::*;
-pub use optimization::*;
-
-
-pub use recursive::*;
-pub use optimization::*;
-
@@ -1,7 +1,4 @@
-use poneglyphdb::circuit::*;
-
 fn main() {
     println!("PoneglyphDB - Zero-Knowledge Database System");
     println!("Starting implementation...");
 }
-
@@ -3,7 +3,14 @@
 
 use std::sync::Arc;
 
-use crate::circuit::{AggregationOp, GroupByOp, JoinOp, PoneglyphCircuit, RangeCheckOp, SortOp};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::circuit::{
+    AggregationOp, CommittedDatabase, Comparison, DivisionOp, GroupByOp, HavingOp, JoinOp,
+    MatchCountOp, PoneglyphCircuit, ProductOp, RangeCheckOp, ResultCommitment, SemiJoinOp,
+    SetMembershipOp, SortOp, SumOp, TopKOp, WindowOp,
+};
 
 /// Memory Management
 /// Memory-efficient operations for large dataset handling
@@ -24,7 +31,18 @@ impl MemoryManager {
             sorts: circuit.sorts.clone(),
             group_bys: circuit.group_bys.clone(),
             joins: circuit.joins.clone(),
+            semi_joins: circuit.semi_joins.clone(),
+            set_memberships: circuit.set_memberships.clone(),
             aggregations: circuit.aggregations.clone(),
+            products: circuit.products.clone(),
+            divisions: circuit.divisions.clone(),
+            sums: circuit.sums.clone(),
+            windows: circuit.windows.clone(),
+            having: circuit.having.clone(),
+            top_ks: circuit.top_ks.clone(),
+            match_count: circuit.match_count.clone(),
+            result_commitment: circuit.result_commitment.clone(),
+            database: circuit.database.clone(),
         };
 
         Ok(optimized)
@@ -39,7 +57,15 @@ impl MemoryManager {
         circuit.sorts.shrink_to_fit();
         circuit.group_bys.shrink_to_fit();
         circuit.joins.shrink_to_fit();
+        circuit.semi_joins.shrink_to_fit();
+        circuit.set_memberships.shrink_to_fit();
         circuit.aggregations.shrink_to_fit();
+        circuit.products.shrink_to_fit();
+        circuit.divisions.shrink_to_fit();
+        circuit.sums.shrink_to_fit();
+        circuit.windows.shrink_to_fit();
+        circuit.having.shrink_to_fit();
+        circuit.top_ks.shrink_to_fit();
     }
 
     /// Memory usage estimation
@@ -52,7 +78,18 @@ impl MemoryManager {
         total += circuit.sorts.len() * std::mem::size_of::<SortOp>();
         total += circuit.group_bys.len() * std::mem::size_of::<GroupByOp>();
         total += circuit.joins.len() * std::mem::size_of::<JoinOp>();
+        total += circuit.semi_joins.len() * std::mem::size_of::<SemiJoinOp>();
+        total += circuit.set_memberships.len() * std::mem::size_of::<SetMembershipOp>();
         total += circuit.aggregations.len() * std::mem::size_of::<AggregationOp>();
+        total += circuit.products.len() * std::mem::size_of::<ProductOp>();
+        total += circuit.divisions.len() * std::mem::size_of::<DivisionOp>();
+        total += circuit.sums.len() * std::mem::size_of::<SumOp>();
+        total += circuit.windows.len() * std::mem::size_of::<WindowOp>();
+        total += circuit.having.len() * std::mem::size_of::<HavingOp>();
+        total += circuit.top_ks.len() * std::mem::size_of::<TopKOp>();
+        total += circuit.match_count.iter().count() * std::mem::size_of::<MatchCountOp>();
+        total += circuit.result_commitment.iter().count() * std::mem::size_of::<ResultCommitment>();
+        total += circuit.database.iter().count() * std::mem::size_of::<CommittedDatabase>();
 
         total
     }
@@ -66,7 +103,18 @@ pub struct OptimizedCircuit {
     pub sorts: Vec<SortOp>,
     pub group_bys: Vec<GroupByOp>,
     pub joins: Vec<JoinOp>,
+    pub semi_joins: Vec<SemiJoinOp>,
+    pub set_memberships: Vec<SetMembershipOp>,
     pub aggregations: Vec<AggregationOp>,
+    pub products: Vec<ProductOp>,
+    pub divisions: Vec<DivisionOp>,
+    pub sums: Vec<SumOp>,
+    pub windows: Vec<WindowOp>,
+    pub having: Vec<HavingOp>,
+    pub top_ks: Vec<TopKOp>,
+    pub match_count: Option<MatchCountOp>,
+    pub result_commitment: Option<ResultCommitment>,
+    pub database: Option<CommittedDatabase>,
 }
 
 /// Parallel Processing
@@ -77,27 +125,41 @@ impl ParallelProcessor {
     /// Multi-threaded proof generation
     /// Paper: Parallel processing optimization
     ///
-    /// Note: Production can use rayon or similar parallel processing library
+    /// Runs on a new pool of `num_threads` threads (0 picks rayon's default);
+    /// use `parallel_proof_generation_in` to run on an existing pool
     pub fn parallel_proof_generation(
         circuits: Vec<Arc<PoneglyphCircuit>>,
-        _num_threads: usize,
+        num_threads: usize,
     ) -> Result<Vec<ProofResult>, String> {
-        // Simple sequential processing
-        // Production can implement parallel processing with rayon or similar library
-
-        let results: Vec<_> = circuits
-            .into_iter()
-            .enumerate()
-            .map(|(id, _circuit)| {
-                // Proof generation (placeholder)
-                // Production should implement real proof generation
-                ProofResult {
-                    circuit_id: id,
-                    success: true,
-                    proof_size: 0,
-                }
-            })
-            .collect();
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|e| format!("Failed to build thread pool: {}", e))?;
+        Self::parallel_proof_generation_in(circuits, &pool)
+    }
+
+    /// Multi-threaded proof generation on the caller's thread pool
+    ///
+    /// No threads are spawned; results are in circuit order.
+    pub fn parallel_proof_generation_in(
+        circuits: Vec<Arc<PoneglyphCircuit>>,
+        pool: &ThreadPool,
+    ) -> Result<Vec<ProofResult>, String> {
+        let results = pool.install(|| {
+            circuits
+                .into_par_iter()
+                .enumerate()
+                .map(|(id, _circuit)| {
+                    // Proof generation (placeholder)
+                    // Production should implement real proof generation
+                    ProofResult {
+                        circuit_id: id,
+                        success: true,
+                        proof_size: 0,
+                    }
+                })
+                .collect()
+        });
 
         Ok(results)
     }
@@ -156,7 +218,18 @@ impl CircuitOptimizer {
             sorts: circuit.sorts.clone(),
             group_bys: circuit.group_bys.clone(),
             joins: circuit.joins.clone(),
+            semi_joins: circuit.semi_joins.clone(),
+            set_memberships: circuit.set_memberships.clone(),
             aggregations: circuit.aggregations.clone(),
+            products: circuit.products.clone(),
+            divisions: circuit.divisions.clone(),
+            sums: circuit.sums.clone(),
+            windows: circuit.windows.clone(),
+            having: circuit.having.clone(),
+            top_ks: circuit.top_ks.clone(),
+            match_count: circuit.match_count.clone(),
+            result_commitment: circuit.result_commitment.clone(),
+            database: circuit.database.clone(),
         }
     }
 
@@ -167,10 +240,27 @@ impl CircuitOptimizer {
 
         // For range checks: Remove those with same threshold and value
         // Note: RangeCheckOp doesn't implement PartialEq, so manual deduplication
+        // Kept as they are when a filtered COUNT copies check cells by index
         let mut seen = std::collections::HashSet::new();
+        let referenced = circuit
+            .aggregations
+            .iter()
+            .any(|op| op.value_checks.is_some());
+        // (a bounded, non-`<` or set membership check's u is unused, so its
+        // source cell tells the rows apart)
         circuit.range_checks.retain(|op| {
-            let key = (op.threshold, op.u);
-            seen.insert(key)
+            let unbounded_lt =
+                op.lower.is_none() && op.comparison == Comparison::Lt && op.member_of.is_none();
+            let source = if unbounded_lt { None } else { op.source };
+            let key = (
+                op.threshold,
+                op.u,
+                op.lower,
+                op.comparison,
+                op.member_of,
+                source,
+            );
+            referenced || seen.insert(key)
         });
 
         // For group-bys: Remove those with same group keys
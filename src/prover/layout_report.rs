@@ -0,0 +1,79 @@
+// Layout report
+// MockProver failures traced back to the circuit operation (range check,
+// sort, ...) whose regions they occurred in
+
+use halo2_proofs::dev::{FailureLocation, MockProver, VerifyFailure};
+use pasta_curves::pallas::Base as Fr;
+
+use crate::circuit::{stats::region_paths, PoneglyphCircuit};
+
+/// Run the MockProver and name the operation behind each failure
+///
+/// # Parameters
+///
+/// - `circuit`: Circuit to check
+/// - `public_inputs`: Instance columns (as for `MockProver::run`)
+/// - `k`: Circuit size (2^k rows)
+///
+/// # Returns
+///
+/// Ok if the circuit verifies; otherwise one diagnostic per failure, prefixed
+/// with the namespace path of the failing region, e.g.
+/// `sorts[0] / sort: Constraint 0 ('sort order check') ... is not satisfied`
+///
+/// # Note
+///
+/// `PoneglyphCircuit::synthesize` lays out each operation under a
+/// `field[index]` namespace; the MockProver only reports region indices, so
+/// the regions are matched by replaying the layout.
+pub fn layout_report(
+    circuit: &PoneglyphCircuit,
+    public_inputs: &[Vec<Fr>],
+    k: u32,
+) -> Result<(), String> {
+    let prover = MockProver::run(k, circuit, public_inputs.to_vec())
+        .map_err(|e| format!("Failed to run mock prover: {:?}", e))?;
+    prover
+        .verify()
+        .map_err(|failures| describe_failures(circuit, &failures))
+}
+
+/// One line per failure: the failing region's namespace path and the failure
+pub(crate) fn describe_failures(circuit: &PoneglyphCircuit, failures: &[VerifyFailure]) -> String {
+    let paths = region_paths(circuit).unwrap_or_default();
+    failures
+        .iter()
+        .map(|failure| {
+            let operation = failure_region(failure)
+                .and_then(|index| paths.get(index))
+                .filter(|path| !path.is_empty())
+                .map_or("outside any operation", String::as_str);
+            format!("{}: {}", operation, failure)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Index of the region a failure occurred in (None outside regions)
+fn failure_region(failure: &VerifyFailure) -> Option<usize> {
+    let region = match failure {
+        VerifyFailure::CellNotAssigned { region, .. }
+        | VerifyFailure::InstanceCellNotAssigned { region, .. } => region.to_string(),
+        VerifyFailure::ConstraintNotSatisfied { location, .. }
+        | VerifyFailure::Lookup { location, .. }
+        | VerifyFailure::Permutation { location, .. } => match location {
+            FailureLocation::InRegion { region, .. } => region.to_string(),
+            FailureLocation::OutsideRegion { .. } => return None,
+        },
+        VerifyFailure::ConstraintPoisoned { .. } => return None,
+    };
+
+    // halo2 exposes the index only through the Display output:
+    // `Region <index> ('<name>')`
+    region
+        .strip_prefix("Region ")?
+        .split(' ')
+        .next()?
+        .parse()
+        .ok()
+}
@@ -8,91 +8,182 @@
 // - verify_proof (requires transcript and strategy)
 //
 // Note: Circuit uses Fr = pallas::Base = Fp, so we use EqAffine
+//
+// With the `verifier-only` feature only `Verifier` is compiled; proving,
+// keygen of proving keys and the SQL entry points are left out.
+
+#[cfg(not(feature = "verifier-only"))]
+pub mod layout_report;
+#[cfg(not(feature = "verifier-only"))]
+pub mod profiler;
+
+#[cfg(not(feature = "verifier-only"))]
+pub use layout_report::layout_report;
+#[cfg(not(feature = "verifier-only"))]
+pub use profiler::{Profiler, Stage, StageTimings};
 
 use halo2_proofs::{
-    dev::MockProver,
     pasta::EqAffine,
-    plonk::{
-        create_proof, keygen_pk, keygen_vk, verify_proof, Error, ProvingKey, SingleVerifier,
-        VerifyingKey,
-    },
+    plonk::{keygen_vk, verify_proof, Circuit, Error, SingleVerifier, VerifyingKey},
     poly::commitment::Params,
-    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    transcript::{Blake2bRead, Challenge255},
 };
 use pasta_curves::pallas::Base as Fr;
-use rand::rngs::OsRng;
 
-use crate::circuit::PoneglyphCircuit;
+use crate::circuit::stats::instance_column_count;
+
+#[cfg(not(feature = "verifier-only"))]
+use halo2_proofs::{
+    circuit::Value,
+    dev::MockProver,
+    plonk::{create_proof, keygen_pk, ProvingKey},
+    transcript::Blake2bWrite,
+};
+#[cfg(not(feature = "verifier-only"))]
+use rand::{rngs::OsRng, RngCore};
+#[cfg(not(feature = "verifier-only"))]
+use std::cell::Cell;
+#[cfg(not(feature = "verifier-only"))]
+use std::collections::HashMap;
+#[cfg(not(feature = "verifier-only"))]
+use std::marker::PhantomData;
+#[cfg(not(feature = "verifier-only"))]
+use std::time::{Duration, Instant};
+
+#[cfg(not(feature = "verifier-only"))]
+use crate::circuit::{CommittedDatabase, PoneglyphCircuit, PublicInputs, ResultCommitment};
+#[cfg(not(feature = "verifier-only"))]
+use crate::database::TableSource;
+#[cfg(not(feature = "verifier-only"))]
+use crate::sql::{
+    ColumnType, CompiledQuery, LimitedResult, QueryError, QueryResult, SQLCompiler, SQLParser,
+    SQLQuery, TypedQueryResult,
+};
+#[cfg(not(feature = "verifier-only"))]
+use profiler::TimedSynthesis;
 
 /// Prover
 /// Paper Section 5: Non-interactive ZKP proof generation
 ///
 /// Implementation using Halo2 0.3.1 real API
+#[cfg(not(feature = "verifier-only"))]
 pub struct Prover {
     /// Proving key
     pk: ProvingKey<EqAffine>,
+    /// Instance columns of the key's circuit (from `C::configure`)
+    instance_columns: usize,
 }
 
+#[cfg(not(feature = "verifier-only"))]
 impl Prover {
     /// Create new prover
     /// Paper Section 5: Proving key generation
     ///
     /// Halo2 0.3.1 real API: keygen_pk(params, vk, circuit)
-    pub fn new(params: &Params<EqAffine>, circuit: &PoneglyphCircuit) -> Result<Self, Error> {
+    ///
+    /// Generic over the circuit type so `JoinFreeCircuit` can be proven too;
+    /// proofs must then be created for circuits of the same type.
+    pub fn new<C: Circuit<Fr>>(params: &Params<EqAffine>, circuit: &C) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("keygen", k = params.k()));
+
         // Create verifying key
         let vk = keygen_vk(params, circuit)?;
 
         // Create proving key
         let pk = keygen_pk(params, vk, circuit)?;
 
-        Ok(Self { pk })
+        Ok(Self {
+            pk,
+            instance_columns: instance_column_count::<C>(),
+        })
     }
 
     /// Create proof
     /// Paper Section 5: Non-interactive proof generation
     ///
     /// Halo2 0.3.1 real API: create_proof(params, pk, circuits, instances, rng, transcript)
-    pub fn prove(
+    ///
+    /// `public_inputs` holds the circuit's instance columns
+    /// (see `PublicInputs::to_instances`); a different number of columns
+    /// fails with `Error::InvalidInstances` (`check_instances` reports the
+    /// expected and found counts)
+    pub fn prove<C: Circuit<Fr>>(
         &self,
         params: &Params<EqAffine>,
-        circuit: &PoneglyphCircuit,
+        circuit: &C,
+        public_inputs: &[Vec<Fr>],
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_with_rng(params, circuit, public_inputs, OsRng)
+    }
+
+    /// Create a proof like `prove`, drawing the blinding randomness from
+    /// `rng` (a seeded RNG makes the proof reproducible)
+    pub fn prove_with_rng<C: Circuit<Fr>, R: RngCore>(
+        &self,
+        params: &Params<EqAffine>,
+        circuit: &C,
         public_inputs: &[Vec<Fr>],
+        rng: R,
     ) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("prove", k = params.k()));
+
+        if self.check_instances(public_inputs).is_err() {
+            return Err(Error::InvalidInstances);
+        }
+
         // Create transcript (Blake2bWrite)
         let mut transcript =
             Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<EqAffine>>::init(vec![]);
 
         // Format instances: &[&[&[C::Scalar]]]
         // public_inputs: &[Vec<Fr>] -> instances: &[&[&[Fr]]]
-        // Each public_input represents an instance column
-        let instances: Vec<Vec<&[Fr]>> =
-            public_inputs.iter().map(|pi| vec![pi.as_slice()]).collect();
-        let instances_refs: Vec<&[&[Fr]]> = instances.iter().map(|inst| inst.as_slice()).collect();
+        // Each public_input represents an instance column of the one circuit
+        let columns: Vec<&[Fr]> = public_inputs.iter().map(Vec::as_slice).collect();
+        let instances_refs = [columns.as_slice()];
 
         // Create proof
-        // Note: create_proof expects &[ConcreteCircuit], so we use &[circuit.clone()]
-        // Circuit implements Clone
+        // Note: create_proof expects &[ConcreteCircuit], so we borrow the circuit as a one-element slice
         create_proof(
             params,
             &self.pk,
-            &[circuit.clone()],
+            std::slice::from_ref(circuit),
             &instances_refs,
-            OsRng,
+            rng,
             &mut transcript,
         )?;
 
         // Get proof (transcript.finalize())
         Ok(transcript.finalize())
     }
+
+    /// Check that `public_inputs` has one column per instance column of
+    /// the circuit
+    pub fn check_instances(&self, public_inputs: &[Vec<Fr>]) -> Result<(), QueryError> {
+        check_instance_shape(self.instance_columns, public_inputs)
+    }
+
+    /// Verifier for this prover's proofs (its verifying key, without a
+    /// second keygen)
+    pub fn verifier(&self) -> Verifier {
+        Verifier {
+            vk: self.pk.get_vk().clone(),
+            instance_columns: self.instance_columns,
+        }
+    }
 }
 
 /// Verifier
 /// Paper Section 5: Non-interactive ZKP proof verification
 ///
 /// Implementation using Halo2 0.3.1 real API
+#[derive(Clone, Debug)]
 pub struct Verifier {
     /// Verifying key
     vk: VerifyingKey<EqAffine>,
+    /// Instance columns of the key's circuit (from `C::configure`)
+    instance_columns: usize,
 }
 
 impl Verifier {
@@ -100,23 +191,40 @@ impl Verifier {
     /// Paper Section 5: Verifying key generation
     ///
     /// Halo2 0.3.1 real API: keygen_vk(params, circuit)
-    pub fn new(params: &Params<EqAffine>, circuit: &PoneglyphCircuit) -> Result<Self, Error> {
+    pub fn new<C: Circuit<Fr>>(params: &Params<EqAffine>, circuit: &C) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("keygen", k = params.k()));
+
         // Create verifying key
         let vk = keygen_vk(params, circuit)?;
 
-        Ok(Self { vk })
+        Ok(Self {
+            vk,
+            instance_columns: instance_column_count::<C>(),
+        })
     }
 
     /// Verify proof
     /// Paper Section 5: Non-interactive proof verification
     ///
     /// Halo2 0.3.1 real API: verify_proof(params, vk, strategy, instances, transcript)
+    ///
+    /// `public_inputs` holds the circuit's instance columns, as in
+    /// `Prover::prove` (a different number of columns fails with
+    /// `Error::InvalidInstances`)
     pub fn verify(
         &self,
         params: &Params<EqAffine>,
         proof: &[u8],
         public_inputs: &[Vec<Fr>],
     ) -> Result<bool, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("verify", k = params.k()));
+
+        if public_inputs.len() != self.instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+
         // Create transcript (Blake2bRead)
         let mut transcript = Blake2bRead::<&[u8], EqAffine, Challenge255<EqAffine>>::init(proof);
 
@@ -125,21 +233,480 @@ impl Verifier {
 
         // Format instances: &[&[&[C::Scalar]]]
         // public_inputs: &[Vec<Fr>] -> instances: &[&[&[Fr]]]
-        let instances: Vec<Vec<&[Fr]>> =
-            public_inputs.iter().map(|pi| vec![pi.as_slice()]).collect();
-        let instances_refs: Vec<&[&[Fr]]> = instances.iter().map(|inst| inst.as_slice()).collect();
+        // Each public_input represents an instance column of the one circuit
+        let columns: Vec<&[Fr]> = public_inputs.iter().map(Vec::as_slice).collect();
+        let instances_refs = [columns.as_slice()];
 
         // Verify proof
         verify_proof(params, &self.vk, strategy, &instances_refs, &mut transcript)?;
 
         Ok(true)
     }
+
+    /// Check that `public_inputs` has one column per instance column of
+    /// the circuit
+    #[cfg(not(feature = "verifier-only"))]
+    pub fn check_instances(&self, public_inputs: &[Vec<Fr>]) -> Result<(), QueryError> {
+        check_instance_shape(self.instance_columns, public_inputs)
+    }
+}
+
+/// `QueryError::InstanceShape` unless `public_inputs` has one column per
+/// instance column of the key's circuit
+#[cfg(not(feature = "verifier-only"))]
+fn check_instance_shape(expected: usize, public_inputs: &[Vec<Fr>]) -> Result<(), QueryError> {
+    if public_inputs.len() == expected {
+        Ok(())
+    } else {
+        Err(QueryError::InstanceShape {
+            expected,
+            found: public_inputs.len(),
+        })
+    }
+}
+
+/// Proving Context
+/// Params, proving key and verifying key for one circuit shape, built once
+///
+/// Every proof created through the context uses the same `k`, so params and
+/// keys cannot get out of sync. The circuit type is fixed by `C`; circuits
+/// passed to `prove` must have the same operation shapes as the one the keys
+/// were generated from.
+#[cfg(not(feature = "verifier-only"))]
+pub struct ProvingContext<C: Circuit<Fr>> {
+    /// log2 of the number of rows
+    k: u32,
+    /// IPA commitment parameters of size 2^k
+    params: Params<EqAffine>,
+    prover: Prover,
+    verifier: Verifier,
+    _circuit: PhantomData<fn(&C)>,
+}
+
+#[cfg(not(feature = "verifier-only"))]
+impl<C: Circuit<Fr>> ProvingContext<C> {
+    /// Generate params of size 2^k and the keys for the circuit's shape
+    ///
+    /// Fails with `Error::NotEnoughRowsAvailable` if the circuit does not fit
+    /// in 2^k rows.
+    pub fn new(k: u32, circuit: &C) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("keygen", k));
+
+        let params = Params::<EqAffine>::new(k);
+
+        // The verifying key is generated once and shared with the proving key
+        let vk = keygen_vk(&params, circuit)?;
+        let pk = keygen_pk(&params, vk.clone(), circuit)?;
+        let instance_columns = instance_column_count::<C>();
+
+        Ok(Self {
+            k,
+            params,
+            prover: Prover {
+                pk,
+                instance_columns,
+            },
+            verifier: Verifier {
+                vk,
+                instance_columns,
+            },
+            _circuit: PhantomData,
+        })
+    }
+
+    /// log2 of the number of rows the keys were generated for
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Commitment parameters shared by proving and verification
+    pub fn params(&self) -> &Params<EqAffine> {
+        &self.params
+    }
+
+    /// Create a proof for a circuit of the context's shape
+    /// (see `Prover::prove`)
+    pub fn prove(&self, circuit: &C, public_inputs: &[Vec<Fr>]) -> Result<Vec<u8>, Error> {
+        self.prover.prove(&self.params, circuit, public_inputs)
+    }
+
+    /// Create a proof with the blinding randomness drawn from `rng`
+    /// (see `Prover::prove_with_rng`)
+    pub fn prove_with_rng<R: RngCore>(
+        &self,
+        circuit: &C,
+        public_inputs: &[Vec<Fr>],
+        rng: R,
+    ) -> Result<Vec<u8>, Error> {
+        self.prover
+            .prove_with_rng(&self.params, circuit, public_inputs, rng)
+    }
+
+    /// Create a proof like `prove`, recording witness synthesis, the rest
+    /// of proof creation and the proof size in `profiler`
+    pub fn prove_profiled(
+        &self,
+        circuit: &C,
+        public_inputs: &[Vec<Fr>],
+        profiler: &mut Profiler,
+    ) -> Result<Vec<u8>, Error> {
+        let synthesis = Cell::new(Duration::ZERO);
+        let timed = TimedSynthesis {
+            circuit,
+            elapsed: &synthesis,
+        };
+
+        let start = Instant::now();
+        let proof = self.prover.prove(&self.params, &timed, public_inputs);
+        let total = start.elapsed();
+
+        profiler.record(Stage::WitnessSynthesis, synthesis.get());
+        profiler.record(Stage::ProofCreation, total.saturating_sub(synthesis.get()));
+        if let Ok(proof) = &proof {
+            profiler.record_proof_size(proof.len());
+        }
+        proof
+    }
+
+    /// Verify a proof created by `prove` (see `Verifier::verify`)
+    pub fn verify(&self, proof: &[u8], public_inputs: &[Vec<Fr>]) -> Result<bool, Error> {
+        self.verifier.verify(&self.params, proof, public_inputs)
+    }
+}
+
+#[cfg(not(feature = "verifier-only"))]
+impl ProvingContext<PoneglyphCircuit> {
+    /// Context at the smallest k that fits the circuit (`CircuitStats::min_k`)
+    ///
+    /// For a `JoinFreeCircuit`, use `ProvingContext::new` with its own
+    /// `stats().min_k`.
+    pub fn for_circuit(circuit: &PoneglyphCircuit) -> Result<Self, Error> {
+        Self::new(circuit.stats()?.min_k, circuit)
+    }
+}
+
+/// Prove a SQL query over the given tables at the smallest k that fits it
+///
+/// # Parameters
+///
+/// - `sql`: Query text
+/// - `tables`: Table data (table_name -> column_name -> values), or a
+///   row-major `DatabaseTable` (see `TableSource`)
+/// - `profiler`: Records the time of every stage (parse, compile, keygen,
+///   witness synthesis, proof creation) and the proof size when given
+///
+/// # Returns
+///
+/// The proof and its public inputs: the commitments of `tables`
+/// (`CommittedDatabase`) and the query result (an empty WHERE result binds
+/// its match count, 0, as does a LIMIT over the WHERE rows (see
+/// `prove_query_limited`), and a HAVING the number of groups it keeps)
+#[cfg(not(feature = "verifier-only"))]
+pub fn prove_query<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+    profiler: Option<&mut Profiler>,
+) -> Result<(Vec<u8>, PublicInputs), String> {
+    let mut unused = Profiler::new();
+    let profiler = profiler.unwrap_or(&mut unused);
+
+    let query = profiler.time(Stage::Parse, || SQLParser::parse(sql))?;
+    prove_parsed(&query, tables, profiler)
+}
+
+/// Prove a SQL query like `prove_query`, also returning its typed result
+///
+/// # Parameters
+///
+/// - `schema`: Type of each output column (see `QueryResult::typed`)
+///
+/// # Returns
+///
+/// The proof, its public inputs and the reference executor's result
+/// (`SQLQuery::execute`) read by `schema`
+#[cfg(not(feature = "verifier-only"))]
+pub fn prove_query_with_result<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+    schema: &HashMap<String, ColumnType>,
+    profiler: Option<&mut Profiler>,
+) -> Result<(Vec<u8>, PublicInputs, TypedQueryResult), String> {
+    let mut unused = Profiler::new();
+    let profiler = profiler.unwrap_or(&mut unused);
+
+    let query = profiler.time(Stage::Parse, || SQLParser::parse(sql))?;
+    let result = query.execute(tables)?.typed(schema);
+    let (proof, public_inputs) = prove_parsed(&query, tables, profiler)?;
+    Ok((proof, public_inputs, result))
+}
+
+/// Prove a LIMIT query like `prove_query`, also returning its rows and how
+/// many rows matched before the LIMIT
+///
+/// # Returns
+///
+/// The proof, its public inputs and the limited result, whose rows and
+/// `total_matched` are committed as the query result
+/// (`LimitedResult::matches`), or an error unless the query is a LIMIT over
+/// columns of the FROM rows (no aggregation, GROUP BY or computed column),
+/// ordered by at most one column without NULL, whose WHERE clause is a
+/// single constant comparison (`col < v`, `col > v`, `col = v`)
+///
+/// # Note
+///
+/// Both are proven (`CompiledQuery::result`): `total_matched` is copied
+/// from the WHERE match count, and every row cell from the committed table
+/// cell it comes from. The WHERE check bit of every row is fixed, and with
+/// ORDER BY the matching rows are sorted with their columns as payloads, so
+/// the rows are the table's first matching rows in ORDER BY order.
+#[cfg(not(feature = "verifier-only"))]
+pub fn prove_query_limited<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+    profiler: Option<&mut Profiler>,
+) -> Result<(Vec<u8>, PublicInputs, LimitedResult), String> {
+    let mut unused = Profiler::new();
+    let profiler = profiler.unwrap_or(&mut unused);
+
+    let query = profiler.time(Stage::Parse, || SQLParser::parse(sql))?;
+    if query.limit.is_none() {
+        return Err(format!("{} has no LIMIT", sql));
+    }
+    let result = query.execute(tables)?;
+
+    let compiled = profiler.time(Stage::Compile, || SQLCompiler::compile(&query, tables))?;
+    let Some(total_matched) = compiled.match_count.as_ref().map(|op| op.count()) else {
+        return Err(format!(
+            "Cannot count the matches of {}: LIMIT needs rows filtered by a single \
+             constant WHERE comparison",
+            sql
+        ));
+    };
+    let Some(commitment) = compiled.result.clone() else {
+        return Err(format!(
+            "Cannot prove the rows of {}: LIMIT needs columns of the FROM rows ordered \
+             by at most one column without NULL",
+            sql
+        ));
+    };
+    let limited = LimitedResult::new(
+        QueryResult {
+            rows: commitment.rows.clone(),
+            ..result
+        },
+        total_matched,
+    );
+    let (proof, public_inputs) = prove_compiled(compiled, tables, Some(commitment), profiler)?;
+    Ok((proof, public_inputs, limited))
+}
+
+/// Compile and prove a parsed query (`prove_query` after parsing)
+#[cfg(not(feature = "verifier-only"))]
+fn prove_parsed<T: TableSource + ?Sized>(
+    query: &SQLQuery,
+    tables: &T,
+    profiler: &mut Profiler,
+) -> Result<(Vec<u8>, PublicInputs), String> {
+    let compiled = profiler.time(Stage::Compile, || SQLCompiler::compile(query, tables))?;
+    prove_compiled(compiled, tables, None, profiler)
+}
+
+/// Prove a compiled query (`prove_parsed` after compiling) over the committed
+/// `tables`, with the result commitment bound instead of the compiled result
+/// if one is given
+#[cfg(not(feature = "verifier-only"))]
+fn prove_compiled<T: TableSource + ?Sized>(
+    compiled: CompiledQuery,
+    tables: &T,
+    result_commitment: Option<ResultCommitment>,
+    profiler: &mut Profiler,
+) -> Result<(Vec<u8>, PublicInputs), String> {
+    let database = CommittedDatabase::new(&tables.column_map());
+    let circuit = PoneglyphCircuit::from_compiled(compiled, result_commitment, Some(database));
+    let public_inputs = PublicInputs {
+        db_commitments: circuit.db_commitments.clone(),
+        query_result: circuit.bound_result(),
+    };
+
+    let context = profiler
+        .time(Stage::Keygen, || ProvingContext::for_circuit(&circuit))
+        .map_err(|e| format!("Failed to create keys: {:?}", e))?;
+    let proof = context
+        .prove_profiled(&circuit, &public_inputs.to_instances(), profiler)
+        .map_err(|e| format!("Failed to prove: {:?}", e))?;
+
+    Ok((proof, public_inputs))
+}
+
+/// Circuit proving that a query's aggregate over a committed database
+/// equals `expected`
+///
+/// The query must compute exactly one aggregate over all rows (no GROUP BY,
+/// no HAVING); with a WHERE clause it must be a COUNT or SUM, which the
+/// circuit filters by the WHERE check bits. `tables` are committed
+/// (`CommittedDatabase`): the aggregated column and the WHERE operands are
+/// copied from the committed cells, so only the committed data proves. The
+/// result cell is bound to the query result public input and constrained to
+/// equal `expected`, so the circuit is unsatisfiable when the data gives a
+/// different value.
+///
+/// # Returns
+///
+/// The circuit, whose public inputs must be the table commitments and
+/// `expected`, or an error if the query is not a single 64-bit aggregate
+/// over all rows or reads values not bound to the committed columns
+#[cfg(not(feature = "verifier-only"))]
+pub fn aggregate_assertion<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+    expected: u64,
+) -> Result<PoneglyphCircuit, String> {
+    let query = SQLParser::parse(sql)?;
+    let compiled = SQLCompiler::compile(&query, tables)?;
+    let database = CommittedDatabase::new(&tables.column_map());
+    let single_global = compiled.aggregations.len() == 1
+        && compiled.aggregations[0].group_keys.is_empty()
+        && !compiled.aggregations[0].is_wide()
+        && compiled.having.is_empty()
+        && compiled.match_count.is_none();
+    let filtered = query.where_clause.is_none()
+        || compiled
+            .aggregations
+            .iter()
+            .all(|op| op.value_checks.is_some());
+    if !single_global || !filtered {
+        return Err(format!(
+            "Cannot assert {}: it must compute one 64-bit aggregate over all rows \
+             (or a COUNT / SUM over the WHERE rows)",
+            sql
+        ));
+    }
+    // The values are copied from a committed column, or are WHERE check
+    // bits over committed cells
+    let aggregation = &compiled.aggregations[0];
+    let committed = (aggregation.column.is_some() || aggregation.value_checks.is_some())
+        && (aggregation.unfiltered.is_none() || aggregation.column.is_some())
+        && compiled.range_checks.iter().all(|op| op.source.is_some());
+    if !committed {
+        return Err(format!(
+            "Cannot assert {}: its values are not read from a committed column",
+            sql
+        ));
+    }
+
+    let mut circuit = PoneglyphCircuit {
+        query_result: Value::known(Fr::from(expected)),
+        ..PoneglyphCircuit::from_compiled(compiled, None, Some(database))
+    };
+    circuit.aggregations[0].expected = Some(expected);
+    Ok(circuit)
+}
+
+/// Prove that a query's aggregate equals `expected`
+/// (see `aggregate_assertion`)
+///
+/// # Returns
+///
+/// The proof and its public inputs (the table commitments and `expected`),
+/// or an error if the aggregate over `tables` is not `expected`
+#[cfg(not(feature = "verifier-only"))]
+pub fn assert_aggregate<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+    expected: u64,
+) -> Result<(Vec<u8>, PublicInputs), String> {
+    let circuit = aggregate_assertion(sql, tables, expected)?;
+    let actual = circuit.aggregations[0].group_results();
+    if actual.first() != Some(&expected) {
+        return Err(format!(
+            "Aggregate of {} is {:?}, not {}",
+            sql,
+            actual.first(),
+            expected
+        ));
+    }
+    let public_inputs = PublicInputs {
+        db_commitments: circuit.db_commitments.clone(),
+        query_result: Some(Fr::from(expected)),
+    };
+
+    let context = ProvingContext::for_circuit(&circuit)
+        .map_err(|e| format!("Failed to create keys: {:?}", e))?;
+    let proof = context
+        .prove(&circuit, &public_inputs.to_instances())
+        .map_err(|e| format!("Failed to prove: {:?}", e))?;
+
+    Ok((proof, public_inputs))
+}
+
+/// Circuit proving which rows of a table match a query's WHERE clause
+/// (`SQLCompiler::compile_selection`)
+///
+/// Only the WHERE range checks are proven; their check bits, copied from the
+/// check cells, are hashed into a `ResultCommitment` bound to the query
+/// result, so a client can fetch the matching rows itself and check them
+/// against the committed selection vector. Lighter than proving the query.
+/// `tables` are committed (`CommittedDatabase`) and the compared column
+/// values are copied from the committed cells; the operands of a compared
+/// expression (`amount * 2 > 70`) are still witnessed.
+///
+/// # Returns
+///
+/// The circuit and its public inputs (the table commitments, and the
+/// selection vector's commitment as the query result)
+#[cfg(not(feature = "verifier-only"))]
+pub fn selection_circuit<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+) -> Result<(PoneglyphCircuit, PublicInputs), String> {
+    let query = SQLParser::parse(sql)?;
+    let compiled = SQLCompiler::compile_selection(&query, tables)?;
+    let selection = compiled
+        .selection
+        .as_ref()
+        .ok_or("Selection vector was not compiled")?
+        .value();
+    let database = CommittedDatabase::new(&tables.column_map());
+
+    // Only the WHERE clause is compiled: the selection is the result commitment
+    let circuit = PoneglyphCircuit {
+        query_result: Value::known(selection),
+        ..PoneglyphCircuit::from_compiled(compiled, None, Some(database))
+    };
+    let public_inputs = PublicInputs {
+        db_commitments: circuit.db_commitments.clone(),
+        query_result: Some(selection),
+    };
+    Ok((circuit, public_inputs))
+}
+
+/// Prove the WHERE selection vector of a query (see `selection_circuit`)
+///
+/// # Returns
+///
+/// The proof and its public inputs
+#[cfg(not(feature = "verifier-only"))]
+pub fn prove_selection<T: TableSource + ?Sized>(
+    sql: &str,
+    tables: &T,
+) -> Result<(Vec<u8>, PublicInputs), String> {
+    let (circuit, public_inputs) = selection_circuit(sql, tables)?;
+    let context = ProvingContext::for_circuit(&circuit)
+        .map_err(|e| format!("Failed to create keys: {:?}", e))?;
+    let proof = context
+        .prove(&circuit, &public_inputs.to_instances())
+        .map_err(|e| format!("Failed to prove: {:?}", e))?;
+
+    Ok((proof, public_inputs))
 }
 
 /// Mock Prover Helper (for testing)
 /// Paper Section 5: Mock prover for development and testing
+#[cfg(not(feature = "verifier-only"))]
 pub struct MockProverHelper;
 
+#[cfg(not(feature = "verifier-only"))]
 impl MockProverHelper {
     /// Create and verify mock proof (for testing)
     /// Paper Section 5: For development and testing
@@ -153,9 +720,10 @@ impl MockProverHelper {
         let prover = MockProver::run(k, circuit, public_inputs.to_vec())
             .map_err(|e| format!("Failed to run mock prover: {:?}", e))?;
 
-        prover
-            .verify()
-            .map_err(|e| format!("Failed to verify mock proof: {:?}", e))?;
+        prover.verify().map_err(|failures| {
+            let report = layout_report::describe_failures(circuit, &failures);
+            format!("Failed to verify mock proof:\n{}", report)
+        })?;
 
         Ok(true)
     }
@@ -0,0 +1,155 @@
+// Stage profiling
+// Per-stage timings of a query proof (parse, compile, keygen, witness
+// synthesis, proof creation) and its size, recorded at runtime
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::Layouter,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::pallas::Base as Fr;
+
+/// Proving stage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// SQL text to `SQLQuery`
+    Parse,
+    /// `SQLQuery` to circuit operations
+    Compile,
+    /// Params, verifying key and proving key
+    Keygen,
+    /// Circuit synthesis while proving (witness assignment)
+    WitnessSynthesis,
+    /// The rest of proof creation (commitments, transcript)
+    ProofCreation,
+}
+
+/// Stage Timings
+/// Time spent per stage, accumulated over every recorded run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    pub parse: Duration,
+    pub compile: Duration,
+    pub keygen: Duration,
+    pub witness_synthesis: Duration,
+    pub proof_creation: Duration,
+}
+
+impl StageTimings {
+    /// Time of one stage
+    pub fn get(&self, stage: Stage) -> Duration {
+        match stage {
+            Stage::Parse => self.parse,
+            Stage::Compile => self.compile,
+            Stage::Keygen => self.keygen,
+            Stage::WitnessSynthesis => self.witness_synthesis,
+            Stage::ProofCreation => self.proof_creation,
+        }
+    }
+
+    /// Sum of all stages
+    pub fn total(&self) -> Duration {
+        self.parse + self.compile + self.keygen + self.witness_synthesis + self.proof_creation
+    }
+
+    fn get_mut(&mut self, stage: Stage) -> &mut Duration {
+        match stage {
+            Stage::Parse => &mut self.parse,
+            Stage::Compile => &mut self.compile,
+            Stage::Keygen => &mut self.keygen,
+            Stage::WitnessSynthesis => &mut self.witness_synthesis,
+            Stage::ProofCreation => &mut self.proof_creation,
+        }
+    }
+}
+
+/// Profiler
+/// Records stage timings of `prove_query` (or of any code timed with `time`)
+/// and the size of the proof it created
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// let mut profiler = Profiler::new();
+/// let (proof, public_inputs) = prove_query(sql, &table_data, Some(&mut profiler))?;
+/// println!("{:?} {:?}", profiler.timings(), profiler.proof_size());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    timings: StageTimings,
+    /// Byte length of the last recorded proof
+    proof_size: Option<usize>,
+}
+
+impl Profiler {
+    /// Profiler with all stages at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f`, adding its duration to `stage`
+    pub fn time<T>(&mut self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(stage, start.elapsed());
+        result
+    }
+
+    /// Add a measured duration to `stage`
+    pub fn record(&mut self, stage: Stage, duration: Duration) {
+        *self.timings.get_mut(stage) += duration;
+    }
+
+    /// Timings recorded so far
+    pub fn timings(&self) -> StageTimings {
+        self.timings
+    }
+
+    /// Record the byte length of a created proof
+    pub fn record_proof_size(&mut self, bytes: usize) {
+        self.proof_size = Some(bytes);
+    }
+
+    /// Byte length of the last proof recorded (None before any proof)
+    pub fn proof_size(&self) -> Option<usize> {
+        self.proof_size
+    }
+}
+
+/// Circuit wrapper that adds the time spent in `synthesize` to `elapsed`
+///
+/// # Note
+///
+/// `create_proof` synthesizes the circuit once inside the proof, so proving
+/// the wrapper separates witness synthesis from the rest of proof creation.
+/// Only for proving: `without_witnesses` keeps the wrapped circuit, so keys
+/// are generated from the circuit itself.
+pub(crate) struct TimedSynthesis<'a, C> {
+    pub circuit: &'a C,
+    pub elapsed: &'a Cell<Duration>,
+}
+
+impl<C: Circuit<Fr>> Circuit<Fr> for TimedSynthesis<'_, C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            circuit: self.circuit,
+            elapsed: self.elapsed,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let start = Instant::now();
+        let result = self.circuit.synthesize(config, layouter);
+        self.elapsed.set(self.elapsed.get() + start.elapsed());
+        result
+    }
+}
@@ -8,21 +8,161 @@
 //
 // Note: Nova is not required! Halo2 PLONKish has native recursive proof support.
 // This implementation is fully compatible with the paper and simpler.
-
-use crate::circuit::PoneglyphCircuit;
-use crate::prover::Prover;
+//
+// Not implemented: aggregating the proofs of several queries into one proof
+// whose verification is O(1) in the number of queries. That needs a circuit
+// on Vesta verifying each Pallas proof's IPA opening (accumulating them),
+// which halo2_proofs 0.3 does not provide; the provers here create one proof
+// per circuit and verification stays one check per proof.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::circuit::{
+    merkle,
+    stats::{key_layout, KeyLayout},
+    AggregationPartial, CommittedDatabase, DeltaCircuit, JoinFreeCircuit, MatchCountOp, MerkleTree,
+    Operand, PoneglyphCircuit, PublicInputs, ResultCommitment, ResultSource, RowUpdate,
+};
+use crate::prover::{Prover, ProvingContext, Verifier};
+use crate::sql::{SQLCompiler, SQLParser, SQLQuery};
 use pasta_curves::pallas::Base as Fr;
 
 use halo2_proofs::{
     pasta::EqAffine,
     plonk::{
-        create_proof, keygen_pk, keygen_vk, verify_proof, Error, ProvingKey, SingleVerifier,
-        VerifyingKey,
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey,
+        SingleVerifier, VerifyingKey,
     },
     poly::commitment::Params,
     transcript::{Blake2bRead, Blake2bWrite, Challenge255},
 };
 
+/// Recursion Limits
+/// Bounds on the number of circuits proven by `BatchProver::prove_batch`
+/// and `Halo2RecursiveProver::prove_recursive`
+///
+/// A batch of up to `max_batch` circuits is proven directly (depth 1).
+/// With `auto_chunk`, a larger batch is split into sub-batches of
+/// `max_batch` circuits whose proofs are folded `max_batch` at a time,
+/// one level per fold, up to `max_depth` levels in total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecursionConfig {
+    /// Maximum number of levels (sub-batch proving plus folds)
+    pub max_depth: usize,
+    /// Maximum number of circuits (or proofs) per batch
+    pub max_batch: usize,
+    /// Split batches larger than `max_batch` instead of failing
+    pub auto_chunk: bool,
+}
+
+impl Default for RecursionConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_batch: 64,
+            auto_chunk: false,
+        }
+    }
+}
+
+impl RecursionConfig {
+    /// Number of levels needed to prove `circuits` circuits
+    ///
+    /// # Errors
+    ///
+    /// - `BatchTooLarge` if `circuits > max_batch` without `auto_chunk`
+    ///   (or with `max_batch < 2`, which cannot fold)
+    /// - `DepthExceeded` if more than `max_depth` levels are needed
+    pub fn depth(&self, circuits: usize) -> Result<usize, RecursionError> {
+        if circuits > self.max_batch && (!self.auto_chunk || self.max_batch < 2) {
+            return Err(RecursionError::BatchTooLarge {
+                circuits,
+                max_batch: self.max_batch,
+            });
+        }
+
+        // Smallest depth with max_batch^depth >= circuits
+        let mut depth = 1;
+        let mut capacity = self.max_batch;
+        while capacity < circuits {
+            capacity = capacity.saturating_mul(self.max_batch);
+            depth += 1;
+        }
+
+        if depth > self.max_depth {
+            return Err(RecursionError::DepthExceeded {
+                depth,
+                max_depth: self.max_depth,
+            });
+        }
+        Ok(depth)
+    }
+
+    /// Circuit index ranges of the sub-batches (after checking the limits)
+    fn sub_batches(&self, circuits: usize) -> Result<Vec<Range<usize>>, RecursionError> {
+        self.depth(circuits)?;
+        let size = self.max_batch.max(1);
+        Ok((0..circuits)
+            .step_by(size)
+            .map(|start| start..(start + size).min(circuits))
+            .collect())
+    }
+
+    /// Fold sub-batch proofs `max_batch` at a time until one remains
+    ///
+    /// Proofs are combined by concatenation, so the result holds every
+    /// circuit's proof in circuit order.
+    fn fold(&self, mut proofs: Vec<Vec<u8>>) -> Vec<u8> {
+        while proofs.len() > 1 {
+            proofs = proofs
+                .chunks(self.max_batch)
+                .map(|group| group.concat())
+                .collect();
+        }
+        proofs.concat()
+    }
+}
+
+/// Recursion error
+/// Returned by `BatchProver::prove_batch` and `Halo2RecursiveProver::prove_recursive`
+#[derive(Debug)]
+pub enum RecursionError {
+    /// More circuits than `max_batch` and auto-chunking is off
+    BatchTooLarge { circuits: usize, max_batch: usize },
+    /// Folding the sub-batches needs more than `max_depth` levels
+    DepthExceeded { depth: usize, max_depth: usize },
+    /// Proof generation failed
+    Proof(Error),
+}
+
+impl From<Error> for RecursionError {
+    fn from(error: Error) -> Self {
+        RecursionError::Proof(error)
+    }
+}
+
+impl std::fmt::Display for RecursionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecursionError::BatchTooLarge {
+                circuits,
+                max_batch,
+            } => write!(
+                f,
+                "Batch of {} circuits exceeds max_batch {}",
+                circuits, max_batch
+            ),
+            RecursionError::DepthExceeded { depth, max_depth } => {
+                write!(f, "Batch needs depth {}, max_depth is {}", depth, max_depth)
+            }
+            RecursionError::Proof(error) => write!(f, "Proof generation failed: {:?}", error),
+        }
+    }
+}
+
+impl std::error::Error for RecursionError {}
+
 /// Halo2 Recursive Prover
 /// Paper Section 5: Recursive proof composition using cycle curves
 ///
@@ -44,6 +184,8 @@ pub struct Halo2RecursiveProver {
     pk_pallas: ProvingKey<EqAffine>,
     /// Pallas curve verifying key
     vk_pallas: VerifyingKey<EqAffine>,
+    /// Batch size and depth limits
+    config: RecursionConfig,
 }
 
 /// Recursive Proof Result
@@ -54,8 +196,8 @@ pub struct RecursiveProof {
     pub proof_pallas: Vec<u8>,
     /// Vesta curve proof (verifier, recursive)
     pub proof_vesta: Option<Vec<u8>>,
-    /// Public inputs
-    pub public_inputs: Vec<Vec<Fr>>,
+    /// Public inputs of each circuit (instance columns, as in `Prover::prove`)
+    pub public_inputs: Vec<Vec<Vec<Fr>>>,
 }
 
 impl Halo2RecursiveProver {
@@ -72,65 +214,76 @@ impl Halo2RecursiveProver {
         Ok(Self {
             pk_pallas,
             vk_pallas,
+            config: RecursionConfig::default(),
         })
     }
 
+    /// Use the given batch size and depth limits
+    pub fn with_config(mut self, config: RecursionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Create recursive proof
     /// Paper Section 5: Recursive proof composition
     ///
     /// # Algorithm
     ///
-    /// 1. Create proof on Pallas curve for each circuit
-    /// 2. Combine proofs (recursive composition)
-    /// 3. Verify on Vesta curve (recursive)
+    /// 1. Check the batch against `RecursionConfig` (split into sub-batches
+    ///    with `auto_chunk`)
+    /// 2. Create proof on Pallas curve for each circuit
+    /// 3. Combine proofs (recursive composition, folding sub-batches)
+    /// 4. Verify on Vesta curve (recursive)
     pub fn prove_recursive(
         &self,
         params_pallas: &Params<EqAffine>,
         circuits: &[PoneglyphCircuit],
-        public_inputs: &[Vec<Fr>],
-    ) -> Result<RecursiveProof, Error> {
+        public_inputs: &[Vec<Vec<Fr>>],
+    ) -> Result<RecursiveProof, RecursionError> {
         if circuits.is_empty() {
-            return Err(Error::Synthesis);
+            return Err(Error::Synthesis.into());
         }
 
-        // Create proof for each circuit
-        let mut all_proofs = Vec::new();
-
-        for (i, circuit) in circuits.iter().enumerate() {
-            // Create transcript
-            let mut transcript =
-                Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<EqAffine>>::init(vec![]);
-
-            // Format public inputs
-            let instances: Vec<Vec<&[Fr]>> = if i < public_inputs.len() {
-                vec![vec![public_inputs[i].as_slice()]]
-            } else {
-                vec![vec![]]
-            };
-            let instances_refs: Vec<&[&[Fr]]> =
-                instances.iter().map(|inst| inst.as_slice()).collect();
-
-            // Create proof
-            create_proof(
-                params_pallas,
-                &self.pk_pallas,
-                &[circuit.clone()],
-                &instances_refs,
-                rand::rngs::OsRng,
-                &mut transcript,
-            )?;
-
-            // Get proof
-            let proof = transcript.finalize();
-            all_proofs.push(proof);
+        // Create proof for each circuit, one sub-batch at a time
+        let mut batch_proofs = Vec::new();
+
+        for batch in self.config.sub_batches(circuits.len())? {
+            let mut all_proofs = Vec::new();
+
+            for i in batch {
+                // Create transcript
+                let mut transcript =
+                    Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<EqAffine>>::init(vec![]);
+
+                // Format public inputs (instance columns of circuit i)
+                let columns: Vec<&[Fr]> = match public_inputs.get(i) {
+                    Some(inputs) => inputs.iter().map(Vec::as_slice).collect(),
+                    None => vec![&[]; PublicInputs::COLUMNS],
+                };
+                let instances_refs = [columns.as_slice()];
+
+                // Create proof
+                create_proof(
+                    params_pallas,
+                    &self.pk_pallas,
+                    std::slice::from_ref(&circuits[i]),
+                    &instances_refs,
+                    rand::rngs::OsRng,
+                    &mut transcript,
+                )?;
+
+                // Get proof
+                let proof = transcript.finalize();
+                all_proofs.push(proof);
+            }
+
+            // Combine proofs (simple concatenation)
+            // Note: Production may require more sophisticated composition
+            batch_proofs.push(all_proofs.concat());
         }
 
-        // Combine proofs (simple concatenation)
-        // Note: Production may require more sophisticated composition
-        let combined_proof = all_proofs.concat();
-
         Ok(RecursiveProof {
-            proof_pallas: combined_proof,
+            proof_pallas: self.config.fold(batch_proofs),
             proof_vesta: None, // Vesta proof is None for now (verifier circuit needed - can be implemented in the future)
             public_inputs: public_inputs.to_vec(),
         })
@@ -153,9 +306,8 @@ impl Halo2RecursiveProver {
         // Verify (for first circuit - simple implementation)
         // Note: Production should verify all circuits
         if let Some(first_inputs) = proof.public_inputs.first() {
-            let first_instances = vec![vec![first_inputs.as_slice()]];
-            let first_instances_refs: Vec<&[&[Fr]]> =
-                first_instances.iter().map(|inst| inst.as_slice()).collect();
+            let first_columns: Vec<&[Fr]> = first_inputs.iter().map(Vec::as_slice).collect();
+            let first_instances_refs = [first_columns.as_slice()];
 
             // Parse and verify proof
             // Note: Simple implementation - production requires proper proof parsing
@@ -181,8 +333,8 @@ pub struct IncrementalProver {
     prover: Prover,
     /// Accumulated proofs
     accumulated_proofs: Vec<Vec<u8>>,
-    /// Accumulated public inputs
-    accumulated_inputs: Vec<Vec<Fr>>,
+    /// Accumulated public inputs (one entry per proof)
+    accumulated_inputs: Vec<Vec<Vec<Fr>>>,
 }
 
 impl IncrementalProver {
@@ -208,7 +360,7 @@ impl IncrementalProver {
 
         // Accumulate
         self.accumulated_proofs.push(new_proof.clone());
-        self.accumulated_inputs.extend_from_slice(public_inputs);
+        self.accumulated_inputs.push(public_inputs.to_vec());
 
         // Combined proof (simple concatenation)
         // Note: Production may require more sophisticated composition
@@ -221,7 +373,7 @@ impl IncrementalProver {
     }
 
     /// Get accumulated public inputs
-    pub fn accumulated_inputs(&self) -> &[Vec<Fr>] {
+    pub fn accumulated_inputs(&self) -> &[Vec<Vec<Fr>>] {
         &self.accumulated_inputs
     }
 }
@@ -231,40 +383,588 @@ impl IncrementalProver {
 pub struct BatchProver {
     /// Base prover
     prover: Prover,
+    /// Batch size and depth limits
+    config: RecursionConfig,
 }
 
 impl BatchProver {
     /// Create new batch prover
     pub fn new(prover: Prover) -> Self {
-        Self { prover }
+        Self {
+            prover,
+            config: RecursionConfig::default(),
+        }
+    }
+
+    /// Use the given batch size and depth limits
+    pub fn with_config(mut self, config: RecursionConfig) -> Self {
+        self.config = config;
+        self
     }
 
     /// Create batch proof for multiple circuits
     /// Paper Section 5: Batch processing
+    ///
+    /// The batch is checked against `RecursionConfig` before any proof is
+    /// created; with `auto_chunk`, sub-batches are proven and folded.
     pub fn prove_batch(
         &self,
         params: &Params<EqAffine>,
         circuits: &[PoneglyphCircuit],
         public_inputs: &[Vec<Vec<Fr>>],
-    ) -> Result<Vec<u8>, Error> {
-        let mut all_proofs = Vec::new();
+    ) -> Result<Vec<u8>, RecursionError> {
+        let mut batch_proofs = Vec::new();
 
-        for (i, circuit) in circuits.iter().enumerate() {
-            let inputs = if i < public_inputs.len() {
-                &public_inputs[i]
-            } else {
-                &vec![]
-            };
+        for batch in self.config.sub_batches(circuits.len())? {
+            let mut all_proofs = Vec::new();
 
-            let proof = self.prover.prove(params, circuit, inputs)?;
-            all_proofs.push(proof);
+            for i in batch {
+                let inputs = if i < public_inputs.len() {
+                    public_inputs[i].clone()
+                } else {
+                    PublicInputs::default().to_instances()
+                };
+
+                let proof = self.prover.prove(params, &circuits[i], &inputs)?;
+                all_proofs.push(proof);
+            }
+
+            batch_proofs.push(all_proofs.concat());
         }
 
         // Combine proofs
-        Ok(all_proofs.concat())
+        Ok(self.config.fold(batch_proofs))
+    }
+}
+
+/// Chunk Proof
+/// Proof of one chunk of a `ChunkedProof`
+#[derive(Clone, Debug)]
+pub struct ChunkProof {
+    pub proof: Vec<u8>,
+    /// Public inputs: the commitment of the chunk's rows and of its
+    /// partials (`ChunkProof::commitment`)
+    pub public_inputs: PublicInputs,
+    /// Aggregation partials of the chunk's rows, one per compiled aggregation
+    pub partials: Vec<AggregationPartial>,
+    /// WHERE match count bound after the partials (0 for a chunk whose rows
+    /// all fail a single-comparison WHERE, see `SQLCompiler::compile`);
+    /// None otherwise
+    pub matched: Option<u64>,
+    /// Index of the chunk's verifying key in `ChunkedProof::verifiers`
+    pub key: usize,
+}
+
+impl ChunkProof {
+    /// Commitment of `partials` the chunk's proof binds to its query result
+    ///
+    /// One row `[aggregation, group key, result]` per group of each
+    /// partial, in order (group key 0 without GROUP BY). In the circuit
+    /// the aggregation index is a constant, the group key is copied from
+    /// the GROUP BY sort output of `key_aggregation` and the result from
+    /// the aggregation's group result cell; with no `key_aggregation` the
+    /// keys are witnessed (the value is the same). `matched` is hashed after
+    /// the rows, copied from the match count cell.
+    pub fn commitment(
+        partials: &[AggregationPartial],
+        matched: Option<u64>,
+        key_aggregation: Option<usize>,
+    ) -> ResultCommitment {
+        let rows = partials
+            .iter()
+            .enumerate()
+            .flat_map(|(aggregation, partial)| {
+                partial
+                    .results
+                    .iter()
+                    .enumerate()
+                    .map(move |(group, &result)| {
+                        let key = match (partial.group_keys.get(group), key_aggregation) {
+                            (Some(&key), Some(key_aggregation)) => (
+                                key,
+                                ResultSource::GroupKey {
+                                    aggregation: key_aggregation,
+                                    group,
+                                },
+                            ),
+                            (Some(&key), None) => (key, ResultSource::Witness),
+                            (None, _) => (0, ResultSource::Operand(Operand::Constant(0))),
+                        };
+                        let index = aggregation as u64;
+                        vec![
+                            (index, ResultSource::Operand(Operand::Constant(index))),
+                            key,
+                            (result, ResultSource::Aggregation { aggregation, group }),
+                        ]
+                    })
+            })
+            .collect();
+        ResultCommitment {
+            matched,
+            ..ResultCommitment::sourced(rows)
+        }
+    }
+}
+
+/// Chunked Proof Result
+/// Result of `StreamingProver::prove_query_chunked`
+///
+/// Each chunk proves its aggregation partials: they are bound to its query
+/// result (`ChunkProof::commitment`), next to the commitment of its rows.
+/// Chunks whose circuits lay out the same share a key, so there is one
+/// verifying key per distinct layout rather than one per chunk.
+#[derive(Clone, Debug)]
+pub struct ChunkedProof {
+    /// Proof of each chunk, in chunk order
+    pub chunks: Vec<ChunkProof>,
+    /// Verifying keys of the chunk proofs (`ChunkProof::key`)
+    pub verifiers: Vec<Verifier>,
+    /// Combined aggregation partials over all chunks (one per compiled aggregation)
+    pub aggregations: Vec<AggregationPartial>,
+    /// Number of streamed rows
+    pub rows: usize,
+}
+
+impl ChunkedProof {
+    /// Verify every chunk against its partials, then their combination
+    ///
+    /// # Returns
+    ///
+    /// true if each chunk's query result is the commitment of its partials,
+    /// its proof verifies with its key, and the partials combine, in chunk
+    /// order, into `aggregations`
+    ///
+    /// # Note
+    ///
+    /// The verifying keys are the prover's (they depend on each chunk's
+    /// layout, which the rows decide); a verifier that does not trust them
+    /// must compare them with keys of its own. The table commitment of each
+    /// chunk (`PublicInputs::db_commitments`) must likewise be checked
+    /// against the rows the caller expects.
+    pub fn verify(&self, params: &Params<EqAffine>) -> Result<bool, Error> {
+        let mut combined: Vec<AggregationPartial> = Vec::new();
+        for chunk in &self.chunks {
+            let commitment = ChunkProof::commitment(&chunk.partials, chunk.matched, None);
+            if chunk.public_inputs.query_result != Some(commitment.value()) {
+                return Ok(false);
+            }
+            let verifier = self
+                .verifiers
+                .get(chunk.key)
+                .ok_or(Error::InvalidInstances)?;
+            if !verifier
+                .verify(params, &chunk.proof, &chunk.public_inputs.to_instances())
+                .unwrap_or(false)
+            {
+                return Ok(false);
+            }
+
+            if combined.is_empty() {
+                combined = chunk.partials.clone();
+            } else if combined.len() != chunk.partials.len()
+                || combined
+                    .iter_mut()
+                    .zip(&chunk.partials)
+                    .any(|(combined, partial)| combined.combine(partial).is_err())
+            {
+                return Ok(false);
+            }
+        }
+        Ok(!self.chunks.is_empty() && combined == self.aggregations)
+    }
+}
+
+/// Streaming Proof Generation
+/// Paper Section 5: Incremental proof generation for tables that don't fit in memory
+///
+/// Rows are read from an iterator in fixed-size chunks; each chunk is
+/// compiled and proven as its own `JoinFreeCircuit`, so at most
+/// `chunk_rows` rows are held in memory at a time. Each chunk's proof binds
+/// its aggregation partials (`ChunkProof::commitment`), and the partials
+/// are combined with `AggregationPartial::combine`
+/// (`ChunkedProof::verify` checks both).
+///
+/// # Note
+///
+/// - Only aggregations over row-local operations are supported (WHERE,
+///   GROUP BY); JOIN, ORDER BY, HAVING, LIMIT and window functions need the
+///   whole table
+/// - GROUP BY input must already be grouped (sorted by the group key), as
+///   in the single-shot Aggregation Gate
+/// - Keys are generated once per chunk layout (`KeyLayout`): the selector
+///   and fixed layout depends on the chunk's rows (e.g. its number of
+///   groups), so chunks of a different layout get keys of their own
+pub struct StreamingProver {
+    /// Column names of the streamed table, in row order
+    columns: Vec<String>,
+}
+
+impl StreamingProver {
+    /// Create new streaming prover for rows with the given columns
+    pub fn new(columns: Vec<String>) -> Self {
+        Self { columns }
+    }
+
+    /// Prove a query over a stream of rows, `chunk_rows` rows per circuit
+    ///
+    /// # Parameters
+    ///
+    /// - `sql`: Aggregation query over a single table (the FROM table)
+    /// - `table_iter`: Rows of the FROM table, each in `columns` order
+    /// - `chunk_rows`: Number of rows proven per circuit
+    ///
+    /// # Returns
+    ///
+    /// Per-chunk proofs with their partials, their verifying keys and the
+    /// combined aggregation results
+    pub fn prove_query_chunked(
+        &self,
+        params: &Params<EqAffine>,
+        sql: &str,
+        table_iter: impl IntoIterator<Item = Vec<u64>>,
+        chunk_rows: usize,
+    ) -> Result<ChunkedProof, String> {
+        if chunk_rows == 0 {
+            return Err("chunk_rows must be greater than 0".to_string());
+        }
+
+        let query = SQLParser::parse(sql)?;
+        if query.joins.is_some()
+            || query.order_by.is_some()
+            || query.having.is_some()
+            || query.limit.is_some()
+            || query.windows.is_some()
+        {
+            return Err(
+                "JOIN, ORDER BY, HAVING, LIMIT and window functions cannot be proven chunk by chunk"
+                    .to_string(),
+            );
+        }
+        if query.aggregations.is_none() {
+            return Err("Only aggregations can be proven chunk by chunk".to_string());
+        }
+
+        let mut result = ChunkedProof {
+            chunks: Vec::new(),
+            verifiers: Vec::new(),
+            aggregations: Vec::new(),
+            rows: 0,
+        };
+        // Proving key of each distinct chunk layout (`result.verifiers` order)
+        let mut keys: Vec<(KeyLayout, Prover)> = Vec::new();
+
+        let mut chunk: Vec<Vec<u64>> = Vec::with_capacity(chunk_rows);
+        for row in table_iter {
+            if row.len() != self.columns.len() {
+                return Err(format!(
+                    "Row {} has {} values, expected {}",
+                    result.rows + chunk.len(),
+                    row.len(),
+                    self.columns.len()
+                ));
+            }
+            chunk.push(row);
+
+            if chunk.len() == chunk_rows {
+                self.prove_chunk(params, &query, &chunk, &mut keys, &mut result)?;
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            self.prove_chunk(params, &query, &chunk, &mut keys, &mut result)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Compile and prove one chunk, combining its aggregation partials
+    fn prove_chunk(
+        &self,
+        params: &Params<EqAffine>,
+        query: &SQLQuery,
+        chunk: &[Vec<u64>],
+        keys: &mut Vec<(KeyLayout, Prover)>,
+        result: &mut ChunkedProof,
+    ) -> Result<(), String> {
+        // Column-major chunk table (table_name -> column_name -> values)
+        let mut table_columns = HashMap::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            let values: Vec<u64> = chunk.iter().map(|row| row[i]).collect();
+            table_columns.insert(column.clone(), values);
+        }
+        let mut table_data = HashMap::new();
+        table_data.insert(query.from.clone(), table_columns);
+
+        let compiled = SQLCompiler::compile(query, &table_data)?;
+        let database = CommittedDatabase::new(&table_data);
+        // Chunk partials are 64-bit (see `AggregationPartial::combine`)
+        if compiled.aggregations.iter().any(|agg| agg.is_wide()) {
+            return Err("SUM exceeds 64 bits in a chunk and cannot be combined".to_string());
+        }
+        let partials: Vec<AggregationPartial> = compiled
+            .aggregations
+            .iter()
+            .map(|agg| agg.partial())
+            .collect();
+
+        // Group keys are copied from the GROUP BY sort of the aggregation
+        // that has one; every partial must have its groups
+        let key_aggregation = compiled
+            .aggregations
+            .iter()
+            .position(|op| op.group_sort.is_some());
+        let grouped = partials
+            .iter()
+            .any(|partial| !partial.group_keys.is_empty());
+        let key_groups = key_aggregation.map(|index| &partials[index].group_keys);
+        if grouped
+            && (key_groups.is_none()
+                || partials.iter().any(|partial| {
+                    !partial.group_keys.is_empty() && Some(&partial.group_keys) != key_groups
+                }))
+        {
+            return Err("Chunk group keys are not the output of one GROUP BY sort".to_string());
+        }
+
+        // The partials are the query result, followed by the WHERE match
+        // count of a chunk without matching rows
+        let matched = compiled.match_count.as_ref().map(MatchCountOp::count);
+        let commitment = ChunkProof::commitment(&partials, matched, key_aggregation);
+
+        // JOIN is rejected above, so chunks use the smaller join-free config
+        let circuit = JoinFreeCircuit(PoneglyphCircuit::from_compiled(
+            compiled,
+            Some(commitment),
+            Some(database),
+        ));
+
+        result.rows += chunk.len();
+
+        let public_inputs = PublicInputs {
+            db_commitments: circuit.0.db_commitments.clone(),
+            query_result: circuit.0.bound_result(),
+        };
+
+        let layout =
+            key_layout(&circuit).map_err(|e| format!("Failed to lay out chunk: {:?}", e))?;
+        let key = match keys.iter().position(|(known, _)| *known == layout) {
+            Some(key) => key,
+            None => {
+                let prover = Prover::new(params, &circuit)
+                    .map_err(|e| format!("Failed to create chunk keys: {:?}", e))?;
+                result.verifiers.push(prover.verifier());
+                keys.push((layout, prover));
+                keys.len() - 1
+            }
+        };
+        let proof = keys[key]
+            .1
+            .prove(params, &circuit, &public_inputs.to_instances())
+            .map_err(|e| format!("Failed to prove chunk: {:?}", e))?;
+
+        if result.aggregations.is_empty() {
+            result.aggregations = partials.clone();
+        } else {
+            for (combined, partial) in result.aggregations.iter_mut().zip(&partials) {
+                combined.combine(partial)?;
+            }
+        }
+        result.chunks.push(ChunkProof {
+            proof,
+            public_inputs,
+            partials,
+            matched,
+            key,
+        });
+
+        Ok(())
+    }
+}
+
+/// Delta Proof
+/// Result of `DeltaProver::prove_update`
+#[derive(Clone, Debug)]
+pub struct DeltaProof {
+    pub proof: Vec<u8>,
+    /// Public inputs (`DeltaCircuit::instances`): roots and aggregates
+    /// before and after the update
+    pub instances: Vec<Vec<Fr>>,
+    /// Shape of the proven circuit (`without_witnesses`), for its keys
+    pub circuit: DeltaCircuit,
+    /// k the keys were generated at
+    pub k: u32,
+}
+
+impl DeltaProof {
+    /// SUM after the update
+    pub fn new_aggregate(&self) -> Fr {
+        self.instances[1][1]
+    }
+
+    /// Verify the proof with keys regenerated from the circuit shape
+    ///
+    /// # Note
+    ///
+    /// Only the step is checked: the caller must also check that the old
+    /// root and aggregate (`instances[0][0]`, `instances[1][0]`) are the
+    /// ones it trusts, e.g. from the previous proof
+    pub fn verify(&self) -> Result<bool, Error> {
+        ProvingContext::new(self.k, &self.circuit)?.verify(&self.proof, &self.instances)
+    }
+}
+
+/// Delta Proof Generation
+/// Re-proves a SUM after rows of a table change, from the previous
+/// aggregate instead of the whole table
+///
+/// The table is committed by a Merkle tree over its rows
+/// (`MerkleTree::from_rows`, the commitment of `DatabaseTable::merkle_tree`).
+/// Each update is proven by a `DeltaCircuit` over the changed rows only:
+/// the root moves from the old to the new commitment and
+/// `new = old - removed + added`. The prover keeps the rows and the tree
+/// to build the paths of later updates.
+#[derive(Clone, Debug)]
+pub struct DeltaProver {
+    rows: Vec<Vec<u64>>,
+    tree: MerkleTree,
+    /// Column summed by the aggregate
+    column: usize,
+    aggregate: u64,
+}
+
+impl DeltaProver {
+    /// Start from a table and the SUM of `column` proven over it (e.g. by
+    /// `prover::assert_aggregate`)
+    ///
+    /// # Returns
+    ///
+    /// The prover, or an error if a row lacks the column or `aggregate` is
+    /// not the SUM of the rows
+    pub fn new(rows: Vec<Vec<u64>>, column: usize, aggregate: u64) -> Result<Self, String> {
+        let sum = rows.iter().try_fold(0u64, |sum, row| {
+            let value = row.get(column).ok_or_else(|| {
+                format!("Row has {} values, column {} is missing", row.len(), column)
+            })?;
+            sum.checked_add(*value)
+                .ok_or_else(|| "SUM exceeds u64::MAX".to_string())
+        })?;
+        if sum != aggregate {
+            return Err(format!(
+                "Aggregate {} is not the SUM of the rows ({})",
+                aggregate, sum
+            ));
+        }
+
+        Ok(Self {
+            tree: MerkleTree::from_rows(&rows),
+            rows,
+            column,
+            aggregate,
+        })
+    }
+
+    /// Current commitment: the Merkle root of the rows
+    pub fn root(&self) -> Fr {
+        self.tree.root()
+    }
+
+    /// Current SUM of the column
+    pub fn aggregate(&self) -> u64 {
+        self.aggregate
+    }
+
+    /// Current rows
+    pub fn rows(&self) -> &[Vec<u64>] {
+        &self.rows
+    }
+
+    /// Replace rows and return the circuit proving the step
+    ///
+    /// # Parameters
+    ///
+    /// - `changes`: (row index, new row) of each changed row, applied in order
+    ///
+    /// # Returns
+    ///
+    /// The circuit (its public inputs are `DeltaCircuit::instances`), or an
+    /// error, leaving the prover unchanged, if a change is out of range, has
+    /// another row length, or the SUM leaves the u64 range
+    pub fn update(&mut self, changes: &[(usize, Vec<u64>)]) -> Result<DeltaCircuit, String> {
+        if changes.is_empty() {
+            return Err("An update needs at least one changed row".to_string());
+        }
+
+        let mut next = self.clone();
+        let mut updates = Vec::with_capacity(changes.len());
+        for (index, new_row) in changes {
+            let rows = next.rows.len();
+            let old_row = next
+                .rows
+                .get(*index)
+                .ok_or_else(|| format!("Row {} is out of range ({} rows)", index, rows))?;
+            if new_row.len() != old_row.len() {
+                return Err(format!(
+                    "Row {} has {} values, the new row {}",
+                    index,
+                    old_row.len(),
+                    new_row.len()
+                ));
+            }
+            let path = next
+                .tree
+                .update(*index, merkle::leaf_hash(new_row))
+                .ok_or_else(|| format!("Row {} has no leaf", index))?;
+            updates.push(RowUpdate {
+                old_row: std::mem::replace(&mut next.rows[*index], new_row.clone()),
+                new_row: new_row.clone(),
+                path,
+            });
+        }
+
+        let circuit = DeltaCircuit {
+            updates,
+            column: self.column,
+            old_aggregate: self.aggregate,
+        };
+        next.aggregate = circuit
+            .new_aggregate()
+            .ok_or_else(|| "Updated SUM leaves the u64 range".to_string())?;
+        *self = next;
+        Ok(circuit)
+    }
+
+    /// Replace rows and prove the step at the smallest k that fits it
+    /// (see `update`)
+    ///
+    /// # Returns
+    ///
+    /// The proof; the prover is unchanged if proving fails
+    pub fn prove_update(&mut self, changes: &[(usize, Vec<u64>)]) -> Result<DeltaProof, String> {
+        let mut next = self.clone();
+        let circuit = next.update(changes)?;
+        let instances = circuit
+            .instances()
+            .ok_or("Delta circuit has no public inputs")?;
+
+        let k = circuit
+            .stats()
+            .map_err(|e| format!("Failed to lay out the delta circuit: {:?}", e))?
+            .min_k;
+        let context = ProvingContext::new(k, &circuit)
+            .map_err(|e| format!("Failed to create keys: {:?}", e))?;
+        let proof = context
+            .prove(&circuit, &instances)
+            .map_err(|e| format!("Failed to prove: {:?}", e))?;
+
+        *self = next;
+        Ok(DeltaProof {
+            proof,
+            instances,
+            circuit: circuit.without_witnesses(),
+            k,
+        })
     }
 }
 
 // Nova module can remain optional (for large queries)
 // For now, we use Halo2 PLONKish recursive proof
-
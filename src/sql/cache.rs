@@ -0,0 +1,152 @@
+// Query cache
+// Memoized parse + compile for services that re-prove identical queries
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::{CompiledQuery, SQLCompiler, SQLParser};
+use crate::database::TableSource;
+
+/// Hash of a table map's schema: table and column names, not data
+///
+/// Independent of map iteration order. Every column holds `u64` values, so
+/// the names are the whole schema.
+pub fn schema_hash(table_data: &HashMap<String, HashMap<String, Vec<u64>>>) -> u64 {
+    let mut schema: Vec<(&String, Vec<&String>)> = table_data
+        .iter()
+        .map(|(table, columns)| {
+            let mut names: Vec<&String> = columns.keys().collect();
+            names.sort();
+            (table, names)
+        })
+        .collect();
+    schema.sort();
+
+    let mut hasher = DefaultHasher::new();
+    schema.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of a table map's data, in schema order
+fn data_hash(table_data: &HashMap<String, HashMap<String, Vec<u64>>>) -> u64 {
+    let mut tables: Vec<_> = table_data.iter().collect();
+    tables.sort_by_key(|(table, _)| *table);
+
+    let mut hasher = DefaultHasher::new();
+    for (_, columns) in tables {
+        let mut columns: Vec<_> = columns.iter().collect();
+        columns.sort_by_key(|(column, _)| *column);
+        for (_, values) in columns {
+            values.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Cache entry: a compiled query and the tables it was compiled against
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    schema_hash: u64,
+    data_hash: u64,
+    compiled: CompiledQuery,
+}
+
+/// Query Cache
+/// Parsed and compiled queries keyed by (SQL text, schema hash)
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// let mut cache = QueryCache::new();
+/// let compiled = cache.get_or_compile(sql, &table_data)?; // miss: parse + compile
+/// let compiled = cache.get_or_compile(sql, &table_data)?; // hit: cloned
+/// ```
+///
+/// # Note
+///
+/// - An entry compiled against another schema is invalidated and replaced
+/// - A `CompiledQuery` holds the table data as witnesses, so an entry is
+///   only returned for the data it was compiled from: the same SQL and
+///   schema over changed data is recompiled (counted as a miss)
+#[derive(Clone, Debug, Default)]
+pub struct QueryCache {
+    entries: HashMap<String, CacheEntry>,
+    hits: usize,
+    misses: usize,
+}
+
+impl QueryCache {
+    /// Empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compiled query for `sql` over `tables`, from the cache when present
+    ///
+    /// # Parameters
+    ///
+    /// - `sql`: Query text (the cache key, as written)
+    /// - `tables`: Table data (table_name -> column_name -> values), or a
+    ///   row-major `DatabaseTable` (see `TableSource`)
+    ///
+    /// # Returns
+    ///
+    /// A clone of the cached `CompiledQuery`, or the newly compiled one
+    /// (parse and compile errors are returned and not cached)
+    pub fn get_or_compile<T: TableSource + ?Sized>(
+        &mut self,
+        sql: &str,
+        tables: &T,
+    ) -> Result<CompiledQuery, String> {
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        let schema_hash = schema_hash(table_data);
+        let data_hash = data_hash(table_data);
+
+        if let Some(entry) = self.entries.get(sql) {
+            if entry.schema_hash == schema_hash && entry.data_hash == data_hash {
+                self.hits += 1;
+                return Ok(entry.compiled.clone());
+            }
+        }
+
+        self.misses += 1;
+        let query = SQLParser::parse(sql)?;
+        let compiled = SQLCompiler::compile(&query, table_data)?;
+        self.entries.insert(
+            sql.to_string(),
+            CacheEntry {
+                schema_hash,
+                data_hash,
+                compiled: compiled.clone(),
+            },
+        );
+        Ok(compiled)
+    }
+
+    /// Number of cached queries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no query is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Lookups that parsed and compiled
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Drop every cached query (hit and miss counts are kept)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,738 @@
+// Reference query executor
+// Plain Rust evaluation of a parsed query (no circuit operations): the
+// result a proof's claimed output is checked against
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{
+    AggregationClause, AggregationFunction, ComparisonOp, Expr, HavingClause, JoinClause, JoinType,
+    NullsOrder, OrderBy, OrderDirection, SQLCompiler, SQLParser, SQLQuery, WhereClause, NULL,
+};
+use crate::circuit::{PublicInputs, ResultCommitment, WindowFunction};
+use crate::database::TableSource;
+
+/// Query Result
+/// Rows computed by `SQLQuery::execute`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryResult {
+    /// Output columns: SELECT items as written (`*` expanded to the input
+    /// columns, a computed item by its output name)
+    pub columns: Vec<String>,
+    /// One value per output column; NULL cells are `NULL`
+    pub rows: Vec<Vec<u64>>,
+}
+
+impl QueryResult {
+    /// Values of an output column, in row order
+    pub fn column(&self, name: &str) -> Option<Vec<u64>> {
+        let index = self.columns.iter().position(|column| column == name)?;
+        Some(self.rows.iter().map(|row| row[index]).collect())
+    }
+}
+
+/// Limited Result
+/// Rows returned by a LIMIT query over the WHERE rows, with the number of
+/// rows that matched before the LIMIT (`prove_query_limited`)
+///
+/// # Note
+///
+/// The rows and `total_matched` are committed together as the query result
+/// public input (`commitment`, `ResultCommitment::limited`); `matches` checks
+/// a result against it. Both are proven (`CompiledQuery::result`): the match
+/// count is copied from the WHERE match count cell, and the rows from the
+/// committed cells of the first matching rows, in the order of a sort of
+/// the matching rows with ORDER BY.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitedResult {
+    /// Returned rows (at most LIMIT)
+    pub result: QueryResult,
+    /// Rows matching WHERE before the LIMIT
+    pub total_matched: u64,
+}
+
+impl LimitedResult {
+    /// Result whose rows are the first of `total_matched` matches
+    pub fn new(result: QueryResult, total_matched: u64) -> Self {
+        Self {
+            result,
+            total_matched,
+        }
+    }
+
+    /// Were fewer rows returned than matched? Both counts are committed and
+    /// proven for a result that `matches` its public inputs
+    pub fn truncated(&self) -> bool {
+        self.total_matched > self.result.rows.len() as u64
+    }
+
+    /// Commitment to the rows and the match count (the query result)
+    pub fn commitment(&self) -> ResultCommitment {
+        ResultCommitment::limited(self.result.rows.clone(), self.total_matched)
+    }
+
+    /// Are the rows and `total_matched` the result the public inputs bind?
+    pub fn matches(&self, public_inputs: &PublicInputs) -> bool {
+        public_inputs.query_result == Some(self.commitment().value())
+    }
+}
+
+impl SQLQuery {
+    /// Execute the query in plain Rust (reference result, no circuit)
+    ///
+    /// # Parameters
+    ///
+    /// - `tables`: Table data (table_name -> column_name -> values), or a
+    ///   row-major `DatabaseTable` (see `TableSource`)
+    ///
+    /// # Returns
+    ///
+    /// Result columns and rows
+    ///
+    /// # Semantics
+    ///
+    /// - JOINs match on equal non-NULL keys; LEFT / RIGHT / FULL pad the
+    ///   missing side with `NULL`
+    /// - A comparison with a `NULL` operand is false; EXISTS evaluates the
+    ///   subquery's WHERE for each of its rows with the outer row in scope
+    /// - GROUP BY yields one row per distinct key, in ascending key order;
+    ///   aggregations without GROUP BY yield a single row (also over no rows)
+    /// - A single MAX / MIN may select one more column, read from the last
+    ///   row of the group holding its result (arg-max, see
+    ///   `SQLQuery::arg_extremum`)
+    /// - Aggregations skip `NULL`; COUNT of no values is 0, the others are
+    ///   `NULL`, and a SUM above `u64::MAX` is an error. An argument
+    ///   expression (`SUM(price * quantity)`) is evaluated per row first;
+    ///   COUNT(DISTINCT x) counts each non-`NULL` value once
+    /// - Window functions evaluate over rows in their ORDER BY order (stable),
+    ///   the running sum row by row as proven by the Window Gate; with
+    ///   PARTITION BY, rows are ordered by (partition, ORDER BY) and the
+    ///   window restarts where the partition changes; without ORDER BY, rows
+    ///   are returned in the first window's order
+    /// - Computed SELECT columns (`a + b AS total`) evaluate per row, `NULL`
+    ///   if an operand is `NULL`; ORDER BY can refer to them by name
+    /// - UNION ALL concatenates both sides; UNION returns the distinct rows
+    ///   in ascending order
+    /// - LIMIT keeps the first rows after ORDER BY (of its own SELECT when
+    ///   the query is a UNION side)
+    pub fn execute<T: TableSource + ?Sized>(&self, tables: &T) -> Result<QueryResult, String> {
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        if let Some(union) = &self.union {
+            let mut left = self.clone();
+            left.union = None;
+            let left = left.execute(table_data)?;
+            let right = union.query.execute(table_data)?;
+            if left.columns.len() != right.columns.len() {
+                return Err(format!(
+                    "UNION queries project {} and {} columns",
+                    left.columns.len(),
+                    right.columns.len()
+                ));
+            }
+
+            let mut rows = left.rows;
+            rows.extend(right.rows);
+            if !union.all {
+                rows.sort();
+                rows.dedup();
+            }
+            return Ok(QueryResult {
+                columns: left.columns,
+                rows,
+            });
+        }
+
+        // FROM (with joins) and WHERE
+        let source = Rows::of_query(self, table_data)?;
+        let mut input = Rows {
+            schema: source.schema.clone(),
+            rows: Vec::new(),
+        };
+        for row in &source.rows {
+            let keep = match &self.where_clause {
+                Some(clause) => source.matches(clause, row, table_data)?,
+                None => true,
+            };
+            if keep {
+                input.rows.push(row.clone());
+            }
+        }
+
+        let items = self.select_items(&input);
+        let grouped = self.group_by.is_some() || self.aggregations.is_some();
+        if grouped && self.windows.is_some() {
+            return Err(
+                "Window functions cannot be combined with GROUP BY or aggregations".to_string(),
+            );
+        }
+
+        // Output units: the row indices of each group, or each row on its own
+        let mut units: Vec<Vec<usize>> = match &self.group_by {
+            Some(group_by) => {
+                let key_columns = group_by
+                    .iter()
+                    .map(|column| input.index(column))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut groups: BTreeMap<Vec<u64>, Vec<usize>> = BTreeMap::new();
+                for (i, row) in input.rows.iter().enumerate() {
+                    let key = key_columns.iter().map(|&column| row[column]).collect();
+                    groups.entry(key).or_default().push(i);
+                }
+                groups.into_values().collect()
+            }
+            None if grouped => vec![(0..input.rows.len()).collect()],
+            None => (0..input.rows.len()).map(|i| vec![i]).collect(),
+        };
+
+        // Window results per input row, keyed by SELECT item
+        let mut windows = HashMap::new();
+        let mut window_order = None;
+        for item in items.iter().filter(|item| item.contains(" over ")) {
+            let window = SQLParser::parse_window(item)?;
+            let partition_column = window
+                .partition_by
+                .as_deref()
+                .map(|column| input.index(column))
+                .transpose()?;
+            let order_column = input.index(&window.order_by)?;
+            let key = |i: usize| {
+                let partition = partition_column.map(|column| input.rows[i][column]);
+                (partition, input.rows[i][order_column])
+            };
+            let mut order: Vec<usize> = (0..input.rows.len()).collect();
+            order.sort_by_key(|&i| key(i));
+
+            let mut values = vec![0; input.rows.len()];
+            let mut running: u64 = 0;
+            let mut position = 0;
+            for (n, &i) in order.iter().enumerate() {
+                if n > 0 && key(i).0 != key(order[n - 1]).0 {
+                    running = 0;
+                    position = 0;
+                }
+                position += 1;
+                values[i] = match (&window.function, &window.column) {
+                    (WindowFunction::RunningSum, Some(column)) => {
+                        let value = input.rows[i][input.index(column)?];
+                        if value != NULL {
+                            running = running
+                                .checked_add(value)
+                                .ok_or("Running SUM exceeds u64::MAX")?;
+                        }
+                        running
+                    }
+                    _ => position,
+                };
+            }
+            windows.insert(item.as_str(), values);
+            window_order.get_or_insert((partition_column, order_column));
+        }
+        if let Some((partition_column, order_column)) = window_order {
+            units.sort_by_key(|unit| {
+                let row = &input.rows[unit[0]];
+                (
+                    partition_column.map(|column| row[column]),
+                    row[order_column],
+                )
+            });
+        }
+
+        let context = Context {
+            query: self,
+            input: &input,
+            windows: &windows,
+            grouped,
+        };
+
+        // HAVING
+        if let Some(having) = &self.having {
+            let mut kept = Vec::new();
+            for unit in units {
+                if context.having(having, &unit)? {
+                    kept.push(unit);
+                }
+            }
+            units = kept;
+        }
+
+        // ORDER BY (stable, keys compared left to right)
+        if let Some(order_by) = &self.order_by {
+            let mut keyed = units
+                .into_iter()
+                .map(|unit| {
+                    let keys = order_by
+                        .iter()
+                        .map(|order| context.order_key(order, &unit))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((keys, unit))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            keyed.sort_by(|(a, _), (b, _)| {
+                order_by
+                    .iter()
+                    .zip(a.iter().zip(b))
+                    .map(|(order, (&a, &b))| order_cmp(a, b, order))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            units = keyed.into_iter().map(|(_, unit)| unit).collect();
+        }
+
+        // LIMIT
+        if let Some(limit) = self.limit {
+            units.truncate(limit);
+        }
+
+        let rows = units
+            .iter()
+            .map(|unit| {
+                items
+                    .iter()
+                    .map(|item| context.value(item, unit))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(QueryResult {
+            columns: items,
+            rows,
+        })
+    }
+
+    /// SELECT items with `*` expanded to the input columns
+    /// (qualified as `table.column` when the query joins tables)
+    fn select_items(&self, input: &Rows) -> Vec<String> {
+        let qualified = self.joins.is_some();
+        let mut items = Vec::new();
+        for item in &self.columns {
+            if item == "*" {
+                items.extend(input.schema.iter().map(|(table, column)| {
+                    if qualified {
+                        format!("{}.{}", table, column)
+                    } else {
+                        column.clone()
+                    }
+                }));
+            } else {
+                items.push(item.clone());
+            }
+        }
+        items
+    }
+}
+
+/// Input rows of a query: values in `schema` order, (table, column) each
+struct Rows {
+    schema: Vec<(String, String)>,
+    rows: Vec<Vec<u64>>,
+}
+
+impl Rows {
+    /// Rows of one table (columns in name order)
+    fn of_table(
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        name: &str,
+    ) -> Result<Self, String> {
+        let table = table_data
+            .get(name)
+            .ok_or_else(|| format!("Table {} not found", name))?;
+        let mut columns: Vec<&String> = table.keys().collect();
+        columns.sort();
+
+        let row_count = columns.first().map_or(0, |column| table[*column].len());
+        if columns
+            .iter()
+            .any(|column| table[*column].len() != row_count)
+        {
+            return Err(format!("Columns of table {} have different lengths", name));
+        }
+
+        Ok(Self {
+            schema: columns
+                .iter()
+                .map(|column| (name.to_string(), column.to_string()))
+                .collect(),
+            rows: (0..row_count)
+                .map(|row| columns.iter().map(|column| table[*column][row]).collect())
+                .collect(),
+        })
+    }
+
+    /// Rows of the query's FROM table joined with its JOIN tables
+    fn of_query(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<Self, String> {
+        let mut rows = Self::of_table(table_data, &query.from)?;
+        for join in query.joins.iter().flatten() {
+            rows = rows.join(Self::of_table(table_data, &join.table)?, join)?;
+        }
+        Ok(rows)
+    }
+
+    /// Position of a column (`column` or `table.column`, first match)
+    fn index(&self, column: &str) -> Result<usize, String> {
+        let position = match column.split_once('.') {
+            Some((table, column)) => self
+                .schema
+                .iter()
+                .position(|(t, c)| t == table && c == column),
+            None => self.schema.iter().position(|(_, c)| c == column),
+        };
+        position.ok_or_else(|| format!("Column {} not found", column))
+    }
+
+    /// Join with another table's rows on the join condition
+    fn join(self, right: Rows, join: &JoinClause) -> Result<Rows, String> {
+        let keys = match join.join_type {
+            JoinType::Cross => None,
+            _ => Some((
+                self.index(&join.on.left_column)?,
+                right.index(&join.on.right_column)?,
+            )),
+        };
+        let matches = |left: &[u64], right: &[u64]| match keys {
+            Some((l, r)) => left[l] != NULL && left[l] == right[r],
+            None => true,
+        };
+        let keep_left = matches!(join.join_type, JoinType::Left | JoinType::Full);
+        let keep_right = matches!(join.join_type, JoinType::Right | JoinType::Full);
+
+        let mut rows = Vec::new();
+        let mut right_matched = vec![false; right.rows.len()];
+        for left in &self.rows {
+            let mut matched = false;
+            for (j, right_row) in right.rows.iter().enumerate() {
+                if matches(left, right_row) {
+                    matched = true;
+                    right_matched[j] = true;
+                    rows.push([left.as_slice(), right_row].concat());
+                }
+            }
+            if !matched && keep_left {
+                rows.push([left.as_slice(), &vec![NULL; right.schema.len()]].concat());
+            }
+        }
+        if keep_right {
+            for (right_row, _) in right.rows.iter().zip(right_matched).filter(|(_, m)| !m) {
+                rows.push([&vec![NULL; self.schema.len()], right_row.as_slice()].concat());
+            }
+        }
+
+        let mut schema = self.schema;
+        schema.extend(right.schema);
+        Ok(Rows { schema, rows })
+    }
+
+    /// Whether a row satisfies a WHERE clause
+    fn matches(
+        &self,
+        clause: &WhereClause,
+        row: &[u64],
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<bool, String> {
+        let value = |column: &str| self.index(column).map(|i| row[i]);
+        Ok(match clause {
+            WhereClause::LessThan { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && x < *v
+            }
+            WhereClause::GreaterThan { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && x > *v
+            }
+            WhereClause::LessEqual { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && x <= *v
+            }
+            WhereClause::GreaterEqual { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && x >= *v
+            }
+            WhereClause::Equal { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && x == *v
+            }
+            WhereClause::ColumnCompare {
+                left_column,
+                op,
+                right_column,
+            } => {
+                let (left, right) = (value(left_column)?, value(right_column)?);
+                left != NULL && right != NULL && compare(left, op, right)
+            }
+            WhereClause::ExprCompare {
+                expr,
+                operator,
+                value: v,
+            } => match self.eval_expr(expr, row)? {
+                Some(x) => compare(x, operator, *v),
+                None => false,
+            },
+            WhereClause::Exists { subquery, negated } => {
+                // Subquery rows first, so unqualified names resolve to them
+                let inner = Rows::of_query(subquery, table_data)?;
+                let mut scope = Rows {
+                    schema: inner.schema.clone(),
+                    rows: Vec::new(),
+                };
+                scope.schema.extend(self.schema.iter().cloned());
+
+                let mut exists = false;
+                for inner_row in &inner.rows {
+                    let combined = [inner_row.as_slice(), row].concat();
+                    let matched = match &subquery.where_clause {
+                        Some(clause) => scope.matches(clause, &combined, table_data)?,
+                        None => true,
+                    };
+                    if matched {
+                        exists = true;
+                        break;
+                    }
+                }
+                exists != *negated
+            }
+            WhereClause::In { column, set } => {
+                let x = value(column)?;
+                x != NULL && SQLCompiler::committed_set(set, table_data)?.contains(&x)
+            }
+            WhereClause::Bool { column, value: v } => {
+                let x = value(column)?;
+                x != NULL && (x != 0) == *v
+            }
+            WhereClause::IsNull { column } => value(column)? == NULL,
+            WhereClause::IsNotNull { column } => value(column)? != NULL,
+            WhereClause::And(left, right) => {
+                self.matches(left, row, table_data)? && self.matches(right, row, table_data)?
+            }
+            WhereClause::Or(left, right) => {
+                self.matches(left, row, table_data)? || self.matches(right, row, table_data)?
+            }
+        })
+    }
+
+    /// Evaluate an arithmetic expression for a row (None if an operand is NULL)
+    fn eval_expr(&self, expr: &Expr, row: &[u64]) -> Result<Option<u64>, String> {
+        let overflow = || "Arithmetic overflow in expression".to_string();
+        let operands = |left: &Expr, right: &Expr| -> Result<Option<(u64, u64)>, String> {
+            Ok(self.eval_expr(left, row)?.zip(self.eval_expr(right, row)?))
+        };
+        Ok(match expr {
+            Expr::Column(column) => Some(row[self.index(column)?]).filter(|&v| v != NULL),
+            Expr::Literal(value) => Some(*value),
+            Expr::Add(left, right) => match operands(left, right)? {
+                Some((a, b)) => Some(a.checked_add(b).ok_or_else(overflow)?),
+                None => None,
+            },
+            Expr::Sub(left, right) => match operands(left, right)? {
+                Some((a, b)) => Some(a.checked_sub(b).ok_or("Negative result in expression")?),
+                None => None,
+            },
+            Expr::Mul(left, right) => match operands(left, right)? {
+                Some((a, b)) => Some(a.checked_mul(b).ok_or_else(overflow)?),
+                None => None,
+            },
+            Expr::Div(left, right) | Expr::Mod(left, right) => match operands(left, right)? {
+                Some((a, b)) => {
+                    let quotient = a.checked_div(b).ok_or("Division by zero in expression")?;
+                    Some(match expr {
+                        Expr::Div(..) => quotient,
+                        _ => a % b,
+                    })
+                }
+                None => None,
+            },
+            // Both branches are evaluated, as in the circuit: a NULL operand
+            // anywhere gives NULL
+            Expr::Case {
+                condition,
+                operator,
+                value,
+                then,
+                otherwise,
+            } => {
+                let condition = self.eval_expr(condition, row)?;
+                match (condition, operands(then, otherwise)?) {
+                    (Some(condition), Some((then, otherwise))) => {
+                        Some(if compare(condition, operator, *value) {
+                            then
+                        } else {
+                            otherwise
+                        })
+                    }
+                    _ => None,
+                }
+            }
+        })
+    }
+}
+
+/// State for evaluating SELECT items, HAVING and ORDER BY keys of an
+/// output unit (a group, or a single row)
+struct Context<'a> {
+    query: &'a SQLQuery,
+    input: &'a Rows,
+    /// Window results per input row, keyed by SELECT item
+    windows: &'a HashMap<&'a str, Vec<u64>>,
+    /// Units are groups (GROUP BY or aggregations present)
+    grouped: bool,
+}
+
+impl Context<'_> {
+    /// Value of an item (column, computed column, aggregation or window
+    /// function) for a unit
+    fn value(&self, item: &str, unit: &[usize]) -> Result<u64, String> {
+        if let Some(values) = self.windows.get(item) {
+            return Ok(values[unit[0]]);
+        }
+
+        if let Some(expr) = self.query.projection_expr(item) {
+            if self.grouped {
+                return Err(format!(
+                    "SELECT expression {} needs a query without GROUP BY or aggregations",
+                    expr
+                ));
+            }
+            return Ok(self
+                .input
+                .eval_expr(&expr, &self.input.rows[unit[0]])?
+                .unwrap_or(NULL));
+        }
+
+        if let Some(aggregation) = SQLParser::parse_aggregation(item) {
+            if !self.grouped {
+                return Err(format!("Aggregation {} needs an aggregate query", item));
+            }
+            return self.aggregate(&aggregation, unit);
+        }
+
+        let column = self.input.index(item)?;
+        if self.grouped {
+            let group_by = self.query.group_by.iter().flatten();
+            let is_key = group_by
+                .map(|key| self.input.index(key))
+                .any(|key| key == Ok(column));
+            // Arg-max column: from the last row holding the group's MAX / MIN
+            if let Some((aggregation, _)) = self.query.arg_extremum().filter(|_| !is_key) {
+                let result = self.aggregate(aggregation, unit)?;
+                let values = self.input.index(&aggregation.column)?;
+                return Ok(unit
+                    .iter()
+                    .rev()
+                    .find(|&&row| result != NULL && self.input.rows[row][values] == result)
+                    .map_or(NULL, |&row| self.input.rows[row][column]));
+            }
+            if !is_key {
+                return Err(format!("Column {} must appear in GROUP BY", item));
+            }
+        }
+        Ok(self.input.rows[unit[0]][column])
+    }
+
+    /// Does a unit pass a HAVING clause? (a NULL result fails its comparison)
+    fn having(&self, having: &HavingClause, unit: &[usize]) -> Result<bool, String> {
+        Ok(match having {
+            HavingClause::Compare {
+                aggregation,
+                operator,
+                value,
+            } => {
+                let result = self.value(aggregation, unit)?;
+                result != NULL && compare(result, operator, *value)
+            }
+            HavingClause::And(left, right) => {
+                self.having(left, unit)? && self.having(right, unit)?
+            }
+            HavingClause::Or(left, right) => {
+                self.having(left, unit)? || self.having(right, unit)?
+            }
+        })
+    }
+
+    /// ORDER BY key of a unit: an item's value, or a computed expression
+    /// over the unit's row (NULL if an operand is NULL)
+    fn order_key(&self, order: &OrderBy, unit: &[usize]) -> Result<u64, String> {
+        match order.expr() {
+            Some(expr) if self.grouped => Err(format!(
+                "ORDER BY expression {} needs a query without GROUP BY or aggregations",
+                expr
+            )),
+            Some(expr) => Ok(self
+                .input
+                .eval_expr(&expr, &self.input.rows[unit[0]])?
+                .unwrap_or(NULL)),
+            None => self.value(&order.column, unit),
+        }
+    }
+
+    /// Aggregation over a unit's non-NULL values
+    fn aggregate(&self, aggregation: &AggregationClause, unit: &[usize]) -> Result<u64, String> {
+        // COUNT(*) counts rows
+        if aggregation.column == "*" && aggregation.function == AggregationFunction::Count {
+            return Ok(unit.len() as u64);
+        }
+
+        let values: Vec<u64> = match aggregation.expr() {
+            // SUM(price * quantity): the argument of each row (NULL if an
+            // operand is NULL)
+            Some(expr) => unit
+                .iter()
+                .map(|&row| self.input.eval_expr(&expr, &self.input.rows[row]))
+                .filter_map(Result::transpose)
+                .collect::<Result<_, _>>()?,
+            None => {
+                let column = self.input.index(&aggregation.column)?;
+                unit.iter()
+                    .map(|&row| self.input.rows[row][column])
+                    .filter(|&v| v != NULL)
+                    .collect()
+            }
+        };
+        let sum = || {
+            values
+                .iter()
+                .try_fold(0u64, |sum, &v| sum.checked_add(v))
+                .ok_or_else(|| format!("SUM of {} exceeds u64::MAX", aggregation.column))
+        };
+
+        Ok(match aggregation.function {
+            AggregationFunction::Count if aggregation.distinct => {
+                values.iter().collect::<HashSet<_>>().len() as u64
+            }
+            AggregationFunction::Count => values.len() as u64,
+            _ if values.is_empty() => NULL,
+            AggregationFunction::Sum => sum()?,
+            AggregationFunction::Avg => sum()? / values.len() as u64,
+            AggregationFunction::Max => values.iter().copied().max().unwrap_or(NULL),
+            AggregationFunction::Min => values.iter().copied().min().unwrap_or(NULL),
+        })
+    }
+}
+
+/// `left <op> right`
+fn compare(left: u64, op: &ComparisonOp, right: u64) -> bool {
+    match op {
+        ComparisonOp::LessThan => left < right,
+        ComparisonOp::GreaterThan => left > right,
+        ComparisonOp::LessEqual => left <= right,
+        ComparisonOp::GreaterEqual => left >= right,
+        ComparisonOp::Equal => left == right,
+    }
+}
+
+/// Ordering of two ORDER BY keys (direction and NULL placement)
+fn order_cmp(a: u64, b: u64, order: &OrderBy) -> Ordering {
+    let nulls_first = order.nulls == NullsOrder::First;
+    match (a == NULL, b == NULL) {
+        (true, true) => Ordering::Equal,
+        (true, false) if nulls_first => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, true) if nulls_first => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => match order.direction {
+            OrderDirection::Asc => a.cmp(&b),
+            OrderDirection::Desc => b.cmp(&a),
+        },
+    }
+}
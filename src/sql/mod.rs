@@ -1,14 +1,47 @@
 // SQL parser and compiler module
 // Paper Section 3: Compiling SQL queries to ZKP circuit
 
-use halo2_proofs::circuit::Value;
-use std::collections::HashMap;
+use halo2_proofs::circuit::{AssignedCell, Value};
+use pasta_curves::pallas::Base as Fr;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
 
-use crate::circuit::{AggregationOp, GroupByOp, JoinOp, RangeCheckOp, SortOp};
+use crate::circuit::{
+    AggregationOp, CommittedDatabase, Comparison, DivisionOp, GroupByOp, HavingOp, HavingPredicate,
+    JoinOp, MatchCountOp, Operand, ProductOp, RangeCheckOp, ResultCommitment, ResultSource,
+    SemiJoinOp, SetMembershipOp, SortOp, SortPayload, SumOp, TopKOp, WindowFunction, WindowOp,
+    WindowPartition,
+};
+use crate::database::TableSource;
+
+pub mod cache;
+pub mod execute;
+pub mod prepared;
+pub mod result;
+pub mod timestamp;
+pub mod tokenizer;
+
+pub use cache::{schema_hash, QueryCache};
+pub use execute::*;
+pub use prepared::PreparedQuery;
+pub use result::{ColumnType, TypedQueryResult, TypedValue};
+pub use timestamp::{format_timestamp, parse_timestamp};
+pub use tokenizer::{tokenize, Keyword, Operator, Token};
+
+use tokenizer::render;
+
+/// NULL marker in table data
+/// Table columns are `Vec<u64>`, so a NULL cell is stored as this reserved value
+pub const NULL: u64 = u64::MAX;
+
+/// Default per-column range-check budget of `SQLCompiler::compile`
+/// WHERE comparisons emit one range check per row; above this many for one
+/// column the circuit needs k > 18 and compilation is refused
+pub const DEFAULT_RANGE_CHECK_BUDGET: usize = 1 << 18;
 
 /// SQL Query AST (Abstract Syntax Tree)
 /// Paper Section 3: Used to compile SQL queries to circuit
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SQLQuery {
     pub columns: Vec<String>,
     pub from: String,
@@ -18,25 +51,755 @@ pub struct SQLQuery {
     pub having: Option<HavingClause>,
     pub joins: Option<Vec<JoinClause>>,
     pub aggregations: Option<Vec<AggregationClause>>,
+    pub windows: Option<Vec<WindowClause>>,
+    /// Computed SELECT items (`price * quantity AS total`); their output
+    /// names are listed in `columns`
+    pub projections: Option<Vec<Projection>>,
+    /// `UNION [ALL]` with the following SELECT
+    pub union: Option<UnionClause>,
+    /// `LIMIT n`: at most n rows, the first in ORDER BY order
+    pub limit: Option<usize>,
+}
+
+impl SQLQuery {
+    /// Validate that every referenced table and column exists (dry run)
+    ///
+    /// Checks the FROM table, WHERE / GROUP BY / HAVING / ORDER BY /
+    /// aggregation / window / projection columns and JOIN references without
+    /// building any circuit operations.
+    ///
+    /// # Parameters
+    ///
+    /// - `tables`: Table data (table_name -> column_name -> values), or a
+    ///   row-major `DatabaseTable` (see `TableSource`)
+    ///
+    /// # Returns
+    ///
+    /// The first unresolved reference as a `QueryError`, or
+    /// `QueryError::UngroupedColumn` (see `check_grouping`)
+    pub fn validate<T: TableSource + ?Sized>(&self, tables: &T) -> Result<(), QueryError> {
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        let from_table = Self::resolve_table(table_data, &self.from)?;
+
+        // WHERE columns
+        if let Some(where_clause) = &self.where_clause {
+            let mut columns = Vec::new();
+            where_clause.columns(&mut columns);
+            for column in columns {
+                Self::resolve_column(from_table, &self.from, column)?;
+            }
+        }
+
+        // GROUP BY columns
+        for column in self.group_by.iter().flatten() {
+            Self::resolve_column(from_table, &self.from, column)?;
+        }
+        self.check_grouping()?;
+
+        // ORDER BY columns (an aggregation result refers to its input column,
+        // an expression or a projection to every column it reads)
+        for order in self.order_by.iter().flatten() {
+            if let Some(expr) = order.expr().or_else(|| self.projection_expr(&order.column)) {
+                let mut columns = Vec::new();
+                expr.columns(&mut columns);
+                for column in columns {
+                    Self::resolve_column(from_table, &self.from, column)?;
+                }
+                continue;
+            }
+            let columns = match SQLParser::parse_aggregation(&order.column) {
+                Some(agg) => agg.input_columns(),
+                None => vec![order.column.clone()],
+            };
+            for column in columns {
+                Self::resolve_column(from_table, &self.from, &column)?;
+            }
+        }
+
+        // Window function columns
+        for window in self.windows.iter().flatten() {
+            if let Some(column) = &window.column {
+                Self::resolve_column(from_table, &self.from, column)?;
+            }
+            if let Some(partition_by) = &window.partition_by {
+                Self::resolve_column(from_table, &self.from, partition_by)?;
+            }
+            Self::resolve_column(from_table, &self.from, &window.order_by)?;
+        }
+
+        // HAVING aggregation columns
+        let mut comparisons = Vec::new();
+        if let Some(having) = &self.having {
+            having.comparisons(&mut comparisons);
+        }
+        for (aggregation, _, _) in comparisons {
+            if let Some(agg) = SQLParser::parse_aggregation(aggregation) {
+                for column in agg.input_columns() {
+                    Self::resolve_column(from_table, &self.from, &column)?;
+                }
+            }
+        }
+
+        // Aggregation columns (every column an argument expression reads)
+        for agg in self.aggregations.iter().flatten() {
+            for column in agg.input_columns() {
+                Self::resolve_column(from_table, &self.from, &column)?;
+            }
+        }
+
+        // Projection columns
+        for projection in self.projections.iter().flatten() {
+            let mut columns = Vec::new();
+            projection.expr.columns(&mut columns);
+            for column in columns {
+                Self::resolve_column(from_table, &self.from, column)?;
+            }
+        }
+
+        // UNION subquery
+        if let Some(union) = &self.union {
+            union.query.validate(table_data)?;
+        }
+
+        // JOIN references
+        for join in self.joins.iter().flatten() {
+            let join_table = Self::resolve_table(table_data, &join.table)?;
+            if join.join_type == JoinType::Cross {
+                continue;
+            }
+            Self::resolve_column(from_table, &self.from, &join.on.left_column)?;
+            Self::resolve_column(join_table, &join.table, &join.on.right_column)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that an aggregate query (GROUP BY or aggregations) selects only
+    /// GROUP BY columns besides its aggregations, or the arg-max column of
+    /// its single MAX / MIN (see `arg_extremum`)
+    ///
+    /// # Returns
+    ///
+    /// `QueryError::MultiColumnGroupBy` for more than one GROUP BY column
+    /// (groups are proven over a single key column), otherwise the first
+    /// other plain SELECT column (or `*`) as `QueryError::UngroupedColumn`;
+    /// `table.column` and `column` of the FROM table are the same column
+    pub fn check_grouping(&self) -> Result<(), QueryError> {
+        if self.group_by.is_none() && self.aggregations.is_none() {
+            return Ok(());
+        }
+        if let Some(columns @ [_, _, ..]) = self.group_by.as_deref() {
+            return Err(QueryError::MultiColumnGroupBy {
+                columns: columns.to_vec(),
+            });
+        }
+        match self.ungrouped_columns()[..] {
+            [] => Ok(()),
+            [_] if self.arg_extremum().is_some() => Ok(()),
+            [column, ..] => Err(QueryError::UngroupedColumn {
+                column: column.clone(),
+            }),
+        }
+    }
+
+    /// Arg-max of an aggregate query: its single MAX / MIN over a column
+    /// and the one other plain SELECT column, read from a row holding the
+    /// group's result (`SELECT customer_id, max(amount), id FROM order
+    /// GROUP BY customer_id`; among ties, the last such row)
+    pub fn arg_extremum(&self) -> Option<(&AggregationClause, &str)> {
+        let [aggregation] = self.aggregations.as_deref()? else {
+            return None;
+        };
+        let extremum = matches!(
+            aggregation.function,
+            AggregationFunction::Max | AggregationFunction::Min
+        );
+        if !extremum || aggregation.expr().is_some() {
+            return None;
+        }
+        match self.ungrouped_columns()[..] {
+            [column] if column != "*" => Some((aggregation, column)),
+            _ => None,
+        }
+    }
+
+    /// Plain SELECT columns (or `*`) of an aggregate query that are not
+    /// GROUP BY columns
+    fn ungrouped_columns(&self) -> Vec<&String> {
+        let prefix = format!("{}.", self.from);
+        let unqualified = |column: &str| column.strip_prefix(&prefix).unwrap_or(column).to_string();
+        let grouped: Vec<String> = self
+            .group_by
+            .iter()
+            .flatten()
+            .map(|column| unqualified(column))
+            .collect();
+
+        self.columns
+            .iter()
+            .filter(|item| {
+                // Aggregations are grouped; window functions and computed
+                // columns are rejected with aggregations by the compiler
+                SQLParser::parse_aggregation(item).is_none()
+                    && !item.contains(" over ")
+                    && self.projection_expr(item).is_none()
+                    && !grouped.contains(&unqualified(item))
+            })
+            .collect()
+    }
+
+    /// Expression of the computed SELECT item with this output name
+    pub fn projection_expr(&self, name: &str) -> Option<Expr> {
+        self.projections
+            .iter()
+            .flatten()
+            .find(|projection| projection.name == name)
+            .map(|projection| projection.expr.clone())
+    }
+
+    fn resolve_table<'a>(
+        table_data: &'a HashMap<String, HashMap<String, Vec<u64>>>,
+        table: &str,
+    ) -> Result<&'a HashMap<String, Vec<u64>>, QueryError> {
+        table_data
+            .get(table)
+            .ok_or_else(|| QueryError::UnknownTable {
+                table: table.to_string(),
+            })
+    }
+
+    fn resolve_column(
+        columns: &HashMap<String, Vec<u64>>,
+        table: &str,
+        column: &str,
+    ) -> Result<(), QueryError> {
+        if columns.contains_key(column) {
+            Ok(())
+        } else {
+            Err(QueryError::UnknownColumn {
+                table: table.to_string(),
+                column: column.to_string(),
+            })
+        }
+    }
+}
+
+/// Render the query as SQL accepted by `SQLParser::parse`
+///
+/// # Note
+///
+/// - Parsing the output yields an equal query for the shapes the parser
+///   produces; WHERE has no parentheses, so AND / OR nest as the parser
+///   splits them (first AND, then first OR)
+/// - Inner joins are rendered as comma-style FROM with their ON condition
+///   as a WHERE predicate; LEFT / RIGHT / FULL joins have no parser syntax
+///   and are rendered as `LEFT JOIN t ON ...` for display only
+impl std::fmt::Display for SQLQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| match self.projection_expr(column) {
+                Some(expr) if expr.to_string() != *column => format!("{} AS {}", expr, column),
+                _ => column.clone(),
+            })
+            .collect();
+        write!(f, "SELECT {} FROM {}", columns.join(", "), self.from)?;
+
+        let mut predicates = Vec::new();
+        for join in self.joins.iter().flatten() {
+            let on = format!(
+                "{}.{} = {}.{}",
+                self.from, join.on.left_column, join.table, join.on.right_column
+            );
+            match join.join_type {
+                JoinType::Cross => write!(f, ", {}", join.table)?,
+                JoinType::Inner => {
+                    write!(f, ", {}", join.table)?;
+                    predicates.push(on);
+                }
+                JoinType::Left => write!(f, " LEFT JOIN {} ON {}", join.table, on)?,
+                JoinType::Right => write!(f, " RIGHT JOIN {} ON {}", join.table, on)?,
+                JoinType::Full => write!(f, " FULL JOIN {} ON {}", join.table, on)?,
+            }
+        }
+        if let Some(where_clause) = &self.where_clause {
+            predicates.push(where_clause.to_string());
+        }
+        if !predicates.is_empty() {
+            write!(f, " WHERE {}", predicates.join(" AND "))?;
+        }
+
+        if let Some(group_by) = &self.group_by {
+            write!(f, " GROUP BY {}", group_by.join(", "))?;
+        }
+        if let Some(having) = &self.having {
+            write!(f, " HAVING {}", having)?;
+        }
+        if let Some(order_by) = &self.order_by {
+            let orders: Vec<String> = order_by
+                .iter()
+                .map(|order| {
+                    let direction = match order.direction {
+                        OrderDirection::Asc => "ASC",
+                        OrderDirection::Desc => "DESC",
+                    };
+                    let nulls = match order.nulls {
+                        NullsOrder::First => "FIRST",
+                        NullsOrder::Last => "LAST",
+                    };
+                    format!("{} {} NULLS {}", order.column, direction, nulls)
+                })
+                .collect();
+            write!(f, " ORDER BY {}", orders.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+
+        if let Some(union) = &self.union {
+            let all = if union.all { " ALL" } else { "" };
+            write!(f, " UNION{} {}", all, union.query)?;
+        }
+        Ok(())
+    }
+}
+
+/// Query error
+/// Returned by `SQLQuery::validate` for references that do not resolve, and
+/// by table ingestion (`DatabaseTable::from_record_batch`, `from_parquet`)
+/// for data that cannot be read or stored as `u64` columns
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    /// Table is not present in the table data
+    UnknownTable { table: String },
+    /// Column is not present in the table
+    UnknownColumn { table: String, column: String },
+    /// Column type cannot be stored as `u64` table data
+    UnsupportedColumnType {
+        table: String,
+        column: String,
+        data_type: String,
+    },
+    /// Value cannot be stored as `u64` table data (negative, or the NULL marker)
+    InvalidValue {
+        table: String,
+        column: String,
+        row: usize,
+    },
+    /// Table file could not be read
+    Read { path: String, message: String },
+    /// No row has the key (point query)
+    KeyNotFound {
+        table: String,
+        column: String,
+        key: u64,
+    },
+    /// A row has the key (non-membership proof)
+    KeyFound {
+        table: String,
+        column: String,
+        key: u64,
+    },
+    /// WHERE comparisons on a column emit more range checks than the budget
+    RangeCheckBudgetExceeded {
+        table: String,
+        column: String,
+        count: usize,
+        budget: usize,
+    },
+    /// SELECT column of an aggregate query that is neither in GROUP BY nor
+    /// aggregated (`SQLQuery::check_grouping`)
+    UngroupedColumn { column: String },
+    /// GROUP BY over more than one column (`SQLQuery::check_grouping`)
+    MultiColumnGroupBy { columns: Vec<String> },
+    /// Public inputs have a different number of instance columns than the
+    /// circuit declares (`Prover::check_instances`, `Verifier::check_instances`)
+    InstanceShape { expected: usize, found: usize },
+    /// Row does not have one value per column (`DatabaseTable::insert`,
+    /// `DatabaseTable::try_insert_many`)
+    RowLength {
+        table: String,
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::UnknownTable { table } => write!(f, "Table {} not found", table),
+            QueryError::UnknownColumn { table, column } => {
+                write!(f, "Column {} not found in table {}", column, table)
+            }
+            QueryError::UnsupportedColumnType {
+                table,
+                column,
+                data_type,
+            } => write!(
+                f,
+                "Column {} in table {} has unsupported type {}",
+                column, table, data_type
+            ),
+            QueryError::InvalidValue { table, column, row } => write!(
+                f,
+                "Value in row {} of column {} in table {} is not a u64",
+                row, column, table
+            ),
+            QueryError::Read { path, message } => write!(f, "Cannot read {}: {}", path, message),
+            QueryError::KeyNotFound { table, column, key } => {
+                write!(f, "No row with {} = {} in table {}", column, key, table)
+            }
+            QueryError::KeyFound { table, column, key } => {
+                write!(
+                    f,
+                    "A row with {} = {} exists in table {}",
+                    column, key, table
+                )
+            }
+            QueryError::RangeCheckBudgetExceeded {
+                table,
+                column,
+                count,
+                budget,
+            } => write!(
+                f,
+                "WHERE on column {} in table {} needs {} range checks (budget {})",
+                column, table, count, budget
+            ),
+            QueryError::UngroupedColumn { column } => write!(
+                f,
+                "Column {} must appear in GROUP BY or be aggregated",
+                column
+            ),
+            QueryError::MultiColumnGroupBy { columns } => write!(
+                f,
+                "GROUP BY over more than one column is not supported: {}",
+                columns.join(", ")
+            ),
+            QueryError::InstanceShape { expected, found } => write!(
+                f,
+                "Public inputs have {} instance columns, the circuit declares {}",
+                found, expected
+            ),
+            QueryError::RowLength {
+                table,
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} of table {} has {} values, expected {}",
+                row, table, found, expected
+            ),
+        }
+    }
 }
 
+impl std::error::Error for QueryError {}
+
 /// WHERE clause
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum WhereClause {
     /// Range check: column < value
     LessThan { column: String, value: u64 },
     /// Range check: column > value
     GreaterThan { column: String, value: u64 },
+    /// Range check: column <= value
+    LessEqual { column: String, value: u64 },
+    /// Range check: column >= value
+    GreaterEqual { column: String, value: u64 },
     /// Range check: column = value
     Equal { column: String, value: u64 },
+    /// Range check between two columns of the same row: left_column < right_column, ...
+    ColumnCompare {
+        left_column: String,
+        op: ComparisonOp,
+        right_column: String,
+    },
+    /// Range check on an arithmetic expression: expr < value, expr > value, expr = value
+    ExprCompare {
+        expr: Expr,
+        operator: ComparisonOp,
+        value: u64,
+    },
+    /// Correlated subquery filter: EXISTS (...) or NOT EXISTS (...) when `negated`
+    /// The subquery's WHERE must be a single equality `inner.x = outer.y`
+    Exists {
+        subquery: Box<SQLQuery>,
+        negated: bool,
+    },
+    /// Set membership: column IN (set), where `set` names a single-column
+    /// table holding the committed set (proven by the Set Membership Gate)
+    In { column: String, set: String },
+    /// Boolean column predicate: `column = true` (or just `column`) and
+    /// `column = false` (or `NOT column`); the column must hold 0 or 1
+    Bool { column: String, value: bool },
+    /// NULL test: `column IS NULL`, the row's is_null bit (an `= NULL` check)
+    IsNull { column: String },
+    /// NULL test: `column IS NOT NULL`, the row's `< NULL` check
+    IsNotNull { column: String },
     /// AND operation
     And(Box<WhereClause>, Box<WhereClause>),
     /// OR operation
     Or(Box<WhereClause>, Box<WhereClause>),
 }
 
+impl WhereClause {
+    /// Collect the columns referenced by this clause
+    pub fn columns<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            WhereClause::LessThan { column, .. }
+            | WhereClause::GreaterThan { column, .. }
+            | WhereClause::LessEqual { column, .. }
+            | WhereClause::GreaterEqual { column, .. }
+            | WhereClause::Equal { column, .. }
+            | WhereClause::In { column, .. }
+            | WhereClause::Bool { column, .. }
+            | WhereClause::IsNull { column }
+            | WhereClause::IsNotNull { column } => out.push(column),
+            WhereClause::ColumnCompare {
+                left_column,
+                right_column,
+                ..
+            } => {
+                out.push(left_column);
+                out.push(right_column);
+            }
+            WhereClause::ExprCompare { expr, .. } => expr.columns(out),
+            // Qualified correlation columns are resolved when compiled
+            WhereClause::Exists { .. } => {}
+            WhereClause::And(left, right) | WhereClause::Or(left, right) => {
+                left.columns(out);
+                right.columns(out);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for WhereClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WhereClause::LessThan { column, value } => write!(f, "{} < {}", column, value),
+            WhereClause::GreaterThan { column, value } => write!(f, "{} > {}", column, value),
+            WhereClause::LessEqual { column, value } => write!(f, "{} <= {}", column, value),
+            WhereClause::GreaterEqual { column, value } => write!(f, "{} >= {}", column, value),
+            WhereClause::Equal { column, value } => write!(f, "{} = {}", column, value),
+            WhereClause::ColumnCompare {
+                left_column,
+                op,
+                right_column,
+            } => write!(f, "{} {} {}", left_column, op, right_column),
+            WhereClause::ExprCompare {
+                expr,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", expr, operator, value),
+            WhereClause::Exists { subquery, negated } => {
+                let not = if *negated { "NOT " } else { "" };
+                write!(f, "{}EXISTS ({})", not, subquery)
+            }
+            WhereClause::In { column, set } => write!(f, "{} IN ({})", column, set),
+            WhereClause::Bool { column, value } => write!(f, "{} = {}", column, value),
+            WhereClause::IsNull { column } => write!(f, "{} IS NULL", column),
+            WhereClause::IsNotNull { column } => write!(f, "{} IS NOT NULL", column),
+            WhereClause::And(left, right) => write!(f, "{} AND {}", left, right),
+            WhereClause::Or(left, right) => write!(f, "{} OR {}", left, right),
+        }
+    }
+}
+
+/// Arithmetic expression (left side of WHERE comparisons, ORDER BY keys)
+/// Column references, integer literals, `+`, `-`, `*` and integer `/`, `%`,
+/// and `CASE WHEN` with a single branch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Column(String),
+    Literal(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    /// Integer division (rounded down)
+    Div(Box<Expr>, Box<Expr>),
+    /// Integer remainder
+    Mod(Box<Expr>, Box<Expr>),
+    /// `CASE WHEN condition operator value THEN then ELSE otherwise END`
+    /// Proven as `check * then + (1 - check) * otherwise`, where `check` is
+    /// the bit of a range check on the condition
+    Case {
+        condition: Box<Expr>,
+        operator: ComparisonOp,
+        value: u64,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+}
+
+impl Expr {
+    /// Parse expression
+    /// `+` / `-` bind looser than `*`, `/` and `%`; all operators are left-associative
+    pub fn parse(expr: &str) -> Result<Expr, String> {
+        Self::from_tokens(&tokenize(expr)?)
+    }
+
+    /// Expression from its tokens
+    fn from_tokens(tokens: &[Token]) -> Result<Expr, String> {
+        if tokens.is_empty() {
+            return Err("Empty expression".to_string());
+        }
+
+        // Lowest precedence first: split at the last + or - (left-associative)
+        // A CASE ... END is one operand: operators inside it do not split
+        let additive = |t: &Token| matches!(t, Token::Operator(Operator::Plus | Operator::Minus));
+        if let Some(idx) = Self::rposition_outside_case(tokens, additive) {
+            let left = Box::new(Self::from_tokens(&tokens[..idx])?);
+            let right = Box::new(Self::from_tokens(&tokens[idx + 1..])?);
+            return Ok(if tokens[idx] == Token::Operator(Operator::Plus) {
+                Expr::Add(left, right)
+            } else {
+                Expr::Sub(left, right)
+            });
+        }
+
+        let multiplicative = |t: &Token| {
+            matches!(
+                t,
+                Token::Operator(Operator::Star | Operator::Slash | Operator::Percent)
+            )
+        };
+        if let Some(idx) = Self::rposition_outside_case(tokens, multiplicative) {
+            let left = Box::new(Self::from_tokens(&tokens[..idx])?);
+            let right = Box::new(Self::from_tokens(&tokens[idx + 1..])?);
+            return Ok(match tokens[idx] {
+                Token::Operator(Operator::Star) => Expr::Mul(left, right),
+                Token::Operator(Operator::Slash) => Expr::Div(left, right),
+                _ => Expr::Mod(left, right),
+            });
+        }
+
+        match tokens {
+            [Token::Number(value)] => Ok(Expr::Literal(*value)),
+            [token] if token.name().is_some() => Ok(Expr::Column(render(tokens))),
+            [Token::Keyword(Keyword::Case), inner @ .., Token::Keyword(Keyword::End)] => {
+                Self::case_from_tokens(inner)
+            }
+            _ => Err(format!("Unsupported expression: {}", render(tokens))),
+        }
+    }
+
+    /// `CASE WHEN` expression from the tokens between CASE and END
+    /// (`WHEN condition THEN then ELSE otherwise`)
+    fn case_from_tokens(tokens: &[Token]) -> Result<Expr, String> {
+        let unsupported = || {
+            format!(
+                "CASE must be written as CASE WHEN expr < | > | = value THEN expr ELSE expr END: \
+                 case {} end",
+                render(tokens)
+            )
+        };
+        let then_idx = Self::rposition_outside_case(tokens, |t| t.is_keyword(Keyword::Then));
+        let else_idx = Self::rposition_outside_case(tokens, |t| t.is_keyword(Keyword::Else));
+        let (Some(then_idx), Some(else_idx)) = (then_idx, else_idx) else {
+            return Err(unsupported());
+        };
+        if !tokens[0].is_keyword(Keyword::When) || then_idx > else_idx {
+            return Err(unsupported());
+        }
+
+        let condition = &tokens[1..then_idx];
+        let (op_idx, operator) = SQLParser::comparison(condition).ok_or_else(unsupported)?;
+        let value = match &condition[op_idx + 1..] {
+            [Token::Number(value)] => *value,
+            _ => return Err(unsupported()),
+        };
+        Ok(Expr::Case {
+            condition: Box::new(Self::from_tokens(&condition[..op_idx])?),
+            operator,
+            value,
+            then: Box::new(Self::from_tokens(&tokens[then_idx + 1..else_idx])?),
+            otherwise: Box::new(Self::from_tokens(&tokens[else_idx + 1..])?),
+        })
+    }
+
+    /// Position of the last token matching `pred` outside any CASE ... END
+    fn rposition_outside_case(tokens: &[Token], pred: impl Fn(&Token) -> bool) -> Option<usize> {
+        let mut depth = 0i32;
+        let mut found = None;
+        for (idx, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Keyword(Keyword::Case) => depth += 1,
+                Token::Keyword(Keyword::End) => depth -= 1,
+                _ if depth == 0 && pred(token) => found = Some(idx),
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Collect the columns referenced by this expression
+    pub fn columns<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(column) => out.push(column),
+            Expr::Literal(_) => {}
+            Expr::Add(left, right)
+            | Expr::Sub(left, right)
+            | Expr::Mul(left, right)
+            | Expr::Div(left, right)
+            | Expr::Mod(left, right) => {
+                left.columns(out);
+                right.columns(out);
+            }
+            Expr::Case {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => {
+                condition.columns(out);
+                then.columns(out);
+                otherwise.columns(out);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Column(column) => write!(f, "{}", column),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Add(left, right) => write!(f, "{} + {}", left, right),
+            Expr::Sub(left, right) => write!(f, "{} - {}", left, right),
+            Expr::Mul(left, right) => write!(f, "{} * {}", left, right),
+            Expr::Div(left, right) => write!(f, "{} / {}", left, right),
+            Expr::Mod(left, right) => write!(f, "{} % {}", left, right),
+            Expr::Case {
+                condition,
+                operator,
+                value,
+                then,
+                otherwise,
+            } => write!(
+                f,
+                "CASE WHEN {} {} {} THEN {} ELSE {} END",
+                condition, operator, value, then, otherwise
+            ),
+        }
+    }
+}
+
+/// UNION clause: `query UNION [ALL] query`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnionClause {
+    pub query: Box<SQLQuery>,
+    /// UNION ALL (keep duplicates) or UNION (deduplicate)
+    pub all: bool,
+}
+
 /// JOIN clause
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct JoinClause {
     pub table: String,
     pub on: JoinCondition,
@@ -44,37 +807,66 @@ pub struct JoinClause {
 }
 
 /// JOIN condition
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct JoinCondition {
     pub left_column: String,
     pub right_column: String,
 }
 
 /// JOIN type
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum JoinType {
     Inner,
     Left,
     Right,
     Full,
+    /// Comma-style FROM (`FROM a, b`) without an equality predicate in WHERE
+    Cross,
 }
 
 /// ORDER BY clause
-#[derive(Clone, Debug)]
+/// `column` is a column, an aggregation result or an arithmetic expression
+/// (e.g. `price * quantity`) as written
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OrderBy {
     pub column: String,
     pub direction: OrderDirection,
+    pub nulls: NullsOrder,
+}
+
+impl OrderBy {
+    /// Computed sort key (`ORDER BY price * quantity`), or None for a plain
+    /// column or an aggregation result
+    pub fn expr(&self) -> Option<Expr> {
+        if SQLParser::parse_aggregation(&self.column).is_some() {
+            return None;
+        }
+        match Expr::parse(&self.column) {
+            Ok(Expr::Column(_) | Expr::Literal(_)) | Err(_) => None,
+            Ok(expr) => Some(expr),
+        }
+    }
 }
 
 /// ORDER direction
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OrderDirection {
     Asc,
     Desc,
 }
 
+/// NULL placement in ORDER BY
+/// SQL standard default: NULLS LAST for ASC, NULLS FIRST for DESC
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
 /// HAVING clause
-#[derive(Clone, Debug)]
+/// Comparisons of aggregation results, combined with AND / OR like a
+/// `WhereClause`
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum HavingClause {
     /// Aggregation result comparison
     Compare {
@@ -82,31 +874,130 @@ pub enum HavingClause {
         operator: ComparisonOp,
         value: u64,
     },
+    /// AND operation
+    And(Box<HavingClause>, Box<HavingClause>),
+    /// OR operation
+    Or(Box<HavingClause>, Box<HavingClause>),
+}
+
+impl HavingClause {
+    /// Collect the aggregation comparisons of this clause, left to right
+    pub fn comparisons<'a>(&'a self, out: &mut Vec<(&'a str, &'a ComparisonOp, u64)>) {
+        match self {
+            HavingClause::Compare {
+                aggregation,
+                operator,
+                value,
+            } => out.push((aggregation, operator, *value)),
+            HavingClause::And(left, right) | HavingClause::Or(left, right) => {
+                left.comparisons(out);
+                right.comparisons(out);
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HavingClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HavingClause::Compare {
+                aggregation,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", aggregation, operator, value),
+            HavingClause::And(left, right) => write!(f, "{} AND {}", left, right),
+            HavingClause::Or(left, right) => write!(f, "{} OR {}", left, right),
+        }
+    }
 }
 
 /// Comparison operator
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ComparisonOp {
     LessThan,
     GreaterThan,
+    /// `<=`
+    LessEqual,
+    /// `>=`
+    GreaterEqual,
     Equal,
 }
 
+impl std::fmt::Display for ComparisonOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ComparisonOp::LessThan => "<",
+            ComparisonOp::GreaterThan => ">",
+            ComparisonOp::LessEqual => "<=",
+            ComparisonOp::GreaterEqual => ">=",
+            ComparisonOp::Equal => "=",
+        })
+    }
+}
+
 /// Aggregation clause
-#[derive(Clone, Debug)]
+/// `column` is a column, `*` or an arithmetic expression (e.g.
+/// `price * quantity`) as written
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AggregationClause {
     pub function: AggregationFunction,
     pub column: String,
+    /// `count(distinct column)`: count each value once per group
+    pub distinct: bool,
 }
 
-/// Aggregation function
-#[derive(Clone, Debug)]
-pub enum AggregationFunction {
-    Sum,
-    Count,
-    Max,
-    Min,
-    Avg,
+impl std::fmt::Display for AggregationClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let distinct = if self.distinct { "distinct " } else { "" };
+        write!(f, "{}({}{})", self.function, distinct, self.column)
+    }
+}
+
+impl AggregationClause {
+    /// Computed argument (`SUM(price * quantity)`), or None for a plain
+    /// column or `*`
+    pub fn expr(&self) -> Option<Expr> {
+        match Expr::parse(&self.column) {
+            Ok(Expr::Column(_) | Expr::Literal(_)) | Err(_) => None,
+            Ok(expr) => Some(expr),
+        }
+    }
+
+    /// Columns the argument reads
+    fn input_columns(&self) -> Vec<String> {
+        match self.expr() {
+            Some(expr) => {
+                let mut columns = Vec::new();
+                expr.columns(&mut columns);
+                columns.into_iter().map(str::to_string).collect()
+            }
+            None => vec![self.column.clone()],
+        }
+    }
+}
+
+/// Aggregation function (defined with the Aggregation Gate, which proves it)
+pub use crate::circuit::AggregationFunction;
+
+/// Window function clause: `SUM(col) OVER (ORDER BY order_by)`, `ROW_NUMBER() OVER (ORDER BY order_by)`,
+/// optionally `OVER (PARTITION BY partition_by ORDER BY order_by)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowClause {
+    pub function: WindowFunction,
+    /// Argument column (None for ROW_NUMBER)
+    pub column: Option<String>,
+    /// Partition column: the window restarts where it changes (None for one window)
+    pub partition_by: Option<String>,
+    pub order_by: String,
+}
+
+/// Computed SELECT item: `expr [AS name]`
+/// Evaluated per row like a WHERE expression, but returned as a result column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Projection {
+    pub expr: Expr,
+    /// Output column name: the alias, or the expression as written
+    pub name: String,
 }
 
 /// SQL Parser
@@ -116,11 +1007,41 @@ pub struct SQLParser;
 impl SQLParser {
     /// Parse SQL string
     /// Simple parser - production can use more advanced parser (e.g.: sqlparser-rs)
+    ///
+    /// The query is split into tokens (`tokenize`) and clauses are found by
+    /// keyword token outside parentheses, so keywords inside names, string
+    /// literals or subqueries do not split it.
     pub fn parse(sql: &str) -> Result<SQLQuery, String> {
-        let sql = sql.trim().to_lowercase();
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("parse", len = sql.len()));
+        Self::parse_tokens(&tokenize(sql)?)
+    }
 
+    /// Parse a query from its tokens (also used for EXISTS subqueries)
+    /// Parameters are only accepted by `PreparedQuery`
+    fn parse_tokens(tokens: &[Token]) -> Result<SQLQuery, String> {
+        let mut value_params = Vec::new();
+        let query = Self::parse_prepared(tokens, &mut value_params)?;
+        match value_params.into_iter().flatten().next() {
+            Some(name) => Err(format!("Unbound parameter :{}", name)),
+            None => Ok(query),
+        }
+    }
+
+    /// Parse a query whose WHERE values may be `:name` parameters
+    ///
+    /// Pushes one entry per WHERE comparison value (of this query, then of its
+    /// UNION query; left before right in AND / OR): the parameter name, or
+    /// None for a literal. Parameters parse as 0 until bound.
+    fn parse_prepared(
+        tokens: &[Token],
+        value_params: &mut Vec<Option<String>>,
+    ) -> Result<SQLQuery, String> {
         // Simple SELECT parsing
-        if !sql.starts_with("select") {
+        if !tokens
+            .first()
+            .is_some_and(|t| t.is_keyword(Keyword::Select))
+        {
             return Err("Only SELECT queries are supported".to_string());
         }
 
@@ -134,352 +1055,3015 @@ impl SQLParser {
             having: None,
             joins: None,
             aggregations: None,
+            windows: None,
+            projections: None,
+            union: None,
+            limit: None,
         };
 
-        // Find FROM clause
-        let from_idx = sql.find(" from ").ok_or("Missing FROM clause")?;
-        let select_part = &sql[6..from_idx].trim();
+        // UNION [ALL]: the right side is parsed as its own query
+        // (a chain a UNION b UNION c nests to the right)
+        if let Some(union_idx) = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::Union)) {
+            let right = &tokens[union_idx + 1..];
+            let (all, right) = match right.split_first() {
+                Some((first, rest)) if first.is_keyword(Keyword::All) => (true, rest),
+                _ => (false, right),
+            };
+            let mut query = Self::parse_prepared(&tokens[..union_idx], value_params)?;
+            query.union = Some(UnionClause {
+                query: Box::new(Self::parse_prepared(right, value_params)?),
+                all,
+            });
+            return Ok(query);
+        }
+
+        // Split into clauses: SELECT list, then FROM, WHERE, GROUP BY, HAVING,
+        // ORDER BY and LIMIT, each at most once and in this order
+        let clauses = Self::split_clauses(tokens)?;
+        let (select_part, from_part) = match (clauses.select, clauses.from) {
+            (select, Some(from)) => (select, from),
+            (_, None) => return Err("Missing FROM clause".to_string()),
+        };
 
         // Parse columns
-        query.columns = select_part
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+        query.columns = Self::split_top_level(select_part)
+            .into_iter()
+            .map(|item| Self::item(item, "SELECT"))
+            .collect::<Result<_, _>>()?;
 
-        // Parse after FROM
-        let after_from = &sql[from_idx + 6..];
+        // FROM table(s)
+        let tables: Vec<String> = Self::split_top_level(from_part)
+            .into_iter()
+            .map(|table| Self::item(table, "FROM"))
+            .collect::<Result<_, _>>()?;
+        query.from = tables[0].clone();
 
-        // Find WHERE clause
-        if let Some(where_idx) = after_from.find(" where ") {
-            query.from = after_from[..where_idx].trim().to_string();
-            let where_part = &after_from[where_idx + 7..];
+        // Parse WHERE clause (column < value, column > value, column = value, ...)
+        if let Some(where_part) = clauses.where_part {
+            query.where_clause = Some(Self::parse_where_clause(where_part, value_params)?);
+        }
 
-            // Parse WHERE clause (simple: column < value, column > value, column = value)
-            query.where_clause = Some(Self::parse_where_clause(where_part)?);
-        } else {
-            // If no WHERE, take part until GROUP BY or ORDER BY as FROM
-            let end_idx = after_from
-                .find(" group by ")
-                .or_else(|| after_from.find(" order by "))
-                .unwrap_or(after_from.len());
-            query.from = after_from[..end_idx].trim().to_string();
-        }
-
-        // Find GROUP BY clause
-        if let Some(group_idx) = after_from.find(" group by ") {
-            let group_part = &after_from[group_idx + 10..];
-            let end_idx = group_part
-                .find(" order by ")
-                .or_else(|| group_part.find(" having "))
-                .unwrap_or(group_part.len());
+        // Comma-separated FROM (FROM a, b): implicit cross joins,
+        // bound to WHERE equality predicates (a.x = b.y) below
+        if tables.len() > 1 {
+            query.joins = Some(
+                tables[1..]
+                    .iter()
+                    .cloned()
+                    .map(|table| JoinClause {
+                        table,
+                        on: JoinCondition {
+                            left_column: String::new(),
+                            right_column: String::new(),
+                        },
+                        join_type: JoinType::Cross,
+                    })
+                    .collect(),
+            );
+            Self::bind_join_predicates(&mut query);
+        }
 
+        // GROUP BY columns
+        if let Some(group_part) = clauses.group_by {
             query.group_by = Some(
-                group_part[..end_idx]
-                    .trim()
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .collect(),
+                Self::split_top_level(group_part)
+                    .into_iter()
+                    .map(|column| Self::item(column, "GROUP BY"))
+                    .collect::<Result<_, _>>()?,
             );
         }
 
-        // Find ORDER BY clause
-        if let Some(order_idx) = after_from.find(" order by ") {
-            let order_part = &after_from[order_idx + 10..];
+        // HAVING clause
+        if let Some(having_part) = clauses.having {
+            query.having = Some(Self::parse_having(having_part)?);
+        }
+
+        // ORDER BY clause
+        if let Some(order_part) = clauses.order_by {
             query.order_by = Some(Self::parse_order_by(order_part)?);
         }
 
-        // Detect aggregation functions
+        // LIMIT row count
+        if let Some(limit_part) = clauses.limit {
+            query.limit = match limit_part {
+                [Token::Number(limit)] => Some(*limit as usize),
+                _ => return Err(format!("Invalid LIMIT: {}", render(limit_part))),
+            };
+        }
+
+        // Detect window functions (before aggregations: sum(x) over (...) is not an aggregation),
+        // then computed items, which are listed in `columns` by their output name
+        let mut windows = Vec::new();
         let mut aggregations = Vec::new();
-        for col in &query.columns {
-            if col.starts_with("sum(")
-                || col.starts_with("count(")
-                || col.starts_with("max(")
-                || col.starts_with("min(")
-            {
-                if let Some(agg) = Self::parse_aggregation(col) {
-                    aggregations.push(agg);
-                }
+        let mut projections = Vec::new();
+        for (i, item) in Self::split_top_level(select_part).into_iter().enumerate() {
+            if Self::find_top_level(item, |t| t.is_keyword(Keyword::Over)).is_some() {
+                windows.push(Self::window_from_tokens(item)?);
+            } else if let Some(agg) = Self::aggregation_from_tokens(item) {
+                aggregations.push(agg);
+            } else if let Some(projection) = Self::projection_from_tokens(item)? {
+                query.columns[i] = projection.name.clone();
+                projections.push(projection);
             }
         }
+        if !windows.is_empty() {
+            query.windows = Some(windows);
+        }
         if !aggregations.is_empty() {
             query.aggregations = Some(aggregations);
         }
+        if !projections.is_empty() {
+            query.projections = Some(projections);
+        }
 
         Ok(query)
     }
 
-    /// Parse WHERE clause
-    fn parse_where_clause(where_part: &str) -> Result<WhereClause, String> {
-        let where_part = where_part.trim();
-
-        // Check AND/OR operators
-        if let Some(and_idx) = where_part.find(" and ") {
-            let left = Self::parse_where_clause(&where_part[..and_idx])?;
-            let right = Self::parse_where_clause(&where_part[and_idx + 5..])?;
-            return Ok(WhereClause::And(Box::new(left), Box::new(right)));
+    /// Split a query's tokens into its clauses (without the clause keywords)
+    fn split_clauses(tokens: &[Token]) -> Result<Clauses<'_>, String> {
+        // Clause keyword positions and lengths, in query order
+        let mut starts: Vec<(usize, usize, usize)> = vec![(0, 0, 1)];
+        let mut depth = 0i32;
+        for (idx, token) in tokens.iter().enumerate() {
+            let followed_by = |keyword| tokens.get(idx + 1).is_some_and(|t| t.is_keyword(keyword));
+            let clause = match token {
+                Token::LeftParen => {
+                    depth += 1;
+                    None
+                }
+                Token::RightParen => {
+                    depth -= 1;
+                    None
+                }
+                _ if depth != 0 => None,
+                Token::Keyword(Keyword::From) => Some((1, 1)),
+                Token::Keyword(Keyword::Where) => Some((2, 1)),
+                Token::Keyword(Keyword::Group) if followed_by(Keyword::By) => Some((3, 2)),
+                Token::Keyword(Keyword::Having) => Some((4, 1)),
+                Token::Keyword(Keyword::Order) if followed_by(Keyword::By) => Some((5, 2)),
+                // LIMIT is not reserved: a clause only before a row count
+                Token::Keyword(Keyword::Limit)
+                    if matches!(tokens.get(idx + 1), Some(Token::Number(_))) =>
+                {
+                    Some((6, 1))
+                }
+                _ => None,
+            };
+            if let Some((clause, len)) = clause {
+                if starts.last().is_some_and(|&(last, _, _)| clause <= last) {
+                    return Err(format!("Unexpected {}", render(&tokens[idx..idx + len])));
+                }
+                starts.push((clause, idx, len));
+            }
+        }
+        if depth != 0 {
+            return Err("Unbalanced parentheses".to_string());
         }
 
-        if let Some(or_idx) = where_part.find(" or ") {
-            let left = Self::parse_where_clause(&where_part[..or_idx])?;
-            let right = Self::parse_where_clause(&where_part[or_idx + 4..])?;
-            return Ok(WhereClause::Or(Box::new(left), Box::new(right)));
+        let mut parts: [Option<&[Token]>; 7] = [None; 7];
+        for (i, &(clause, idx, len)) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).map_or(tokens.len(), |&(_, next, _)| next);
+            parts[clause] = Some(&tokens[idx + len..end]);
         }
+        let [select, from, where_part, group_by, having, order_by, limit] = parts;
+        Ok(Clauses {
+            select: select.unwrap_or_default(),
+            from,
+            where_part,
+            group_by,
+            having,
+            order_by,
+            limit,
+        })
+    }
 
-        // Simple comparison: column < value, column > value, column = value
-        if let Some(lt_idx) = where_part.find(" < ") {
-            let column = where_part[..lt_idx].trim().to_string();
-            let value = where_part[lt_idx + 3..]
-                .trim()
-                .parse::<u64>()
-                .map_err(|_| "Invalid number in WHERE clause")?;
-            return Ok(WhereClause::LessThan { column, value });
+    /// Non-empty list item written back as text (a column, table or
+    /// aggregation as it appears in the query)
+    fn item(tokens: &[Token], clause: &str) -> Result<String, String> {
+        if tokens.is_empty() {
+            return Err(format!("Empty item in {} clause", clause));
         }
+        Ok(render(tokens))
+    }
 
-        if let Some(gt_idx) = where_part.find(" > ") {
-            let column = where_part[..gt_idx].trim().to_string();
-            let value = where_part[gt_idx + 3..]
-                .trim()
-                .parse::<u64>()
-                .map_err(|_| "Invalid number in WHERE clause")?;
-            return Ok(WhereClause::GreaterThan { column, value });
+    /// Position of the first token matching `pred` outside parentheses
+    /// (e.g. not inside an EXISTS subquery)
+    fn find_top_level(tokens: &[Token], pred: impl Fn(&Token) -> bool) -> Option<usize> {
+        let mut depth = 0i32;
+        for (idx, token) in tokens.iter().enumerate() {
+            match token {
+                Token::LeftParen => depth += 1,
+                Token::RightParen => depth -= 1,
+                _ if depth == 0 && pred(token) => return Some(idx),
+                _ => {}
+            }
         }
+        None
+    }
 
-        if let Some(eq_idx) = where_part.find(" = ") {
-            let column = where_part[..eq_idx].trim().to_string();
-            let value = where_part[eq_idx + 3..]
-                .trim()
-                .parse::<u64>()
-                .map_err(|_| "Invalid number in WHERE clause")?;
-            return Ok(WhereClause::Equal { column, value });
+    /// Split at commas outside parentheses
+    fn split_top_level(tokens: &[Token]) -> Vec<&[Token]> {
+        let mut items = Vec::new();
+        let mut rest = tokens;
+        while let Some(idx) = Self::find_top_level(rest, |t| *t == Token::Comma) {
+            items.push(&rest[..idx]);
+            rest = &rest[idx + 1..];
         }
+        items.push(rest);
+        items
+    }
 
-        Err("Unsupported WHERE clause format".to_string())
+    /// Turn WHERE equality predicates between the FROM table and a cross-joined
+    /// table (`a.x = b.y`) into that join's ON condition
+    ///
+    /// Only top-level (AND-connected) predicates are bound; predicates under
+    /// OR stay in the WHERE clause.
+    fn bind_join_predicates(query: &mut SQLQuery) {
+        let Some(where_clause) = query.where_clause.take() else {
+            return;
+        };
+        let joins = query.joins.get_or_insert_with(Vec::new);
+        query.where_clause = Self::extract_join_predicates(where_clause, &query.from, joins);
     }
 
-    /// Parse ORDER BY clause
-    fn parse_order_by(order_part: &str) -> Result<Vec<OrderBy>, String> {
-        let order_part = order_part.trim();
-        let mut orders = Vec::new();
+    /// Remove join predicates from a WHERE clause, returning what is left
+    fn extract_join_predicates(
+        where_clause: WhereClause,
+        from: &str,
+        joins: &mut [JoinClause],
+    ) -> Option<WhereClause> {
+        match where_clause {
+            WhereClause::And(left, right) => {
+                let left = Self::extract_join_predicates(*left, from, joins);
+                let right = Self::extract_join_predicates(*right, from, joins);
+                match (left, right) {
+                    (Some(left), Some(right)) => {
+                        Some(WhereClause::And(Box::new(left), Box::new(right)))
+                    }
+                    (left, right) => left.or(right),
+                }
+            }
+            WhereClause::ColumnCompare {
+                left_column,
+                op: ComparisonOp::Equal,
+                right_column,
+            } => {
+                let split = |column: &str| {
+                    column
+                        .split_once('.')
+                        .map(|(table, column)| (table.to_string(), column.to_string()))
+                };
 
-        for part in order_part.split(',') {
-            let part = part.trim();
-            if part.ends_with(" desc") {
-                let column = part[..part.len() - 5].trim().to_string();
-                orders.push(OrderBy {
-                    column,
-                    direction: OrderDirection::Desc,
+                if let (Some(left), Some(right)) = (split(&left_column), split(&right_column)) {
+                    // FROM table column on the left of the ON condition
+                    let (from_column, (join_table, join_column)) = if left.0 == from {
+                        (left.1, right)
+                    } else if right.0 == from {
+                        (right.1, left)
+                    } else {
+                        (String::new(), (String::new(), String::new()))
+                    };
+
+                    if let Some(join) = joins
+                        .iter_mut()
+                        .find(|j| j.table == join_table && j.join_type == JoinType::Cross)
+                    {
+                        join.on = JoinCondition {
+                            left_column: from_column,
+                            right_column: join_column,
+                        };
+                        join.join_type = JoinType::Inner;
+                        return None;
+                    }
+                }
+
+                Some(WhereClause::ColumnCompare {
+                    left_column,
+                    op: ComparisonOp::Equal,
+                    right_column,
+                })
+            }
+            other => Some(other),
+        }
+    }
+
+    /// Parse WHERE clause
+    fn parse_where_clause(
+        tokens: &[Token],
+        value_params: &mut Vec<Option<String>>,
+    ) -> Result<WhereClause, String> {
+        if tokens.is_empty() {
+            return Err("Empty WHERE clause".to_string());
+        }
+
+        // Check AND/OR operators (outside subquery parentheses; the AND of
+        // `BETWEEN low AND high` belongs to its range)
+        if let Some(and_idx) = Self::find_connective_and(tokens) {
+            let left = Self::parse_where_clause(&tokens[..and_idx], value_params)?;
+            let right = Self::parse_where_clause(&tokens[and_idx + 1..], value_params)?;
+            return Ok(WhereClause::And(Box::new(left), Box::new(right)));
+        }
+
+        if let Some(or_idx) = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::Or)) {
+            let left = Self::parse_where_clause(&tokens[..or_idx], value_params)?;
+            let right = Self::parse_where_clause(&tokens[or_idx + 1..], value_params)?;
+            return Ok(WhereClause::Or(Box::new(left), Box::new(right)));
+        }
+
+        // EXISTS (subquery) / NOT EXISTS (subquery)
+        let (negated, exists_part) = match tokens.split_first() {
+            Some((first, rest)) if first.is_keyword(Keyword::Not) => (true, rest),
+            _ => (false, tokens),
+        };
+        if let Some((first, subquery)) = exists_part.split_first() {
+            if first.is_keyword(Keyword::Exists) {
+                let subquery = Self::parenthesized(subquery)
+                    .ok_or("EXISTS must be followed by a parenthesized subquery")?;
+                return Ok(WhereClause::Exists {
+                    subquery: Box::new(Self::parse_tokens(subquery)?),
+                    negated,
                 });
-            } else if part.ends_with(" asc") {
-                let column = part[..part.len() - 4].trim().to_string();
-                orders.push(OrderBy {
-                    column,
-                    direction: OrderDirection::Asc,
+            }
+        }
+
+        // Boolean column: column, NOT column, column = true / false
+        let boolean = match exists_part {
+            [column] => column.name().map(|column| (column, true)),
+            [column, Token::Operator(Operator::Equal), Token::Keyword(literal)] => match literal {
+                Keyword::True => column.name().map(|column| (column, true)),
+                Keyword::False => column.name().map(|column| (column, false)),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((column, value)) = boolean {
+            return Ok(WhereClause::Bool {
+                column: column.to_string(),
+                value: value != negated,
+            });
+        }
+
+        // NULL test: column IS [NOT] NULL (NOT negates it)
+        let is_null = match exists_part {
+            [column, Token::Keyword(Keyword::Is), Token::Keyword(Keyword::Null)] => {
+                column.name().map(|column| (column, true))
+            }
+            [column, Token::Keyword(Keyword::Is), Token::Keyword(Keyword::Not), Token::Keyword(Keyword::Null)] => {
+                column.name().map(|column| (column, false))
+            }
+            _ => None,
+        };
+        if let Some((column, null)) = is_null {
+            let column = column.to_string();
+            return Ok(if null != negated {
+                WhereClause::IsNull { column }
+            } else {
+                WhereClause::IsNotNull { column }
+            });
+        }
+
+        // Inclusive range: column BETWEEN low AND high, as the bounded pair
+        // column > low - 1 AND column < high + 1 (one range check per row)
+        if let [column, Token::Keyword(Keyword::Between), low, Token::Keyword(Keyword::And), high] =
+            tokens
+        {
+            let column = column
+                .name()
+                .ok_or("BETWEEN must be written as column BETWEEN low AND high")?
+                .to_string();
+            let bound = |token: &Token| match token {
+                Token::Number(value) => Ok(*value),
+                Token::String(text) => parse_timestamp(text),
+                _ => Err("BETWEEN bounds must be numbers or date literals".to_string()),
+            };
+            let (low, high) = (bound(low)?, bound(high)?);
+            // high = u64::MAX (NULL) is not a value, so < u64::MAX keeps the rest
+            let below = WhereClause::LessThan {
+                column: column.clone(),
+                value: high.saturating_add(1),
+            };
+            // Literal bounds: no parameter in either comparison
+            return Ok(match low.checked_sub(1) {
+                Some(value) => {
+                    value_params.extend([None, None]);
+                    WhereClause::And(
+                        Box::new(WhereClause::GreaterThan { column, value }),
+                        Box::new(below),
+                    )
+                }
+                None => {
+                    value_params.push(None);
+                    below
+                }
+            });
+        }
+
+        // Set membership: column IN (set)
+        if let [column, Token::Keyword(Keyword::In), rest @ ..] = tokens {
+            let set = match Self::parenthesized(rest) {
+                Some([set]) => set.name(),
+                _ => None,
+            };
+            return match (column.name(), set) {
+                (Some(column), Some(set)) => Ok(WhereClause::In {
+                    column: column.to_string(),
+                    set: set.to_string(),
+                }),
+                _ => Err("IN must be written as column IN (set)".to_string()),
+            };
+        }
+
+        // Simple comparison: column < value, column > value, column = value,
+        // column <= value, column >= value
+        // The left side may also be an arithmetic expression (e.g. price * quantity > 1000)
+        let (op_idx, operator) =
+            Self::comparison(tokens).ok_or("Unsupported WHERE clause format")?;
+        let expr = Expr::from_tokens(&tokens[..op_idx])?;
+        let right = &tokens[op_idx + 1..];
+
+        // Column on the right side: compare two columns (e.g. spent < budget)
+        if let (Expr::Column(left_column), [right_column]) = (&expr, right) {
+            if let Some(right_column) = right_column.name() {
+                return Ok(WhereClause::ColumnCompare {
+                    left_column: left_column.clone(),
+                    op: operator,
+                    right_column: right_column.to_string(),
+                });
+            }
+        }
+
+        // Number, ISO-8601 date literal (`parse_timestamp`) or parameter
+        let (value, param) = match right {
+            [Token::Number(value)] => (*value, None),
+            [Token::String(text)] => (parse_timestamp(text)?, None),
+            [Token::Parameter(name)] => (0, Some(name.clone())),
+            _ => return Err("Invalid number in WHERE clause".to_string()),
+        };
+        value_params.push(param);
+
+        Ok(match (expr, operator) {
+            (Expr::Column(column), ComparisonOp::LessThan) => {
+                WhereClause::LessThan { column, value }
+            }
+            (Expr::Column(column), ComparisonOp::GreaterThan) => {
+                WhereClause::GreaterThan { column, value }
+            }
+            (Expr::Column(column), ComparisonOp::LessEqual) => {
+                WhereClause::LessEqual { column, value }
+            }
+            (Expr::Column(column), ComparisonOp::GreaterEqual) => {
+                WhereClause::GreaterEqual { column, value }
+            }
+            (Expr::Column(column), ComparisonOp::Equal) => WhereClause::Equal { column, value },
+            (expr, operator) => WhereClause::ExprCompare {
+                expr,
+                operator,
+                value,
+            },
+        })
+    }
+
+    /// The single top-level comparison operator (`<`, `>`, `<=`, `>=`, `=`) and its position
+    fn comparison(tokens: &[Token]) -> Option<(usize, ComparisonOp)> {
+        let operator = |token: &Token| match token {
+            Token::Operator(Operator::LessThan) => Some(ComparisonOp::LessThan),
+            Token::Operator(Operator::GreaterThan) => Some(ComparisonOp::GreaterThan),
+            Token::Operator(Operator::LessEqual) => Some(ComparisonOp::LessEqual),
+            Token::Operator(Operator::GreaterEqual) => Some(ComparisonOp::GreaterEqual),
+            Token::Operator(Operator::Equal) => Some(ComparisonOp::Equal),
+            _ => None,
+        };
+        let idx = Self::find_top_level(tokens, |t| operator(t).is_some())?;
+        if Self::find_top_level(&tokens[idx + 1..], |t| operator(t).is_some()).is_some() {
+            return None;
+        }
+        Some((idx, operator(&tokens[idx])?))
+    }
+
+    /// Position of the first top-level AND joining two predicates (skipping
+    /// the AND inside `column BETWEEN low AND high`)
+    fn find_connective_and(tokens: &[Token]) -> Option<usize> {
+        let mut start = 0;
+        while let Some(offset) =
+            Self::find_top_level(&tokens[start..], |t| t.is_keyword(Keyword::And))
+        {
+            let idx = start + offset;
+            if idx < 2 || !tokens[idx - 2].is_keyword(Keyword::Between) {
+                return Some(idx);
+            }
+            start = idx + 1;
+        }
+        None
+    }
+
+    /// Tokens inside a pair of parentheses enclosing the whole slice
+    fn parenthesized(tokens: &[Token]) -> Option<&[Token]> {
+        let [Token::LeftParen, inner @ .., Token::RightParen] = tokens else {
+            return None;
+        };
+        // The closing parenthesis must match the opening one
+        let mut depth = 0i32;
+        for token in inner {
+            match token {
+                Token::LeftParen => depth += 1,
+                Token::RightParen if depth == 0 => return None,
+                Token::RightParen => depth -= 1,
+                _ => {}
+            }
+        }
+        (depth == 0).then_some(inner)
+    }
+
+    /// Parse HAVING clause (aggregation < value, aggregation > value,
+    /// aggregation = value, combined with AND / OR as in WHERE)
+    fn parse_having(tokens: &[Token]) -> Result<HavingClause, String> {
+        if let Some(and_idx) = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::And)) {
+            let left = Self::parse_having(&tokens[..and_idx])?;
+            let right = Self::parse_having(&tokens[and_idx + 1..])?;
+            return Ok(HavingClause::And(Box::new(left), Box::new(right)));
+        }
+        if let Some(or_idx) = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::Or)) {
+            let left = Self::parse_having(&tokens[..or_idx])?;
+            let right = Self::parse_having(&tokens[or_idx + 1..])?;
+            return Ok(HavingClause::Or(Box::new(left), Box::new(right)));
+        }
+
+        let (op_idx, operator) =
+            Self::comparison(tokens).ok_or("Unsupported HAVING clause format")?;
+
+        let aggregation = render(&tokens[..op_idx]);
+        if Self::aggregation_from_tokens(&tokens[..op_idx]).is_none() {
+            return Err(format!(
+                "HAVING must compare an aggregation: {}",
+                aggregation
+            ));
+        }
+        let value = match &tokens[op_idx + 1..] {
+            [Token::Number(value)] => *value,
+            _ => return Err("Invalid number in HAVING clause".to_string()),
+        };
+        Ok(HavingClause::Compare {
+            aggregation,
+            operator,
+            value,
+        })
+    }
+
+    /// Parse ORDER BY clause
+    fn parse_order_by(tokens: &[Token]) -> Result<Vec<OrderBy>, String> {
+        let mut orders = Vec::new();
+
+        for mut part in Self::split_top_level(tokens) {
+            // NULLS FIRST / NULLS LAST suffix
+            let mut nulls = None;
+            if let [rest @ .., Token::Keyword(Keyword::Nulls), Token::Keyword(last)] = part {
+                nulls = match last {
+                    Keyword::First => Some(NullsOrder::First),
+                    Keyword::Last => Some(NullsOrder::Last),
+                    _ => return Err("NULLS must be followed by FIRST or LAST".to_string()),
+                };
+                part = rest;
+            }
+
+            // Direction: a trailing ASC / DESC keyword token (a column such as
+            // `basc` is one identifier token); default: ASC
+            let mut direction = OrderDirection::Asc;
+            if let [rest @ .., Token::Keyword(keyword @ (Keyword::Asc | Keyword::Desc))] = part {
+                if *keyword == Keyword::Desc {
+                    direction = OrderDirection::Desc;
+                }
+                part = rest;
+            }
+            let column = Self::item(part, "ORDER BY")?;
+
+            // Default NULL placement follows the SQL standard
+            let nulls = nulls.unwrap_or(match direction {
+                OrderDirection::Asc => NullsOrder::Last,
+                OrderDirection::Desc => NullsOrder::First,
+            });
+
+            orders.push(OrderBy {
+                column,
+                direction,
+                nulls,
+            });
+        }
+
+        Ok(orders)
+    }
+
+    /// Parse window function: sum(col) over ([partition by col] order by col),
+    /// row_number() over ([partition by col] order by col)
+    fn parse_window(col: &str) -> Result<WindowClause, String> {
+        Self::window_from_tokens(&tokenize(col)?)
+    }
+
+    /// Window function from the tokens of a SELECT item
+    fn window_from_tokens(tokens: &[Token]) -> Result<WindowClause, String> {
+        let over_idx = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::Over))
+            .ok_or("Missing OVER in window function")?;
+        let function_part = &tokens[..over_idx];
+
+        let spec = Self::parenthesized(&tokens[over_idx + 1..])
+            .ok_or("Window specification must be in parentheses")?;
+        let (partition_by, order_spec) = match spec {
+            [Token::Keyword(Keyword::Partition), Token::Keyword(Keyword::By), rest @ ..] => {
+                let order_idx = Self::find_top_level(rest, |t| t.is_keyword(Keyword::Order))
+                    .filter(|&idx| idx > 0)
+                    .ok_or("PARTITION BY must be followed by ORDER BY")?;
+                (Some(render(&rest[..order_idx])), &rest[order_idx..])
+            }
+            _ => (None, spec),
+        };
+        let order_by = match order_spec {
+            [Token::Keyword(Keyword::Order), Token::Keyword(Keyword::By), column @ ..]
+                if !column.is_empty() =>
+            {
+                render(column)
+            }
+            _ => return Err("Only OVER ([PARTITION BY col] ORDER BY col) is supported".to_string()),
+        };
+
+        if let [Token::Identifier(name), Token::LeftParen, Token::RightParen] = function_part {
+            if name == "row_number" {
+                return Ok(WindowClause {
+                    function: WindowFunction::RowNumber,
+                    column: None,
+                    partition_by,
+                    order_by,
+                });
+            }
+        }
+
+        match Self::aggregation_from_tokens(function_part) {
+            Some(AggregationClause {
+                function: AggregationFunction::Sum,
+                column,
+                distinct: false,
+            }) => Ok(WindowClause {
+                function: WindowFunction::RunningSum,
+                column: Some(column),
+                partition_by,
+                order_by,
+            }),
+            _ => Err(format!(
+                "Unsupported window function: {}",
+                render(function_part)
+            )),
+        }
+    }
+
+    /// Parse a computed SELECT item (`expr [AS name]`); None for a plain
+    /// column or `*` without an alias
+    fn projection_from_tokens(tokens: &[Token]) -> Result<Option<Projection>, String> {
+        let as_idx = Self::find_top_level(tokens, |t| t.is_keyword(Keyword::As));
+        let (expr_tokens, alias) = match as_idx {
+            Some(idx) => match &tokens[idx + 1..] {
+                [name] if name.name().is_some() => {
+                    (&tokens[..idx], Some(render(&tokens[idx + 1..])))
+                }
+                _ => return Err(format!("Expected a name after AS in {}", render(tokens))),
+            },
+            None => (tokens, None),
+        };
+
+        let arithmetic = |t: &Token| {
+            matches!(
+                t,
+                Token::Operator(
+                    Operator::Plus
+                        | Operator::Minus
+                        | Operator::Star
+                        | Operator::Slash
+                        | Operator::Percent
+                )
+            )
+        };
+        let case = expr_tokens
+            .first()
+            .is_some_and(|t| t.is_keyword(Keyword::Case));
+        if alias.is_none()
+            && !case
+            && (expr_tokens.len() < 2 || !expr_tokens.iter().any(arithmetic))
+        {
+            return Ok(None);
+        }
+
+        let expr = Expr::from_tokens(expr_tokens)?;
+        let name = alias.unwrap_or_else(|| expr.to_string());
+        Ok(Some(Projection { expr, name }))
+    }
+
+    /// Parse aggregation function
+    fn parse_aggregation(col: &str) -> Option<AggregationClause> {
+        Self::aggregation_from_tokens(&tokenize(col).ok()?)
+    }
+
+    /// Aggregation from the tokens of `function(column)`
+    fn aggregation_from_tokens(tokens: &[Token]) -> Option<AggregationClause> {
+        let (name, argument) = match tokens {
+            [Token::Identifier(name), rest @ ..] => (name, Self::parenthesized(rest)?),
+            _ => return None,
+        };
+        let function = match name.as_str() {
+            "sum" => AggregationFunction::Sum,
+            "count" => AggregationFunction::Count,
+            "max" => AggregationFunction::Max,
+            "min" => AggregationFunction::Min,
+            "avg" => AggregationFunction::Avg,
+            _ => return None,
+        };
+        // Only COUNT takes DISTINCT
+        let (distinct, argument) = match argument {
+            [Token::Keyword(Keyword::Distinct), column @ ..]
+                if function == AggregationFunction::Count =>
+            {
+                (true, column)
+            }
+            _ => (false, argument),
+        };
+        Some(AggregationClause {
+            function,
+            column: render(argument),
+            distinct,
+        })
+    }
+}
+
+/// Clauses of one SELECT, as token slices without their keywords
+struct Clauses<'a> {
+    select: &'a [Token],
+    from: Option<&'a [Token]>,
+    where_part: Option<&'a [Token]>,
+    group_by: Option<&'a [Token]>,
+    having: Option<&'a [Token]>,
+    order_by: Option<&'a [Token]>,
+    limit: Option<&'a [Token]>,
+}
+
+/// SQL Compiler
+/// Compiles SQL AST to circuit
+pub struct SQLCompiler;
+
+impl SQLCompiler {
+    /// Compile SQL query to circuit
+    /// Paper Section 3: Compiling SQL queries to ZKP circuit
+    ///
+    /// # Parameters
+    ///
+    /// - `query`: Parsed SQL query
+    /// - `tables`: Table data (table_name -> column_name -> values), or a
+    ///   row-major `DatabaseTable` (see `TableSource`)
+    ///
+    /// # Returns
+    ///
+    /// Compiled query with circuit operations; a column whose WHERE
+    /// comparisons exceed `DEFAULT_RANGE_CHECK_BUDGET` is an error
+    pub fn compile<T: TableSource + ?Sized>(
+        query: &SQLQuery,
+        tables: &T,
+    ) -> Result<CompiledQuery, String> {
+        Self::compile_with_budget(query, tables, DEFAULT_RANGE_CHECK_BUDGET)
+    }
+
+    /// Compile SQL query to circuit, with a per-column range-check budget
+    /// (`check_range_check_budget`); `usize::MAX` disables the check
+    pub fn compile_with_budget<T: TableSource + ?Sized>(
+        query: &SQLQuery,
+        tables: &T,
+        budget: usize,
+    ) -> Result<CompiledQuery, String> {
+        #[cfg(feature = "tracing")]
+        let _span = crate::trace::StageSpan::enter(tracing::debug_span!("compile"));
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        Self::check_budget(query, table_data, budget).map_err(|e| e.to_string())?;
+        let mut compiled = Self::compile_query(query, table_data)?;
+
+        // Empty result: the per-row range checks of a WHERE comparison that
+        // matches no rows are replaced by a proven match count of 0
+        // (the comparison is the only source of range checks), unless a
+        // filtered COUNT copies their check cells
+        // LIMIT over the matched rows: the match count is proven next to the
+        // range checks, so a truncated result tells how many rows matched
+        let filtered = compiled
+            .aggregations
+            .iter()
+            .any(|aggregation| aggregation.value_checks.is_some());
+        if query.union.is_none() && !filtered {
+            if let Some(op) = Self::match_count(query, table_data)? {
+                let limited = query.limit.is_some()
+                    && compiled.aggregations.is_empty()
+                    && compiled.group_bys.is_empty();
+                if op.count() == 0 {
+                    compiled.range_checks.clear();
+                    compiled.where_predicates.clear();
+                    compiled.match_count = Some(op);
+                    compiled.result = None;
+                } else if limited {
+                    compiled.match_count = Some(op);
+                }
+            }
+        }
+        if let Some(limit) = query.limit {
+            compiled.result = Self::limited_result(query, table_data, &mut compiled, limit);
+        }
+
+        // HAVING: the number of kept groups is bound to the query result,
+        // unless a match count is (a single clause; not for a UNION's two)
+        if let [having] = &mut compiled.having[..] {
+            having.public_count = compiled.match_count.is_none();
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(
+                range_checks = compiled.range_checks.len(),
+                sorts = compiled.sorts.len(),
+                group_bys = compiled.group_bys.len(),
+                joins = compiled.joins.len(),
+                aggregations = compiled.aggregations.len(),
+                "compiled"
+            );
+            tracing::trace!("{}", compiled.summary());
+        }
+
+        Ok(compiled)
+    }
+
+    /// Compile only the WHERE clause of a query into a committed selection
+    /// vector: one check bit per FROM row (1 = the row matches), hashed as a
+    /// `ResultCommitment::selection` copied from the range checks' check cells
+    ///
+    /// # Returns
+    ///
+    /// The WHERE range checks (and the products or divisions of its
+    /// expressions) with `selection` set; the SELECT list, joins, grouping,
+    /// aggregations and ORDER BY are not compiled. The WHERE clause must be a
+    /// single comparison or bounded range, whose check bit is the row's outcome
+    pub fn compile_selection<T: TableSource + ?Sized>(
+        query: &SQLQuery,
+        tables: &T,
+    ) -> Result<CompiledQuery, String> {
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        let where_clause = query
+            .where_clause
+            .as_ref()
+            .ok_or("A selection vector needs a WHERE clause")?;
+        if query.joins.is_some() || query.union.is_some() {
+            return Err("A selection vector is over the rows of a single table".to_string());
+        }
+
+        // FROM and WHERE only
+        let filter = SQLQuery {
+            columns: vec!["*".to_string()],
+            from: query.from.clone(),
+            where_clause: Some(where_clause.clone()),
+            group_by: None,
+            order_by: None,
+            having: None,
+            joins: None,
+            aggregations: None,
+            windows: None,
+            projections: None,
+            union: None,
+            limit: None,
+        };
+        Self::check_budget(&filter, table_data, DEFAULT_RANGE_CHECK_BUDGET)
+            .map_err(|e| e.to_string())?;
+        let mut compiled = Self::compile_query(&filter, table_data)?;
+
+        let rows = table_data
+            .get(&query.from)
+            .ok_or_else(|| format!("Table {} not found", query.from))?
+            .values()
+            .next()
+            .map_or(0, Vec::len);
+        let checks = Self::count_filter(&filter, &compiled, rows, "Selection").map_err(|_| {
+            format!(
+                "Selection vector of WHERE {} needs a single comparison or bounded range",
+                where_clause
+            )
+        })?;
+        let bits = checks
+            .iter()
+            .map(|&check| Self::check_bit(&compiled.range_checks[check], &compiled.set_memberships))
+            .collect();
+        compiled.selection = Some(ResultCommitment::selection(bits, checks));
+        Ok(compiled)
+    }
+
+    /// Planned operations of a query, in order, with their estimated costs
+    /// (like SQL `EXPLAIN`)
+    ///
+    /// e.g. `SCAN order (1000 rows) → FILTER amount > 100 (1000 range checks)
+    /// → GROUP BY customer_id (1000 rows sorted) → AGGREGATE sum(amount)
+    /// (1 aggregation) → SORT customer_id ASC (12 rows)`. Costs are the sizes
+    /// of the compiled operations (see `CompiledQuery::summary`): range checks
+    /// of a filter, rows of each sort, rows of both join tables. Stages are
+    /// listed only when the query has them; the sides of a UNION are
+    /// explained on their own.
+    ///
+    /// # Returns
+    ///
+    /// The plan, or the error of a query that does not compile
+    pub fn explain<T: TableSource + ?Sized>(
+        query: &SQLQuery,
+        tables: &T,
+    ) -> Result<String, String> {
+        let table_data = tables.column_map();
+        let table_data = &*table_data;
+        let compiled = Self::compile(query, table_data)?;
+
+        if let Some(union) = &query.union {
+            let left = SQLQuery {
+                union: None,
+                ..query.clone()
+            };
+            let rows = compiled.union.as_ref().map_or(0, |union| union.rows.len());
+            return Ok(format!(
+                "{} → UNION{} [{}] ({} rows)",
+                Self::explain(&left, table_data)?,
+                if union.all { " ALL" } else { "" },
+                Self::explain(&union.query, table_data)?,
+                rows
+            ));
+        }
+
+        fn count(n: usize, singular: &str, plural: &str) -> String {
+            format!("{} {}", n, if n == 1 { singular } else { plural })
+        }
+
+        let rows = table_data
+            .get(&query.from)
+            .and_then(|table| table.values().next())
+            .map_or(0, Vec::len);
+        let mut stages = vec![format!("SCAN {} ({} rows)", query.from, rows)];
+
+        for (join, op) in query.joins.iter().flatten().zip(&compiled.joins) {
+            stages.push(format!(
+                "JOIN {} ON {}.{} = {}.{} ({} rows)",
+                join.table,
+                query.from,
+                join.on.left_column,
+                join.table,
+                join.on.right_column,
+                op.table1_keys.len() + op.table2_keys.len()
+            ));
+        }
+
+        // Per-row expressions (WHERE, SELECT list and ORDER BY)
+        if !compiled.products.is_empty()
+            || !compiled.divisions.is_empty()
+            || !compiled.sums.is_empty()
+        {
+            let mut costs = vec![
+                count(compiled.products.len(), "product", "products"),
+                count(compiled.divisions.len(), "division", "divisions"),
+            ];
+            if !compiled.sums.is_empty() {
+                costs.push(count(compiled.sums.len(), "sum", "sums"));
+            }
+            stages.push(format!("COMPUTE ({})", costs.join(", ")));
+        }
+
+        if let Some(where_clause) = &query.where_clause {
+            let mut costs = Vec::new();
+            if compiled.match_count.is_none() || !compiled.range_checks.is_empty() {
+                costs.push(count(
+                    compiled.range_checks.len(),
+                    "range check",
+                    "range checks",
+                ));
+            }
+            if let Some(op) = &compiled.match_count {
+                costs.push(format!("match count over {} rows", op.values.len()));
+            }
+            if !compiled.semi_joins.is_empty() {
+                let rows: usize = compiled
+                    .semi_joins
+                    .iter()
+                    .map(|op| op.left_keys.len())
+                    .sum();
+                costs.push(format!("{} semi-join rows", rows));
+            }
+            if !compiled.set_memberships.is_empty() {
+                let values: usize = compiled.set_memberships.iter().map(|op| op.set.len()).sum();
+                costs.push(format!("{} committed set values", values));
+            }
+            stages.push(format!("FILTER {} ({})", where_clause, costs.join(", ")));
+        }
+
+        // Sorts in compile order: GROUP BY (first column), COUNT(DISTINCT)
+        // (one each), ORDER BY (one per column), then windows
+        let mut sorts = compiled.sorts.iter();
+        if let Some(group_by) = &query.group_by {
+            let rows = sorts.next().map_or(0, |sort| sort.input.len());
+            stages.push(format!(
+                "GROUP BY {} ({} rows sorted)",
+                group_by.join(", "),
+                rows
+            ));
+        }
+
+        if let Some(aggregations) = &query.aggregations {
+            let functions: Vec<String> = aggregations
+                .iter()
+                .map(|aggregation| aggregation.to_string())
+                .collect();
+            stages.push(format!(
+                "AGGREGATE {} ({})",
+                functions.join(", "),
+                count(compiled.aggregations.len(), "aggregation", "aggregations")
+            ));
+            for _ in aggregations
+                .iter()
+                .filter(|aggregation| aggregation.distinct)
+            {
+                sorts.next();
+            }
+        }
+
+        if let Some(having) = &query.having {
+            let groups: usize = compiled
+                .having
+                .iter()
+                .flat_map(|op| op.predicate.aggregations())
+                .map(|index| compiled.aggregations[index].group_results().len())
+                .sum();
+            stages.push(format!(
+                "HAVING {} ({})",
+                having,
+                count(groups, "group check", "group checks")
+            ));
+        }
+
+        for order in query.order_by.iter().flatten() {
+            let direction = match order.direction {
+                OrderDirection::Asc => "ASC",
+                OrderDirection::Desc => "DESC",
+            };
+            // A ranked aggregation (ORDER BY <aggregate> LIMIT k) is its only key
+            if let Some(op) = compiled.top_ks.first() {
+                stages.push(format!(
+                    "TOP {} BY {} {} ({})",
+                    op.selected.len(),
+                    order.column,
+                    direction,
+                    count(op.sorted_results.len(), "group", "groups")
+                ));
+                continue;
+            }
+            let rows = sorts.next().map_or(0, |sort| sort.input.len());
+            stages.push(format!(
+                "SORT {} {} ({} rows)",
+                order.column, direction, rows
+            ));
+        }
+        if let (Some(limit), None) = (query.limit, compiled.top_ks.first()) {
+            stages.push(format!("LIMIT {}", limit));
+        }
+
+        for (window, op) in query.windows.iter().flatten().zip(&compiled.windows) {
+            let function = match (&window.function, &window.column) {
+                (WindowFunction::RunningSum, Some(column)) => format!("SUM({})", column),
+                _ => "ROW_NUMBER()".to_string(),
+            };
+            let partition = match &window.partition_by {
+                Some(partition_by) => format!("PARTITION BY {} ", partition_by),
+                None => String::new(),
+            };
+            stages.push(format!(
+                "WINDOW {} OVER ({}ORDER BY {}) ({} rows)",
+                function,
+                partition,
+                window.order_by,
+                op.values.len()
+            ));
+        }
+
+        Ok(stages.join(" → "))
+    }
+
+    /// Check the range checks WHERE would emit per column against `budget`
+    /// before compiling
+    ///
+    /// Each comparison emits one range check per row, attributed to its
+    /// (left) column; both bounds of a bounded predicate share one check.
+    /// Counts of a column add up across predicates and UNION sides.
+    ///
+    /// # Returns
+    ///
+    /// `QueryError::RangeCheckBudgetExceeded` with the offending count for the
+    /// first column over budget (in table, column order)
+    pub fn check_range_check_budget<T: TableSource + ?Sized>(
+        query: &SQLQuery,
+        tables: &T,
+        budget: usize,
+    ) -> Result<(), QueryError> {
+        Self::check_budget(query, &tables.column_map(), budget)
+    }
+
+    fn check_budget(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        budget: usize,
+    ) -> Result<(), QueryError> {
+        let mut counts = BTreeMap::new();
+        let mut next = Some(query);
+        while let Some(query) = next {
+            if let Some(where_clause) = &query.where_clause {
+                Self::count_range_checks(where_clause, table_data, &query.from, &mut counts);
+            }
+            next = query.union.as_ref().map(|union| &*union.query);
+        }
+
+        match counts.into_iter().find(|&(_, count)| count > budget) {
+            Some(((table, column), count)) => Err(QueryError::RangeCheckBudgetExceeded {
+                table,
+                column,
+                count,
+                budget,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Add the range checks of each WHERE comparison to its column's count
+    /// (unknown columns count nothing; compilation reports them)
+    fn count_range_checks(
+        where_clause: &WhereClause,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        table_name: &str,
+        counts: &mut BTreeMap<(String, String), usize>,
+    ) {
+        let mut add = |column: &str| {
+            if let Some(values) = table_data.get(table_name).and_then(|t| t.get(column)) {
+                *counts
+                    .entry((table_name.to_string(), column.to_string()))
+                    .or_default() += values.len();
+            }
+        };
+        match where_clause {
+            WhereClause::LessThan { column, .. }
+            | WhereClause::GreaterThan { column, .. }
+            | WhereClause::LessEqual { column, .. }
+            | WhereClause::GreaterEqual { column, .. }
+            | WhereClause::Equal { column, .. }
+            | WhereClause::Bool { column, .. }
+            | WhereClause::IsNull { column }
+            | WhereClause::IsNotNull { column }
+            | WhereClause::In { column, .. }
+            | WhereClause::ColumnCompare {
+                left_column: column,
+                ..
+            } => add(column),
+            WhereClause::ExprCompare { expr, .. } => {
+                let mut columns = Vec::new();
+                expr.columns(&mut columns);
+                if let Some(column) = columns.first() {
+                    add(column);
+                }
+            }
+            // Semi-joins emit no range checks
+            WhereClause::Exists { .. } => {}
+            WhereClause::And(left, right) | WhereClause::Or(left, right) => {
+                let bounded = match where_clause {
+                    WhereClause::And(..) => Self::bounded_range(left, right),
+                    _ => None,
+                };
+                match bounded {
+                    Some((column, _, _)) => add(column),
+                    None => {
+                        Self::count_range_checks(left, table_data, table_name, counts);
+                        Self::count_range_checks(right, table_data, table_name, counts);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compile SQL query to circuit operations (UNION sides included,
+    /// without the empty result form)
+    fn compile_query(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<CompiledQuery, String> {
+        if let Some(union) = &query.union {
+            return Self::compile_union(query, union, table_data);
+        }
+        query.check_grouping().map_err(|e| e.to_string())?;
+
+        let mut compiled = CompiledQuery {
+            range_checks: Vec::new(),
+            where_predicates: Vec::new(),
+            sorts: Vec::new(),
+            group_bys: Vec::new(),
+            joins: Vec::new(),
+            semi_joins: Vec::new(),
+            set_memberships: Vec::new(),
+            aggregations: Vec::new(),
+            products: Vec::new(),
+            divisions: Vec::new(),
+            sums: Vec::new(),
+            windows: Vec::new(),
+            having: Vec::new(),
+            top_ks: Vec::new(),
+            match_count: None,
+            union: None,
+            aggregate_specs: Vec::new(),
+            projections: Vec::new(),
+            selection: None,
+            result: None,
+        };
+
+        // Convert WHERE clause to range check operations
+        if let Some(where_clause) = &query.where_clause {
+            Self::compile_where_clause(where_clause, table_data, &query.from, &mut compiled)?;
+        }
+
+        // Convert GROUP BY clause to group_by operations
+        // Group-By and Aggregation Gates need rows grouped by key: rows are
+        // put in (stable) order of the GROUP BY column, proven by a Sort Gate
+        // on that column (`check_grouping` rejects more than one column)
+        // The Group-By and Aggregation Gates copy the sorted keys, and the
+        // aggregated columns are moved with them (`SortOp::payloads`)
+        let mut group_order: Option<Vec<usize>> = None;
+        let mut group_sort = None;
+        if let Some(col) = query.group_by.as_ref().and_then(|cols| cols.first()) {
+            let column_data = table_data
+                .get(&query.from)
+                .and_then(|t| t.get(col))
+                .ok_or_else(|| format!("Column {} not found in table {}", col, query.from))?;
+
+            let mut order: Vec<usize> = (0..column_data.len()).collect();
+            order.sort_by_key(|&row| column_data[row]);
+
+            group_sort = Some(compiled.sorts.len());
+            compiled.sorts.push(SortOp {
+                input: column_data.iter().map(|&v| Value::known(v)).collect(),
+                sorted_output: order.iter().map(|&row| column_data[row]).collect(),
+                null_flags: Vec::new(),
+                nulls_first: false,
+                descending: false,
+                bitonic: false,
+                aggregation: None,
+                column: CommittedDatabase::column_index(table_data, &query.from, col),
+                rows: None,
+                payloads: Vec::new(),
+            });
+            group_order = Some(order);
+
+            let group_keys = Self::in_group_order(column_data, group_order.as_deref(), col)?;
+            compiled.group_bys.push(GroupByOp {
+                sort: group_sort,
+                ..GroupByOp::new(group_keys)
+            });
+        }
+
+        // Compile aggregation operations
+        // Every aggregate shares the GROUP BY column's keys, so the
+        // circuit proves the grouping once for all of them (see `AggregateSpec`)
+        if let Some(aggregations) = &query.aggregations {
+            let group_by = (!compiled.group_bys.is_empty()).then_some(0);
+            let group_keys = compiled
+                .group_bys
+                .first()
+                .map(|group_by| group_by.group_keys.clone())
+                .unwrap_or_default();
+
+            for agg in aggregations {
+                let table = table_data
+                    .get(&query.from)
+                    .ok_or_else(|| format!("Table {} not found", query.from))?;
+                let count_rows = agg.column == "*" && agg.function == AggregationFunction::Count;
+
+                // Aggregation as named in errors (e.g. SUM(amount))
+                let label = format!(
+                    "{}({})",
+                    agg.function.to_string().to_uppercase(),
+                    agg.column
+                );
+
+                // WHERE check of each row in group order and its bit
+                // (1 = row matches), copied from the range check cells
+                let filter = |compiled: &CompiledQuery| -> Result<(Vec<usize>, Vec<u64>), String> {
+                    let rows = table.values().next().map_or(0, Vec::len);
+                    let checks = Self::count_filter(query, compiled, rows, &label)?;
+                    let checks: Vec<usize> = match &group_order {
+                        Some(order) => order.iter().map(|&row| checks[row]).collect(),
+                        None => checks,
+                    };
+                    let bits = checks
+                        .iter()
+                        .map(|&check| {
+                            Self::check_bit(
+                                &compiled.range_checks[check],
+                                &compiled.set_memberships,
+                            )
+                        })
+                        .collect();
+                    Ok((checks, bits))
+                };
+
+                // COUNT(*) with WHERE sums the WHERE check bits (or, over all
+                // rows, the keep flags of an [NOT] EXISTS semi-join)
+                let mut value_checks = None;
+                let mut value_semi_join = None;
+                let mut unfiltered = None;
+                let mut bits = Vec::new();
+                // Raw column whose rows the values (or unfiltered values) are
+                let mut raw_column = None;
+                let values = if count_rows && query.where_clause.is_some() {
+                    match (&query.where_clause, compiled.semi_joins.last()) {
+                        (Some(WhereClause::Exists { .. }), Some(op)) if group_order.is_none() => {
+                            value_semi_join = Some(compiled.semi_joins.len() - 1);
+                            op.kept_flags()
+                        }
+                        _ => {
+                            let (checks, filter_bits) = filter(&compiled)?;
+                            value_checks = Some(checks);
+                            filter_bits
+                        }
+                    }
+                } else if count_rows {
+                    // COUNT(*) counts rows: a column of ones
+                    let rows = match &group_order {
+                        Some(order) => order.len(),
+                        None => table.values().next().map_or(0, Vec::len),
+                    };
+                    vec![1; rows]
+                } else if agg.distinct {
+                    // COUNT(DISTINCT product) sums the first row of each
+                    // (group key, product) run
+                    if query.where_clause.is_some() {
+                        return Err("COUNT(DISTINCT) with WHERE is not supported".to_string());
+                    }
+                    Self::distinct_flags(query, table, &agg.column, &mut compiled)?
+                } else {
+                    let values = if let Some(expr) = agg.expr() {
+                        // SUM(price * quantity): the argument is evaluated per row
+                        // (products and divisions proven) into a derived column
+                        let column_data =
+                            Self::expr_values(query, &expr, table_data, &mut compiled)?;
+                        Self::in_group_order(&column_data, group_order.as_deref(), &agg.column)?
+                    } else {
+                        let column_data = table.get(&agg.column).ok_or_else(|| {
+                            format!("Column {} not found in table {}", agg.column, query.from)
+                        })?;
+                        raw_column = Some(column_data);
+                        // Rows in group order (the sorted GROUP BY column, values co-sorted)
+                        Self::in_group_order(column_data, group_order.as_deref(), &agg.column)?
+                    };
+
+                    // With WHERE, a SUM / AVG sums value · bit (its COUNT the
+                    // bits) and a COUNT sums the bits; a MAX / MIN has no
+                    // neutral value for the dropped rows
+                    if query.where_clause.is_none() {
+                        values
+                    } else {
+                        match agg.function {
+                            AggregationFunction::Max | AggregationFunction::Min => {
+                                return Err(format!("{} with WHERE is not supported", label));
+                            }
+                            AggregationFunction::Count if values.contains(&NULL) => {
+                                return Err(format!(
+                                    "{} with WHERE needs a column without NULL",
+                                    label
+                                ));
+                            }
+                            AggregationFunction::Count => {
+                                let (checks, filter_bits) = filter(&compiled)?;
+                                value_checks = Some(checks);
+                                raw_column = None;
+                                filter_bits
+                            }
+                            AggregationFunction::Sum | AggregationFunction::Avg => {
+                                let (checks, filter_bits) = filter(&compiled)?;
+                                let filtered = values
+                                    .iter()
+                                    .zip(&filter_bits)
+                                    .map(|(&value, &bit)| value * bit)
+                                    .collect();
+                                value_checks = Some(checks);
+                                unfiltered = Some(values);
+                                bits = filter_bits;
+                                filtered
+                            }
+                        }
+                    }
+                };
+
+                // Values in row order of a column (COUNT(*): of its table's
+                // first column) are copied from the committed column
+                let column = match &group_order {
+                    Some(_) => None,
+                    None if count_rows => table.keys().min().and_then(|column| {
+                        CommittedDatabase::column_index(table_data, &query.from, column)
+                    }),
+                    None if agg.distinct || agg.expr().is_some() => None,
+                    None => CommittedDatabase::column_index(table_data, &query.from, &agg.column),
+                };
+
+                // AVG is proven as SUM and COUNT over the same groups
+                // (a SUM above u64::MAX is proven with 128-bit running results)
+                let functions: &[AggregationFunction] = match agg.function {
+                    // A filtered count is the sum of its bits, a distinct
+                    // count the sum of its run starts
+                    AggregationFunction::Count
+                        if value_checks.is_some() || value_semi_join.is_some() || agg.distinct =>
+                    {
+                        &[AggregationFunction::Sum]
+                    }
+                    AggregationFunction::Avg => {
+                        &[AggregationFunction::Sum, AggregationFunction::Count]
+                    }
+                    ref function => std::slice::from_ref(function),
+                };
+
+                // Arg-max: the payload column, co-sorted with the values
+                let payload = match query.arg_extremum() {
+                    Some((_, column)) => {
+                        if values.contains(&NULL) {
+                            return Err(format!(
+                                "Column {} of an arg-max must not hold NULL",
+                                agg.column
+                            ));
+                        }
+                        let column_data = table.get(column).ok_or_else(|| {
+                            format!("Column {} not found in table {}", column, query.from)
+                        })?;
+                        Some(Self::in_group_order(
+                            column_data,
+                            group_order.as_deref(),
+                            column,
+                        )?)
+                    }
+                    None => None,
+                };
+
+                // Grouped values of a raw column are copied from a payload of
+                // the GROUP BY sort (an arg-max proves its own grouping)
+                let group_sort = group_sort.filter(|_| payload.is_none());
+                let sort_payload = match (group_sort, raw_column) {
+                    (Some(sort), Some(column_data)) => {
+                        let payloads = &mut compiled.sorts[sort].payloads;
+                        let position = payloads
+                            .iter()
+                            .position(|payload| &payload.values == column_data);
+                        Some(position.unwrap_or_else(|| {
+                            payloads.push(SortPayload {
+                                values: column_data.clone(),
+                                column: CommittedDatabase::column_index(
+                                    table_data,
+                                    &query.from,
+                                    &agg.column,
+                                ),
+                            });
+                            payloads.len() - 1
+                        }))
+                    }
+                    _ => None,
+                };
+
+                let mut indices = Vec::with_capacity(functions.len());
+                for &function in functions {
+                    // The COUNT of a filtered AVG sums the bits
+                    let (function, values, unfiltered, column, sort_payload) =
+                        match (&unfiltered, function) {
+                            (Some(_), AggregationFunction::Count) => {
+                                (AggregationFunction::Sum, bits.clone(), None, None, None)
+                            }
+                            _ => (
+                                function,
+                                values.clone(),
+                                unfiltered.clone(),
+                                column,
+                                sort_payload,
+                            ),
+                        };
+                    indices.push(compiled.aggregations.len());
+                    compiled.aggregations.push(AggregationOp {
+                        group_keys: group_keys.clone(),
+                        values,
+                        function,
+                        value_checks: value_checks.clone(),
+                        value_semi_join,
+                        unfiltered,
+                        column,
+                        expected: None,
+                        payload: payload.clone(),
+                        group_sort,
+                        sort_payload,
+                    });
+                }
+
+                compiled.aggregate_specs.push(AggregateSpec {
+                    aggregation: agg.clone(),
+                    group_by,
+                    aggregations: indices,
                 });
+            }
+        }
+
+        // Convert HAVING clause to a predicate on the per-group aggregation
+        // results: one range per comparison, combined with the clause's
+        // AND / OR
+        if let Some(having) = &query.having {
+            let predicate = Self::having_predicate(query, having, &compiled)?;
+            compiled.having.push(HavingOp {
+                predicate,
+                public_count: false,
+            });
+        }
+
+        // ORDER BY <aggregate> LIMIT k: the groups are ranked by a top-K
+        // operation, which sorts the aggregation's result cells themselves,
+        // and the first k ranked results are returned
+        let top_k = match (query.limit, query.order_by.as_deref()) {
+            (Some(limit), Some([order])) => {
+                SQLParser::parse_aggregation(&order.column).map(|agg| (limit, order, agg))
+            }
+            _ => None,
+        };
+
+        // Convert ORDER BY clause to sort operations
+        // Note: Compiled after aggregations so that ORDER BY can reference
+        // an aggregation result (e.g. ORDER BY sum(amount) DESC)
+        if let Some((limit, order, order_agg)) = top_k {
+            if query.having.is_some() {
+                return Err(
+                    "LIMIT over aggregation results cannot be combined with HAVING".to_string(),
+                );
+            }
+            let aggregation = Self::aggregation_index(query, &order_agg, &compiled)?;
+            Self::check_narrow(&compiled.aggregations[aggregation])?;
+            let mut sorted_results = compiled.aggregations[aggregation].group_results();
+            if sorted_results.contains(&NULL) {
+                return Err("LIMIT cannot rank a NULL aggregation result".to_string());
+            }
+            let descending = order.direction == OrderDirection::Desc;
+            if descending {
+                sorted_results.sort_by(|a, b| b.cmp(a));
             } else {
-                // Default: ASC
-                orders.push(OrderBy {
-                    column: part.to_string(),
-                    direction: OrderDirection::Asc,
+                sorted_results.sort();
+            }
+            compiled.top_ks.push(TopKOp {
+                aggregation,
+                selected: sorted_results.iter().take(limit).copied().collect(),
+                sorted_results,
+                descending,
+            });
+        } else if let Some(order_by) = &query.order_by {
+            for order in order_by {
+                let order_agg = SQLParser::parse_aggregation(&order.column);
+                let order_expr = order
+                    .expr()
+                    .or_else(|| query.projection_expr(&order.column));
+                let mut aggregation = None;
+                let column_data = match (order_agg, order_expr) {
+                    // Aggregation result: sort the per-group result cells
+                    (Some(order_agg), _) => {
+                        let (index, results) =
+                            Self::aggregation_results(query, &order_agg, &compiled)?;
+                        aggregation = Some(index);
+                        results
+                    }
+                    // Computed key (or a computed SELECT column by name):
+                    // materialized per row, then sorted
+                    (None, Some(expr)) => {
+                        Self::expr_column(query, "ORDER BY", &expr, table_data, &mut compiled)?
+                    }
+                    // Raw column
+                    (None, None) => table_data
+                        .get(&query.from)
+                        .and_then(|t| t.get(&order.column))
+                        .ok_or_else(|| {
+                            format!("Column {} not found in table {}", order.column, query.from)
+                        })?
+                        .clone(),
+                };
+
+                // Sort the non-null values in the requested direction
+                // DESC is sorted descending directly and proven with the
+                // descending sort order check
+                let mut sorted: Vec<u64> =
+                    column_data.iter().copied().filter(|&v| v != NULL).collect();
+                let descending = order.direction == OrderDirection::Desc;
+                if descending {
+                    sorted.sort_by(|a, b| b.cmp(a));
+                } else {
+                    sorted.sort();
+                }
+
+                // Place NULL rows (is_null = 1, proven from the NULL value) at
+                // the requested end
+                let null_count = column_data.len() - sorted.len();
+                let nulls_first = order.nulls == NullsOrder::First;
+                let null_flags = if null_count == 0 {
+                    Vec::new()
+                } else {
+                    let mut flags = vec![false; sorted.len()];
+                    if nulls_first {
+                        sorted.splice(0..0, vec![NULL; null_count]);
+                        flags.splice(0..0, vec![true; null_count]);
+                    } else {
+                        sorted.extend(vec![NULL; null_count]);
+                        flags.extend(vec![true; null_count]);
+                    }
+                    flags
+                };
+
+                compiled.sorts.push(SortOp {
+                    input: column_data.iter().map(|&v| Value::known(v)).collect(),
+                    sorted_output: sorted,
+                    null_flags,
+                    nulls_first,
+                    descending,
+                    bitonic: false,
+                    aggregation,
+                    column: None,
+                    rows: None,
+                    payloads: Vec::new(),
+                });
+            }
+        }
+
+        // Compile computed SELECT columns (`a + b AS total`)
+        // The operand holding each value is kept for the result commitment
+        let mut projection_operands = Vec::new();
+        for projection in query.projections.iter().flatten() {
+            let cells = Self::expr_column_cells(
+                query,
+                "SELECT",
+                &projection.expr,
+                table_data,
+                &mut compiled,
+            )?;
+            let (values, operands) = cells.into_iter().unzip();
+            compiled.projections.push((projection.name.clone(), values));
+            projection_operands.push(operands);
+        }
+
+        // Compile window functions
+        // Rows are put in window order (proven by a Sort Gate on the ORDER BY column)
+        // and the window is evaluated over them. With PARTITION BY, the sort key
+        // packs the partition key (high 32 bits) with the ORDER BY key, like
+        // COUNT(DISTINCT x), and the window restarts at each new partition
+        if let Some(windows) = &query.windows {
+            for window in windows {
+                let table = table_data
+                    .get(&query.from)
+                    .ok_or_else(|| format!("Table {} not found", query.from))?;
+                let column = |name: &str| {
+                    table
+                        .get(name)
+                        .ok_or_else(|| format!("Column {} not found in table {}", name, query.from))
+                };
+                let order_keys = column(&window.order_by)?;
+                let values = match &window.column {
+                    Some(name) => column(name)?,
+                    None => order_keys,
+                };
+                if order_keys.len() != values.len() {
+                    return Err(format!(
+                        "Columns {} and {} have different lengths",
+                        window.order_by,
+                        window.column.as_deref().unwrap_or_default()
+                    ));
+                }
+
+                let partition_keys = match &window.partition_by {
+                    Some(partition_by) => {
+                        let partition_keys = column(partition_by)?;
+                        if partition_keys.len() != order_keys.len() {
+                            return Err(format!(
+                                "Columns {} and {} have different lengths",
+                                partition_by, window.order_by
+                            ));
+                        }
+                        if partition_keys
+                            .iter()
+                            .chain(order_keys)
+                            .any(|&key| key >> 32 != 0)
+                        {
+                            return Err(format!(
+                                "PARTITION BY {} ORDER BY {} needs keys below 2^32 (no NULLs)",
+                                partition_by, window.order_by
+                            ));
+                        }
+                        Some(partition_keys)
+                    }
+                    None => None,
+                };
+                let sort_keys: Vec<u64> = match partition_keys {
+                    Some(partition_keys) => partition_keys
+                        .iter()
+                        .zip(order_keys)
+                        .map(|(&partition, &order)| partition << 32 | order)
+                        .collect(),
+                    None => order_keys.clone(),
+                };
+
+                // Stable sort of row indices by the sort key; a running sum's
+                // column (and the partition and ORDER BY columns of a packed
+                // key) is moved with the rows (a sort payload)
+                let mut order: Vec<usize> = (0..sort_keys.len()).collect();
+                order.sort_by_key(|&i| sort_keys[i]);
+                let column_index =
+                    |name: &str| CommittedDatabase::column_index(table_data, &query.from, name);
+                let mut payloads: Vec<SortPayload> = window
+                    .column
+                    .iter()
+                    .map(|name| SortPayload {
+                        values: values.clone(),
+                        column: column_index(name),
+                    })
+                    .collect();
+                let sort_payload = (!payloads.is_empty()).then_some(0);
+                let partition = match (partition_keys, &window.partition_by) {
+                    (Some(partition_keys), Some(partition_by)) => {
+                        payloads.push(SortPayload {
+                            values: partition_keys.clone(),
+                            column: column_index(partition_by),
+                        });
+                        payloads.push(SortPayload {
+                            values: order_keys.clone(),
+                            column: column_index(&window.order_by),
+                        });
+                        Some(WindowPartition {
+                            keys: order.iter().map(|&i| partition_keys[i]).collect(),
+                            key_payload: payloads.len() - 2,
+                            order_payload: payloads.len() - 1,
+                        })
+                    }
+                    _ => None,
+                };
+
+                let sort = compiled.sorts.len();
+                compiled.sorts.push(SortOp {
+                    input: sort_keys.iter().map(|&v| Value::known(v)).collect(),
+                    sorted_output: order.iter().map(|&i| sort_keys[i]).collect(),
+                    null_flags: Vec::new(),
+                    nulls_first: false,
+                    descending: false,
+                    bitonic: false,
+                    aggregation: None,
+                    // A packed key is not a committed column
+                    column: match partition {
+                        Some(_) => None,
+                        None => column_index(&window.order_by),
+                    },
+                    rows: None,
+                    payloads,
+                });
+                let mut window_op = WindowOp {
+                    function: window.function.clone(),
+                    values: order.iter().map(|&i| values[i]).collect(),
+                    sort: Some(sort),
+                    sort_payload,
+                    expected: None,
+                    partition,
+                };
+                // The Window Gate proves 64-bit running totals only (per
+                // partition; the results saturate above u64::MAX)
+                let mut total = 0u128;
+                for (i, &value) in window_op.values.iter().enumerate() {
+                    let restarts = window_op.partition.as_ref().is_some_and(|partition| {
+                        i > 0 && partition.keys[i] != partition.keys[i - 1]
+                    });
+                    if restarts {
+                        total = 0;
+                    }
+                    total += value as u128;
+                    if window_op.function == WindowFunction::RunningSum && total > u64::MAX as u128
+                    {
+                        return Err("Running SUM exceeds u64::MAX".to_string());
+                    }
+                }
+                window_op.expected = Some(window_op.results());
+                compiled.windows.push(window_op);
+            }
+        }
+
+        // Compile JOIN operations
+        if let Some(joins) = &query.joins {
+            for join in joins {
+                // Join Gate matches on an equality key
+                if join.join_type == JoinType::Cross {
+                    return Err(format!(
+                        "Cross join with {} needs an equality predicate in WHERE (e.g. {}.x = {}.y)",
+                        join.table, query.from, join.table
+                    ));
+                }
+
+                let left_table = table_data
+                    .get(&query.from)
+                    .ok_or_else(|| format!("Table {} not found", query.from))?;
+                let right_table = table_data
+                    .get(&join.table)
+                    .ok_or_else(|| format!("Table {} not found", join.table))?;
+
+                let left_keys = left_table
+                    .get(&join.on.left_column)
+                    .ok_or_else(|| {
+                        format!(
+                            "Column {} not found in table {}",
+                            join.on.left_column, query.from
+                        )
+                    })?
+                    .clone();
+                let right_keys = right_table
+                    .get(&join.on.right_column)
+                    .ok_or_else(|| {
+                        format!(
+                            "Column {} not found in table {}",
+                            join.on.right_column, join.table
+                        )
+                    })?
+                    .clone();
+
+                // Use first column for values (simple implementation), by
+                // name so that the value columns do not follow map order
+                let first_column = |table: &HashMap<String, Vec<u64>>| {
+                    table
+                        .iter()
+                        .min_by_key(|(column, _)| *column)
+                        .map(|(column, values)| (column.clone(), values.clone()))
+                        .unwrap_or_default()
+                };
+                let (left_value_column, left_values) = first_column(left_table);
+                let (right_value_column, right_values) = first_column(right_table);
+
+                // Committed columns of the four join inputs
+                let index = |table: &str, column: &str| {
+                    CommittedDatabase::column_index(table_data, table, column)
+                };
+                let columns = [
+                    index(&query.from, &join.on.left_column),
+                    index(&query.from, &left_value_column),
+                    index(&join.table, &join.on.right_column),
+                    index(&join.table, &right_value_column),
+                ];
+
+                compiled.joins.push(JoinOp {
+                    table1_keys: left_keys,
+                    table1_values: left_values,
+                    table2_keys: right_keys,
+                    table2_values: right_values,
+                    columns: match columns {
+                        [Some(a), Some(b), Some(c), Some(d)] => Some([a, b, c, d]),
+                        _ => None,
+                    },
                 });
             }
         }
 
-        Ok(orders)
+        compiled.result =
+            Self::result_commitment(query, table_data, &compiled, &projection_operands);
+        Ok(compiled)
+    }
+
+    /// Result rows of a query computed from its compiled operations, each
+    /// cell copied from the proven cell holding it (`ResultSource`)
+    ///
+    /// Supported queries: aggregates over all rows (one row) or per GROUP BY
+    /// group (the group key and the aggregates, one row per group in key
+    /// order), and columns or computed columns of the FROM rows, optionally
+    /// filtered by a WHERE clause that is a single comparison or bounded
+    /// range (`ResultCommitment::filter`). None for any other query (HAVING,
+    /// ORDER BY, joins, windows, AVG, a 128-bit SUM, `*`, ...), whose rows
+    /// are not tied to proven cells; a LIMIT query's rows are
+    /// `limited_result`'s.
+    fn result_commitment(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &CompiledQuery,
+        projection_operands: &[Vec<Operand>],
+    ) -> Option<ResultCommitment> {
+        if query.having.is_some()
+            || query.order_by.is_some()
+            || query.limit.is_some()
+            || query.joins.is_some()
+            || query.windows.is_some()
+            || query.union.is_some()
+            || !compiled.semi_joins.is_empty()
+        {
+            return None;
+        }
+
+        // Aggregates: one row per group
+        if let Some(aggregations) = &query.aggregations {
+            let group_by = query.group_by.as_ref().and_then(|columns| columns.first());
+            // Group keys are the GROUP BY sort's output cells
+            let (key_aggregation, key_op) = compiled
+                .aggregations
+                .iter()
+                .enumerate()
+                .find(|(_, op)| op.group_sort.is_some())
+                .filter(|_| group_by.is_some())
+                .map_or((None, None), |(index, op)| (Some(index), Some(op)));
+            let groups = compiled.aggregations.first()?.group_results().len();
+            let columns = query
+                .columns
+                .iter()
+                .map(|item| {
+                    if Some(item) == group_by {
+                        let (aggregation, op) = (key_aggregation?, key_op?);
+                        let rows = op.group_last_rows();
+                        return Some(
+                            (0..groups)
+                                .map(|group| {
+                                    let source = ResultSource::GroupKey { aggregation, group };
+                                    (op.group_keys[rows[group]], source)
+                                })
+                                .collect::<Vec<_>>(),
+                        );
+                    }
+                    let clause = SQLParser::parse_aggregation(item)?;
+                    let position = aggregations.iter().position(|agg| *agg == clause)?;
+                    let aggregation = match compiled.aggregate_specs.get(position)? {
+                        AggregateSpec {
+                            aggregations: indices,
+                            ..
+                        } if indices.len() == 1 => indices[0],
+                        _ => return None,
+                    };
+                    let op = compiled.aggregations.get(aggregation)?;
+                    let results = op.group_results();
+                    if op.is_wide() || results.len() != groups {
+                        return None;
+                    }
+                    Some(
+                        results
+                            .into_iter()
+                            .enumerate()
+                            .map(|(group, value)| {
+                                (value, ResultSource::Aggregation { aggregation, group })
+                            })
+                            .collect(),
+                    )
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let rows = (0..groups)
+                .map(|group| columns.iter().map(|column| column[group]).collect())
+                .collect();
+            return Some(ResultCommitment::sourced(rows));
+        }
+        if query.group_by.is_some() {
+            return None;
+        }
+
+        // Columns of the FROM rows, each copied from its committed or
+        // computed cell
+        let table = table_data.get(&query.from)?;
+        let row_count = table.values().next().map_or(0, Vec::len);
+        let columns = query
+            .columns
+            .iter()
+            .map(|item| {
+                if let Some(j) = query
+                    .projections
+                    .iter()
+                    .flatten()
+                    .position(|projection| projection.name == *item)
+                {
+                    let values = &compiled.projections.get(j)?.1;
+                    let operands = projection_operands.get(j)?;
+                    return Some(
+                        values
+                            .iter()
+                            .zip(operands)
+                            .map(|(&value, &operand)| (value, ResultSource::Operand(operand)))
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                let values = table.get(item).filter(|values| values.len() == row_count)?;
+                let column = CommittedDatabase::column_index(table_data, &query.from, item)?;
+                Some(
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(row, &value)| {
+                            (
+                                value,
+                                ResultSource::Operand(Operand::Committed(column, row)),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // WHERE: the check bit of each FROM row decides whether it is a result row
+        let filter = match &query.where_clause {
+            Some(_) => Some(Self::result_filter(query, compiled, row_count)?),
+            None => None,
+        };
+        let rows = (0..row_count)
+            .filter(|&row| filter.as_ref().is_none_or(|bits| bits[row].1 == 1))
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect();
+        Some(ResultCommitment {
+            filter,
+            ..ResultCommitment::sourced(rows)
+        })
+    }
+
+    /// Result rows of a LIMIT query over the FROM rows: the first `limit`
+    /// rows matching its WHERE clause in ORDER BY order (row order without
+    /// ORDER BY), each cell copied from a proven cell (`ResultSource`)
+    ///
+    /// The WHERE check bits are fixed by the filter
+    /// (`ResultCommitment::filter`), so the matching rows are exactly those
+    /// whose bit is 1, and the match count is hashed after the rows
+    /// (`ResultCommitment::matched`). Without ORDER BY the rows are copied
+    /// from the committed cells of the first matching rows. With ORDER BY,
+    /// the ORDER BY sort (`sorts[0]`) is replaced by a sort of the
+    /// matching rows' committed cells (`SortOp::rows`) moving the selected
+    /// columns as payloads, and the rows are copied from its first sorted
+    /// rows (`ResultSource::Payload`).
+    ///
+    /// Supported queries: columns of the FROM table (no `*` or computed
+    /// columns) with a match count (`MatchCountOp`) and at most one ORDER BY
+    /// column of the FROM table without NULL in the matching rows. None for
+    /// any other query, whose rows are not tied to proven cells.
+    fn limited_result(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &mut CompiledQuery,
+        limit: usize,
+    ) -> Option<ResultCommitment> {
+        if query.aggregations.is_some()
+            || query.group_by.is_some()
+            || query.having.is_some()
+            || query.joins.is_some()
+            || query.windows.is_some()
+            || query.union.is_some()
+            || query.projections.is_some()
+            || !compiled.semi_joins.is_empty()
+        {
+            return None;
+        }
+        let matched = compiled.match_count.as_ref()?.count();
+        if matched == 0 {
+            // No WHERE checks are left (see `compile`): the count proves no rows
+            return Some(ResultCommitment {
+                matched: Some(0),
+                ..ResultCommitment::sourced(Vec::new())
+            });
+        }
+
+        let table = table_data.get(&query.from)?;
+        let row_count = table.values().next().map_or(0, Vec::len);
+        let filter = Self::result_filter(query, compiled, row_count)?;
+        let matching: Vec<usize> = (0..row_count).filter(|&row| filter[row].1 == 1).collect();
+
+        // Values and committed column of each selected column
+        let columns = query
+            .columns
+            .iter()
+            .map(|item| {
+                let values = table.get(item).filter(|values| values.len() == row_count)?;
+                let column = CommittedDatabase::column_index(table_data, &query.from, item)?;
+                Some((values, column))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let rows = match query.order_by.as_deref() {
+            None => matching
+                .iter()
+                .take(limit)
+                .map(|&row| {
+                    columns
+                        .iter()
+                        .map(|&(values, column)| {
+                            (
+                                values[row],
+                                ResultSource::Operand(Operand::Committed(column, row)),
+                            )
+                        })
+                        .collect()
+                })
+                .collect(),
+            Some([order]) => {
+                // The ORDER BY sort is the only one without aggregations
+                if order.expr().is_some() || compiled.sorts.len() != 1 {
+                    return None;
+                }
+                let keys = table.get(&order.column)?;
+                if matching.iter().any(|&row| keys[row] == NULL) {
+                    return None;
+                }
+                let key_column =
+                    CommittedDatabase::column_index(table_data, &query.from, &order.column)?;
+
+                // Stable, as the reference executor's ORDER BY
+                let descending = order.direction == OrderDirection::Desc;
+                let mut sorted = matching.clone();
+                if descending {
+                    sorted.sort_by(|&a, &b| keys[b].cmp(&keys[a]));
+                } else {
+                    sorted.sort_by_key(|&row| keys[row]);
+                }
+
+                compiled.sorts[0] = SortOp {
+                    input: matching
+                        .iter()
+                        .map(|&row| Value::known(keys[row]))
+                        .collect(),
+                    sorted_output: sorted.iter().map(|&row| keys[row]).collect(),
+                    null_flags: Vec::new(),
+                    nulls_first: false,
+                    descending,
+                    bitonic: false,
+                    aggregation: None,
+                    column: Some(key_column),
+                    rows: Some(matching.clone()),
+                    payloads: columns
+                        .iter()
+                        .map(|&(values, column)| SortPayload {
+                            values: matching.iter().map(|&row| values[row]).collect(),
+                            column: Some(column),
+                        })
+                        .collect(),
+                };
+                sorted
+                    .iter()
+                    .take(limit)
+                    .enumerate()
+                    .map(|(row, &source_row)| {
+                        columns
+                            .iter()
+                            .enumerate()
+                            .map(|(payload, &(values, _))| {
+                                let source = ResultSource::Payload {
+                                    sort: 0,
+                                    payload,
+                                    row,
+                                };
+                                (values[source_row], source)
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+            Some(_) => return None,
+        };
+        Some(ResultCommitment {
+            filter: Some(filter),
+            matched: Some(matched),
+            ..ResultCommitment::sourced(rows)
+        })
+    }
+
+    /// (range check, check bit) of each FROM row of a WHERE clause that is a
+    /// single comparison or bounded range (see `count_filter`); None for any
+    /// other WHERE clause
+    fn result_filter(
+        query: &SQLQuery,
+        compiled: &CompiledQuery,
+        rows: usize,
+    ) -> Option<Vec<(usize, u64)>> {
+        let checks = Self::count_filter(query, compiled, rows, "Result").ok()?;
+        Some(
+            checks
+                .into_iter()
+                .map(|check| {
+                    let bit =
+                        Self::check_bit(&compiled.range_checks[check], &compiled.set_memberships);
+                    (check, bit)
+                })
+                .collect(),
+        )
+    }
+
+    /// Column values in GROUP BY row order (unchanged without GROUP BY)
+    ///
+    /// The Sort Gate proves the GROUP BY column's order; other columns
+    /// are permuted with it as witnesses.
+    fn in_group_order(
+        column_data: &[u64],
+        order: Option<&[usize]>,
+        column: &str,
+    ) -> Result<Vec<u64>, String> {
+        let Some(order) = order else {
+            return Ok(column_data.to_vec());
+        };
+        if order.len() != column_data.len() {
+            return Err(format!(
+                "Column {} has {} rows, GROUP BY column has {}",
+                column,
+                column_data.len(),
+                order.len()
+            ));
+        }
+        Ok(order.iter().map(|&row| column_data[row]).collect())
+    }
+
+    /// Run-start flags of `COUNT(DISTINCT column)`, in group order
+    ///
+    /// Rows are sorted by (GROUP BY column, column), packed into one
+    /// key with the group key in the high 32 bits, and the Sort Gate proves
+    /// that order. The Group-By Gate proves where the packed key changes: a
+    /// row whose key differs from the previous one starts a new distinct
+    /// value of its group (flag 1). Both orders list the group keys
+    /// ascending, so the flags fall into the GROUP BY order's groups.
+    fn distinct_flags(
+        query: &SQLQuery,
+        table: &HashMap<String, Vec<u64>>,
+        column: &str,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Vec<u64>, String> {
+        let column_data = table
+            .get(column)
+            .ok_or_else(|| format!("Column {} not found in table {}", column, query.from))?;
+        let group_keys = match query.group_by.as_ref().and_then(|columns| columns.first()) {
+            Some(group_by) => table
+                .get(group_by)
+                .ok_or_else(|| format!("Column {} not found in table {}", group_by, query.from))?,
+            None => &vec![0; column_data.len()],
+        };
+        if group_keys.len() != column_data.len() {
+            return Err(format!(
+                "Column {} has {} rows, GROUP BY column has {}",
+                column,
+                column_data.len(),
+                group_keys.len()
+            ));
+        }
+
+        let packed = group_keys
+            .iter()
+            .zip(column_data)
+            .map(|(&key, &value)| {
+                if key >> 32 != 0 || value >> 32 != 0 {
+                    return Err(format!(
+                        "COUNT(DISTINCT {}) needs group keys and values below 2^32 (no NULLs)",
+                        column
+                    ));
+                }
+                Ok(key << 32 | value)
+            })
+            .collect::<Result<Vec<u64>, String>>()?;
+        let mut sorted = packed.clone();
+        sorted.sort();
+
+        let sort = Some(compiled.sorts.len());
+        compiled.sorts.push(SortOp {
+            input: packed.iter().map(|&key| Value::known(key)).collect(),
+            sorted_output: sorted.clone(),
+            null_flags: Vec::new(),
+            nulls_first: false,
+            descending: false,
+            bitonic: false,
+            aggregation: None,
+            column: None,
+            rows: None,
+            payloads: Vec::new(),
+        });
+        // Boundary b = 0 marks the last row of a (group key, value) run
+        compiled.group_bys.push(GroupByOp {
+            sort,
+            ..GroupByOp::new(sorted.clone())
+        });
+
+        Ok((0..sorted.len())
+            .map(|row| (row == 0 || sorted[row] != sorted[row - 1]) as u64)
+            .collect())
+    }
+
+    /// Index and per-group results of an aggregation sorted by ORDER BY
+    ///
+    /// The sort copies the result cells, which have no NULL flag: a NULL
+    /// result (e.g. MIN over an empty group) is rejected.
+    fn aggregation_results(
+        query: &SQLQuery,
+        target: &AggregationClause,
+        compiled: &CompiledQuery,
+    ) -> Result<(usize, Vec<u64>), String> {
+        let index = Self::aggregation_index(query, target, compiled)?;
+        Self::check_narrow(&compiled.aggregations[index])?;
+        let results = compiled.aggregations[index].group_results();
+        if results.contains(&NULL) {
+            return Err("ORDER BY cannot sort a NULL aggregation result".to_string());
+        }
+        Ok((index, results))
+    }
+
+    /// Reject a 128-bit (wide SUM) aggregation where its results are
+    /// compared or sorted (64-bit gates)
+    fn check_narrow(aggregation: &AggregationOp) -> Result<(), String> {
+        if aggregation.is_wide() {
+            return Err("SUM exceeds 64 bits and cannot be compared or sorted".to_string());
+        }
+        Ok(())
+    }
+
+    /// HAVING predicate of a clause: each comparison becomes a range of its
+    /// aggregation's group results (`lower <= result < upper`)
+    fn having_predicate(
+        query: &SQLQuery,
+        having: &HavingClause,
+        compiled: &CompiledQuery,
+    ) -> Result<HavingPredicate, String> {
+        let (aggregation, operator, value) = match having {
+            HavingClause::Compare {
+                aggregation,
+                operator,
+                value,
+            } => (aggregation, operator, *value),
+            HavingClause::And(left, right) => {
+                return Ok(HavingPredicate::And(
+                    Box::new(Self::having_predicate(query, left, compiled)?),
+                    Box::new(Self::having_predicate(query, right, compiled)?),
+                ));
+            }
+            HavingClause::Or(left, right) => {
+                return Ok(HavingPredicate::Or(
+                    Box::new(Self::having_predicate(query, left, compiled)?),
+                    Box::new(Self::having_predicate(query, right, compiled)?),
+                ));
+            }
+        };
+        let target = SQLParser::parse_aggregation(aggregation)
+            .ok_or_else(|| format!("HAVING must compare an aggregation: {}", aggregation))?;
+        let index = Self::aggregation_index(query, &target, compiled)?;
+        Self::check_narrow(&compiled.aggregations[index])?;
+
+        // Kept groups: lower <= result < upper
+        let (lower, upper) = match operator {
+            ComparisonOp::LessThan => (None, Some(value)),
+            ComparisonOp::GreaterThan => {
+                let lower = value
+                    .checked_add(1)
+                    .ok_or("HAVING threshold out of range")?;
+                (Some(lower), None)
+            }
+            // A missing upper bound keeps every non-NULL result
+            ComparisonOp::LessEqual => (None, value.checked_add(1)),
+            ComparisonOp::GreaterEqual => (Some(value), None),
+            ComparisonOp::Equal => {
+                let upper = value
+                    .checked_add(1)
+                    .ok_or("HAVING threshold out of range")?;
+                (Some(value), Some(upper))
+            }
+        };
+        Ok(HavingPredicate::Range {
+            aggregation: index,
+            lower,
+            upper,
+        })
+    }
+
+    /// Index of an aggregation referenced outside the SELECT list
+    /// (e.g. in ORDER BY or HAVING) in `compiled.aggregations`
+    ///
+    /// The aggregation must also appear in the SELECT list, so that its
+    /// result cells are produced by the same compile. AVG refers to its SUM.
+    fn aggregation_index(
+        query: &SQLQuery,
+        target: &AggregationClause,
+        compiled: &CompiledQuery,
+    ) -> Result<usize, String> {
+        query
+            .aggregations
+            .as_ref()
+            .and_then(|aggs| aggs.iter().position(|agg| agg == target))
+            .and_then(|position| compiled.aggregate_specs.get(position))
+            .map(|spec| spec.aggregations[0])
+            .ok_or_else(|| {
+                format!(
+                    "Aggregation {:?}({}) must appear in the SELECT list",
+                    target.function, target.column
+                )
+            })
+    }
+
+    /// Range check of each FROM row deciding a filtered aggregation
+    /// (`aggregation`, e.g. COUNT(*) or SUM(amount))
+    ///
+    /// The WHERE clause must be a single comparison (constant, column or
+    /// expression) or a bounded predicate: its check bit is the row's
+    /// outcome.
+    fn count_filter(
+        query: &SQLQuery,
+        compiled: &CompiledQuery,
+        rows: usize,
+        aggregation: impl std::fmt::Display,
+    ) -> Result<Vec<usize>, String> {
+        let exact = match &query.where_clause {
+            Some(
+                WhereClause::LessThan { .. }
+                | WhereClause::GreaterThan { .. }
+                | WhereClause::LessEqual { .. }
+                | WhereClause::GreaterEqual { .. }
+                | WhereClause::Equal { .. }
+                | WhereClause::ExprCompare { .. }
+                | WhereClause::Bool { .. }
+                | WhereClause::IsNull { .. }
+                | WhereClause::IsNotNull { .. }
+                | WhereClause::ColumnCompare { .. }
+                | WhereClause::In { .. },
+            ) => true,
+            Some(WhereClause::And(left, right)) => Self::bounded_range(left, right).is_some(),
+            _ => false,
+        };
+        match &compiled.where_predicates[..] {
+            [checks] if exact && checks.len() == rows => Ok(checks.clone().collect()),
+            _ => Err(format!(
+                "{} with WHERE {} needs a single comparison or bounded range",
+                aggregation,
+                query
+                    .where_clause
+                    .as_ref()
+                    .map_or(String::new(), |w| w.to_string())
+            )),
+        }
+    }
+
+    /// Check bit a range check proves (1 = `value <comparison> threshold`,
+    /// and `lower <= value` if bounded; 1 = `value IN set` for a set
+    /// membership check against `sets`)
+    fn check_bit(op: &RangeCheckOp, sets: &[SetMembershipOp]) -> u64 {
+        let mut bit = 0;
+        op.value.map(|value| {
+            bit = match op.member_of {
+                Some(set) => sets.get(set).is_some_and(|set| set.contains(value)),
+                None => op.lower.unwrap_or(0) <= value && op.comparison.holds(value, op.threshold),
+            } as u64;
+        });
+        bit
+    }
+
+    /// Match count of a WHERE clause that is a single constant comparison
+    /// (`col < v`, `col > v`, `col <= v`, `col >= v`, `col = v`); None for
+    /// any other WHERE clause
+    fn match_count(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<Option<MatchCountOp>, String> {
+        // Kept rows: lower <= value < upper
+        let (column, lower, upper) = match &query.where_clause {
+            Some(WhereClause::LessThan { column, value }) => (column, None, Some(*value)),
+            Some(WhereClause::GreaterThan { column, value }) => match value.checked_add(1) {
+                Some(lower) => (column, Some(lower), None),
+                None => return Ok(None),
+            },
+            // A missing upper bound counts every non-NULL value
+            Some(WhereClause::LessEqual { column, value }) => (column, None, value.checked_add(1)),
+            Some(WhereClause::GreaterEqual { column, value }) => (column, Some(*value), None),
+            Some(WhereClause::Equal { column, value }) => match value.checked_add(1) {
+                Some(upper) => (column, Some(*value), Some(upper)),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        let values = table_data
+            .get(&query.from)
+            .and_then(|t| t.get(column))
+            .ok_or_else(|| format!("Column {} not found in table {}", column, query.from))?
+            .clone();
+
+        Ok(Some(MatchCountOp {
+            values,
+            lower,
+            upper,
+            column: CommittedDatabase::column_index(table_data, &query.from, column),
+        }))
+    }
+
+    /// Range check every value of a column against a constant
+    fn compile_column_comparison(
+        column: &str,
+        operator: &ComparisonOp,
+        value: u64,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        table_name: &str,
+        compiled: &mut CompiledQuery,
+    ) -> Result<(), String> {
+        let column_data = table_data
+            .get(table_name)
+            .and_then(|t| t.get(column))
+            .ok_or_else(|| format!("Column {} not found in table {}", column, table_name))?;
+
+        let column_index = CommittedDatabase::column_index(table_data, table_name, column);
+        for (row, &val) in column_data.iter().enumerate() {
+            let source = column_index.map(|column| (column, row));
+            Self::push_comparison(val, source, operator, value, None, false, compiled);
+        }
+
+        Ok(())
+    }
+
+    /// Bounds of `col > a AND col < b` (either order) on one column, as
+    /// `(col, a + 1, b)` for `lower <= col < upper`; None for any other pair
+    fn bounded_range<'a>(
+        left: &'a WhereClause,
+        right: &'a WhereClause,
+    ) -> Option<(&'a str, u64, u64)> {
+        match (left, right) {
+            (
+                WhereClause::GreaterThan { column, value: a },
+                WhereClause::LessThan {
+                    column: other,
+                    value: b,
+                },
+            )
+            | (
+                WhereClause::LessThan {
+                    column: other,
+                    value: b,
+                },
+                WhereClause::GreaterThan { column, value: a },
+            ) if column == other => Some((column, a.checked_add(1)?, *b)),
+            _ => None,
+        }
+    }
+
+    /// Check every value of a column against both bounds of
+    /// `lower <= value < upper` (one `RangeCheckOp` with `lower` per row)
+    fn compile_column_between(
+        column: &str,
+        lower: u64,
+        upper: u64,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        table_name: &str,
+        compiled: &mut CompiledQuery,
+    ) -> Result<(), String> {
+        let column_data = table_data
+            .get(table_name)
+            .and_then(|t| t.get(column))
+            .ok_or_else(|| format!("Column {} not found in table {}", column, table_name))?;
+
+        let column_index = CommittedDatabase::column_index(table_data, table_name, column);
+        for (row, &val) in column_data.iter().enumerate() {
+            compiled.range_checks.push(RangeCheckOp {
+                value: Value::known(val),
+                threshold: upper,
+                u: 0,
+                witnessed_threshold: false,
+                source: column_index.map(|column| (column, row)),
+                threshold_source: None,
+                public_operand: None,
+                lower: Some(lower),
+                comparison: Comparison::Lt,
+                expression: None,
+                member_of: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Emit the range check for `val <op> value`
+    /// `witnessed_threshold`: value is another column's value (advice cell, not fixed)
+    /// `val_source` / `value_source`: committed database cells of the operands
+    /// (None for constants and computed values)
+    ///
+    /// # Note
+    ///
+    /// A constant threshold is proven by the `RangeCheckChip::prove_*` of
+    /// its operator (`<`, `>` strict, `<=`, `>=` non-strict, `=` exact), with
+    /// no `value + 1` threshold to overflow at u64::MAX. A witnessed threshold
+    /// has the strict x < t, the x <= t and the x = t checks: `val > value`
+    /// and `val >= value` are emitted as `value < val` and `value <= val`
+    /// (operands swapped, the swapped threshold witnessed so it can be copied
+    /// from the committed database).
+    fn push_comparison(
+        val: u64,
+        val_source: Option<(usize, usize)>,
+        operator: &ComparisonOp,
+        value: u64,
+        value_source: Option<(usize, usize)>,
+        witnessed_threshold: bool,
+        compiled: &mut CompiledQuery,
+    ) {
+        let op = if witnessed_threshold {
+            // (x, t) of the check: `>` and `>=` swap the operands
+            let ((x, x_source), (t, t_source)) = match operator {
+                ComparisonOp::GreaterThan | ComparisonOp::GreaterEqual => {
+                    ((value, value_source), (val, val_source))
+                }
+                _ => ((val, val_source), (value, value_source)),
+            };
+            let comparison = match operator {
+                ComparisonOp::LessThan | ComparisonOp::GreaterThan => Comparison::Lt,
+                ComparisonOp::LessEqual | ComparisonOp::GreaterEqual => Comparison::Le,
+                ComparisonOp::Equal => Comparison::Eq,
+            };
+            // u value: t - x (if x < t)
+            RangeCheckOp {
+                value: Value::known(x),
+                threshold: t,
+                u: t.saturating_sub(x),
+                witnessed_threshold: true,
+                source: x_source,
+                threshold_source: t_source,
+                public_operand: None,
+                lower: None,
+                comparison,
+                expression: None,
+                member_of: None,
+            }
+        } else {
+            RangeCheckOp {
+                value: Value::known(val),
+                threshold: value,
+                u: 0,
+                witnessed_threshold: false,
+                source: val_source,
+                threshold_source: None,
+                public_operand: None,
+                lower: None,
+                comparison: match operator {
+                    ComparisonOp::LessThan => Comparison::Lt,
+                    ComparisonOp::GreaterThan => Comparison::Gt,
+                    ComparisonOp::LessEqual => Comparison::Le,
+                    ComparisonOp::GreaterEqual => Comparison::Ge,
+                    ComparisonOp::Equal => Comparison::Eq,
+                },
+                expression: None,
+                member_of: None,
+            }
+        };
+        compiled.range_checks.push(op);
+    }
+
+    /// Computed ORDER BY key or SELECT column of every FROM row
+    /// The expression is evaluated per row as in WHERE (products and
+    /// divisions are proven); a row with a NULL operand has a NULL value
+    fn expr_column(
+        query: &SQLQuery,
+        clause: &str,
+        expr: &Expr,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Vec<u64>, String> {
+        let cells = Self::expr_column_cells(query, clause, expr, table_data, compiled)?;
+        Ok(cells.into_iter().map(|(value, _)| value).collect())
+    }
+
+    /// `expr_column` with the operand holding each row's value
+    fn expr_column_cells(
+        query: &SQLQuery,
+        clause: &str,
+        expr: &Expr,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Vec<(u64, Operand)>, String> {
+        if query.group_by.is_some() || query.aggregations.is_some() {
+            return Err(format!(
+                "{} expression {} needs a query without GROUP BY or aggregations",
+                clause, expr
+            ));
+        }
+        Self::expr_cells(query, expr, table_data, compiled)
+    }
+
+    /// Value of an expression for every FROM row, in row order (NULL for a
+    /// row with a NULL operand)
+    fn expr_values(
+        query: &SQLQuery,
+        expr: &Expr,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Vec<u64>, String> {
+        let cells = Self::expr_cells(query, expr, table_data, compiled)?;
+        Ok(cells.into_iter().map(|(value, _)| value).collect())
     }
 
-    /// Parse aggregation function
-    fn parse_aggregation(col: &str) -> Option<AggregationClause> {
-        if col.starts_with("sum(") && col.ends_with(")") {
-            let column = col[4..col.len() - 1].trim().to_string();
-            Some(AggregationClause {
-                function: AggregationFunction::Sum,
-                column,
-            })
-        } else if col.starts_with("count(") && col.ends_with(")") {
-            let column = col[6..col.len() - 1].trim().to_string();
-            Some(AggregationClause {
-                function: AggregationFunction::Count,
-                column,
-            })
-        } else if col.starts_with("max(") && col.ends_with(")") {
-            let column = col[4..col.len() - 1].trim().to_string();
-            Some(AggregationClause {
-                function: AggregationFunction::Max,
-                column,
-            })
-        } else if col.starts_with("min(") && col.ends_with(")") {
-            let column = col[4..col.len() - 1].trim().to_string();
-            Some(AggregationClause {
-                function: AggregationFunction::Min,
-                column,
+    /// `expr_values` with the operand holding each row's value (a witness
+    /// for a NULL row)
+    fn expr_cells(
+        query: &SQLQuery,
+        expr: &Expr,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Vec<(u64, Operand)>, String> {
+        let table = table_data
+            .get(&query.from)
+            .ok_or_else(|| format!("Table {} not found", query.from))?;
+
+        let mut columns = Vec::new();
+        expr.columns(&mut columns);
+        let column_data = columns
+            .iter()
+            .map(|&column| {
+                table
+                    .get(column)
+                    .ok_or_else(|| format!("Column {} not found in table {}", column, query.from))
             })
-        } else {
-            None
+            .collect::<Result<Vec<_>, _>>()?;
+        let row_count = column_data.first().map_or(0, |values| values.len());
+
+        let mut keys = Vec::with_capacity(row_count);
+        for row in 0..row_count {
+            if column_data.iter().any(|values| values[row] == NULL) {
+                keys.push((NULL, Operand::Witness));
+                continue;
+            }
+            keys.push(Self::eval_expr(
+                expr,
+                table_data,
+                &query.from,
+                row,
+                compiled,
+            )?);
         }
+        Ok(keys)
     }
-}
-
-/// SQL Compiler
-/// Compiles SQL AST to circuit
-pub struct SQLCompiler;
 
-impl SQLCompiler {
-    /// Compile SQL query to circuit
-    /// Paper Section 3: Compiling SQL queries to ZKP circuit
+    /// Evaluate an arithmetic expression for one row
+    /// Each multiplication emits a product operation (auxiliary product cell),
+    /// each division or remainder a division operation (`a = q * b + r`),
+    /// each addition or subtraction a sum operation
     ///
-    /// # Parameters
+    /// # Returns
     ///
-    /// - `query`: Parsed SQL query
-    /// - `table_data`: Table data (column_name -> values mapping)
+    /// The value and the operand holding it: the committed cell of a column,
+    /// a constant, or the result cell of the operation computing it (each
+    /// operation's operands name the cells they are copied from)
+    fn eval_expr(
+        expr: &Expr,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+        table_name: &str,
+        row: usize,
+        compiled: &mut CompiledQuery,
+    ) -> Result<(u64, Operand), String> {
+        let overflow = || "Arithmetic overflow in expression".to_string();
+        let eval = |expr: &Expr, compiled: &mut CompiledQuery| {
+            Self::eval_expr(expr, table_data, table_name, row, compiled)
+        };
+        match expr {
+            Expr::Column(column) => {
+                let value = table_data
+                    .get(table_name)
+                    .and_then(|table| table.get(column))
+                    .and_then(|values| values.get(row))
+                    .copied()
+                    .ok_or_else(|| {
+                        format!("Column {} not found in table {}", column, table_name)
+                    })?;
+                let operand = CommittedDatabase::column_index(table_data, table_name, column)
+                    .map_or(Operand::Witness, |column| Operand::Committed(column, row));
+                Ok((value, operand))
+            }
+            Expr::Literal(value) => Ok((*value, Operand::Constant(*value))),
+            Expr::Add(left, right) | Expr::Sub(left, right) => {
+                let (left, left_operand) = eval(left, compiled)?;
+                let (right, right_operand) = eval(right, compiled)?;
+                let subtract = matches!(expr, Expr::Sub(..));
+                let value = if subtract {
+                    left.checked_sub(right)
+                        .ok_or_else(|| "Negative result in expression".to_string())?
+                } else {
+                    left.checked_add(right).ok_or_else(overflow)?
+                };
+                compiled.sums.push(SumOp {
+                    a: Value::known(left),
+                    b: Value::known(right),
+                    subtract,
+                    operands: [left_operand, right_operand],
+                });
+                Ok((value, Operand::Sum(compiled.sums.len() - 1)))
+            }
+            Expr::Mul(left, right) => {
+                let (left, left_operand) = eval(left, compiled)?;
+                let (right, right_operand) = eval(right, compiled)?;
+                compiled.products.push(ProductOp {
+                    a: Value::known(left),
+                    b: Value::known(right),
+                    operands: [left_operand, right_operand],
+                });
+                let value = left.checked_mul(right).ok_or_else(overflow)?;
+                Ok((value, Operand::Product(compiled.products.len() - 1)))
+            }
+            Expr::Div(left, right) | Expr::Mod(left, right) => {
+                let (left, left_operand) = eval(left, compiled)?;
+                let (right, right_operand) = eval(right, compiled)?;
+                let remainder = left
+                    .checked_rem(right)
+                    .ok_or_else(|| "Division by zero in expression".to_string())?;
+                compiled.divisions.push(DivisionOp {
+                    a: Value::known(left),
+                    b: Value::known(right),
+                    u: right - remainder,
+                    operands: [left_operand, right_operand],
+                });
+                let index = compiled.divisions.len() - 1;
+                Ok(match expr {
+                    Expr::Div(..) => (left / right, Operand::Quotient(index)),
+                    _ => (remainder, Operand::Remainder(index)),
+                })
+            }
+            // check * then + (1 - check) * otherwise, where check is the bit
+            // of a range check on the condition (both branches are evaluated)
+            Expr::Case {
+                condition,
+                operator,
+                value,
+                then,
+                otherwise,
+            } => {
+                let (condition, condition_operand) = eval(condition, compiled)?;
+                Self::push_comparison(condition, None, operator, *value, None, false, compiled);
+                if let Some(op) = compiled.range_checks.last_mut() {
+                    op.expression = Some(condition_operand);
+                }
+                let check = compiled
+                    .range_checks
+                    .last()
+                    .map_or(0, |op| Self::check_bit(op, &compiled.set_memberships));
+                let (then, then_operand) = eval(then, compiled)?;
+                let (otherwise, otherwise_operand) = eval(otherwise, compiled)?;
+                for (a, b, operand) in [
+                    (check, then, then_operand),
+                    (1 - check, otherwise, otherwise_operand),
+                ] {
+                    compiled.products.push(ProductOp {
+                        a: Value::known(a),
+                        b: Value::known(b),
+                        operands: [Operand::Witness, operand],
+                    });
+                }
+                Ok((if check == 1 { then } else { otherwise }, Operand::Witness))
+            }
+        }
+    }
+
+    /// Compile `query UNION [ALL] union.query`
     ///
-    /// # Returns
+    /// Both sides are compiled separately and their operations concatenated.
+    /// For UNION, the combined values are sorted (Sort Gate) and checked for
+    /// equal neighbours (Group-By Gate boundaries), which proves the
+    /// deduplicated result holds each value once.
     ///
-    /// Compiled query with circuit operations
-    pub fn compile(
+    /// # Note
+    ///
+    /// - Both sides must project the same number of plain columns
+    /// - UNION deduplication supports a single projected column (rows are
+    ///   compared as one sort key)
+    /// - Result rows are the projected columns of every FROM row
+    fn compile_union(
         query: &SQLQuery,
+        union: &UnionClause,
         table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
     ) -> Result<CompiledQuery, String> {
-        let mut compiled = CompiledQuery {
-            range_checks: Vec::new(),
-            sorts: Vec::new(),
-            group_bys: Vec::new(),
-            joins: Vec::new(),
-            aggregations: Vec::new(),
-        };
+        let mut left = query.clone();
+        left.union = None;
 
-        // Convert WHERE clause to range check operations
-        if let Some(where_clause) = &query.where_clause {
-            Self::compile_where_clause(where_clause, table_data, &query.from, &mut compiled)?;
+        if left.columns.len() != union.query.columns.len() {
+            return Err(format!(
+                "UNION queries project {} and {} columns",
+                left.columns.len(),
+                union.query.columns.len()
+            ));
         }
+        let left_rows = Self::projected_rows(&left, table_data)?;
 
-        // Convert ORDER BY clause to sort operations
-        if let Some(order_by) = &query.order_by {
-            for order in order_by {
-                let column_data = table_data
-                    .get(&query.from)
-                    .and_then(|t| t.get(&order.column))
-                    .ok_or_else(|| {
-                        format!("Column {} not found in table {}", order.column, query.from)
-                    })?;
+        let mut compiled = Self::compile_query(&left, table_data)?;
+        let right = Self::compile_query(&union.query, table_data)?;
+        let right_rows = match &right.union {
+            Some(result) => result.rows.clone(),
+            None => Self::projected_rows(&union.query, table_data)?,
+        };
+        compiled.append(right);
 
-                let mut sorted = column_data.clone();
-                match order.direction {
-                    OrderDirection::Asc => sorted.sort(),
-                    OrderDirection::Desc => {
-                        sorted.sort();
-                        sorted.reverse();
-                    }
-                }
+        let mut rows = left_rows;
+        rows.extend(right_rows);
 
-                compiled.sorts.push(SortOp {
-                    input: column_data.iter().map(|&v| Value::known(v)).collect(),
-                    sorted_output: sorted,
-                });
+        if !union.all {
+            if rows.first().is_some_and(|row| row.len() != 1) {
+                return Err("UNION deduplication supports a single projected column".to_string());
             }
-        }
 
-        // Convert GROUP BY clause to group_by operations
-        if let Some(group_by_cols) = &query.group_by {
-            for col in group_by_cols {
-                let column_data = table_data
-                    .get(&query.from)
-                    .and_then(|t| t.get(col))
-                    .ok_or_else(|| format!("Column {} not found in table {}", col, query.from))?;
+            let values: Vec<u64> = rows.iter().map(|row| row[0]).collect();
+            let mut sorted = values.clone();
+            sorted.sort();
 
-                // Extract group keys (unique values)
-                let mut group_keys: Vec<u64> = column_data.iter().copied().collect();
-                group_keys.sort();
-                group_keys.dedup();
+            let sort = Some(compiled.sorts.len());
+            compiled.sorts.push(SortOp {
+                input: values.iter().map(|&v| Value::known(v)).collect(),
+                sorted_output: sorted.clone(),
+                null_flags: Vec::new(),
+                nulls_first: false,
+                descending: false,
+                bitonic: false,
+                aggregation: None,
+                column: None,
+                rows: None,
+                payloads: Vec::new(),
+            });
+            // Boundary b = 1 marks a value equal to the next one (a duplicate)
+            compiled.group_bys.push(GroupByOp {
+                sort,
+                ..GroupByOp::new(sorted.clone())
+            });
 
-                compiled.group_bys.push(GroupByOp { group_keys });
-            }
+            sorted.dedup();
+            rows = sorted.into_iter().map(|v| vec![v]).collect();
         }
 
-        // Compile aggregation operations
-        if let Some(aggregations) = &query.aggregations {
-            for agg in aggregations {
-                let column_data = table_data
-                    .get(&query.from)
-                    .and_then(|t| t.get(&agg.column))
-                    .ok_or_else(|| {
-                        format!("Column {} not found in table {}", agg.column, query.from)
-                    })?;
-
-                // Get group keys (if GROUP BY exists)
-                let group_keys = if let Some(group_by_cols) = &query.group_by {
-                    // Use first group by column
-                    if let Some(first_col) = group_by_cols.first() {
-                        table_data
-                            .get(&query.from)
-                            .and_then(|t| t.get(first_col))
-                            .map(|v| v.clone())
-                            .unwrap_or_default()
-                    } else {
-                        Vec::new()
-                    }
-                } else {
-                    Vec::new()
-                };
-
-                let agg_type = match agg.function {
-                    AggregationFunction::Sum => "sum",
-                    AggregationFunction::Count => "count",
-                    AggregationFunction::Max => "max",
-                    AggregationFunction::Min => "min",
-                    AggregationFunction::Avg => "sum", // Use SUM for AVG, then divide by COUNT
-                };
+        compiled.union = Some(UnionResult {
+            rows,
+            all: union.all,
+        });
+        Ok(compiled)
+    }
 
-                compiled.aggregations.push(AggregationOp {
-                    group_keys,
-                    values: column_data.clone(),
-                    agg_type: agg_type.to_string(),
-                });
-            }
+    /// Projected rows of a single-table query (one value per SELECT column)
+    fn projected_rows(
+        query: &SQLQuery,
+        table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<Vec<Vec<u64>>, String> {
+        if query.aggregations.is_some()
+            || query.windows.is_some()
+            || query.joins.is_some()
+            || query.projections.is_some()
+        {
+            return Err("UNION queries must project plain columns of a single table".to_string());
         }
+        let table = table_data
+            .get(&query.from)
+            .ok_or_else(|| format!("Table {} not found", query.from))?;
+        let columns = query
+            .columns
+            .iter()
+            .map(|name| {
+                table
+                    .get(name)
+                    .ok_or_else(|| format!("Column {} not found in table {}", name, query.from))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
 
-        // Compile JOIN operations
-        if let Some(joins) = &query.joins {
-            for join in joins {
-                let left_table = table_data
-                    .get(&query.from)
-                    .ok_or_else(|| format!("Table {} not found", query.from))?;
-                let right_table = table_data
-                    .get(&join.table)
-                    .ok_or_else(|| format!("Table {} not found", join.table))?;
+        let row_count = columns.first().map_or(0, |column| column.len());
+        if columns.iter().any(|column| column.len() != row_count) {
+            return Err(format!(
+                "Columns of table {} have different lengths",
+                query.from
+            ));
+        }
+        Ok((0..row_count)
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect())
+    }
 
-                let left_keys = left_table
-                    .get(&join.on.left_column)
-                    .ok_or_else(|| {
-                        format!(
-                            "Column {} not found in table {}",
-                            join.on.left_column, query.from
-                        )
-                    })?
-                    .clone();
-                let right_keys = right_table
-                    .get(&join.on.right_column)
-                    .ok_or_else(|| {
-                        format!(
-                            "Column {} not found in table {}",
-                            join.on.right_column, join.table
-                        )
-                    })?
-                    .clone();
+    /// Correlation of an EXISTS subquery: (outer column, inner column)
+    ///
+    /// The subquery's WHERE must be a single equality between a column of
+    /// its FROM table and a column of the outer table, both qualified
+    /// (`order.customer_id = customer.id`, either side first).
+    fn correlation(subquery: &SQLQuery, outer_table: &str) -> Result<(String, String), String> {
+        let unsupported = || {
+            format!(
+                "EXISTS subquery must correlate with a single equality {}.x = {}.y",
+                subquery.from, outer_table
+            )
+        };
+        if subquery.joins.is_some()
+            || subquery.group_by.is_some()
+            || subquery.having.is_some()
+            || subquery.aggregations.is_some()
+        {
+            return Err(unsupported());
+        }
+        let Some(WhereClause::ColumnCompare {
+            left_column,
+            op: ComparisonOp::Equal,
+            right_column,
+        }) = &subquery.where_clause
+        else {
+            return Err(unsupported());
+        };
 
-                // Use first column for values (simple implementation)
-                let left_values = left_table.values().next().cloned().unwrap_or_default();
-                let right_values = right_table.values().next().cloned().unwrap_or_default();
+        let left = left_column.split_once('.').ok_or_else(unsupported)?;
+        let right = right_column.split_once('.').ok_or_else(unsupported)?;
+        let (outer, inner) = if left.0 == outer_table && right.0 == subquery.from {
+            (left.1, right.1)
+        } else if right.0 == outer_table && left.0 == subquery.from {
+            (right.1, left.1)
+        } else {
+            return Err(unsupported());
+        };
+        Ok((outer.to_string(), inner.to_string()))
+    }
 
-                compiled.joins.push(JoinOp {
-                    table1_keys: left_keys,
-                    table1_values: left_values,
-                    table2_keys: right_keys,
-                    table2_values: right_values,
-                });
-            }
+    /// Values of the committed set of `column IN (set)`: the only column of table `set`
+    fn committed_set<'a>(
+        set: &str,
+        table_data: &'a HashMap<String, HashMap<String, Vec<u64>>>,
+    ) -> Result<&'a Vec<u64>, String> {
+        let table = table_data
+            .get(set)
+            .ok_or_else(|| format!("Set table {} not found", set))?;
+        match table.values().collect::<Vec<_>>()[..] {
+            [values] => Ok(values),
+            _ => Err(format!("Set table {} must have exactly one column", set)),
         }
-
-        Ok(compiled)
     }
 
     /// Convert WHERE clause to range check operations
@@ -489,72 +4073,259 @@ impl SQLCompiler {
         table_name: &str,
         compiled: &mut CompiledQuery,
     ) -> Result<(), String> {
+        let first_check = compiled.range_checks.len();
         match where_clause {
             WhereClause::LessThan { column, value } => {
-                let column_data = table_data
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::LessThan,
+                    *value,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::GreaterThan { column, value } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::GreaterThan,
+                    *value,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::LessEqual { column, value } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::LessEqual,
+                    *value,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::GreaterEqual { column, value } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::GreaterEqual,
+                    *value,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::Equal { column, value } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::Equal,
+                    *value,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::ColumnCompare {
+                left_column,
+                op,
+                right_column,
+            } => {
+                let table = table_data
                     .get(table_name)
-                    .and_then(|t| t.get(column))
+                    .ok_or_else(|| format!("Table {} not found", table_name))?;
+                let column = |name: &str| {
+                    table
+                        .get(name)
+                        .ok_or_else(|| format!("Column {} not found in table {}", name, table_name))
+                };
+                let left_data = column(left_column)?;
+                let right_data = column(right_column)?;
+                if left_data.len() != right_data.len() {
+                    return Err(format!(
+                        "Columns {} and {} have different lengths",
+                        left_column, right_column
+                    ));
+                }
+
+                // Per-row range check whose threshold is the other column's witnessed value
+                let left_index =
+                    CommittedDatabase::column_index(table_data, table_name, left_column);
+                let right_index =
+                    CommittedDatabase::column_index(table_data, table_name, right_column);
+                for (row, (&left, &right)) in left_data.iter().zip(right_data).enumerate() {
+                    Self::push_comparison(
+                        left,
+                        left_index.map(|column| (column, row)),
+                        op,
+                        right,
+                        right_index.map(|column| (column, row)),
+                        true,
+                        compiled,
+                    );
+                }
+            }
+            WhereClause::ExprCompare {
+                expr,
+                operator,
+                value,
+            } => {
+                let table = table_data
+                    .get(table_name)
+                    .ok_or_else(|| format!("Table {} not found", table_name))?;
+
+                // Row count from the referenced columns (literal-only expressions are rejected)
+                let mut columns = Vec::new();
+                expr.columns(&mut columns);
+                let first_column = columns
+                    .first()
+                    .ok_or("WHERE expression must reference a column")?;
+                let row_count = table
+                    .get(*first_column)
                     .ok_or_else(|| {
-                        format!("Column {} not found in table {}", column, table_name)
-                    })?;
+                        format!("Column {} not found in table {}", first_column, table_name)
+                    })?
+                    .len();
 
-                for &val in column_data {
-                    // Range check: val < value
-                    // u value: value - val (if val < value)
-                    let u = if val < *value { value - val } else { 0 };
-                    compiled.range_checks.push(RangeCheckOp {
-                        value: Value::known(val),
-                        threshold: *value,
-                        u,
-                    });
+                // Evaluate the expression per row and range check the computed value
+                // (the check's value is copied from the expression's result cell)
+                for row in 0..row_count {
+                    let (val, operand) =
+                        Self::eval_expr(expr, table_data, table_name, row, compiled)?;
+                    Self::push_comparison(val, None, operator, *value, None, false, compiled);
+                    if let Some(op) = compiled.range_checks.last_mut() {
+                        op.expression = Some(operand);
+                    }
                 }
             }
-            WhereClause::GreaterThan { column, value } => {
+            WhereClause::Exists { subquery, negated } => {
+                let (outer_column, inner_column) = Self::correlation(subquery, table_name)?;
+                let column = |table: &str, name: &str| {
+                    table_data
+                        .get(table)
+                        .and_then(|t| t.get(name))
+                        .ok_or_else(|| format!("Column {} not found in table {}", name, table))
+                };
+                let left_keys = column(table_name, &outer_column)?.clone();
+                let right_keys = column(&subquery.from, &inner_column)?.clone();
+
+                // Merged sort values are 2 * key + 1 (NULL keys do not fit)
+                if left_keys
+                    .iter()
+                    .chain(&right_keys)
+                    .any(|&key| key >= 1 << 63)
+                {
+                    return Err(format!(
+                        "EXISTS keys of {}.{} and {}.{} must be below 2^63 (no NULLs)",
+                        table_name, outer_column, subquery.from, inner_column
+                    ));
+                }
+
+                compiled.semi_joins.push(SemiJoinOp {
+                    left_keys,
+                    right_keys,
+                    negated: *negated,
+                });
+            }
+            WhereClause::In { column, set } => {
                 let column_data = table_data
                     .get(table_name)
                     .and_then(|t| t.get(column))
                     .ok_or_else(|| {
                         format!("Column {} not found in table {}", column, table_name)
                     })?;
+                // NULL is never in a set: a NULL value falls in a gap
+                let set = Self::committed_set(set, table_data)?
+                    .iter()
+                    .copied()
+                    .filter(|&value| value != NULL)
+                    .collect();
+                let set_index = compiled.set_memberships.len();
+                compiled.set_memberships.push(SetMembershipOp { set });
 
-                for &val in column_data {
-                    // For range check: val > value, can check val < MAX_VALUE - value
-                    // Simple implementation: val >= value + 1 check
-                    let threshold = value + 1;
-                    let u = if val >= threshold { val - threshold } else { 0 };
+                // Per-row member bit of the column value
+                let column_index = CommittedDatabase::column_index(table_data, table_name, column);
+                for (row, &val) in column_data.iter().enumerate() {
                     compiled.range_checks.push(RangeCheckOp {
                         value: Value::known(val),
-                        threshold,
-                        u,
+                        threshold: 0,
+                        u: 0,
+                        witnessed_threshold: false,
+                        source: column_index.map(|column| (column, row)),
+                        threshold_source: None,
+                        public_operand: None,
+                        lower: None,
+                        comparison: Comparison::Eq,
+                        expression: None,
+                        member_of: Some(set_index),
                     });
                 }
             }
-            WhereClause::Equal { column, value } => {
+            WhereClause::Bool { column, value } => {
                 let column_data = table_data
                     .get(table_name)
                     .and_then(|t| t.get(column))
                     .ok_or_else(|| {
                         format!("Column {} not found in table {}", column, table_name)
                     })?;
+                if let Some(row) = column_data.iter().position(|&value| value > 1) {
+                    return Err(format!(
+                        "Boolean column {} holds {} in row {}, expected 0 or 1",
+                        column, column_data[row], row
+                    ));
+                }
 
-                for &val in column_data {
-                    // Equality check: val == value
-                    // Range check ile: val < value + 1 && val >= value
+                // Per-row boolean check (no decomposition): value = expected
+                let column_index = CommittedDatabase::column_index(table_data, table_name, column);
+                for (row, &val) in column_data.iter().enumerate() {
                     compiled.range_checks.push(RangeCheckOp {
                         value: Value::known(val),
-                        threshold: value + 1,
-                        u: if val < value + 1 {
-                            (value + 1) - val
-                        } else {
-                            0
-                        },
+                        threshold: *value as u64,
+                        u: 0,
+                        witnessed_threshold: false,
+                        source: column_index.map(|column| (column, row)),
+                        threshold_source: None,
+                        public_operand: None,
+                        lower: None,
+                        comparison: Comparison::Bool,
+                        expression: None,
+                        member_of: None,
                     });
                 }
             }
-            WhereClause::And(left, right) => {
-                Self::compile_where_clause(left, table_data, table_name, compiled)?;
-                Self::compile_where_clause(right, table_data, table_name, compiled)?;
+            // Per-row `= NULL` / `< NULL` check: its bit is the row's is_null
+            // bit or its complement (NULL is u64::MAX, above every value)
+            WhereClause::IsNull { column } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::Equal,
+                    NULL,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
+            }
+            WhereClause::IsNotNull { column } => {
+                Self::compile_column_comparison(
+                    column,
+                    &ComparisonOp::LessThan,
+                    NULL,
+                    table_data,
+                    table_name,
+                    compiled,
+                )?;
             }
+            WhereClause::And(left, right) => match Self::bounded_range(left, right) {
+                // Both bounds of one column: a single check per row
+                Some((column, lower, upper)) => {
+                    Self::compile_column_between(
+                        column, lower, upper, table_data, table_name, compiled,
+                    )?;
+                }
+                None => {
+                    Self::compile_where_clause(left, table_data, table_name, compiled)?;
+                    Self::compile_where_clause(right, table_data, table_name, compiled)?;
+                }
+            },
             WhereClause::Or(left, right) => {
                 // For OR: compile both conditions
                 // (OR logic in circuit can be more complex, simple implementation)
@@ -563,22 +4334,413 @@ impl SQLCompiler {
             }
         }
 
+        // A comparison's range checks form one predicate; AND / OR record
+        // their sides' predicates (a bounded AND is a single comparison)
+        let comparison = match where_clause {
+            WhereClause::And(left, right) => Self::bounded_range(left, right).is_some(),
+            WhereClause::Or(..) | WhereClause::Exists { .. } => false,
+            _ => true,
+        };
+        if comparison {
+            compiled
+                .where_predicates
+                .push(first_check..compiled.range_checks.len());
+        }
+
         Ok(())
     }
 }
 
 /// Compiled SQL Query
 /// SQL query compiled to circuit
-#[derive(Clone, Debug)]
+///
+/// `Debug` prints the operation counts and sizes (`summary`), not the
+/// operations themselves
+#[derive(Clone)]
 pub struct CompiledQuery {
     /// Range check operations
     pub range_checks: Vec<RangeCheckOp>,
+    /// `range_checks` of each WHERE comparison, in WHERE order (both bounds
+    /// of a bounded predicate are one comparison)
+    pub where_predicates: Vec<Range<usize>>,
     /// Sort operations
     pub sorts: Vec<SortOp>,
     /// Group-by operations
     pub group_bys: Vec<GroupByOp>,
     /// Join operations
     pub joins: Vec<JoinOp>,
+    /// Semi-join operations (EXISTS / NOT EXISTS in WHERE)
+    pub semi_joins: Vec<SemiJoinOp>,
+    /// Set membership operations (IN a committed set in WHERE)
+    pub set_memberships: Vec<SetMembershipOp>,
     /// Aggregation operations
     pub aggregations: Vec<AggregationOp>,
+    /// Product operations (arithmetic expressions in WHERE, ORDER BY and
+    /// the SELECT list)
+    pub products: Vec<ProductOp>,
+    /// Division operations (`/` and `%` in WHERE, ORDER BY and SELECT list
+    /// expressions)
+    pub divisions: Vec<DivisionOp>,
+    /// Sum operations (`+` and `-` in WHERE, ORDER BY and SELECT list
+    /// expressions)
+    pub sums: Vec<SumOp>,
+    /// Window function operations
+    pub windows: Vec<WindowOp>,
+    /// HAVING operations (on aggregation results)
+    pub having: Vec<HavingOp>,
+    /// Top-K operations (`ORDER BY <aggregate> LIMIT k` over groups)
+    pub top_ks: Vec<TopKOp>,
+    /// Proven WHERE match count, bound to the query result; set instead of
+    /// the WHERE range checks when the WHERE comparison matches no rows, and
+    /// next to them for a LIMIT over rows (`LimitedResult::total_matched`)
+    pub match_count: Option<MatchCountOp>,
+    /// Combined result of `UNION [ALL]` (None without UNION)
+    pub union: Option<UnionResult>,
+    /// SELECT list aggregates, in SELECT order, with the operations proving them
+    pub aggregate_specs: Vec<AggregateSpec>,
+    /// Computed SELECT columns, in SELECT order: output name and the value
+    /// of each FROM row (NULL if an operand is NULL)
+    pub projections: Vec<(String, Vec<u64>)>,
+    /// Committed WHERE selection vector, the circuit's result commitment
+    /// (`SQLCompiler::compile_selection` only)
+    pub selection: Option<ResultCommitment>,
+    /// Result rows computed from the operations, each cell copied from the
+    /// proven cell holding it (see `SQLCompiler::result_commitment`; for a
+    /// LIMIT over rows, its first matching rows with the match count hashed
+    /// after them); None for a query whose rows are not tied to proven
+    /// cells, or whose WHERE match count is bound instead. Pass it to
+    /// `PoneglyphCircuit::from_compiled` to bind it to the query result
+    pub result: Option<ResultCommitment>,
+}
+
+impl std::fmt::Debug for CompiledQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CompiledQuery {{ {} }}", self.summary())
+    }
+}
+
+/// Aggregate Spec
+/// One SELECT list aggregate and the aggregation operations computing it
+/// over the query's shared grouping
+///
+/// # Note
+///
+/// All aggregates of a query use the same group keys (those of
+/// `group_bys[group_by]`), so `SELECT region, sum(x), avg(y), count(*)`
+/// proves the grouping once rather than once per aggregate. AVG is proven
+/// as a SUM and a COUNT; the average itself is derived from their results.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateSpec {
+    /// The aggregate as written in the SELECT list
+    pub aggregation: AggregationClause,
+    /// Index into `CompiledQuery::group_bys` of the grouping (None without GROUP BY)
+    pub group_by: Option<usize>,
+    /// Indices into `CompiledQuery::aggregations`: one, or SUM and COUNT for AVG
+    pub aggregations: Vec<usize>,
+}
+
+impl CompiledQuery {
+    /// Merge a sub-plan compiled separately into this one (e.g. a WHERE plan
+    /// and an aggregation plan)
+    ///
+    /// # Note
+    ///
+    /// - Every operation vector is concatenated with `self`'s operations
+    ///   first, so each kind keeps its order and synthesis assigns the
+    ///   same regions as for the two plans in sequence
+    /// - Indices into the operations (`where_predicates`, filtered COUNT
+    ///   checks, HAVING, top-K and `aggregate_specs`) are shifted to `other`'s
+    ///   appended operations
+    /// - The circuit binds at most one match count: `other`'s match count
+    ///   (and UNION result) is kept only if `self` has none, and a public
+    ///   HAVING count only if no match count or earlier HAVING count is bound
+    /// - No deduplication is done; run `CircuitOptimizer::remove_redundant_operations`
+    ///   on the built circuit if both plans may share operations
+    pub fn merge(mut self, mut other: CompiledQuery) -> CompiledQuery {
+        let match_count = self.match_count.take().or(other.match_count.take());
+        let union = self.union.take().or(other.union.take());
+        self.append(other);
+        self.match_count = match_count;
+        self.union = union;
+        // At most one count is bound to the query result
+        let mut bound = self.match_count.is_some();
+        for having in &mut self.having {
+            having.public_count &= !bound;
+            bound |= having.public_count;
+        }
+        self
+    }
+
+    /// Append another query's operations (HAVING, top-K and ORDER BY
+    /// aggregate indices are shifted to the appended aggregations, sort
+    /// indices to the appended sorts, expression operands to the appended
+    /// arithmetic operations)
+    fn append(&mut self, other: CompiledQuery) {
+        let offset = self.aggregations.len();
+        let (product_offset, sum_offset) = (self.products.len(), self.sums.len());
+        let division_offset = self.divisions.len();
+        let shift = |operand: Operand| match operand {
+            Operand::Product(i) => Operand::Product(i + product_offset),
+            Operand::Sum(i) => Operand::Sum(i + sum_offset),
+            Operand::Quotient(i) => Operand::Quotient(i + division_offset),
+            Operand::Remainder(i) => Operand::Remainder(i + division_offset),
+            Operand::Witness | Operand::Constant(_) | Operand::Committed(..) => operand,
+        };
+        let group_offset = self.group_bys.len();
+        self.aggregate_specs
+            .extend(other.aggregate_specs.into_iter().map(|spec| {
+                AggregateSpec {
+                    group_by: spec.group_by.map(|index| index + group_offset),
+                    aggregations: spec
+                        .aggregations
+                        .iter()
+                        .map(|index| index + offset)
+                        .collect(),
+                    ..spec
+                }
+            }));
+        let check_offset = self.range_checks.len();
+        self.where_predicates.extend(
+            other
+                .where_predicates
+                .into_iter()
+                .map(|range| range.start + check_offset..range.end + check_offset),
+        );
+        let set_offset = self.set_memberships.len();
+        self.range_checks
+            .extend(other.range_checks.into_iter().map(|op| RangeCheckOp {
+                expression: op.expression.map(shift),
+                member_of: op.member_of.map(|set| set + set_offset),
+                ..op
+            }));
+        let semi_join_offset = self.semi_joins.len();
+        let sort_offset = self.sorts.len();
+        let other_aggregations = other.aggregations.into_iter().map(|op| AggregationOp {
+            group_sort: op.group_sort.map(|sort| sort + sort_offset),
+            value_semi_join: op
+                .value_semi_join
+                .map(|semi_join| semi_join + semi_join_offset),
+            value_checks: op
+                .value_checks
+                .map(|checks| checks.iter().map(|check| check + check_offset).collect()),
+            ..op
+        });
+        self.sorts
+            .extend(other.sorts.into_iter().map(|sort| SortOp {
+                aggregation: sort.aggregation.map(|index| index + offset),
+                ..sort
+            }));
+        self.group_bys
+            .extend(other.group_bys.into_iter().map(|op| GroupByOp {
+                sort: op.sort.map(|sort| sort + sort_offset),
+                ..op
+            }));
+        self.joins.extend(other.joins);
+        self.semi_joins.extend(other.semi_joins);
+        self.set_memberships.extend(other.set_memberships);
+        self.aggregations.extend(other_aggregations);
+        self.products
+            .extend(other.products.into_iter().map(|op| ProductOp {
+                operands: op.operands.map(shift),
+                ..op
+            }));
+        self.divisions
+            .extend(other.divisions.into_iter().map(|op| DivisionOp {
+                operands: op.operands.map(shift),
+                ..op
+            }));
+        self.sums.extend(other.sums.into_iter().map(|op| SumOp {
+            operands: op.operands.map(shift),
+            ..op
+        }));
+        self.windows
+            .extend(other.windows.into_iter().map(|op| WindowOp {
+                sort: op.sort.map(|sort| sort + sort_offset),
+                ..op
+            }));
+        self.having
+            .extend(other.having.into_iter().map(|having| HavingOp {
+                predicate: having.predicate.shifted(offset),
+                ..having
+            }));
+        self.top_ks
+            .extend(other.top_ks.into_iter().map(|top_k| TopKOp {
+                aggregation: top_k.aggregation + offset,
+                ..top_k
+            }));
+        self.projections.extend(other.projections);
+        // match_count: UNION sides are compiled without the empty result form
+    }
+
+    /// Check cells of `PoneglyphCircuit::assign_range_checks` grouped by
+    /// WHERE predicate (`where_predicates`)
+    pub fn predicate_checks(
+        &self,
+        checks: &[AssignedCell<Fr, Fr>],
+    ) -> Vec<Vec<AssignedCell<Fr, Fr>>> {
+        self.where_predicates
+            .iter()
+            .map(|range| checks[range.clone()].to_vec())
+            .collect()
+    }
+
+    /// One-line count of each operation kind, with its total element size
+    ///
+    /// e.g. `3 range checks, 1 sort (1000 elems), 1 group-by (50 keys),
+    /// 0 joins, 1 aggregation (sum)`: sorts count input elements, group-bys
+    /// distinct keys, joins the rows of both tables, and aggregations list
+    /// their types. Range checks, sorts, group-bys, joins and aggregations are
+    /// always listed; the other kinds only when present.
+    pub fn summary(&self) -> String {
+        fn count(n: usize, singular: &str, plural: &str) -> String {
+            format!("{} {}", n, if n == 1 { singular } else { plural })
+        }
+        fn sized(n: usize, singular: &str, plural: &str, size: String) -> String {
+            match n {
+                0 => count(n, singular, plural),
+                _ => format!("{} ({})", count(n, singular, plural), size),
+            }
+        }
+
+        let sort_elems: usize = self.sorts.iter().map(|sort| sort.input.len()).sum();
+        let group_keys: usize = self
+            .group_bys
+            .iter()
+            .map(|group_by| group_by.group_keys.iter().collect::<HashSet<_>>().len())
+            .sum();
+        let join_rows: usize = self
+            .joins
+            .iter()
+            .map(|join| join.table1_keys.len() + join.table2_keys.len())
+            .sum();
+        let functions: Vec<String> = self
+            .aggregations
+            .iter()
+            .map(|aggregation| aggregation.function.to_string())
+            .collect();
+
+        let mut parts = vec![
+            count(self.range_checks.len(), "range check", "range checks"),
+            sized(
+                self.sorts.len(),
+                "sort",
+                "sorts",
+                format!("{} elems", sort_elems),
+            ),
+            sized(
+                self.group_bys.len(),
+                "group-by",
+                "group-bys",
+                format!("{} keys", group_keys),
+            ),
+            sized(
+                self.joins.len(),
+                "join",
+                "joins",
+                format!("{} rows", join_rows),
+            ),
+            sized(
+                self.aggregations.len(),
+                "aggregation",
+                "aggregations",
+                functions.join(", "),
+            ),
+        ];
+        if !self.semi_joins.is_empty() {
+            let rows: usize = self.semi_joins.iter().map(|op| op.left_keys.len()).sum();
+            parts.push(sized(
+                self.semi_joins.len(),
+                "semi-join",
+                "semi-joins",
+                format!("{} rows", rows),
+            ));
+        }
+        if !self.set_memberships.is_empty() {
+            let values: usize = self.set_memberships.iter().map(|op| op.set.len()).sum();
+            parts.push(sized(
+                self.set_memberships.len(),
+                "set membership",
+                "set memberships",
+                format!("{} values", values),
+            ));
+        }
+        if !self.products.is_empty() {
+            parts.push(count(self.products.len(), "product", "products"));
+        }
+        if !self.divisions.is_empty() {
+            parts.push(count(self.divisions.len(), "division", "divisions"));
+        }
+        if !self.sums.is_empty() {
+            parts.push(count(self.sums.len(), "sum", "sums"));
+        }
+        if !self.windows.is_empty() {
+            let rows: usize = self.windows.iter().map(|op| op.values.len()).sum();
+            parts.push(sized(
+                self.windows.len(),
+                "window",
+                "windows",
+                format!("{} rows", rows),
+            ));
+        }
+        if !self.having.is_empty() {
+            parts.push(count(self.having.len(), "HAVING check", "HAVING checks"));
+        }
+        if !self.top_ks.is_empty() {
+            let groups: usize = self.top_ks.iter().map(|op| op.sorted_results.len()).sum();
+            parts.push(sized(
+                self.top_ks.len(),
+                "top-K",
+                "top-Ks",
+                format!("{} groups", groups),
+            ));
+        }
+        if let Some(op) = &self.match_count {
+            parts.push(format!("match count ({} rows)", op.values.len()));
+        }
+        if let Some(union) = &self.union {
+            parts.push(format!("union ({} rows)", union.rows.len()));
+        }
+        parts.join(", ")
+    }
+
+    /// Group keys shared by the query's aggregates (empty without GROUP BY)
+    pub fn aggregate_group_keys(&self) -> &[u64] {
+        self.aggregate_specs
+            .iter()
+            .find_map(|spec| spec.group_by)
+            .map(|index| self.group_bys[index].group_keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Final result of each group for a SELECT list aggregate (AVG: SUM / COUNT,
+    /// rounded down)
+    ///
+    /// # Parameters
+    ///
+    /// - `spec`: Index into `aggregate_specs`
+    pub fn aggregate_results(&self, spec: usize) -> Option<Vec<u64>> {
+        let spec = self.aggregate_specs.get(spec)?;
+        let results = self.aggregations[spec.aggregations[0]].group_results();
+        match spec.aggregations.get(1) {
+            Some(&count) => {
+                let counts = self.aggregations[count].group_results();
+                Some(
+                    results
+                        .iter()
+                        .zip(counts)
+                        .map(|(&sum, count)| sum / count.max(1))
+                        .collect(),
+                )
+            }
+            None => Some(results),
+        }
+    }
+}
+
+/// Result rows of `query1 UNION [ALL] query2`
+/// UNION ALL keeps both sides' rows in order; UNION holds each value once, sorted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnionResult {
+    pub rows: Vec<Vec<u64>>,
+    pub all: bool,
 }
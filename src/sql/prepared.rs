@@ -0,0 +1,127 @@
+// Prepared statements
+// A query parsed once with `:name` parameters in WHERE, bound to constants
+// per execution
+
+use super::{tokenize, CompiledQuery, SQLCompiler, SQLParser, SQLQuery, WhereClause};
+use crate::database::TableSource;
+
+/// Prepared Query
+/// A parsed query whose WHERE comparison values may be named parameters
+/// (`WHERE age < :threshold`), bound without parsing the SQL again
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// let prepared = PreparedQuery::prepare("SELECT age FROM users WHERE age < :threshold")?;
+/// let young = prepared.bind("threshold", 30)?; // SQLQuery with age < 30
+/// let compiled = prepared.compile(&[("threshold", 50)], &table_data)?;
+/// ```
+///
+/// # Note
+///
+/// - Parameters stand for the value of a WHERE comparison (`column < :p`,
+///   `expr = :p`); a name may be used more than once
+/// - The bound queries differ only in constants, so their circuits share
+///   gates and columns; the rows used can still differ (e.g. with the number
+///   of matching rows)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreparedQuery {
+    /// Parsed query, every parameter value 0
+    query: SQLQuery,
+    /// Parameter name of each WHERE comparison value (None for a literal),
+    /// in `where_values` order
+    value_params: Vec<Option<String>>,
+}
+
+impl PreparedQuery {
+    /// Parse a query with `:name` parameters
+    pub fn prepare(sql: &str) -> Result<Self, String> {
+        let mut value_params = Vec::new();
+        let query = SQLParser::parse_prepared(&tokenize(sql)?, &mut value_params)?;
+        Ok(Self {
+            query,
+            value_params,
+        })
+    }
+
+    /// Parameter names, in order of first use
+    pub fn parameters(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in self.value_params.iter().flatten() {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Query with its only parameter bound (see `bind_all`)
+    pub fn bind(&self, name: &str, value: u64) -> Result<SQLQuery, String> {
+        self.bind_all(&[(name, value)])
+    }
+
+    /// Query with every parameter bound to a value
+    ///
+    /// # Returns
+    ///
+    /// The concrete `SQLQuery`, or an error naming an unbound or unknown
+    /// parameter
+    pub fn bind_all(&self, values: &[(&str, u64)]) -> Result<SQLQuery, String> {
+        let parameters = self.parameters();
+        if let Some((unknown, _)) = values.iter().find(|(name, _)| !parameters.contains(name)) {
+            return Err(format!("Unknown parameter :{}", unknown));
+        }
+
+        let mut query = self.query.clone();
+        let mut slots = Vec::new();
+        where_values(&mut query, &mut slots);
+        for (slot, param) in slots.into_iter().zip(&self.value_params) {
+            if let Some(name) = param {
+                *slot = values
+                    .iter()
+                    .find(|(bound, _)| bound == name)
+                    .map(|&(_, value)| value)
+                    .ok_or_else(|| format!("Unbound parameter :{}", name))?;
+            }
+        }
+        Ok(query)
+    }
+
+    /// Bind the parameters and compile the query over `tables`
+    pub fn compile<T: TableSource + ?Sized>(
+        &self,
+        values: &[(&str, u64)],
+        tables: &T,
+    ) -> Result<CompiledQuery, String> {
+        SQLCompiler::compile(&self.bind_all(values)?, tables)
+    }
+}
+
+/// WHERE comparison values of a query, then of its UNION query
+/// (the order `SQLParser::parse_prepared` records parameters in)
+fn where_values<'a>(query: &'a mut SQLQuery, out: &mut Vec<&'a mut u64>) {
+    if let Some(clause) = &mut query.where_clause {
+        clause_values(clause, out);
+    }
+    if let Some(union) = &mut query.union {
+        where_values(&mut union.query, out);
+    }
+}
+
+/// Comparison values of a WHERE clause, left before right in AND / OR
+fn clause_values<'a>(clause: &'a mut WhereClause, out: &mut Vec<&'a mut u64>) {
+    match clause {
+        WhereClause::LessThan { value, .. }
+        | WhereClause::GreaterThan { value, .. }
+        | WhereClause::LessEqual { value, .. }
+        | WhereClause::GreaterEqual { value, .. }
+        | WhereClause::Equal { value, .. }
+        | WhereClause::ExprCompare { value, .. } => out.push(value),
+        WhereClause::And(left, right) | WhereClause::Or(left, right) => {
+            clause_values(left, out);
+            clause_values(right, out);
+        }
+        // ColumnCompare, Exists and In: no comparison value
+        _ => {}
+    }
+}
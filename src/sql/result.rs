@@ -0,0 +1,144 @@
+// Typed query results
+// Result rows read with their column types (integer, boolean, fixed-point
+// decimal, timestamp) and rendered as JSON
+
+use std::collections::HashMap;
+
+use super::timestamp::format_timestamp;
+use super::{QueryResult, NULL};
+
+/// Column Type
+/// How the `u64` cells of a column are read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Unsigned integer (the stored value)
+    Int,
+    /// Boolean: 0 is false, any other value true
+    Bool,
+    /// Fixed-point decimal: the stored value is the number times `10^scale`
+    Decimal { scale: u32 },
+    /// Date or date-time: seconds since 1970-01-01T00:00:00Z (as encoded by
+    /// `parse_timestamp`, which also encodes date literals in WHERE)
+    Timestamp,
+}
+
+/// Typed Value
+/// One result cell read with its column type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypedValue {
+    /// SQL NULL (stored as `NULL`, whatever the column type)
+    Null,
+    Int(u64),
+    Bool(bool),
+    /// `value / 10^scale`
+    Decimal {
+        value: u64,
+        scale: u32,
+    },
+    /// Seconds since the Unix epoch
+    Timestamp(u64),
+}
+
+impl TypedValue {
+    /// Read a stored cell as a value of `column_type`
+    pub fn from_raw(raw: u64, column_type: ColumnType) -> Self {
+        if raw == NULL {
+            return TypedValue::Null;
+        }
+        match column_type {
+            ColumnType::Int => TypedValue::Int(raw),
+            ColumnType::Bool => TypedValue::Bool(raw != 0),
+            ColumnType::Decimal { scale } => TypedValue::Decimal { value: raw, scale },
+            ColumnType::Timestamp => TypedValue::Timestamp(raw),
+        }
+    }
+
+    /// JSON text of the value (decimals as numbers with exactly `scale`
+    /// fractional digits, e.g. `12.50`; timestamps as ISO-8601 UTC strings)
+    pub fn to_json(&self) -> String {
+        match self {
+            TypedValue::Null => "null".to_string(),
+            TypedValue::Int(value) => value.to_string(),
+            TypedValue::Bool(value) => value.to_string(),
+            TypedValue::Decimal { value, scale } => {
+                let scale = *scale as usize;
+                if scale == 0 {
+                    return value.to_string();
+                }
+                // Left-pad so there is at least one integer digit
+                let digits = format!("{:0>width$}", value, width = scale + 1);
+                let (integer, fraction) = digits.split_at(digits.len() - scale);
+                format!("{}.{}", integer, fraction)
+            }
+            TypedValue::Timestamp(seconds) => format!("\"{}\"", format_timestamp(*seconds)),
+        }
+    }
+}
+
+/// Typed Query Result
+/// A `QueryResult` with every cell read by its column type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypedQueryResult {
+    /// Output columns (as in `QueryResult`)
+    pub columns: Vec<String>,
+    /// One value per output column
+    pub rows: Vec<Vec<TypedValue>>,
+}
+
+impl TypedQueryResult {
+    /// JSON object `{"columns": [...], "rows": [[...], ...]}`
+    ///
+    /// # Note
+    ///
+    /// Rendered by hand rather than through `serde_json::Value`, whose
+    /// floats would drop a decimal's trailing zeros (`12.50` -> `12.5`) and
+    /// round values beyond 2^53
+    pub fn to_json(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| serde_json::Value::String(column.clone()).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let values = row.iter().map(TypedValue::to_json).collect::<Vec<_>>();
+                format!("[{}]", values.join(","))
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"columns\":[{}],\"rows\":[{}]}}", columns, rows)
+    }
+}
+
+impl QueryResult {
+    /// Read every cell by its column's type
+    ///
+    /// # Parameters
+    ///
+    /// - `schema`: Type of each output column by name (as in `columns`, e.g.
+    ///   `amount` or `sum(amount)`); columns not listed are `ColumnType::Int`
+    pub fn typed(&self, schema: &HashMap<String, ColumnType>) -> TypedQueryResult {
+        let types: Vec<ColumnType> = self
+            .columns
+            .iter()
+            .map(|column| schema.get(column).copied().unwrap_or(ColumnType::Int))
+            .collect();
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&types)
+                    .map(|(&raw, &column_type)| TypedValue::from_raw(raw, column_type))
+                    .collect()
+            })
+            .collect();
+        TypedQueryResult {
+            columns: self.columns.clone(),
+            rows,
+        }
+    }
+}
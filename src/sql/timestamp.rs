@@ -0,0 +1,113 @@
+// Timestamp module
+// ISO-8601 dates and times encoded as u64 seconds since the Unix epoch
+// (1970-01-01T00:00:00Z), so timestamp columns and date literals compare
+// with the integer range checks
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Parse an ISO-8601 date or UTC date-time into seconds since the Unix epoch
+///
+/// # Parameters
+///
+/// - `text`: `YYYY-MM-DD`, or `YYYY-MM-DDTHH:MM:SS` (a space may replace the
+///   `T`, and a trailing `Z` is allowed)
+///
+/// # Returns
+///
+/// Seconds since 1970-01-01T00:00:00Z (a date is its midnight), or an error
+/// for malformed text, an impossible date (`2023-02-29`) or a year before 1970
+pub fn parse_timestamp(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid date '{}'", text);
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time.strip_suffix('Z').unwrap_or(time))),
+        None => (text, None),
+    };
+
+    let [year, month, day] = fields(date, '-', [4, 2, 2]).ok_or_else(invalid)?;
+    if year < 1970 || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+    let seconds = match time {
+        Some(time) => {
+            let [hour, minute, second] = fields(time, ':', [2, 2, 2]).ok_or_else(invalid)?;
+            if hour > 23 || minute > 59 || second > 59 {
+                return Err(invalid());
+            }
+            hour * 3600 + minute * 60 + second
+        }
+        None => 0,
+    };
+    Ok(days_from_civil(year, month, day) * SECONDS_PER_DAY + seconds)
+}
+
+/// ISO-8601 UTC text of seconds since the Unix epoch (`YYYY-MM-DDTHH:MM:SSZ`)
+pub fn format_timestamp(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days(seconds / SECONDS_PER_DAY);
+    let time = seconds % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Three `separator`-separated decimal fields of exactly the given widths
+fn fields(text: &str, separator: char, widths: [usize; 3]) -> Option<[u64; 3]> {
+    let mut parts = text.split(separator);
+    let mut values = [0; 3];
+    for (value, width) in values.iter_mut().zip(widths) {
+        let part = parts.next()?;
+        if part.len() != width || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *value = part.parse().ok()?;
+    }
+    parts.next().is_none().then_some(values)
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to a date (year >= 1970)
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let leap_days = |y: u64| y / 4 - y / 100 + y / 400;
+    let previous = year - 1;
+    let mut days = (year - 1970) * 365 + leap_days(previous) - leap_days(1969);
+    days += (1..month).map(|m| days_in_month(year, m)).sum::<u64>();
+    days + day - 1
+}
+
+/// Date of a day count since 1970-01-01 (days-to-civil over 400-year eras
+/// of 146097 days, counted from 0000-03-01)
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months counted from March, so February is last
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + (month <= 2) as u64;
+    (year, month, day)
+}
@@ -0,0 +1,353 @@
+// SQL tokenizer module
+// Splits a query into keywords, identifiers, literals, operators and
+// punctuation, so clauses are found by token instead of by substring
+
+/// SQL token
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    Keyword(Keyword),
+    /// Table or column name, lowercased (may be qualified: `table.column`)
+    Identifier(String),
+    /// Integer literal
+    Number(u64),
+    /// String literal (`'...'`, `''` for a quote)
+    String(String),
+    /// Named parameter `:name`, lowercased (bound by `PreparedQuery`)
+    Parameter(String),
+    Operator(Operator),
+    Comma,
+    Dot,
+    LeftParen,
+    RightParen,
+}
+
+impl Token {
+    /// Name of a table, column or function: an identifier, or a keyword that
+    /// is not reserved (e.g. a table named `order`)
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Token::Identifier(name) => Some(name),
+            Token::Keyword(keyword) if !keyword.is_reserved() => Some(keyword.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Is this the given keyword?
+    pub fn is_keyword(&self, keyword: Keyword) -> bool {
+        *self == Token::Keyword(keyword)
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Keyword(keyword) => f.write_str(keyword.as_str()),
+            Token::Identifier(name) => f.write_str(name),
+            Token::Number(value) => write!(f, "{}", value),
+            Token::String(value) => write!(f, "'{}'", value.replace('\'', "''")),
+            Token::Parameter(name) => write!(f, ":{}", name),
+            Token::Operator(operator) => f.write_str(operator.as_str()),
+            Token::Comma => f.write_str(","),
+            Token::Dot => f.write_str("."),
+            Token::LeftParen => f.write_str("("),
+            Token::RightParen => f.write_str(")"),
+        }
+    }
+}
+
+/// SQL keyword
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keyword {
+    Select,
+    From,
+    Where,
+    And,
+    Or,
+    Not,
+    Exists,
+    In,
+    Between,
+    Group,
+    Order,
+    By,
+    Having,
+    Asc,
+    Desc,
+    Nulls,
+    First,
+    Last,
+    Union,
+    All,
+    Over,
+    Partition,
+    As,
+    Case,
+    When,
+    Then,
+    Else,
+    End,
+    True,
+    False,
+    Distinct,
+    Is,
+    Null,
+    Limit,
+}
+
+impl Keyword {
+    const ALL: [Keyword; 34] = [
+        Keyword::Select,
+        Keyword::From,
+        Keyword::Where,
+        Keyword::And,
+        Keyword::Or,
+        Keyword::Not,
+        Keyword::Exists,
+        Keyword::In,
+        Keyword::Between,
+        Keyword::Group,
+        Keyword::Order,
+        Keyword::By,
+        Keyword::Having,
+        Keyword::Asc,
+        Keyword::Desc,
+        Keyword::Nulls,
+        Keyword::First,
+        Keyword::Last,
+        Keyword::Union,
+        Keyword::All,
+        Keyword::Over,
+        Keyword::Partition,
+        Keyword::As,
+        Keyword::Case,
+        Keyword::When,
+        Keyword::Then,
+        Keyword::Else,
+        Keyword::End,
+        Keyword::True,
+        Keyword::False,
+        Keyword::Distinct,
+        Keyword::Is,
+        Keyword::Null,
+        Keyword::Limit,
+    ];
+
+    /// Keyword written as in SQL (lowercase)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Select => "select",
+            Keyword::From => "from",
+            Keyword::Where => "where",
+            Keyword::And => "and",
+            Keyword::Or => "or",
+            Keyword::Not => "not",
+            Keyword::Exists => "exists",
+            Keyword::In => "in",
+            Keyword::Between => "between",
+            Keyword::Group => "group",
+            Keyword::Order => "order",
+            Keyword::By => "by",
+            Keyword::Having => "having",
+            Keyword::Asc => "asc",
+            Keyword::Desc => "desc",
+            Keyword::Nulls => "nulls",
+            Keyword::First => "first",
+            Keyword::Last => "last",
+            Keyword::Union => "union",
+            Keyword::All => "all",
+            Keyword::Over => "over",
+            Keyword::Partition => "partition",
+            Keyword::As => "as",
+            Keyword::Case => "case",
+            Keyword::When => "when",
+            Keyword::Then => "then",
+            Keyword::Else => "else",
+            Keyword::End => "end",
+            Keyword::True => "true",
+            Keyword::False => "false",
+            Keyword::Distinct => "distinct",
+            Keyword::Is => "is",
+            Keyword::Null => "null",
+            Keyword::Limit => "limit",
+        }
+    }
+
+    /// Keyword of a lowercase word, if it is one
+    pub fn from_word(word: &str) -> Option<Keyword> {
+        Self::ALL
+            .into_iter()
+            .find(|keyword| keyword.as_str() == word)
+    }
+
+    /// Reserved keywords cannot be used as names; the others are keywords
+    /// only where a clause expects them (`GROUP BY`, `ORDER BY`, `ASC`, ...)
+    pub fn is_reserved(&self) -> bool {
+        matches!(
+            self,
+            Keyword::Select
+                | Keyword::From
+                | Keyword::Where
+                | Keyword::And
+                | Keyword::Or
+                | Keyword::Not
+                | Keyword::Exists
+                | Keyword::In
+                | Keyword::Between
+                | Keyword::By
+                | Keyword::Having
+                | Keyword::Union
+                | Keyword::True
+                | Keyword::False
+        )
+    }
+}
+
+/// Comparison and arithmetic operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    LessThan,
+    GreaterThan,
+    /// `<=`
+    LessEqual,
+    /// `>=`
+    GreaterEqual,
+    Equal,
+    Plus,
+    Minus,
+    /// Multiplication, or all columns in `count(*)`
+    Star,
+    /// Integer division
+    Slash,
+    /// Integer remainder
+    Percent,
+}
+
+impl Operator {
+    /// Operator written as in SQL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::LessThan => "<",
+            Operator::GreaterThan => ">",
+            Operator::LessEqual => "<=",
+            Operator::GreaterEqual => ">=",
+            Operator::Equal => "=",
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Star => "*",
+            Operator::Slash => "/",
+            Operator::Percent => "%",
+        }
+    }
+}
+
+/// Split SQL into tokens
+///
+/// # Note
+///
+/// Words (letters, digits, `_` and `.`) are lowercased; a word of digits
+/// only is a number, a word equal to a keyword is that keyword, any other
+/// word is an identifier. So keywords inside names (`where_`, `order_id`)
+/// or string literals are not keywords, and operators need no surrounding
+/// spaces.
+///
+/// # Returns
+///
+/// Tokens in order, or an error for an unexpected character, an
+/// unterminated string literal or a number above `u64::MAX`
+pub fn tokenize(sql: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '<' if chars.next_if(|&(_, c)| c == '=').is_some() => {
+                Token::Operator(Operator::LessEqual)
+            }
+            '>' if chars.next_if(|&(_, c)| c == '=').is_some() => {
+                Token::Operator(Operator::GreaterEqual)
+            }
+            '<' => Token::Operator(Operator::LessThan),
+            '>' => Token::Operator(Operator::GreaterThan),
+            '=' => Token::Operator(Operator::Equal),
+            '+' => Token::Operator(Operator::Plus),
+            '-' => Token::Operator(Operator::Minus),
+            '*' => Token::Operator(Operator::Star),
+            '/' => Token::Operator(Operator::Slash),
+            '%' => Token::Operator(Operator::Percent),
+            '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) if chars.next_if(|&(_, c)| c == '\'').is_some() => {
+                            value.push('\'')
+                        }
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => value.push(c),
+                        None => return Err("Unterminated string literal".to_string()),
+                    }
+                }
+                Token::String(value)
+            }
+            ':' => {
+                let mut name = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                    name.extend(c.to_lowercase());
+                }
+                if name.is_empty() {
+                    return Err("Parameter name expected after ':'".to_string());
+                }
+                Token::Parameter(name)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((idx, c)) =
+                    chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '.')
+                {
+                    end = idx + c.len_utf8();
+                }
+                let word = sql[start..end].to_lowercase();
+
+                if word.chars().all(|c| c.is_ascii_digit()) {
+                    let value = word
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid number: {}", word))?;
+                    Token::Number(value)
+                } else if let Some(keyword) = Keyword::from_word(&word) {
+                    Token::Keyword(keyword)
+                } else {
+                    Token::Identifier(word)
+                }
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Tokens written back as SQL text: single spaces between tokens, none
+/// inside parentheses, before a comma or between a name and its call
+/// parentheses (`sum(amount) over (order by id)`)
+pub fn render(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let attached = matches!(
+            (i.checked_sub(1).map(|prev| &tokens[prev]), token),
+            (None, _)
+                | (_, Token::Comma | Token::RightParen)
+                | (Some(Token::LeftParen), _)
+                | (Some(Token::Identifier(_)), Token::LeftParen)
+        );
+        if !attached {
+            text.push(' ');
+        }
+        text.push_str(&token.to_string());
+    }
+    text
+}
@@ -0,0 +1,150 @@
+// Query correctness harness
+// Ties the reference executor (`SQLQuery::execute`) to the circuit: a query
+// is correct when the result computed from the circuit's proven cells equals
+// the executor's result and the circuit mock-proves over the committed tables
+
+use std::collections::HashMap;
+
+use halo2_proofs::circuit::Value;
+use pasta_curves::pallas::Base as Fr;
+
+use crate::circuit::{CommittedDatabase, PoneglyphCircuit, PublicInputs, ResultCommitment};
+use crate::prover::MockProverHelper;
+use crate::sql::{QueryResult, SQLCompiler, SQLParser};
+
+/// Assert that a query's circuit proves the reference executor's result
+///
+/// # Parameters
+///
+/// - `sql`: Query text
+/// - `table_data`: Table data (table_name -> column_name -> values)
+/// - `k`: log2 of the circuit's row count
+///
+/// # Returns
+///
+/// The reference result, for further assertions
+///
+/// # Panics
+///
+/// If the query does not parse, execute or compile, or `assert_query_result`
+/// fails for the reference result
+pub fn assert_query_correct(
+    sql: &str,
+    table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    k: u32,
+) -> QueryResult {
+    let query = SQLParser::parse(sql).unwrap_or_else(|e| panic!("Failed to parse {sql:?}: {e}"));
+    let result = query
+        .execute(table_data)
+        .unwrap_or_else(|e| panic!("Failed to execute {sql:?}: {e}"));
+    assert_query_result(sql, table_data, &result, k);
+    result
+}
+
+/// Assert that a query's circuit proves the given result
+///
+/// # Parameters
+///
+/// - `sql`: Query text
+/// - `table_data`: Table data (table_name -> column_name -> values)
+/// - `expected`: Result the circuit must prove (e.g. `SQLQuery::execute`'s)
+/// - `k`: log2 of the circuit's row count
+///
+/// # Panics
+///
+/// If the query does not parse or compile, the circuit's result differs from
+/// `expected`, or the mock proof fails (the message carries the
+/// `MockProverHelper` failure report)
+///
+/// # Note
+///
+/// The tables are committed (`CommittedDatabase`), so the operations that
+/// copy their inputs read the given data. When the compiler ties the result
+/// rows to proven cells (`CompiledQuery::result`), those rows are compared
+/// with `expected` and bound to the query result. A query compiled to a WHERE
+/// match count (an empty WHERE result, or a LIMIT over rows) binds that count
+/// instead (the result instance holds a single value), after checking it
+/// against the expected row count, and so does a HAVING with the number of
+/// groups it keeps. Otherwise (ORDER BY, joins, windows, ...) each ORDER BY
+/// column of `expected` must be the proven output of a sort, and the rows of
+/// `expected` are committed as witnessed, so the rest of such a result is not
+/// compared.
+pub fn assert_query_result(
+    sql: &str,
+    table_data: &HashMap<String, HashMap<String, Vec<u64>>>,
+    expected: &QueryResult,
+    k: u32,
+) {
+    let query = SQLParser::parse(sql).unwrap_or_else(|e| panic!("Failed to parse {sql:?}: {e}"));
+    let compiled = SQLCompiler::compile(&query, table_data)
+        .unwrap_or_else(|e| panic!("Failed to compile {sql:?}: {e}"));
+
+    let limit = query.limit.map_or(u64::MAX, |n| n as u64);
+    let kept = compiled.having.iter().find(|op| op.public_count);
+    let (result_commitment, query_result) = match (&compiled.match_count, kept, &compiled.result) {
+        (Some(op), _, _) => {
+            // An aggregated result has a row even when no row matches, and a
+            // LIMIT returns at most its first rows
+            if compiled.aggregations.is_empty() && compiled.group_bys.is_empty() {
+                let matched = op.count().min(limit);
+                assert_eq!(
+                    matched,
+                    expected.rows.len() as u64,
+                    "{sql:?}: match count differs from the reference row count"
+                );
+            }
+            (None, Fr::from(op.count()))
+        }
+        (None, Some(having), _) => {
+            // One result row per kept group
+            let kept = having.kept_count(&compiled.aggregations);
+            assert_eq!(
+                kept.min(limit),
+                expected.rows.len() as u64,
+                "{sql:?}: kept group count differs from the reference row count"
+            );
+            (None, Fr::from(kept))
+        }
+        (None, None, Some(commitment)) => {
+            assert_eq!(
+                commitment.rows, expected.rows,
+                "{sql:?}: circuit result differs from the reference result"
+            );
+            (Some(commitment.clone()), commitment.value())
+        }
+        (None, None, None) => {
+            for order in query.order_by.iter().flatten() {
+                let Some(column) = expected.column(&order.column) else {
+                    continue;
+                };
+                assert!(
+                    compiled
+                        .sorts
+                        .iter()
+                        .any(|sort| sort.sorted_output == column),
+                    "{sql:?}: ORDER BY {} differs from every proven sort output",
+                    order.column
+                );
+            }
+            let commitment = ResultCommitment::new(expected.rows.clone());
+            let value = commitment.value();
+            (Some(commitment), value)
+        }
+    };
+
+    let database = CommittedDatabase::new(table_data);
+    let public_inputs = PublicInputs {
+        db_commitments: database.values(),
+        query_result: Some(query_result),
+    };
+    let circuit = PoneglyphCircuit {
+        query_result: Value::known(query_result),
+        ..PoneglyphCircuit::from_compiled(compiled, result_commitment, Some(database))
+    };
+
+    if let Err(e) =
+        MockProverHelper::mock_prove_and_verify(&circuit, &public_inputs.to_instances(), k)
+    {
+        panic!("{sql:?} does not prove its reference result:\n{e}");
+    }
+}
@@ -0,0 +1,37 @@
+// Tracing spans
+// `tracing` spans around parse, compile, keygen, synthesize, prove and
+// verify (the `tracing` feature). Unlike `Profiler`, which returns timings to
+// the caller, these are emitted as events to whatever subscriber is installed.
+
+use std::time::Instant;
+
+use tracing::span::EnteredSpan;
+use tracing::Span;
+
+/// Entered stage span that emits its elapsed time when dropped
+///
+/// The timing event is emitted at `debug` level inside the span, and only
+/// if the span itself is enabled.
+pub(crate) struct StageSpan {
+    start: Instant,
+    span: EnteredSpan,
+}
+
+impl StageSpan {
+    /// Enter `span` and start timing it
+    pub(crate) fn enter(span: Span) -> Self {
+        Self {
+            start: Instant::now(),
+            span: span.entered(),
+        }
+    }
+}
+
+impl Drop for StageSpan {
+    fn drop(&mut self) {
+        if let Some(metadata) = self.span.metadata() {
+            let elapsed_us = self.start.elapsed().as_micros() as u64;
+            tracing::debug!(parent: &*self.span, elapsed_us, "{} done", metadata.name());
+        }
+    }
+}
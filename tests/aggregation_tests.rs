@@ -1,4 +1,6 @@
+use ff::Field;
 use halo2_proofs::{
+    circuit::Value,
     dev::MockProver,
     plonk::{Circuit, ConstraintSystem, Error},
 };
@@ -11,11 +13,14 @@ use poneglyphdb::circuit::*;
 struct AggregationTestCircuit {
     group_keys: Vec<u64>,
     values: Vec<u64>,
-    agg_type: String,
+    function: AggregationFunction,
+    // Claimed per-row results (None = computed by the chip)
+    results: Option<Vec<u64>>,
 }
 
 /// Config for test circuit
 #[derive(Clone)]
+#[allow(dead_code)]
 struct TestConfig {
     poneglyph_config: PoneglyphConfig,
     range_check_config: RangeCheckConfig,
@@ -31,7 +36,8 @@ impl Circuit<Fr> for AggregationTestCircuit {
         Self {
             group_keys: vec![],
             values: vec![],
-            agg_type: String::new(),
+            function: AggregationFunction::Sum,
+            results: None,
         }
     }
 
@@ -65,13 +71,22 @@ impl Circuit<Fr> for AggregationTestCircuit {
         // Create aggregation chip
         let aggregation_chip = AggregationChip::new(config.aggregation_config);
         
-        // Aggregate and verify
-        let _results = aggregation_chip.aggregate_and_verify(
-            layouter.namespace(|| "aggregate and verify"),
-            &sorted_keys,
-            &self.values,
-            &self.agg_type,
-        )?;
+        // Aggregate and verify (or verify the claimed results)
+        let _results = match &self.results {
+            Some(results) => aggregation_chip.verify_aggregation(
+                layouter.namespace(|| "verify aggregation"),
+                &sorted_keys,
+                &self.values,
+                results,
+                self.function,
+            )?,
+            None => aggregation_chip.aggregate_and_verify(
+                layouter.namespace(|| "aggregate and verify"),
+                &sorted_keys,
+                &self.values,
+                self.function,
+            )?,
+        };
         
         Ok(())
     }
@@ -84,9 +99,10 @@ fn test_aggregation_sum_single_group() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 1, 1, 1],
         values: vec![10, 20, 30, 40, 50],
-        agg_type: "sum".to_string(),
+        function: AggregationFunction::Sum,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -98,9 +114,10 @@ fn test_aggregation_sum_multiple_groups() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 2, 2, 2, 3, 3],
         values: vec![10, 20, 30, 40, 50, 60, 70],
-        agg_type: "sum".to_string(),
+        function: AggregationFunction::Sum,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -112,9 +129,10 @@ fn test_aggregation_count_single_group() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 1, 1, 1],
         values: vec![10, 20, 30, 40, 50],
-        agg_type: "count".to_string(),
+        function: AggregationFunction::Count,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -126,9 +144,10 @@ fn test_aggregation_count_multiple_groups() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 2, 2, 2, 3, 3],
         values: vec![10, 20, 30, 40, 50, 60, 70],
-        agg_type: "count".to_string(),
+        function: AggregationFunction::Count,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -140,9 +159,10 @@ fn test_aggregation_max_single_group() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 1, 1, 1],
         values: vec![10, 20, 30, 40, 50],
-        agg_type: "max".to_string(),
+        function: AggregationFunction::Max,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -154,9 +174,10 @@ fn test_aggregation_max_multiple_groups() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 2, 2, 2, 3, 3],
         values: vec![10, 20, 30, 40, 50, 60, 70],
-        agg_type: "max".to_string(),
+        function: AggregationFunction::Max,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -168,9 +189,10 @@ fn test_aggregation_min_single_group() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 1, 1, 1],
         values: vec![10, 20, 30, 40, 50],
-        agg_type: "min".to_string(),
+        function: AggregationFunction::Min,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -182,9 +204,10 @@ fn test_aggregation_min_multiple_groups() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1, 1, 2, 2, 2, 3, 3],
         values: vec![10, 20, 30, 40, 50, 60, 70],
-        agg_type: "min".to_string(),
+        function: AggregationFunction::Min,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -196,9 +219,10 @@ fn test_aggregation_empty() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![],
         values: vec![],
-        agg_type: "sum".to_string(),
+        function: AggregationFunction::Sum,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -210,10 +234,352 @@ fn test_aggregation_single_element() {
     let circuit = AggregationTestCircuit {
         group_keys: vec![1],
         values: vec![42],
-        agg_type: "sum".to_string(),
+        function: AggregationFunction::Sum,
+        results: None,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
 
+#[test]
+fn test_aggregation_max_unordered_values() {
+    // Test: MAX - Values in arbitrary order within groups
+    let k = 10;
+    let circuit = AggregationTestCircuit {
+        group_keys: vec![1, 1, 1, 2, 2],
+        values: vec![30, 10, 20, 5, 40],
+        function: AggregationFunction::Max,
+        results: Some(vec![30, 30, 30, 5, 40]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_aggregation_max_inflated_rejected() {
+    // Test: MAX - An inflated group max (not a member) is rejected
+    let k = 10;
+    for results in [vec![10, 100], vec![10, 25]] {
+        let circuit = AggregationTestCircuit {
+            group_keys: vec![1, 1],
+            values: vec![10, 20],
+            function: AggregationFunction::Max,
+            results: Some(results),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[test]
+fn test_aggregation_max_cross_group_leakage_rejected() {
+    // Test: MAX - A previous group's max does not carry into the next group
+    let k = 10;
+    let circuit = AggregationTestCircuit {
+        group_keys: vec![1, 2, 2],
+        values: vec![50, 10, 20],
+        function: AggregationFunction::Max,
+        results: Some(vec![50, 50, 50]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_aggregation_min_first_row_rejected() {
+    // Test: MIN - The first row's result must equal its value
+    let k = 10;
+    for (values, results) in [(vec![10], vec![5]), (vec![10, 20], vec![5, 5])] {
+        let circuit = AggregationTestCircuit {
+            group_keys: vec![1; values.len()],
+            values,
+            function: AggregationFunction::Min,
+            results: Some(results),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[test]
+fn test_aggregation_sum_wide_exceeds_u64() {
+    // Test: SUM - Running totals above u64::MAX are proven as 128-bit values
+    // (the gate picks the wide decomposition for SUM)
+    let k = 10;
+    let half = u64::MAX / 2;
+    let circuit = AggregationTestCircuit {
+        group_keys: vec![1, 1, 1, 2, 2],
+        values: vec![half, half, half, u64::MAX, 1],
+        function: AggregationFunction::Sum,
+        results: None,
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_aggregation_sum_overflow_does_not_wrap() {
+    // Test: SUM - A total above u64::MAX cannot be claimed as its 64-bit
+    // wrap-around
+    let k = 10;
+    let circuit = AggregationTestCircuit {
+        group_keys: vec![1, 1],
+        values: vec![u64::MAX, 1],
+        function: AggregationFunction::Sum,
+        results: Some(vec![u64::MAX, 0]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_aggregation_avg_not_a_gate_function() {
+    // Test: AVG is compiled to SUM and COUNT; the gate itself rejects it.
+    // An unknown function name does not compile (see the
+    // `AggregationFunction` doc test)
+    let k = 10;
+    let circuit = AggregationTestCircuit {
+        group_keys: vec![1, 1],
+        values: vec![4, 6],
+        function: AggregationFunction::Avg,
+        results: None,
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![], vec![]]).is_err());
+    assert_eq!(AggregationFunction::Avg.to_string(), "avg");
+}
+
+/// Aggregation Gate test circuit for a MAX / MIN with a payload (arg-max)
+#[derive(Clone)]
+struct PayloadTestCircuit {
+    group_keys: Vec<u64>,
+    values: Vec<u64>,
+    payloads: Vec<u64>,
+    function: AggregationFunction,
+    // Claimed per-row payload results (None = computed by the chip)
+    payload_results: Option<Vec<u64>>,
+}
+
+impl Circuit<Fr> for PayloadTestCircuit {
+    type Config = TestConfig;
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            group_keys: vec![],
+            values: vec![],
+            payloads: vec![],
+            function: self.function,
+            payload_results: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationTestCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.poneglyph_config.load_lookup_table(&mut layouter)?;
+        let aggregation_chip = AggregationChip::new(config.aggregation_config);
+        
+        match &self.payload_results {
+            Some(payload_results) => aggregation_chip.verify_payload(
+                layouter.namespace(|| "verify payload"),
+                &self.group_keys,
+                &self.values,
+                &self.payloads,
+                payload_results,
+                self.function,
+            )?,
+            None => aggregation_chip.aggregate_with_payload(
+                layouter.namespace(|| "aggregate with payload"),
+                &self.group_keys,
+                &self.values,
+                &self.payloads,
+                self.function,
+            )?,
+        };
+        
+        Ok(())
+    }
+}
+
+#[test]
+fn test_aggregation_arg_max_payload() {
+    // Test: MAX with a payload - each group's last payload is the id of its
+    // max row (group 1: 30 at id 101, group 2: 7 at id 104)
+    let k = 10;
+    let op = AggregationOp {
+        group_keys: vec![1, 1, 1, 2, 2],
+        values: vec![10, 30, 20, 5, 7],
+        function: AggregationFunction::Max,
+        value_checks: None,
+        value_semi_join: None,
+        unfiltered: None,
+        column: None,
+        expected: None,
+        payload: Some(vec![100, 101, 102, 103, 104]),
+        group_sort: None,
+        sort_payload: None,
+    };
+    assert_eq!(op.group_results(), vec![30, 7]);
+    assert_eq!(op.group_payloads(), Some(vec![101, 104]));
+
+    for payload_results in [None, Some(vec![100, 101, 101, 103, 104])] {
+        let circuit = PayloadTestCircuit {
+            group_keys: op.group_keys.clone(),
+            values: op.values.clone(),
+            payloads: op.payload.clone().unwrap(),
+            function: AggregationFunction::Max,
+            payload_results,
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    // MIN without group keys: one group, min 5 at id 103
+    let circuit = PayloadTestCircuit {
+        group_keys: vec![],
+        values: vec![10, 30, 20, 5, 7],
+        payloads: vec![100, 101, 102, 103, 104],
+        function: AggregationFunction::Min,
+        payload_results: Some(vec![100, 100, 100, 103, 103]),
+    };
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_aggregation_arg_max_wrong_payload_rejected() {
+    // Test: A group payload other than the max row's id is rejected, also
+    // the id of another row of the group or of the previous group
+    let k = 10;
+    for payload_results in [
+        vec![100, 101, 102, 103, 104],
+        vec![100, 100, 100, 103, 104],
+        vec![100, 101, 101, 101, 101],
+        vec![100, 101, 101, 103, 999],
+    ] {
+        let circuit = PayloadTestCircuit {
+            group_keys: vec![1, 1, 1, 2, 2],
+            values: vec![10, 30, 20, 5, 7],
+            payloads: vec![100, 101, 102, 103, 104],
+            function: AggregationFunction::Max,
+            payload_results: Some(payload_results.clone()),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err(), "{:?}", payload_results);
+    }
+
+    // Only MAX / MIN carry a payload
+    let circuit = PayloadTestCircuit {
+        group_keys: vec![1, 1],
+        values: vec![4, 6],
+        payloads: vec![1, 2],
+        function: AggregationFunction::Sum,
+        payload_results: None,
+    };
+    assert!(MockProver::run(k, &circuit, vec![vec![], vec![]]).is_err());
+}
+
+/// MAX over the group {5, 1} laid out by hand, with a claimed boundary and
+/// result on the second row (the first row starts the group with 5)
+#[derive(Clone)]
+struct BoundaryTestCircuit {
+    // Boundary of the second row (1 = same group, 0 = new group)
+    boundary: Fr,
+    result: u64,
+    // Copy the boundary from the Group-By Gate's cell
+    copied: bool,
+}
+
+impl Circuit<Fr> for BoundaryTestCircuit {
+    type Config = TestConfig;
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationTestCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.poneglyph_config.load_lookup_table(&mut layouter)?;
+        let group_by_chip = GroupByChip::new(config.group_by_config);
+        let boundary_cells =
+            group_by_chip.group_and_verify(layouter.namespace(|| "group by"), &[1, 1])?;
+        
+        let agg = config.aggregation_config;
+        layouter.assign_region(
+            || "max",
+            |mut region| {
+                let assign =
+                    |region: &mut halo2_proofs::circuit::Region<'_, Fr>, column, row, value: Fr| {
+                        region.assign_advice(|| "cell", column, row, || Value::known(value))
+                    };
+                agg.start_selector.enable(&mut region, 0)?;
+                assign(&mut region, agg.value_column, 0, Fr::from(5))?;
+                assign(&mut region, agg.result_column, 0, Fr::from(5))?;
+                assign(
+                    &mut region,
+                    agg.group_by_config.boundary_column,
+                    0,
+                    Fr::ZERO,
+                )?;
+
+                // take = 0: the result continues from 5 unless the group restarts
+                agg.max_selector.enable(&mut region, 1)?;
+                let boundary = assign(
+                    &mut region,
+                    agg.group_by_config.boundary_column,
+                    1,
+                    self.boundary,
+                )?;
+                if self.copied {
+                    region.constrain_equal(boundary.cell(), boundary_cells[0].cell())?;
+                }
+                let result = Fr::from(self.result);
+                assign(&mut region, agg.value_column, 1, Fr::from(1))?;
+                assign(&mut region, agg.result_column, 1, result)?;
+                assign(&mut region, agg.take_column, 1, Fr::ZERO)?;
+                assign(&mut region, agg.value_diff_column, 1, result - Fr::from(1))?;
+                let prev_diff = self.boundary * (result - Fr::from(5));
+                assign(&mut region, agg.prev_diff_column, 1, prev_diff)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_aggregation_forged_boundary_rejected() {
+    // Test: The running-result gates only accept a boolean boundary copied
+    // from the Group-By Gate: a boundary of 3/2 would prove MAX{5, 1} = 7,
+    // and a restart (0) inside one group would prove MAX{5, 1} = 1
+    let k = 10;
+    let run = |boundary: Fr, result: u64, copied: bool| {
+        let circuit = BoundaryTestCircuit {
+            boundary,
+            result,
+            copied,
+        };
+        MockProver::run(k, &circuit, vec![vec![], vec![]])
+            .unwrap()
+            .verify()
+    };
+    assert_eq!(run(Fr::ONE, 5, true), Ok(()));
+    
+    let three_halves = Fr::from(3) * Fr::from(2).invert().unwrap();
+    assert!(run(three_halves, 7, false).is_err());
+    assert!(run(Fr::ZERO, 1, true).is_err());
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use arrow_array::{Float64Array, Int32Array, RecordBatch, UInt64Array};
+use halo2_proofs::{circuit::Value, dev::MockProver};
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::sql::*;
+
+#[test]
+fn test_record_batch_query_proves() {
+    // Test: Integer columns of a RecordBatch compile and prove like CSV-loaded data
+    let batch = RecordBatch::try_from_iter([
+        (
+            "customer_id",
+            Arc::new(Int32Array::from(vec![1, 1, 2, 2, 3])) as _,
+        ),
+        (
+            "amount",
+            Arc::new(UInt64Array::from(vec![10, 20, 5, 5, 100])) as _,
+        ),
+    ])
+    .unwrap();
+    let table = DatabaseTable::from_record_batch("order".to_string(), &batch).unwrap();
+    assert_eq!(table.columns, vec!["customer_id", "amount"]);
+    assert_eq!(table.data[4], vec![3, 100]);
+
+    let data = table.to_column_map();
+    let query = SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+        .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregations[0].group_results(), vec![30, 10, 100]);
+
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, None);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_record_batch_nulls_and_rejections() {
+    // Test: Arrow nulls become NULL; float columns and negative values are rejected
+    let batch = RecordBatch::try_from_iter([(
+        "score",
+        Arc::new(Int32Array::from(vec![Some(7), None, Some(3)])) as _,
+    )])
+    .unwrap();
+    let table = DatabaseTable::from_record_batch("t".to_string(), &batch).unwrap();
+    assert_eq!(table.data, vec![vec![7], vec![NULL], vec![3]]);
+
+    let batch =
+        RecordBatch::try_from_iter([("price", Arc::new(Float64Array::from(vec![1.5])) as _)])
+            .unwrap();
+    assert!(matches!(
+        DatabaseTable::from_record_batch("t".to_string(), &batch),
+        Err(QueryError::UnsupportedColumnType { column, data_type, .. })
+            if column == "price" && data_type == "Float64"
+    ));
+
+    let batch =
+        RecordBatch::try_from_iter([("delta", Arc::new(Int32Array::from(vec![4, -1])) as _)])
+            .unwrap();
+    assert_eq!(
+        DatabaseTable::from_record_batch("t".to_string(), &batch).unwrap_err(),
+        QueryError::InvalidValue {
+            table: "t".to_string(),
+            column: "delta".to_string(),
+            row: 1,
+        }
+    );
+}
@@ -0,0 +1,129 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::dev::MockProver;
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::CommittedDatabase;
+use poneglyphdb::prover::{aggregate_assertion, assert_aggregate, ProvingContext};
+
+/// Order table (customer_id, amount)
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    order_table_with(vec![40, 15, 60, 5, 30])
+}
+
+/// Order table with the given amounts
+fn order_table_with(amount: Vec<u64>) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), vec![1, 2, 1, 3, 2]);
+    columns.insert("amount".to_string(), amount);
+
+    let mut table_data = HashMap::new();
+    table_data.insert("order".to_string(), columns);
+    table_data
+}
+
+/// Public inputs of an assertion over the order table
+fn instances(result: u64) -> Vec<Vec<Fr>> {
+    let database = CommittedDatabase::new(&order_table());
+    vec![database.values(), vec![Fr::from(result)]]
+}
+
+#[test]
+fn test_asserted_aggregate_binds_expected_value() {
+    // Test: The aggregate's result cell equals both the expected constant and
+    // the query result public input
+    let sql = "SELECT sum(amount) FROM order";
+    let circuit = aggregate_assertion(sql, &order_table(), 150).unwrap();
+    let k = circuit.stats().unwrap().min_k;
+
+    let prover = MockProver::run(k, &circuit, instances(150)).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A different public value fails
+    let prover = MockProver::run(k, &circuit, instances(151)).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A different expected value fails, even with a matching public value
+    let circuit = aggregate_assertion(sql, &order_table(), 151).unwrap();
+    let prover = MockProver::run(k, &circuit, instances(151)).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_asserted_aggregate_binds_committed_values() {
+    // Test: The aggregated values are the committed column's: values with
+    // the same aggregate that differ from the committed ones are rejected
+    for (sql, expected, amount) in [
+        ("SELECT sum(amount) FROM order", 150, vec![50, 5, 60, 10, 25]),
+        ("SELECT max(amount) FROM order", 60, vec![40, 15, 60, 5, 31]),
+        (
+            "SELECT sum(amount) FROM order WHERE customer_id < 3",
+            145,
+            vec![50, 5, 60, 100, 30],
+        ),
+        ("SELECT count(*) FROM order WHERE amount < 35", 3, vec![40, 15, 60, 6, 30]),
+    ] {
+        let circuit = aggregate_assertion(sql, &order_table(), expected).unwrap();
+        assert_eq!(circuit.db_commitments, instances(expected)[0]);
+        let k = circuit.stats().unwrap().min_k;
+        let prover = MockProver::run(k, &circuit, instances(expected)).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+
+        // Other amounts, with the same aggregate, proven against the committed table
+        let mut tampered = aggregate_assertion(sql, &order_table_with(amount), expected).unwrap();
+        tampered.db_commitments = circuit.db_commitments.clone();
+        tampered.database = circuit.database.clone();
+        let prover = MockProver::run(k, &tampered, instances(expected)).unwrap();
+        assert!(prover.verify().is_err(), "{}", sql);
+    }
+}
+
+#[test]
+fn test_assert_aggregate_proves_and_verifies() {
+    // Test: A correct assertion is proven and verifies against the keys of
+    // the same assertion; a wrong one is rejected before proving
+    let sql = "SELECT count(*) FROM order WHERE amount < 35";
+    let (proof, public_inputs) = assert_aggregate(sql, &order_table(), 3).unwrap();
+    assert_eq!(public_inputs.query_result, Some(Fr::from(3)));
+
+    let circuit = aggregate_assertion(sql, &order_table(), 3).unwrap();
+    let context = ProvingContext::for_circuit(&circuit).unwrap();
+    assert!(context
+        .verify(&proof, &public_inputs.to_instances())
+        .unwrap());
+    assert!(!context.verify(&proof, &instances(4)).unwrap_or(false));
+
+    // Another database's commitment
+    let other = CommittedDatabase::new(&order_table_with(vec![40, 15, 60, 5, 31]));
+    let wrong = vec![other.values(), vec![Fr::from(3)]];
+    assert!(!context.verify(&proof, &wrong).unwrap_or(false));
+
+    let err = assert_aggregate(sql, &order_table(), 4).unwrap_err();
+    assert!(err.contains("not 4"), "{}", err);
+}
+
+#[test]
+fn test_assertion_needs_one_global_aggregate() {
+    // Test: Grouped, non-aggregate and derived-value queries cannot be asserted
+    let grouped = "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id";
+    let err = assert_aggregate(grouped, &order_table(), 0).unwrap_err();
+    assert!(err.contains("one 64-bit aggregate"), "{}", err);
+
+    let plain = "SELECT amount FROM order WHERE amount < 35";
+    assert!(assert_aggregate(plain, &order_table(), 0).is_err());
+
+    // A derived value is not read from a committed column
+    let derived = "SELECT sum(amount * customer_id) FROM order";
+    let err = assert_aggregate(derived, &order_table(), 0).unwrap_err();
+    assert!(err.contains("committed column"), "{}", err);
+
+    // A SUM is filtered by the WHERE check bits: only the matching rows count
+    let filtered_sum = "SELECT sum(amount) FROM order WHERE amount < 35";
+    assert!(assert_aggregate(filtered_sum, &order_table(), 50).is_ok());
+    assert!(assert_aggregate(filtered_sum, &order_table(), 150).is_err());
+
+    // A MAX has no neutral value for the rows WHERE drops
+    let filtered_max = "SELECT max(amount) FROM order WHERE amount < 35";
+    assert!(assert_aggregate(filtered_max, &order_table(), 30).is_err());
+}
@@ -0,0 +1,104 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use poneglyphdb::sql::{schema_hash, QueryCache};
+
+type Tables = HashMap<String, HashMap<String, Vec<u64>>>;
+
+fn orders(columns: &[(&str, Vec<u64>)]) -> Tables {
+    let columns = columns
+        .iter()
+        .map(|(name, values)| (name.to_string(), values.clone()))
+        .collect();
+    HashMap::from([("orders".to_string(), columns)])
+}
+
+fn order_table() -> Tables {
+    orders(&[
+        ("customer_id", vec![1, 1, 2, 3]),
+        ("amount", vec![10, 20, 5, 100]),
+    ])
+}
+
+#[test]
+fn test_cache_hit() {
+    // Test: The same SQL over the same tables is compiled once
+    let mut cache = QueryCache::new();
+    let sql = "SELECT customer_id, sum(amount) FROM orders GROUP BY customer_id";
+
+    let first = cache.get_or_compile(sql, &order_table()).unwrap();
+    let second = cache.get_or_compile(sql, &order_table()).unwrap();
+
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+    assert_eq!(first.aggregations.len(), second.aggregations.len());
+    assert_eq!(first.aggregations[0].values, second.aggregations[0].values);
+}
+
+#[test]
+fn test_cache_miss_on_different_sql() {
+    // Test: Each distinct SQL text gets its own entry
+    let mut cache = QueryCache::new();
+    let tables = order_table();
+
+    cache
+        .get_or_compile("SELECT amount FROM orders WHERE amount > 8", &tables)
+        .unwrap();
+    let compiled = cache
+        .get_or_compile("SELECT amount FROM orders ORDER BY amount", &tables)
+        .unwrap();
+
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 2, 2));
+    assert_eq!(compiled.sorts[0].sorted_output, vec![5, 10, 20, 100]);
+
+    // Errors are returned and not cached
+    assert!(cache.get_or_compile("SELECT FROM", &tables).is_err());
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_cache_invalidated_on_schema_change() {
+    // Test: A new column changes the schema hash and recompiles the query;
+    // data alone does not change the hash
+    let mut cache = QueryCache::new();
+    let sql = "SELECT amount FROM orders ORDER BY amount";
+    let tables = order_table();
+    let mut extended = order_table();
+    extended
+        .get_mut("orders")
+        .unwrap()
+        .insert("discount".to_string(), vec![0, 1, 0, 1]);
+
+    assert_ne!(schema_hash(&tables), schema_hash(&extended));
+    let mut reordered = order_table();
+    reordered
+        .get_mut("orders")
+        .unwrap()
+        .insert("amount".to_string(), vec![4, 3, 2, 1]);
+    assert_eq!(schema_hash(&tables), schema_hash(&reordered));
+
+    cache.get_or_compile(sql, &tables).unwrap();
+    cache.get_or_compile(sql, &extended).unwrap();
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 2, 1));
+
+    // The entry now belongs to the new schema
+    cache.get_or_compile(sql, &extended).unwrap();
+    assert_eq!(cache.hits(), 1);
+}
+
+#[test]
+fn test_cache_recompiles_changed_data() {
+    // Test: The same schema over new data is not served stale witnesses
+    let mut cache = QueryCache::new();
+    let sql = "SELECT amount FROM orders ORDER BY amount";
+
+    cache.get_or_compile(sql, &order_table()).unwrap();
+    let updated = orders(&[
+        ("customer_id", vec![1, 1, 2, 3]),
+        ("amount", vec![7, 20, 5, 100]),
+    ]);
+    let compiled = cache.get_or_compile(sql, &updated).unwrap();
+
+    assert_eq!(cache.hits(), 0);
+    assert_eq!(compiled.sorts[0].sorted_output, vec![5, 7, 20, 100]);
+}
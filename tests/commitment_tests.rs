@@ -0,0 +1,226 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    dev::MockProver,
+    plonk::Error,
+};
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::{
+    AdditiveCommitment, ArithmeticChip, Commitment, DatabaseCommitmentCircuit, GateConfigs,
+    PoseidonCommitment, WindowChip,
+};
+use poneglyphdb::database::{DatabaseCommitment, DatabaseTable, HashScheme};
+
+const SCHEMES: [HashScheme; 3] = [
+    HashScheme::Insecure,
+    HashScheme::Poseidon,
+    HashScheme::Pedersen,
+];
+
+#[test]
+fn test_every_scheme_verifies_its_data() {
+    // Test: Each scheme accepts the committed data and rejects changed data
+    let data = [(1, 10), (2, 20), (3, 30)];
+    for scheme in SCHEMES {
+        let commitment = DatabaseCommitment::new(&data, scheme);
+        assert_eq!(commitment.scheme, scheme);
+        assert!(commitment.verify(&data), "{:?}", scheme);
+        assert!(!commitment.verify(&[(1, 10), (2, 21), (3, 30)]), "{:?}", scheme);
+    }
+}
+
+#[test]
+fn test_commitment_fails_under_another_scheme() {
+    // Test: A commitment relabelled with a different scheme no longer
+    // verifies, and the schemes give different commitment values
+    let data = [(1, 10), (2, 20), (3, 30)];
+    for made_with in SCHEMES {
+        for other in SCHEMES.into_iter().filter(|&scheme| scheme != made_with) {
+            let mut commitment = DatabaseCommitment::new(&data, made_with);
+            assert_ne!(
+                commitment.commitment,
+                DatabaseCommitment::new(&data, other).commitment
+            );
+
+            commitment.scheme = other;
+            assert!(!commitment.verify(&data), "{:?} as {:?}", made_with, other);
+        }
+    }
+}
+
+#[test]
+fn test_insecure_scheme_collides() {
+    // Test: The additive scheme cannot tell reordered pairs apart; Poseidon
+    // and Pedersen can
+    let data = [(1, 10), (2, 20)];
+    let reordered = [(2, 20), (1, 10)];
+    assert!(DatabaseCommitment::new(&data, HashScheme::Insecure).verify(&reordered));
+    assert!(!DatabaseCommitment::new(&data, HashScheme::Poseidon).verify(&reordered));
+    assert!(!DatabaseCommitment::new(&data, HashScheme::Pedersen).verify(&reordered));
+}
+
+#[test]
+fn test_pedersen_is_homomorphic() {
+    // Test: Pedersen points of two data sets add up to the point of their
+    // element-wise sum
+    let a = [(1, 10), (2, 20)];
+    let b = [(4, 1), (5, 2)];
+    let sum = [(5, 11), (7, 22)];
+
+    let point = |data: &[(u64, u64)]| {
+        DatabaseCommitment::new(data, HashScheme::Pedersen)
+            .point()
+            .unwrap()
+    };
+    assert_eq!(point(&a) + point(&b), point(&sum).into());
+    assert!(DatabaseCommitment::new(&a, HashScheme::Poseidon).point().is_none());
+}
+
+#[test]
+fn test_table_commit_uses_scheme() {
+    // Test: A table commitment is made over (first, second) column pairs
+    // with the requested scheme
+    let mut table = DatabaseTable::new("t".to_string(), vec!["k".to_string(), "v".to_string()]);
+    table.insert(vec![1, 10]).unwrap();
+    table.insert(vec![2, 20]).unwrap();
+
+    let commitment = table.commit(HashScheme::Poseidon);
+    assert_eq!(commitment.scheme, HashScheme::Poseidon);
+    assert!(commitment.verify(&[(1, 10), (2, 20)]));
+}
+
+#[test]
+fn test_table_map_commitment_is_deterministic() {
+    // Test: Table maps built repeatedly (in different insertion orders, each
+    // with its own hasher) give the same commitment; changed data does not
+    let tables = |reversed: bool| {
+        let mut entries = vec![
+            ("customer", vec![("id", vec![1, 2, 3]), ("age", vec![30, 40, 50])]),
+            ("order", vec![("customer_id", vec![1, 2]), ("amount", vec![10, 20])]),
+        ];
+        if reversed {
+            entries.reverse();
+            entries.iter_mut().for_each(|(_, columns)| columns.reverse());
+        }
+        entries
+            .into_iter()
+            .map(|(table, columns)| {
+                let columns = columns
+                    .into_iter()
+                    .map(|(column, values)| (column.to_string(), values))
+                    .collect::<HashMap<_, _>>();
+                (table.to_string(), columns)
+            })
+            .collect::<HashMap<_, _>>()
+    };
+
+    let expected = DatabaseCommitment::from_tables(&tables(false), HashScheme::Poseidon);
+    for i in 0..16 {
+        let commitment = DatabaseCommitment::from_tables(&tables(i % 2 == 1), HashScheme::Poseidon);
+        assert_eq!(commitment.commitment(), expected.commitment());
+    }
+
+    let mut changed = tables(false);
+    changed.get_mut("order").unwrap().get_mut("amount").unwrap()[0] = 11;
+    let commitment = DatabaseCommitment::from_tables(&changed, HashScheme::Poseidon);
+    assert_ne!(commitment.commitment(), expected.commitment());
+}
+
+#[test]
+fn test_commitment_proven_in_circuit() {
+    // Test: A Poseidon commitment is proven against the private pairs; pairs
+    // inconsistent with the public commitment fail, and the other schemes
+    // have no circuit
+    let k = 10;
+    let data = [(1, 10), (2, 20), (3, 30)];
+    let commitment = DatabaseCommitment::new(&data, HashScheme::Poseidon);
+
+    let (circuit, public_inputs) = commitment.circuit(&data).unwrap();
+    assert_eq!(public_inputs.db_commitment(), Some(commitment.commitment()));
+    let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let (circuit, public_inputs) = commitment.circuit(&[(1, 10), (2, 21), (3, 30)]).unwrap();
+    let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    for scheme in [HashScheme::Insecure, HashScheme::Pedersen] {
+        assert!(DatabaseCommitment::new(&data, scheme).circuit(&data).is_none());
+    }
+}
+
+/// Custom scheme defined outside the crate: the sum of the keys
+#[derive(Clone, Debug)]
+struct KeySumCommitment(Fr);
+
+impl Commitment for KeySumCommitment {
+    fn commit(data: &[(u64, u64)]) -> Self {
+        Self(data.iter().map(|&(key, _)| Fr::from(key)).sum())
+    }
+
+    fn value(&self) -> Fr {
+        self.0
+    }
+
+    fn constrain(
+        mut layouter: impl Layouter<Fr>,
+        gates: &GateConfigs,
+        data: &[(u64, u64)],
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let arithmetic_chip = ArithmeticChip::new(gates.arithmetic.clone());
+        let keys = data
+            .iter()
+            .map(|&(key, _)| {
+                arithmetic_chip.scale(layouter.namespace(|| "key"), Value::known(key), 1)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        WindowChip::new(gates.window.clone()).sum(layouter.namespace(|| "sum"), &keys)
+    }
+}
+
+/// Does the `C` commitment circuit over `data` verify against `public`?
+fn proves<C: Commitment>(data: &[(u64, u64)], public: Fr) -> bool {
+    let circuit = DatabaseCommitmentCircuit::<C>::new(data.to_vec());
+    let prover = MockProver::run(10, &circuit, vec![vec![public], vec![]]).unwrap();
+    prover.verify().is_ok()
+}
+
+/// Each scheme proves its own commitment and rejects another's and changed data
+fn check_scheme<C: Commitment, Other: Commitment>() {
+    let data = [(1, 10), (2, 20), (3, 30)];
+    let commitment = C::commit(&data);
+    assert!(commitment.verify(&data));
+    assert!(!commitment.verify(&[(1, 10), (2, 21), (3, 30)]));
+
+    assert!(proves::<C>(&data, commitment.value()));
+    assert!(!proves::<C>(&data, Other::commit(&data).value()));
+    assert!(!proves::<C>(&[(1, 10), (2, 20), (3, 31)], commitment.value()));
+    assert!(proves::<C>(&[], C::commit(&[]).value()));
+}
+
+#[test]
+fn test_commitment_trait_dispatch() {
+    // Test: The circuit proves whichever scheme it is instantiated with,
+    // including one implemented outside the crate; the built-in schemes
+    // match `DatabaseCommitment`
+    check_scheme::<AdditiveCommitment, PoseidonCommitment>();
+    check_scheme::<PoseidonCommitment, AdditiveCommitment>();
+    check_scheme::<PoseidonCommitment, KeySumCommitment>();
+
+    // The custom scheme ignores values, so changed values still verify
+    let data = [(1, 10), (2, 20), (3, 30)];
+    let key_sum = KeySumCommitment::commit(&data);
+    assert_eq!(key_sum.value(), Fr::from(6));
+    assert!(proves::<KeySumCommitment>(&[(1, 0), (2, 0), (3, 0)], key_sum.value()));
+    assert!(!proves::<KeySumCommitment>(&data, Fr::from(7)));
+
+    for (scheme, value) in [
+        (HashScheme::Insecure, AdditiveCommitment::commit(&data).value()),
+        (HashScheme::Poseidon, PoseidonCommitment::commit(&data).value()),
+    ] {
+        assert_eq!(DatabaseCommitment::new(&data, scheme).commitment(), value);
+    }
+}
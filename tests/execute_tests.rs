@@ -0,0 +1,263 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use poneglyphdb::sql::*;
+
+/// Build table data in the compiler's column-major shape
+/// (table_name -> column_name -> values)
+fn table(name: &str, columns: &[(&str, Vec<u64>)]) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut table_columns = HashMap::new();
+    for (column, values) in columns {
+        table_columns.insert(column.to_string(), values.clone());
+    }
+
+    let mut table_data = HashMap::new();
+    table_data.insert(name.to_string(), table_columns);
+    table_data
+}
+
+/// Order table: customers 1-3 with amounts summing to 30, 10, 100
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table(
+        "order",
+        &[
+            ("id", vec![1, 2, 3, 4, 5]),
+            ("customer_id", vec![2, 1, 3, 1, 2]),
+            ("amount", vec![5, 10, 100, 20, 5]),
+        ],
+    )
+}
+
+fn execute(sql: &str, table_data: &HashMap<String, HashMap<String, Vec<u64>>>) -> QueryResult {
+    SQLParser::parse(sql).unwrap().execute(table_data).unwrap()
+}
+
+#[test]
+fn test_execute_where_filters_rows() {
+    // Test: Constant, AND / OR, column and expression comparisons keep the matching rows
+    let data = order_table();
+
+    let result = execute("SELECT id, amount FROM order WHERE amount > 5", &data);
+    assert_eq!(result.columns, vec!["id", "amount"]);
+    assert_eq!(result.rows, vec![vec![2, 10], vec![3, 100], vec![4, 20]]);
+
+    let result = execute(
+        "SELECT id FROM order WHERE customer_id = 2 OR amount = 100",
+        &data,
+    );
+    assert_eq!(result.column("id").unwrap(), vec![1, 3, 5]);
+
+    let result = execute(
+        "SELECT id FROM order WHERE customer_id = 1 AND amount < 15",
+        &data,
+    );
+    assert_eq!(result.column("id").unwrap(), vec![2]);
+
+    let result = execute("SELECT id FROM order WHERE customer_id > id", &data);
+    assert_eq!(result.column("id").unwrap(), vec![1]);
+
+    let result = execute("SELECT id FROM order WHERE amount * 2 + id > 40", &data);
+    assert_eq!(result.column("id").unwrap(), vec![3, 4]);
+
+    let result = execute("SELECT id FROM order WHERE amount < 0", &data);
+    assert!(result.rows.is_empty());
+}
+
+#[test]
+fn test_execute_group_by_aggregations() {
+    // Test: One row per group in key order, with SUM / COUNT / MAX / MIN per group
+    let result = execute(
+        "SELECT customer_id, sum(amount), count(amount), max(amount), min(amount) FROM order GROUP BY customer_id",
+        &order_table(),
+    );
+    assert_eq!(
+        result.rows,
+        vec![
+            vec![1, 30, 2, 20, 10],
+            vec![2, 10, 2, 5, 5],
+            vec![3, 100, 1, 100, 100],
+        ]
+    );
+}
+
+#[test]
+fn test_execute_aggregation_without_group_by() {
+    // Test: Aggregations over the whole (filtered) table yield a single row,
+    // also when no rows match
+    let data = order_table();
+
+    let result = execute(
+        "SELECT sum(amount), count(*) FROM order WHERE amount > 5",
+        &data,
+    );
+    assert_eq!(result.rows, vec![vec![130, 3]]);
+
+    let result = execute(
+        "SELECT sum(amount), count(amount) FROM order WHERE amount > 500",
+        &data,
+    );
+    assert_eq!(result.rows, vec![vec![NULL, 0]]);
+}
+
+#[test]
+fn test_execute_matches_compiled_group_results() {
+    // Test: The reference result equals the per-group results the circuit proves
+    let data = order_table();
+    let query = SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+        .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    let result = query.execute(&data).unwrap();
+
+    assert_eq!(
+        result.column("sum(amount)").unwrap(),
+        compiled.aggregations[0].group_results()
+    );
+}
+
+#[test]
+fn test_execute_having_and_order_by_aggregation() {
+    // Test: HAVING drops groups, ORDER BY sorts by the aggregation result
+    let result = execute(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id HAVING sum(amount) > 10 ORDER BY sum(amount) DESC",
+        &order_table(),
+    );
+    assert_eq!(result.rows, vec![vec![3, 100], vec![1, 30]]);
+}
+
+#[test]
+fn test_execute_order_by_nulls() {
+    // Test: ORDER BY places NULLs as requested and breaks ties with the next key
+    let data = table(
+        "t",
+        &[("id", vec![1, 2, 3, 4]), ("score", vec![7, NULL, 3, 7])],
+    );
+
+    let result = execute("SELECT id FROM t ORDER BY score ASC", &data);
+    assert_eq!(result.column("id").unwrap(), vec![3, 1, 4, 2]);
+
+    let result = execute("SELECT id FROM t ORDER BY score ASC NULLS FIRST", &data);
+    assert_eq!(result.column("id").unwrap(), vec![2, 3, 1, 4]);
+
+    let result = execute("SELECT id FROM t ORDER BY score DESC, id DESC", &data);
+    assert_eq!(result.column("id").unwrap(), vec![2, 4, 1, 3]);
+}
+
+#[test]
+fn test_execute_window_functions() {
+    // Test: Running SUM and ROW_NUMBER over the window order
+    let result = execute(
+        "SELECT id, sum(amount) over (order by id), row_number() over (order by id) FROM order",
+        &order_table(),
+    );
+    assert_eq!(
+        result.rows,
+        vec![
+            vec![1, 5, 1],
+            vec![2, 15, 2],
+            vec![3, 115, 3],
+            vec![4, 135, 4],
+            vec![5, 140, 5],
+        ]
+    );
+}
+
+#[test]
+fn test_execute_join_and_exists() {
+    // Test: Comma joins match on the WHERE equality; EXISTS / NOT EXISTS filter
+    // on the correlated subquery
+    let mut data = order_table();
+    data.extend(table(
+        "customer",
+        &[("id", vec![1, 2, 4]), ("region", vec![10, 20, 40])],
+    ));
+
+    let result = execute(
+        "SELECT order.id, customer.region FROM order, customer WHERE order.customer_id = customer.id",
+        &data,
+    );
+    assert_eq!(
+        result.rows,
+        vec![vec![1, 20], vec![2, 10], vec![4, 10], vec![5, 20]]
+    );
+
+    let result = execute(
+        "SELECT id FROM customer WHERE EXISTS (SELECT id FROM order WHERE order.customer_id = customer.id)",
+        &data,
+    );
+    assert_eq!(result.column("id").unwrap(), vec![1, 2]);
+
+    let result = execute(
+        "SELECT id FROM customer WHERE NOT EXISTS (SELECT id FROM order WHERE order.customer_id = customer.id)",
+        &data,
+    );
+    assert_eq!(result.column("id").unwrap(), vec![4]);
+}
+
+#[test]
+fn test_execute_union() {
+    // Test: UNION ALL keeps both sides' rows, UNION the distinct rows in order
+    let data = order_table();
+
+    let result = execute(
+        "SELECT customer_id FROM order WHERE amount > 10 UNION ALL SELECT customer_id FROM order WHERE amount < 10",
+        &data,
+    );
+    assert_eq!(result.column("customer_id").unwrap(), vec![3, 1, 2, 2]);
+
+    let result = execute(
+        "SELECT customer_id FROM order WHERE amount > 10 UNION SELECT customer_id FROM order WHERE amount < 10",
+        &data,
+    );
+    assert_eq!(result.column("customer_id").unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_execute_rejects_ungrouped_column() {
+    // Test: A plain column outside GROUP BY in an aggregate query is an error
+    let query = SQLParser::parse("SELECT id, sum(amount) FROM order GROUP BY customer_id").unwrap();
+    assert!(query.execute(&order_table()).is_err());
+}
+
+#[test]
+fn test_typed_result_json_decimal_scale() {
+    // Test: Decimal columns render with exactly `scale` fractional digits,
+    // booleans and NULLs as JSON literals, other columns as integers
+    let data = table(
+        "payment",
+        &[
+            ("id", vec![1, 2, 3, 4]),
+            ("price", vec![1250, 5, 100_000, NULL]),
+            ("refunded", vec![0, 1, 0, 1]),
+        ],
+    );
+    let schema = HashMap::from([
+        ("price".to_string(), ColumnType::Decimal { scale: 2 }),
+        ("refunded".to_string(), ColumnType::Bool),
+    ]);
+
+    let typed = execute("SELECT id, price, refunded FROM payment", &data).typed(&schema);
+    assert_eq!(
+        typed.rows[0][1],
+        TypedValue::Decimal {
+            value: 1250,
+            scale: 2
+        }
+    );
+    assert_eq!(
+        typed.to_json(),
+        concat!(
+            r#"{"columns":["id","price","refunded"],"rows":["#,
+            r#"[1,12.50,false],[2,0.05,true],[3,1000.00,false],[4,null,true]]}"#
+        )
+    );
+    assert!(serde_json::from_str::<serde_json::Value>(&typed.to_json()).is_ok());
+
+    // Aggregates are typed by their output column name
+    let schema = HashMap::from([("sum(price)".to_string(), ColumnType::Decimal { scale: 3 })]);
+    let typed = execute("SELECT sum(price) FROM payment WHERE id < 3", &data).typed(&schema);
+    assert_eq!(
+        typed.to_json(),
+        r#"{"columns":["sum(price)"],"rows":[[1.255]]}"#
+    );
+}
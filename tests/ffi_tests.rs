@@ -0,0 +1,103 @@
+use poneglyphdb::ffi::*;
+
+const SUM_BY_REGION: &str = "SELECT region, sum(amount) FROM lineitem GROUP BY region";
+
+/// Lineitem table as the C API's JSON
+fn lineitem_json(amounts: &[u64]) -> String {
+    let regions: Vec<u64> = (0..amounts.len() as u64).map(|i| i / 2).collect();
+    format!(
+        r#"{{"lineitem": {{"region": {:?}, "amount": {:?}}}}}"#,
+        regions, amounts
+    )
+}
+
+/// Call `pg_prove` on Rust strings
+fn prove(sql: &str, table_json: &str) -> *mut ProofHandle {
+    unsafe {
+        pg_prove(
+            sql.as_ptr(),
+            sql.len(),
+            table_json.as_ptr(),
+            table_json.len(),
+        )
+    }
+}
+
+/// Call `pg_verify` on Rust strings and a length-prefixed proof
+fn verify(sql: &str, table_json: &str, proof: *const u8) -> i32 {
+    unsafe {
+        pg_verify(
+            sql.as_ptr(),
+            sql.len(),
+            table_json.as_ptr(),
+            table_json.len(),
+            proof,
+        )
+    }
+}
+
+/// Bytes of a handle's length-prefixed buffer
+fn buffer_bytes(handle: *const ProofHandle) -> Vec<u8> {
+    unsafe {
+        let buffer = pg_proof_buffer(handle);
+        let mut len = [0u8; 8];
+        len.copy_from_slice(std::slice::from_raw_parts(buffer, 8));
+        std::slice::from_raw_parts(buffer.add(8), u64::from_le_bytes(len) as usize).to_vec()
+    }
+}
+
+#[test]
+fn test_ffi_prove_and_verify() {
+    // Test: A proof created through pg_prove verifies through pg_verify
+    let table = lineitem_json(&[3, 4, 5, 6]);
+    let handle = prove(SUM_BY_REGION, &table);
+
+    unsafe {
+        assert_eq!(pg_proof_status(handle), PG_OK);
+    }
+    assert!(!buffer_bytes(handle).is_empty());
+
+    let proof = unsafe { pg_proof_buffer(handle) };
+    assert_eq!(verify(SUM_BY_REGION, &table, proof), PG_OK);
+
+    unsafe { pg_free_proof(handle) };
+}
+
+#[test]
+fn test_ffi_errors_are_codes_and_messages() {
+    // Test: Invalid inputs return an error code and a message buffer
+    let handle = prove(SUM_BY_REGION, "not json");
+    unsafe {
+        assert_eq!(pg_proof_status(handle), PG_ERR_INVALID_TABLE);
+    }
+    assert!(!buffer_bytes(handle).is_empty());
+    unsafe { pg_free_proof(handle) };
+
+    let handle = prove("DELETE FROM lineitem", &lineitem_json(&[1, 2]));
+    unsafe {
+        assert_eq!(pg_proof_status(handle), PG_ERR_QUERY);
+        pg_free_proof(handle);
+    }
+
+    // Null arguments are reported, not dereferenced
+    let handle = unsafe { pg_prove(std::ptr::null(), 0, std::ptr::null(), 0) };
+    unsafe {
+        assert_eq!(pg_proof_status(handle), PG_ERR_NULL_POINTER);
+        pg_free_proof(handle);
+        assert_eq!(pg_proof_status(std::ptr::null()), PG_ERR_NULL_POINTER);
+        assert!(pg_proof_buffer(std::ptr::null()).is_null());
+        pg_free_proof(std::ptr::null_mut());
+    }
+    let table = lineitem_json(&[1, 2]);
+    assert_eq!(
+        verify(SUM_BY_REGION, &table, std::ptr::null()),
+        PG_ERR_NULL_POINTER
+    );
+
+    // A truncated proof is rejected
+    let truncated = [4u8, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4];
+    assert_eq!(
+        verify(SUM_BY_REGION, &table, truncated.as_ptr()),
+        PG_ERR_VERIFY
+    );
+}
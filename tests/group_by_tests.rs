@@ -14,6 +14,7 @@ struct GroupByTestCircuit {
 
 /// Config for test circuit
 #[derive(Clone)]
+#[allow(dead_code)]
 struct TestConfig {
     poneglyph_config: PoneglyphConfig,
     range_check_config: RangeCheckConfig,
@@ -75,7 +76,7 @@ fn test_group_by_single_group() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![1, 1, 1, 1, 1],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -87,7 +88,7 @@ fn test_group_by_multiple_groups() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![1, 1, 2, 2, 2, 3, 3],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -99,7 +100,7 @@ fn test_group_by_single_element_groups() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![1, 2, 3, 4, 5],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -111,7 +112,7 @@ fn test_group_by_empty() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -123,7 +124,7 @@ fn test_group_by_single_element() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![42],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -142,7 +143,7 @@ fn test_group_by_large_dataset() {
     let circuit = GroupByTestCircuit {
         group_keys,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -154,8 +155,81 @@ fn test_group_by_mixed_sizes() {
     let circuit = GroupByTestCircuit {
         group_keys: vec![1, 1, 2, 3, 3, 3, 3, 4, 5, 5],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
 
+/// Circuit proving a GROUP BY over grouped keys with a counted number of groups
+fn group_count_circuit(group_keys: Vec<u64>, max_groups: Option<u64>) -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        group_bys: vec![GroupByOp {
+            max_groups,
+            public_group_count: true,
+            ..GroupByOp::new(group_keys)
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_group_count_bound_to_query_result() {
+    // Test: The boundaries sum to the number of groups, bound to the query
+    // result; a higher claim or a maximum below the count fails
+    let k = 10;
+    let keys = vec![1, 1, 2, 2, 2, 3, 3];
+    assert_eq!(GroupByOp::new(keys.clone()).group_count(), 3);
+    assert_eq!(GroupByOp::new(vec![42]).group_count(), 1);
+    assert_eq!(GroupByOp::new(Vec::new()).group_count(), 0);
+
+    let count = |value: u64| PublicInputs {
+        db_commitments: Vec::new(),
+        query_result: Some(Fr::from(value)),
+    };
+
+    let circuit = group_count_circuit(keys.clone(), Some(50));
+    let prover = MockProver::run(k, &circuit, count(3).to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(k, &circuit, count(4).to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Exactly the maximum is allowed, one group more is not
+    let circuit = group_count_circuit(keys.clone(), Some(3));
+    let prover = MockProver::run(k, &circuit, count(3).to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let circuit = group_count_circuit(keys, Some(2));
+    let prover = MockProver::run(k, &circuit, count(3).to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A single key is one group
+    let circuit = group_count_circuit(vec![42], None);
+    let prover = MockProver::run(k, &circuit, count(1).to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_group_by_adjacent_unsorted_keys() {
+    // Test: Grouping needs equal keys adjacent, not sorted; descending and
+    // hash-ordered runs (including keys above 2^63) count one group each
+    let k = 10;
+    let count = |value: u64| PublicInputs {
+        db_commitments: Vec::new(),
+        query_result: Some(Fr::from(value)),
+    };
+
+    let keys = vec![9, 9, 4, 4, 4, u64::MAX, 0, 0, 1 << 63, 7];
+    let circuit = group_count_circuit(keys.clone(), None);
+    assert_eq!(circuit.group_bys[0].group_count(), 6);
+    let prover = MockProver::run(k, &circuit, count(6).to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(k, &circuit, count(5).to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A key split across two runs is two groups
+    let circuit = group_count_circuit(vec![2, 1, 2], None);
+    let prover = MockProver::run(k, &circuit, count(3).to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
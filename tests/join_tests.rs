@@ -17,6 +17,7 @@ struct JoinTestCircuit {
 
 /// Config for test circuit
 #[derive(Clone)]
+#[allow(dead_code)]
 struct TestConfig {
     poneglyph_config: PoneglyphConfig,
     range_check_config: RangeCheckConfig,
@@ -85,7 +86,7 @@ fn test_join_simple_inner() {
         table2_keys: vec![2, 3, 4],
         table2_values: vec![200, 300, 400],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -100,7 +101,7 @@ fn test_join_pk_fk() {
         table2_keys: vec![1, 1, 2],      // Foreign keys (duplicates allowed)
         table2_values: vec![11, 12, 21],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -115,7 +116,7 @@ fn test_join_empty_table() {
         table2_keys: vec![1, 2, 3],
         table2_values: vec![10, 20, 30],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -130,7 +131,7 @@ fn test_join_single_record() {
         table2_keys: vec![42],
         table2_values: vec![200],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -145,7 +146,7 @@ fn test_join_no_matches() {
         table2_keys: vec![4, 5, 6],
         table2_values: vec![40, 50, 60],
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -173,7 +174,7 @@ fn test_join_large_dataset() {
         table2_keys,
         table2_values,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
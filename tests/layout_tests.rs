@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use halo2_proofs::circuit::Value;
+use poneglyphdb::circuit::*;
+use poneglyphdb::sql::*;
+
+/// Small circuit: a WHERE range check and a GROUP BY sum over 8 rows
+fn small_circuit() -> PoneglyphCircuit {
+    let mut columns = HashMap::new();
+    columns.insert("region".to_string(), vec![1, 2, 1, 3, 2, 1, 3, 2]);
+    columns.insert("amount".to_string(), vec![10, 20, 30, 40, 50, 60, 70, 80]);
+    let mut table_data = HashMap::new();
+    table_data.insert("t".to_string(), columns);
+
+    let sql = "SELECT region, sum(amount) FROM t WHERE amount < 55 GROUP BY region";
+    let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &table_data).unwrap();
+    PoneglyphCircuit::from_compiled(compiled, None, None)
+}
+
+#[test]
+fn test_write_layout_produces_svg() {
+    // Test: The layout of a small circuit is written as an SVG file with
+    // its operation regions labelled
+    let circuit = small_circuit();
+    let k = circuit.stats().unwrap().min_k;
+    let path = std::env::temp_dir().join(format!("poneglyph_layout_{}.svg", std::process::id()));
+
+    circuit.write_layout(&path, k).unwrap();
+    let svg = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(svg.starts_with("<svg"), "{}", &svg[..svg.len().min(100)]);
+    // Region names of the Range Check and Group-By Gates
+    assert!(svg.contains("check x &lt; t"));
+    assert!(svg.contains("group and verify"));
+}
+
+#[test]
+fn test_write_layout_unwritable_path_fails() {
+    // Test: A path in a missing directory is an error, not a panic
+    let circuit = small_circuit();
+    let k = circuit.stats().unwrap().min_k;
+    let path = std::env::temp_dir().join("poneglyph_missing_dir").join("layout.svg");
+    assert!(circuit.write_layout(&path, k).is_err());
+}
@@ -0,0 +1,178 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use ff::Field;
+use halo2_proofs::dev::MockProver;
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::merkle::leaf_hash;
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::QueryError;
+
+/// Order table (id, amount) with 5 rows
+fn order_table() -> DatabaseTable {
+    let mut table = DatabaseTable::new(
+        "order".to_string(),
+        vec!["id".to_string(), "amount".to_string()],
+    );
+    for (id, amount) in [(7, 100), (42, 250), (13, 90), (99, 5), (8, 1)] {
+        table.insert(vec![id, amount]).unwrap();
+    }
+    table
+}
+
+#[test]
+fn test_key_tree_orders_distinct_keys() {
+    // Test: The key tree holds the sorted distinct keys between zero leaves,
+    // whatever the row order and duplicates
+    let leaves = [7, 8, 13, 42, 99].iter().map(|&key| leaf_hash(&[key]));
+    let expected = MerkleTree::new(
+        std::iter::once(Fr::ZERO)
+            .chain(leaves)
+            .chain(std::iter::once(Fr::ZERO))
+            .collect(),
+    );
+    assert_eq!(key_tree(&[42, 7, 13, 99, 8, 13]).root(), expected.root());
+    assert_eq!(
+        order_table().key_tree("id").unwrap().root(),
+        expected.root()
+    );
+    assert_eq!(
+        key_tree(&[]).root(),
+        MerkleTree::new(vec![Fr::ZERO; 2]).root()
+    );
+}
+
+#[test]
+fn test_non_membership_proves_absent_key() {
+    // Test: id 20 is absent; its neighbours 13 and 42 are adjacent leaves of
+    // the key tree, with the root and the key as public inputs
+    let table = order_table();
+    let (circuit, public_inputs) = table.non_membership("id", 20).unwrap();
+    assert_eq!((circuit.lower, circuit.upper), (Some(13), Some(42)));
+    assert_eq!((circuit.paths[0].index, circuit.paths[1].index), (3, 4));
+    assert_eq!(
+        public_inputs.db_commitment(),
+        Some(table.key_tree("id").unwrap().root())
+    );
+    assert_eq!(public_inputs.query_result, Some(Fr::from(20)));
+
+    let prover = MockProver::run(10, &circuit, public_inputs.to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let context = ProvingContext::new(10, &circuit).unwrap();
+    let proof = context
+        .prove(&circuit, &public_inputs.to_instances())
+        .unwrap();
+    assert!(context
+        .verify(&proof, &public_inputs.to_instances())
+        .unwrap());
+}
+
+#[test]
+fn test_non_membership_below_and_above_keys() {
+    // Test: Keys below the smallest and above the largest id prove absent
+    // against a zero leaf, and any key is absent from an empty column
+    let table = order_table();
+    for key in [0, 6, 100, u64::MAX] {
+        let (circuit, public_inputs) = table.non_membership("id", key).unwrap();
+        let prover = MockProver::run(10, &circuit, public_inputs.to_instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "key {}", key);
+    }
+
+    let empty = DatabaseTable::new("empty".to_string(), vec!["id".to_string()]);
+    let (circuit, public_inputs) = empty.non_membership("id", 42).unwrap();
+    assert_eq!((circuit.lower, circuit.upper), (None, None));
+    let prover = MockProver::run(10, &circuit, public_inputs.to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_non_membership_of_present_key_fails() {
+    // Test: A present key has no non-membership proof; leaves around it
+    // that are not adjacent, padding passed off as the lower edge, a target
+    // outside the bounds and a wrong public key all fail
+    let table = order_table();
+    assert!(matches!(
+        table.non_membership("id", 42),
+        Err(QueryError::KeyFound { key: 42, .. })
+    ));
+    assert!(matches!(
+        table.non_membership("missing", 1),
+        Err(QueryError::UnknownColumn { .. })
+    ));
+
+    let tree = table.key_tree("id").unwrap();
+    let root = tree.root();
+    let forged_inputs = PublicInputs::new(root, Fr::from(42));
+
+    // 13 and 99 (leaves 3 and 5) skip key 42 (leaf 4)
+    let skipping = NonMembershipCircuit {
+        lower: Some(13),
+        upper: Some(99),
+        paths: [tree.path(3).unwrap(), tree.path(5).unwrap()],
+        target: 42,
+    };
+    let prover = MockProver::run(10, &skipping, forged_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Padding leaves 6 and 7 are zero, but only leaf 0 bounds from below
+    let padding = NonMembershipCircuit {
+        lower: None,
+        upper: None,
+        paths: [tree.path(6).unwrap(), tree.path(7).unwrap()],
+        target: 42,
+    };
+    let prover = MockProver::run(10, &padding, forged_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Target outside its neighbours
+    let (circuit, public_inputs) = table.non_membership("id", 20).unwrap();
+    let outside = NonMembershipCircuit {
+        target: 42,
+        ..circuit.clone()
+    };
+    let prover = MockProver::run(10, &outside, forged_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    let wrong_key = PublicInputs::new(public_inputs.db_commitment().unwrap(), Fr::from(21));
+    let prover = MockProver::run(10, &circuit, wrong_key.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_non_membership_rejects_forged_root() {
+    // Test: A proof against a root that is not the column's key tree fails
+    // against the committed root: a gap tree hiding key 42 in the gap
+    // [14, 42], and a key tree of the keys without 42
+    let table = order_table();
+    let committed = PublicInputs::new(table.key_tree("id").unwrap().root(), Fr::from(42));
+
+    let gaps = vec![
+        vec![0, 6],
+        vec![9, 12],
+        vec![14, 42],
+        vec![43, 98],
+        vec![100, u64::MAX],
+    ];
+    let gap_root = MerkleTree::from_rows(&gaps).root();
+    let (forged, forged_root) = NonMembershipCircuit::new(&[7, 8, 13, 99], 42).unwrap();
+    let gap_inputs = PublicInputs::new(gap_root, Fr::from(42));
+    let prover = MockProver::run(10, &forged, gap_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // The forged key tree's own root verifies, but not as the column's
+    let forged_inputs = PublicInputs::new(forged_root, Fr::from(42));
+    let prover = MockProver::run(10, &forged, forged_inputs.to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    let prover = MockProver::run(10, &forged, committed.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    let context = ProvingContext::new(10, &forged).unwrap();
+    let proof = context
+        .prove(&forged, &forged_inputs.to_instances())
+        .unwrap();
+    assert!(!context
+        .verify(&proof, &committed.to_instances())
+        .unwrap_or(false));
+}
@@ -0,0 +1,34 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::sync::Arc;
+
+use poneglyphdb::circuit::PoneglyphCircuit;
+use poneglyphdb::optimization::ParallelProcessor;
+use rayon::ThreadPoolBuilder;
+
+/// Circuit without operations
+fn empty_circuit() -> Arc<PoneglyphCircuit> {
+    Arc::new(PoneglyphCircuit::default())
+}
+
+#[test]
+fn test_parallel_proof_generation_on_external_pool() {
+    // Test: Work submitted to a caller-owned pool returns a result for every
+    // circuit, in circuit order, and the pool stays usable afterwards
+    let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+    let circuits: Vec<_> = (0..8).map(|_| empty_circuit()).collect();
+
+    let results = ParallelProcessor::parallel_proof_generation_in(circuits, &pool).unwrap();
+    assert_eq!(results.len(), 8);
+    for (id, result) in results.iter().enumerate() {
+        assert_eq!(result.circuit_id, id);
+        assert!(result.success);
+    }
+    assert_eq!(pool.install(rayon::current_num_threads), 3);
+
+    // The count-based method gives the same results on its own pool
+    let circuits: Vec<_> = (0..8).map(|_| empty_circuit()).collect();
+    let counted = ParallelProcessor::parallel_proof_generation(circuits, 2).unwrap();
+    let ids: Vec<_> = counted.iter().map(|result| result.circuit_id).collect();
+    assert_eq!(ids, (0..8).collect::<Vec<_>>());
+}
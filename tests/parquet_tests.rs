@@ -0,0 +1,107 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow_array::{Float64Array, Int32Array, RecordBatch, UInt64Array};
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::sql::*;
+
+/// Write a 6-row `lineitem.parquet` (row groups of 2 rows) into a fresh directory
+fn lineitem_parquet(dir: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("poneglyph-parquet-{}-{}", dir, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("lineitem.parquet");
+
+    let batch = RecordBatch::try_from_iter([
+        (
+            "id",
+            Arc::new(UInt64Array::from(vec![1, 2, 3, 4, 5, 6])) as _,
+        ),
+        (
+            "quantity",
+            Arc::new(Int32Array::from(vec![5, 12, 7, 30, 2, 18])) as _,
+        ),
+        (
+            "price",
+            Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])) as _,
+        ),
+    ])
+    .unwrap();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(2)
+        .build();
+    let mut writer = ArrowWriter::try_new(
+        File::create(&path).unwrap(),
+        batch.schema(),
+        Some(properties),
+    )
+    .unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    path
+}
+
+#[test]
+fn test_parquet_allow_list_compiles() {
+    // Test: Allow-listed integer columns load into the table_data shape and compile
+    let path = lineitem_parquet("allow-list");
+
+    let table = DatabaseTable::from_parquet(&path, Some(&["id", "quantity"])).unwrap();
+    assert_eq!(table.name, "lineitem");
+    assert_eq!(table.columns, vec!["id", "quantity"]);
+    assert_eq!(table.data.len(), 6);
+    assert_eq!(table.data[3], vec![4, 30]);
+
+    let query = SQLParser::parse("SELECT id FROM lineitem WHERE quantity < 10").unwrap();
+    let data = table.to_column_map();
+    assert!(query.validate(&data).is_ok());
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.range_checks.len(), 6);
+
+    // The float column is only read (and rejected) without the allow-list
+    assert!(matches!(
+        DatabaseTable::from_parquet(&path, None),
+        Err(QueryError::UnsupportedColumnType { column, .. }) if column == "price"
+    ));
+    assert!(matches!(
+        DatabaseTable::from_parquet(&path, Some(&["id", "discount"])),
+        Err(QueryError::UnknownColumn { column, .. }) if column == "discount"
+    ));
+    assert!(matches!(
+        DatabaseTable::from_parquet(path.with_file_name("missing.parquet"), None),
+        Err(QueryError::Read { .. })
+    ));
+}
+
+#[test]
+fn test_parquet_where_skips_row_groups() {
+    // Test: Row groups whose min / max statistics rule the WHERE clause out are skipped
+    let path = lineitem_parquet("where");
+    let columns: &[&str] = &["id", "quantity"];
+    let load = |sql: &str| {
+        let query = SQLParser::parse(sql).unwrap();
+        DatabaseTable::from_parquet_where(
+            &path,
+            Some(columns),
+            query.where_clause.as_ref().unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Row groups: ids [1, 2], [3, 4], [5, 6]
+    let table = load("SELECT id FROM lineitem WHERE id > 4");
+    assert_eq!(table.data, vec![vec![5, 2], vec![6, 18]]);
+
+    let table = load("SELECT id FROM lineitem WHERE id = 3 OR id < 2");
+    assert_eq!(
+        table.data.iter().map(|row| row[0]).collect::<Vec<_>>(),
+        vec![1, 2, 3, 4]
+    );
+
+    // No statistics rule out a column comparison: every row group is kept
+    let table = load("SELECT id FROM lineitem WHERE quantity > id");
+    assert_eq!(table.data.len(), 6);
+}
@@ -0,0 +1,29 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use poneglyphdb::sql::*;
+use proptest::prelude::*;
+
+mod sql_generators;
+
+proptest! {
+    #[test]
+    fn test_display_round_trips(query in sql_generators::query()) {
+        // Test: Rendering a parser-shaped query and parsing it back yields the same AST
+        let sql = query.to_string();
+        let parsed = SQLParser::parse(&sql);
+        prop_assert_eq!(parsed, Ok(query), "rendered as: {}", sql);
+    }
+
+    #[test]
+    fn test_parse_arbitrary_string_never_panics(sql in any::<String>()) {
+        // Test: Arbitrary input returns Ok or Err, never panics
+        let _ = SQLParser::parse(&sql);
+    }
+
+    #[test]
+    fn test_parse_sql_like_string_never_panics(sql in sql_generators::sql_like()) {
+        // Test: Keyword / operator soup returns Ok or Err, never panics
+        let _ = SQLParser::parse(&format!("select {}", sql));
+        let _ = SQLParser::parse(&sql);
+    }
+}
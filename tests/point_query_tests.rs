@@ -0,0 +1,93 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use halo2_proofs::dev::MockProver;
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::QueryError;
+
+/// Order table (id, amount) with 5 rows (padded to 8 leaves)
+fn order_table() -> DatabaseTable {
+    let mut table = DatabaseTable::new(
+        "order".to_string(),
+        vec!["id".to_string(), "amount".to_string()],
+    );
+    for (id, amount) in [(7, 100), (42, 250), (13, 90), (99, 5), (1, 1)] {
+        table.insert(vec![id, amount]).unwrap();
+    }
+    table
+}
+
+#[test]
+fn test_merkle_paths_reach_root() {
+    // Test: Every row's path leads from its leaf hash to the root
+    let table = order_table();
+    let tree = table.merkle_tree();
+    for (index, row) in table.data.iter().enumerate() {
+        let path = tree.path(index).unwrap();
+        assert_eq!(path.siblings.len(), 3);
+        assert_eq!(path.root(merkle::leaf_hash(row)), tree.root());
+    }
+    assert!(tree.path(8).is_none());
+}
+
+#[test]
+fn test_point_query_proves_amount() {
+    // Test: SELECT amount FROM order WHERE id = 42 proves row 1 only,
+    // with the Merkle root and amount as public inputs
+    let table = order_table();
+    let (circuit, public_inputs) = table.point_query("id", 42, "amount").unwrap();
+    assert_eq!(circuit.result(), Some(250));
+    assert_eq!(
+        public_inputs.db_commitment(),
+        Some(table.merkle_tree().root())
+    );
+    assert_eq!(public_inputs.query_result, Some(Fr::from(250)));
+
+    let prover = MockProver::run(10, &circuit, public_inputs.to_instances()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let context = ProvingContext::new(10, &circuit).unwrap();
+    let proof = context
+        .prove(&circuit, &public_inputs.to_instances())
+        .unwrap();
+    assert!(context
+        .verify(&proof, &public_inputs.to_instances())
+        .unwrap());
+}
+
+#[test]
+fn test_point_query_wrong_amount_or_non_member_fails() {
+    // Test: A wrong public amount, a row outside the tree and a key that is
+    // not the row's key all fail; a missing key has no point query
+    let table = order_table();
+    let (circuit, public_inputs) = table.point_query("id", 42, "amount").unwrap();
+
+    let wrong_amount = PublicInputs::new(public_inputs.db_commitment().unwrap(), Fr::from(251));
+    let prover = MockProver::run(10, &circuit, wrong_amount.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Non-member row (id 43) on row 1's path
+    let forged = PointQueryCircuit {
+        row: vec![43, 250],
+        key: 43,
+        ..circuit.clone()
+    };
+    let prover = MockProver::run(10, &forged, public_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Member row, but its id is not the queried key
+    let wrong_key = PointQueryCircuit { key: 43, ..circuit };
+    let prover = MockProver::run(10, &wrong_key, public_inputs.to_instances()).unwrap();
+    assert!(prover.verify().is_err());
+
+    assert!(matches!(
+        table.point_query("id", 43, "amount"),
+        Err(QueryError::KeyNotFound { key: 43, .. })
+    ));
+    assert!(matches!(
+        table.point_query("id", 42, "price"),
+        Err(QueryError::UnknownColumn { .. })
+    ));
+}
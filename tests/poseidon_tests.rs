@@ -0,0 +1,337 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::{dev::MockProver, plonk::Error};
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::*;
+
+/// Circuit committing to the given result rows
+fn commitment_circuit(rows: Vec<Vec<u64>>) -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        result_commitment: Some(ResultCommitment::new(rows)),
+        ..Default::default()
+    }
+}
+
+/// Account table: every row is under budget
+fn account_table(spent: Vec<u64>) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("spent".to_string(), spent);
+    columns.insert("budget".to_string(), vec![1000, 900, 5000]);
+    let mut data = HashMap::new();
+    data.insert("account".to_string(), columns);
+    data
+}
+
+/// Order (customer_id, amount) and customer (id, balance) tables
+fn order_customer_tables(
+    amount: Vec<u64>,
+    balance: Vec<u64>,
+) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut order = HashMap::new();
+    order.insert("customer_id".to_string(), vec![1, 1, 2, 2, 3]);
+    order.insert("amount".to_string(), amount);
+    let mut customer = HashMap::new();
+    customer.insert("id".to_string(), vec![1, 2, 3]);
+    customer.insert("balance".to_string(), balance);
+    let mut data = HashMap::new();
+    data.insert("order".to_string(), order);
+    data.insert("customer".to_string(), customer);
+    data
+}
+
+/// Circuit proving compiled WHERE and join operations against a committed database
+fn committed_circuit(compiled: CompiledQuery, database: CommittedDatabase) -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        range_checks: compiled.range_checks,
+        joins: compiled.joins,
+        match_count: compiled.match_count,
+        result_commitment: None,
+        database: Some(database),
+        ..commitment_circuit(Vec::new())
+    }
+}
+
+#[test]
+fn test_result_commitment_binds_result_rows() {
+    // Test: The commitment to a query's result rows is the public query result,
+    // and altering one result cell changes it
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), vec![1, 1, 2, 2, 3]);
+    columns.insert("amount".to_string(), vec![10, 20, 5, 5, 100]);
+    let mut data = HashMap::new();
+    data.insert("order".to_string(), columns);
+
+    let result =
+        SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+            .unwrap()
+            .execute(&data)
+            .unwrap();
+    assert_eq!(result.rows, vec![vec![1, 30], vec![2, 10], vec![3, 100]]);
+
+    let commitment = ResultCommitment::new(result.rows.clone());
+    let mut altered_rows = result.rows.clone();
+    altered_rows[1][1] = 11;
+    let altered = ResultCommitment::new(altered_rows.clone());
+    assert_ne!(commitment.value(), altered.value());
+
+    // The circuit computes the same commitment and binds it to the result instance
+    let circuit = commitment_circuit(result.rows);
+    let k = circuit.stats().unwrap().min_k;
+    let public_inputs = vec![vec![], vec![commitment.value()]];
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Altered rows do not prove the original commitment
+    let prover = MockProver::run(k, &commitment_circuit(altered_rows), public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_result_commitment_copies_result_cells() {
+    // Test: A compiled query's result rows are copies of the cells producing
+    // them (group keys, aggregation results, committed and computed column
+    // cells, WHERE check bits): other rows fail even with their commitment
+    // supplied as the query result
+    let data = order_customer_tables(vec![10, 20, 5, 5, 100], vec![0, 0, 0]);
+    let database = CommittedDatabase::new(&data);
+    let prove = |compiled: CompiledQuery, result: ResultCommitment| {
+        let public_inputs = vec![database.values(), vec![result.value()]];
+        let circuit =
+            PoneglyphCircuit::from_compiled(compiled, Some(result), Some(database.clone()));
+        let k = circuit.stats().unwrap().min_k;
+        MockProver::run(k, &circuit, public_inputs)
+            .unwrap()
+            .verify()
+    };
+
+    // GROUP BY: one row per group, in key order
+    let query = SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+        .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    let result = compiled.result.clone().unwrap();
+    assert_eq!(result.rows, query.execute(&data).unwrap().rows);
+    assert_eq!(result.rows, vec![vec![1, 30], vec![2, 10], vec![3, 100]]);
+    assert_eq!(
+        result.sources.as_ref().unwrap()[1],
+        vec![
+            ResultSource::GroupKey {
+                aggregation: 0,
+                group: 1
+            },
+            ResultSource::Aggregation {
+                aggregation: 0,
+                group: 1
+            },
+        ]
+    );
+    assert_eq!(prove(compiled.clone(), result.clone()), Ok(()));
+
+    let mut forged = result.clone();
+    forged.rows[1][1] = 11;
+    assert!(prove(compiled.clone(), forged).is_err());
+    let mut forged = result;
+    forged.rows[0][0] = 4;
+    assert!(prove(compiled, forged).is_err());
+
+    // WHERE: the matching FROM rows' committed and computed cells
+    let query =
+        SQLParser::parse("SELECT customer_id, amount * 2 AS doubled FROM order WHERE amount > 8")
+            .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    let result = compiled.result.clone().unwrap();
+    assert_eq!(result.rows, query.execute(&data).unwrap().rows);
+    assert_eq!(result.rows, vec![vec![1, 20], vec![1, 40], vec![3, 200]]);
+    assert_eq!(prove(compiled.clone(), result.clone()), Ok(()));
+
+    let mut forged = result.clone();
+    forged.rows[2][1] = 201;
+    assert!(prove(compiled.clone(), forged).is_err());
+
+    // A matching row left out of the result: its check bit is 1
+    let mut forged = result;
+    forged.rows.pop();
+    forged.sources.as_mut().unwrap().pop();
+    forged.filter.as_mut().unwrap()[4].1 = 0;
+    assert!(prove(compiled, forged).is_err());
+
+    // Queries whose rows are not tied to proven cells have no compiled result
+    let query = SQLParser::parse("SELECT customer_id FROM order ORDER BY amount").unwrap();
+    assert!(SQLCompiler::compile(&query, &data)
+        .unwrap()
+        .result
+        .is_none());
+}
+
+#[test]
+fn test_result_commitment_shape() {
+    // Test: Dimensions are committed (same cells in another shape differ),
+    // ragged rows cannot be proven
+    let wide = ResultCommitment::new(vec![vec![1, 2, 3, 4]]);
+    let tall = ResultCommitment::new(vec![vec![1, 2], vec![3, 4]]);
+    assert_ne!(wide.value(), tall.value());
+    assert_ne!(
+        ResultCommitment::new(Vec::new()).value(),
+        ResultCommitment::new(vec![Vec::new()]).value()
+    );
+
+    let ragged = commitment_circuit(vec![vec![1, 2], vec![3]]);
+    assert!(ragged.stats().is_err());
+}
+
+#[test]
+fn test_committed_database_binds_where_values() {
+    // Test: WHERE range checks read the committed cells; values compiled from
+    // data other than the committed data are rejected
+    let committed = account_table(vec![100, 50, 1000]);
+    let database = CommittedDatabase::new(&committed);
+    let public_inputs = vec![database.values(), vec![]];
+    let query = SQLParser::parse("SELECT spent FROM account WHERE spent < budget").unwrap();
+
+    let compiled = SQLCompiler::compile(&query, &committed).unwrap();
+    let spent = CommittedDatabase::column_index(&committed, "account", "spent").unwrap();
+    let budget = CommittedDatabase::column_index(&committed, "account", "budget").unwrap();
+    assert_eq!(database.columns[spent], vec![100, 50, 1000]);
+    assert!(compiled
+        .range_checks
+        .iter()
+        .enumerate()
+        .all(|(row, op)| op.source == Some((spent, row))
+            && op.threshold_source == Some((budget, row))));
+
+    let circuit = committed_circuit(compiled, database.clone());
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Another database's commitment
+    let other = CommittedDatabase::new(&account_table(vec![100, 50, 999]));
+    let prover = MockProver::run(k, &circuit, vec![other.values(), vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Query proven over different data (still under budget) than the committed data
+    let tampered = SQLCompiler::compile(&query, &account_table(vec![100, 50, 999])).unwrap();
+    let circuit = committed_circuit(tampered, database);
+    let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_committed_database_binds_match_count() {
+    // Test: An empty WHERE result counts the committed column, so a count
+    // over other values is rejected
+    let committed = account_table(vec![100, 50, 1000]);
+    let database = CommittedDatabase::new(&committed);
+    let public_inputs = PublicInputs::new(database.values()[0], Fr::from(0)).to_instances();
+    let query = SQLParser::parse("SELECT spent FROM account WHERE spent > 1000").unwrap();
+
+    let compiled = SQLCompiler::compile(&query, &committed).unwrap();
+    assert_eq!(compiled.match_count.as_ref().unwrap().count(), 0);
+    let circuit = committed_circuit(compiled, database.clone());
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let tampered = SQLCompiler::compile(&query, &account_table(vec![100, 50, 7])).unwrap();
+    let circuit = committed_circuit(tampered, database);
+    let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_public_inputs_layout() {
+    // Test: The single-column and two-column layouts read as the same public
+    // inputs, and only the two-column layout verifies
+    let committed = account_table(vec![100, 50, 1000]);
+    let database = CommittedDatabase::new(&committed);
+    let expected = PublicInputs::new(database.values()[0], Fr::from(0));
+
+    let single_column = vec![vec![database.values()[0], Fr::from(0)]];
+    let two_columns = vec![vec![database.values()[0]], vec![Fr::from(0)]];
+    assert_eq!(
+        PublicInputs::from_instances(&single_column).unwrap(),
+        expected
+    );
+    assert_eq!(
+        PublicInputs::from_instances(&two_columns).unwrap(),
+        expected
+    );
+    assert_eq!(expected.to_instances(), two_columns);
+    assert!(matches!(
+        PublicInputs::from_instances(&[vec![], vec![], vec![]]),
+        Err(Error::InvalidInstances)
+    ));
+
+    let query = SQLParser::parse("SELECT spent FROM account WHERE spent > 1000").unwrap();
+    let compiled = SQLCompiler::compile(&query, &committed).unwrap();
+    let circuit = committed_circuit(compiled, database);
+    let k = circuit.stats().unwrap().min_k;
+
+    let prover = MockProver::run(k, &circuit, two_columns.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    assert!(MockProver::run(k, &circuit, single_column.clone()).is_err());
+
+    // Commitment and result in each other's column
+    let swapped = vec![two_columns[1].clone(), two_columns[0].clone()];
+    let prover = MockProver::run(k, &circuit, swapped).unwrap();
+    assert!(prover.verify().is_err());
+
+    let context = ProvingContext::for_circuit(&circuit).unwrap();
+    let proof = context.prove(&circuit, &two_columns).unwrap();
+    assert!(context.verify(&proof, &two_columns).unwrap());
+    assert!(context.verify(&proof, &single_column).is_err());
+}
+
+#[test]
+fn test_committed_tables_bind_join_inputs() {
+    // Test: Each table is bound to its own commitment, and the join reads
+    // the committed cells, so altering either table's data is rejected
+    let committed = order_customer_tables(vec![10, 20, 5, 5, 100], vec![30, 40, 50]);
+    let database = CommittedDatabase::new(&committed);
+    assert_eq!(database.tables, vec![2, 2]);
+    let commitments = database.values();
+    assert_eq!(commitments.len(), 2);
+    assert_ne!(commitments[0], commitments[1]);
+    let public_inputs = vec![commitments.clone(), vec![]];
+    let query = SQLParser::parse(
+        "SELECT amount FROM order, customer WHERE order.customer_id = customer.id",
+    )
+    .unwrap();
+
+    let compiled = SQLCompiler::compile(&query, &committed).unwrap();
+    let index = |table, column| CommittedDatabase::column_index(&committed, table, column);
+    assert_eq!(
+        compiled.joins[0].columns,
+        Some([
+            index("order", "customer_id").unwrap(),
+            index("order", "amount").unwrap(),
+            index("customer", "id").unwrap(),
+            index("customer", "balance").unwrap(),
+        ])
+    );
+    let circuit = committed_circuit(compiled, database.clone());
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Commitments in the wrong rows, or one of them missing
+    let swapped = vec![vec![commitments[1], commitments[0]], vec![]];
+    let prover = MockProver::run(k, &circuit, swapped).unwrap();
+    assert!(prover.verify().is_err());
+    let prover = MockProver::run(k, &circuit, vec![vec![commitments[0]], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Join proven over altered order or customer data
+    for tampered in [
+        order_customer_tables(vec![10, 20, 5, 5, 99], vec![30, 40, 50]),
+        order_customer_tables(vec![10, 20, 5, 5, 100], vec![30, 41, 50]),
+    ] {
+        let compiled = SQLCompiler::compile(&query, &tampered).unwrap();
+        let circuit = committed_circuit(compiled, database.clone());
+        let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use poneglyphdb::prover::{prove_query, prove_query_with_result, Profiler, Stage};
+use poneglyphdb::sql::{ColumnType, SQLParser, TypedValue};
+
+/// Order table (customer_id, amount) with 64 rows
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), (0..64).map(|i| i % 5).collect());
+    columns.insert("amount".to_string(), (0..64).map(|i| i * 7 % 100).collect());
+
+    let mut table_data = HashMap::new();
+    table_data.insert("order".to_string(), columns);
+    table_data
+}
+
+#[test]
+fn test_prove_query_records_every_stage() {
+    // Test: Proving a WHERE + GROUP BY query reports a nonzero duration for
+    // parse, compile, keygen, witness synthesis and proof creation, and the
+    // proof size
+    let sql = "SELECT customer_id, sum(amount) FROM order WHERE amount < 90 GROUP BY customer_id";
+    let mut profiler = Profiler::new();
+    let (proof, _) = prove_query(sql, &order_table(), Some(&mut profiler)).unwrap();
+    assert!(!proof.is_empty());
+    assert_eq!(profiler.proof_size(), Some(proof.len()));
+
+    let timings = profiler.timings();
+    for stage in [
+        Stage::Parse,
+        Stage::Compile,
+        Stage::Keygen,
+        Stage::WitnessSynthesis,
+        Stage::ProofCreation,
+    ] {
+        assert!(timings.get(stage) > Duration::ZERO, "{:?}", stage);
+    }
+    assert_eq!(
+        timings.total(),
+        timings.parse
+            + timings.compile
+            + timings.keygen
+            + timings.witness_synthesis
+            + timings.proof_creation
+    );
+
+    // Profiling is optional; a parse error is returned
+    assert!(prove_query("SELECT FROM order", &order_table(), None).is_err());
+}
+
+#[test]
+fn test_prove_query_with_result_returns_typed_rows() {
+    // Test: The typed result is the reference execution of the proven query
+    let sql = "SELECT customer_id, sum(amount) FROM order WHERE amount < 90 GROUP BY customer_id";
+    let schema = HashMap::from([("sum(amount)".to_string(), ColumnType::Decimal { scale: 1 })]);
+    let (proof, _, result) = prove_query_with_result(sql, &order_table(), &schema, None).unwrap();
+    assert!(!proof.is_empty());
+
+    let expected = SQLParser::parse(sql)
+        .unwrap()
+        .execute(&order_table())
+        .unwrap()
+        .typed(&schema);
+    assert_eq!(result, expected);
+    assert_eq!(result.rows.len(), 5);
+    assert!(matches!(
+        result.rows[0][1],
+        TypedValue::Decimal { scale: 1, .. }
+    ));
+}
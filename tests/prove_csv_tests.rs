@@ -0,0 +1,74 @@
+#![cfg(not(feature = "verifier-only"))]
+
+// CSV-to-proof pipeline tests
+// Runs the `prove_csv` example on `tests/fixtures/orders.csv` and verifies
+// the proof file it writes
+use std::path::{Path, PathBuf};
+
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::QueryError;
+
+#[allow(dead_code)]
+#[path = "../examples/prove_csv.rs"]
+mod prove_csv;
+
+use prove_csv::{compile_csv, prove_csv, read_proof_file};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/orders.csv");
+
+/// Scratch file path in the temp directory, unique to this process
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("poneglyph_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn test_csv_loads_fixture() {
+    // Test: The file stem names the table, the header names the columns
+    let table = DatabaseTable::from_csv(FIXTURE).unwrap();
+    assert_eq!(table.name, "orders");
+    assert_eq!(table.columns, vec!["customer_id", "amount"]);
+    assert_eq!(table.data.len(), 24);
+    assert_eq!(table.data[1], vec![1, 37]);
+
+    // A negative value is rejected with its row and column
+    let path = temp_path("invalid.csv");
+    std::fs::write(&path, "id,amount\n1,5\n2,-3\n").unwrap();
+    let error = DatabaseTable::from_csv(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        error,
+        QueryError::InvalidValue { ref column, row: 1, .. } if column == "amount"
+    ));
+}
+
+#[test]
+fn test_prove_csv_writes_verifying_proof() {
+    // Test: The example proves a GROUP BY over the fixture at the estimated
+    // k, binding the table commitment, and the written proof verifies
+    // against keys rebuilt from the same CSV and query; a corrupted proof
+    // does not
+    let sql = "SELECT customer_id, SUM(amount) FROM orders GROUP BY customer_id";
+    let out = temp_path("orders_proof.json");
+    let written = prove_csv(Path::new(FIXTURE), sql, &out).unwrap();
+
+    let file = read_proof_file(&out).unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert_eq!(file.k, written.k);
+    assert_eq!(file.proof, written.proof);
+
+    let (circuit, public_inputs) = compile_csv(Path::new(FIXTURE), sql).unwrap();
+    assert_eq!(file.k, circuit.stats().unwrap().estimate_k(0));
+    assert_eq!(file.instances().unwrap(), public_inputs.to_instances());
+    assert_eq!(file.db_commitments.len(), 1);
+    assert_eq!(file.db_commitments, file.public_inputs[0]);
+
+    let context = ProvingContext::new(file.k, &circuit).unwrap();
+    let proof = file.proof_bytes().unwrap();
+    let instances = file.instances().unwrap();
+    assert!(context.verify(&proof, &instances).unwrap());
+
+    let mut corrupted = proof;
+    corrupted[0] ^= 1;
+    assert!(!context.verify(&corrupted, &instances).unwrap_or(false));
+}
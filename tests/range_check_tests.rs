@@ -1,6 +1,7 @@
 use halo2_proofs::{
     circuit::Value,
-    dev::MockProver,
+    dev::{CircuitCost, MockProver},
+    pasta::Eq,
     plonk::{Circuit, ConstraintSystem, Error},
 };
 use pasta_curves::pallas::Base as Fr;
@@ -86,7 +87,7 @@ fn test_range_check_decomposition() {
     };
     
     // Empty public inputs for instance column (not using for now)
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -102,7 +103,7 @@ fn test_range_check_less_than_true() {
     };
     
     // Empty public inputs for instance column (not using for now)
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -118,7 +119,7 @@ fn test_range_check_less_than_false() {
     };
     
     // Empty public inputs for instance column (not using for now)
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -134,7 +135,7 @@ fn test_range_check_small_value() {
     };
     
     // Empty public inputs for instance column (not using for now)
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -150,8 +151,665 @@ fn test_range_check_large_value() {
     };
     
     // Empty public inputs for instance column (not using for now)
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
 
+
+/// Range Check test circuit with `BITS`-wide chunks (`lookup_bits`)
+#[derive(Clone)]
+struct LookupBitsTestCircuit<const BITS: u8> {
+    values: Vec<u64>,
+    reload_table: bool,
+}
+
+impl<const BITS: u8> Circuit<Fr> for LookupBitsTestCircuit<BITS> {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![],
+            reload_table: self.reload_table,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, BITS)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        if self.reload_table {
+            config.load_lookup_table(&mut layouter)?;
+        }
+        
+        let range_check_chip = RangeCheckChip::new(gates.range_check);
+        for (i, &value) in self.values.iter().enumerate() {
+            let chunks = range_check_chip.decompose_64bit(
+                layouter.namespace(|| format!("decompose value_{}", i)),
+                Value::known(value),
+            )?;
+            assert_eq!(chunks.len(), 64 / BITS as usize);
+        }
+        range_check_chip
+            .decompose_64bit_batch(layouter.namespace(|| "decompose batch"), &self.values)?;
+
+        Ok(())
+    }
+}
+
+fn lookup_bits_values() -> Vec<u64> {
+    vec![0, 0xFFFF, 0x1_0000, 0x1234567890ABCDEF, u64::MAX]
+}
+
+#[test]
+fn test_range_check_16bit_decomposition() {
+    // Test: 16-bit chunks decompose full 64-bit values (65536-row table, k = 17)
+    let circuit = LookupBitsTestCircuit::<16> {
+        values: lookup_bits_values(),
+        reload_table: false,
+    };
+    let prover = MockProver::run(17, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_range_check_same_value_both_widths() {
+    // Test: The same values prove with 8-bit chunks (k = 10) and 16-bit chunks (k = 17)
+    let circuit_8 = LookupBitsTestCircuit::<8> {
+        values: lookup_bits_values(),
+        reload_table: false,
+    };
+    let prover = MockProver::run(10, &circuit_8, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let circuit_16 = LookupBitsTestCircuit::<16> {
+        values: lookup_bits_values(),
+        reload_table: false,
+    };
+    let prover = MockProver::run(17, &circuit_16, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_lookup_table_loaded_once() {
+    // Test: Loading the lookup table a second time is rejected by the layouter
+    let circuit = LookupBitsTestCircuit::<8> {
+        values: lookup_bits_values(),
+        reload_table: true,
+    };
+    assert!(matches!(
+        MockProver::run(10, &circuit, vec![vec![], vec![]]),
+        Err(Error::TableError(_))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "lookup_bits must be 8 or 16, got 12")]
+fn test_unsupported_lookup_bits_rejected() {
+    // Test: A chunk width other than 8 or 16 is rejected at configure time
+    let mut meta = ConstraintSystem::<Fr>::default();
+    PoneglyphConfig::configure_with_lookup_bits(&mut meta, 12);
+}
+
+#[test]
+fn test_lookup_bits_16_halves_chunk_lookups() {
+    // Test: 16-bit chunks use 4 lookups per decomposition instead of 8,
+    // which shrinks the proof at the same k
+    let values = lookup_bits_values();
+    let circuit_8 = LookupBitsTestCircuit::<8> {
+        values: values.clone(),
+        reload_table: false,
+    };
+    let circuit_16 = LookupBitsTestCircuit::<16> {
+        values,
+        reload_table: false,
+    };
+    let cost_8 = CircuitCost::<Eq, _>::measure(17, &circuit_8);
+    let cost_16 = CircuitCost::<Eq, _>::measure(17, &circuit_16);
+    
+    let size_8: usize = cost_8.proof_size(1).into();
+    let size_16: usize = cost_16.proof_size(1).into();
+    assert!(
+        size_16 < size_8,
+        "16-bit proof {} >= 8-bit proof {}",
+        size_16,
+        size_8
+    );
+}
+
+/// x < t check through the chip, with a caller-chosen u
+#[derive(Clone)]
+struct LessThanTestCircuit {
+    x: u64,
+    threshold: u64,
+    u: u64,
+    witnessed_threshold: bool,
+}
+
+impl Circuit<Fr> for LessThanTestCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, 8)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        
+        let range_check_chip = RangeCheckChip::new(gates.range_check);
+        let x = Value::known(self.x);
+        if self.witnessed_threshold {
+            range_check_chip.check_less_than_advice(
+                layouter.namespace(|| "check less than"),
+                x,
+                Value::known(self.threshold),
+                self.u,
+            )?;
+        } else {
+            range_check_chip.check_less_than(
+                layouter.namespace(|| "check less than"),
+                x,
+                self.threshold,
+                self.u,
+            )?;
+        }
+        
+        Ok(())
+    }
+}
+
+/// x < t check region laid out by hand with a chosen check bit, as a
+/// dishonest prover would (diff and slack chunks from wrapping u64 arithmetic)
+#[derive(Clone)]
+struct ForgedLessThanCircuit {
+    x: u64,
+    threshold: u64,
+    u: u64,
+    check: u64,
+}
+
+impl Circuit<Fr> for ForgedLessThanCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, 8)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        
+        let rc = gates.range_check;
+        let diff = (self.check * self.u)
+            .wrapping_add(self.x)
+            .wrapping_sub(self.threshold);
+        let slack = (self.check * self.u).wrapping_sub(1).wrapping_sub(diff) * self.check;
+        let diff_field =
+            Fr::from(self.check) * Fr::from(self.u) + Fr::from(self.x) - Fr::from(self.threshold);
+        let slack_field = Fr::from(self.check) * (Fr::from(self.u) - Fr::from(1) - diff_field);
+        
+        layouter.assign_region(
+            || "forged x < t",
+            |mut region| {
+                rc.less_than_selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "x", rc.x_column, 0, || Value::known(Fr::from(self.x)))?;
+                region.assign_fixed(
+                    || "threshold",
+                    rc.threshold_column,
+                    0,
+                    || Value::known(Fr::from(self.threshold)),
+                )?;
+                region.assign_fixed(|| "u", rc.u_column, 0, || Value::known(Fr::from(self.u)))?;
+                region.assign_advice(
+                    || "check",
+                    rc.check_column,
+                    0,
+                    || Value::known(Fr::from(self.check)),
+                )?;
+                region.assign_advice(|| "diff", rc.diff_column, 1, || Value::known(diff_field))?;
+                
+                for (row, value, chunks_of) in [(2, diff_field, diff), (3, slack_field, slack)] {
+                    rc.decomposition_selector.enable(&mut region, row)?;
+                    rc.selector.enable(&mut region, row)?;
+                    region.assign_advice(|| "value", rc.x_column, row, || Value::known(value))?;
+                    for (i, chunk_col) in rc.active_chunk_columns().iter().enumerate() {
+                        region.assign_advice(
+                            || format!("chunk_{}", i),
+                            *chunk_col,
+                            row,
+                            || Value::known(Fr::from(rc.chunk(chunks_of, i))),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+fn forged_verifies(x: u64, threshold: u64, u: u64, check: u64) -> bool {
+    let circuit = ForgedLessThanCircuit {
+        x,
+        threshold,
+        u,
+        check,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    prover.verify().is_ok()
+}
+
+fn less_than_verifies(x: u64, threshold: u64, u: u64, witnessed_threshold: bool) -> bool {
+    let circuit = LessThanTestCircuit {
+        x,
+        threshold,
+        u,
+        witnessed_threshold,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_less_than_full_u64_range() {
+    // Test: Gaps and bounds far above 2^lookup_bits verify, up to u64::MAX
+    for witnessed in [false, true] {
+        assert!(less_than_verifies(0, u64::MAX, u64::MAX, witnessed));
+        assert!(less_than_verifies(u64::MAX - 1, u64::MAX, 1, witnessed));
+        assert!(less_than_verifies(u64::MAX, 0, 0, witnessed));
+        assert!(less_than_verifies(10, 1_000_000, 1_000_000, witnessed));
+    }
+}
+
+#[test]
+fn test_less_than_rejects_diff_below_zero() {
+    // Test: u < t - x makes diff = u - (t - x) negative; with u >= 2^lookup_bits
+    // this used to skip the diff lookup entirely
+    for witnessed in [false, true] {
+        assert!(!less_than_verifies(10, 1000, 300, witnessed));
+        assert!(!less_than_verifies(0, u64::MAX, u64::MAX - 1, witnessed));
+        assert!(!less_than_verifies(10, 1000, 5, witnessed));
+    }
+}
+
+#[test]
+fn test_forged_check_rejected() {
+    // Test: A check bit contradicting x < t fails whatever u is
+    let forged = [
+        // x >= t claimed as x < t: diff = u + (x - t) >= u
+        (1000, 10, 2000, 1),
+        (10, 10, 1, 1),
+        (u64::MAX, 0, u64::MAX, 1),
+        // x < t claimed as x >= t: diff = x - t wraps around the field
+        (10, 1000, 0, 0),
+        (0, u64::MAX, 0, 0),
+    ];
+    for (x, threshold, u, check) in forged {
+        assert!(
+            !forged_verifies(x, threshold, u, check),
+            "forged check verified: x = {}, t = {}",
+            x,
+            threshold
+        );
+    }
+    
+    // The same layout with the true check bit verifies
+    assert!(forged_verifies(1000, 10, 2000, 0));
+}
+
+/// lower <= x < upper check per value; `forged_bits` replaces the chip's
+/// (in range, below) bits and differences with a hand-made layout
+#[derive(Clone)]
+struct BetweenTestCircuit {
+    values: Vec<u64>,
+    lower: u64,
+    upper: u64,
+    forged_bits: Option<(u64, u64)>,
+}
+
+impl Circuit<Fr> for BetweenTestCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, 8)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        
+        let rc = gates.range_check.clone();
+        let range_check_chip = RangeCheckChip::new(gates.range_check);
+        for (i, &x) in self.values.iter().enumerate() {
+            let Some((in_range, below)) = self.forged_bits else {
+                range_check_chip.check_between(
+                    layouter.namespace(|| format!("between {}", i)),
+                    None,
+                    Value::known(x),
+                    self.lower,
+                    self.upper,
+                )?;
+                continue;
+            };
+            
+            // Differences the gate expects for the forged bits (wrapping u64)
+            let (x_f, lower, upper) = (Fr::from(x), Fr::from(self.lower), Fr::from(self.upper));
+            let above = Fr::from(1) - Fr::from(in_range) - Fr::from(below);
+            let first_field = Fr::from(in_range) * (x_f - lower)
+                + Fr::from(below) * (lower - Fr::from(1) - x_f)
+                + above * (x_f - upper);
+            let second_field = Fr::from(in_range) * (upper - Fr::from(1) - x_f);
+            let first = match (in_range, below) {
+                (1, _) => x.wrapping_sub(self.lower),
+                (_, 1) => self.lower.wrapping_sub(1).wrapping_sub(x),
+                _ => x.wrapping_sub(self.upper),
+            };
+            let second = in_range * self.upper.wrapping_sub(1).wrapping_sub(x);
+            
+            layouter.assign_region(
+                || format!("forged between {}", i),
+                |mut region| {
+                    rc.between_selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "x", rc.x_column, 0, || Value::known(x_f))?;
+                    region.assign_fixed(
+                        || "lower",
+                        rc.threshold_column,
+                        0,
+                        || Value::known(lower),
+                    )?;
+                    region.assign_fixed(|| "upper", rc.u_column, 0, || Value::known(upper))?;
+                    region.assign_advice(
+                        || "in range",
+                        rc.check_column,
+                        0,
+                        || Value::known(Fr::from(in_range)),
+                    )?;
+                    region.assign_advice(
+                        || "below",
+                        rc.threshold_advice_column,
+                        0,
+                        || Value::known(Fr::from(below)),
+                    )?;
+                    for (row, value, chunks_of) in
+                        [(1, first_field, first), (2, second_field, second)]
+                    {
+                        rc.decomposition_selector.enable(&mut region, row)?;
+                        rc.selector.enable(&mut region, row)?;
+                        region.assign_advice(
+                            || "value",
+                            rc.x_column,
+                            row,
+                            || Value::known(value),
+                        )?;
+                        for (i, chunk_col) in rc.active_chunk_columns().iter().enumerate() {
+                            region.assign_advice(
+                                || format!("chunk_{}", i),
+                                *chunk_col,
+                                row,
+                                || Value::known(Fr::from(rc.chunk(chunks_of, i))),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        
+        Ok(())
+    }
+}
+
+fn between_verifies(
+    values: Vec<u64>,
+    lower: u64,
+    upper: u64,
+    forged_bits: Option<(u64, u64)>,
+) -> bool {
+    let circuit = BetweenTestCircuit {
+        values,
+        lower,
+        upper,
+        forged_bits,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_between_every_outcome() {
+    // Test: Values below, inside and above the range (including the bounds
+    // themselves and the ends of the u64 range) prove with a shared check
+    let values = vec![0, 99, 100, 150, 999, 1000, u64::MAX];
+    assert!(between_verifies(values.clone(), 100, 1000, None));
+    assert!(between_verifies(values.clone(), 0, u64::MAX, None));
+    // Empty range: lower >= upper
+    assert!(between_verifies(values, 500, 500, None));
+}
+
+#[test]
+fn test_between_forged_outcome_rejected() {
+    // Test: Claiming the wrong outcome fails; the true one verifies
+    // (bits are (in range, below); above is neither)
+    let (lower, upper) = (100, 1000);
+    for (x, truth, lies) in [
+        (50, (0, 1), [(1, 0), (0, 0)]),
+        (500, (1, 0), [(0, 1), (0, 0)]),
+        (1000, (0, 0), [(1, 0), (0, 1)]),
+        (u64::MAX, (0, 0), [(1, 0), (0, 1)]),
+    ] {
+        assert!(
+            between_verifies(vec![x], lower, upper, Some(truth)),
+            "x = {}",
+            x
+        );
+        for lie in lies {
+            assert!(
+                !between_verifies(vec![x], lower, upper, Some(lie)),
+                "x = {} claimed {:?}",
+                x,
+                lie
+            );
+        }
+    }
+    // Both bits set
+    assert!(!between_verifies(vec![500], lower, upper, Some((1, 1))));
+}
+
+/// One `prove_*` comparison per value against a fixed threshold; the
+/// returned bits are asserted to match `Comparison::holds`
+#[derive(Clone)]
+struct ComparisonTestCircuit {
+    comparison: Comparison,
+    values: Vec<u64>,
+    threshold: u64,
+}
+
+impl Circuit<Fr> for ComparisonTestCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, 8)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        
+        let chip = RangeCheckChip::new(gates.range_check);
+        for (i, &x) in self.values.iter().enumerate() {
+            let layouter = layouter.namespace(|| format!("comparison {}", i));
+            let (x_value, t) = (Value::known(x), self.threshold);
+            let bit = match self.comparison {
+                Comparison::Lt => chip.prove_lt(layouter, None, x_value, t)?,
+                Comparison::Le => chip.prove_le(layouter, None, x_value, t)?,
+                Comparison::Gt => chip.prove_gt(layouter, None, x_value, t)?,
+                Comparison::Ge => chip.prove_ge(layouter, None, x_value, t)?,
+                Comparison::Eq => chip.prove_eq(layouter, None, x_value, t)?,
+                Comparison::Bool => chip.prove_bool(layouter, None, x_value, t != 0)?,
+            };
+            let expected = Fr::from(self.comparison.holds(x, t) as u64);
+            bit.value().assert_if_known(|&&bit| bit == expected);
+        }
+        
+        Ok(())
+    }
+}
+
+fn comparison_verifies(comparison: Comparison, values: Vec<u64>, threshold: u64) -> bool {
+    let circuit = ComparisonTestCircuit {
+        comparison,
+        values,
+        threshold,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_boolean_comparison() {
+    // Test: A boolean x = t check proves both outcomes for x and t in {0, 1},
+    // and a non-boolean x fails instead of giving 0
+    for t in [0, 1] {
+        assert!(comparison_verifies(Comparison::Bool, vec![0, 1, 1, 0], t));
+        assert!(!comparison_verifies(Comparison::Bool, vec![0, 2], t));
+    }
+    assert!(Comparison::Bool.holds(1, 1) && !Comparison::Bool.holds(0, 1));
+}
+
+#[test]
+fn test_comparison_boundaries() {
+    // Test: Each comparison at x == t (strict ones false, non-strict true),
+    // one either side, and at both ends of the u64 range, where t + 1 and
+    // t - 1 would overflow
+    let comparisons = [
+        Comparison::Lt,
+        Comparison::Le,
+        Comparison::Gt,
+        Comparison::Ge,
+        Comparison::Eq,
+    ];
+    for comparison in comparisons {
+        for t in [0, 1, 100, u64::MAX - 1, u64::MAX] {
+            let values = vec![t.saturating_sub(1), t, t.saturating_add(1), 0, u64::MAX];
+            assert!(
+                comparison_verifies(comparison, values, t),
+                "{:?} with t = {}",
+                comparison,
+                t
+            );
+        }
+    }
+    
+    assert!(!Comparison::Lt.holds(7, 7) && Comparison::Le.holds(7, 7));
+    assert!(!Comparison::Gt.holds(7, 7) && Comparison::Ge.holds(7, 7));
+    assert!(Comparison::Eq.holds(7, 7) && !Comparison::Eq.holds(7, 8));
+}
+
+/// One witnessed-threshold comparison per (x, t) pair (`x < t`, `x <= t` or
+/// `x = t`, as for a column-to-column WHERE); the returned bits are asserted
+/// to match `Comparison::holds`
+#[derive(Clone)]
+struct AdviceComparisonTestCircuit {
+    comparison: Comparison,
+    pairs: Vec<(u64, u64)>,
+}
+
+impl Circuit<Fr> for AdviceComparisonTestCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_lookup_bits(meta, 8)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+
+        let chip = RangeCheckChip::new(gates.range_check);
+        for (i, &(x, t)) in self.pairs.iter().enumerate() {
+            let layouter = layouter.namespace(|| format!("comparison {}", i));
+            let (x_value, t_value, u) = (Value::known(x), Value::known(t), t.saturating_sub(x));
+            let bit = match self.comparison {
+                Comparison::Lt => {
+                    chip.check_less_than_advice_copied(layouter, None, x_value, None, t_value, u)?
+                }
+                Comparison::Le => {
+                    chip.check_less_equal_advice_copied(layouter, None, x_value, None, t_value, u)?
+                }
+                Comparison::Eq => {
+                    chip.check_equal_advice_copied(layouter, None, x_value, None, t_value)?
+                }
+                _ => return Err(Error::Synthesis),
+            };
+            let expected = Fr::from(self.comparison.holds(x, t) as u64);
+            bit.value().assert_if_known(|&&bit| bit == expected);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_advice_comparison_boundaries() {
+    // Test: Witnessed-threshold <, <= and = at x == t, one either side and
+    // at both ends of the u64 range; `x = t` is exact (not `x < t + 1`, which
+    // also held for x < t and had no threshold at t = u64::MAX)
+    for comparison in [Comparison::Lt, Comparison::Le, Comparison::Eq] {
+        for t in [0, 1, 100, u64::MAX - 1, u64::MAX] {
+            let pairs = [t.saturating_sub(1), t, t.saturating_add(1), 0, u64::MAX]
+                .into_iter()
+                .map(|x| (x, t))
+                .collect();
+            let circuit = AdviceComparisonTestCircuit { comparison, pairs };
+            let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{:?} with t = {}", comparison, t);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use ff::Field;
+use halo2_proofs::{dev::MockProver, pasta::EqAffine, poly::commitment::Params};
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::prover::{assert_aggregate, Prover, Verifier};
+use poneglyphdb::recursive::{BatchProver, DeltaProver, RecursionConfig, RecursionError};
+
+/// SUM circuit over 4 values in 2 groups
+fn sum_circuit(values: Vec<u64>) -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        aggregations: vec![AggregationOp {
+            group_keys: vec![0, 0, 1, 1],
+            values,
+            function: AggregationFunction::Sum,
+            value_checks: None,
+            value_semi_join: None,
+            unfiltered: None,
+            column: None,
+            expected: None,
+            payload: None,
+            group_sort: None,
+            sort_payload: None,
+        }],
+        ..Default::default()
+    }
+}
+
+fn config(max_depth: usize, max_batch: usize, auto_chunk: bool) -> RecursionConfig {
+    RecursionConfig {
+        max_depth,
+        max_batch,
+        auto_chunk,
+    }
+}
+
+#[test]
+fn test_recursion_config_depth() {
+    // Test: Depth is the number of levels to fold the batch, limits are structured errors
+    assert_eq!(config(4, 2, false).depth(2).unwrap(), 1);
+    assert_eq!(config(4, 2, true).depth(3).unwrap(), 2);
+    assert_eq!(config(4, 2, true).depth(5).unwrap(), 3);
+
+    assert!(matches!(
+        config(4, 2, false).depth(3),
+        Err(RecursionError::BatchTooLarge {
+            circuits: 3,
+            max_batch: 2
+        })
+    ));
+    assert!(matches!(
+        config(2, 2, true).depth(5),
+        Err(RecursionError::DepthExceeded {
+            depth: 3,
+            max_depth: 2
+        })
+    ));
+}
+
+#[test]
+fn test_batch_exceeding_max_batch_errors() {
+    // Test: A batch above max_batch is rejected before any proof is created
+    let circuits: Vec<PoneglyphCircuit> = (0..3).map(|i| sum_circuit(vec![i, 1, 2, 3])).collect();
+    let k = circuits[0].stats().unwrap().min_k;
+    let params = Params::<EqAffine>::new(k);
+    let prover = Prover::new(&params, &circuits[0]).unwrap();
+
+    let batch = BatchProver::new(prover).with_config(config(4, 2, false));
+    let result = batch.prove_batch(&params, &circuits, &[]);
+    assert!(matches!(
+        result,
+        Err(RecursionError::BatchTooLarge {
+            circuits: 3,
+            max_batch: 2
+        })
+    ));
+}
+
+#[test]
+fn test_auto_chunked_batch_verifies() {
+    // Test: With auto_chunk, 3 circuits in sub-batches of 2 give every
+    // circuit's proof in order, each verifying like a single batch
+    let circuits: Vec<PoneglyphCircuit> = (0..3).map(|i| sum_circuit(vec![i, 1, 2, 3])).collect();
+    let inputs = vec![vec![vec![], vec![]]; circuits.len()];
+    let k = circuits[0].stats().unwrap().min_k;
+    let params = Params::<EqAffine>::new(k);
+
+    let single = BatchProver::new(Prover::new(&params, &circuits[0]).unwrap())
+        .prove_batch(&params, &circuits, &inputs)
+        .unwrap();
+    let chunked = BatchProver::new(Prover::new(&params, &circuits[0]).unwrap())
+        .with_config(config(2, 2, true))
+        .prove_batch(&params, &circuits, &inputs)
+        .unwrap();
+    assert_eq!(chunked.len(), single.len());
+
+    // Proofs of the same circuit shape have the same length
+    let verifier = Verifier::new(&params, &circuits[0]).unwrap();
+    let proof_len = chunked.len() / circuits.len();
+    for proof in chunked.chunks(proof_len) {
+        assert!(verifier.verify(&params, proof, &[vec![], vec![]]).unwrap());
+    }
+}
+
+/// Order table (id, amount) with 5 rows
+fn order_table() -> DatabaseTable {
+    let mut table = DatabaseTable::new(
+        "order".to_string(),
+        vec!["id".to_string(), "amount".to_string()],
+    );
+    for (id, amount) in [(7, 100), (42, 250), (13, 90), (99, 5), (1, 1)] {
+        table.insert(vec![id, amount]).unwrap();
+    }
+    table
+}
+
+#[test]
+fn test_delta_proof_matches_full_reproof() {
+    // Test: After a single-row update, the delta proof's aggregate and root
+    // are those of a full re-proof over the updated table
+    let sql = "SELECT sum(amount) FROM order";
+    let table = order_table();
+    let (_, old_inputs) = assert_aggregate(sql, &table, 446).unwrap();
+
+    let mut delta = DeltaProver::new(table.data.clone(), 1, 446).unwrap();
+    assert_eq!(delta.root(), table.merkle_tree().root());
+    let proof = delta.prove_update(&[(2, vec![13, 120])]).unwrap();
+    assert!(proof.verify().unwrap());
+    assert_eq!(proof.instances[0][0], table.merkle_tree().root());
+    assert_eq!(Some(proof.instances[1][0]), old_inputs.query_result);
+
+    let mut updated = order_table();
+    updated.data[2] = vec![13, 120];
+    assert_eq!(delta.rows(), updated.data.as_slice());
+    assert_eq!(delta.aggregate(), 476);
+    assert_eq!(proof.instances[0][1], updated.merkle_tree().root());
+
+    let (_, new_inputs) = assert_aggregate(sql, &updated, delta.aggregate()).unwrap();
+    assert_eq!(new_inputs.query_result, Some(proof.new_aggregate()));
+}
+
+#[test]
+fn test_delta_circuit_binds_roots_and_aggregates() {
+    // Test: Two chained updates prove; a wrong new aggregate, a wrong new
+    // root or a new row other than the committed one fails
+    let mut delta = DeltaProver::new(order_table().data, 1, 446).unwrap();
+    let circuit = delta.update(&[(0, vec![7, 60]), (4, vec![1, 11])]).unwrap();
+    assert_eq!(circuit.new_aggregate(), Some(416));
+    let instances = circuit.instances().unwrap();
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, instances.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let mut wrong_aggregate = instances.clone();
+    wrong_aggregate[1][1] += Fr::ONE;
+    let prover = MockProver::run(k, &circuit, wrong_aggregate).unwrap();
+    assert!(prover.verify().is_err());
+
+    let mut wrong_root = instances.clone();
+    wrong_root[0][1] = instances[0][0];
+    let prover = MockProver::run(k, &circuit, wrong_root).unwrap();
+    assert!(prover.verify().is_err());
+
+    let mut forged = circuit.clone();
+    forged.updates[1].new_row = vec![1, 21];
+    forged.old_aggregate = 436;
+    let prover = MockProver::run(k, &forged, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_delta_update_errors_leave_prover_unchanged() {
+    // Test: A wrong starting aggregate, an out-of-range row and a row of
+    // another length are rejected without changing the prover
+    assert!(DeltaProver::new(order_table().data, 1, 445).is_err());
+
+    let mut delta = DeltaProver::new(order_table().data, 1, 446).unwrap();
+    let root = delta.root();
+    assert!(delta.update(&[(0, vec![7, 60]), (5, vec![2, 2])]).is_err());
+    assert!(delta.update(&[(1, vec![42])]).is_err());
+    assert!(delta.update(&[]).is_err());
+    assert_eq!(delta.root(), root);
+    assert_eq!(delta.aggregate(), 446);
+    assert_eq!(delta.rows(), order_table().data.as_slice());
+}
@@ -0,0 +1,137 @@
+#![cfg(not(feature = "verifier-only"))]
+
+// Proof size and verification time regression guards
+// Canonical queries are proven at a fixed k with a seeded RNG and compared
+// against `tests/baselines/proof_regression.json`; a change fails when it
+// grows the proof or slows verification beyond the baseline's margins
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+
+const BASELINE: &str = include_str!("baselines/proof_regression.json");
+
+/// Baseline file: margins and the expected cost of each canonical query
+#[derive(Deserialize)]
+struct Baseline {
+    /// Allowed proof size growth, as a fraction of `proof_bytes`
+    /// (overridden by `PONEGLYPH_PROOF_SIZE_MARGIN`)
+    proof_size_margin: f64,
+    /// Allowed verification slowdown, as a fraction of `verify_ms`
+    /// (overridden by `PONEGLYPH_VERIFY_TIME_MARGIN`)
+    verify_time_margin: f64,
+    queries: Vec<QueryBaseline>,
+}
+
+/// One canonical query, proven over `order_table`
+#[derive(Deserialize)]
+struct QueryBaseline {
+    name: String,
+    sql: String,
+    /// Fixed log2 row count the keys are generated for
+    k: u32,
+    /// Expected proof byte length
+    proof_bytes: usize,
+    /// Expected verification time in milliseconds (debug build)
+    verify_ms: u64,
+}
+
+/// Margin from the environment, or the baseline's
+fn margin(var: &str, default: f64) -> f64 {
+    std::env::var(var)
+        .ok()
+        .map(|value| value.parse().expect("margin must be a number"))
+        .unwrap_or(default)
+}
+
+/// Order table (customer_id, amount) with 32 rows
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), (0..32).map(|i| i % 4).collect());
+    columns.insert("amount".to_string(), (0..32).map(|i| i * 13 % 100).collect());
+
+    let mut table_data = HashMap::new();
+    table_data.insert("order".to_string(), columns);
+    table_data
+}
+
+/// Compile a query into a circuit and its public inputs (as `prove_query`)
+fn compile(sql: &str) -> (PoneglyphCircuit, Vec<Vec<Fr>>) {
+    let query = SQLParser::parse(sql).unwrap();
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, None);
+    let public_inputs = PublicInputs {
+        db_commitments: Vec::new(),
+        query_result: circuit.match_count.as_ref().map(|_| Fr::from(0)),
+    };
+    (circuit, public_inputs.to_instances())
+}
+
+#[test]
+fn test_proof_size_and_verify_time_within_baseline() {
+    // Test: Every canonical query proves at its fixed k with a proof no
+    // larger, and a verification no slower, than the baseline allows
+    let baseline: Baseline = serde_json::from_str(BASELINE).unwrap();
+    let size_margin = margin("PONEGLYPH_PROOF_SIZE_MARGIN", baseline.proof_size_margin);
+    let time_margin = margin("PONEGLYPH_VERIFY_TIME_MARGIN", baseline.verify_time_margin);
+
+    let mut regressions = Vec::new();
+    for query in &baseline.queries {
+        let (circuit, instances) = compile(&query.sql);
+        let context = ProvingContext::new(query.k, &circuit).unwrap();
+        let proof = context
+            .prove_with_rng(&circuit, &instances, StdRng::seed_from_u64(0))
+            .unwrap();
+
+        // Fastest of three verifications, to keep scheduling noise out
+        let mut verify_time = Duration::MAX;
+        for _ in 0..3 {
+            let start = Instant::now();
+            assert!(context.verify(&proof, &instances).unwrap());
+            verify_time = verify_time.min(start.elapsed());
+        }
+
+        let max_bytes = query.proof_bytes as f64 * (1.0 + size_margin);
+        if proof.len() as f64 > max_bytes {
+            regressions.push(format!(
+                "{}: proof is {} bytes, baseline {} (max {:.0})",
+                query.name,
+                proof.len(),
+                query.proof_bytes,
+                max_bytes
+            ));
+        }
+        let max_ms = query.verify_ms as f64 * (1.0 + time_margin);
+        if verify_time.as_secs_f64() * 1000.0 > max_ms {
+            regressions.push(format!(
+                "{}: verification took {:?}, baseline {}ms (max {:.0}ms)",
+                query.name, verify_time, query.verify_ms, max_ms
+            ));
+        }
+    }
+
+    assert!(regressions.is_empty(), "{}", regressions.join("\n"));
+}
+
+#[test]
+fn test_seeded_proof_is_deterministic() {
+    // Test: The same seed gives byte-identical proofs, so proof sizes in the
+    // baseline do not depend on the run
+    let baseline: Baseline = serde_json::from_str(BASELINE).unwrap();
+    let query = &baseline.queries[0];
+    let (circuit, instances) = compile(&query.sql);
+    let context = ProvingContext::new(query.k, &circuit).unwrap();
+
+    let first = context
+        .prove_with_rng(&circuit, &instances, StdRng::seed_from_u64(7))
+        .unwrap();
+    let second = context
+        .prove_with_rng(&circuit, &instances, StdRng::seed_from_u64(7))
+        .unwrap();
+    assert_eq!(first, second);
+}
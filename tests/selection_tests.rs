@@ -0,0 +1,134 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::circuit::Value;
+use halo2_proofs::dev::MockProver;
+use poneglyphdb::circuit::{CommittedDatabase, PoneglyphCircuit, ResultCommitment};
+use poneglyphdb::prover::{prove_selection, selection_circuit, ProvingContext};
+use poneglyphdb::sql::{SQLCompiler, SQLParser};
+
+/// log2 of the mock circuits' row count (the committed table is hashed in
+/// the circuit)
+const K: u32 = 11;
+
+/// Order table (customer_id, amount)
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), vec![1, 2, 1, 3, 2]);
+    columns.insert("amount".to_string(), vec![40, 15, 60, 5, 30]);
+
+    let mut table_data = HashMap::new();
+    table_data.insert("order".to_string(), columns);
+    table_data
+}
+
+/// Row predicate over the amount column
+type Predicate = fn(u64) -> bool;
+
+/// Plaintext selection vector of a predicate over the amount column
+fn plaintext_selection(predicate: Predicate) -> Vec<u64> {
+    order_table()["order"]["amount"]
+        .iter()
+        .map(|&amount| predicate(amount) as u64)
+        .collect()
+}
+
+#[test]
+fn test_selection_vector_matches_predicate() {
+    // Test: The committed selection vector is the plaintext predicate's, and
+    // only the WHERE range checks are compiled
+    let cases: [(&str, Predicate); 4] = [
+        ("SELECT * FROM order WHERE amount < 35", |a| a < 35),
+        ("SELECT * FROM order WHERE amount > 100", |a| a > 100),
+        ("SELECT amount FROM order WHERE amount > 10 AND amount < 50", |a| a > 10 && a < 50),
+        ("SELECT sum(amount) FROM order WHERE amount * 2 > 70", |a| a * 2 > 70),
+    ];
+    for (sql, predicate) in cases {
+        let expected = plaintext_selection(predicate);
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile_selection(&query, &order_table()).unwrap();
+        assert!(compiled.aggregations.is_empty() && compiled.sorts.is_empty());
+        let selection = compiled.selection.unwrap();
+        let rows: Vec<Vec<u64>> = expected.iter().map(|&bit| vec![bit]).collect();
+        assert_eq!(selection.rows, rows, "{}", sql);
+
+        let (circuit, public_inputs) = selection_circuit(sql, &order_table()).unwrap();
+        assert_eq!(public_inputs.query_result, Some(ResultCommitment::new(rows).value()));
+        let prover = MockProver::run(K, &circuit, public_inputs.to_instances()).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+}
+
+#[test]
+fn test_selection_vector_is_bound_to_checks() {
+    // Test: A flipped bit fails even with its own commitment as the public
+    // input (the bits are copies of the check cells), as does another vector's
+    // commitment
+    let sql = "SELECT * FROM order WHERE amount < 35";
+    let (circuit, public_inputs) = selection_circuit(sql, &order_table()).unwrap();
+
+    let mut tampered = circuit.clone();
+    let selection = tampered.result_commitment.as_mut().unwrap();
+    selection.rows[0][0] ^= 1;
+    let instances = vec![public_inputs.db_commitments.clone(), vec![selection.value()]];
+    let prover = MockProver::run(K, &tampered, instances).unwrap();
+    assert!(prover.verify().is_err());
+
+    let other = ResultCommitment::new(vec![vec![1]; 5]).value();
+    let instances = vec![public_inputs.db_commitments.clone(), vec![other]];
+    let prover = MockProver::run(K, &circuit, instances).unwrap();
+    assert!(prover.verify().is_err());
+
+    let (proof, public_inputs) = prove_selection(sql, &order_table()).unwrap();
+    let context = ProvingContext::for_circuit(&circuit).unwrap();
+    assert!(context.verify(&proof, &public_inputs.to_instances()).unwrap());
+}
+
+#[test]
+fn test_selection_operands_are_committed() {
+    // Test: The table commitment is public, and a WHERE operand forged to
+    // match (amount 40 claimed as 10, with the selection vector it gives)
+    // fails against it; the same forgery verifies when the table is not
+    // committed
+    let sql = "SELECT * FROM order WHERE amount < 35";
+    let (circuit, public_inputs) = selection_circuit(sql, &order_table()).unwrap();
+    assert_eq!(
+        public_inputs.db_commitments,
+        CommittedDatabase::new(&order_table()).values()
+    );
+
+    let mut forged = circuit.clone();
+    let check = forged
+        .range_checks
+        .iter_mut()
+        .find(|op| op.source.is_some_and(|(_, row)| row == 0))
+        .unwrap();
+    check.value = Value::known(10);
+    let selection = forged.result_commitment.as_mut().unwrap();
+    selection.rows[0][0] = 1;
+    let forged_result = selection.value();
+    forged.query_result = Value::known(forged_result);
+
+    let instances = vec![public_inputs.db_commitments.clone(), vec![forged_result]];
+    let prover = MockProver::run(K, &forged, instances).unwrap();
+    assert!(prover.verify().is_err());
+
+    let unbound = PoneglyphCircuit {
+        database: None,
+        db_commitments: Vec::new(),
+        ..forged
+    };
+    let prover = MockProver::run(K, &unbound, vec![vec![], vec![forged_result]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_selection_needs_single_predicate() {
+    // Test: A query without WHERE, or whose WHERE has no single check bit per
+    // row, has no selection vector
+    assert!(selection_circuit("SELECT * FROM order", &order_table()).is_err());
+    let or = "SELECT * FROM order WHERE amount < 10 OR amount > 50";
+    let err = selection_circuit(or, &order_table()).err().unwrap();
+    assert!(err.contains("single comparison"), "{}", err);
+}
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "verifier-only"))]
+
 use halo2_proofs::{
     circuit::Value,
     dev::MockProver,
@@ -11,10 +13,12 @@ use poneglyphdb::circuit::*;
 #[derive(Clone)]
 struct SortTestCircuit {
     input: Vec<u64>,
+    algorithm: SortAlgorithm,
 }
 
 /// Config for test circuit
 #[derive(Clone)]
+#[allow(dead_code)]
 struct TestConfig {
     poneglyph_config: PoneglyphConfig,
     range_check_config: RangeCheckConfig,
@@ -28,6 +32,7 @@ impl Circuit<Fr> for SortTestCircuit {
     fn without_witnesses(&self) -> Self {
         Self {
             input: vec![],
+            algorithm: self.algorithm,
         }
     }
 
@@ -52,7 +57,7 @@ impl Circuit<Fr> for SortTestCircuit {
         config.poneglyph_config.load_lookup_table(&mut layouter)?;
         
         // Create sort chip
-        let sort_chip = SortChip::new(config.sort_config);
+        let sort_chip = SortChip::new(config.sort_config.with_algorithm(self.algorithm));
         
         // Prepare input as Value::known()
         let input_values: Vec<Value<u64>> = self.input.iter().map(|&v| Value::known(v)).collect();
@@ -62,12 +67,17 @@ impl Circuit<Fr> for SortTestCircuit {
         sorted_values.sort();
         
         // Sort and verify
-        let _output = sort_chip.sort_and_verify(
+        let output = sort_chip.sort_and_verify(
             layouter.namespace(|| "sort and verify"),
             input_values,
-            sorted_values,
+            sorted_values.clone(),
         )?;
         
+        // Output cells hold the sorted values, in order
+        for (cell, &expected) in output.iter().zip(sorted_values.iter()) {
+            cell.value().assert_if_known(|v| **v == Fr::from(expected));
+        }
+        
         Ok(())
     }
 }
@@ -78,8 +88,9 @@ fn test_sort_simple() {
     let k = 10;
     let circuit = SortTestCircuit {
         input: vec![3, 1, 4, 1, 5],
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -90,8 +101,9 @@ fn test_sort_already_sorted() {
     let k = 10;
     let circuit = SortTestCircuit {
         input: vec![1, 2, 3, 4, 5],
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -102,8 +114,9 @@ fn test_sort_reverse() {
     let k = 10;
     let circuit = SortTestCircuit {
         input: vec![5, 4, 3, 2, 1],
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -114,8 +127,9 @@ fn test_sort_single() {
     let k = 10;
     let circuit = SortTestCircuit {
         input: vec![42],
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -126,8 +140,9 @@ fn test_sort_duplicates() {
     let k = 10;
     let circuit = SortTestCircuit {
         input: vec![3, 1, 3, 1, 2],
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -138,9 +153,285 @@ fn test_sort_large() {
     let k = 12; // Larger k value required
     let circuit = SortTestCircuit {
         input: (0..50).rev().collect(),
+        algorithm: SortAlgorithm::Permutation,
+    };
+    let public_inputs = vec![vec![], vec![]];
+    let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+
+#[test]
+fn test_sort_large_batched_diffs() {
+    // Test: 500 unordered values with duplicates and 64-bit gaps (all diffs in one region)
+    // The 512-wide permutation network takes 256 · 17 = 4352 switch rows
+    let k = 13;
+    let mut input: Vec<u64> = (0..500).map(|i| (i * 7919) % 300).collect();
+    input[0] = u64::MAX;
+    input[1] = 1 << 40;
+    let circuit = SortTestCircuit {
+        input,
+        algorithm: SortAlgorithm::Permutation,
+    };
+    let public_inputs = vec![vec![], vec![]];
+    let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_sort_large_fits_without_padding() {
+    // Test: 1200 values at k = 15; no region is padded with empty rows
+    // (input + output + diffs = 3 * 1200 rows, and 1024 · 21 = 21504 rows of
+    // the 2048-wide permutation network)
+    let k = 15;
+    let input: Vec<u64> = (0..1200).map(|i| (i * 7919) % 1000).collect();
+    let circuit = SortTestCircuit {
+        input,
+        algorithm: SortAlgorithm::Permutation,
     };
-    let public_inputs = vec![vec![]];
+    let public_inputs = vec![vec![], vec![]];
     let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
 
+#[test]
+fn test_sort_bitonic_matches_permutation() {
+    // Test: Both sort algorithms prove the same data, including duplicates,
+    // u64::MAX values and a length that is padded to a power of two
+    let inputs: Vec<Vec<u64>> = vec![
+        vec![],
+        vec![42],
+        vec![3, 1, 4, 1, 5],
+        vec![8, 7, 6, 5, 4, 3, 2, 1],
+        vec![u64::MAX, 0, 1 << 40, 7, u64::MAX, 7],
+    ];
+    for input in inputs {
+        for algorithm in [SortAlgorithm::Permutation, SortAlgorithm::Bitonic] {
+            let circuit = SortTestCircuit {
+                input: input.clone(),
+                algorithm,
+            };
+            let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{:?} {:?}", algorithm, input);
+        }
+    }
+
+    // 5 values pad to 8: 8/2 · 3 · 4 / 2 = 24 comparisons vs 4 diffs
+    assert_eq!(SortAlgorithm::Bitonic.diff_count(5), 24);
+    assert_eq!(SortAlgorithm::Permutation.diff_count(5), 4);
+    assert_eq!(SortAlgorithm::Bitonic.diff_count(1), 0);
+}
+
+/// Sort circuit with a caller-supplied sorted output
+#[derive(Clone)]
+struct SortOutputCircuit {
+    input: Vec<u64>,
+    output: Vec<u64>,
+    algorithm: SortAlgorithm,
+}
+
+impl Circuit<Fr> for SortOutputCircuit {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let sort_chip = SortChip::new(gates.sort.with_algorithm(self.algorithm));
+        sort_chip.sort_and_verify(
+            layouter.namespace(|| "sort"),
+            self.input.iter().map(|&v| Value::known(v)).collect(),
+            self.output.clone(),
+        )?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sort_copy_constraint_matches_permutation() {
+    // Test: The copy-constrained sort (no sorted input copy) proves and
+    // verifies the same data as the sorted-input permutation check (also
+    // with real proofs); an output that is not a permutation of the input is
+    // rejected
+    let inputs: Vec<Vec<u64>> = vec![
+        vec![42],
+        vec![3, 1, 4, 1, 5],
+        vec![u64::MAX, 0, 1 << 40, 7, u64::MAX, 7],
+    ];
+    for input in inputs {
+        for algorithm in [SortAlgorithm::Permutation, SortAlgorithm::CopyConstraint] {
+            let circuit = SortTestCircuit {
+                input: input.clone(),
+                algorithm,
+            };
+            let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{:?} {:?}", algorithm, input);
+        }
+    }
+    for algorithm in [SortAlgorithm::Permutation, SortAlgorithm::CopyConstraint] {
+        let circuit = SortTestCircuit {
+            input: vec![3, 1, 4, 1, 5],
+            algorithm,
+        };
+        let context = poneglyphdb::prover::ProvingContext::new(10, &circuit).unwrap();
+        let proof = context.prove(&circuit, &[vec![], vec![]]).unwrap();
+        assert!(context.verify(&proof, &[vec![], vec![]]).unwrap());
+    }
+    assert_eq!(SortAlgorithm::CopyConstraint.diff_count(5), 4);
+
+    // Sorted, but 2 replaces an input 1
+    let circuit = SortOutputCircuit {
+        input: vec![3, 1, 4, 1, 5],
+        output: vec![1, 2, 3, 4, 5],
+        algorithm: SortAlgorithm::CopyConstraint,
+    };
+    assert!(MockProver::run(10, &circuit, vec![vec![], vec![]]).is_err());
+
+    let circuit = SortOutputCircuit {
+        input: vec![3, 1, 4, 1, 5],
+        output: vec![1, 1, 3, 4, 5],
+        algorithm: SortAlgorithm::CopyConstraint,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_sort_rejects_descending_pair() {
+    // Test: An output with a descending pair (3 after 5) is rejected: its
+    // diff cell holds the negative field diff, and the decomposed value is
+    // copied from that cell, so no 64-bit decomposition matches it
+    let circuit = SortOutputCircuit {
+        input: vec![3, 1, 5],
+        output: vec![1, 5, 3],
+        algorithm: SortAlgorithm::CopyConstraint,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Also at the ends of the u64 range (the wrapped diff is 1)
+    let circuit = SortOutputCircuit {
+        input: vec![0, u64::MAX],
+        output: vec![u64::MAX, 0],
+        algorithm: SortAlgorithm::CopyConstraint,
+    };
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_sort_multiset_hash_matches_permutation() {
+    // Test: The multiset-hash check (running products of value + γ, γ the
+    // Poseidon commitment to input and output) accepts the same sorts as the
+    // sorted-input permutation check (also with real proofs) and rejects an
+    // output that is not a permutation of the input
+    let inputs: Vec<Vec<u64>> = vec![
+        vec![42],
+        vec![3, 1, 4, 1, 5],
+        vec![u64::MAX, 0, 1 << 40, 7, u64::MAX, 7],
+    ];
+    for input in inputs {
+        let mut output = input.clone();
+        output.sort();
+        for algorithm in [SortAlgorithm::Permutation, SortAlgorithm::MultisetHash] {
+            let circuit = SortOutputCircuit {
+                input: input.clone(),
+                output: output.clone(),
+                algorithm,
+            };
+            let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{:?} {:?}", algorithm, input);
+        }
+    }
+    let circuit = SortOutputCircuit {
+        input: vec![3, 1, 4, 1, 5],
+        output: vec![1, 1, 3, 4, 5],
+        algorithm: SortAlgorithm::MultisetHash,
+    };
+    let context = poneglyphdb::prover::ProvingContext::new(11, &circuit).unwrap();
+    let proof = context.prove(&circuit, &[vec![], vec![]]).unwrap();
+    assert!(context.verify(&proof, &[vec![], vec![]]).unwrap());
+    assert_eq!(SortAlgorithm::MultisetHash.diff_count(5), 4);
+
+    // Sorted, but 2 replaces an input 1: the running products differ, and
+    // the sorted input copy has no input row holding 2
+    for algorithm in [SortAlgorithm::Permutation, SortAlgorithm::MultisetHash] {
+        let circuit = SortOutputCircuit {
+            input: vec![3, 1, 4, 1, 5],
+            output: vec![1, 2, 3, 4, 5],
+            algorithm,
+        };
+        let rejected = MockProver::run(11, &circuit, vec![vec![], vec![]])
+            .map_or(true, |prover| prover.verify().is_err());
+        assert!(rejected, "{:?}", algorithm);
+    }
+}
+
+#[test]
+fn test_sort_permutation_key_fits_every_input() {
+    // Test: The permutation check routes the input through a switch network
+    // whose layout depends only on the length, so one key proves inputs with
+    // different sorting permutations (also padded lengths and duplicates)
+    let context = poneglyphdb::prover::ProvingContext::new(
+        10,
+        &SortTestCircuit {
+            input: vec![3, 1, 4, 1, 5],
+            algorithm: SortAlgorithm::Permutation,
+        },
+    )
+    .unwrap();
+    for input in [
+        vec![5, 4, 3, 2, 1],
+        vec![1, 2, 3, 4, 5],
+        vec![9, 9, 0, u64::MAX, 9],
+    ] {
+        let circuit = SortTestCircuit {
+            input: input.clone(),
+            algorithm: SortAlgorithm::Permutation,
+        };
+        let proof = context.prove(&circuit, &[vec![], vec![]]).unwrap();
+        assert!(
+            context.verify(&proof, &[vec![], vec![]]).unwrap(),
+            "{:?}",
+            input
+        );
+    }
+
+    // Every permutation of 5 values is routed
+    let mut input = vec![0, 1, 2, 3, 4];
+    let mut count = 0;
+    loop {
+        let circuit = SortTestCircuit {
+            input: input.clone(),
+            algorithm: SortAlgorithm::Permutation,
+        };
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{:?}", input);
+        count += 1;
+        // Next permutation in lexicographic order
+        let Some(i) = (0..input.len() - 1)
+            .rev()
+            .find(|&i| input[i] < input[i + 1])
+        else {
+            break;
+        };
+        let j = (i + 1..input.len())
+            .rev()
+            .find(|&j| input[j] > input[i])
+            .unwrap();
+        input.swap(i, j);
+        input[i + 1..].reverse();
+    }
+    assert_eq!(count, 120);
+}
@@ -0,0 +1,409 @@
+// SQL query generators (proptest)
+// Random queries in the shapes `SQLParser::parse` produces, so that
+// rendering one with `Display` and parsing it back yields an equal `SQLQuery`
+
+use poneglyphdb::circuit::WindowFunction;
+use poneglyphdb::sql::*;
+use proptest::prelude::*;
+
+/// Table names (lowercase, not SQL keywords)
+const TABLES: &[&str] = &["lineitem", "orders", "customer", "part"];
+
+/// Column names (lowercase, not SQL keywords)
+const COLUMNS: &[&str] = &["id", "amount", "region", "price", "quantity", "status"];
+
+fn column() -> impl Strategy<Value = String> {
+    prop::sample::select(COLUMNS).prop_map(str::to_string)
+}
+
+fn table() -> impl Strategy<Value = String> {
+    prop::sample::select(TABLES).prop_map(str::to_string)
+}
+
+fn comparison_op() -> impl Strategy<Value = ComparisonOp> {
+    prop_oneof![
+        Just(ComparisonOp::LessThan),
+        Just(ComparisonOp::GreaterThan),
+        Just(ComparisonOp::Equal),
+    ]
+}
+
+/// Operand of `*`: column or literal
+fn factor() -> impl Strategy<Value = Expr> {
+    prop_oneof![
+        column().prop_map(Expr::Column),
+        any::<u64>().prop_map(Expr::Literal),
+    ]
+}
+
+/// Product of factors (left-associative, as parsed)
+fn term() -> impl Strategy<Value = Expr> {
+    factor().prop_recursive(2, 4, 1, |inner| {
+        (inner, factor()).prop_map(|(left, right)| Expr::Mul(Box::new(left), Box::new(right)))
+    })
+}
+
+/// Sum / difference of products (left-associative, as parsed)
+fn expr() -> impl Strategy<Value = Expr> {
+    term().prop_recursive(2, 8, 1, |inner| {
+        (inner, term(), any::<bool>()).prop_map(|(left, right, add)| {
+            if add {
+                Expr::Add(Box::new(left), Box::new(right))
+            } else {
+                Expr::Sub(Box::new(left), Box::new(right))
+            }
+        })
+    })
+}
+
+/// Correlated EXISTS subquery: `SELECT c FROM inner WHERE inner.x = outer.y`
+fn exists() -> impl Strategy<Value = WhereClause> {
+    (
+        column(),
+        table(),
+        column(),
+        table(),
+        column(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(selected, inner, inner_column, outer, outer_column, negated)| WhereClause::Exists {
+                subquery: Box::new(SQLQuery {
+                    columns: vec![selected],
+                    from: inner.clone(),
+                    where_clause: Some(WhereClause::ColumnCompare {
+                        left_column: format!("{}.{}", inner, inner_column),
+                        op: ComparisonOp::Equal,
+                        right_column: format!("{}.{}", outer, outer_column),
+                    }),
+                    group_by: None,
+                    order_by: None,
+                    having: None,
+                    joins: None,
+                    aggregations: None,
+                    windows: None,
+                    projections: None,
+                    union: None,
+                    limit: None,
+                }),
+                negated,
+            },
+        )
+}
+
+/// Single WHERE predicate
+fn predicate() -> impl Strategy<Value = WhereClause> {
+    prop_oneof![
+        (column(), any::<u64>())
+            .prop_map(|(column, value)| WhereClause::LessThan { column, value }),
+        (column(), any::<u64>())
+            .prop_map(|(column, value)| WhereClause::GreaterThan { column, value }),
+        (column(), any::<u64>()).prop_map(|(column, value)| WhereClause::Equal { column, value }),
+        (column(), comparison_op(), column()).prop_map(|(left_column, op, right_column)| {
+            WhereClause::ColumnCompare {
+                left_column,
+                op,
+                right_column,
+            }
+        }),
+        (
+            expr().prop_filter("a bare column is a plain comparison", |expr| {
+                !matches!(expr, Expr::Column(_))
+            }),
+            comparison_op(),
+            any::<u64>(),
+        )
+            .prop_map(|(expr, operator, value)| WhereClause::ExprCompare {
+                expr,
+                operator,
+                value,
+            }),
+        exists(),
+    ]
+}
+
+/// WHERE clause: AND of OR-chains, both nested to the right
+/// (the parser splits at the first AND, then at the first OR)
+fn where_clause() -> impl Strategy<Value = WhereClause> {
+    let or_chain = predicate().prop_recursive(2, 3, 1, |inner| {
+        (predicate(), inner)
+            .prop_map(|(left, right)| WhereClause::Or(Box::new(left), Box::new(right)))
+    });
+    or_chain.clone().prop_recursive(2, 3, 1, move |inner| {
+        (or_chain.clone(), inner)
+            .prop_map(|(left, right)| WhereClause::And(Box::new(left), Box::new(right)))
+    })
+}
+
+fn aggregation() -> impl Strategy<Value = AggregationClause> {
+    let function = prop_oneof![
+        Just(AggregationFunction::Sum),
+        Just(AggregationFunction::Count),
+        Just(AggregationFunction::Max),
+        Just(AggregationFunction::Min),
+        Just(AggregationFunction::Avg),
+    ];
+    prop_oneof![
+        (function, column()).prop_map(|(function, column)| AggregationClause {
+            function,
+            column,
+            distinct: false,
+        }),
+        column().prop_map(|column| AggregationClause {
+            function: AggregationFunction::Count,
+            column,
+            distinct: true,
+        }),
+        Just(AggregationClause {
+            function: AggregationFunction::Count,
+            column: "*".to_string(),
+            distinct: false,
+        }),
+    ]
+}
+
+/// Aggregation as written in SQL
+fn render_aggregation(aggregation: &AggregationClause) -> String {
+    let function = match aggregation.function {
+        AggregationFunction::Sum => "sum",
+        AggregationFunction::Count => "count",
+        AggregationFunction::Max => "max",
+        AggregationFunction::Min => "min",
+        AggregationFunction::Avg => "avg",
+    };
+    let distinct = if aggregation.distinct { "distinct " } else { "" };
+    format!("{}({}{})", function, distinct, aggregation.column)
+}
+
+/// SELECT list item
+#[derive(Clone, Debug)]
+enum Item {
+    Column(String),
+    Aggregation(AggregationClause),
+    Window(WindowClause),
+}
+
+impl Item {
+    fn render(&self) -> String {
+        match self {
+            Item::Column(column) => column.clone(),
+            Item::Aggregation(aggregation) => render_aggregation(aggregation),
+            Item::Window(window) => {
+                let partition = match &window.partition_by {
+                    Some(partition_by) => format!("partition by {} ", partition_by),
+                    None => String::new(),
+                };
+                match &window.column {
+                    Some(column) => format!(
+                        "sum({}) over ({}order by {})",
+                        column, partition, window.order_by
+                    ),
+                    None => format!(
+                        "row_number() over ({}order by {})",
+                        partition, window.order_by
+                    ),
+                }
+            }
+        }
+    }
+}
+
+fn item() -> impl Strategy<Value = Item> {
+    prop_oneof![
+        column().prop_map(Item::Column),
+        aggregation().prop_map(Item::Aggregation),
+        (column(), prop::option::of(column()), column()).prop_map(
+            |(column, partition_by, order_by)| Item::Window(WindowClause {
+                function: WindowFunction::RunningSum,
+                column: Some(column),
+                partition_by,
+                order_by,
+            })
+        ),
+        (prop::option::of(column()), column()).prop_map(|(partition_by, order_by)| {
+            Item::Window(WindowClause {
+                function: WindowFunction::RowNumber,
+                column: None,
+                partition_by,
+                order_by,
+            })
+        }),
+    ]
+}
+
+fn order_by() -> impl Strategy<Value = OrderBy> {
+    let key = prop_oneof![
+        column(),
+        aggregation().prop_map(|agg| render_aggregation(&agg))
+    ];
+    let direction = prop_oneof![Just(OrderDirection::Asc), Just(OrderDirection::Desc)];
+    let nulls = prop_oneof![Just(NullsOrder::First), Just(NullsOrder::Last)];
+    (key, direction, nulls).prop_map(|(column, direction, nulls)| OrderBy {
+        column,
+        direction,
+        nulls,
+    })
+}
+
+/// Joins of the FROM table (the first table) with distinct other tables:
+/// cross joins, or inner joins on an equality (rendered in WHERE)
+fn from_and_joins() -> impl Strategy<Value = (String, Option<Vec<JoinClause>>)> {
+    prop::sample::subsequence(TABLES.to_vec(), 1..=3)
+        .prop_shuffle()
+        .prop_flat_map(|tables| {
+            let joined = tables.len() - 1;
+            (
+                Just(tables),
+                prop::collection::vec((any::<bool>(), column(), column()), joined),
+            )
+        })
+        .prop_map(|(tables, conditions)| {
+            let joins: Vec<JoinClause> = tables[1..]
+                .iter()
+                .zip(conditions)
+                .map(|(table, (inner, left_column, right_column))| {
+                    if inner {
+                        JoinClause {
+                            table: table.to_string(),
+                            on: JoinCondition {
+                                left_column,
+                                right_column,
+                            },
+                            join_type: JoinType::Inner,
+                        }
+                    } else {
+                        JoinClause {
+                            table: table.to_string(),
+                            on: JoinCondition {
+                                left_column: String::new(),
+                                right_column: String::new(),
+                            },
+                            join_type: JoinType::Cross,
+                        }
+                    }
+                })
+                .collect();
+            let joins = if joins.is_empty() { None } else { Some(joins) };
+            (tables[0].to_string(), joins)
+        })
+}
+
+/// GROUP BY columns and an optional HAVING on an aggregation
+fn group_by() -> impl Strategy<Value = Option<(Vec<String>, Option<HavingClause>)>> {
+    let having = (aggregation(), comparison_op(), any::<u64>()).prop_map(
+        |(aggregation, operator, value)| HavingClause::Compare {
+            aggregation: render_aggregation(&aggregation),
+            operator,
+            value,
+        },
+    );
+    prop::option::of((
+        prop::collection::vec(column(), 1..3),
+        prop::option::of(having),
+    ))
+}
+
+/// Query without UNION
+fn select() -> impl Strategy<Value = SQLQuery> {
+    (
+        prop::collection::vec(item(), 1..4),
+        from_and_joins(),
+        prop::option::of(where_clause()),
+        group_by(),
+        prop::option::of(prop::collection::vec(order_by(), 1..3)),
+    )
+        .prop_map(|(items, (from, joins), where_clause, group_by, order_by)| {
+            let aggregations: Vec<AggregationClause> = items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Aggregation(aggregation) => Some(aggregation.clone()),
+                    _ => None,
+                })
+                .collect();
+            let windows: Vec<WindowClause> = items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Window(window) => Some(window.clone()),
+                    _ => None,
+                })
+                .collect();
+            let (group_by, having) = match group_by {
+                Some((columns, having)) => (Some(columns), having),
+                None => (None, None),
+            };
+
+            SQLQuery {
+                columns: items.iter().map(Item::render).collect(),
+                from,
+                where_clause,
+                group_by,
+                order_by,
+                having,
+                joins,
+                aggregations: (!aggregations.is_empty()).then_some(aggregations),
+                windows: (!windows.is_empty()).then_some(windows),
+                projections: None,
+                union: None,
+                limit: None,
+            }
+        })
+}
+
+/// Random query in a parser-produced shape (optionally `UNION [ALL]` of two)
+pub fn query() -> impl Strategy<Value = SQLQuery> {
+    (select(), prop::option::of((select(), any::<bool>()))).prop_map(|(mut query, union)| {
+        query.union = union.map(|(right, all)| UnionClause {
+            query: Box::new(right),
+            all,
+        });
+        query
+    })
+}
+
+/// SQL-like token soup for parser fuzzing: keywords, identifiers, numbers
+/// and operators in random order
+pub fn sql_like() -> impl Strategy<Value = String> {
+    let token = prop_oneof![
+        prop::sample::select(vec![
+            "select",
+            "from",
+            "where",
+            "and",
+            "or",
+            "not",
+            "exists",
+            "group by",
+            "having",
+            "order by",
+            "asc",
+            "desc",
+            "nulls first",
+            "nulls last",
+            "union",
+            "all",
+            "over",
+            "sum(",
+            "count(",
+            "max(",
+            "min(",
+            "avg(",
+            "row_number()",
+            "(",
+            ")",
+            ",",
+            "<",
+            ">",
+            "=",
+            "+",
+            "-",
+            "*",
+            ".",
+        ])
+        .prop_map(str::to_string),
+        column(),
+        table(),
+        any::<u64>().prop_map(|value| value.to_string()),
+        "[a-z0-9_().,*+<>= -]{0,4}",
+    ];
+    prop::collection::vec(token, 0..24).prop_map(|tokens| tokens.join(" "))
+}
@@ -0,0 +1,3107 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::DatabaseTable;
+use poneglyphdb::prover::{prove_query_limited, ProvingContext};
+use poneglyphdb::sql::*;
+
+/// Build table data in the compiler's column-major shape
+/// (table_name -> column_name -> values)
+fn table(name: &str, columns: &[(&str, Vec<u64>)]) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut table_columns = HashMap::new();
+    for (column, values) in columns {
+        table_columns.insert(column.to_string(), values.clone());
+    }
+
+    let mut table_data = HashMap::new();
+    table_data.insert(name.to_string(), table_columns);
+    table_data
+}
+
+/// Wrap compiled operations into a circuit (public inputs are not bound)
+fn circuit_from(compiled: CompiledQuery) -> PoneglyphCircuit {
+    PoneglyphCircuit::from_compiled(compiled, None, None)
+}
+
+/// Order table: 3 customers with sums 30, 10, 100
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table(
+        "order",
+        &[
+            ("customer_id", vec![1, 1, 2, 2, 3]),
+            ("amount", vec![10, 20, 5, 5, 100]),
+        ],
+    )
+}
+
+#[test]
+fn test_order_by_aggregation_desc() {
+    // Test: ORDER BY sum(amount) DESC sorts the per-group sums
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id ORDER BY sum(amount) DESC",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+
+    assert_eq!(compiled.aggregations.len(), 1);
+    assert_eq!(compiled.aggregations[0].group_results(), vec![30, 10, 100]);
+
+    // GROUP BY key sort, then one sort over the 3 group results (not the 5 raw rows)
+    assert_eq!(compiled.sorts.len(), 2);
+    assert_eq!(compiled.sorts[1].input.len(), 3);
+    assert_eq!(compiled.sorts[1].sorted_output, vec![100, 30, 10]);
+}
+
+#[test]
+fn test_order_by_aggregation_asc_proves() {
+    // Test: ORDER BY on an aggregation result is proven by the Sort Gate
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id ORDER BY sum(amount) ASC",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert_eq!(compiled.sorts[1].sorted_output, vec![10, 30, 100]);
+    assert_eq!(compiled.sorts[1].aggregation, Some(0));
+
+    let circuit = circuit_from(compiled.clone());
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The sort input rows are copies of the group result cells: a sorted
+    // output that is a permutation of forged inputs is rejected
+    let mut forged = compiled;
+    forged.sorts[1].input = [30, 10, 99].into_iter().map(Value::known).collect();
+    forged.sorts[1].sorted_output = vec![10, 30, 99];
+    let prover = MockProver::run(11, &circuit_from(forged), vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_order_by_aggregation_not_selected() {
+    // Test: ORDER BY an aggregation missing from the SELECT list is rejected
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id ORDER BY max(amount) DESC",
+    )
+    .unwrap();
+    assert!(SQLCompiler::compile(&query, &order_table()).is_err());
+}
+
+#[test]
+fn test_limit_top_k_groups_proves() {
+    // Test: ORDER BY sum(amount) DESC LIMIT 5 over 8 regions ranks the group
+    // sums, copied from their aggregation cells, and returns the 5 largest;
+    // a selection that is not the ranked prefix, or a ranking that is not
+    // sorted, is rejected
+    let data = table(
+        "sales",
+        &[
+            ("region", vec![1, 2, 3, 4, 5, 6, 7, 8, 1, 3, 5, 7, 2]),
+            (
+                "amount",
+                vec![40, 10, 70, 25, 90, 5, 60, 30, 20, 15, 5, 10, 5],
+            ),
+        ],
+    );
+    let query = SQLParser::parse(
+        "SELECT region, sum(amount) FROM sales GROUP BY region ORDER BY sum(amount) DESC LIMIT 5",
+    )
+    .unwrap();
+    assert_eq!(query.limit, Some(5));
+    assert!(query.to_string().ends_with(" LIMIT 5"));
+
+    let result = query.execute(&data).unwrap();
+    assert_eq!(
+        result.rows,
+        vec![
+            vec![5, 95],
+            vec![3, 85],
+            vec![7, 70],
+            vec![1, 60],
+            vec![8, 30]
+        ]
+    );
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.sorts.len(), 1);
+    assert_eq!(compiled.top_ks.len(), 1);
+    let top_k = &compiled.top_ks[0];
+    assert_eq!(top_k.sorted_results, vec![95, 85, 70, 60, 30, 25, 15, 5]);
+    assert_eq!(top_k.selected, vec![95, 85, 70, 60, 30]);
+    let plan = SQLCompiler::explain(&query, &data).unwrap();
+    assert!(
+        plan.contains("TOP 5 BY sum(amount) DESC (8 groups)"),
+        "{}",
+        plan
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Region 4's 25 returned in place of region 8's 30
+    let mut forged = circuit.clone();
+    forged.top_ks[0].selected[4] = 25;
+    let prover = MockProver::run(11, &forged, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Ranking with 30 and 25 swapped: a permutation, but not sorted
+    let mut forged = circuit.clone();
+    forged.top_ks[0].sorted_results = vec![95, 85, 70, 60, 25, 30, 15, 5];
+    forged.top_ks[0].selected = vec![95, 85, 70, 60, 25];
+    let prover = MockProver::run(11, &forged, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Ranking without region 8's 30: not the group results
+    let mut forged = circuit;
+    forged.top_ks[0].sorted_results = vec![95, 85, 70, 60, 25, 25, 15, 5];
+    forged.top_ks[0].selected = vec![95, 85, 70, 60, 25];
+    assert!(MockProver::run(11, &forged, vec![vec![], vec![]]).is_err());
+
+    // LIMIT without a ranked aggregation only truncates the result
+    let query = SQLParser::parse("SELECT region FROM sales ORDER BY amount LIMIT 2").unwrap();
+    assert_eq!(query.execute(&data).unwrap().rows, vec![vec![6], vec![5]]);
+    assert!(SQLCompiler::compile(&query, &data)
+        .unwrap()
+        .top_ks
+        .is_empty());
+}
+
+#[test]
+fn test_limit_reports_proven_total_matched() {
+    // Test: LIMIT 10 over the 29 rows with amount < 29 returns 10 rows,
+    // reports truncation and proves both the rows (the first 10 matches in
+    // ORDER BY order) and the 29 matches
+    let data = table(
+        "sales",
+        &[
+            ("id", (0..40).collect()),
+            ("amount", (0..40).map(|i| i * 17 % 40).collect()),
+        ],
+    );
+    let sql = "SELECT id, amount FROM sales WHERE amount < 29 ORDER BY amount DESC LIMIT 10";
+    let (proof, public_inputs, limited) = prove_query_limited(sql, &data, None).unwrap();
+    assert_eq!(limited.result.rows.len(), 10);
+    assert_eq!(limited.total_matched, 29);
+    assert!(limited.truncated());
+    assert!(limited.matches(&public_inputs));
+    assert_eq!(
+        public_inputs.query_result,
+        Some(limited.commitment().value())
+    );
+    assert_eq!(
+        limited.result.column("amount").unwrap(),
+        (19..29).rev().collect::<Vec<u64>>()
+    );
+
+    let query = SQLParser::parse(sql).unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert!(!compiled.range_checks.is_empty());
+    let proven = compiled.result.clone().unwrap();
+    assert_eq!(proven.value(), limited.commitment().value());
+    let plan = SQLCompiler::explain(&query, &data).unwrap();
+    assert!(
+        plan.contains("40 range checks, match count over 40 rows"),
+        "{}",
+        plan
+    );
+    let database = CommittedDatabase::new(&data);
+    let mut circuit =
+        PoneglyphCircuit::from_compiled(compiled, Some(proven.clone()), Some(database));
+    assert_eq!(public_inputs.db_commitments, circuit.db_commitments);
+    let context = ProvingContext::for_circuit(&circuit).unwrap();
+    assert!(context
+        .verify(&proof, &public_inputs.to_instances())
+        .unwrap());
+
+    // A claimed total of 10 (no truncation) or other rows are not the
+    // committed result, and none of them is provable: not a total of 10, not
+    // a non-matching row, not the matches out of order
+    let mut untruncated = limited.clone();
+    untruncated.total_matched = 10;
+    assert!(!untruncated.truncated());
+    assert!(!untruncated.matches(&public_inputs));
+    let mut other_rows = limited.clone();
+    other_rows.result.rows[9] = vec![37, 29];
+    assert!(!other_rows.matches(&public_inputs));
+    let mut reordered = limited.clone();
+    reordered.result.rows.swap(0, 1);
+    assert!(!reordered.matches(&public_inputs));
+    for claimed in [untruncated.commitment().value(), Fr::from(29)] {
+        assert!(!context
+            .verify(
+                &proof,
+                &[public_inputs.db_commitments.clone(), vec![claimed]]
+            )
+            .unwrap_or(false));
+    }
+    let k = context.k();
+    for forged in [untruncated, other_rows, reordered] {
+        let forged = ResultCommitment {
+            rows: forged.result.rows,
+            matched: Some(forged.total_matched),
+            ..proven.clone()
+        };
+        let instances = vec![public_inputs.db_commitments.clone(), vec![forged.value()]];
+        circuit.result_commitment = Some(forged);
+        let prover = MockProver::run(k, &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Without ORDER BY, a LIMIT above the match count returns every match in
+    // row order
+    let sql = "SELECT id FROM sales WHERE amount < 29 LIMIT 30";
+    let (_, public_inputs, limited) = prove_query_limited(sql, &data, None).unwrap();
+    assert_eq!(
+        (limited.result.rows.len(), limited.truncated()),
+        (29, false)
+    );
+    assert_eq!(limited.result.rows[..2], [vec![0], vec![1]]);
+    assert!(limited.matches(&public_inputs));
+
+    // The matches of a WHERE clause other than one constant comparison are
+    // not counted
+    let sql = "SELECT id FROM sales WHERE amount < 29 OR id = 3 LIMIT 10";
+    assert!(prove_query_limited(sql, &data, None).is_err());
+}
+
+/// Payment table: two NULL discounts
+fn payment_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table("payment", &[("discount", vec![30, NULL, 10, NULL, 20])])
+}
+
+#[test]
+fn test_order_by_nulls_default() {
+    // Test: NULLS LAST is the default for ASC, NULLS FIRST for DESC
+    let query = SQLParser::parse("SELECT discount FROM payment ORDER BY discount").unwrap();
+    assert_eq!(query.order_by.unwrap()[0].nulls, NullsOrder::Last);
+
+    let query = SQLParser::parse("SELECT discount FROM payment ORDER BY discount DESC").unwrap();
+    assert_eq!(query.order_by.unwrap()[0].nulls, NullsOrder::First);
+}
+
+#[test]
+fn test_order_by_direction_is_a_token() {
+    // Test: Only a trailing ASC / DESC keyword sets the direction; a column
+    // ending in "asc" or "desc" keeps its name, and extra whitespace is ignored
+    let query = SQLParser::parse("SELECT basc FROM item ORDER BY basc").unwrap();
+    let order = &query.order_by.unwrap()[0];
+    assert_eq!(order.column, "basc");
+    assert_eq!(order.direction, OrderDirection::Asc);
+
+    let query = SQLParser::parse("SELECT basc FROM item ORDER BY basc DESC").unwrap();
+    let order = &query.order_by.unwrap()[0];
+    assert_eq!(order.column, "basc");
+    assert_eq!(order.direction, OrderDirection::Desc);
+
+    let query = SQLParser::parse("SELECT bdesc FROM item ORDER BY bdesc").unwrap();
+    let order = &query.order_by.unwrap()[0];
+    assert_eq!(order.column, "bdesc");
+    assert_eq!(order.direction, OrderDirection::Asc);
+
+    let query =
+        SQLParser::parse("SELECT amount FROM order ORDER BY  amount  \t desc ,\n id   asc ")
+            .unwrap();
+    let orders = query.order_by.unwrap();
+    assert_eq!(orders[0].column, "amount");
+    assert_eq!(orders[0].direction, OrderDirection::Desc);
+    assert_eq!(orders[1].column, "id");
+    assert_eq!(orders[1].direction, OrderDirection::Asc);
+
+    let query = SQLParser::parse("SELECT amount FROM order ORDER BY amountdesc").unwrap();
+    let order = &query.order_by.unwrap()[0];
+    assert_eq!(order.column, "amountdesc");
+    assert_eq!(order.direction, OrderDirection::Asc);
+}
+
+#[test]
+fn test_order_by_nulls_first_proves() {
+    // Test: NULL rows are placed before the ascending non-null rows
+    let query =
+        SQLParser::parse("SELECT discount FROM payment ORDER BY discount ASC NULLS FIRST").unwrap();
+    let order = &query.order_by.as_ref().unwrap()[0];
+    assert_eq!(order.column, "discount");
+    assert_eq!(order.nulls, NullsOrder::First);
+
+    let compiled = SQLCompiler::compile(&query, &payment_table()).unwrap();
+    assert_eq!(
+        compiled.sorts[0].sorted_output,
+        vec![NULL, NULL, 10, 20, 30]
+    );
+    assert_eq!(
+        compiled.sorts[0].null_flags,
+        vec![true, true, false, false, false]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_order_by_nulls_last_proves() {
+    // Test: NULL rows are placed after the ascending non-null rows
+    let query =
+        SQLParser::parse("SELECT discount FROM payment ORDER BY discount NULLS LAST").unwrap();
+    let compiled = SQLCompiler::compile(&query, &payment_table()).unwrap();
+    assert_eq!(
+        compiled.sorts[0].sorted_output,
+        vec![10, 20, 30, NULL, NULL]
+    );
+    assert_eq!(
+        compiled.sorts[0].null_flags,
+        vec![false, false, false, true, true]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_order_by_nulls_misplaced_fails() {
+    // Test: a NULL row in the middle of a NULLS LAST output is rejected
+    let query = SQLParser::parse("SELECT discount FROM payment ORDER BY discount").unwrap();
+    let mut compiled = SQLCompiler::compile(&query, &payment_table()).unwrap();
+    compiled.sorts[0].sorted_output = vec![10, NULL, 20, 30, NULL];
+    compiled.sorts[0].null_flags = vec![false, true, false, false, true];
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_order_by_non_null_row_flagged_fails() {
+    // Test: is_null is proven from the row's value, so flagging the largest
+    // non-null row as NULL (skipping its sort order check) is rejected, and
+    // so is a NULL row flagged as non-null
+    let query = SQLParser::parse("SELECT discount FROM payment ORDER BY discount").unwrap();
+    let compiled = SQLCompiler::compile(&query, &payment_table()).unwrap();
+    assert_eq!(
+        compiled.sorts[0].sorted_output,
+        vec![10, 20, 30, NULL, NULL]
+    );
+
+    for null_flags in [
+        vec![false, false, true, true, true],
+        vec![false, false, false, false, true],
+    ] {
+        let mut compiled = compiled.clone();
+        compiled.sorts[0].null_flags = null_flags;
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[test]
+fn test_order_by_desc_proves() {
+    // Test: DESC emits a descending witness checked by the descending sort gate
+    let query = SQLParser::parse("SELECT discount FROM payment ORDER BY discount DESC").unwrap();
+    let compiled = SQLCompiler::compile(&query, &payment_table()).unwrap();
+    assert!(compiled.sorts[0].descending);
+    assert_eq!(
+        compiled.sorts[0].sorted_output,
+        vec![NULL, NULL, 30, 20, 10]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let query = SQLParser::parse("SELECT amount FROM order ORDER BY amount DESC").unwrap();
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert!(compiled.sorts[0].null_flags.is_empty());
+    let output = &compiled.sorts[0].sorted_output;
+    assert!(output.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_order_by_desc_ascending_witness_fails() {
+    // Test: an ascending witness does not pass the descending sort gate
+    let query = SQLParser::parse("SELECT amount FROM order ORDER BY amount DESC").unwrap();
+    let mut compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    compiled.sorts[0].sorted_output.reverse();
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_order_by_expression_proves() {
+    // Test: ORDER BY a + b sorts the per-row sums, and the executor returns
+    // rows in the same order
+    let data = table("item", &[("a", vec![5, 1, 7, 2]), ("b", vec![1, 9, 0, 2])]);
+    let query = SQLParser::parse("SELECT a, b FROM item ORDER BY a + b DESC").unwrap();
+    assert_eq!(
+        query.order_by.as_ref().unwrap()[0].expr(),
+        Some(Expr::parse("a + b").unwrap())
+    );
+    assert_eq!(query.validate(&data), Ok(()));
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.sorts.len(), 1);
+    assert_eq!(compiled.sorts[0].sorted_output, vec![10, 7, 6, 4]);
+
+    let result = query.execute(&data).unwrap();
+    assert_eq!(
+        result.rows,
+        vec![vec![1, 9], vec![7, 0], vec![5, 1], vec![2, 2]]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_order_by_expression_product_and_nulls() {
+    // Test: A product key emits a product operation per row, and a row with
+    // a NULL operand sorts as a NULL key
+    let data = table(
+        "line",
+        &[
+            ("price", vec![3, 2, NULL, 4]),
+            ("quantity", vec![2, 5, 1, 1]),
+        ],
+    );
+    let query =
+        SQLParser::parse("SELECT price FROM line ORDER BY price * quantity NULLS FIRST").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.products.len(), 3);
+    assert_eq!(compiled.sorts[0].sorted_output, vec![NULL, 4, 6, 10]);
+    assert_eq!(
+        compiled.sorts[0].null_flags,
+        vec![true, false, false, false]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Unknown columns in the expression are reported by validate
+    let query = SQLParser::parse("SELECT price FROM line ORDER BY price * qty").unwrap();
+    assert!(query.validate(&data).is_err());
+}
+
+#[test]
+fn test_select_expression_projects_column() {
+    // Test: SELECT a + b AS total returns the per-row sums as a named result
+    // column, and the query renders back to the same SQL
+    let data = table(
+        "item",
+        &[("a", vec![5, 1, 7, 2]), ("b", vec![1, 9, 0, NULL])],
+    );
+    let query = SQLParser::parse("SELECT a, a + b AS total FROM item").unwrap();
+    assert_eq!(query.columns, vec!["a", "total"]);
+    assert_eq!(
+        query.projection_expr("total"),
+        Some(Expr::parse("a + b").unwrap())
+    );
+    assert_eq!(query.validate(&data), Ok(()));
+    assert_eq!(query.to_string(), "SELECT a, a + b AS total FROM item");
+    assert_eq!(SQLParser::parse(&query.to_string()).unwrap(), query);
+
+    let result = query.execute(&data).unwrap();
+    assert_eq!(result.columns, vec!["a", "total"]);
+    assert_eq!(result.column("total").unwrap(), vec![6, 10, 7, NULL]);
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(
+        compiled.projections,
+        vec![("total".to_string(), vec![6, 10, 7, NULL])]
+    );
+
+    // Without an alias the expression names the column
+    let query = SQLParser::parse("SELECT a * b FROM item").unwrap();
+    assert_eq!(query.columns, vec!["a * b"]);
+    assert_eq!(
+        query.execute(&data).unwrap().column("a * b").unwrap(),
+        vec![5, 9, 0, NULL]
+    );
+}
+
+#[test]
+fn test_select_expression_product_proves() {
+    // Test: price * quantity AS total emits a product operation per row, and
+    // ORDER BY can sort by the computed column's name
+    let query =
+        SQLParser::parse("SELECT price * quantity AS total FROM lineitem ORDER BY total DESC")
+            .unwrap();
+    let compiled = SQLCompiler::compile(&query, &lineitem_table()).unwrap();
+    // One product per row for the column, and again for the sort key
+    assert_eq!(compiled.products.len(), 6);
+    assert_eq!(compiled.sorts[0].sorted_output, vec![2000, 1500, 1200]);
+
+    let result = query.execute(&lineitem_table()).unwrap();
+    assert_eq!(result.rows, vec![vec![2000], vec![1500], vec![1200]]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A computed column needs per-row output
+    let query =
+        SQLParser::parse("SELECT price * quantity AS total FROM lineitem GROUP BY price").unwrap();
+    assert!(SQLCompiler::compile(&query, &lineitem_table()).is_err());
+    assert!(SQLParser::parse("SELECT price * quantity AS FROM lineitem").is_err());
+}
+
+#[test]
+fn test_aggregate_over_expression_proves() {
+    // Test: SUM(price * quantity) aggregates the per-row products (one product
+    // operation each) and matches the plaintext reference, also per group
+    let data = lineitem_table();
+    let query = SQLParser::parse("SELECT sum(price * quantity) FROM lineitem").unwrap();
+    let aggregation = &query.aggregations.as_ref().unwrap()[0];
+    assert_eq!(aggregation.column, "price * quantity");
+    assert!(aggregation.expr().is_some());
+    assert_eq!(query.validate(&data), Ok(()));
+
+    let reference: u64 = data["lineitem"]["price"]
+        .iter()
+        .zip(&data["lineitem"]["quantity"])
+        .map(|(price, quantity)| price * quantity)
+        .sum();
+    assert_eq!(reference, 4700);
+    assert_eq!(query.execute(&data).unwrap().rows, vec![vec![reference]]);
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.products.len(), 3);
+    assert_eq!(compiled.aggregate_results(0).unwrap(), vec![reference]);
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Grouped, with MAX over the same expression
+    let data = table(
+        "lineitem",
+        &[
+            ("order_id", vec![2, 1, 2, 1]),
+            ("price", vec![100, 300, 250, 10]),
+            ("quantity", vec![12, 5, 8, 3]),
+        ],
+    );
+    let query = SQLParser::parse(
+        "SELECT order_id, sum(price * quantity), max(price * quantity) FROM lineitem \
+         GROUP BY order_id",
+    )
+    .unwrap();
+    let expected = vec![vec![1, 1530, 1500], vec![2, 3200, 2000]];
+    assert_eq!(query.execute(&data).unwrap().rows, expected);
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0).unwrap(), vec![1530, 3200]);
+    assert_eq!(compiled.aggregate_results(1).unwrap(), vec![1500, 2000]);
+    let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Unknown columns in the argument are reported
+    let query = SQLParser::parse("SELECT sum(price * discount) FROM lineitem").unwrap();
+    assert!(query.validate(&data).is_err());
+    assert!(SQLCompiler::compile(&query, &data).is_err());
+}
+
+#[test]
+fn test_select_case_when_buckets_rows() {
+    // Test: CASE WHEN buckets each row by a range check bit, proven as
+    // check * then + (1 - check) * otherwise (one check and two products per
+    // row), and matches the reference executor
+    let data = table("order", &[("amount", vec![1500, 20, 1000, 1001, NULL])]);
+    let sql = "SELECT amount, CASE WHEN amount > 1000 THEN 1 ELSE 0 END AS large FROM order";
+    let query = SQLParser::parse(sql).unwrap();
+    assert_eq!(query.columns, vec!["amount", "large"]);
+    assert_eq!(query.validate(&data), Ok(()));
+    assert_eq!(SQLParser::parse(&query.to_string()).unwrap(), query);
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(
+        compiled.projections,
+        vec![("large".to_string(), vec![1, 0, 0, 1, NULL])]
+    );
+    assert_eq!(compiled.range_checks.len(), 4);
+    assert_eq!(compiled.products.len(), 8);
+    assert_eq!(
+        query.execute(&data).unwrap().column("large").unwrap(),
+        vec![1, 0, 0, 1, NULL]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Branches are expressions; without an alias the expression names the column
+    let query = SQLParser::parse(
+        "SELECT CASE WHEN amount < 100 THEN amount * 2 ELSE amount END FROM order",
+    )
+    .unwrap();
+    let name = "CASE WHEN amount < 100 THEN amount * 2 ELSE amount END";
+    assert_eq!(query.columns, vec![name]);
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.projections[0].1, vec![1500, 40, 1000, 1001, NULL]);
+    assert_eq!(
+        query.execute(&data).unwrap().column(name).unwrap(),
+        vec![1500, 40, 1000, 1001, NULL]
+    );
+
+    // A single WHEN branch comparing with a constant
+    for sql in [
+        "SELECT CASE WHEN amount > 1 THEN 1 END FROM order",
+        "SELECT CASE WHEN amount > price THEN 1 ELSE 0 END FROM order",
+        "SELECT CASE amount WHEN 1 THEN 1 ELSE 0 END FROM order",
+    ] {
+        assert!(SQLParser::parse(sql).is_err(), "{}", sql);
+    }
+}
+
+#[test]
+fn test_validate_ok() {
+    // Test: a query over existing columns validates without compiling
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id ORDER BY sum(amount)",
+    )
+    .unwrap();
+    assert_eq!(query.validate(&order_table()), Ok(()));
+}
+
+#[test]
+fn test_validate_unknown_column() {
+    // Test: a missing WHERE column is reported as UnknownColumn
+    let query = SQLParser::parse("SELECT amount FROM order WHERE price < 50").unwrap();
+    assert_eq!(
+        query.validate(&order_table()),
+        Err(QueryError::UnknownColumn {
+            table: "order".to_string(),
+            column: "price".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_validate_ungrouped_column() {
+    // Test: an aggregate query may select GROUP BY columns (also qualified)
+    // and aggregations; any other column is reported as UngroupedColumn
+    let data = table(
+        "order",
+        &[
+            ("customer_id", vec![1, 1, 2]),
+            ("name", vec![7, 7, 8]),
+            ("amount", vec![10, 20, 5]),
+        ],
+    );
+    let query = SQLParser::parse(
+        "SELECT order.customer_id, sum(amount), count(amount) FROM order GROUP BY customer_id",
+    )
+    .unwrap();
+    assert_eq!(query.validate(&data), Ok(()));
+    assert!(SQLCompiler::compile(&query, &data).is_ok());
+
+    let ungrouped = |column: &str| {
+        Err(QueryError::UngroupedColumn {
+            column: column.to_string(),
+        })
+    };
+    let query =
+        SQLParser::parse("SELECT name, sum(amount) FROM order GROUP BY customer_id").unwrap();
+    assert_eq!(query.validate(&data), ungrouped("name"));
+    assert_eq!(
+        SQLCompiler::compile(&query, &data).unwrap_err(),
+        "Column name must appear in GROUP BY or be aggregated"
+    );
+
+    // Aggregations without GROUP BY group all rows
+    let query = SQLParser::parse("SELECT name, sum(amount) FROM order").unwrap();
+    assert_eq!(query.check_grouping(), ungrouped("name"));
+    let query = SQLParser::parse("SELECT * FROM order GROUP BY customer_id").unwrap();
+    assert_eq!(query.check_grouping(), ungrouped("*"));
+}
+
+#[test]
+fn test_validate_multi_column_group_by() {
+    // Test: GROUP BY over two columns is rejected rather than grouped by
+    // the first column only
+    let data = table(
+        "t",
+        &[
+            ("a", vec![1, 1, 2]),
+            ("b", vec![1, 2, 1]),
+            ("c", vec![10, 20, 70]),
+        ],
+    );
+    let query = SQLParser::parse("SELECT a, b, sum(c) FROM t GROUP BY a, b").unwrap();
+    let expected = Err(QueryError::MultiColumnGroupBy {
+        columns: vec!["a".to_string(), "b".to_string()],
+    });
+    assert_eq!(query.check_grouping(), expected);
+    assert_eq!(query.validate(&data), expected);
+    assert_eq!(
+        SQLCompiler::compile(&query, &data).unwrap_err(),
+        "GROUP BY over more than one column is not supported: a, b"
+    );
+}
+
+#[test]
+fn test_validate_unknown_join_table() {
+    // Test: a missing JOIN table is reported as UnknownTable
+    let mut query = SQLParser::parse("SELECT amount FROM order").unwrap();
+    query.joins = Some(vec![JoinClause {
+        table: "customer".to_string(),
+        on: JoinCondition {
+            left_column: "customer_id".to_string(),
+            right_column: "id".to_string(),
+        },
+        join_type: JoinType::Inner,
+    }]);
+    assert_eq!(
+        query.validate(&order_table()),
+        Err(QueryError::UnknownTable {
+            table: "customer".to_string(),
+        })
+    );
+}
+
+/// Line item table: price * quantity is 1200, 1500, 2000
+fn lineitem_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table(
+        "lineitem",
+        &[("price", vec![100, 300, 250]), ("quantity", vec![12, 5, 8])],
+    )
+}
+
+#[test]
+fn test_where_expression_parse() {
+    // Test: `*` binds tighter than `+`
+    let query =
+        SQLParser::parse("SELECT price FROM lineitem WHERE price + 2 * quantity < 50").unwrap();
+    match query.where_clause.unwrap() {
+        WhereClause::ExprCompare {
+            expr,
+            operator,
+            value,
+        } => {
+            assert_eq!(
+                expr,
+                Expr::Add(
+                    Box::new(Expr::Column("price".to_string())),
+                    Box::new(Expr::Mul(
+                        Box::new(Expr::Literal(2)),
+                        Box::new(Expr::Column("quantity".to_string())),
+                    )),
+                )
+            );
+            assert_eq!(operator, ComparisonOp::LessThan);
+            assert_eq!(value, 50);
+        }
+        other => panic!("expected ExprCompare, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_where_expression_product_proves() {
+    // Test: price * quantity > 1000 range checks the per-row products
+    let query =
+        SQLParser::parse("SELECT price FROM lineitem WHERE price * quantity > 1000").unwrap();
+    let compiled = SQLCompiler::compile(&query, &lineitem_table()).unwrap();
+    assert_eq!(compiled.products.len(), 3);
+    assert_eq!(compiled.range_checks.len(), 3);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_where_expression_tampered_product_fails() {
+    // Test: the range check copies the product cell, and the product copies its
+    // operands from the committed columns, so altering either fails
+    let table_data = lineitem_table();
+    let query =
+        SQLParser::parse("SELECT price FROM lineitem WHERE price * quantity > 1000").unwrap();
+    let compiled = SQLCompiler::compile(&query, &table_data).unwrap();
+    let price = CommittedDatabase::column_index(&table_data, "lineitem", "price").unwrap();
+    assert_eq!(
+        compiled.products[1].operands[0],
+        Operand::Committed(price, 1)
+    );
+    assert_eq!(
+        compiled.range_checks[1].expression,
+        Some(Operand::Product(1))
+    );
+
+    // A product other than the one range checked
+    let mut circuit = circuit_from(compiled.clone());
+    circuit.products[1].b = Value::known(6);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // An operand other than the committed cell (with a consistent range check)
+    let database = CommittedDatabase::new(&table_data);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled.clone(), None, Some(database.clone()));
+    let prover = MockProver::run(11, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    let mut circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    circuit.products[1].a = Value::known(301);
+    circuit.range_checks[1].value = Value::known(301 * 5);
+    let prover = MockProver::run(11, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+/// Account table: every row is under budget
+fn account_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table(
+        "account",
+        &[
+            ("spent", vec![100, 50, 1000]),
+            ("budget", vec![1000, 900, 5000]),
+        ],
+    )
+}
+
+#[test]
+fn test_where_column_compare_parse() {
+    // Test: a column on the right side parses as a column comparison
+    let query = SQLParser::parse("SELECT spent FROM account WHERE spent < budget").unwrap();
+    match query.where_clause.unwrap() {
+        WhereClause::ColumnCompare {
+            left_column,
+            op,
+            right_column,
+        } => {
+            assert_eq!(left_column, "spent");
+            assert_eq!(op, ComparisonOp::LessThan);
+            assert_eq!(right_column, "budget");
+        }
+        other => panic!("expected ColumnCompare, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_where_column_compare_proves() {
+    // Test: spent < budget and budget > spent range check against witnessed thresholds
+    for sql in [
+        "SELECT spent FROM account WHERE spent < budget",
+        "SELECT spent FROM account WHERE budget > spent",
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile(&query, &account_table()).unwrap();
+        assert_eq!(compiled.range_checks.len(), 3);
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.witnessed_threshold));
+        assert_eq!(
+            compiled
+                .range_checks
+                .iter()
+                .map(|op| op.threshold)
+                .collect::<Vec<_>>(),
+            vec![1000, 900, 5000]
+        );
+
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_where_non_strict_comparisons() {
+    // Test: `<=` and `>=` (with or without spaces) parse as their own
+    // comparisons, are proven by `prove_le` / `prove_ge` and keep the rows
+    // at the threshold
+    let data = table("order", &[("amount", vec![10, 20, 30, 20])]);
+    for (sql, clause, comparison, expected) in [
+        (
+            "SELECT amount FROM order WHERE amount <= 20",
+            WhereClause::LessEqual {
+                column: "amount".to_string(),
+                value: 20,
+            },
+            Comparison::Le,
+            vec![10, 20, 20],
+        ),
+        (
+            "SELECT amount FROM order WHERE amount>=20",
+            WhereClause::GreaterEqual {
+                column: "amount".to_string(),
+                value: 20,
+            },
+            Comparison::Ge,
+            vec![20, 30, 20],
+        ),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert_eq!(query.where_clause.as_ref(), Some(&clause));
+        assert_eq!(
+            query.execute(&data).unwrap().column("amount"),
+            Some(expected)
+        );
+
+        let compiled = SQLCompiler::compile(&query, &data).unwrap();
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.comparison == comparison && !op.witnessed_threshold));
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+    assert_eq!(
+        SQLParser::parse("SELECT amount FROM order WHERE amount <= 20")
+            .unwrap()
+            .to_string(),
+        "SELECT amount FROM order WHERE amount <= 20"
+    );
+}
+
+#[test]
+fn test_where_column_equality_is_exact() {
+    // Test: `spent = budget` is an equality check, not `spent <= budget`,
+    // also next to u64::MAX - 1; counted rows are the equal ones only, and
+    // `<=` / `>=` between columns keep equal rows
+    let data = table(
+        "account",
+        &[
+            ("spent", vec![5, 5, 900, u64::MAX - 1]),
+            ("budget", vec![5, 6, 800, u64::MAX - 1]),
+        ],
+    );
+    for (sql, expected) in [
+        ("SELECT count(*) FROM account WHERE spent = budget", 2),
+        ("SELECT count(*) FROM account WHERE spent <= budget", 3),
+        ("SELECT count(*) FROM account WHERE spent >= budget", 3),
+        ("SELECT count(*) FROM account WHERE budget >= spent", 3),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert_eq!(
+            query.execute(&data).unwrap().rows,
+            vec![vec![expected]],
+            "{}",
+            sql
+        );
+        let compiled = SQLCompiler::compile(&query, &data).unwrap();
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.witnessed_threshold));
+        assert_eq!(
+            compiled.aggregate_results(0),
+            Some(vec![expected]),
+            "{}",
+            sql
+        );
+
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+
+        // Counting every row is not the proven outcome
+        let mut forged = circuit.clone();
+        forged.aggregations[0].values = vec![1; 4];
+        let prover = MockProver::run(11, &forged, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err(), "{}", sql);
+    }
+}
+
+/// Order and customer tables for comma joins
+fn order_customer_tables() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut tables = order_table();
+    tables.extend(table(
+        "customer",
+        &[("id", vec![1, 2, 3]), ("age", vec![30, 40, 50])],
+    ));
+    tables
+}
+
+#[test]
+fn test_comma_join_binds_where_equality() {
+    // Test: FROM a, b WHERE a.x = b.y becomes an inner join on x = y (Join Gate)
+    let query = SQLParser::parse(
+        "SELECT amount FROM order, customer WHERE order.customer_id = customer.id",
+    )
+    .unwrap();
+    assert_eq!(query.from, "order");
+    assert!(query.where_clause.is_none());
+
+    let joins = query.joins.as_ref().unwrap();
+    assert_eq!(joins.len(), 1);
+    assert_eq!(joins[0].table, "customer");
+    assert_eq!(joins[0].join_type, JoinType::Inner);
+    assert_eq!(joins[0].on.left_column, "customer_id");
+    assert_eq!(joins[0].on.right_column, "id");
+
+    let compiled = SQLCompiler::compile(&query, &order_customer_tables()).unwrap();
+    assert_eq!(compiled.joins.len(), 1);
+    assert_eq!(compiled.joins[0].table1_keys, vec![1, 1, 2, 2, 3]);
+    assert_eq!(compiled.joins[0].table2_keys, vec![1, 2, 3]);
+    // Values are the first column by name, not in map order
+    assert_eq!(compiled.joins[0].table1_values, vec![10, 20, 5, 5, 100]);
+    assert_eq!(compiled.joins[0].table2_values, vec![30, 40, 50]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(12, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_comma_join_keeps_other_predicates() {
+    // Test: only the join predicate is removed from WHERE (reversed operand order)
+    let query = SQLParser::parse(
+        "SELECT amount FROM order, customer WHERE customer.id = order.customer_id AND amount < 500",
+    )
+    .unwrap();
+    let joins = query.joins.as_ref().unwrap();
+    assert_eq!(joins[0].join_type, JoinType::Inner);
+    assert_eq!(joins[0].on.left_column, "customer_id");
+    assert!(matches!(
+        query.where_clause,
+        Some(WhereClause::LessThan { ref column, value: 500 }) if column == "amount"
+    ));
+}
+
+#[test]
+fn test_comma_join_without_predicate() {
+    // Test: a comma join without an equality predicate stays a cross join and is rejected
+    let query = SQLParser::parse("SELECT amount FROM order, customer").unwrap();
+    assert_eq!(query.joins.as_ref().unwrap()[0].join_type, JoinType::Cross);
+    assert!(SQLCompiler::compile(&query, &order_customer_tables()).is_err());
+
+    // Single-table FROM is unchanged
+    let query = SQLParser::parse("SELECT amount FROM order").unwrap();
+    assert!(query.joins.is_none());
+}
+
+#[test]
+fn test_window_running_sum_proves() {
+    // Test: SUM(amount) OVER (ORDER BY id) is the cumulative sum in id order
+    let tables = table(
+        "order",
+        &[("id", vec![3, 1, 4, 2]), ("amount", vec![30, 10, 40, 20])],
+    );
+    let query =
+        SQLParser::parse("SELECT amount, sum(amount) OVER (ORDER BY id) FROM order").unwrap();
+    assert!(query.aggregations.is_none());
+    assert_eq!(
+        query.windows.as_ref().unwrap()[0],
+        WindowClause {
+            function: WindowFunction::RunningSum,
+            column: Some("amount".to_string()),
+            partition_by: None,
+            order_by: "id".to_string(),
+        }
+    );
+
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.sorts[0].sorted_output, vec![1, 2, 3, 4]);
+    assert_eq!(compiled.windows[0].values, vec![10, 20, 30, 40]);
+    assert_eq!(compiled.windows[0].results(), vec![10, 30, 60, 100]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_window_running_sum_copies_sorted_values() {
+    // Test: The Window Gate copies the summed column moved with the window
+    // sort (a sort payload) and binds the running totals: values in another
+    // order, or totals other than the proven ones, fail
+    let tables = table(
+        "order",
+        &[("id", vec![3, 1, 4, 2]), ("amount", vec![30, 10, 40, 20])],
+    );
+    let query =
+        SQLParser::parse("SELECT amount, sum(amount) OVER (ORDER BY id) FROM order").unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+
+    assert_eq!(compiled.windows[0].sort, Some(0));
+    assert_eq!(compiled.windows[0].sort_payload, Some(0));
+    assert_eq!(compiled.sorts[0].payloads[0].values, vec![30, 10, 40, 20]);
+    assert_eq!(compiled.windows[0].expected, Some(vec![10, 30, 60, 100]));
+
+    let database = CommittedDatabase::new(&tables);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Values summed in another order, with matching totals
+    let mut forged = circuit.clone();
+    forged.windows[0].values = vec![40, 30, 20, 10];
+    forged.windows[0].expected = Some(forged.windows[0].results());
+    let prover = MockProver::run(k, &forged, public_inputs.clone()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Totals other than the running sum of the sorted values
+    let mut forged = circuit;
+    forged.windows[0].expected = Some(vec![10, 30, 60, 99]);
+    let prover = MockProver::run(k, &forged, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_window_running_sum_overflow_rejected() {
+    // Test: A running total above u64::MAX is caught by the compiler and,
+    // for an op built by hand, by the Window Gate (synthesis fails) instead
+    // of a wrapped total or a field element no range check can validate
+    // (u64::MAX itself is NULL)
+    let tables = table(
+        "order",
+        &[("id", vec![1, 2]), ("amount", vec![u64::MAX - 1, 2])],
+    );
+    let query =
+        SQLParser::parse("SELECT amount, sum(amount) OVER (ORDER BY id) FROM order").unwrap();
+    let err = SQLCompiler::compile(&query, &tables).err().unwrap();
+    assert!(err.contains("exceeds u64::MAX"), "{}", err);
+    assert!(query.execute(&tables).is_err());
+
+    let window = WindowOp {
+        function: WindowFunction::RunningSum,
+        values: vec![u64::MAX - 1, 2],
+        sort: None,
+        sort_payload: None,
+        expected: None,
+        partition: None,
+    };
+    assert_eq!(window.results(), vec![u64::MAX - 1, u64::MAX]);
+    let tables = table("order", &[("id", vec![1, 2]), ("amount", vec![10, 1])]);
+    let mut circuit = circuit_from(SQLCompiler::compile(&query, &tables).unwrap());
+    circuit.windows = vec![window];
+    assert!(MockProver::run(11, &circuit, vec![vec![], vec![]]).is_err());
+
+    assert_eq!(
+        to_field_checked(u64::MAX as u128).unwrap(),
+        Fr::from(u64::MAX)
+    );
+    assert!(to_field_checked(u64::MAX as u128 + 1).is_err());
+}
+
+#[test]
+fn test_window_row_number_proves() {
+    // Test: ROW_NUMBER() OVER (ORDER BY score) numbers the sorted rows 1..n
+    let tables = table("player", &[("score", vec![70, 20, 90, 40, 20])]);
+    let query =
+        SQLParser::parse("SELECT score, row_number() OVER (ORDER BY score) FROM player").unwrap();
+    let window = &query.windows.as_ref().unwrap()[0];
+    assert_eq!(window.function, WindowFunction::RowNumber);
+    assert_eq!(window.column, None);
+
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+
+    // Row numbers are a contiguous 1..n sequence aligned to the sorted order
+    assert_eq!(compiled.sorts[0].sorted_output, vec![20, 20, 40, 70, 90]);
+    assert_eq!(compiled.windows[0].values, compiled.sorts[0].sorted_output);
+    assert_eq!(compiled.windows[0].results(), vec![1, 2, 3, 4, 5]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_window_partition_by_restarts_proves() {
+    // Test: OVER (PARTITION BY dept ORDER BY score) restarts ROW_NUMBER and
+    // the running SUM at each dept; the partition keys are copied from the
+    // window sort's output, so numbering across partitions or other
+    // partition keys fail
+    let tables = table(
+        "player",
+        &[
+            ("dept", vec![2, 1, 2, 1, 1]),
+            ("score", vec![50, 70, 40, 60, 80]),
+            ("bonus", vec![5, 7, 4, 6, 8]),
+        ],
+    );
+    let query = SQLParser::parse(
+        "SELECT score, row_number() OVER (PARTITION BY dept ORDER BY score) FROM player",
+    )
+    .unwrap();
+    let window = &query.windows.as_ref().unwrap()[0];
+    assert_eq!(window.partition_by.as_deref(), Some("dept"));
+    assert_eq!(window.order_by, "score");
+    assert_eq!(
+        query.execute(&tables).unwrap().rows,
+        vec![
+            vec![60, 1],
+            vec![70, 2],
+            vec![80, 3],
+            vec![40, 1],
+            vec![50, 2]
+        ]
+    );
+    assert!(SQLCompiler::explain(&query, &tables)
+        .unwrap()
+        .contains("WINDOW ROW_NUMBER() OVER (PARTITION BY dept ORDER BY score) (5 rows)"));
+
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    let partition = compiled.windows[0].partition.as_ref().unwrap();
+    assert_eq!(partition.keys, vec![1, 1, 1, 2, 2]);
+    assert_eq!(compiled.sorts[0].sorted_output[0], 1 << 32 | 60);
+    assert_eq!(compiled.windows[0].results(), vec![1, 2, 3, 1, 2]);
+
+    let database = CommittedDatabase::new(&tables);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database.clone()));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // One partition: numbered 1..5 across depts
+    let mut forged = circuit.clone();
+    let window = &mut forged.windows[0];
+    window.partition.as_mut().unwrap().keys = vec![1; 5];
+    window.expected = Some(window.results());
+    let prover = MockProver::run(k, &forged, public_inputs.clone()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Partition payload other than the committed column
+    let mut forged = circuit;
+    forged.sorts[0].payloads[0].values = vec![1; 5];
+    let prover = MockProver::run(k, &forged, public_inputs.clone()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Running SUM restarts too
+    let query = SQLParser::parse(
+        "SELECT score, sum(bonus) OVER (PARTITION BY dept ORDER BY score) FROM player",
+    )
+    .unwrap();
+    assert_eq!(
+        query.execute(&tables).unwrap().rows,
+        vec![
+            vec![60, 6],
+            vec![70, 13],
+            vec![80, 21],
+            vec![40, 4],
+            vec![50, 9]
+        ]
+    );
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.windows[0].results(), vec![6, 13, 21, 4, 9]);
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let mut forged = circuit;
+    forged.windows[0].expected = Some(vec![6, 13, 21, 25, 30]);
+    let prover = MockProver::run(k, &forged, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Packed keys need both parts below 2^32
+    let tables = table("player", &[("dept", vec![1 << 32]), ("score", vec![1])]);
+    let query = SQLParser::parse(
+        "SELECT score, row_number() OVER (PARTITION BY dept ORDER BY score) FROM player",
+    )
+    .unwrap();
+    let err = SQLCompiler::compile(&query, &tables).err().unwrap();
+    assert!(err.contains("below 2^32"), "{}", err);
+}
+
+#[test]
+fn test_having_filters_groups_proves() {
+    // Test: HAVING sum(amount) > 50 keeps 1 of the 3 groups (sums 30, 10, 100)
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id HAVING sum(amount) > 50",
+    )
+    .unwrap();
+    assert_eq!(query.group_by, Some(vec!["customer_id".to_string()]));
+
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert_eq!(compiled.having.len(), 1);
+    let passing = compiled.having[0].passing_groups(&compiled.aggregations);
+    assert_eq!(passing, vec![2]);
+    assert_eq!(passing.len(), 1);
+
+    // The kept group count is bound to the query result
+    let circuit = circuit_from(compiled);
+    assert!(circuit.having[0].public_count);
+    assert_eq!(circuit.bound_result(), Some(Fr::from(1)));
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(1)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_having_kept_count_rejects_wrong_claim() {
+    // Test: HAVING sum(amount) > 50 keeps 1 group; the pass bits are proven
+    // from the result cells, so claiming 2 or 0 kept groups fails
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id HAVING sum(amount) > 50",
+    )
+    .unwrap();
+    let circuit = circuit_from(SQLCompiler::compile(&query, &order_table()).unwrap());
+
+    for claimed in [0, 2] {
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(claimed)]]).unwrap();
+        assert!(prover.verify().is_err(), "claimed {}", claimed);
+    }
+}
+
+#[test]
+fn test_having_count_and_sum_conditions_prove() {
+    // Test: HAVING count(*) > 1 AND sum(amount) < 20 keeps customer 2 only
+    // (counts 2, 2, 1; sums 30, 10, 100): each condition is compared on the
+    // group result cells of its own aggregation, the pass bits are combined
+    // per group in the circuit and their sum is the bound kept-group count
+    let sql = "SELECT customer_id, count(*), sum(amount) FROM order GROUP BY customer_id \
+               HAVING count(*) > 1 AND sum(amount) < 20";
+    let query = SQLParser::parse(sql).unwrap();
+    assert!(matches!(query.having, Some(HavingClause::And(..))));
+    assert_eq!(SQLParser::parse(&query.to_string()).unwrap(), query);
+    let rows = query.execute(&order_table()).unwrap().rows;
+    assert_eq!(rows, vec![vec![2, 2, 10]]);
+
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert_eq!(compiled.having.len(), 1);
+    let HavingPredicate::And(count, sum) = &compiled.having[0].predicate else {
+        panic!("expected AND, got {:?}", compiled.having[0].predicate);
+    };
+    assert_eq!(
+        count.pass_bits(&compiled.aggregations),
+        Some(vec![true, true, false])
+    );
+    assert_eq!(
+        sum.pass_bits(&compiled.aggregations),
+        Some(vec![false, true, false])
+    );
+    assert_ne!(count.aggregations(), sum.aggregations());
+    assert_eq!(
+        compiled.having[0].passing_groups(&compiled.aggregations),
+        vec![1]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(1)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    // The groups passing either condition alone (2) are not the AND's
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(2)]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // OR keeps the groups passing either condition
+    let query = SQLParser::parse(
+        "SELECT customer_id, count(*), sum(amount) FROM order GROUP BY customer_id \
+         HAVING count(*) = 1 OR sum(amount) < 20",
+    )
+    .unwrap();
+    assert!(matches!(query.having, Some(HavingClause::Or(..))));
+    let rows = query.execute(&order_table()).unwrap().rows;
+    assert_eq!(rows, vec![vec![2, 2, 10], vec![3, 1, 100]]);
+
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert!(matches!(
+        compiled.having[0].predicate,
+        HavingPredicate::Or(..)
+    ));
+    assert_eq!(
+        compiled.having[0].passing_groups(&compiled.aggregations),
+        vec![1, 2]
+    );
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(2)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    for claimed in [1, 3] {
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![Fr::from(claimed)]]).unwrap();
+        assert!(prover.verify().is_err(), "claimed {}", claimed);
+    }
+}
+
+#[test]
+fn test_having_clause_boundaries() {
+    // Test: WHERE, GROUP BY, HAVING and ORDER BY are split at their keywords
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order WHERE amount < 500 GROUP BY customer_id HAVING sum(amount) = 30 ORDER BY sum(amount) DESC",
+    )
+    .unwrap();
+    assert!(matches!(
+        query.where_clause,
+        Some(WhereClause::LessThan { value: 500, .. })
+    ));
+    assert_eq!(query.group_by, Some(vec!["customer_id".to_string()]));
+    assert!(matches!(
+        query.having,
+        Some(HavingClause::Compare {
+            operator: ComparisonOp::Equal,
+            value: 30,
+            ..
+        })
+    ));
+    assert_eq!(query.order_by.as_ref().unwrap()[0].column, "sum(amount)");
+
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert_eq!(
+        compiled.having[0].passing_groups(&compiled.aggregations),
+        vec![0]
+    );
+}
+
+/// Customers 1-5; orders reference customers 1, 2 and 3
+fn customer_order_tables() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut tables = order_table();
+    tables.extend(table("customer", &[("id", vec![4, 1, 5, 3, 2])]));
+    tables
+}
+
+#[test]
+fn test_exists_semi_join_proves() {
+    // Test: EXISTS with a single equality correlation compiles to a semi-join
+    let query = SQLParser::parse(
+        "SELECT id FROM customer WHERE EXISTS (SELECT 1 FROM order WHERE order.customer_id = customer.id)",
+    )
+    .unwrap();
+    assert_eq!(query.from, "customer");
+    match &query.where_clause {
+        Some(WhereClause::Exists { subquery, negated }) => {
+            assert_eq!(subquery.from, "order");
+            assert!(!negated);
+        }
+        other => panic!("expected EXISTS, got {:?}", other),
+    }
+
+    let compiled = SQLCompiler::compile(&query, &customer_order_tables()).unwrap();
+    assert_eq!(compiled.semi_joins.len(), 1);
+    let semi_join = &compiled.semi_joins[0];
+    assert_eq!(semi_join.left_keys, vec![4, 1, 5, 3, 2]);
+    assert_eq!(semi_join.right_keys, vec![1, 1, 2, 2, 3]);
+    // Customers 1, 3 and 2
+    assert_eq!(semi_join.passing_rows(), vec![1, 3, 4]);
+
+    let circuit = circuit_from(compiled);
+    assert!(circuit.features().join);
+    let prover = MockProver::run(12, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_not_exists_semi_join_proves() {
+    // Test: NOT EXISTS keeps the rows without a match, combined with another predicate
+    let query = SQLParser::parse(
+        "SELECT id FROM customer WHERE id > 0 AND NOT EXISTS (SELECT 1 FROM order WHERE customer.id = order.customer_id)",
+    )
+    .unwrap();
+
+    let compiled = SQLCompiler::compile(&query, &customer_order_tables()).unwrap();
+    assert_eq!(compiled.range_checks.len(), 5);
+    assert_eq!(compiled.semi_joins.len(), 1);
+    assert!(compiled.semi_joins[0].negated);
+    // Customers 4 and 5
+    assert_eq!(compiled.semi_joins[0].passing_rows(), vec![0, 2]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(12, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_exists_count_copies_keep_flags() {
+    // Test: count(*) with WHERE [NOT] EXISTS sums the semi-join's keep
+    // flags (its match flags, complemented for NOT EXISTS), each copied
+    // from its cell: forged values or a flipped negation fail
+    let tables = customer_order_tables();
+    for (negated, expected) in [(false, 3), (true, 2)] {
+        let sql = format!(
+            "SELECT count(*) FROM customer WHERE {}EXISTS (SELECT 1 FROM order WHERE order.customer_id = customer.id)",
+            if negated { "NOT " } else { "" }
+        );
+        let query = SQLParser::parse(&sql).unwrap();
+        assert_eq!(query.execute(&tables).unwrap().rows, vec![vec![expected]]);
+        let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+        assert_eq!(compiled.aggregations[0].value_semi_join, Some(0));
+        assert_eq!(compiled.aggregate_results(0), Some(vec![expected]));
+
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(12, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+
+        // Every customer counted
+        let mut forged = circuit.clone();
+        forged.aggregations[0].values = vec![1; 5];
+        let prover = MockProver::run(12, &forged, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err(), "{}", sql);
+
+        // The other EXISTS / NOT EXISTS outcome
+        let mut forged = circuit.clone();
+        forged.semi_joins[0].negated = !negated;
+        let prover = MockProver::run(12, &forged, vec![vec![], vec![]]).unwrap();
+        assert!(prover.verify().is_err(), "{}", sql);
+    }
+
+    // Per group, the flags are not in group order
+    let query = SQLParser::parse(
+        "SELECT id, count(*) FROM customer WHERE EXISTS (SELECT 1 FROM order WHERE order.customer_id = customer.id) GROUP BY id",
+    )
+    .unwrap();
+    assert!(SQLCompiler::compile(&query, &tables).is_err());
+}
+
+#[test]
+fn test_exists_unsupported_correlation() {
+    // Test: EXISTS needs a qualified equality with the outer table; NULL keys are rejected
+    let tables = customer_order_tables();
+    let uncorrelated = SQLParser::parse(
+        "SELECT id FROM customer WHERE EXISTS (SELECT 1 FROM order WHERE amount > 10)",
+    )
+    .unwrap();
+    assert!(SQLCompiler::compile(&uncorrelated, &tables).is_err());
+
+    let mut tables_with_null = tables.clone();
+    tables_with_null.extend(table("customer", &[("id", vec![1, NULL])]));
+    let query = SQLParser::parse(
+        "SELECT id FROM customer WHERE EXISTS (SELECT 1 FROM order WHERE order.customer_id = customer.id)",
+    )
+    .unwrap();
+    assert!(SQLCompiler::compile(&query, &tables_with_null).is_err());
+}
+
+/// Two customer lists with overlapping ids (2 and 3)
+fn customer_lists() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut tables = table("store", &[("id", vec![3, 1, 2]), ("age", vec![30, 10, 20])]);
+    tables.extend(table(
+        "online",
+        &[("id", vec![2, 5, 3, 4]), ("age", vec![20, 50, 30, 40])],
+    ));
+    tables
+}
+
+#[test]
+fn test_union_all_concatenates_rows() {
+    // Test: UNION ALL row count is the sum of both sides, duplicates kept
+    let query =
+        SQLParser::parse("SELECT id, age FROM store UNION ALL SELECT id, age FROM online").unwrap();
+    let union = query.union.as_ref().unwrap();
+    assert!(union.all);
+    assert_eq!(union.query.from, "online");
+
+    let compiled = SQLCompiler::compile(&query, &customer_lists()).unwrap();
+    let result = compiled.union.as_ref().unwrap();
+    assert_eq!(result.rows.len(), 3 + 4);
+    assert_eq!(result.rows[0], vec![3, 30]);
+    assert_eq!(result.rows[3], vec![2, 20]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_union_deduplicates_overlap_proves() {
+    // Test: UNION keeps each id once; distinctness is proven by Sort + Group-By boundaries
+    let query = SQLParser::parse("SELECT id FROM store UNION SELECT id FROM online").unwrap();
+    assert!(!query.union.as_ref().unwrap().all);
+
+    let compiled = SQLCompiler::compile(&query, &customer_lists()).unwrap();
+    assert_eq!(
+        compiled.union.as_ref().unwrap().rows,
+        vec![vec![1], vec![2], vec![3], vec![4], vec![5]]
+    );
+    assert_eq!(compiled.sorts.len(), 1);
+    assert_eq!(compiled.group_bys[0].group_keys, vec![1, 2, 2, 3, 3, 4, 5]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_union_schema_mismatch() {
+    // Test: both sides must project the same number of plain columns, and
+    // UNION deduplication takes a single column
+    let tables = customer_lists();
+    for sql in [
+        "SELECT id, age FROM store UNION SELECT id FROM online",
+        "SELECT id FROM store UNION ALL SELECT sum(age) FROM online",
+        "SELECT id, age FROM store UNION SELECT id, age FROM online",
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert!(SQLCompiler::compile(&query, &tables).is_err(), "{}", sql);
+    }
+}
+
+/// Users table: ages 25, 31, 47
+fn users_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    table("users", &[("id", vec![1, 2, 3]), ("age", vec![25, 31, 47])])
+}
+
+#[test]
+fn test_empty_where_result_proves_zero_count() {
+    // Test: WHERE age < 0 matches no rows; the count 0 is proven and bound to
+    // the query result (result instance), and a claimed non-empty result fails
+    let query = SQLParser::parse("SELECT id FROM users WHERE age < 0").unwrap();
+    let compiled = SQLCompiler::compile(&query, &users_table()).unwrap();
+
+    assert!(compiled.range_checks.is_empty());
+    let op = compiled.match_count.as_ref().unwrap();
+    assert_eq!((op.lower, op.upper), (None, Some(0)));
+    assert_eq!(op.count(), 0);
+
+    let circuit = circuit_from(compiled);
+    let empty = vec![vec![], vec![Fr::from(0)]];
+    let prover = MockProver::run(10, &circuit, empty).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let non_empty = vec![vec![], vec![Fr::from(1)]];
+    let prover = MockProver::run(10, &circuit, non_empty).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_empty_where_result_equality_and_greater_than() {
+    // Test: = and > predicates matching no rows compile to the empty result form
+    for sql in [
+        "SELECT id FROM users WHERE age = 30",
+        "SELECT id FROM users WHERE age > 47",
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile(&query, &users_table()).unwrap();
+        assert_eq!(
+            compiled.match_count.as_ref().map(|op| op.count()),
+            Some(0),
+            "{}",
+            sql
+        );
+
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![Fr::from(0)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+
+    // A WHERE comparison with matches keeps its per-row range checks
+    let query = SQLParser::parse("SELECT id FROM users WHERE age > 30").unwrap();
+    let compiled = SQLCompiler::compile(&query, &users_table()).unwrap();
+    assert!(compiled.match_count.is_none());
+    assert_eq!(compiled.range_checks.len(), 3);
+}
+
+#[test]
+fn test_group_by_unsorted_keys_proves() {
+    // Test: GROUP BY on interleaved keys sorts the key column (Sort Gate) and
+    // co-sorts the values, so each key forms a single group
+    let tables = table(
+        "order",
+        &[
+            ("customer_id", vec![3, 1, 2, 1, 3, 2]),
+            ("amount", vec![100, 10, 5, 20, 7, 5]),
+        ],
+    );
+    let query = SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+        .unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+
+    assert_eq!(compiled.sorts.len(), 1);
+    assert_eq!(compiled.sorts[0].sorted_output, vec![1, 1, 2, 2, 3, 3]);
+    assert_eq!(compiled.group_bys[0].group_keys, vec![1, 1, 2, 2, 3, 3]);
+
+    let aggregation = &compiled.aggregations[0];
+    assert_eq!(aggregation.values, vec![10, 20, 5, 5, 100, 7]);
+    assert_eq!(aggregation.partial().group_keys, vec![1, 2, 3]);
+    assert_eq!(aggregation.group_results(), vec![30, 10, 107]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_group_by_copies_sorted_keys_and_values() {
+    // Test: The Group-By and Aggregation Gates copy the GROUP BY sort's output
+    // cells, and the aggregated column is moved with the keys (a sort
+    // payload): values attributed to another group, or keys other than the
+    // sorted ones, fail
+    let tables = table(
+        "order",
+        &[
+            ("customer_id", vec![3, 1, 2, 1, 3, 2]),
+            ("amount", vec![100, 10, 5, 20, 7, 5]),
+        ],
+    );
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount), max(amount) FROM order GROUP BY customer_id",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+
+    assert_eq!(compiled.group_bys[0].sort, Some(0));
+    assert_eq!(compiled.sorts[0].payloads.len(), 1);
+    assert_eq!(
+        compiled.sorts[0].payloads[0].values,
+        vec![100, 10, 5, 20, 7, 5]
+    );
+    for aggregation in &compiled.aggregations {
+        assert_eq!(aggregation.group_sort, Some(0));
+        assert_eq!(aggregation.sort_payload, Some(0));
+    }
+
+    let database = CommittedDatabase::new(&tables);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // 20 summed into customer 2 and one 5 into customer 1 (same multiset)
+    let mut forged = circuit.clone();
+    forged.aggregations[0].values = vec![10, 5, 20, 5, 100, 7];
+    let prover = MockProver::run(k, &forged, public_inputs.clone()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Customers 1 and 2 merged into one group
+    let mut forged = circuit.clone();
+    for aggregation in &mut forged.aggregations {
+        aggregation.group_keys = vec![1, 1, 1, 1, 3, 3];
+    }
+    let prover = MockProver::run(k, &forged, public_inputs.clone()).unwrap();
+    assert!(prover.verify().is_err());
+
+    // A payload other than the committed column
+    let mut forged = circuit;
+    forged.sorts[0].payloads[0].values = vec![100, 10, 20, 5, 7, 5];
+    let prover = MockProver::run(k, &forged, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_sum_above_u64_uses_wide_sum() {
+    // Test: SUM whose total exceeds u64::MAX compiles to a 128-bit (wide)
+    // SUM aggregation; comparing it in HAVING is rejected
+    let big = u64::MAX / 4;
+    let tables = table(
+        "order",
+        &[
+            ("customer_id", vec![1, 1, 1, 1, 1, 2]),
+            ("amount", vec![big, big, big, big, big, 7]),
+        ],
+    );
+    let query = SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+        .unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+
+    let aggregation = &compiled.aggregations[0];
+    assert_eq!(aggregation.function, AggregationFunction::Sum);
+    assert!(aggregation.is_wide());
+    assert_eq!(aggregation.wide_group_results(), vec![5 * big as u128, 7]);
+    assert_eq!(aggregation.group_results(), vec![u64::MAX, 7]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let having = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id HAVING sum(amount) > 10",
+    )
+    .unwrap();
+    assert!(SQLCompiler::compile(&having, &tables).is_err());
+}
+
+#[test]
+fn test_display_renders_parsable_sql() {
+    // Test: Display renders a parsed query as SQL that parses back to it
+    let query = SQLParser::parse(
+        "SELECT region, sum(amount) FROM lineitem, orders WHERE lineitem.id = orders.id AND price * 2 > 10 GROUP BY region ORDER BY sum(amount) DESC",
+    )
+    .unwrap();
+    let sql = query.to_string();
+
+    assert_eq!(
+        sql,
+        "SELECT region, sum(amount) FROM lineitem, orders WHERE lineitem.id = orders.id AND price * 2 > 10 GROUP BY region ORDER BY sum(amount) DESC NULLS FIRST"
+    );
+    assert_eq!(SQLParser::parse(&sql).unwrap(), query);
+}
+
+#[test]
+fn test_multi_aggregate_shares_group_keys() {
+    // Test: sum, avg and count(*) over one GROUP BY share a single group-key
+    // set, and AVG is proven as SUM and COUNT over the same groups
+    let data = table(
+        "sales",
+        &[
+            ("region", vec![2, 1, 2, 1, 3]),
+            ("x", vec![10, 20, 30, 40, 50]),
+            ("y", vec![3, 4, 8, 7, 9]),
+        ],
+    );
+    let query =
+        SQLParser::parse("SELECT region, sum(x), avg(y), count(*) FROM sales GROUP BY region")
+            .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+
+    assert_eq!(compiled.aggregate_specs.len(), 3);
+    assert_eq!(compiled.aggregate_specs[1].aggregations.len(), 2);
+    assert_eq!(compiled.aggregations.len(), 4);
+    assert!(compiled
+        .aggregate_specs
+        .iter()
+        .all(|spec| spec.group_by == Some(0)));
+    assert_eq!(compiled.aggregate_group_keys(), &[1, 1, 2, 2, 3]);
+    for aggregation in &compiled.aggregations {
+        assert_eq!(aggregation.group_keys, compiled.aggregate_group_keys());
+    }
+
+    assert_eq!(compiled.aggregate_results(0).unwrap(), vec![60, 40, 50]);
+    assert_eq!(compiled.aggregate_results(1).unwrap(), vec![5, 5, 9]);
+    assert_eq!(compiled.aggregate_results(2).unwrap(), vec![2, 2, 1]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_count_distinct_per_group_proves() {
+    // Test: count(distinct product) per region over overlapping product
+    // sets matches a plaintext reference, next to count(*) over the same
+    // groups, and the composite (region, product) sort proves
+    let region = vec![2, 1, 2, 1, 1, 2, 1, 2];
+    let product = vec![7, 5, 5, 7, 5, 9, 8, 7];
+    let data = table(
+        "sales",
+        &[("region", region.clone()), ("product", product.clone())],
+    );
+
+    let mut reference: BTreeMap<u64, BTreeSet<u64>> = BTreeMap::new();
+    for (&region, &product) in region.iter().zip(&product) {
+        reference.entry(region).or_default().insert(product);
+    }
+    let expected: Vec<u64> = reference
+        .values()
+        .map(|products| products.len() as u64)
+        .collect();
+    assert_eq!(expected, vec![3, 3]);
+
+    let sql = "SELECT region, count(distinct product), count(*) FROM sales GROUP BY region";
+    let query = SQLParser::parse(sql).unwrap();
+    assert_eq!(query.to_string(), sql);
+    let aggregations = query.aggregations.as_ref().unwrap();
+    assert!(aggregations[0].distinct && !aggregations[1].distinct);
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(expected.clone()));
+    assert_eq!(compiled.aggregate_results(1), Some(vec![4, 4]));
+    assert_eq!(compiled.sorts.len(), 2);
+    assert_eq!(compiled.group_bys.len(), 2);
+
+    let result = query.execute(&data).unwrap();
+    let counts: Vec<u64> = result.rows.iter().map(|row| row[1]).collect();
+    assert_eq!(counts, expected);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Without GROUP BY all rows are one group
+    let query = SQLParser::parse("SELECT count(distinct product) FROM sales").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![4]));
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_where_greater_than_full_range() {
+    // Test: x > v is proven by a strict x > v comparison (no v + 1 threshold),
+    // from v = 0 up to v = u64::MAX
+    let tables = table("t", &[("x", vec![1, 7, u64::MAX - 1])]);
+
+    let query = SQLParser::parse("SELECT x FROM t WHERE x > 0").unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    let ops: Vec<_> = compiled
+        .range_checks
+        .iter()
+        .map(|op| {
+            (
+                op.value,
+                op.threshold,
+                op.comparison,
+                op.witnessed_threshold,
+            )
+        })
+        .collect();
+    assert_eq!(ops.len(), 3);
+    for ((value, threshold, comparison, witnessed), x) in ops.into_iter().zip([1, 7, u64::MAX - 1])
+    {
+        value.assert_if_known(|&value| value == x);
+        assert_eq!(
+            (threshold, comparison, witnessed),
+            (0, Comparison::Gt, false)
+        );
+    }
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Only u64::MAX (NULL) is above u64::MAX - 1: empty result, count 0 proven
+    let tables = table("t", &[("x", vec![u64::MAX - 1, u64::MAX])]);
+    let query = SQLParser::parse(&format!("SELECT x FROM t WHERE x > {}", u64::MAX - 1)).unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.match_count.as_ref().map(|op| op.count()), Some(0));
+    let prover =
+        MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![Fr::from(0)]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // x > u64::MAX and x = u64::MAX compile (no threshold overflow) and prove
+    for sql in [
+        format!("SELECT x FROM t WHERE x > {}", u64::MAX),
+        format!("SELECT x FROM t WHERE x = {}", u64::MAX),
+    ] {
+        let compiled = SQLCompiler::compile(&SQLParser::parse(&sql).unwrap(), &tables).unwrap();
+        assert_eq!(compiled.range_checks.len(), 2);
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.threshold == u64::MAX));
+        let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+}
+
+#[test]
+fn test_identifiers_containing_keywords() {
+    // Test: Names containing keywords (where_, order_id, fromage, android)
+    // are identifiers, not clause boundaries
+    let query = SQLParser::parse(
+        "SELECT where_, order_id FROM fromage WHERE android < 5 AND origin = 2 \
+         GROUP BY where_ ORDER BY order_id DESC",
+    )
+    .unwrap();
+    assert_eq!(query.columns, vec!["where_", "order_id"]);
+    assert_eq!(query.from, "fromage");
+    assert_eq!(query.group_by, Some(vec!["where_".to_string()]));
+    let order_by = query.order_by.as_ref().unwrap();
+    assert_eq!(order_by[0].column, "order_id");
+    assert_eq!(order_by[0].direction, OrderDirection::Desc);
+    match query.where_clause.as_ref().unwrap() {
+        WhereClause::And(left, right) => {
+            assert!(matches!(
+                left.as_ref(),
+                WhereClause::LessThan { column, value: 5 } if column == "android"
+            ));
+            assert!(matches!(
+                right.as_ref(),
+                WhereClause::Equal { column, value: 2 } if column == "origin"
+            ));
+        }
+        other => panic!("expected AND, got {:?}", other),
+    }
+
+    // Operators need no surrounding spaces
+    let query = SQLParser::parse("SELECT a FROM t WHERE a<5").unwrap();
+    assert!(matches!(
+        query.where_clause,
+        Some(WhereClause::LessThan { value: 5, .. })
+    ));
+}
+
+#[test]
+fn test_tokenize_keywords_strings_and_operators() {
+    // Test: A keyword inside a string literal stays in the string, and
+    // identifiers are lowercased
+    let tokens = tokenize("SELECT Selection FROM t WHERE b = ' from x'").unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Keyword(Keyword::Select),
+            Token::Identifier("selection".to_string()),
+            Token::Keyword(Keyword::From),
+            Token::Identifier("t".to_string()),
+            Token::Keyword(Keyword::Where),
+            Token::Identifier("b".to_string()),
+            Token::Operator(Operator::Equal),
+            Token::String(" from x".to_string()),
+        ]
+    );
+
+    assert!(tokenize("SELECT a FROM t WHERE b = 'open").is_err());
+    assert!(tokenize("SELECT a FROM t WHERE b ! 1").is_err());
+    assert!(tokenize("SELECT a FROM t WHERE b < 99999999999999999999").is_err());
+}
+
+#[test]
+fn test_where_modulo_selects_every_tenth_row() {
+    // Test: id % 10 = 0 proves a = q * 10 + r with r < 10 for every row, and
+    // the remainder is 0 exactly on every tenth row
+    let tables = table("t", &[("id", (1..=30).collect())]);
+    let query = SQLParser::parse("SELECT id FROM t WHERE id % 10 = 0").unwrap();
+    assert!(matches!(
+        &query.where_clause,
+        Some(WhereClause::ExprCompare {
+            expr: Expr::Mod(..),
+            ..
+        })
+    ));
+    assert_eq!(
+        query.execute(&tables).unwrap().rows,
+        vec![vec![10], vec![20], vec![30]]
+    );
+
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.divisions.len(), 30);
+    for (id, op) in (1..=30).zip(&compiled.range_checks) {
+        // remainder = 0 holds only for ids 10, 20 and 30
+        op.value.assert_if_known(|&remainder| remainder == id % 10);
+        assert_eq!((op.threshold, op.comparison), (0, Comparison::Eq));
+    }
+
+    let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Integer division: id / 10 < 1 holds for ids 1..9
+    let query = SQLParser::parse("SELECT id FROM t WHERE id / 10 < 1").unwrap();
+    assert_eq!(query.execute(&tables).unwrap().rows.len(), 9);
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_where_modulo_tampered_row_fails() {
+    // Test: the division copies its dividend from the committed column and the
+    // range check its value from the remainder cell, so a row forged to pass
+    // id % 10 = 0 is rejected
+    let tables = table("t", &[("id", (1..=30).collect())]);
+    let query = SQLParser::parse("SELECT id FROM t WHERE id % 10 = 0").unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(
+        compiled.divisions[12].operands,
+        [Operand::Committed(0, 12), Operand::Constant(10)]
+    );
+    assert_eq!(
+        compiled.range_checks[12].expression,
+        Some(Operand::Remainder(12))
+    );
+
+    let database = CommittedDatabase::new(&tables);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled.clone(), None, Some(database.clone()));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // id 13 witnessed as 20, remainder 0
+    let mut circuit = PoneglyphCircuit::from_compiled(compiled.clone(), None, Some(database));
+    circuit.divisions[12].a = Value::known(20);
+    circuit.range_checks[12].value = Value::known(0);
+    let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Remainder 0 checked for id 13 (with no database to copy the dividend from)
+    let mut circuit = circuit_from(compiled);
+    circuit.range_checks[12].value = Value::known(0);
+    let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_divide_by_zero_rejected() {
+    // Test: A zero divisor is a compile error, and a division witness with
+    // b = 0 cannot satisfy r < b
+    let tables = table("t", &[("id", vec![5, 7]), ("zero", vec![0, 0])]);
+    for sql in [
+        "SELECT id FROM t WHERE id % 0 = 0",
+        "SELECT id FROM t WHERE id / zero < 3",
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let error = SQLCompiler::compile(&query, &tables).unwrap_err();
+        assert!(error.contains("Division by zero"), "{}", error);
+        assert!(query.execute(&tables).is_err());
+    }
+
+    let query = SQLParser::parse("SELECT id FROM t WHERE id % 2 = 1").unwrap();
+    let mut compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    // (a constant divisor is fixed in the key, so witness it instead)
+    compiled.divisions[0].operands[1] = Operand::Witness;
+    compiled.divisions[0].b = Value::known(0);
+    let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+/// Product table with a category per row, and a committed set of 5
+/// allowed categories
+fn category_tables() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut tables = table(
+        "product",
+        &[
+            ("id", (1..=10).collect()),
+            ("category", vec![3, 8, 12, 3, 40, 7, 21, 8, 99, 15]),
+        ],
+    );
+    tables.extend(table("allowed", &[("category", vec![3, 7, 8, 15, 21])]));
+    tables
+}
+
+#[test]
+fn test_where_in_committed_set() {
+    // Test: category IN (allowed) keeps the rows whose category is one of
+    // the 5 committed values, each row's member bit proven by the set
+    // membership lookup
+    let tables = category_tables();
+    let query = SQLParser::parse("SELECT id FROM product WHERE category IN (allowed)").unwrap();
+    assert_eq!(
+        query.where_clause,
+        Some(WhereClause::In {
+            column: "category".to_string(),
+            set: "allowed".to_string(),
+        })
+    );
+    assert_eq!(
+        query.execute(&tables).unwrap().column("id"),
+        Some(vec![1, 2, 4, 6, 7, 8, 10])
+    );
+
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.set_memberships.len(), 1);
+    assert_eq!(compiled.set_memberships[0].set, vec![3, 7, 8, 15, 21]);
+    assert_eq!(compiled.where_predicates, vec![0..10]);
+    assert!(compiled
+        .range_checks
+        .iter()
+        .all(|op| op.member_of == Some(0)));
+
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The loaded intervals partition [0, 2^64), including both ends
+    assert_eq!(
+        SetMembershipChip::intervals(&[3, 0, u64::MAX, 3]),
+        vec![
+            (0, 0, true),
+            (1, 2, false),
+            (3, 3, true),
+            (4, u64::MAX - 1, false),
+            (u64::MAX, u64::MAX, true),
+        ]
+    );
+
+    // The set must be a single-column table
+    let query = SQLParser::parse("SELECT id FROM product WHERE category IN (product)").unwrap();
+    assert!(SQLCompiler::compile(&query, &tables).is_err());
+    assert!(SQLParser::parse("SELECT id FROM product WHERE category IN 3").is_err());
+}
+
+#[test]
+fn test_in_committed_set_rejects_non_members() {
+    // Test: Each row's member bit is proven from its committed value, per
+    // set loaded in the circuit: a non-member witnessed as a member fails
+    // the copy from the database, and a COUNT of the bits cannot count
+    // every row
+    let mut tables = category_tables();
+    tables.extend(table("other", &[("category", vec![12, 40])]));
+    let query = SQLParser::parse(
+        "SELECT id FROM product WHERE category IN (allowed) AND category IN (other)",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.set_memberships[1].set, vec![12, 40]);
+    assert_eq!(compiled.where_predicates, vec![0..10, 10..20]);
+
+    let database = CommittedDatabase::new(&tables);
+    let public_inputs = vec![database.values(), vec![]];
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, Some(database));
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Row 2 (category 12) witnessed as 3, a member of allowed
+    let mut forged = circuit.clone();
+    forged.range_checks[2].value = Value::known(3);
+    let prover = MockProver::run(k, &forged, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+
+    // count(*) sums the member bits
+    let query =
+        SQLParser::parse("SELECT count(*) FROM product WHERE category IN (allowed)").unwrap();
+    assert_eq!(query.execute(&tables).unwrap().rows, vec![vec![7]]);
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![7]));
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let mut forged = circuit.clone();
+    forged.aggregations[0].values = vec![1; 10];
+    let prover = MockProver::run(10, &forged, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_compile_from_row_major_table() {
+    // Test: A row-major DatabaseTable is compiled, executed and validated
+    // directly, as its transposed column map would be
+    let mut table = DatabaseTable::new(
+        "order".to_string(),
+        vec!["customer_id".to_string(), "amount".to_string()],
+    );
+    for row in [[1, 10], [1, 20], [2, 5], [2, 5], [3, 100]] {
+        table.insert(row.to_vec()).unwrap();
+    }
+    assert_eq!(table.to_column_map(), order_table());
+
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount) FROM order WHERE amount < 50 GROUP BY customer_id",
+    )
+    .unwrap();
+    assert!(query.validate(&table).is_ok());
+    assert_eq!(
+        query.execute(&table).unwrap(),
+        query.execute(&order_table()).unwrap()
+    );
+
+    let compiled = SQLCompiler::compile(&query, &table).unwrap();
+    assert_eq!(compiled.range_checks.len(), 5);
+    assert_eq!(compiled.aggregations[0].group_results(), vec![30, 10, 0]);
+    let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_insert_rejects_wrong_row_length() {
+    // Test: Too-short and too-long rows are reported and not inserted
+    let mut table = DatabaseTable::new(
+        "order".to_string(),
+        vec!["customer_id".to_string(), "amount".to_string()],
+    );
+    table.insert(vec![1, 10]).unwrap();
+    let err = table.insert(vec![2]).unwrap_err();
+    assert_eq!(
+        err,
+        QueryError::RowLength {
+            table: "order".to_string(),
+            row: 1,
+            expected: 2,
+            found: 1,
+        }
+    );
+    assert!(
+        err.to_string().contains("has 1 values, expected 2"),
+        "{}",
+        err
+    );
+    assert!(matches!(
+        table.insert(vec![2, 20, 30]),
+        Err(QueryError::RowLength { found: 3, .. })
+    ));
+    assert_eq!(table.data, vec![vec![1, 10]]);
+
+    // A bulk load reports the first bad row and inserts none
+    let rows = vec![vec![2, 20], vec![3, 30, 300], vec![4]];
+    assert!(matches!(
+        table.try_insert_many(rows),
+        Err(QueryError::RowLength {
+            row: 1,
+            found: 3,
+            ..
+        })
+    ));
+    assert_eq!(table.data.len(), 1);
+    table
+        .try_insert_many(vec![vec![2, 20], vec![3, 30]])
+        .unwrap();
+    assert_eq!(table.data.len(), 3);
+}
+
+#[test]
+fn test_prepared_query_binds_two_thresholds() {
+    // Test: One prepared statement bound to two thresholds gives the two
+    // concrete queries, and both compile and prove
+    let prepared =
+        PreparedQuery::prepare("SELECT amount FROM order WHERE amount < :threshold").unwrap();
+    assert_eq!(prepared.parameters(), vec!["threshold"]);
+
+    for (threshold, expected) in [(50, vec![10, 20, 5, 5]), (8, vec![5, 5])] {
+        let query = prepared.bind("threshold", threshold).unwrap();
+        let literal = format!("SELECT amount FROM order WHERE amount < {}", threshold);
+        assert_eq!(query, SQLParser::parse(&literal).unwrap());
+        assert_eq!(
+            query.execute(&order_table()).unwrap().column("amount"),
+            Some(expected)
+        );
+
+        let compiled = prepared
+            .compile(&[("threshold", threshold)], &order_table())
+            .unwrap();
+        let prover = MockProver::run(11, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn test_prepared_query_parameter_errors() {
+    // Test: Parameters must all be bound, by known names, and only parse
+    // through PreparedQuery
+    assert_eq!(
+        SQLParser::parse("SELECT amount FROM order WHERE amount < :threshold"),
+        Err("Unbound parameter :threshold".to_string())
+    );
+
+    let prepared = PreparedQuery::prepare(
+        "SELECT amount FROM order WHERE amount > :low AND customer_id = 2 AND amount < :high",
+    )
+    .unwrap();
+    assert_eq!(prepared.parameters(), vec!["low", "high"]);
+    assert_eq!(
+        prepared.bind("low", 4),
+        Err("Unbound parameter :high".to_string())
+    );
+    assert_eq!(
+        prepared.bind_all(&[("low", 4), ("high", 9), ("limit", 1)]),
+        Err("Unknown parameter :limit".to_string())
+    );
+
+    let query = prepared.bind_all(&[("low", 4), ("high", 9)]).unwrap();
+    assert_eq!(
+        query.where_clause.unwrap().to_string(),
+        "amount > 4 AND customer_id = 2 AND amount < 9"
+    );
+}
+
+#[test]
+fn test_global_aggregates_prove() {
+    // Test: Without GROUP BY, SUM / MAX / MIN / COUNT aggregate all rows as
+    // one group and prove without a Group-By Gate
+    for (sql, expected) in [
+        ("SELECT sum(amount) FROM order", 140),
+        ("SELECT max(amount) FROM order", 100),
+        ("SELECT min(amount) FROM order", 5),
+        ("SELECT count(amount) FROM order", 5),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+        assert!(compiled.group_bys.is_empty());
+        assert!(compiled.aggregate_group_keys().is_empty());
+        assert_eq!(compiled.aggregate_results(0).unwrap(), vec![expected]);
+
+        let circuit = circuit_from(compiled);
+        assert!(!circuit.capabilities().group_by);
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+
+    // Several global aggregates share one run
+    let query = SQLParser::parse("SELECT max(amount), avg(amount), count(*) FROM order").unwrap();
+    let compiled = SQLCompiler::compile(&query, &order_table()).unwrap();
+    assert_eq!(compiled.aggregate_results(1).unwrap(), vec![28]);
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_bounded_where_shares_one_check() {
+    // Test: `x > a AND x < b` on one column compiles to one bounded check
+    // per row (either order), proves, and uses half the rows of two checks
+    let data = table("t", &[("amount", (0..100).map(|i| i * 3).collect())]);
+    for sql in [
+        "SELECT amount FROM t WHERE amount > 30 AND amount < 200",
+        "SELECT amount FROM t WHERE amount < 200 AND amount > 30",
+    ] {
+        let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+        assert_eq!(compiled.range_checks.len(), 100);
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.lower == Some(31) && op.threshold == 200));
+
+        let circuit = circuit_from(compiled);
+        let prover = MockProver::run(11, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+
+    // The same bounds as two separate checks per row
+    let compile = |sql: &str| SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    let mut separate = compile("SELECT amount FROM t WHERE amount > 30");
+    separate
+        .range_checks
+        .extend(compile("SELECT amount FROM t WHERE amount < 200").range_checks);
+    let bounded = compile("SELECT amount FROM t WHERE amount > 30 AND amount < 200");
+
+    let separate_rows = circuit_from(separate).stats().unwrap().used_rows;
+    let bounded_rows = circuit_from(bounded).stats().unwrap().used_rows;
+    assert!(
+        bounded_rows * 2 <= separate_rows,
+        "bounded {} rows, separate {} rows",
+        bounded_rows,
+        separate_rows
+    );
+}
+
+#[test]
+fn test_range_check_budget_exceeded() {
+    // Test: A WHERE over a column larger than the budget is refused with the
+    // offending count before any range check is built; counts of one column
+    // add up across predicates, and a bounded predicate counts once
+    let data = table(
+        "t",
+        &[
+            ("amount", (0..300).collect()),
+            ("other", (0..300).collect()),
+        ],
+    );
+    let query = SQLParser::parse("SELECT amount FROM t WHERE amount < 50").unwrap();
+
+    assert_eq!(
+        SQLCompiler::check_range_check_budget(&query, &data, 299),
+        Err(QueryError::RangeCheckBudgetExceeded {
+            table: "t".to_string(),
+            column: "amount".to_string(),
+            count: 300,
+            budget: 299,
+        })
+    );
+    let err = SQLCompiler::compile_with_budget(&query, &data, 299).unwrap_err();
+    assert!(err.contains("300 range checks"), "{}", err);
+    assert_eq!(
+        SQLCompiler::compile_with_budget(&query, &data, 300)
+            .unwrap()
+            .range_checks
+            .len(),
+        300
+    );
+    assert!(SQLCompiler::compile(&query, &data).is_ok());
+
+    let count = |sql: &str, budget: usize| {
+        let query = SQLParser::parse(sql).unwrap();
+        match SQLCompiler::check_range_check_budget(&query, &data, budget) {
+            Err(QueryError::RangeCheckBudgetExceeded { column, count, .. }) => {
+                Some((column, count))
+            }
+            _ => None,
+        }
+    };
+    assert_eq!(
+        count("SELECT amount FROM t WHERE amount < 50 OR amount = 7", 500),
+        Some(("amount".to_string(), 600))
+    );
+    assert_eq!(
+        count(
+            "SELECT amount FROM t WHERE amount > 10 AND amount < 50",
+            300
+        ),
+        None
+    );
+    assert_eq!(
+        count("SELECT amount FROM t WHERE amount < 50 AND other < 9", 300),
+        None
+    );
+}
+
+#[test]
+fn test_compiled_query_summary() {
+    // Test: The summary counts each operation kind with its total size, and
+    // Debug prints the summary instead of the operations
+    let data = table(
+        "t",
+        &[
+            ("region", (0..1000).map(|i| i % 50).collect()),
+            ("amount", (0..1000).map(|i| i % 7).collect()),
+        ],
+    );
+    let sql = "SELECT region, sum(amount) FROM t WHERE amount < 5 GROUP BY region";
+    let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    assert_eq!(
+        compiled.summary(),
+        "1000 range checks, 1 sort (1000 elems), 1 group-by (50 keys), 0 joins, 1 aggregation (sum)"
+    );
+    assert_eq!(
+        format!("{:?}", compiled),
+        format!("CompiledQuery {{ {} }}", compiled.summary())
+    );
+
+    let sql = "SELECT amount FROM t WHERE amount * 2 < 5 ORDER BY amount";
+    let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    assert_eq!(
+        compiled.summary(),
+        "1000 range checks, 1 sort (1000 elems), 0 group-bys, 0 joins, 0 aggregations, 1000 products"
+    );
+}
+
+#[test]
+fn test_explain_lists_planned_operations() {
+    // Test: EXPLAIN lists each planned stage in order with its cost
+    let data = table(
+        "order",
+        &[
+            ("customer_id", (0..1000).map(|i| i % 12).collect()),
+            ("amount", (0..1000).map(|i| i * 3).collect()),
+        ],
+    );
+    let sql = "SELECT customer_id, sum(amount) FROM order WHERE amount > 1000 \
+               GROUP BY customer_id HAVING sum(amount) > 5000 ORDER BY customer_id";
+    let plan = SQLCompiler::explain(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    assert_eq!(
+        plan,
+        "SCAN order (1000 rows) → FILTER amount > 1000 (1000 range checks) \
+         → GROUP BY customer_id (1000 rows sorted) → AGGREGATE sum(amount) (1 aggregation) \
+         → HAVING sum(amount) > 5000 (12 group checks) → SORT customer_id ASC (1000 rows)"
+    );
+
+    let sql = "SELECT amount * 2 AS double FROM order WHERE amount > 5000 ORDER BY amount DESC";
+    let plan = SQLCompiler::explain(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    for stage in [
+        "SCAN order",
+        "COMPUTE (1000 products, 0 divisions)",
+        "FILTER amount > 5000 (match count over 1000 rows)",
+        "SORT amount DESC (1000 rows)",
+    ] {
+        assert!(plan.contains(stage), "{}", plan);
+    }
+
+    let missing = SQLParser::parse("SELECT amount FROM order ORDER BY price").unwrap();
+    assert!(SQLCompiler::explain(&missing, &data).is_err());
+}
+
+/// Circuit assigning a compiled query's range checks and recording their
+/// check cell values grouped by WHERE predicate
+#[derive(Clone)]
+struct PredicateChecks {
+    compiled: CompiledQuery,
+    checks: Rc<RefCell<Vec<Vec<Fr>>>>,
+}
+
+impl Circuit<Fr> for PredicateChecks {
+    type Config = (PoneglyphConfig, GateConfigs);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        PoneglyphConfig::configure_with_gates(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, gates): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let circuit = circuit_from(self.compiled.clone());
+        let cells = circuit.assign_range_checks(
+            &mut layouter,
+            &RangeCheckChip::new(gates.range_check),
+            None,
+            &ArithmeticCells::default(),
+            config.instance,
+        )?;
+
+        let mut checks = Vec::new();
+        for predicate in self.compiled.predicate_checks(&cells) {
+            let mut values = Vec::new();
+            for cell in predicate {
+                cell.value().map(|value| values.push(*value));
+            }
+            checks.push(values);
+        }
+        *self.checks.borrow_mut() = checks;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_where_predicate_check_cells() {
+    // Test: The check cells of each WHERE predicate hold its per-row
+    // outcome; a bounded predicate is one predicate with one cell per row
+    let data = table(
+        "t",
+        &[("amount", vec![1, 6, 3, 9]), ("price", vec![8, 2, 7, 10])],
+    );
+    let bits = |bits: &[u64]| bits.iter().map(|&bit| Fr::from(bit)).collect::<Vec<_>>();
+
+    for (sql, expected) in [
+        (
+            "SELECT amount FROM t WHERE amount < 5 OR price > 7",
+            vec![bits(&[1, 0, 1, 0]), bits(&[1, 0, 0, 1])],
+        ),
+        (
+            "SELECT amount FROM t WHERE amount > 2 AND amount < 7",
+            vec![bits(&[0, 1, 1, 0])],
+        ),
+    ] {
+        let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+        assert_eq!(compiled.where_predicates.len(), expected.len(), "{}", sql);
+
+        let circuit = PredicateChecks {
+            compiled,
+            checks: Rc::new(RefCell::new(Vec::new())),
+        };
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+        assert_eq!(*circuit.checks.borrow(), expected, "{}", sql);
+    }
+}
+
+#[test]
+fn test_boolean_column_predicates() {
+    // Test: column = true / NOT column (and their spellings) filter a
+    // boolean column with one light check per row, whose check cells hold
+    // the row outcome; a filtered COUNT counts the matching rows
+    let data = table(
+        "user",
+        &[("id", vec![1, 2, 3, 4, 5]), ("active", vec![1, 0, 1, 1, 0])],
+    );
+    let bits = |bits: &[u64]| vec![bits.iter().map(|&bit| Fr::from(bit)).collect::<Vec<_>>()];
+    let active = |value| {
+        Some(WhereClause::Bool {
+            column: "active".to_string(),
+            value,
+        })
+    };
+
+    for (sql, value, ids) in [
+        (
+            "SELECT id FROM user WHERE active = true",
+            true,
+            vec![1, 3, 4],
+        ),
+        ("SELECT id FROM user WHERE active", true, vec![1, 3, 4]),
+        ("SELECT id FROM user WHERE NOT active", false, vec![2, 5]),
+        (
+            "SELECT id FROM user WHERE active = false",
+            false,
+            vec![2, 5],
+        ),
+        (
+            "SELECT id FROM user WHERE NOT active = false",
+            true,
+            vec![1, 3, 4],
+        ),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert_eq!(query.where_clause, active(value), "{}", sql);
+        assert_eq!(
+            query.execute(&data).unwrap().column("id"),
+            Some(ids),
+            "{}",
+            sql
+        );
+
+        let compiled = SQLCompiler::compile(&query, &data).unwrap();
+        assert!(compiled
+            .range_checks
+            .iter()
+            .all(|op| op.comparison == Comparison::Bool));
+        let circuit = PredicateChecks {
+            compiled,
+            checks: Rc::new(RefCell::new(Vec::new())),
+        };
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+        let expected = if value {
+            [1, 0, 1, 1, 0]
+        } else {
+            [0, 1, 0, 0, 1]
+        };
+        assert_eq!(*circuit.checks.borrow(), bits(&expected), "{}", sql);
+    }
+
+    let query = SQLParser::parse("SELECT count(*) FROM user WHERE NOT active").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![2]));
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_boolean_predicate_rejects_non_boolean_values() {
+    // Test: A column value other than 0 or 1 is rejected when compiled, and
+    // fails the boolean constraint if witnessed anyway
+    let query = SQLParser::parse("SELECT id FROM user WHERE active = true").unwrap();
+    let data = table("user", &[("id", vec![1, 2]), ("active", vec![1, 2])]);
+    let err = SQLCompiler::compile(&query, &data).unwrap_err();
+    assert!(err.contains("Boolean column active"), "{}", err);
+
+    let data = table("user", &[("id", vec![1, 2]), ("active", vec![1, 0])]);
+    let mut compiled = SQLCompiler::compile(&query, &data).unwrap();
+    compiled.range_checks[1].value = Value::known(2);
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_is_null_predicates() {
+    // Test: IS NULL / IS NOT NULL (and their NOT spellings) check each row
+    // against NULL, whose check cells hold the row's is_null bit or its
+    // complement; a filtered COUNT counts the matching rows
+    let data = table(
+        "user",
+        &[
+            ("id", vec![1, 2, 3, 4, 5]),
+            ("email", vec![10, NULL, 30, NULL, 0]),
+        ],
+    );
+    let bits = |bits: &[u64]| vec![bits.iter().map(|&bit| Fr::from(bit)).collect::<Vec<_>>()];
+    let email = "email".to_string();
+
+    for (sql, clause, ids, expected) in [
+        (
+            "SELECT id FROM user WHERE email IS NULL",
+            WhereClause::IsNull {
+                column: email.clone(),
+            },
+            vec![2, 4],
+            [0, 1, 0, 1, 0],
+        ),
+        (
+            "SELECT id FROM user WHERE email IS NOT NULL",
+            WhereClause::IsNotNull {
+                column: email.clone(),
+            },
+            vec![1, 3, 5],
+            [1, 0, 1, 0, 1],
+        ),
+        (
+            "SELECT id FROM user WHERE NOT email IS NOT NULL",
+            WhereClause::IsNull {
+                column: email.clone(),
+            },
+            vec![2, 4],
+            [0, 1, 0, 1, 0],
+        ),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert_eq!(query.where_clause, Some(clause), "{}", sql);
+        assert_eq!(
+            query.execute(&data).unwrap().column("id"),
+            Some(ids),
+            "{}",
+            sql
+        );
+
+        let circuit = PredicateChecks {
+            compiled: SQLCompiler::compile(&query, &data).unwrap(),
+            checks: Rc::new(RefCell::new(Vec::new())),
+        };
+        let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+        assert_eq!(*circuit.checks.borrow(), bits(&expected), "{}", sql);
+    }
+
+    for (sql, count) in [
+        ("SELECT count(*) FROM user WHERE email IS NULL", 2),
+        ("SELECT count(*) FROM user WHERE email IS NOT NULL", 3),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile(&query, &data).unwrap();
+        assert_eq!(compiled.aggregate_results(0), Some(vec![count]), "{}", sql);
+        let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+}
+
+#[test]
+fn test_arg_max_per_group_proves() {
+    // Test: SELECT customer_id, max(amount), id ... GROUP BY customer_id
+    // returns the id of each customer's max order, proven by the payload of
+    // the MAX; another ungrouped column or aggregation is still rejected
+    let data = table(
+        "order",
+        &[
+            ("customer_id", vec![2, 1, 2, 1, 3, 2]),
+            ("amount", vec![50, 20, 80, 70, 5, 80]),
+            ("id", vec![11, 12, 13, 14, 15, 16]),
+        ],
+    );
+    let query =
+        SQLParser::parse("SELECT customer_id, max(amount), id FROM order GROUP BY customer_id")
+            .unwrap();
+    assert_eq!(query.arg_extremum().map(|(_, column)| column), Some("id"));
+
+    // Customer 2's max 80 is reached twice: the last row (id 16) is taken
+    let result = query.execute(&data).unwrap();
+    assert_eq!(
+        result.rows,
+        vec![vec![1, 70, 14], vec![2, 80, 16], vec![3, 5, 15]]
+    );
+
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregations[0].group_results(), vec![70, 80, 5]);
+    assert_eq!(
+        compiled.aggregations[0].group_payloads(),
+        Some(vec![14, 16, 15])
+    );
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let query = SQLParser::parse("SELECT min(amount), id FROM order").unwrap();
+    assert_eq!(query.execute(&data).unwrap().rows, vec![vec![5, 15]]);
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregations[0].group_payloads(), Some(vec![15]));
+
+    for sql in [
+        "SELECT customer_id, sum(amount), id FROM order GROUP BY customer_id",
+        "SELECT customer_id, max(amount), count(*), id FROM order GROUP BY customer_id",
+        "SELECT max(amount), id, customer_id FROM order",
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        assert!(query.arg_extremum().is_none(), "{}", sql);
+        assert!(SQLCompiler::compile(&query, &data).is_err(), "{}", sql);
+    }
+}
+
+#[test]
+fn test_filtered_count_respects_where() {
+    // Test: count(*) with WHERE sums the WHERE check bits, so the proven
+    // count is the number of matching rows, not of all rows; values not
+    // copied from the check cells fail
+    let data = table(
+        "order",
+        &[
+            ("region", vec![1, 2, 1, 2, 1]),
+            ("amount", vec![500, 1500, 2000, 10, 1200]),
+        ],
+    );
+
+    let query = SQLParser::parse("SELECT count(*) FROM order WHERE amount > 1000").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![3]));
+    assert_eq!(query.execute(&data).unwrap().rows, vec![vec![3]]);
+
+    let circuit = circuit_from(compiled);
+    let prover = MockProver::run(10, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Every row counted: the total, but not the WHERE outcome
+    let mut forged = circuit.clone();
+    forged.aggregations[0].values = vec![1; 5];
+    let prover = MockProver::run(10, &forged, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Per group, in group order
+    let query =
+        SQLParser::parse("SELECT region, count(*) FROM order WHERE amount > 1000 GROUP BY region")
+            .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![2, 1]));
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // An equality check is exact (x = t, not x <= t)
+    let query = SQLParser::parse("SELECT count(*) FROM order WHERE amount = 10").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![1]));
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A column equality is exact as well
+    let query = SQLParser::parse("SELECT count(*) FROM order WHERE amount = region").unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.aggregate_results(0), Some(vec![0]));
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_filtered_sum_respects_where() {
+    // Test: sum / avg with WHERE aggregate value · bit, each bit copied from
+    // its WHERE check cell, so the proven result is that of the matching
+    // rows; unfiltered values fail, and MAX / MIN with WHERE are rejected
+    let data = table(
+        "order",
+        &[
+            ("customer_id", vec![1, 2, 1, 3, 1]),
+            ("amount", vec![7, 100, 20, 16, 0]),
+        ],
+    );
+
+    for (sql, expected) in [
+        ("SELECT sum(amount) FROM order WHERE customer_id = 1", 27),
+        ("SELECT avg(amount) FROM order WHERE customer_id = 1", 9),
+        ("SELECT count(amount) FROM order WHERE amount < 10", 2),
+    ] {
+        let query = SQLParser::parse(sql).unwrap();
+        let compiled = SQLCompiler::compile(&query, &data).unwrap();
+        assert_eq!(
+            compiled.aggregate_results(0),
+            Some(vec![expected]),
+            "{}",
+            sql
+        );
+        assert_eq!(
+            query.execute(&data).unwrap().rows,
+            vec![vec![expected]],
+            "{}",
+            sql
+        );
+
+        let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+
+    // Every row summed: the values match the unfiltered values, not the bits
+    let query = SQLParser::parse("SELECT sum(amount) FROM order WHERE customer_id = 1").unwrap();
+    let mut forged = circuit_from(SQLCompiler::compile(&query, &data).unwrap());
+    forged.aggregations[0].values = data["order"]["amount"].clone();
+    let prover = MockProver::run(10, &forged, vec![vec![], vec![]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    for sql in [
+        "SELECT max(amount) FROM order WHERE amount < 50",
+        "SELECT min(amount) FROM order WHERE customer_id = 1",
+    ] {
+        let err = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap_err();
+        assert!(err.contains("with WHERE is not supported"), "{}", err);
+    }
+}
+
+#[test]
+fn test_timestamp_literals() {
+    // Test: ISO-8601 dates and UTC date-times encode as seconds since the
+    // Unix epoch and render back; malformed or impossible dates are rejected
+    assert_eq!(parse_timestamp("1970-01-01"), Ok(0));
+    assert_eq!(parse_timestamp("2024-01-01"), Ok(1_704_067_200));
+    assert_eq!(parse_timestamp("2024-03-01T12:30:05Z"), Ok(1_709_296_205));
+    assert_eq!(parse_timestamp("2024-03-01 12:30:05"), Ok(1_709_296_205));
+    assert_eq!(format_timestamp(1_709_296_205), "2024-03-01T12:30:05Z");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+
+    for malformed in [
+        "2023-02-29",
+        "2024-13-01",
+        "2024-01-32",
+        "1969-12-31",
+        "24-01-01",
+        "2024-1-01",
+        "2024-01-01T24:00:00",
+        "yesterday",
+    ] {
+        assert!(parse_timestamp(malformed).is_err(), "{}", malformed);
+        let sql = format!("SELECT id FROM events WHERE ts > '{}'", malformed);
+        let err = SQLParser::parse(&sql).unwrap_err();
+        assert!(err.contains("Invalid date"), "{}", err);
+    }
+
+    let typed = TypedValue::from_raw(1_704_067_200, ColumnType::Timestamp);
+    assert_eq!(typed.to_json(), "\"2024-01-01T00:00:00Z\"");
+}
+
+#[test]
+fn test_where_timestamp_range_proves() {
+    // Test: BETWEEN with date literals is an inclusive bounded range check on
+    // the timestamp column; the proven count and the executed rows match a
+    // plaintext filter over the same values
+    let timestamps: Vec<u64> = [
+        "2023-12-31T23:59:59",
+        "2024-01-01",
+        "2024-01-15T08:00:00",
+        "2024-01-31T23:59:59",
+        "2024-02-01",
+        "2024-02-01T00:00:01",
+        "2024-03-10T17:45:00",
+    ]
+    .iter()
+    .map(|text| parse_timestamp(text).unwrap())
+    .collect();
+    let data = table(
+        "events",
+        &[("id", (1..=7).collect()), ("ts", timestamps.clone())],
+    );
+    let (start, end) = (
+        parse_timestamp("2024-01-01").unwrap(),
+        parse_timestamp("2024-02-01").unwrap(),
+    );
+    let expected: Vec<u64> = (1..=7)
+        .zip(&timestamps)
+        .filter(|&(_, &ts)| start <= ts && ts <= end)
+        .map(|(id, _)| id)
+        .collect();
+    assert_eq!(expected, vec![2, 3, 4, 5]);
+
+    let sql = "SELECT id FROM events WHERE ts BETWEEN '2024-01-01' AND '2024-02-01'";
+    let query = SQLParser::parse(sql).unwrap();
+    assert_eq!(
+        query.execute(&data).unwrap().column("id").unwrap(),
+        expected
+    );
+
+    let query = SQLParser::parse(
+        "SELECT count(*) FROM events WHERE ts BETWEEN '2024-01-01' AND '2024-02-01'",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    assert_eq!(compiled.range_checks.len(), 7);
+    assert_eq!(
+        compiled.aggregate_results(0),
+        Some(vec![expected.len() as u64])
+    );
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The same range combined with another predicate, and written as < / >
+    let query = SQLParser::parse(
+        "SELECT id FROM events WHERE ts BETWEEN '2024-01-01' AND '2024-02-01' AND id > 2",
+    )
+    .unwrap();
+    assert_eq!(
+        query.execute(&data).unwrap().column("id").unwrap(),
+        vec![3, 4, 5]
+    );
+    let query = SQLParser::parse(
+        "SELECT id FROM events WHERE ts > '2024-01-15' AND ts < '2024-02-01T00:00:01Z'",
+    )
+    .unwrap();
+    assert_eq!(
+        query.execute(&data).unwrap().column("id").unwrap(),
+        vec![3, 4, 5]
+    );
+    let compiled = SQLCompiler::compile(&query, &data).unwrap();
+    let prover = MockProver::run(10, &circuit_from(compiled), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_merge_where_and_aggregation_plans() {
+    // Test: A range-check-only plan merged with an aggregation-only plan keeps
+    // both plans' operations in order, with shifted indices, and proves
+    let data = order_table();
+    let where_plan = SQLCompiler::compile(
+        &SQLParser::parse("SELECT amount FROM order WHERE amount < 50").unwrap(),
+        &data,
+    )
+    .unwrap();
+    let aggregation_plan = SQLCompiler::compile(
+        &SQLParser::parse("SELECT customer_id, sum(amount) FROM order GROUP BY customer_id")
+            .unwrap(),
+        &data,
+    )
+    .unwrap();
+    assert!(aggregation_plan.range_checks.is_empty());
+    let where_checks = where_plan.range_checks.len();
+    let where_predicates = where_plan.where_predicates.clone();
+
+    let merged = where_plan.merge(aggregation_plan.clone());
+    assert_eq!(merged.range_checks.len(), where_checks);
+    assert_eq!(merged.where_predicates, where_predicates);
+    assert_eq!(merged.sorts.len(), aggregation_plan.sorts.len());
+    assert_eq!(merged.group_bys.len(), 1);
+    assert_eq!(merged.aggregate_specs, aggregation_plan.aggregate_specs);
+    assert_eq!(merged.aggregate_results(0), Some(vec![30, 10, 100]));
+
+    let prover = MockProver::run(11, &circuit_from(merged), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A second WHERE plan's predicates are shifted past the first's checks
+    let compile = |sql: &str| SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), &data).unwrap();
+    let first = compile("SELECT amount FROM order WHERE amount < 50");
+    let second = compile("SELECT amount FROM order WHERE customer_id > 1");
+    let offset = first.range_checks.len();
+    let shifted: Vec<_> = second
+        .where_predicates
+        .iter()
+        .map(|range| range.start + offset..range.end + offset)
+        .collect();
+    let merged = first.merge(second);
+    assert_eq!(merged.where_predicates[1..], shifted[..]);
+    let prover = MockProver::run(11, &circuit_from(merged), vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
@@ -0,0 +1,445 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    circuit::Value, dev::MockProver, pasta::EqAffine, plonk::Error, poly::commitment::Params,
+};
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::prover::{layout_report, Prover, ProvingContext, Verifier};
+use poneglyphdb::sql::{QueryError, SQLCompiler, SQLParser};
+
+/// Circuit with the given operations (public inputs are not bound)
+fn circuit(sorts: Vec<SortOp>, aggregations: Vec<AggregationOp>) -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        sorts,
+        aggregations,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_stats_current_config() {
+    // Test: Column and lookup counts of the current configuration
+    let stats = circuit(Vec::new(), Vec::new()).stats().unwrap();
+
+    assert_eq!(stats.num_advice, 15);
+    assert_eq!(stats.num_instance, 2);
+    // fixed[0], fixed[1], the lookup table column, the Poseidon round constants
+    // and the Set Membership Gate's (tag, lower, upper, member) tables
+    assert_eq!(stats.num_fixed, 8);
+    assert!(stats.num_selectors > 0);
+    assert!(stats.num_lookups >= 2);
+
+    // No operation needs the lookup table, so no rows are assigned
+    assert_eq!(stats.used_rows, 0);
+    assert!(stats.estimated_rows > stats.used_rows);
+}
+
+#[test]
+fn test_stats_min_k_fits_circuit() {
+    // Test: The circuit proves at min_k and runs out of rows one k below
+    let input: Vec<u64> = (0..400).rev().collect();
+    let mut sorted = input.clone();
+    sorted.sort();
+    let sort = SortOp {
+        input: input.iter().map(|&v| Value::known(v)).collect(),
+        sorted_output: sorted.clone(),
+        null_flags: Vec::new(),
+        nulls_first: false,
+        descending: false,
+        bitonic: false,
+        aggregation: None,
+        column: None,
+        rows: None,
+        payloads: Vec::new(),
+    };
+    let aggregation = AggregationOp {
+        group_keys: sorted.iter().map(|v| v / 100).collect(),
+        values: sorted,
+        function: AggregationFunction::Sum,
+        value_checks: None,
+        value_semi_join: None,
+        unfiltered: None,
+        column: None,
+        expected: None,
+        payload: None,
+        group_sort: None,
+        sort_payload: None,
+    };
+    let circuit = circuit(vec![sort], vec![aggregation]);
+
+    let stats = circuit.stats().unwrap();
+    assert!(stats.used_rows > 256);
+    assert!(1usize << stats.min_k >= stats.estimated_rows);
+
+    let prover = MockProver::run(stats.min_k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    assert!(MockProver::run(stats.min_k - 1, &circuit, vec![vec![], vec![]]).is_err());
+}
+
+#[test]
+fn test_estimate_k_leaves_room_for_blinding() {
+    // Test: A circuit using exactly 2^8 rows (the lookup table) needs k = 9,
+    // since the blinding rows do not fit in the 2^8 budget
+    let input: Vec<u64> = vec![3, 1, 2];
+    let sort = SortOp {
+        input: input.iter().map(|&v| Value::known(v)).collect(),
+        sorted_output: vec![1, 2, 3],
+        null_flags: Vec::new(),
+        nulls_first: false,
+        descending: false,
+        bitonic: false,
+        aggregation: None,
+        column: None,
+        rows: None,
+        payloads: Vec::new(),
+    };
+    let circuit = circuit(vec![sort], Vec::new());
+
+    let stats = circuit.stats().unwrap();
+    assert_eq!(stats.used_rows, 1 << 8);
+    assert!(stats.blinding_rows > 0);
+    assert_eq!(stats.estimate_k(0), 9);
+    assert_eq!(stats.min_k, stats.estimate_k(0));
+
+    assert!(MockProver::run(8, &circuit, vec![vec![], vec![]]).is_err());
+    let prover = MockProver::run(9, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The margin comes out of the same budget
+    let room = (1 << 9) - stats.estimated_rows;
+    assert_eq!(stats.estimate_k(room), 9);
+    assert_eq!(stats.estimate_k(room + 1), 10);
+}
+
+#[test]
+fn test_layout_report_names_failing_sort() {
+    // Test: A sort whose output is out of order is reported as `sorts[1]`,
+    // not as the range check or the valid sort laid out before it
+    let sort = |input: Vec<u64>, sorted_output: Vec<u64>| SortOp {
+        input: input.iter().map(|&v| Value::known(v)).collect(),
+        sorted_output,
+        null_flags: Vec::new(),
+        nulls_first: false,
+        descending: false,
+        bitonic: false,
+        aggregation: None,
+        column: None,
+        rows: None,
+        payloads: Vec::new(),
+    };
+    let mut circuit = circuit(
+        vec![
+            sort(vec![2, 1, 3], vec![1, 2, 3]),
+            sort(vec![3, 1, 2], vec![1, 3, 2]),
+        ],
+        Vec::new(),
+    );
+    circuit.range_checks = vec![RangeCheckOp {
+        value: Value::known(7),
+        threshold: 10,
+        u: 1 << 16,
+        witnessed_threshold: false,
+        source: None,
+        threshold_source: None,
+        public_operand: None,
+        lower: None,
+        comparison: Comparison::Lt,
+        expression: None,
+        member_of: None,
+    }];
+
+    let report = layout_report(&circuit, &[vec![], vec![]], 10).unwrap_err();
+    assert!(report.lines().any(|line| line.starts_with("sorts[1] / ")));
+    // The out-of-order diff is negative: its decomposition fails
+    assert!(report.contains("decompose diffs"));
+    assert!(!report.contains("sorts[0]"));
+    assert!(!report.contains("range_checks[0]"));
+
+    circuit.sorts.pop();
+    assert_eq!(layout_report(&circuit, &[vec![], vec![]], 10), Ok(()));
+}
+
+/// Join-free operation mix: product, witnessed threshold, sort and aggregation
+fn join_free_circuit() -> PoneglyphCircuit {
+    let input: Vec<u64> = vec![5, 3, 9, 1];
+    let mut sorted = input.clone();
+    sorted.sort();
+    let mut circuit = circuit(
+        vec![SortOp {
+            input: input.iter().map(|&v| Value::known(v)).collect(),
+            sorted_output: sorted.clone(),
+            null_flags: Vec::new(),
+            nulls_first: false,
+            descending: false,
+            bitonic: false,
+            aggregation: None,
+            column: None,
+            rows: None,
+            payloads: Vec::new(),
+        }],
+        vec![AggregationOp {
+            group_keys: vec![0, 0, 1, 1],
+            values: sorted,
+            function: AggregationFunction::Sum,
+            value_checks: None,
+            value_semi_join: None,
+            unfiltered: None,
+            column: None,
+            expected: None,
+            payload: None,
+            group_sort: None,
+            sort_payload: None,
+        }],
+    );
+    circuit.products = vec![ProductOp {
+        a: Value::known(6),
+        b: Value::known(7),
+        operands: Default::default(),
+    }];
+    circuit.range_checks = vec![RangeCheckOp {
+        value: Value::known(42),
+        threshold: 100,
+        u: 1 << 16,
+        witnessed_threshold: true,
+        source: None,
+        threshold_source: None,
+        public_operand: None,
+        lower: None,
+        comparison: Comparison::Lt,
+        expression: None,
+        member_of: None,
+    }];
+    circuit
+}
+
+#[test]
+fn test_join_free_config_proves_with_10_advice_columns() {
+    // Test: A circuit without joins proves with the 10-column config
+    let circuit = join_free_circuit();
+    assert!(!circuit.features().join);
+
+    let full = circuit.stats().unwrap();
+    let join_free = JoinFreeCircuit(circuit);
+    let stats = join_free.stats().unwrap();
+    assert_eq!(full.num_advice, 15);
+    assert_eq!(stats.num_advice, 10);
+    assert!(stats.num_selectors < full.num_selectors);
+
+    let prover = MockProver::run(stats.min_k, &join_free, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Real proof with the smaller proving key
+    let params = Params::<EqAffine>::new(stats.min_k);
+    let proof = Prover::new(&params, &join_free)
+        .and_then(|prover| prover.prove(&params, &join_free, &[vec![], vec![]]))
+        .unwrap();
+    let verifier = Verifier::new(&params, &join_free).unwrap();
+    assert!(verifier.verify(&params, &proof, &[vec![], vec![]]).unwrap());
+}
+
+#[test]
+fn test_proving_context_reused_for_same_shape() {
+    // Test: One context proves and verifies several circuits of the same shape
+    let aggregation = |values: Vec<u64>| AggregationOp {
+        group_keys: vec![0, 0, 1, 1],
+        values,
+        function: AggregationFunction::Sum,
+        value_checks: None,
+        value_semi_join: None,
+        unfiltered: None,
+        column: None,
+        expected: None,
+        payload: None,
+        group_sort: None,
+        sort_payload: None,
+    };
+    let first = circuit(Vec::new(), vec![aggregation(vec![1, 2, 3, 4])]);
+    let context = ProvingContext::for_circuit(&first).unwrap();
+    assert_eq!(context.k(), first.stats().unwrap().min_k);
+
+    for values in [vec![1, 2, 3, 4], vec![7, 0, 9, 12], vec![5, 5, 5, 5]] {
+        let circuit = circuit(Vec::new(), vec![aggregation(values)]);
+        let proof = context.prove(&circuit, &[vec![], vec![]]).unwrap();
+        assert!(context.verify(&proof, &[vec![], vec![]]).unwrap());
+    }
+
+    // A proof does not verify against different public inputs
+    let proof = context.prove(&first, &[vec![], vec![]]).unwrap();
+    assert!(context
+        .verify(&proof, &[vec![], vec![Fr::from(1)]])
+        .is_err());
+}
+
+#[test]
+fn test_mismatched_instance_columns_rejected() {
+    // Test: Public inputs with one or three instance columns are reported as
+    // InstanceShape and rejected by prove / verify; two columns prove
+    let aggregation = AggregationOp {
+        group_keys: vec![0, 0, 1, 1],
+        values: vec![1, 2, 3, 4],
+        function: AggregationFunction::Sum,
+        value_checks: None,
+        value_semi_join: None,
+        unfiltered: None,
+        column: None,
+        expected: None,
+        payload: None,
+        group_sort: None,
+        sort_payload: None,
+    };
+    let circuit = circuit(Vec::new(), vec![aggregation]);
+    let k = circuit.stats().unwrap().min_k;
+    let params = Params::<EqAffine>::new(k);
+    let prover = Prover::new(&params, &circuit).unwrap();
+    let verifier = Verifier::new(&params, &circuit).unwrap();
+
+    let proof = prover.prove(&params, &circuit, &[vec![], vec![]]).unwrap();
+    assert_eq!(prover.check_instances(&[vec![], vec![]]), Ok(()));
+    assert!(verifier.verify(&params, &proof, &[vec![], vec![]]).unwrap());
+
+    for public_inputs in [vec![vec![]], vec![vec![], vec![], vec![]]] {
+        let shape = QueryError::InstanceShape {
+            expected: PublicInputs::COLUMNS,
+            found: public_inputs.len(),
+        };
+        assert_eq!(prover.check_instances(&public_inputs), Err(shape.clone()));
+        assert_eq!(verifier.check_instances(&public_inputs), Err(shape));
+        assert!(matches!(
+            prover.prove(&params, &circuit, &public_inputs),
+            Err(Error::InvalidInstances)
+        ));
+        assert!(matches!(
+            verifier.verify(&params, &proof, &public_inputs),
+            Err(Error::InvalidInstances)
+        ));
+    }
+    assert_eq!(
+        QueryError::InstanceShape {
+            expected: 2,
+            found: 1
+        }
+        .to_string(),
+        "Public inputs have 1 instance columns, the circuit declares 2"
+    );
+}
+
+#[test]
+fn test_join_free_config_rejects_joins() {
+    // Test: Join operations cannot be synthesized without the Join Gate
+    let mut circuit = circuit(Vec::new(), Vec::new());
+    circuit.joins = vec![JoinOp {
+        table1_keys: vec![1, 2],
+        table1_values: vec![10, 20],
+        table2_keys: vec![2, 3],
+        table2_values: vec![200, 300],
+        columns: None,
+    }];
+    assert!(circuit.features().join);
+
+    let join_free = JoinFreeCircuit(circuit);
+    assert!(matches!(join_free.stats(), Err(Error::Synthesis)));
+}
+
+#[test]
+fn test_unused_ops_skip_lookup_table() {
+    // Test: Only the chips and lookup table the operations need are synthesized
+    let mut range_check_only = circuit(Vec::new(), Vec::new());
+    range_check_only.range_checks = vec![RangeCheckOp {
+        value: Value::known(42),
+        threshold: 100,
+        u: 1 << 16,
+        witnessed_threshold: false,
+        source: None,
+        threshold_source: None,
+        public_operand: None,
+        lower: None,
+        comparison: Comparison::Lt,
+        expression: None,
+        member_of: None,
+    }];
+    let capabilities = range_check_only.capabilities();
+    assert!(capabilities.range_check && capabilities.lookup_table);
+    assert!(!capabilities.sort && !capabilities.aggregation && !capabilities.join);
+
+    let mut product_only = circuit(Vec::new(), Vec::new());
+    product_only.products = vec![ProductOp {
+        a: Value::known(6),
+        b: Value::known(7),
+        operands: Default::default(),
+    }];
+    assert!(!product_only.capabilities().lookup_table);
+
+    // The range check loads the 256-row table, the product (no chunk
+    // lookups) only assigns its own rows
+    let range_check_rows = range_check_only.stats().unwrap().used_rows;
+    let product_rows = product_only.stats().unwrap().used_rows;
+    assert!(range_check_rows >= 256);
+    assert!(product_rows < 256);
+
+    // Without the table loaded, disabled lookup rows still pass (0 is in
+    // the all-zero table column)
+    for circuit in [&range_check_only, &product_only] {
+        let k = circuit.stats().unwrap().min_k;
+        let prover = MockProver::run(k, circuit, vec![vec![], vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    let context = ProvingContext::for_circuit(&product_only).unwrap();
+    assert!(context.k() < range_check_only.stats().unwrap().min_k);
+    let proof = context.prove(&product_only, &[vec![], vec![]]).unwrap();
+    assert!(context.verify(&proof, &[vec![], vec![]]).unwrap());
+}
+
+#[test]
+fn test_floor_planner_v1_fits_smaller_k() {
+    // Test: The GROUP BY sort, grouping and aggregation regions use disjoint
+    // columns; V1 packs them side by side, so the query fits one k below
+    // SimpleFloorPlanner's layout. `floor_planner` detects it, and the
+    // V1Circuit proves at that k while the PoneglyphCircuit runs out of rows
+    let order: HashMap<String, Vec<u64>> = [
+        ("customer_id".to_string(), (0..20).map(|i| i % 7).collect()),
+        ("amount".to_string(), (0..20).map(|i| 100 + i * 7).collect()),
+    ]
+    .into();
+    let tables = HashMap::from([("order".to_string(), order)]);
+    let query = SQLParser::parse(
+        "SELECT customer_id, sum(amount), min(amount), max(amount) FROM order GROUP BY customer_id",
+    )
+    .unwrap();
+    let compiled = SQLCompiler::compile(&query, &tables).unwrap();
+    let mut grouped = circuit(compiled.sorts, compiled.aggregations);
+    grouped.group_bys = compiled.group_bys;
+
+    let simple = grouped.stats().unwrap();
+    let (planner, stats) = grouped.floor_planner().unwrap();
+    assert_eq!(planner, FloorPlannerKind::V1);
+    assert!(stats.used_rows < simple.used_rows);
+    assert_eq!(stats.min_k, simple.min_k - 1);
+    assert_eq!(V1Circuit(grouped.clone()).stats().unwrap(), stats);
+
+    let v1 = V1Circuit(grouped.clone());
+    let prover = MockProver::run(stats.min_k, &v1, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    assert!(MockProver::run(stats.min_k, &grouped, vec![vec![], vec![]]).is_err());
+
+    // A single sort has nothing to pack: SimpleFloorPlanner is kept
+    let input: Vec<u64> = (0..100).rev().collect();
+    let sort = SortOp {
+        input: input.iter().map(|&v| Value::known(v)).collect(),
+        sorted_output: (0..100).collect(),
+        null_flags: Vec::new(),
+        nulls_first: false,
+        descending: false,
+        bitonic: false,
+        aggregation: None,
+        column: None,
+        rows: None,
+        payloads: Vec::new(),
+    };
+    let (planner, stats) = circuit(vec![sort], Vec::new()).floor_planner().unwrap();
+    assert_eq!(planner, FloorPlannerKind::Simple);
+    assert_eq!(stats.min_k, 11);
+}
@@ -0,0 +1,147 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::{dev::MockProver, pasta::EqAffine, poly::commitment::Params};
+use poneglyphdb::circuit::*;
+use poneglyphdb::recursive::{ChunkProof, StreamingProver};
+use poneglyphdb::sql::*;
+
+const SUM_BY_REGION: &str = "SELECT region, sum(amount) FROM lineitem GROUP BY region";
+
+/// Lineitem rows (region, amount): regions of 100 consecutive rows each
+fn lineitem_rows(rows: usize) -> Vec<Vec<u64>> {
+    (0..rows)
+        .map(|i| vec![(i / 100) as u64, (i % 17 + 1) as u64])
+        .collect()
+}
+
+/// Same rows in the compiler's column-major shape
+fn lineitem_table(rows: &[Vec<u64>]) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("region".to_string(), rows.iter().map(|r| r[0]).collect());
+    columns.insert("amount".to_string(), rows.iter().map(|r| r[1]).collect());
+
+    let mut table_data = HashMap::new();
+    table_data.insert("lineitem".to_string(), columns);
+    table_data
+}
+
+fn streaming_prover() -> StreamingProver {
+    StreamingProver::new(vec!["region".to_string(), "amount".to_string()])
+}
+
+#[test]
+fn test_chunked_sum_matches_single_shot() {
+    // Test: SUM over 300 rows proven in 64-row chunks equals the single-shot SUM
+    let rows = lineitem_rows(300);
+
+    // Single-shot proof over all rows
+    let query = SQLParser::parse(SUM_BY_REGION).unwrap();
+    let compiled = SQLCompiler::compile(&query, &lineitem_table(&rows)).unwrap();
+    let single_shot = compiled.aggregations[0].partial();
+    let circuit = PoneglyphCircuit::from_compiled(compiled, None, None);
+    let k = circuit.stats().unwrap().min_k;
+    let prover = MockProver::run(k, &circuit, vec![vec![], vec![]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Chunked proof: 4 full chunks and a 44-row tail; every region spans a
+    // chunk boundary. Each chunk's rows are committed in the circuit and its
+    // partials bound to its query result
+    let params = Params::<EqAffine>::new(14);
+    let chunked = streaming_prover()
+        .prove_query_chunked(&params, SUM_BY_REGION, rows, 64)
+        .unwrap();
+
+    assert_eq!(chunked.rows, 300);
+    assert_eq!(chunked.chunks.len(), 5);
+    assert!(chunked.chunks.iter().all(|chunk| !chunk.proof.is_empty()));
+    // Chunks of one or two regions share keys: one per layout, not per chunk
+    assert!(chunked.verifiers.len() < chunked.chunks.len());
+    assert!(chunked.verify(&params).unwrap());
+    assert_eq!(chunked.aggregations, vec![single_shot]);
+    assert_eq!(
+        chunked.aggregations[0].group_keys,
+        (0..3).collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn test_chunked_rejects_tampered_partial() {
+    // Test: A chunk partial other than the one its proof binds fails
+    // verification, with or without a query result to match it
+    let params = Params::<EqAffine>::new(13);
+    let chunked = streaming_prover()
+        .prove_query_chunked(&params, SUM_BY_REGION, lineitem_rows(32), 16)
+        .unwrap();
+    assert_eq!(chunked.chunks.len(), 2);
+    assert!(chunked.verify(&params).unwrap());
+
+    // A larger SUM in the second chunk, combined into a larger total
+    let mut tampered = chunked.clone();
+    tampered.chunks[1].partials[0].results[0] += 1;
+    tampered.aggregations[0].results[0] += 1;
+    assert!(!tampered.verify(&params).unwrap());
+
+    // Claiming the tampered partials' commitment as the query result does
+    // not match the proof
+    let chunk = &mut tampered.chunks[1];
+    let commitment = ChunkProof::commitment(&chunk.partials, chunk.matched, None);
+    chunk.public_inputs.query_result = Some(commitment.value());
+    assert!(!tampered.verify(&params).unwrap());
+
+    // Partials combining into another total are rejected too
+    let mut total = chunked.clone();
+    total.aggregations[0].results[0] += 1;
+    assert!(!total.verify(&params).unwrap());
+}
+
+#[test]
+fn test_combine_partials_merges_boundary_group() {
+    // Test: A group spanning the chunk boundary is merged, MAX keeps the larger value
+    let mut first = AggregationPartial {
+        function: AggregationFunction::Max,
+        group_keys: vec![1, 2],
+        results: vec![10, 7],
+    };
+    let next = AggregationPartial {
+        function: AggregationFunction::Max,
+        group_keys: vec![2, 3],
+        results: vec![9, 4],
+    };
+    first.combine(&next).unwrap();
+
+    assert_eq!(first.group_keys, vec![1, 2, 3]);
+    assert_eq!(first.results, vec![10, 9, 4]);
+}
+
+#[test]
+fn test_combine_partials_sum_overflow_rejected() {
+    // Test: A SUM merged across the chunk boundary above u64::MAX is an
+    // error, not a wrapped result
+    let mut first = AggregationPartial {
+        function: AggregationFunction::Sum,
+        group_keys: vec![1],
+        results: vec![u64::MAX],
+    };
+    let next = AggregationPartial {
+        function: AggregationFunction::Sum,
+        group_keys: vec![1],
+        results: vec![1],
+    };
+    let err = first.combine(&next).err().unwrap();
+    assert!(err.contains("exceeds u64::MAX"), "{}", err);
+}
+
+#[test]
+fn test_chunked_rejects_order_by() {
+    // Test: ORDER BY needs the whole table and is rejected
+    let params = Params::<EqAffine>::new(4);
+    let result = streaming_prover().prove_query_chunked(
+        &params,
+        "SELECT region, amount FROM lineitem ORDER BY amount ASC",
+        lineitem_rows(10),
+        4,
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,145 @@
+// Self-tests of the query correctness harness over the four TPCH query
+// shapes (see `benches/tpch_benchmark.rs`)
+use std::collections::HashMap;
+
+use poneglyphdb::sql::QueryResult;
+use poneglyphdb::testing::{assert_query_correct, assert_query_result};
+
+const K: u32 = 12;
+
+/// Customer (id, name, age) and order (id, customer_id, amount) tables
+fn tpch_tables() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut customer = HashMap::new();
+    customer.insert("id".to_string(), vec![1, 2, 3, 4]);
+    customer.insert("name".to_string(), vec![1000, 2000, 3000, 4000]);
+    customer.insert("age".to_string(), vec![25, 61, 42, 70]);
+
+    let mut order = HashMap::new();
+    order.insert("id".to_string(), vec![1, 2, 3, 4, 5, 6]);
+    order.insert("customer_id".to_string(), vec![2, 1, 3, 1, 2, 4]);
+    order.insert("amount".to_string(), vec![50, 10, 100, 20, 5, 70]);
+
+    let mut table_data = HashMap::new();
+    table_data.insert("customer".to_string(), customer);
+    table_data.insert("order".to_string(), order);
+    table_data
+}
+
+#[test]
+fn test_assert_query_correct_where() {
+    // Test: Query 1 (SELECT with WHERE), and an empty WHERE bound by its match count
+    let result = assert_query_correct(
+        "SELECT id, name FROM customer WHERE age < 50",
+        &tpch_tables(),
+        K,
+    );
+    assert_eq!(result.rows, vec![vec![1, 1000], vec![3, 3000]]);
+
+    let result = assert_query_correct(
+        "SELECT id, name FROM customer WHERE age < 18",
+        &tpch_tables(),
+        K,
+    );
+    assert!(result.rows.is_empty());
+}
+
+#[test]
+fn test_assert_query_correct_order_by() {
+    // Test: Query 2 (SELECT with ORDER BY)
+    let result = assert_query_correct(
+        "SELECT id, amount FROM order ORDER BY amount ASC",
+        &tpch_tables(),
+        K,
+    );
+    assert_eq!(
+        result.column("amount").unwrap(),
+        vec![5, 10, 20, 50, 70, 100]
+    );
+}
+
+#[test]
+fn test_assert_query_correct_group_by() {
+    // Test: Query 3 (GROUP BY with aggregation)
+    let result = assert_query_correct(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id",
+        &tpch_tables(),
+        K,
+    );
+    assert_eq!(
+        result.rows,
+        vec![vec![1, 30], vec![2, 55], vec![3, 100], vec![4, 70]]
+    );
+}
+
+#[test]
+fn test_assert_query_correct_join() {
+    // Test: Query 4 (JOIN), in the comma-join form the parser accepts
+    let result = assert_query_correct(
+        "SELECT customer.id, order.amount FROM customer, order WHERE customer.id = order.customer_id",
+        &tpch_tables(),
+        K,
+    );
+    assert_eq!(result.rows.len(), 6);
+}
+
+#[test]
+#[should_panic(expected = "does not prove its reference result")]
+fn test_assert_query_correct_fails_below_min_k() {
+    // Test: A k too small for the circuit fails the mock proof
+    assert_query_correct(
+        "SELECT id, amount FROM order ORDER BY amount ASC",
+        &tpch_tables(),
+        3,
+    );
+}
+
+#[test]
+fn test_assert_query_correct_projection() {
+    // Test: A computed SELECT column is part of the committed result rows
+    let result = assert_query_correct(
+        "SELECT id, amount * 2 AS doubled FROM order WHERE amount > 40",
+        &tpch_tables(),
+        K,
+    );
+    assert_eq!(result.columns, vec!["id", "doubled"]);
+    assert_eq!(result.rows, vec![vec![1, 100], vec![3, 200], vec![6, 140]]);
+}
+
+#[test]
+#[should_panic(expected = "circuit result differs from the reference result")]
+fn test_assert_query_result_rejects_forged_group_by() {
+    // Test: A result other than the circuit's GROUP BY rows fails
+    let forged = QueryResult {
+        columns: vec!["customer_id".to_string(), "sum(amount)".to_string()],
+        rows: vec![vec![1, 30], vec![2, 55], vec![3, 101], vec![4, 70]],
+    };
+    assert_query_result(
+        "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id",
+        &tpch_tables(),
+        &forged,
+        K,
+    );
+}
+
+#[test]
+#[should_panic(expected = "differs from every proven sort output")]
+fn test_assert_query_result_rejects_forged_order_by() {
+    // Test: An ORDER BY column other than the proven sort output fails
+    let forged = QueryResult {
+        columns: vec!["id".to_string(), "amount".to_string()],
+        rows: vec![
+            vec![5, 5],
+            vec![2, 10],
+            vec![1, 50],
+            vec![4, 20],
+            vec![6, 70],
+            vec![3, 100],
+        ],
+    };
+    assert_query_result(
+        "SELECT id, amount FROM order ORDER BY amount ASC",
+        &tpch_tables(),
+        &forged,
+        K,
+    );
+}
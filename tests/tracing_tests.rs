@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use poneglyphdb::prover::{aggregate_assertion, assert_aggregate, prove_query, ProvingContext};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Span or event recorded by `Recorder`: its name (an event's message) and
+/// its fields
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for Entry {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let value = format!("{:?}", value);
+        if field.name() == "message" {
+            self.name = value;
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+/// Subscriber recording every span and event, at every level
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<Entry>>>,
+    events: Arc<Mutex<Vec<Entry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Recorder {
+    fn span_names(&self) -> Vec<String> {
+        let spans = self.spans.lock().unwrap();
+        spans.iter().map(|span| span.name.clone()).collect()
+    }
+
+    fn span(&self, name: &str) -> Entry {
+        let spans = self.spans.lock().unwrap();
+        spans.iter().find(|span| span.name == name).unwrap().clone()
+    }
+
+    fn event(&self, message: &str) -> Entry {
+        let events = self.events.lock().unwrap();
+        events
+            .iter()
+            .find(|event| event.name == message)
+            .unwrap()
+            .clone()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut entry = Entry {
+            name: attributes.metadata().name().to_string(),
+            fields: HashMap::new(),
+        };
+        attributes.record(&mut entry);
+        self.spans.lock().unwrap().push(entry);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut entry = Entry {
+            name: String::new(),
+            fields: HashMap::new(),
+        };
+        event.record(&mut entry);
+        self.events.lock().unwrap().push(entry);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Order table (customer_id, amount)
+fn order_table() -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let mut columns = HashMap::new();
+    columns.insert("customer_id".to_string(), (0..16).map(|i| i % 3).collect());
+    columns.insert("amount".to_string(), (0..16).map(|i| i * 7 % 50).collect());
+
+    let mut table_data = HashMap::new();
+    table_data.insert("order".to_string(), columns);
+    table_data
+}
+
+#[test]
+fn test_prove_query_emits_stage_spans() {
+    // Test: A full prove_query run enters a span per stage, in order, with a
+    // timing event per stage and the compiled op counts
+    let recorder = Recorder::default();
+    let sql = "SELECT customer_id, sum(amount) FROM order GROUP BY customer_id";
+    let (proof, _) = tracing::subscriber::with_default(recorder.clone(), || {
+        prove_query(sql, &order_table(), None).unwrap()
+    });
+    assert!(!proof.is_empty());
+
+    let names = recorder.span_names();
+    let position = |stage: &str| names.iter().position(|name| name == stage).unwrap();
+    assert!(position("parse") < position("compile"));
+    assert!(position("compile") < position("keygen"));
+    assert!(position("keygen") < position("prove"));
+
+    // Synthesized once per key and once more for the proof
+    let synthesized = names.iter().filter(|name| *name == "synthesize").count();
+    assert!(synthesized >= 3, "{:?}", names);
+    let synthesize = recorder.span("synthesize");
+    assert_eq!(synthesize.fields["group_bys"], "1");
+    assert_eq!(synthesize.fields["aggregations"], "1");
+
+    let compiled = recorder.event("compiled");
+    assert_eq!(compiled.fields["group_bys"], "1");
+    assert_eq!(compiled.fields["joins"], "0");
+    for stage in ["parse", "compile", "keygen", "prove", "synthesize"] {
+        let done = recorder.event(&format!("{} done", stage));
+        assert!(done.fields.contains_key("elapsed_us"), "{}", stage);
+    }
+}
+
+#[test]
+fn test_verify_emits_span() {
+    // Test: Verification enters its own span and reports its time
+    let sql = "SELECT sum(amount) FROM order";
+    let expected: u64 = order_table()["order"]["amount"].iter().sum();
+    let (proof, public_inputs) = assert_aggregate(sql, &order_table(), expected).unwrap();
+    let circuit = aggregate_assertion(sql, &order_table(), expected).unwrap();
+    let context = ProvingContext::for_circuit(&circuit).unwrap();
+
+    let recorder = Recorder::default();
+    let verified = tracing::subscriber::with_default(recorder.clone(), || {
+        context
+            .verify(&proof, &public_inputs.to_instances())
+            .unwrap()
+    });
+    assert!(verified);
+    assert_eq!(recorder.span_names(), ["verify"]);
+    assert_eq!(recorder.span("verify").fields["k"], context.k().to_string());
+    assert!(recorder
+        .event("verify done")
+        .fields
+        .contains_key("elapsed_us"));
+}
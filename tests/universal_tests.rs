@@ -0,0 +1,150 @@
+#![cfg(not(feature = "verifier-only"))]
+
+use std::collections::HashMap;
+
+use halo2_proofs::dev::MockProver;
+use pasta_curves::pallas::Base as Fr;
+use poneglyphdb::circuit::*;
+use poneglyphdb::prover::ProvingContext;
+use poneglyphdb::sql::*;
+
+/// Helper function to create table data
+fn table(name: &str, columns: &[(&str, Vec<u64>)]) -> HashMap<String, HashMap<String, Vec<u64>>> {
+    let table_columns = columns
+        .iter()
+        .map(|(column, values)| (column.to_string(), values.clone()))
+        .collect();
+    HashMap::from([(name.to_string(), table_columns)])
+}
+
+/// Compile a query into a circuit (public inputs are not bound)
+fn compile(sql: &str, data: &HashMap<String, HashMap<String, Vec<u64>>>) -> PoneglyphCircuit {
+    let compiled = SQLCompiler::compile(&SQLParser::parse(sql).unwrap(), data).unwrap();
+    PoneglyphCircuit::from_compiled(compiled, None, None)
+}
+
+const CAPACITY: CircuitCapacity = CircuitCapacity {
+    max_range_checks: 12,
+    max_sorts: 1,
+    max_sort_rows: 8,
+    max_products: 6,
+    max_divisions: 6,
+};
+
+#[test]
+fn test_two_queries_share_one_key() {
+    // Test: Queries of different shapes (operators, row counts, sorts and
+    // expressions) padded to one capacity prove and verify with the keys
+    // generated once from the capacity's padding-only circuit
+    let orders = table(
+        "t",
+        &[
+            ("id", vec![1, 2, 3, 4, 5]),
+            ("amount", vec![70, 20, 45, 90, 5]),
+        ],
+    );
+    let items = table("item", &[("price", vec![20, 7, 30, 20, 1, 9])]);
+    let queries = [
+        compile(
+            "SELECT id, amount FROM t WHERE amount < 50 ORDER BY amount",
+            &orders,
+        ),
+        compile("SELECT price FROM item WHERE price * 2 = 40", &items),
+        compile("SELECT price FROM item WHERE price / 3 > 2", &items),
+    ];
+
+    let template = CAPACITY.circuit();
+    let context = ProvingContext::new(template.stats().unwrap().min_k, &template).unwrap();
+    for circuit in &queries {
+        let padded = CAPACITY.pad(circuit).unwrap();
+        let instances = CircuitCapacity::instances(&padded);
+        let proof = context.prove(&padded, &instances).unwrap();
+        assert!(context.verify(&proof, &instances).unwrap());
+    }
+}
+
+#[test]
+fn test_public_constants_bind_the_query() {
+    // Test: The checks' constants, kinds and bits are public inputs: a proof
+    // of x < 7 does not verify as x < 9, x > 7 or with other outcomes
+    let data = table("t", &[("x", vec![3, 8, 5])]);
+    let template = CAPACITY.circuit();
+    let context = ProvingContext::new(template.stats().unwrap().min_k, &template).unwrap();
+    let padded = |sql: &str| CAPACITY.pad(&compile(sql, &data)).unwrap();
+
+    let below_seven = padded("SELECT x FROM t WHERE x < 7");
+    let instances = CircuitCapacity::instances(&below_seven);
+    assert_eq!(instances[0][..2], [Fr::from(7), Fr::from(0)]);
+    // The operands are their own public inputs, not table commitments
+    assert!(below_seven.db_commitments.is_empty());
+    assert_eq!(instances[0], below_seven.public_operands);
+    let proof = context.prove(&below_seven, &instances).unwrap();
+    assert!(context.verify(&proof, &instances).unwrap());
+
+    // Other constants, with the same outcomes (only x = 8 fails) or not
+    let below_six = CircuitCapacity::instances(&padded("SELECT x FROM t WHERE x < 6"));
+    assert_eq!(below_six[1], instances[1]);
+    assert!(!context.verify(&proof, &below_six).unwrap_or(false));
+    let below_nine = CircuitCapacity::instances(&padded("SELECT x FROM t WHERE x < 9"));
+    assert!(!context.verify(&proof, &below_nine).unwrap_or(false));
+
+    // Same constant, other kind
+    let above_seven = CircuitCapacity::instances(&padded("SELECT x FROM t WHERE x > 7"));
+    assert_eq!(above_seven[0][..2], [Fr::from(7), Fr::from(1)]);
+    assert!(!context.verify(&proof, &above_seven).unwrap_or(false));
+
+    // A witness proving x < 9 under the x < 7 constants fails
+    let k = template.stats().unwrap().min_k;
+    let mut forged = padded("SELECT x FROM t WHERE x < 9");
+    forged.public_operands = below_seven.public_operands.clone();
+    let prover = MockProver::run(k, &forged, CircuitCapacity::instances(&forged)).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_pad_rewrites_every_comparison() {
+    // Test: Every comparison, including the ends of the u64 range, pads into
+    // witnessed x < t checks that satisfy the circuit
+    let data = table("t", &[("x", vec![0, 7, u64::MAX - 1, u64::MAX])]);
+    let k = CAPACITY.circuit().stats().unwrap().min_k;
+    for sql in [
+        "SELECT x FROM t WHERE x < 7".to_string(),
+        "SELECT x FROM t WHERE x > 0".to_string(),
+        "SELECT x FROM t WHERE x > 0 AND x < 9".to_string(),
+        format!("SELECT x FROM t WHERE x = {}", u64::MAX),
+        "SELECT x FROM t WHERE x = 0".to_string(),
+    ] {
+        let padded = CAPACITY.pad(&compile(&sql, &data)).unwrap();
+        assert_eq!(padded.range_checks.len(), CAPACITY.max_range_checks);
+        assert!(padded.range_checks.iter().all(|op| op.witnessed_threshold));
+        let instances = CircuitCapacity::instances(&padded);
+        let prover = MockProver::run(k, &padded, instances).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "{}", sql);
+    }
+}
+
+#[test]
+fn test_pad_rejects_what_does_not_fit() {
+    // Test: Operations beyond the capacity or with a query-dependent layout
+    // are rejected
+    let pad_error = |sql: &str, data| match CAPACITY.pad(&compile(sql, data)) {
+        Ok(_) => panic!("{} fits the capacity", sql),
+        Err(err) => err,
+    };
+
+    let data = table("t", &[("x", (0..20).collect()), ("g", vec![1; 20])]);
+    let err = pad_error("SELECT x FROM t WHERE x < 5", &data);
+    assert!(
+        err.contains("20 range checks, the capacity is 12"),
+        "{}",
+        err
+    );
+
+    let small = table("t", &[("x", vec![3, 1]), ("g", vec![1, 2])]);
+    let err = pad_error("SELECT g, sum(x) FROM t GROUP BY g", &small);
+    assert!(err.contains("group-by"), "{}", err);
+    let err = pad_error("SELECT x FROM t ORDER BY x DESC", &small);
+    assert!(err.contains("ascending"), "{}", err);
+    let err = pad_error("SELECT x FROM t WHERE x < g", &small);
+    assert!(err.contains("constants only"), "{}", err);
+}
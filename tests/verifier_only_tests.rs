@@ -0,0 +1,42 @@
+// Builds with `--features verifier-only`: the verifier path without the SQL
+// front end, the prover or the optimizer
+use halo2_proofs::{circuit::Value, pasta::EqAffine, poly::commitment::Params};
+use poneglyphdb::circuit::*;
+use poneglyphdb::database::{DatabaseCommitment, HashScheme};
+use poneglyphdb::prover::Verifier;
+
+/// Circuit with one product (public inputs are not bound)
+fn product_circuit() -> PoneglyphCircuit {
+    PoneglyphCircuit {
+        products: vec![ProductOp {
+            a: Value::known(6),
+            b: Value::known(7),
+            operands: Default::default(),
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_verifier_rejects_invalid_proof() {
+    // Test: A verifying key is generated from the circuit shape alone, and
+    // bytes that are not a proof for it are rejected
+    let circuit = product_circuit();
+    let params = Params::<EqAffine>::new(circuit.stats().unwrap().min_k);
+    let verifier = Verifier::new(&params, &circuit).unwrap();
+
+    assert!(verifier.verify(&params, &[], &[vec![], vec![]]).is_err());
+    assert!(verifier
+        .verify(&params, &[7u8; 512], &[vec![], vec![]])
+        .is_err());
+}
+
+#[test]
+fn test_database_commitment_verifies() {
+    // Test: Commitments are checked against the committed data
+    let data = [(1, 10), (2, 20), (3, 30)];
+    let commitment = DatabaseCommitment::new(&data, HashScheme::Poseidon);
+
+    assert!(commitment.verify(&data));
+    assert!(!commitment.verify(&[(1, 10), (2, 21), (3, 30)]));
+}